@@ -30,6 +30,8 @@ echo "in root: gitoxide CLI"
 (enter git-validate && indent cargo diet -n --package-size-limit 5KB)
 (enter git-object && indent cargo diet -n --package-size-limit 20KB)
 (enter git-commitgraph && indent cargo diet -n --package-size-limit 15KB)
+(enter git-bundle && indent cargo diet -n --package-size-limit 10KB)
+(enter git-credentials && indent cargo diet -n --package-size-limit 5KB)
 (enter git-pack && indent cargo diet -n --package-size-limit 75KB)
 (enter git-odb && indent cargo diet -n --package-size-limit 15KB)
 (enter git-protocol && indent cargo diet -n --package-size-limit 25KB)
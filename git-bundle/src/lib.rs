@@ -0,0 +1,43 @@
+//! Read and write [git bundles][format], files which package a pack along with the references it contains
+//! and the commits it requires to be present, to transport objects between repositories without a network connection.
+//!
+//! Bundles are [read][Bundle::from_reader()] fully into memory to provide access to their [pack entries][Bundle::entries()],
+//! and written using a [`BundleWriter`].
+//!
+//! [format]: https://git-scm.com/docs/bundle-format
+#![forbid(unsafe_code)]
+#![deny(rust_2018_idioms, missing_docs)]
+
+use bstr::BString;
+use git_hash::ObjectId;
+
+///
+pub mod read;
+///
+pub mod write;
+pub use write::BundleWriter;
+
+/// The first line of every bundle in version 2.
+pub const SIGNATURE_V2: &[u8] = b"# v2 git bundle\n";
+
+/// A commit that must be present in the repository receiving a bundle, as the pack within it is based on it.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Prerequisite {
+    /// The id of the required commit.
+    pub id: ObjectId,
+    /// A comment to help humans identify the commit, typically its summary. It may be empty.
+    pub comment: BString,
+}
+
+/// A parsed bundle, holding its header information along with the pack data.
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bundle {
+    /// The commits the receiving repository needs to have for the pack to be complete, in order of appearance.
+    pub prerequisites: Vec<Prerequisite>,
+    /// The references contained in the bundle as pairs of the object they point to and their full name, in order of appearance.
+    pub refs: Vec<(ObjectId, BString)>,
+    /// The complete pack data, starting with its header and ending with its trailing checksum.
+    pub pack: Vec<u8>,
+}
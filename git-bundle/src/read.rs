@@ -0,0 +1,124 @@
+use std::{
+    convert::TryInto,
+    io::{self, BufRead, Read},
+};
+
+use bstr::{BString, ByteSlice};
+use git_hash::ObjectId;
+use git_pack::data::{self, input};
+
+use crate::{Bundle, Prerequisite, SIGNATURE_V2};
+
+/// The error returned by [`Bundle::from_reader()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not read bundle")]
+    Io(#[from] io::Error),
+    #[error("Bundle signature {:?} is not supported, expected {:?}", .signature, SIGNATURE_V2.as_bstr())]
+    Signature { signature: BString },
+    #[error("Bundle header line {:?} is neither a prerequisite nor a reference", .line)]
+    Line { line: BString },
+    #[error("The object id in bundle header line {:?} could not be decoded", .line)]
+    Id {
+        line: BString,
+        source: git_hash::decode::Error,
+    },
+    #[error("The bundle header must be terminated by an empty line")]
+    UnterminatedHeader,
+    #[error("The bundle ended before the pack header")]
+    MissingPack,
+    #[error(transparent)]
+    PackHeader(#[from] data::header::decode::Error),
+}
+
+/// Initialization
+impl Bundle {
+    /// Read a bundle in version 2 from `read` until its end, validating its header and the header of the pack data following it.
+    ///
+    /// The pack itself is kept as is and can be decoded with [`entries()`][Bundle::entries()].
+    pub fn from_reader(read: impl Read) -> Result<Self, Error> {
+        let mut read = io::BufReader::new(read);
+        let mut line = Vec::new();
+        read.read_until(b'\n', &mut line)?;
+        if line != SIGNATURE_V2 {
+            return Err(Error::Signature { signature: line.into() });
+        }
+
+        let mut prerequisites = Vec::new();
+        let mut refs = Vec::new();
+        loop {
+            line.clear();
+            read.read_until(b'\n', &mut line)?;
+            let line = match line.strip_suffix(b"\n") {
+                Some(line) => line,
+                None => return Err(Error::UnterminatedHeader),
+            };
+            if line.is_empty() {
+                break;
+            }
+            match line.strip_prefix(b"-") {
+                Some(prerequisite) => {
+                    let (hex, comment) = split_at_space(prerequisite);
+                    prerequisites.push(Prerequisite {
+                        id: parse_id(hex, line)?,
+                        comment: comment.unwrap_or_default().into(),
+                    });
+                }
+                None => match split_at_space(line) {
+                    (hex, Some(name)) if !name.is_empty() => refs.push((parse_id(hex, line)?, name.into())),
+                    _ => return Err(Error::Line { line: line.into() }),
+                },
+            }
+        }
+
+        let mut pack = Vec::new();
+        read.read_to_end(&mut pack)?;
+        let header: &[u8; 12] = pack
+            .get(..12)
+            .and_then(|header| header.try_into().ok())
+            .ok_or(Error::MissingPack)?;
+        data::header::decode(header)?;
+
+        Ok(Bundle {
+            prerequisites,
+            refs,
+            pack,
+        })
+    }
+}
+
+/// Access
+impl Bundle {
+    /// Return the amount of objects in the pack.
+    pub fn num_objects(&self) -> u32 {
+        let header = self.pack[..12].try_into().expect("validated on instantiation");
+        data::header::decode(header).expect("validated on instantiation").1
+    }
+
+    /// Return an iterator over all entries in the pack, configured by `mode` and `compressed` like
+    /// [`BytesToEntriesIter::new_from_header()`][input::BytesToEntriesIter::new_from_header()].
+    ///
+    /// Note that bundles may contain thin packs whose deltas refer to objects in the [prerequisites][Bundle::prerequisites].
+    pub fn entries(
+        &self,
+        mode: input::Mode,
+        compressed: input::EntryDataMode,
+    ) -> Result<input::BytesToEntriesIter<&[u8]>, input::Error> {
+        input::BytesToEntriesIter::new_from_header(self.pack.as_slice(), mode, compressed)
+    }
+}
+
+fn split_at_space(line: &[u8]) -> (&[u8], Option<&[u8]>) {
+    match line.find_byte(b' ') {
+        Some(pos) => (&line[..pos], Some(&line[pos + 1..])),
+        None => (line, None),
+    }
+}
+
+fn parse_id(hex: &[u8], line: &[u8]) -> Result<ObjectId, Error> {
+    ObjectId::from_hex(hex).map_err(|source| Error::Id {
+        line: line.into(),
+        source,
+    })
+}
@@ -0,0 +1,126 @@
+use std::io;
+
+use bstr::{BString, ByteSlice};
+use git_hash::ObjectId;
+use git_pack::data::{self, output};
+
+use crate::{Prerequisite, SIGNATURE_V2};
+
+/// The error returned by [`BundleWriter::write_to()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error<E>
+where
+    E: std::error::Error + 'static,
+{
+    #[error("Could not write the bundle header")]
+    Io(#[from] io::Error),
+    #[error("The value {:?} would not form a valid bundle header line", .value)]
+    HeaderValue { value: BString },
+    #[error("Could not write the pack")]
+    Pack(#[from] output::bytes::Error<E>),
+    #[error("The pack was announced to contain {expected} entries, but {actual} were provided")]
+    EntryCount { expected: u32, actual: usize },
+}
+
+/// Write a bundle in version 2 from references, prerequisites and pack entries.
+pub struct BundleWriter<I> {
+    /// The references to write as pairs of the object they point to and their full name.
+    pub refs: Vec<(ObjectId, BString)>,
+    /// The commits the pack is based on.
+    pub prerequisites: Vec<Prerequisite>,
+    /// The chunks of pack entries to write, sorted in the order in which they should appear in the pack.
+    pub entries: I,
+    /// The total amount of entries provided by `entries`.
+    pub num_entries: u32,
+}
+
+impl<I, E> BundleWriter<I>
+where
+    I: Iterator<Item = Result<Vec<output::Entry>, E>>,
+    E: std::error::Error + 'static,
+{
+    /// Create a new instance to write `refs` and `prerequisites` as header, followed by a pack of exactly `num_entries`
+    /// obtained from `entries`.
+    ///
+    /// Entries can be created with [`iter_from_counts()`][output::entry::iter_from_counts()] and brought into order using
+    /// an [`InOrderIter`][output::InOrderIter].
+    pub fn new(refs: Vec<(ObjectId, BString)>, prerequisites: Vec<Prerequisite>, entries: I, num_entries: u32) -> Self {
+        BundleWriter {
+            refs,
+            prerequisites,
+            entries,
+            num_entries,
+        }
+    }
+
+    /// Write the bundle into `out` and return the checksum of its pack.
+    ///
+    /// Note that `out` should be buffered as many small writes are performed.
+    pub fn write_to(self, mut out: impl io::Write) -> Result<ObjectId, Error<E>> {
+        let BundleWriter {
+            refs,
+            prerequisites,
+            entries,
+            num_entries,
+        } = self;
+        if let Some(value) = prerequisites
+            .iter()
+            .map(|p| &p.comment)
+            .find(|comment| comment.contains(&b'\n'))
+            .or_else(|| refs.iter().map(|(_, name)| name).find(|name| is_invalid_name(name)))
+        {
+            return Err(Error::HeaderValue {
+                value: value.to_owned(),
+            });
+        }
+
+        out.write_all(SIGNATURE_V2)?;
+        for Prerequisite { id, comment } in &prerequisites {
+            out.write_all(b"-")?;
+            id.write_hex_to(&mut out)?;
+            if !comment.is_empty() {
+                out.write_all(b" ")?;
+                out.write_all(comment)?;
+            }
+            out.write_all(b"\n")?;
+        }
+        for (id, name) in &refs {
+            id.write_hex_to(&mut out)?;
+            out.write_all(b" ")?;
+            out.write_all(name)?;
+            out.write_all(b"\n")?;
+        }
+        out.write_all(b"\n")?;
+
+        let mut num_written = 0;
+        let checksum = {
+            let mut pack = output::bytes::FromEntriesIter::new(
+                entries.inspect(|chunk| {
+                    if let Ok(chunk) = chunk {
+                        num_written += chunk.len();
+                    }
+                }),
+                &mut out,
+                num_entries,
+                data::Version::V2,
+                git_hash::Kind::default(),
+            );
+            for written in pack.by_ref() {
+                written?;
+            }
+            pack.digest().expect("iteration is done")
+        };
+        if num_written != num_entries as usize {
+            return Err(Error::EntryCount {
+                expected: num_entries,
+                actual: num_written,
+            });
+        }
+        Ok(checksum)
+    }
+}
+
+fn is_invalid_name(name: &BString) -> bool {
+    name.is_empty() || name.contains(&b'\n') || name.find_byte(b' ').is_some()
+}
@@ -0,0 +1,311 @@
+use std::path::PathBuf;
+
+use git_testtools::scripted_fixture_repo_read_only;
+
+type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn fixture_repo() -> Result<PathBuf> {
+    scripted_fixture_repo_read_only("make_bundles.sh")
+}
+
+fn git(dir: &std::path::Path, args: &[&str]) -> Result<String> {
+    let output = std::process::Command::new("git").args(args).current_dir(dir).output()?;
+    if !output.status.success() {
+        return Err(format!("git {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr)).into());
+    }
+    Ok(String::from_utf8(output.stdout)?.trim_end().to_owned())
+}
+
+fn rev_parse(dir: &std::path::Path, spec: &str) -> Result<git_hash::ObjectId> {
+    Ok(git_hash::ObjectId::from_hex(
+        git(dir, &["rev-parse", spec])?.as_bytes(),
+    )?)
+}
+
+mod read {
+    use git_bundle::{read, Bundle, Prerequisite};
+    use git_pack::data::input;
+
+    use crate::{fixture_repo, rev_parse};
+
+    fn count_verified_entries(bundle: &Bundle) -> crate::Result<usize> {
+        let mut count = 0;
+        for entry in bundle.entries(input::Mode::Verify, input::EntryDataMode::Crc32)? {
+            entry?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    #[test]
+    fn full_bundle_created_by_git() -> crate::Result {
+        let dir = fixture_repo()?;
+        let bundle = Bundle::from_reader(std::fs::File::open(dir.join("all.bundle"))?)?;
+        assert!(bundle.prerequisites.is_empty());
+        let main = rev_parse(&dir, "main")?;
+        assert_eq!(
+            bundle.refs,
+            vec![
+                (main, "refs/heads/main".into()),
+                (rev_parse(&dir, "v1")?, "refs/tags/v1".into()),
+                (main, "HEAD".into())
+            ]
+        );
+        assert_eq!(
+            bundle.num_objects(),
+            3 * 3 + 1,
+            "3 commits, trees and blobs each, and a tag"
+        );
+        assert_eq!(count_verified_entries(&bundle)?, bundle.num_objects() as usize);
+        Ok(())
+    }
+
+    #[test]
+    fn incremental_bundle_created_by_git() -> crate::Result {
+        let dir = fixture_repo()?;
+        let bundle = Bundle::from_reader(std::fs::File::open(dir.join("incremental.bundle"))?)?;
+        assert_eq!(
+            bundle.prerequisites,
+            vec![Prerequisite {
+                id: rev_parse(&dir, "main~1")?,
+                comment: "c2".into()
+            }]
+        );
+        assert_eq!(bundle.refs, vec![(rev_parse(&dir, "main")?, "refs/heads/main".into())]);
+        assert_eq!(bundle.num_objects(), 3, "a commit, its tree and the changed blob");
+        assert_eq!(count_verified_entries(&bundle)?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_headers() -> crate::Result {
+        let dir = fixture_repo()?;
+        let pack = {
+            let bundle = std::fs::read(dir.join("incremental.bundle"))?;
+            let pack_start = bundle.windows(6).position(|w| w == b"\n\nPACK").expect("pack present") + 2;
+            bundle[pack_start..].to_vec()
+        };
+        let bundle_with_header = |header: &str| {
+            let mut data = header.as_bytes().to_vec();
+            data.extend_from_slice(&pack);
+            Bundle::from_reader(data.as_slice())
+        };
+        let id = "b5ccb2d47b7ea4e74d653fd98051f93b8fce1a7f";
+
+        assert!(matches!(
+            bundle_with_header(&format!("# v3 git bundle\n{} refs/heads/main\n\n", id)),
+            Err(read::Error::Signature { .. })
+        ));
+        assert!(matches!(
+            bundle_with_header(&format!("# v2 git bundle\n{}\n\n", id)),
+            Err(read::Error::Line { .. })
+        ));
+        assert!(matches!(
+            bundle_with_header("# v2 git bundle\n-b5ccb2 comment\n\n"),
+            Err(read::Error::Id { .. })
+        ));
+        assert!(matches!(
+            Bundle::from_reader(format!("# v2 git bundle\n{} HEAD", id).as_bytes()),
+            Err(read::Error::UnterminatedHeader)
+        ));
+        assert!(matches!(
+            Bundle::from_reader(format!("# v2 git bundle\n{} HEAD\n\nPACK", id).as_bytes()),
+            Err(read::Error::MissingPack)
+        ));
+        assert!(matches!(
+            Bundle::from_reader(format!("# v2 git bundle\n{} HEAD\n\nKCAP00000000", id).as_bytes()),
+            Err(read::Error::PackHeader(_))
+        ));
+
+        let bundle = bundle_with_header(&format!("# v2 git bundle\n-{}\n{} HEAD\n\n", id, id))?;
+        assert_eq!(bundle.prerequisites[0].comment, "", "comments are optional");
+        Ok(())
+    }
+}
+
+mod write {
+    use std::{convert::TryInto, sync::Arc};
+
+    use git_bundle::{write, Bundle, BundleWriter, Prerequisite};
+    use git_odb::{pack, FindExt};
+    use git_pack::data::output;
+
+    use crate::{fixture_repo, git, rev_parse};
+
+    type Entries = Box<dyn Iterator<Item = Result<Vec<output::Entry>, EntriesError>>>;
+    type EntriesError = output::entry::iter_from_counts::Error<git_odb::compound::find::Error>;
+
+    /// Produce pack entries for all commits reachable from `tip` but not from `hidden` along with their trees and blobs.
+    fn pack_entries(
+        repo: &std::path::Path,
+        tip: git_hash::ObjectId,
+        hidden: Option<git_hash::ObjectId>,
+    ) -> crate::Result<(Entries, u32)> {
+        let db = Arc::new(git_odb::linked::Store::at(repo.join(".git").join("objects"))?);
+        let hidden: Vec<_> = match hidden {
+            Some(hidden) => git_traverse::commit::Ancestors::new(
+                Some(hidden),
+                git_traverse::commit::ancestors::State::default(),
+                |id, buf| db.find_commit_iter(id, buf, &mut pack::cache::Never).ok(),
+            )
+            .collect::<Result<_, _>>()?,
+            None => Vec::new(),
+        };
+        let commits = git_traverse::commit::Ancestors::filtered(
+            Some(tip),
+            git_traverse::commit::ancestors::State::default(),
+            |id, buf| db.find_commit_iter(id, buf, &mut pack::cache::Never).ok(),
+            |id| !hidden.iter().any(|hidden| hidden.as_ref() == id),
+        )
+        .collect::<Vec<_>>();
+        let (counts, _) = output::count::objects_unthreaded(
+            Arc::clone(&db),
+            (&mut pack::cache::Never, &mut pack::cache::object::Never),
+            commits.into_iter(),
+            git_features::progress::Discard,
+            &std::sync::atomic::AtomicBool::default(),
+            output::count::objects::ObjectExpansion::TreeAdditionsComparedToAncestor,
+        )?;
+        let num_entries = counts.len().try_into()?;
+        let entries = output::InOrderIter::from(output::entry::iter_from_counts(
+            counts,
+            db,
+            || pack::cache::Never,
+            git_features::progress::Discard,
+            Default::default(),
+        ));
+        Ok((Box::new(entries), num_entries))
+    }
+
+    #[test]
+    fn written_bundles_can_be_cloned_by_git() -> crate::Result {
+        let dir = fixture_repo()?;
+        let main = rev_parse(&dir, "main")?;
+        let (entries, num_entries) = pack_entries(&dir, main, None)?;
+        let refs = vec![(main, "refs/heads/main".into()), (main, "HEAD".into())];
+
+        let tmp = git_testtools::tempfile::TempDir::new()?;
+        let bundle_path = tmp.path().join("our.bundle");
+        let pack_checksum = BundleWriter::new(refs.clone(), Vec::new(), entries, num_entries)
+            .write_to(std::io::BufWriter::new(std::fs::File::create(&bundle_path)?))?;
+
+        let bundle = Bundle::from_reader(std::fs::File::open(&bundle_path)?)?;
+        assert_eq!(bundle.refs, refs);
+        assert_eq!(bundle.num_objects(), num_entries);
+        assert_eq!(&bundle.pack[bundle.pack.len() - 20..], pack_checksum.as_slice());
+
+        git(
+            &dir,
+            &["bundle", "verify", "-q", bundle_path.to_str().expect("valid UTF-8")],
+        )?;
+        git(tmp.path(), &["clone", "-q", "our.bundle", "clone"])?;
+        assert_eq!(rev_parse(&tmp.path().join("clone"), "HEAD")?, main);
+        assert_eq!(git(&tmp.path().join("clone"), &["fsck", "--no-dangling"])?, "");
+        Ok(())
+    }
+
+    #[test]
+    fn incremental_bundles_can_be_verified_and_fetched_by_git() -> crate::Result {
+        let dir = fixture_repo()?;
+        let (main, base) = (rev_parse(&dir, "main")?, rev_parse(&dir, "main~1")?);
+        let (entries, num_entries) = pack_entries(&dir, main, Some(base))?;
+        assert_eq!(
+            num_entries, 5,
+            "the changes compared to the prerequisite are packed along with the prerequisite and its tree, which is harmless"
+        );
+
+        let tmp = git_testtools::tempfile::TempDir::new()?;
+        let bundle_path = tmp.path().join("incremental.bundle");
+        BundleWriter::new(
+            vec![(main, "refs/heads/main".into())],
+            vec![Prerequisite {
+                id: base,
+                comment: "c2".into(),
+            }],
+            entries,
+            num_entries,
+        )
+        .write_to(std::fs::File::create(&bundle_path)?)?;
+        assert_eq!(
+            std::fs::read(&bundle_path)?
+                .split(|b| *b == b'\n')
+                .take(3)
+                .collect::<Vec<_>>(),
+            std::fs::read(dir.join("incremental.bundle"))?
+                .split(|b| *b == b'\n')
+                .take(3)
+                .collect::<Vec<_>>(),
+            "the header is the same as the one written by git"
+        );
+
+        let url = format!("file://{}", dir.canonicalize()?.display());
+        git(
+            tmp.path(),
+            &[
+                "clone",
+                "-q",
+                "--bare",
+                "--single-branch",
+                "--branch",
+                "v1",
+                &url,
+                "clone",
+            ],
+        )?;
+        let clone = tmp.path().join("clone");
+        assert!(
+            git(&clone, &["cat-file", "-e", &main.to_string()]).is_err(),
+            "the clone only has the objects up to the prerequisite"
+        );
+        git(
+            &clone,
+            &["bundle", "verify", "-q", bundle_path.to_str().expect("valid UTF-8")],
+        )?;
+        git(
+            &clone,
+            &[
+                "fetch",
+                "-q",
+                bundle_path.to_str().expect("valid UTF-8"),
+                "main:refs/remotes/bundle/main",
+            ],
+        )?;
+        assert_eq!(rev_parse(&clone, "refs/remotes/bundle/main")?, main);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_header_values_and_entry_counts_are_errors() -> crate::Result {
+        let dir = fixture_repo()?;
+        let main = rev_parse(&dir, "main")?;
+        for name in ["", "refs/heads/with space", "refs/heads/new\nline"] {
+            let (entries, num_entries) = pack_entries(&dir, main, None)?;
+            assert!(matches!(
+                BundleWriter::new(vec![(main, name.into())], Vec::new(), entries, num_entries).write_to(Vec::new()),
+                Err(write::Error::HeaderValue { .. })
+            ));
+        }
+
+        let (entries, num_entries) = pack_entries(&dir, main, None)?;
+        assert!(matches!(
+            BundleWriter::new(
+                vec![(main, "HEAD".into())],
+                vec![Prerequisite {
+                    id: main,
+                    comment: "multi\nline".into()
+                }],
+                entries,
+                num_entries
+            )
+            .write_to(Vec::new()),
+            Err(write::Error::HeaderValue { .. })
+        ));
+
+        let (entries, num_entries) = pack_entries(&dir, main, None)?;
+        assert!(matches!(
+            BundleWriter::new(vec![(main, "HEAD".into())], Vec::new(), entries, num_entries + 1).write_to(Vec::new()),
+            Err(write::Error::EntryCount { expected, actual }) if expected == num_entries + 1 && actual == num_entries as usize
+        ));
+        Ok(())
+    }
+}
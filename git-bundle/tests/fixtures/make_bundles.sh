@@ -0,0 +1,19 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+git checkout -q -b main
+echo 1 > a
+git add a
+git commit -q -m c1
+echo 2 > b
+git add b
+git commit -q -m c2
+git tag -a -m "annotated" v1
+echo 3 >> a
+git commit -q -am c3
+
+git bundle create -q all.bundle --all
+git bundle create -q incremental.bundle main~1..main
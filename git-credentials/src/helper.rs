@@ -0,0 +1,167 @@
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use bstr::{BStr, ByteSlice};
+use zeroize::Zeroize;
+
+use crate::{CredentialStore, Credentials, Error};
+
+/// The program to run, along with the way to run it.
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+enum Program {
+    /// Execute the program directly.
+    Path(PathBuf),
+    /// Run the script using the shell, similar to what `git` does with the `credential.helper` configuration.
+    Shell(String),
+}
+
+/// A credential helper program speaking the [credential helper protocol][protocol], which is invoked with `get`, `store` or `erase`
+/// as last argument and receives `key=value` lines on stdin.
+///
+/// [protocol]: https://git-scm.com/docs/git-credential#IOFMT
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+pub struct Helper {
+    program: Program,
+}
+
+impl Helper {
+    /// Use the program at `path` as helper, which is executed directly without a shell.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Helper {
+            program: Program::Path(path.into()),
+        }
+    }
+
+    /// Interpret `value` like the `credential.helper` configuration variable of `git`, and run the resulting helper with the shell.
+    ///
+    /// * `!script` runs `script` as is.
+    /// * an absolute path like `/usr/bin/helper --flag` runs the program at that path, along with all given arguments.
+    /// * a name like `store --file creds` runs `git credential-store --file creds`.
+    pub fn from_config_value(value: &str) -> Self {
+        let script = match value.strip_prefix('!') {
+            Some(script) => script.to_owned(),
+            None if std::path::Path::new(value).is_absolute() => value.to_owned(),
+            None => format!("git credential-{}", value),
+        };
+        Helper {
+            program: Program::Shell(script),
+        }
+    }
+
+    /// Obtain credentials for `protocol` and `host`, like `https` and `example.com`, or fail with
+    /// [`Error::CredentialsMissing`] if the helper didn't provide a username and password.
+    pub fn get(&self, protocol: &str, host: &str) -> Result<Credentials, Error> {
+        let mut input = Vec::new();
+        encode(&mut input, protocol, host, None)?;
+        let mut output = self.invoke("get", &input)?;
+        let res = decode(output.as_bstr());
+        output.zeroize();
+        res
+    }
+
+    /// Tell the helper that `credentials` were accepted for `protocol` and `host`, so it may store them.
+    pub fn approve(&self, protocol: &str, host: &str, credentials: &Credentials) -> Result<(), Error> {
+        self.invoke_with_credentials("store", protocol, host, credentials)
+    }
+
+    /// Tell the helper that `credentials` were rejected for `protocol` and `host`, so it should forget them.
+    pub fn erase(&self, protocol: &str, host: &str, credentials: &Credentials) -> Result<(), Error> {
+        self.invoke_with_credentials("erase", protocol, host, credentials)
+    }
+
+    fn invoke_with_credentials(
+        &self,
+        action: &str,
+        protocol: &str,
+        host: &str,
+        credentials: &Credentials,
+    ) -> Result<(), Error> {
+        let mut input = Vec::new();
+        let res = encode(&mut input, protocol, host, Some(credentials)).and_then(|_| self.invoke(action, &input));
+        input.zeroize();
+        res.map(|mut output| output.zeroize())
+    }
+
+    fn invoke(&self, action: &str, input: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut cmd = match &self.program {
+            Program::Path(path) => Command::new(path),
+            Program::Shell(script) => {
+                let mut cmd = Command::new(if cfg!(windows) { "sh.exe" } else { "sh" });
+                cmd.arg("-c").arg(format!("{} \"$@\"", script)).arg(script);
+                cmd
+            }
+        };
+        let mut child = cmd.arg(action).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+        let mut stdin = child.stdin.take().expect("stdin to be configured");
+        match stdin.write_all(input) {
+            // Helpers may exit without reading their input, just like `git` we don't consider this an error.
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {}
+            res => res?,
+        }
+        drop(stdin);
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(Error::HelperFailed(output.status.code()));
+        }
+        Ok(output.stdout)
+    }
+}
+
+impl CredentialStore for Helper {
+    fn get(&mut self, protocol: &str, host: &str) -> Result<Credentials, Error> {
+        Helper::get(self, protocol, host)
+    }
+
+    fn approve(&mut self, protocol: &str, host: &str, credentials: &Credentials) -> Result<(), Error> {
+        Helper::approve(self, protocol, host, credentials)
+    }
+
+    fn erase(&mut self, protocol: &str, host: &str, credentials: &Credentials) -> Result<(), Error> {
+        Helper::erase(self, protocol, host, credentials)
+    }
+}
+
+fn encode(out: &mut Vec<u8>, protocol: &str, host: &str, credentials: Option<&Credentials>) -> Result<(), Error> {
+    let mut write = |key: &'static str, value: &[u8]| -> Result<(), Error> {
+        if value.contains(&b'\n') || value.contains(&0) {
+            return Err(Error::InvalidValue(key));
+        }
+        out.extend_from_slice(key.as_bytes());
+        out.push(b'=');
+        out.extend_from_slice(value);
+        out.push(b'\n');
+        Ok(())
+    };
+    write("protocol", protocol.as_bytes())?;
+    write("host", host.as_bytes())?;
+    if let Some(credentials) = credentials {
+        write("username", &credentials.username)?;
+        write("password", &credentials.password)?;
+    }
+    out.push(b'\n');
+    Ok(())
+}
+
+/// Decode the `key=value` lines up to the first empty line in `output`, using the last value if a key is given more than once.
+fn decode(output: &BStr) -> Result<Credentials, Error> {
+    let (mut username, mut password) = (None, None);
+    for line in output.lines().take_while(|line| !line.is_empty()) {
+        let pos = line.find_byte(b'=').ok_or(Error::InvalidOutput)?;
+        match &line[..pos] {
+            b"username" => username = Some(&line[pos + 1..]),
+            b"password" => password = Some(&line[pos + 1..]),
+            _ => {}
+        }
+    }
+    match (username, password) {
+        (Some(username), Some(password)) => Ok(Credentials {
+            username: username.into(),
+            password: password.into(),
+        }),
+        _ => Err(Error::CredentialsMissing),
+    }
+}
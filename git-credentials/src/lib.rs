@@ -0,0 +1,117 @@
+//! Obtain credentials for authenticating with remotes using [git credential helpers][helpers], and tell them whether
+//! the credentials worked to let them be stored or erased.
+//!
+//! The [`CredentialStore`] trait abstracts over the way credentials are obtained to allow transports to use the
+//! system's [`Helper`], a keychain or an in-memory store alike.
+//!
+//! [helpers]: https://git-scm.com/docs/gitcredentials#_custom_helpers
+#![forbid(unsafe_code)]
+#![deny(rust_2018_idioms, missing_docs)]
+
+use std::fmt;
+
+use bstr::BString;
+use quick_error::quick_error;
+use zeroize::Zeroize;
+
+///
+pub mod helper;
+pub use helper::Helper;
+
+quick_error! {
+    /// The error returned by [`Helper`] and other implementors of [`CredentialStore`].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        Io(err: std::io::Error) {
+            display("An IO error occurred while communicating with the credential helper")
+            from()
+            source(err)
+        }
+        HelperFailed(code: Option<i32>) {
+            display("The credential helper failed with status code {:?}", code)
+        }
+        CredentialsMissing {
+            display("The credential helper did not provide both a username and a password")
+        }
+        InvalidValue(key: &'static str) {
+            display("The value of '{}' must not contain newlines or NUL bytes", key)
+        }
+        InvalidOutput {
+            display("The credential helper produced a line which isn't of the form key=value")
+        }
+        Other(err: Box<dyn std::error::Error + Send + Sync>) {
+            display("The credential store failed")
+            from()
+            source(&**err)
+        }
+    }
+}
+
+/// A username and password to authenticate with a remote.
+///
+/// The password is overwritten with zeros when dropped, and not shown in its [`Debug`][fmt::Debug] representation.
+#[derive(PartialEq, Eq, Hash, Clone)]
+pub struct Credentials {
+    /// The name of the user.
+    pub username: BString,
+    /// The password or token of the user.
+    pub password: BString,
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+impl Drop for Credentials {
+    fn drop(&mut self) {
+        let password: &mut Vec<u8> = &mut self.password;
+        password.zeroize();
+    }
+}
+
+/// A source of credentials for the `protocol` and `host` of a remote, like `https` and `example.com:8080`.
+///
+/// After using obtained credentials, tell the store whether they worked with [`approve()`][CredentialStore::approve()]
+/// or [`erase()`][CredentialStore::erase()] to let it remember them or forget about them.
+pub trait CredentialStore {
+    /// Obtain credentials for `protocol` and `host`.
+    fn get(&mut self, protocol: &str, host: &str) -> Result<Credentials, Error>;
+    /// Tell the store that `credentials` were accepted by `host`, allowing it to store them for later use.
+    fn approve(&mut self, protocol: &str, host: &str, credentials: &Credentials) -> Result<(), Error>;
+    /// Tell the store that `credentials` were rejected by `host`, so it should forget about them.
+    fn erase(&mut self, protocol: &str, host: &str, credentials: &Credentials) -> Result<(), Error>;
+}
+
+impl<T: CredentialStore + ?Sized> CredentialStore for &mut T {
+    fn get(&mut self, protocol: &str, host: &str) -> Result<Credentials, Error> {
+        (**self).get(protocol, host)
+    }
+
+    fn approve(&mut self, protocol: &str, host: &str, credentials: &Credentials) -> Result<(), Error> {
+        (**self).approve(protocol, host, credentials)
+    }
+
+    fn erase(&mut self, protocol: &str, host: &str, credentials: &Credentials) -> Result<(), Error> {
+        (**self).erase(protocol, host, credentials)
+    }
+}
+
+impl<T: CredentialStore + ?Sized> CredentialStore for Box<T> {
+    fn get(&mut self, protocol: &str, host: &str) -> Result<Credentials, Error> {
+        (**self).get(protocol, host)
+    }
+
+    fn approve(&mut self, protocol: &str, host: &str, credentials: &Credentials) -> Result<(), Error> {
+        (**self).approve(protocol, host, credentials)
+    }
+
+    fn erase(&mut self, protocol: &str, host: &str, credentials: &Credentials) -> Result<(), Error> {
+        (**self).erase(protocol, host, credentials)
+    }
+}
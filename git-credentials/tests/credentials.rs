@@ -0,0 +1,205 @@
+use std::path::PathBuf;
+
+use once_cell::sync::Lazy;
+
+type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The path to the mock credential helper, compiled once from its fixture.
+static MOCK_HELPER: Lazy<PathBuf> = Lazy::new(|| {
+    let out =
+        PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("git-credential-mock{}", std::env::consts::EXE_SUFFIX));
+    let status = std::process::Command::new(std::env::var("RUSTC").unwrap_or_else(|_| "rustc".into()))
+        .args(["--edition", "2018", "-o"])
+        .arg(&out)
+        .arg(git_testtools::fixture_path("mock_helper.rs"))
+        .status()
+        .expect("rustc to be available");
+    assert!(status.success(), "the mock helper compiles");
+    out
+});
+
+mod helper {
+    use git_credentials::{Credentials, Error, Helper};
+    use git_testtools::tempfile;
+
+    use crate::MOCK_HELPER;
+
+    fn helper_with_log() -> crate::Result<(Helper, tempfile::TempDir)> {
+        let dir = tempfile::tempdir()?;
+        let helper = Helper::from_config_value(&format!(
+            "{} '{}'",
+            MOCK_HELPER.display(),
+            dir.path().join("log").display()
+        ));
+        Ok((helper, dir))
+    }
+
+    fn log(dir: &tempfile::TempDir) -> crate::Result<String> {
+        Ok(std::fs::read_to_string(dir.path().join("log"))?)
+    }
+
+    #[test]
+    fn get_approve_and_erase_round_trip_through_the_helper() -> crate::Result {
+        let (helper, dir) = helper_with_log()?;
+        let credentials = helper.get("https", "example.com")?;
+        assert_eq!(credentials.username, "user");
+        assert_eq!(credentials.password, "pass");
+
+        helper.approve("https", "example.com", &credentials)?;
+        helper.erase("https", "example.com", &credentials)?;
+        assert_eq!(
+            log(&dir)?,
+            "get
+protocol=https
+host=example.com
+
+store
+protocol=https
+host=example.com
+username=user
+password=pass
+
+erase
+protocol=https
+host=example.com
+username=user
+password=pass
+
+"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn programs_can_be_invoked_without_shell() -> crate::Result {
+        let helper = Helper::new(MOCK_HELPER.as_path());
+        assert_eq!(helper.get("https", "example.com")?.username, "user");
+        Ok(())
+    }
+
+    #[test]
+    fn missing_credentials_are_an_error() -> crate::Result {
+        let (helper, _dir) = helper_with_log()?;
+        assert!(matches!(
+            helper.get("https", "unknown.example.com"),
+            Err(Error::CredentialsMissing)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn failing_helpers_provide_their_status_code() -> crate::Result {
+        let (helper, _dir) = helper_with_log()?;
+        assert!(matches!(
+            helper.get("https", "fails.example.com"),
+            Err(Error::HelperFailed(Some(42)))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn values_with_newlines_are_rejected_before_invoking_the_helper() -> crate::Result {
+        let (helper, dir) = helper_with_log()?;
+        assert!(matches!(
+            helper.get("https", "example.com\nusername=evil"),
+            Err(Error::InvalidValue("host"))
+        ));
+        let credentials = Credentials {
+            username: "user".into(),
+            password: "pa\0ss".into(),
+        };
+        assert!(matches!(
+            helper.approve("https", "example.com", &credentials),
+            Err(Error::InvalidValue("password"))
+        ));
+        assert!(!dir.path().join("log").exists(), "the helper was never invoked");
+        Ok(())
+    }
+
+    #[test]
+    fn shell_scripts_can_be_used_as_helpers() -> crate::Result {
+        let helper =
+            Helper::from_config_value("!f() { test \"$1\" = get && echo username=me && echo password=secret; }; f");
+        let credentials = helper.get("https", "anything")?;
+        assert_eq!(credentials.username, "me");
+        assert_eq!(credentials.password, "secret");
+        Ok(())
+    }
+}
+
+mod credentials {
+    use git_credentials::Credentials;
+
+    #[test]
+    fn debug_does_not_show_the_password() {
+        let credentials = Credentials {
+            username: "user".into(),
+            password: "secret".into(),
+        };
+        let debug = format!("{:?}", credentials);
+        assert!(debug.contains("user"));
+        assert!(!debug.contains("secret"), "{}", debug);
+    }
+}
+
+mod store {
+    use std::collections::BTreeMap;
+
+    use git_credentials::{CredentialStore, Credentials, Error, Helper};
+
+    use crate::MOCK_HELPER;
+
+    /// A store keeping approved credentials in memory, while obtaining new ones from `fallback`.
+    struct InMemory<S> {
+        approved: BTreeMap<(String, String), Credentials>,
+        fallback: S,
+    }
+
+    impl<S: CredentialStore> CredentialStore for InMemory<S> {
+        fn get(&mut self, protocol: &str, host: &str) -> Result<Credentials, Error> {
+            match self.approved.get(&(protocol.into(), host.into())) {
+                Some(credentials) => Ok(credentials.clone()),
+                None => self.fallback.get(protocol, host),
+            }
+        }
+
+        fn approve(&mut self, protocol: &str, host: &str, credentials: &Credentials) -> Result<(), Error> {
+            self.approved
+                .insert((protocol.into(), host.into()), credentials.clone());
+            Ok(())
+        }
+
+        fn erase(&mut self, protocol: &str, host: &str, _credentials: &Credentials) -> Result<(), Error> {
+            self.approved.remove(&(protocol.into(), host.into()));
+            Ok(())
+        }
+    }
+
+    fn get_twice(store: &mut dyn CredentialStore) -> Result<(Credentials, Credentials), Error> {
+        let first = store.get("https", "example.com")?;
+        store.approve(
+            "https",
+            "example.com",
+            &Credentials {
+                username: "other".into(),
+                password: "pass".into(),
+            },
+        )?;
+        Ok((first, store.get("https", "example.com")?))
+    }
+
+    #[test]
+    fn stores_can_be_used_as_trait_objects_and_wrap_helpers() -> crate::Result {
+        let mut store = InMemory {
+            approved: BTreeMap::new(),
+            fallback: Helper::new(MOCK_HELPER.as_path()),
+        };
+        let (first, second) = get_twice(&mut store)?;
+        assert_eq!(first.username, "user", "obtained from the helper");
+        assert_eq!(second.username, "other", "obtained from memory");
+
+        store.erase("https", "example.com", &second)?;
+        assert_eq!(store.get("https", "example.com")?.username, "user");
+        Ok(())
+    }
+}
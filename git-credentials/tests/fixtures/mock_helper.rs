@@ -0,0 +1,32 @@
+//! A credential helper for use in tests, compiled on demand.
+//!
+//! Invoked as `mock_helper [<log-file>] <action>`, it appends the action and its input to `log-file` if given,
+//! provides credentials for `example.com`, fails for `fails.example.com` and provides nothing for all other hosts.
+use std::io::{Read, Write};
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let action = args.pop().expect("action as last argument");
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .expect("input to be readable");
+
+    if let Some(log) = args.first() {
+        let mut log = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log)
+            .expect("log to be writable");
+        write!(log, "{}\n{}", action, input).expect("log to be writable");
+    }
+
+    if action != "get" {
+        return;
+    }
+    match input.lines().find_map(|line| line.strip_prefix("host=")) {
+        Some("example.com") => print!("protocol=https\nhost=example.com\nusername=user\npassword=pass\n"),
+        Some("fails.example.com") => std::process::exit(42),
+        _ => {}
+    }
+}
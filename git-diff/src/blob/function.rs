@@ -0,0 +1,171 @@
+use git_object::bstr::BStr;
+
+/// Determines which lines of the old version of a blob start a function, to show the closest one preceding a hunk after its
+/// header like `@@ -10,7 +10,8 @@ fn main() {`.
+///
+/// This corresponds to the built-in `xfuncname` patterns of the diff drivers in `git`, which are typically selected with the
+/// `diff` attribute, like `*.rs diff=rust`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Driver {
+    /// Lines starting with a letter, `_` or `$`, which is what `git` uses if no diff driver is configured.
+    Default,
+    /// Lines declaring a `fn`, `struct`, `enum`, `union`, `mod`, `trait`, `impl` or `macro_rules!`, like the `rust` driver.
+    Rust,
+    /// Lines which aren't indented and aren't labels or access specifiers, like the `cpp` driver which is also used for C.
+    Cpp,
+    /// Lines declaring a `class` or a function with `def` or `async def`, like the `python` driver.
+    Python,
+}
+
+impl Default for Driver {
+    fn default() -> Self {
+        Driver::Default
+    }
+}
+
+impl Driver {
+    /// Return the driver called `name` as used in the `diff` attribute, like `rust` in `diff=rust`, or `None` if there is
+    /// no built-in driver of that name.
+    ///
+    /// In that case, or if the attribute isn't set, the [`Default`][Driver::Default] driver is what `git` would use.
+    pub fn from_name(name: &BStr) -> Option<Self> {
+        Some(match name.as_ref() {
+            b"rust" => Driver::Rust,
+            b"cpp" => Driver::Cpp,
+            b"python" => Driver::Python,
+            _ => return None,
+        })
+    }
+
+    /// Return the part of `line` to show after a hunk header if it starts a function, or `None` otherwise.
+    ///
+    /// The returned text doesn't include the line terminator, but isn't trimmed or limited in length.
+    pub fn function_line<'a>(&self, line: &'a [u8]) -> Option<&'a [u8]> {
+        let line = line
+            .strip_suffix(b"\r\n")
+            .or_else(|| line.strip_suffix(b"\n"))
+            .unwrap_or(line);
+        match self {
+            Driver::Default => {
+                matches!(line.first(), Some(b) if b.is_ascii_alphabetic() || *b == b'_' || *b == b'$').then(|| line)
+            }
+            Driver::Rust => rust(line),
+            Driver::Cpp => cpp(line),
+            Driver::Python => python(line),
+        }
+    }
+}
+
+/// `^[\t ]*((pub(\([^\)]+\))?[\t ]+)?((async|const|unsafe|extern([\t ]+"[^"]+"))[\t ]+)?(struct|enum|union|mod|trait|fn|impl|macro_rules!)[< \t]+[^;]*)$`
+fn rust(line: &[u8]) -> Option<&[u8]> {
+    let function = skip_blanks(line);
+    let mut rest = function;
+    if let Some(after_pub) = rest.strip_prefix(b"pub") {
+        let after_restriction = after_pub
+            .strip_prefix(b"(")
+            .and_then(|restriction| {
+                let end = restriction.iter().position(|b| *b == b')')?;
+                (end > 0).then(|| &restriction[end + 1..])
+            })
+            .filter(|rest| starts_with_blank(rest))
+            .unwrap_or(after_pub);
+        if starts_with_blank(after_restriction) {
+            rest = skip_blanks(after_restriction);
+        }
+    }
+    let after_qualifier = [&b"async"[..], b"const", b"unsafe"]
+        .iter()
+        .find_map(|qualifier| rest.strip_prefix(*qualifier))
+        .or_else(|| {
+            let abi = skip_blanks(rest.strip_prefix(b"extern").filter(|rest| starts_with_blank(rest))?);
+            let abi = abi.strip_prefix(b"\"")?;
+            let end = abi.iter().position(|b| *b == b'"').filter(|end| *end > 0)?;
+            Some(&abi[end + 1..])
+        })
+        .filter(|rest| starts_with_blank(rest));
+    if let Some(after_qualifier) = after_qualifier {
+        rest = skip_blanks(after_qualifier);
+    }
+    let after_keyword = [
+        &b"struct"[..],
+        b"enum",
+        b"union",
+        b"mod",
+        b"trait",
+        b"fn",
+        b"impl",
+        b"macro_rules!",
+    ]
+    .iter()
+    .find_map(|keyword| rest.strip_prefix(*keyword))?;
+    if matches!(after_keyword.first(), Some(b'<' | b' ' | b'\t')) && !after_keyword.contains(&b';') {
+        Some(function)
+    } else {
+        None
+    }
+}
+
+/// `!^[ \t]*[A-Za-z_][A-Za-z_0-9]*:[[:space:]]*($|/[/*])` followed by `^((::[[:space:]]*)?[A-Za-z_].*)$`
+fn cpp(line: &[u8]) -> Option<&[u8]> {
+    let indented = skip_blanks(line);
+    if matches!(indented.first(), Some(b) if is_identifier_start(*b)) {
+        let after_label = &indented[indented.iter().take_while(|b| is_identifier(**b)).count()..];
+        if let Some(after_colon) = after_label.strip_prefix(b":") {
+            let after_space = &after_colon[after_colon.iter().take_while(|b| is_space(**b)).count()..];
+            if after_space.is_empty() || after_space.starts_with(b"//") || after_space.starts_with(b"/*") {
+                return None;
+            }
+        }
+    }
+
+    let after_scope = match line.strip_prefix(b"::") {
+        Some(rest) => &rest[rest.iter().take_while(|b| is_space(**b)).count()..],
+        None => line,
+    };
+    matches!(after_scope.first(), Some(b) if is_identifier_start(*b)).then(|| line)
+}
+
+/// `^[ \t]*((class|(async[ \t]+)?def)[ \t].*)$`
+fn python(line: &[u8]) -> Option<&[u8]> {
+    let function = skip_blanks(line);
+    let rest = function
+        .strip_prefix(b"async")
+        .filter(|rest| starts_with_blank(rest))
+        .map(skip_blanks)
+        .unwrap_or(function);
+    let after_keyword = if rest.len() < function.len() {
+        rest.strip_prefix(b"def")
+    } else {
+        rest.strip_prefix(b"class").or_else(|| rest.strip_prefix(b"def"))
+    }?;
+    if starts_with_blank(after_keyword) {
+        Some(function)
+    } else {
+        None
+    }
+}
+
+fn is_blank(b: u8) -> bool {
+    b == b' ' || b == b'\t'
+}
+
+fn starts_with_blank(data: &[u8]) -> bool {
+    matches!(data.first(), Some(b) if is_blank(*b))
+}
+
+fn skip_blanks(data: &[u8]) -> &[u8] {
+    &data[data.iter().take_while(|b| is_blank(**b)).count()..]
+}
+
+fn is_identifier_start(b: u8) -> bool {
+    b.is_ascii_alphabetic() || b == b'_'
+}
+
+fn is_identifier(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// Like `isspace()` in C, which includes the vertical tab unlike [`u8::is_ascii_whitespace()`].
+pub(crate) fn is_space(b: u8) -> bool {
+    b.is_ascii_whitespace() || b == b'\x0b'
+}
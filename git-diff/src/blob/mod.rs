@@ -0,0 +1,205 @@
+use std::ops::Range;
+
+use git_object::bstr::{BStr, ByteSlice};
+use imara_diff::{intern::InternedInput, sources::byte_lines_with_terminator};
+
+///
+pub mod function;
+///
+pub mod unified;
+pub use unified::UnifiedDiff;
+
+/// The amount of bytes at the beginning of a blob which are searched for NUL bytes to detect binary content, like `git` does.
+pub const BINARY_DETECTION_LEN: usize = 8000;
+
+/// The algorithm to use for computing line differences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// The algorithm by Eugene Myers, which is the default in `git`.
+    Myers,
+    /// A variant of the patience algorithm which often produces more readable diffs, like `git diff --histogram`.
+    Histogram,
+}
+
+impl Default for Algorithm {
+    fn default() -> Self {
+        Algorithm::Myers
+    }
+}
+
+impl From<Algorithm> for imara_diff::Algorithm {
+    fn from(v: Algorithm) -> Self {
+        match v {
+            Algorithm::Myers => imara_diff::Algorithm::Myers,
+            Algorithm::Histogram => imara_diff::Algorithm::Histogram,
+        }
+    }
+}
+
+/// The header of a hunk, with line numbers starting at 1 like in unified diffs.
+///
+/// If a side of the hunk has no lines, its start is the line after which lines would be inserted, or 0 if they are inserted
+/// at the beginning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Header {
+    /// The first line of the hunk in the old version.
+    pub old_start: u32,
+    /// The amount of lines of the hunk in the old version.
+    pub old_lines: u32,
+    /// The first line of the hunk in the new version.
+    pub new_start: u32,
+    /// The amount of lines of the hunk in the new version.
+    pub new_lines: u32,
+}
+
+impl std::fmt::Display for Header {
+    /// Display this header like `@@ -1,3 +1,4 @@`, omitting line counts of 1 like `git` does.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn range(f: &mut std::fmt::Formatter<'_>, start: u32, lines: u32) -> std::fmt::Result {
+            if lines == 1 {
+                write!(f, "{}", start)
+            } else {
+                write!(f, "{},{}", start, lines)
+            }
+        }
+        f.write_str("@@ -")?;
+        range(f, self.old_start, self.old_lines)?;
+        f.write_str(" +")?;
+        range(f, self.new_start, self.new_lines)?;
+        f.write_str(" @@")
+    }
+}
+
+/// Receive the hunks computed by [`diff()`].
+///
+/// All lines are passed as they are stored, including their line terminator unless it is the last line of a blob without trailing newline.
+/// Consecutive lines of the same kind are passed at once.
+pub trait Sink {
+    /// The result produced once all hunks were received.
+    type Out;
+
+    /// Called at the beginning of each hunk, before any of its lines.
+    fn hunk(&mut self, header: Header);
+    /// Called with one or more unchanged lines surrounding the changes of a hunk.
+    fn context(&mut self, lines: &BStr);
+    /// Called with one or more lines which are only present in the old version.
+    fn removed(&mut self, lines: &BStr);
+    /// Called with one or more lines which are only present in the new version.
+    fn added(&mut self, lines: &BStr);
+    /// Called after all hunks were received to produce the result.
+    fn finish(self) -> Self::Out;
+}
+
+/// The result of [`diff()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome<T> {
+    /// Both blobs were compared line by line and the hunks were passed to the sink which produced `T`.
+    Text(T),
+    /// At least one of the blobs appears to be binary, hence they were not compared line by line.
+    Binary {
+        /// The size of the old blob in bytes.
+        old_len: usize,
+        /// The size of the new blob in bytes.
+        new_len: usize,
+    },
+}
+
+/// Return true if `data` is considered binary as it contains a NUL byte within the first [`BINARY_DETECTION_LEN`] bytes.
+pub fn is_binary(data: &[u8]) -> bool {
+    data[..data.len().min(BINARY_DETECTION_LEN)].contains(&0)
+}
+
+/// Compare the lines of the `old` and `new` blob using `algorithm` and pass the resulting hunks with up to `context_lines`
+/// unchanged lines around each change to `sink`.
+///
+/// Hunks whose changes are separated by no more than twice the amount of `context_lines` are merged, like `git diff` does.
+/// If [either blob is binary][is_binary()], the `sink` isn't called and [`Outcome::Binary`] is returned.
+pub fn diff<S: Sink>(old: &[u8], new: &[u8], algorithm: Algorithm, context_lines: u32, mut sink: S) -> Outcome<S::Out> {
+    if is_binary(old) || is_binary(new) {
+        return Outcome::Binary {
+            old_len: old.len(),
+            new_len: new.len(),
+        };
+    }
+
+    let input = InternedInput::new(byte_lines_with_terminator(old), byte_lines_with_terminator(new));
+    let mut changes = Vec::<(Range<u32>, Range<u32>)>::new();
+    imara_diff::diff(algorithm.into(), &input, |before, after| changes.push((before, after)));
+
+    let old_lines = Lines::new(old);
+    let new_lines = Lines::new(new);
+    let mut changes = changes.into_iter().peekable();
+    while let Some(first) = changes.next() {
+        let mut last = first.clone();
+        let mut hunk = vec![first.clone()];
+        while let Some(next) = changes.next_if(|(before, _)| before.start - last.0.end <= 2 * context_lines) {
+            last = next.clone();
+            hunk.push(next);
+        }
+
+        let leading_context = first.0.start.min(context_lines);
+        let trailing_context = (old_lines.len() - last.0.end).min(context_lines);
+        let old_range = first.0.start - leading_context..last.0.end + trailing_context;
+        let new_range = first.1.start - leading_context..last.1.end + trailing_context;
+        sink.hunk(Header {
+            old_start: start_of(&old_range),
+            old_lines: old_range.len() as u32,
+            new_start: start_of(&new_range),
+            new_lines: new_range.len() as u32,
+        });
+
+        let mut unchanged_start = old_range.start;
+        for (before, after) in hunk {
+            if unchanged_start < before.start {
+                sink.context(old_lines.slice(unchanged_start..before.start));
+            }
+            if !before.is_empty() {
+                sink.removed(old_lines.slice(before.clone()));
+            }
+            if !after.is_empty() {
+                sink.added(new_lines.slice(after));
+            }
+            unchanged_start = before.end;
+        }
+        if unchanged_start < old_range.end {
+            sink.context(old_lines.slice(unchanged_start..old_range.end));
+        }
+    }
+    Outcome::Text(sink.finish())
+}
+
+/// Return the line number at which `range` starts when displayed in a hunk header.
+fn start_of(range: &Range<u32>) -> u32 {
+    if range.is_empty() {
+        range.start
+    } else {
+        range.start + 1
+    }
+}
+
+/// The offsets at which each line of a blob starts, split just like the tokens compared by the diff algorithm.
+struct Lines<'a> {
+    data: &'a [u8],
+    /// The start of each line, followed by the end of the last line.
+    offsets: Vec<usize>,
+}
+
+impl<'a> Lines<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        let mut offsets: Vec<_> = std::iter::once(0)
+            .chain(data.find_iter(b"\n").map(|pos| pos + 1))
+            .collect();
+        if offsets.last() != Some(&data.len()) {
+            offsets.push(data.len());
+        }
+        Lines { data, offsets }
+    }
+
+    fn len(&self) -> u32 {
+        (self.offsets.len() - 1) as u32
+    }
+
+    fn slice(&self, lines: Range<u32>) -> &'a BStr {
+        self.data[self.offsets[lines.start as usize]..self.offsets[lines.end as usize]].as_bstr()
+    }
+}
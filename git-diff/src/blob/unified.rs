@@ -0,0 +1,286 @@
+use std::{borrow::Cow, io};
+
+use git_object::bstr::{BStr, BString, ByteSlice, LinesWithTerminator};
+
+use crate::{
+    blob,
+    blob::{function, Algorithm, Header, Outcome},
+};
+
+/// The maximum length of the text of a function line shown in hunk headers, like in `git`.
+const MAX_FUNCTION_LINE_LEN: usize = 80;
+
+/// Options to configure a [`UnifiedDiff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Options {
+    /// The algorithm to use for computing line differences.
+    pub algorithm: Algorithm,
+    /// If true, `\r\n` line endings are converted to `\n` in both blobs before comparing them, so that changes to line endings
+    /// alone are not considered.
+    pub normalize_crlf: bool,
+    /// Determines which lines start a function, to show the closest one preceding each hunk after its header
+    /// when [writing patches][UnifiedDiff::write_patch()].
+    pub function_driver: function::Driver,
+}
+
+/// A line of a [`Hunk`], including its line terminator unless it is the last line of a blob without trailing newline.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Line {
+    /// An unchanged line surrounding the changes of a hunk.
+    Context(BString),
+    /// A line only present in the old version.
+    Removed(BString),
+    /// A line only present in the new version.
+    Added(BString),
+}
+
+/// A hunk of a unified diff, consisting of removed and added lines along with a window of unchanged lines as context.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hunk {
+    /// The line numbers of the hunk in the old and the new version.
+    pub header: Header,
+    /// All lines of the hunk in the order they would be shown in a patch.
+    pub lines: Vec<Line>,
+}
+
+/// The difference between two blobs, to be obtained as [hunks][UnifiedDiff::hunks()] or
+/// written as [unified patch][UnifiedDiff::write_patch()].
+pub struct UnifiedDiff<'a> {
+    old: Cow<'a, [u8]>,
+    new: Cow<'a, [u8]>,
+    options: Options,
+}
+
+impl<'a> UnifiedDiff<'a> {
+    /// Prepare a diff between the `old` and the `new` version of a blob, configured with `options`.
+    pub fn new(old: &'a [u8], new: &'a [u8], options: Options) -> Self {
+        UnifiedDiff {
+            old: normalize(old.into(), options),
+            new: normalize(new.into(), options),
+            options,
+        }
+    }
+
+    /// Like [`new()`][UnifiedDiff::new()], but reads the `old` and the `new` version from streams.
+    pub fn from_read(
+        mut old: impl io::Read,
+        mut new: impl io::Read,
+        options: Options,
+    ) -> io::Result<UnifiedDiff<'static>> {
+        let mut old_buf = Vec::new();
+        old.read_to_end(&mut old_buf)?;
+        let mut new_buf = Vec::new();
+        new.read_to_end(&mut new_buf)?;
+        Ok(UnifiedDiff {
+            old: normalize(old_buf.into(), options),
+            new: normalize(new_buf.into(), options),
+            options,
+        })
+    }
+
+    /// Compute all hunks with up to `context_lines` of unchanged lines around each change, or learn that one of
+    /// the versions is binary.
+    pub fn hunks(&self, context_lines: u32) -> Outcome<Vec<Hunk>> {
+        blob::diff(
+            &self.old,
+            &self.new,
+            self.options.algorithm,
+            context_lines,
+            Collect::default(),
+        )
+    }
+
+    /// Write a unified patch with up to `context_lines` of unchanged lines around each change to `out`, with `---` and `+++`
+    /// headers showing `old_path` and `new_path` as given, like `a/file` or `/dev/null`.
+    ///
+    /// Like `git`, nothing is written if both versions are equal, and only a `Binary files … differ` line is written if one of them
+    /// is binary. Hunk headers are followed by the closest preceding line of the old version that starts a function according to
+    /// [`Options::function_driver`], which by default is any line that starts like an identifier.
+    pub fn write_patch(
+        &self,
+        mut out: impl io::Write,
+        old_path: &BStr,
+        new_path: &BStr,
+        context_lines: u32,
+    ) -> io::Result<()> {
+        if self.old == self.new {
+            return Ok(());
+        }
+        let writer = Writer {
+            out: &mut out,
+            old_path,
+            new_path,
+            wrote_file_header: false,
+            old_lines: self.old.lines_with_terminator(),
+            old_lines_seen: 0,
+            function_driver: self.options.function_driver,
+            function: None,
+            err: None,
+        };
+        match blob::diff(&self.old, &self.new, self.options.algorithm, context_lines, writer) {
+            Outcome::Text(res) => res,
+            Outcome::Binary { .. } => {
+                out.write_all(b"Binary files ")?;
+                out.write_all(old_path)?;
+                out.write_all(b" and ")?;
+                out.write_all(new_path)?;
+                out.write_all(b" differ\n")
+            }
+        }
+    }
+}
+
+fn normalize(data: Cow<'_, [u8]>, options: Options) -> Cow<'_, [u8]> {
+    if options.normalize_crlf && data.find(b"\r\n").is_some() {
+        data.replace(b"\r\n", b"\n").into()
+    } else {
+        data
+    }
+}
+
+#[derive(Default)]
+struct Collect {
+    hunks: Vec<Hunk>,
+}
+
+impl Collect {
+    fn lines(&mut self, lines: &BStr, make_line: impl Fn(BString) -> Line) {
+        let hunk = self.hunks.last_mut().expect("lines are always part of a hunk");
+        hunk.lines
+            .extend(lines.lines_with_terminator().map(|line| make_line(line.into())));
+    }
+}
+
+impl blob::Sink for Collect {
+    type Out = Vec<Hunk>;
+
+    fn hunk(&mut self, header: Header) {
+        self.hunks.push(Hunk {
+            header,
+            lines: Vec::new(),
+        });
+    }
+
+    fn context(&mut self, lines: &BStr) {
+        self.lines(lines, Line::Context)
+    }
+
+    fn removed(&mut self, lines: &BStr) {
+        self.lines(lines, Line::Removed)
+    }
+
+    fn added(&mut self, lines: &BStr) {
+        self.lines(lines, Line::Added)
+    }
+
+    fn finish(self) -> Self::Out {
+        self.hunks
+    }
+}
+
+/// A sink to stream a unified patch into `out`, keeping the first error to return it once done.
+struct Writer<'a, W> {
+    out: W,
+    old_path: &'a BStr,
+    new_path: &'a BStr,
+    wrote_file_header: bool,
+    old_lines: LinesWithTerminator<'a>,
+    old_lines_seen: u32,
+    function_driver: function::Driver,
+    /// The last line of the old version which looks like the start of a function, as seen so far.
+    function: Option<&'a [u8]>,
+    err: Option<io::Error>,
+}
+
+impl<'a, W: io::Write> Writer<'a, W> {
+    fn write_lines(&mut self, prefix: u8, lines: &BStr) {
+        if self.err.is_some() {
+            return;
+        }
+        for line in lines.lines_with_terminator() {
+            if let Err(err) = self.write_line(prefix, line) {
+                self.err = Some(err);
+                return;
+            }
+        }
+    }
+
+    fn write_line(&mut self, prefix: u8, line: &[u8]) -> io::Result<()> {
+        self.out.write_all(&[prefix])?;
+        self.out.write_all(line)?;
+        if !line.ends_with(b"\n") {
+            self.out.write_all(b"\n\\ No newline at end of file\n")?;
+        }
+        Ok(())
+    }
+
+    fn write_hunk_header(&mut self, header: Header) -> io::Result<()> {
+        if !self.wrote_file_header {
+            self.wrote_file_header = true;
+            self.out.write_all(b"--- ")?;
+            self.out.write_all(self.old_path)?;
+            self.out.write_all(b"\n+++ ")?;
+            self.out.write_all(self.new_path)?;
+            self.out.write_all(b"\n")?;
+        }
+
+        let first_line_of_hunk = if header.old_lines == 0 {
+            header.old_start
+        } else {
+            header.old_start - 1
+        };
+        while self.old_lines_seen < first_line_of_hunk {
+            let line = self.old_lines.next().expect("hunks are within the old version");
+            if let Some(function) = self.function_driver.function_line(line) {
+                self.function = Some(function);
+            }
+            self.old_lines_seen += 1;
+        }
+
+        write!(self.out, "{}", header)?;
+        if let Some(function) = self.function {
+            let mut function = &function[..function.len().min(MAX_FUNCTION_LINE_LEN)];
+            while let Some((last, rest)) = function.split_last() {
+                if !function::is_space(*last) {
+                    break;
+                }
+                function = rest;
+            }
+            self.out.write_all(b" ")?;
+            self.out.write_all(function)?;
+        }
+        self.out.write_all(b"\n")
+    }
+}
+
+impl<'a, W: io::Write> blob::Sink for Writer<'a, W> {
+    type Out = io::Result<()>;
+
+    fn hunk(&mut self, header: Header) {
+        if self.err.is_some() {
+            return;
+        }
+        if let Err(err) = self.write_hunk_header(header) {
+            self.err = Some(err);
+        }
+    }
+
+    fn context(&mut self, lines: &BStr) {
+        self.write_lines(b' ', lines)
+    }
+
+    fn removed(&mut self, lines: &BStr) {
+        self.write_lines(b'-', lines)
+    }
+
+    fn added(&mut self, lines: &BStr) {
+        self.write_lines(b'+', lines)
+    }
+
+    fn finish(self) -> Self::Out {
+        match self.err {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
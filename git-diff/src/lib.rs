@@ -2,5 +2,7 @@
 #![forbid(unsafe_code, rust_2018_idioms)]
 #[deny(missing_docs)]
 
+///
+pub mod blob;
 ///
 pub mod tree;
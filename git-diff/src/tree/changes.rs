@@ -5,7 +5,7 @@ use quick_error::quick_error;
 
 use crate::{
     tree,
-    tree::{visit::Change, TreeInfoPair},
+    tree::{visit::Change, Statistics, TreeInfoPair},
 };
 
 quick_error! {
@@ -42,6 +42,8 @@ impl<'a> tree::Changes<'a> {
     /// * To obtain progress, implement it within the `delegate`.
     /// * Tree entries are expected to be ordered using [`tree-entry-comparison`][git_cmp_c] (the same [in Rust][git_cmp_rs])
     /// * it does a breadth first iteration as buffer space only fits two trees, the current one on the one we compare with.
+    /// * subtrees with equal object ids on both sides are skipped without being obtained, and entries with equal names and
+    ///   object ids never reach the `delegate`. [`State::statistics()`][tree::State::statistics()] tells how much work was done.
     /// * does not do rename tracking but attempts to reduce allocations to zero (so performance is mostly determined
     ///   by the delegate implementation which should be as specific as possible. Rename tracking can be computed on top of the changes
     ///   received by the `delegate`.
@@ -100,9 +102,29 @@ impl<'a> tree::Changes<'a> {
                     use std::cmp::Ordering::*;
                     let (lhs, rhs) = (lhs?, rhs?);
                     match lhs.filename.cmp(rhs.filename) {
-                        Equal => handle_lhs_and_rhs_with_equal_filenames(lhs, rhs, &mut state.trees, delegate)?,
-                        Less => catchup_lhs_with_rhs(&mut lhs_entries, lhs, rhs, &mut state.trees, delegate)?,
-                        Greater => catchup_rhs_with_lhs(&mut rhs_entries, lhs, rhs, &mut state.trees, delegate)?,
+                        Equal => handle_lhs_and_rhs_with_equal_filenames(
+                            lhs,
+                            rhs,
+                            &mut state.trees,
+                            &mut state.statistics,
+                            delegate,
+                        )?,
+                        Less => catchup_lhs_with_rhs(
+                            &mut lhs_entries,
+                            lhs,
+                            rhs,
+                            &mut state.trees,
+                            &mut state.statistics,
+                            delegate,
+                        )?,
+                        Greater => catchup_rhs_with_lhs(
+                            &mut rhs_entries,
+                            lhs,
+                            rhs,
+                            &mut state.trees,
+                            &mut state.statistics,
+                            delegate,
+                        )?,
                     }
                 }
                 (Some(lhs), None) => {
@@ -168,6 +190,7 @@ fn catchup_rhs_with_lhs<R: tree::Visit>(
     lhs: git_object::tree::EntryRef<'_>,
     rhs: git_object::tree::EntryRef<'_>,
     queue: &mut VecDeque<TreeInfoPair>,
+    statistics: &mut Statistics,
     delegate: &mut R,
 ) -> Result<(), Error> {
     use std::cmp::Ordering::*;
@@ -178,7 +201,7 @@ fn catchup_rhs_with_lhs<R: tree::Visit>(
                 Equal => {
                     let rhs = rhs_entries.next().transpose()?.expect("the peeked item tobe present");
                     delegate.pop_path_component();
-                    handle_lhs_and_rhs_with_equal_filenames(lhs, rhs, queue, delegate)?;
+                    handle_lhs_and_rhs_with_equal_filenames(lhs, rhs, queue, statistics, delegate)?;
                     break;
                 }
                 Greater => {
@@ -208,6 +231,7 @@ fn catchup_lhs_with_rhs<R: tree::Visit>(
     lhs: git_object::tree::EntryRef<'_>,
     rhs: git_object::tree::EntryRef<'_>,
     queue: &mut VecDeque<TreeInfoPair>,
+    statistics: &mut Statistics,
     delegate: &mut R,
 ) -> Result<(), Error> {
     use std::cmp::Ordering::*;
@@ -218,7 +242,7 @@ fn catchup_lhs_with_rhs<R: tree::Visit>(
                 Equal => {
                     let lhs = lhs_entries.next().expect("the peeked item to be present")?;
                     delegate.pop_path_component();
-                    handle_lhs_and_rhs_with_equal_filenames(lhs, rhs, queue, delegate)?;
+                    handle_lhs_and_rhs_with_equal_filenames(lhs, rhs, queue, statistics, delegate)?;
                     break;
                 }
                 Less => {
@@ -247,21 +271,28 @@ fn handle_lhs_and_rhs_with_equal_filenames<R: tree::Visit>(
     lhs: git_object::tree::EntryRef<'_>,
     rhs: git_object::tree::EntryRef<'_>,
     queue: &mut VecDeque<TreeInfoPair>,
+    statistics: &mut Statistics,
     delegate: &mut R,
 ) -> Result<(), Error> {
     use git_object::tree::EntryMode::*;
+    statistics.entries_compared += 1;
     match (lhs.mode, rhs.mode) {
         (Tree, Tree) => {
+            if lhs.oid == rhs.oid {
+                statistics.subtrees_skipped += 1;
+                // Balance the path component popped by the caller before handling the next entry.
+                delegate.push_path_component(lhs.filename);
+                return Ok(());
+            }
             delegate.push_back_tracked_path_component(lhs.filename);
-            if lhs.oid != rhs.oid
-                && delegate
-                    .visit(Change::Modification {
-                        previous_entry_mode: lhs.mode,
-                        previous_oid: lhs.oid.to_owned(),
-                        entry_mode: rhs.mode,
-                        oid: rhs.oid.to_owned(),
-                    })
-                    .cancelled()
+            if delegate
+                .visit(Change::Modification {
+                    previous_entry_mode: lhs.mode,
+                    previous_oid: lhs.oid.to_owned(),
+                    entry_mode: rhs.mode,
+                    oid: rhs.oid.to_owned(),
+                })
+                .cancelled()
             {
                 return Err(Error::Cancelled);
             }
@@ -314,7 +345,7 @@ fn handle_lhs_and_rhs_with_equal_filenames<R: tree::Visit>(
         (lhs_non_tree, rhs_non_tree) => {
             delegate.push_path_component(lhs.filename);
             debug_assert!(lhs_non_tree.is_no_tree() && rhs_non_tree.is_no_tree());
-            if lhs.oid != rhs.oid
+            if (lhs.oid != rhs.oid || lhs.mode != rhs.mode)
                 && delegate
                     .visit(Change::Modification {
                         previous_entry_mode: lhs.mode,
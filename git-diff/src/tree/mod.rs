@@ -9,15 +9,31 @@ pub struct State {
     buf1: Vec<u8>,
     buf2: Vec<u8>,
     trees: VecDeque<TreeInfoPair>,
+    statistics: Statistics,
 }
 
 type TreeInfoPair = (Option<ObjectId>, Option<ObjectId>);
 
+/// Counters describing the work done by the last call to [`Changes::needed_to_obtain()`], obtained with [`State::statistics()`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Statistics {
+    /// The amount of entries with equal names on both sides which were compared by mode and object id.
+    pub entries_compared: usize,
+    /// The amount of subtrees on both sides which weren't traversed as their object ids are equal.
+    pub subtrees_skipped: usize,
+}
+
 impl State {
     fn clear(&mut self) {
         self.trees.clear();
         self.buf1.clear();
         self.buf2.clear();
+        self.statistics = Statistics::default();
+    }
+
+    /// Return the counters of the last diff performed with this state.
+    pub fn statistics(&self) -> Statistics {
+        self.statistics
     }
 }
 
@@ -41,6 +57,9 @@ pub mod visit;
 #[doc(inline)]
 pub use visit::Visit;
 
+/// Write unified patches for changes between trees.
+pub mod patch;
+
 /// Useful for use as delegate implementing [`Visit`] to keep track of all seen changes. Useful for debugging or printing primarily.
 pub mod recorder;
 #[doc(inline)]
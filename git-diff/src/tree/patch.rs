@@ -0,0 +1,266 @@
+use std::io;
+
+use git_hash::{oid, ObjectId};
+use git_object::{
+    bstr::{BStr, BString, ByteVec},
+    tree::EntryMode,
+    BlobRef,
+};
+use quick_error::quick_error;
+
+use crate::{
+    blob::{function, unified},
+    tree::recorder::Change,
+};
+
+quick_error! {
+    /// The error returned by [`write()`].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        Changes(err: crate::tree::changes::Error) {
+            display("The changes between both trees could not be computed")
+            from()
+            source(err)
+        }
+        NotFound { oid: ObjectId } {
+            display("The blob {} could not be found in the object database", oid)
+        }
+        Io(err: io::Error) {
+            display("The patch could not be written")
+            from()
+            source(err)
+        }
+    }
+}
+
+/// Options for [`write()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// The amount of unchanged lines to show around each change.
+    pub context_lines: u32,
+    /// Options to configure how blobs are compared.
+    pub diff: unified::Options,
+    /// What to do if a blob can't be found, as is expected in partial clones.
+    pub missing_blobs: Missing,
+}
+
+/// Determines what [`write()`] does if a blob can't be found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Missing {
+    /// Fail with [`Error::NotFound`].
+    Error,
+    /// Write only the header of the file whose blob is missing, and report its id in [`Outcome::missing_blobs`].
+    Skip,
+}
+
+impl Default for Missing {
+    fn default() -> Self {
+        Missing::Error
+    }
+}
+
+/// The outcome of [`write()`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Outcome {
+    /// The ids of all blobs which couldn't be found and were skipped, in the order they were encountered.
+    ///
+    /// It's always empty unless [`Options::missing_blobs`] is [`Missing::Skip`].
+    pub missing_blobs: Vec<ObjectId>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            context_lines: 3,
+            diff: Default::default(),
+            missing_blobs: Default::default(),
+        }
+    }
+}
+
+/// One side of a file in a patch.
+type Side = Option<(EntryMode, ObjectId)>;
+
+/// Write a unified patch for all `changes` to `out`, like `git diff-tree -p --full-index` does, with `find_blob` being a function
+/// to lookup blobs by id and place their data into the given buffer.
+///
+/// Files are written in the order of their path, and changes to trees are skipped as their contents are part of `changes`.
+/// Changes to the type of an entry, like from a file to a symbolic link, are written as deletion followed by an addition.
+/// Submodules are shown like `git` does, as commit id prefixed with `Subproject commit`.
+///
+/// Blobs which can't be found fail the operation, unless [`Options::missing_blobs`] is set to [`Missing::Skip`] to write only
+/// the headers of the affected files.
+///
+/// Note that paths are written as they are, without quoting them if they contain unusual characters.
+pub fn write<FindBlob>(
+    changes: impl IntoIterator<Item = Change>,
+    find_blob: FindBlob,
+    options: Options,
+    out: impl io::Write,
+) -> Result<Outcome, Error>
+where
+    FindBlob: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<BlobRef<'b>>,
+{
+    let driver = options.diff.function_driver;
+    write_with_drivers(changes, find_blob, |_| driver, options, out)
+}
+
+/// Like [`write()`], but call `function_driver` with the path of each file to obtain the [driver][function::Driver] which finds
+/// the function lines shown after its hunk headers, instead of using [`unified::Options::function_driver`] for all files.
+///
+/// This is where the `diff` attribute of each path, like `diff=rust`, is applied when attributes are available, typically with
+/// [`Driver::from_name()`][function::Driver::from_name()].
+pub fn write_with_drivers<FindBlob>(
+    changes: impl IntoIterator<Item = Change>,
+    mut find_blob: FindBlob,
+    mut function_driver: impl FnMut(&BStr) -> function::Driver,
+    options: Options,
+    mut out: impl io::Write,
+) -> Result<Outcome, Error>
+where
+    FindBlob: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<BlobRef<'b>>,
+{
+    let mut files = Vec::<(BString, Side, Side)>::new();
+    for change in changes {
+        match change {
+            Change::Addition { entry_mode, oid, path } => files.push((path, None, Some((entry_mode, oid)))),
+            Change::Deletion { entry_mode, oid, path } => files.push((path, Some((entry_mode, oid)), None)),
+            Change::Modification {
+                previous_entry_mode,
+                previous_oid,
+                entry_mode,
+                oid,
+                path,
+            } => {
+                if file_type(previous_entry_mode) == file_type(entry_mode) {
+                    files.push((path, Some((previous_entry_mode, previous_oid)), Some((entry_mode, oid))));
+                } else {
+                    files.push((path.clone(), Some((previous_entry_mode, previous_oid)), None));
+                    files.push((path, None, Some((entry_mode, oid))));
+                }
+            }
+        }
+    }
+    let is_tree = |side: &Side| matches!(side, Some((mode, _)) if mode.is_tree());
+    files.retain(|(_, old, new)| !is_tree(old) && !is_tree(new));
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut outcome = Outcome::default();
+    let (mut old_buf, mut new_buf) = (Vec::new(), Vec::new());
+    for (path, old, new) in files {
+        write_file_header(&mut out, path.as_ref(), old, new)?;
+        let (old_data, new_data) = match (
+            data_of(old, &mut find_blob, &mut old_buf),
+            data_of(new, &mut find_blob, &mut new_buf),
+        ) {
+            (Ok(old_data), Ok(new_data)) => (old_data, new_data),
+            (old_data, new_data) => {
+                for res in [old_data, new_data] {
+                    match res {
+                        Err(Error::NotFound { oid }) if options.missing_blobs == Missing::Skip => {
+                            outcome.missing_blobs.push(oid)
+                        }
+                        Err(err) => return Err(err),
+                        Ok(_) => {}
+                    }
+                }
+                continue;
+            }
+        };
+        let diff_options = unified::Options {
+            function_driver: function_driver(path.as_ref()),
+            ..options.diff
+        };
+        unified::UnifiedDiff::new(old_data, new_data, diff_options).write_patch(
+            &mut out,
+            side_path(b"a/", path.as_ref(), old).as_ref(),
+            side_path(b"b/", path.as_ref(), new).as_ref(),
+            options.context_lines,
+        )?;
+    }
+    Ok(outcome)
+}
+
+/// The type of a file as `git` distinguishes it, ignoring the executable bit.
+fn file_type(mode: EntryMode) -> EntryMode {
+    match mode {
+        EntryMode::BlobExecutable => EntryMode::Blob,
+        mode => mode,
+    }
+}
+
+fn data_of<'b, FindBlob>(side: Side, find_blob: &mut FindBlob, buf: &'b mut Vec<u8>) -> Result<&'b [u8], Error>
+where
+    FindBlob: for<'c> FnMut(&oid, &'c mut Vec<u8>) -> Option<BlobRef<'c>>,
+{
+    match side {
+        None => Ok(&[]),
+        Some((EntryMode::Commit, id)) => {
+            buf.clear();
+            buf.push_str("Subproject commit ");
+            buf.push_str(id.to_sha1_hex());
+            buf.push(b'\n');
+            Ok(buf.as_slice())
+        }
+        Some((_, id)) => find_blob(&id, buf)
+            .map(|blob| blob.data)
+            .ok_or(Error::NotFound { oid: id }),
+    }
+}
+
+fn side_path(prefix: &[u8], path: &BStr, side: Side) -> BString {
+    match side {
+        Some(_) => {
+            let mut out = BString::from(prefix);
+            out.push_str(path);
+            out
+        }
+        None => "/dev/null".into(),
+    }
+}
+
+fn write_file_header(out: &mut impl io::Write, path: &BStr, old: Side, new: Side) -> io::Result<()> {
+    out.write_all(b"diff --git a/")?;
+    out.write_all(path)?;
+    out.write_all(b" b/")?;
+    out.write_all(path)?;
+    out.write_all(b"\n")?;
+
+    let null = |id: ObjectId| ObjectId::null(id.kind());
+    let (old_id, new_id, mode) = match (old, new) {
+        (None, Some((mode, id))) => {
+            out.write_all(b"new file mode ")?;
+            out.write_all(mode.as_bytes())?;
+            out.write_all(b"\n")?;
+            (null(id), id, None)
+        }
+        (Some((mode, id)), None) => {
+            out.write_all(b"deleted file mode ")?;
+            out.write_all(mode.as_bytes())?;
+            out.write_all(b"\n")?;
+            (id, null(id), None)
+        }
+        (Some((old_mode, old_id)), Some((new_mode, new_id))) => {
+            if old_mode != new_mode {
+                out.write_all(b"old mode ")?;
+                out.write_all(old_mode.as_bytes())?;
+                out.write_all(b"\nnew mode ")?;
+                out.write_all(new_mode.as_bytes())?;
+                out.write_all(b"\n")?;
+            }
+            if old_id == new_id {
+                return Ok(());
+            }
+            let unchanged_mode = if old_mode == new_mode { Some(new_mode) } else { None };
+            (old_id, new_id, unchanged_mode)
+        }
+        (None, None) => unreachable!("changes always have at least one side"),
+    };
+    write!(out, "index {}..{}", old_id, new_id)?;
+    if let Some(mode) = mode {
+        out.write_all(b" ")?;
+        out.write_all(mode.as_bytes())?;
+    }
+    out.write_all(b"\n")
+}
@@ -0,0 +1,228 @@
+use git_diff::blob::{self, Algorithm, Header, Outcome};
+use git_object::bstr::{BStr, ByteSlice};
+
+/// A sink producing the hunks of a unified diff, like `git diff` does.
+#[derive(Default)]
+struct Patch {
+    out: String,
+    num_hunks: usize,
+}
+
+impl Patch {
+    fn lines(&mut self, prefix: char, lines: &BStr) {
+        for line in lines.lines_with_terminator() {
+            self.out.push(prefix);
+            self.out.push_str(line.to_str().expect("valid UTF-8"));
+            if !line.ends_with(b"\n") {
+                self.out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+}
+
+impl blob::Sink for Patch {
+    type Out = (String, usize);
+
+    fn hunk(&mut self, header: Header) {
+        self.num_hunks += 1;
+        self.out.push_str(&header.to_string());
+        self.out.push('\n');
+    }
+
+    fn context(&mut self, lines: &BStr) {
+        self.lines(' ', lines)
+    }
+
+    fn removed(&mut self, lines: &BStr) {
+        self.lines('-', lines)
+    }
+
+    fn added(&mut self, lines: &BStr) {
+        self.lines('+', lines)
+    }
+
+    fn finish(self) -> Self::Out {
+        (self.out, self.num_hunks)
+    }
+}
+
+fn patch(old: &str, new: &str, algorithm: Algorithm) -> String {
+    match blob::diff(old.as_bytes(), new.as_bytes(), algorithm, 3, Patch::default()) {
+        Outcome::Text((patch, _)) => patch,
+        Outcome::Binary { .. } => unreachable!("text isn't binary"),
+    }
+}
+
+/// Return the hunks produced by `git diff --no-index` for `old` and `new`.
+fn git_patch(old: &str, new: &str, algorithm: Algorithm) -> crate::Result<String> {
+    let dir = git_testtools::tempfile::tempdir()?;
+    std::fs::write(dir.path().join("old"), old)?;
+    std::fs::write(dir.path().join("new"), new)?;
+    let output = std::process::Command::new("git")
+        .args([
+            "diff",
+            "--no-index",
+            "--no-color",
+            "--no-ext-diff",
+            "--no-indent-heuristic",
+        ])
+        .arg(match algorithm {
+            Algorithm::Myers => "--diff-algorithm=myers",
+            Algorithm::Histogram => "--diff-algorithm=histogram",
+        })
+        .args(["old", "new"])
+        .current_dir(dir.path())
+        .output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    Ok(stdout
+        .find("@@")
+        .map(|pos| stdout[pos..].to_owned())
+        .unwrap_or_default())
+}
+
+fn assert_matches_git(old: &str, new: &str) -> crate::Result {
+    for algorithm in [Algorithm::Myers, Algorithm::Histogram] {
+        assert_eq!(
+            patch(old, new, algorithm),
+            git_patch(old, new, algorithm)?,
+            "{:?}: {:?} -> {:?}",
+            algorithm,
+            old,
+            new
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn identical_blobs_have_no_hunks() {
+    assert_eq!(patch("a\nb\n", "a\nb\n", Algorithm::Myers), "");
+    assert_eq!(patch("", "", Algorithm::Histogram), "");
+}
+
+#[test]
+fn empty_and_non_empty_blobs() -> crate::Result {
+    assert_eq!(patch("", "a\nb\n", Algorithm::Myers), "@@ -0,0 +1,2 @@\n+a\n+b\n");
+    assert_eq!(patch("a\n", "", Algorithm::Myers), "@@ -1 +0,0 @@\n-a\n");
+    assert_matches_git("", "a\nb\n")?;
+    assert_matches_git("a\nb\n", "")
+}
+
+#[test]
+fn blobs_without_trailing_newline() -> crate::Result {
+    assert_eq!(
+        patch("a\nb", "a\nb\n", Algorithm::Myers),
+        "@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+b\n"
+    );
+    assert_matches_git("a\nb", "a\nb\n")?;
+    assert_matches_git("a\nb\n", "a\nc")?;
+    assert_matches_git("a", "b")
+}
+
+#[test]
+fn context_is_limited_and_close_changes_are_merged_into_one_hunk() -> crate::Result {
+    let old: String = (1..=30).map(|n| format!("{}\n", n)).collect();
+    let change = |lines: &[usize]| -> String {
+        (1..=30)
+            .map(|n| {
+                if lines.contains(&n) {
+                    format!("{} changed\n", n)
+                } else {
+                    format!("{}\n", n)
+                }
+            })
+            .collect()
+    };
+
+    for (changed_lines, expected_hunks) in [
+        (&[10, 17][..], 1),
+        (&[10, 18][..], 2),
+        (&[1, 30][..], 2),
+        (&[2, 3, 15, 29][..], 3),
+    ] {
+        let new = change(changed_lines);
+        match blob::diff(old.as_bytes(), new.as_bytes(), Algorithm::Myers, 3, Patch::default()) {
+            Outcome::Text((_, num_hunks)) => assert_eq!(num_hunks, expected_hunks, "{:?}", changed_lines),
+            Outcome::Binary { .. } => unreachable!("text isn't binary"),
+        }
+        assert_matches_git(&old, &new)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn binary_blobs_are_not_diffed_line_by_line() {
+    let mut late_nul = vec![b'a'; blob::BINARY_DETECTION_LEN];
+    late_nul.push(0);
+    for (old, new, is_binary) in [
+        (&b"a\0b"[..], &b"text\n"[..], true),
+        (b"text\n", b"\0", true),
+        (&late_nul, b"text\n", false),
+    ] {
+        let outcome = blob::diff(old, new, Algorithm::Myers, 3, Patch::default());
+        if is_binary {
+            assert_eq!(
+                outcome,
+                Outcome::Binary {
+                    old_len: old.len(),
+                    new_len: new.len()
+                }
+            );
+        } else {
+            assert!(
+                matches!(outcome, Outcome::Text(_)),
+                "NUL bytes are only searched for at the beginning"
+            );
+        }
+    }
+}
+
+#[test]
+fn large_blobs_are_diffed_in_reasonable_time() {
+    let num_lines = 100_000;
+    let old: String = (0..num_lines).map(|n| format!("line {}\n", n)).collect();
+    let every_100th_line_changed: String = (0..num_lines)
+        .map(|n| {
+            if n % 100 == 0 {
+                format!("changed {}\n", n)
+            } else {
+                format!("line {}\n", n)
+            }
+        })
+        .collect();
+    let entirely_different: String = (0..num_lines).map(|n| format!("other {}\n", n)).collect();
+
+    for algorithm in [Algorithm::Myers, Algorithm::Histogram] {
+        let start = std::time::Instant::now();
+        match blob::diff(
+            old.as_bytes(),
+            every_100th_line_changed.as_bytes(),
+            algorithm,
+            3,
+            Patch::default(),
+        ) {
+            Outcome::Text((_, num_hunks)) => assert_eq!(num_hunks, num_lines / 100),
+            Outcome::Binary { .. } => unreachable!("text isn't binary"),
+        }
+        match blob::diff(
+            old.as_bytes(),
+            entirely_different.as_bytes(),
+            algorithm,
+            3,
+            Patch::default(),
+        ) {
+            Outcome::Text((patch, num_hunks)) => {
+                assert_eq!(num_hunks, 1);
+                assert!(patch.starts_with(&format!("@@ -1,{0} +1,{0} @@\n", num_lines)));
+            }
+            Outcome::Binary { .. } => unreachable!("text isn't binary"),
+        }
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(30),
+            "{:?} should not exhibit quadratic behaviour",
+            algorithm
+        );
+    }
+}
+
+mod unified;
@@ -0,0 +1,346 @@
+use git_diff::blob::{
+    unified::{Hunk, Line, Options},
+    Algorithm, Header, Outcome, UnifiedDiff,
+};
+
+fn write_patch(old: &[u8], new: &[u8], options: Options, context_lines: u32) -> crate::Result<String> {
+    let mut out = Vec::new();
+    UnifiedDiff::new(old, new, options).write_patch(&mut out, "a/old".into(), "b/new".into(), context_lines)?;
+    Ok(String::from_utf8(out)?)
+}
+
+/// Return the patch produced by `git diff --no-index` for `old` and `new`, starting at the `---` line.
+fn git_patch(old: &[u8], new: &[u8], algorithm: Algorithm, context_lines: u32) -> crate::Result<String> {
+    git_patch_with_driver(old, new, algorithm, context_lines, None)
+}
+
+/// Like [`git_patch()`], but with the `diff` attribute set to `driver` for both files.
+fn git_patch_with_driver(
+    old: &[u8],
+    new: &[u8],
+    algorithm: Algorithm,
+    context_lines: u32,
+    driver: Option<&str>,
+) -> crate::Result<String> {
+    let dir = git_testtools::tempfile::tempdir()?;
+    if let Some(driver) = driver {
+        let status = std::process::Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(dir.path())
+            .status()?;
+        assert!(status.success());
+        std::fs::write(dir.path().join(".gitattributes"), format!("* diff={}\n", driver))?;
+    }
+    std::fs::write(dir.path().join("old"), old)?;
+    std::fs::write(dir.path().join("new"), new)?;
+    let output = std::process::Command::new("git")
+        .args([
+            "diff",
+            "--no-index",
+            "--no-color",
+            "--no-ext-diff",
+            "--no-indent-heuristic",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+        ])
+        .arg(format!("--unified={}", context_lines))
+        .arg(match algorithm {
+            Algorithm::Myers => "--diff-algorithm=myers",
+            Algorithm::Histogram => "--diff-algorithm=histogram",
+        })
+        .args(["old", "new"])
+        .current_dir(dir.path())
+        .output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    Ok(stdout
+        .find("\n--- ")
+        .or_else(|| stdout.find("\nBinary files"))
+        .map(|pos| stdout[pos + 1..].to_owned())
+        .unwrap_or_default())
+}
+
+fn assert_matches_git(old: &[u8], new: &[u8]) -> crate::Result {
+    for algorithm in [Algorithm::Myers, Algorithm::Histogram] {
+        for context_lines in [0, 1, 3] {
+            let options = Options {
+                algorithm,
+                ..Default::default()
+            };
+            assert_eq!(
+                write_patch(old, new, options, context_lines)?,
+                git_patch(old, new, algorithm, context_lines)?,
+                "{:?} with {} lines of context",
+                algorithm,
+                context_lines
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn hunks_hold_context_and_changed_lines() {
+    let diff = UnifiedDiff::new(b"a\nb\nc\nd\ne\nf\n", b"a\nB\nc\nd\ne\nf", Options::default());
+    assert_eq!(
+        diff.hunks(1),
+        Outcome::Text(vec![
+            Hunk {
+                header: Header {
+                    old_start: 1,
+                    old_lines: 3,
+                    new_start: 1,
+                    new_lines: 3
+                },
+                lines: vec![
+                    Line::Context("a\n".into()),
+                    Line::Removed("b\n".into()),
+                    Line::Added("B\n".into()),
+                    Line::Context("c\n".into()),
+                ]
+            },
+            Hunk {
+                header: Header {
+                    old_start: 5,
+                    old_lines: 2,
+                    new_start: 5,
+                    new_lines: 2
+                },
+                lines: vec![
+                    Line::Context("e\n".into()),
+                    Line::Removed("f\n".into()),
+                    Line::Added("f".into())
+                ]
+            }
+        ])
+    );
+    assert_eq!(
+        UnifiedDiff::new(b"same\n", b"same\n", Options::default()).hunks(3),
+        Outcome::Text(vec![])
+    );
+}
+
+#[test]
+fn patches_match_git_byte_for_byte() -> crate::Result {
+    let old: String = (1..=40)
+        .map(|n| {
+            if n % 10 == 1 {
+                format!("fn function_{}() {{\n", n)
+            } else {
+                format!("    {}\n", n)
+            }
+        })
+        .collect();
+    let new = old
+        .replace("    5\n", "    5 changed\n")
+        .replace("    18\n", "")
+        .replace("    39\n", "    39\n    39.5\n");
+    assert_matches_git(old.as_bytes(), new.as_bytes())?;
+    assert_matches_git(b"", b"added\n")?;
+    assert_matches_git(b"removed\n", b"")?;
+    assert_matches_git(b"a\nb", b"a\nc")?;
+    assert_matches_git(
+        format!("{}\n    1\n    2\n    3\n    4\n", "x".repeat(100)).as_bytes(),
+        format!("{}\n    1\n    2\n    3\n    4 changed\n", "x".repeat(100)).as_bytes(),
+    )
+}
+
+#[test]
+fn function_lines_are_trimmed_and_limited_in_length() -> crate::Result {
+    let lines: String = (1..=9).map(|n| format!("{}\n", n)).collect();
+    let old = format!("_start \t\n{}$var{}\n{}", lines, "x".repeat(100), lines);
+    let new = old.replace("8\n", "eight\n");
+    let patch = write_patch(old.as_bytes(), new.as_bytes(), Options::default(), 3)?;
+    assert!(patch.contains("@@ _start\n"), "{}", patch);
+    assert!(patch.contains(&format!("@@ $var{}\n", "x".repeat(76))), "{}", patch);
+    assert_matches_git(old.as_bytes(), new.as_bytes())
+}
+
+#[test]
+fn identical_blobs_produce_no_patch() -> crate::Result {
+    assert_eq!(write_patch(b"a\n", b"a\n", Options::default(), 3)?, "");
+    assert_eq!(write_patch(b"\0", b"\0", Options::default(), 3)?, "");
+    Ok(())
+}
+
+#[test]
+fn binary_blobs_are_mentioned_only() -> crate::Result {
+    assert_eq!(
+        write_patch(b"a\0b", b"text\n", Options::default(), 3)?,
+        "Binary files a/old and b/new differ\n"
+    );
+    assert_matches_git(b"a\0b", b"text\n")
+}
+
+#[test]
+fn crlf_can_be_normalized() -> crate::Result {
+    let old = b"a\r\nb\r\nc\r\n";
+    let new = b"a\nB\nc\n";
+    let normalize = Options {
+        normalize_crlf: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        write_patch(old, new, normalize, 3)?,
+        git_patch(b"a\nb\nc\n", new, Algorithm::Myers, 3)?,
+        "only the actual change remains"
+    );
+    assert_eq!(
+        write_patch(old, new, Options::default(), 3)?,
+        git_patch(old, new, Algorithm::Myers, 3)?,
+        "without normalization, all lines differ"
+    );
+    Ok(())
+}
+
+#[test]
+fn blobs_can_be_read_from_streams() -> crate::Result {
+    let diff = UnifiedDiff::from_read(
+        &b"a\r\n"[..],
+        std::io::Cursor::new(b"a\nb\n"),
+        Options {
+            normalize_crlf: true,
+            ..Default::default()
+        },
+    )?;
+    let mut out = Vec::new();
+    diff.write_patch(&mut out, "a/file".into(), "b/file".into(), 3)?;
+    assert_eq!(out, b"--- a/file\n+++ b/file\n@@ -1 +1,2 @@\n a\n+b\n");
+    Ok(())
+}
+
+mod function_driver {
+    use git_diff::blob::{function::Driver, unified::Options, Algorithm};
+
+    use super::{git_patch_with_driver, write_patch};
+
+    /// Lines which may or may not start a function, depending on the driver.
+    const CANDIDATES: &[&str] = &[
+        "fn main() {",
+        "pub fn public() {",
+        "pub(crate) fn restricted() {",
+        "pub(in crate::module) struct Restricted {",
+        "pub (crate) fn spaced_restriction() {",
+        "    pub async fn indented() {",
+        "const fn constant() -> u8 {",
+        "unsafe impl Send for Type {}",
+        "extern \"C\" fn with_abi() {",
+        "pub extern  \"system\"  fn spaced_abi() {",
+        "extern fn without_abi() {",
+        "pub async unsafe fn two_qualifiers() {",
+        "impl<T> Trait for T {",
+        "trait Trait: Sized {",
+        "enum Enum {",
+        "union Union {",
+        "mod module {",
+        "macro_rules! mac {",
+        "fn declaration();",
+        "struct Unit;",
+        "pub const CONSTANT: u32 = 1;",
+        "pubfn missing_space() {",
+        "fnord()",
+        "int main(int argc, char **argv)",
+        "::global_function()",
+        ":: spaced_global_function()",
+        "std::string Type::name() const",
+        "  public:",
+        "label:",
+        "label: // comment",
+        "default: /* comment */",
+        "case 1: return 2;",
+        "class Widget {",
+        "#define MACRO 1",
+        "$dollar",
+        "_underscore()",
+        "class Class:",
+        "    def method(self):",
+        "async def coroutine():",
+        "  async \tdef spaced():",
+        "def\tfunction():",
+        "define = 1",
+        "classic = 2",
+        "async def",
+        "\tclass Indented(Base):",
+    ];
+
+    fn old_and_new() -> (String, String) {
+        let mut long_function = format!("    pub fn {}() {{", "long".repeat(30));
+        long_function.push_str("   ");
+        let filler = "    filler\n".repeat(8);
+        let (mut old, mut new) = (String::new(), String::new());
+        for (idx, candidate) in CANDIDATES
+            .iter()
+            .copied()
+            .chain(Some(long_function.as_str()))
+            .enumerate()
+        {
+            old.push_str(&format!("{}\n{}    change {}\n", candidate, filler, idx));
+            new.push_str(&format!("{}\n{}    changed {}\n", candidate, filler, idx));
+        }
+        (old, new)
+    }
+
+    #[test]
+    fn hunk_headers_match_git_for_each_driver() -> crate::Result {
+        let (old, new) = old_and_new();
+        let mut patches = Vec::new();
+        for (driver, name) in [
+            (Driver::Default, None),
+            (Driver::Rust, Some("rust")),
+            (Driver::Cpp, Some("cpp")),
+            (Driver::Python, Some("python")),
+        ] {
+            for context_lines in [0, 1, 3] {
+                let options = Options {
+                    function_driver: driver,
+                    ..Default::default()
+                };
+                let patch = write_patch(old.as_bytes(), new.as_bytes(), options, context_lines)?;
+                assert_eq!(
+                    patch,
+                    git_patch_with_driver(old.as_bytes(), new.as_bytes(), Algorithm::Myers, context_lines, name)?,
+                    "{:?} with {} lines of context",
+                    driver,
+                    context_lines
+                );
+                patches.push(patch);
+            }
+        }
+        patches.sort();
+        patches.dedup();
+        assert_eq!(patches.len(), 4 * 3, "each driver finds different function lines");
+        Ok(())
+    }
+
+    #[test]
+    fn drivers_pick_their_own_kind_of_function_lines() {
+        for (driver, line, expected) in [
+            (Driver::Default, "fn main() {\n", Some("fn main() {")),
+            (Driver::Default, "    fn indented() {\n", None),
+            (Driver::Rust, "    pub(crate) fn f() {\r\n", Some("pub(crate) fn f() {")),
+            (Driver::Rust, "fn declaration();\n", None),
+            (Driver::Rust, "let x = 1;\n", None),
+            (Driver::Cpp, "int main()\n", Some("int main()")),
+            (Driver::Cpp, "  public:\n", None),
+            (Driver::Cpp, "    indented();\n", None),
+            (Driver::Python, "    async def f():\n", Some("async def f():")),
+            (Driver::Python, "define = 1\n", None),
+        ] {
+            assert_eq!(
+                driver.function_line(line.as_bytes()),
+                expected.map(str::as_bytes),
+                "{:?}: {:?}",
+                driver,
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn drivers_are_found_by_the_name_used_in_the_diff_attribute() {
+        assert_eq!(Driver::from_name("rust".into()), Some(Driver::Rust));
+        assert_eq!(Driver::from_name("cpp".into()), Some(Driver::Cpp));
+        assert_eq!(Driver::from_name("python".into()), Some(Driver::Python));
+        assert_eq!(Driver::from_name("java".into()), None, "not yet built-in");
+        assert_eq!(Driver::default(), Driver::Default);
+    }
+}
@@ -2,4 +2,6 @@ pub type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 pub use git_testtools::hex_to_id;
 
+mod blob;
+mod patch;
 mod visit;
@@ -0,0 +1,46 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+git config core.autocrlf false
+
+git checkout -q -b main
+
+mkdir -p dir/nested
+seq 1 30 | sed 's/^/line /' > dir/nested/text
+{ echo "fn first() {"; seq 1 10 | sed 's/^/    a/'; echo "}"; echo "fn second() {"; seq 1 10 | sed 's/^/    b/'; echo "}"; } > code
+echo "to be deleted" > deleted
+echo "mode only" > mode-only
+echo "mode and content" > mode-and-content
+printf 'no newline' > no-newline
+printf 'binary\0content' > binary
+echo "becomes a directory" > file-to-dir
+mkdir dir-to-file
+echo "in directory" > dir-to-file/file
+echo "target" > link-target
+ln -s link-target link-to-file
+git add .
+git commit -qm c1
+git tag c1
+
+sed -i 's/^line 5$/line five/; s/^line 27$/line twenty-seven/' dir/nested/text
+sed -i 's/^    a5$/    a5 changed/; s/^    b9$/    b9 changed/' code
+git rm -q deleted
+chmod +x mode-only mode-and-content
+echo "more content" >> mode-and-content
+printf 'still no newline' > no-newline
+printf 'binary\0changed' > binary
+git rm -q file-to-dir
+mkdir file-to-dir
+echo "now in a directory" > file-to-dir/file
+git rm -qr dir-to-file
+echo "now a file" > dir-to-file
+rm link-to-file
+echo "now a file" > link-to-file
+touch empty
+printf 'added\r\nwith crlf\r\n' > added
+git add .
+git update-index --add --cacheinfo "160000,$(git rev-parse HEAD),submodule"
+git commit -qm c2
+git tag c2
@@ -0,0 +1,246 @@
+use std::path::Path;
+
+use git_diff::{blob::function::Driver, tree::patch};
+use git_hash::oid;
+use git_object::{
+    bstr::{BStr, ByteSlice},
+    TreeRefIter,
+};
+use git_odb::{linked, pack, Find};
+
+fn find_tree<'a>(db: &linked::Store, id: &oid, buf: &'a mut Vec<u8>) -> Option<TreeRefIter<'a>> {
+    db.try_find(id, buf, &mut pack::cache::Never)
+        .ok()
+        .flatten()
+        .and_then(|obj| obj.try_into_tree_iter())
+}
+
+fn tree_of_tag(repo: &Path, tag: &str) -> crate::Result<git_hash::ObjectId> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", &format!("{}^{{tree}}", tag)])
+        .current_dir(repo)
+        .output()?;
+    Ok(git_hash::ObjectId::from_hex(output.stdout.trim())?)
+}
+
+fn patch_between(repo: &Path, from: &str, to: &str, options: patch::Options) -> crate::Result<String> {
+    Ok(patch_between_with_missing_blobs(repo, from, to, options, |_| false)?.0)
+}
+
+fn patch_between_with_missing_blobs(
+    repo: &Path,
+    from: &str,
+    to: &str,
+    options: patch::Options,
+    is_missing: impl Fn(&oid) -> bool,
+) -> crate::Result<(String, patch::Outcome)> {
+    let driver = options.diff.function_driver;
+    patch_between_with_drivers(repo, from, to, options, is_missing, |_| driver)
+}
+
+fn patch_between_with_drivers(
+    repo: &Path,
+    from: &str,
+    to: &str,
+    options: patch::Options,
+    is_missing: impl Fn(&oid) -> bool,
+    function_driver: impl FnMut(&BStr) -> Driver,
+) -> crate::Result<(String, patch::Outcome)> {
+    let db = linked::Store::at(repo.join(".git").join("objects"))?;
+    let (from, to) = (tree_of_tag(repo, from)?, tree_of_tag(repo, to)?);
+    let (mut buf1, mut buf2) = (Vec::new(), Vec::new());
+    let mut recorder = git_diff::tree::Recorder::default();
+    git_diff::tree::Changes::from(find_tree(&db, &from, &mut buf1)).needed_to_obtain(
+        find_tree(&db, &to, &mut buf2).expect("tree present"),
+        git_diff::tree::State::default(),
+        |id, buf| find_tree(&db, id, buf),
+        &mut recorder,
+    )?;
+
+    let mut out = Vec::new();
+    let outcome = patch::write_with_drivers(
+        recorder.records,
+        |id, buf| {
+            if is_missing(id) {
+                return None;
+            }
+            db.try_find(id, buf, &mut pack::cache::Never)
+                .ok()
+                .flatten()
+                .and_then(|obj| obj.decode().ok())
+                .and_then(|obj| obj.into_blob())
+        },
+        function_driver,
+        options,
+        &mut out,
+    )?;
+    Ok((String::from_utf8(out)?, outcome))
+}
+
+fn git_patch(repo: &Path, from: &str, to: &str, extra_args: &[&str]) -> crate::Result<String> {
+    git_patch_with_config(repo, from, to, &[], extra_args)
+}
+
+fn git_patch_with_config(
+    repo: &Path,
+    from: &str,
+    to: &str,
+    config: &[&str],
+    extra_args: &[&str],
+) -> crate::Result<String> {
+    let output = std::process::Command::new("git")
+        .args(config.iter().flat_map(|config| ["-c", config]))
+        .args([
+            "diff-tree",
+            "-p",
+            "--full-index",
+            "--no-renames",
+            "--no-color",
+            "--no-ext-diff",
+            "--no-indent-heuristic",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+        ])
+        .args(extra_args)
+        .args([from, to])
+        .current_dir(repo)
+        .output()?;
+    assert!(output.status.success(), "{}", output.stderr.as_bstr());
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+#[test]
+fn all_kinds_of_changes_match_git_byte_for_byte() -> crate::Result {
+    let repo = git_testtools::scripted_fixture_repo_read_only("make_patch_repo.sh")?;
+    let actual = patch_between(&repo, "c1", "c2", Default::default())?;
+    assert_eq!(actual, git_patch(&repo, "c1", "c2", &[])?);
+    for needle in [
+        "Binary files a/binary and b/binary differ\n",
+        "old mode 100644\nnew mode 100755\ndiff --git",
+        "+Subproject commit ",
+        "deleted file mode 120000\n",
+    ] {
+        assert!(actual.contains(needle), "{:?} is part of the patch", needle);
+    }
+
+    assert_eq!(
+        patch_between(&repo, "c2", "c1", Default::default())?,
+        git_patch(&repo, "c2", "c1", &[])?,
+        "the reverse patch matches as well"
+    );
+    Ok(())
+}
+
+#[test]
+fn options_for_context_and_algorithm_are_respected() -> crate::Result {
+    let repo = git_testtools::scripted_fixture_repo_read_only("make_patch_repo.sh")?;
+    let options = patch::Options {
+        context_lines: 1,
+        diff: git_diff::blob::unified::Options {
+            algorithm: git_diff::blob::Algorithm::Histogram,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert_eq!(
+        patch_between(&repo, "c1", "c2", options)?,
+        git_patch(&repo, "c1", "c2", &["--unified=1", "--histogram"])?
+    );
+    Ok(())
+}
+
+#[test]
+fn function_drivers_can_be_chosen_per_path_like_with_the_diff_attribute() -> crate::Result {
+    let repo = git_testtools::scripted_fixture_repo_read_only("make_patch_repo.sh")?;
+    let attributes = git_testtools::tempfile::NamedTempFile::new()?;
+    std::fs::write(attributes.path(), "code diff=python\n")?;
+    let (actual, _) = patch_between_with_drivers(
+        &repo,
+        "c1",
+        "c2",
+        Default::default(),
+        |_| false,
+        |path| {
+            if path == "code" {
+                Driver::Python
+            } else {
+                Driver::Default
+            }
+        },
+    )?;
+    let expected = git_patch_with_config(
+        &repo,
+        "c1",
+        "c2",
+        &[&format!("core.attributesFile={}", attributes.path().display())],
+        &[],
+    )?;
+    assert_eq!(actual, expected);
+    assert_ne!(
+        expected,
+        git_patch(&repo, "c1", "c2", &[])?,
+        "the driver makes a difference"
+    );
+    Ok(())
+}
+
+#[test]
+fn missing_blobs_fail_by_default_or_are_skipped_and_reported() -> crate::Result {
+    let repo = git_testtools::scripted_fixture_repo_read_only("make_patch_repo.sh")?;
+    let missing = blob_id(&repo, "c2:code")?;
+    let is_missing = |id: &oid| id == missing;
+
+    match patch_between_with_missing_blobs(&repo, "c1", "c2", Default::default(), is_missing) {
+        Err(err) => assert_eq!(
+            err.to_string(),
+            format!("The blob {} could not be found in the object database", missing)
+        ),
+        Ok(_) => unreachable!("missing blobs are an error by default"),
+    }
+
+    let (actual, outcome) = patch_between_with_missing_blobs(
+        &repo,
+        "c1",
+        "c2",
+        patch::Options {
+            missing_blobs: patch::Missing::Skip,
+            ..Default::default()
+        },
+        is_missing,
+    )?;
+    assert_eq!(outcome.missing_blobs, vec![missing]);
+    let expected = git_patch(&repo, "c1", "c2", &[])?;
+    let code_header = format!(
+        "diff --git a/code b/code\nindex {}..{} 100644\n",
+        blob_id(&repo, "c1:code")?,
+        missing
+    );
+    assert!(
+        actual.contains(&format!("{}diff --git ", code_header)),
+        "only the header of the file with the missing blob is written"
+    );
+    assert!(expected.contains(&code_header));
+    assert_eq!(
+        actual.replace(&code_header, ""),
+        remove_file(&expected, "code"),
+        "all other files are written as usual"
+    );
+    Ok(())
+}
+
+fn blob_id(repo: &Path, spec: &str) -> crate::Result<git_hash::ObjectId> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", spec])
+        .current_dir(repo)
+        .output()?;
+    Ok(git_hash::ObjectId::from_hex(output.stdout.trim())?)
+}
+
+fn remove_file(patch: &str, path: &str) -> String {
+    let header = format!("diff --git a/{} b/{}\n", path, path);
+    let start = patch.find(&header).expect("file is part of the patch");
+    let end = patch[start + header.len()..]
+        .find("diff --git ")
+        .map_or(patch.len(), |pos| start + header.len() + pos);
+    format!("{}{}", &patch[..start], &patch[end..])
+}
@@ -606,4 +606,150 @@ mod changes {
             Ok(())
         }
     }
+
+    mod statistics {
+        use std::collections::BTreeMap;
+
+        use git_diff::tree::{recorder, Statistics};
+        use git_hash::{oid, ObjectId};
+        use git_object::{bstr::BString, tree, tree::EntryMode, TreeRefIter};
+        use git_odb::{loose, Write};
+
+        const FANOUT: usize = 10;
+        const DEPTH: usize = 3;
+
+        /// Write a tree with `FANOUT` blobs and, unless at the bottom, `FANOUT` subtrees per level, where only the blob
+        /// at `changed_leaf` differs between calls.
+        fn write_tree(db: &loose::Store, level: usize, changed_leaf: Option<&[usize]>) -> crate::Result<ObjectId> {
+            let mut entries = Vec::new();
+            for idx in 0..FANOUT {
+                let changed = changed_leaf.map_or(false, |path| path.len() == 1 && path[0] == idx);
+                let content = if changed {
+                    b"changed".to_vec()
+                } else {
+                    b"content".to_vec()
+                };
+                entries.push(tree::Entry {
+                    mode: EntryMode::Blob,
+                    filename: format!("blob{}", idx).into(),
+                    oid: db.write_buf(git_object::Kind::Blob, &content, git_hash::Kind::Sha1)?,
+                });
+                if level + 1 < DEPTH {
+                    let changed_leaf = changed_leaf
+                        .filter(|path| path.len() > 1 && path[0] == idx)
+                        .map(|path| &path[1..]);
+                    entries.push(tree::Entry {
+                        mode: EntryMode::Tree,
+                        filename: format!("tree{}", idx).into(),
+                        oid: write_tree(db, level + 1, changed_leaf)?,
+                    });
+                }
+            }
+            entries.sort();
+            Ok(db.write(&git_object::Tree { entries }, git_hash::Kind::Sha1)?)
+        }
+
+        fn find<'a>(db: &loose::Store, id: &oid, buf: &'a mut Vec<u8>) -> Option<TreeRefIter<'a>> {
+            db.try_find(id, buf)
+                .ok()
+                .flatten()
+                .and_then(|obj| obj.try_into_tree_iter())
+        }
+
+        fn all_entries(db: &loose::Store, root: &oid) -> crate::Result<BTreeMap<BString, (EntryMode, ObjectId)>> {
+            let mut buf = Vec::new();
+            let mut recorder = git_traverse::tree::Recorder::default();
+            git_traverse::tree::breadthfirst(
+                find(db, root, &mut buf).expect("root tree"),
+                git_traverse::tree::breadthfirst::State::default(),
+                |id, buf| find(db, id, buf),
+                &mut recorder,
+            )?;
+            Ok(recorder
+                .records
+                .into_iter()
+                .map(|e| (e.filepath, (e.mode, e.oid)))
+                .collect())
+        }
+
+        /// Compute the changes by comparing all entries of both trees, without skipping anything.
+        fn unoptimized_changes(db: &loose::Store, lhs: &oid, rhs: &oid) -> crate::Result<Vec<recorder::Change>> {
+            let (lhs, rhs) = (all_entries(db, lhs)?, all_entries(db, rhs)?);
+            let mut out = Vec::new();
+            for (path, (previous_entry_mode, previous_oid)) in &lhs {
+                match rhs.get(path) {
+                    Some((entry_mode, oid)) if (entry_mode, oid) != (previous_entry_mode, previous_oid) => {
+                        out.push(recorder::Change::Modification {
+                            previous_entry_mode: *previous_entry_mode,
+                            previous_oid: *previous_oid,
+                            entry_mode: *entry_mode,
+                            oid: *oid,
+                            path: path.clone(),
+                        })
+                    }
+                    Some(_) => {}
+                    None => out.push(recorder::Change::Deletion {
+                        entry_mode: *previous_entry_mode,
+                        oid: *previous_oid,
+                        path: path.clone(),
+                    }),
+                }
+            }
+            for (path, (entry_mode, oid)) in rhs.iter().filter(|(path, _)| !lhs.contains_key(*path)) {
+                out.push(recorder::Change::Addition {
+                    entry_mode: *entry_mode,
+                    oid: *oid,
+                    path: path.clone(),
+                });
+            }
+            Ok(out)
+        }
+
+        fn path_of(change: &recorder::Change) -> &BString {
+            match change {
+                recorder::Change::Addition { path, .. }
+                | recorder::Change::Deletion { path, .. }
+                | recorder::Change::Modification { path, .. } => path,
+            }
+        }
+
+        #[test]
+        fn equal_subtrees_are_skipped_and_only_differences_reach_the_delegate() -> crate::Result {
+            let dir = tempfile::tempdir()?;
+            let db = loose::Store::at(dir.path());
+            let lhs = write_tree(&db, 0, None)?;
+            let rhs = write_tree(&db, 0, Some(&[4, 7, 2]))?;
+
+            let (mut buf1, mut buf2) = (Vec::new(), Vec::new());
+            let mut state = git_diff::tree::State::default();
+            let mut recorder = git_diff::tree::Recorder::default();
+            git_diff::tree::Changes::from(find(&db, &lhs, &mut buf1)).needed_to_obtain(
+                find(&db, &rhs, &mut buf2).expect("rhs tree"),
+                &mut state,
+                |id, buf| find(&db, id, buf),
+                &mut recorder,
+            )?;
+
+            assert_eq!(
+                state.statistics(),
+                Statistics {
+                    entries_compared: 2 * FANOUT * (DEPTH - 1) + FANOUT,
+                    subtrees_skipped: (FANOUT - 1) * (DEPTH - 1),
+                },
+                "only the entries of the trees along the path to the changed leaf are compared"
+            );
+            assert_eq!(
+                recorder.records.iter().map(path_of).collect::<Vec<_>>(),
+                vec!["tree4", "tree4/tree7", "tree4/tree7/blob2"]
+            );
+
+            let mut expected = unoptimized_changes(&db, &lhs, &rhs)?;
+            expected.sort_by(|a, b| path_of(a).cmp(path_of(b)));
+            assert_eq!(
+                recorder.records, expected,
+                "the same changes as seen when comparing all entries"
+            );
+            Ok(())
+        }
+    }
 }
@@ -1,4 +1,379 @@
-//!A unidirectional pipe for bytes, analogous to a unix pipe. Available with the `io-pipe` feature toggle.
+//! Adaptors for readers and writers to count, hash or report progress on the bytes passing through them, along with a pipe for bytes.
+//!
+//! All adaptors pass vectored reads and writes through to the inner implementation and can be nested in any order,
+//! like `HashingWrite<CountedWrite<W>>` or `CountedRead<ProgressRead<R, P>>`.
+pub use counted::{CountedRead, CountedWrite};
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1"))]
+pub use hashing::{HashingRead, HashingWrite};
+#[cfg(feature = "progress")]
+pub use progress::{ProgressRead, ProgressWrite};
+
+mod counted {
+    use std::io;
+
+    /// A reader which counts all bytes read from its `inner` reader.
+    pub struct CountedRead<R> {
+        /// The reader to count bytes for.
+        pub inner: R,
+        /// The amount of bytes read so far, including those consumed via [`BufRead`][io::BufRead].
+        pub count: u64,
+    }
+
+    impl<R> CountedRead<R> {
+        /// Count all bytes read from `inner`.
+        pub fn new(inner: R) -> Self {
+            CountedRead { inner, count: 0 }
+        }
+    }
+
+    impl<R> io::Read for CountedRead<R>
+    where
+        R: io::Read,
+    {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let bytes_read = self.inner.read(buf)?;
+            self.count += bytes_read as u64;
+            Ok(bytes_read)
+        }
+
+        fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+            let bytes_read = self.inner.read_vectored(bufs)?;
+            self.count += bytes_read as u64;
+            Ok(bytes_read)
+        }
+    }
+
+    impl<R> io::BufRead for CountedRead<R>
+    where
+        R: io::BufRead,
+    {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            self.inner.fill_buf()
+        }
+
+        fn consume(&mut self, amt: usize) {
+            self.count += amt as u64;
+            self.inner.consume(amt)
+        }
+    }
+
+    /// A writer which counts all bytes written to its `inner` writer.
+    pub struct CountedWrite<W> {
+        /// The writer to count bytes for.
+        pub inner: W,
+        /// The amount of bytes written so far.
+        pub count: u64,
+    }
+
+    impl<W> CountedWrite<W> {
+        /// Count all bytes written to `inner`.
+        pub fn new(inner: W) -> Self {
+            CountedWrite { inner, count: 0 }
+        }
+    }
+
+    impl<W> io::Write for CountedWrite<W>
+    where
+        W: io::Write,
+    {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let written = self.inner.write(buf)?;
+            self.count += written as u64;
+            Ok(written)
+        }
+
+        fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+            let written = self.inner.write_vectored(bufs)?;
+            self.count += written as u64;
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.inner.flush()
+        }
+    }
+}
+
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1"))]
+mod hashing {
+    use std::io;
+
+    use crate::hash::Sha1;
+
+    /// Update `hash` with the first `len` bytes stored in `bufs`.
+    fn update_vectored<'a>(hash: &mut Sha1, bufs: impl IntoIterator<Item = &'a [u8]>, mut len: usize) {
+        for buf in bufs {
+            if len == 0 {
+                break;
+            }
+            let buf = &buf[..len.min(buf.len())];
+            hash.update(buf);
+            len -= buf.len();
+        }
+    }
+
+    /// A reader which hashes all bytes read from its `inner` reader.
+    pub struct HashingRead<R> {
+        /// The hash implementation.
+        pub hash: Sha1,
+        /// The inner reader.
+        pub inner: R,
+    }
+
+    impl<R> HashingRead<R> {
+        /// Create a new hash reader which hashes all bytes read from `inner` with a hash of `kind`.
+        pub fn new(inner: R, kind: git_hash::Kind) -> Self {
+            HashingRead {
+                hash: crate::hash::hasher(kind),
+                inner,
+            }
+        }
+    }
+
+    impl<R> io::Read for HashingRead<R>
+    where
+        R: io::Read,
+    {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let bytes_read = self.inner.read(buf)?;
+            self.hash.update(&buf[..bytes_read]);
+            Ok(bytes_read)
+        }
+
+        fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+            let bytes_read = self.inner.read_vectored(bufs)?;
+            update_vectored(&mut self.hash, bufs.iter().map(|buf| &**buf), bytes_read);
+            Ok(bytes_read)
+        }
+    }
+
+    impl<R> io::BufRead for HashingRead<R>
+    where
+        R: io::BufRead,
+    {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            self.inner.fill_buf()
+        }
+
+        /// Hash the consumed bytes, which are still available in the buffer of our `inner` reader.
+        fn consume(&mut self, amt: usize) {
+            if let Ok(buf) = self.inner.fill_buf() {
+                self.hash.update(&buf[..amt.min(buf.len())]);
+            }
+            self.inner.consume(amt)
+        }
+    }
+
+    /// A utility to automatically generate a hash while writing into an inner writer.
+    pub struct HashingWrite<W> {
+        /// The hash implementation.
+        pub hash: Sha1,
+        /// The inner writer.
+        pub inner: W,
+    }
+
+    impl<W> HashingWrite<W> {
+        /// Create a new hash writer which hashes all bytes written to `inner` with a hash of `kind`.
+        pub fn new(inner: W, kind: git_hash::Kind) -> Self {
+            HashingWrite {
+                hash: crate::hash::hasher(kind),
+                inner,
+            }
+        }
+    }
+
+    impl<W> io::Write for HashingWrite<W>
+    where
+        W: io::Write,
+    {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let written = self.inner.write(buf)?;
+            self.hash.update(&buf[..written]);
+            Ok(written)
+        }
+
+        fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+            let written = self.inner.write_vectored(bufs)?;
+            update_vectored(&mut self.hash, bufs.iter().map(|buf| &**buf), written);
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.inner.flush()
+        }
+    }
+}
+
+#[cfg(feature = "progress")]
+mod progress {
+    use std::io;
+
+    use crate::progress::Progress;
+
+    /// Accumulate `bytes` and tick `progress` once at least `chunk_size` bytes were seen.
+    fn tick(progress: &mut impl Progress, pending: &mut usize, chunk_size: usize, bytes: usize) {
+        *pending += bytes;
+        if *pending != 0 && *pending >= chunk_size {
+            progress.inc_by(*pending);
+            *pending = 0;
+        }
+    }
+
+    /// A reader which increments its `progress` by the amount of bytes read from its `inner` reader.
+    ///
+    /// By default, `progress` is incremented on each read, use [`with_chunk_size()`][ProgressRead::with_chunk_size()] to
+    /// increment it less often. Bytes not yet accounted for are added to `progress` when dropped.
+    pub struct ProgressRead<R, P>
+    where
+        P: Progress,
+    {
+        /// The reader whose bytes are counted.
+        pub inner: R,
+        /// The progress instance to increment.
+        pub progress: P,
+        chunk_size: usize,
+        pending: usize,
+    }
+
+    impl<R, P> ProgressRead<R, P>
+    where
+        P: Progress,
+    {
+        /// Increment `progress` by each amount of bytes read from `inner`.
+        pub fn new(inner: R, progress: P) -> Self {
+            ProgressRead {
+                inner,
+                progress,
+                chunk_size: 0,
+                pending: 0,
+            }
+        }
+
+        /// Increment the progress only once at least `bytes` were read since the last increment.
+        pub fn with_chunk_size(mut self, bytes: usize) -> Self {
+            self.chunk_size = bytes;
+            self
+        }
+    }
+
+    impl<R, P> io::Read for ProgressRead<R, P>
+    where
+        R: io::Read,
+        P: Progress,
+    {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let bytes_read = self.inner.read(buf)?;
+            tick(&mut self.progress, &mut self.pending, self.chunk_size, bytes_read);
+            Ok(bytes_read)
+        }
+
+        fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+            let bytes_read = self.inner.read_vectored(bufs)?;
+            tick(&mut self.progress, &mut self.pending, self.chunk_size, bytes_read);
+            Ok(bytes_read)
+        }
+    }
+
+    impl<R, P> io::BufRead for ProgressRead<R, P>
+    where
+        R: io::BufRead,
+        P: Progress,
+    {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            self.inner.fill_buf()
+        }
+
+        fn consume(&mut self, amt: usize) {
+            tick(&mut self.progress, &mut self.pending, self.chunk_size, amt);
+            self.inner.consume(amt)
+        }
+    }
+
+    impl<R, P> Drop for ProgressRead<R, P>
+    where
+        P: Progress,
+    {
+        fn drop(&mut self) {
+            if self.pending != 0 {
+                self.progress.inc_by(self.pending);
+            }
+        }
+    }
+
+    /// A writer which increments its `progress` by the amount of bytes written to its `inner` writer.
+    ///
+    /// By default, `progress` is incremented on each write, use [`with_chunk_size()`][ProgressWrite::with_chunk_size()] to
+    /// increment it less often. Bytes not yet accounted for are added to `progress` when flushing or when dropped.
+    pub struct ProgressWrite<W, P>
+    where
+        P: Progress,
+    {
+        /// The writer whose bytes are counted.
+        pub inner: W,
+        /// The progress instance to increment.
+        pub progress: P,
+        chunk_size: usize,
+        pending: usize,
+    }
+
+    impl<W, P> ProgressWrite<W, P>
+    where
+        P: Progress,
+    {
+        /// Increment `progress` by each amount of bytes written to `inner`.
+        pub fn new(inner: W, progress: P) -> Self {
+            ProgressWrite {
+                inner,
+                progress,
+                chunk_size: 0,
+                pending: 0,
+            }
+        }
+
+        /// Increment the progress only once at least `bytes` were written since the last increment.
+        pub fn with_chunk_size(mut self, bytes: usize) -> Self {
+            self.chunk_size = bytes;
+            self
+        }
+    }
+
+    impl<W, P> io::Write for ProgressWrite<W, P>
+    where
+        W: io::Write,
+        P: Progress,
+    {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let written = self.inner.write(buf)?;
+            tick(&mut self.progress, &mut self.pending, self.chunk_size, written);
+            Ok(written)
+        }
+
+        fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+            let written = self.inner.write_vectored(bufs)?;
+            tick(&mut self.progress, &mut self.pending, self.chunk_size, written);
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            if self.pending != 0 {
+                self.progress.inc_by(self.pending);
+                self.pending = 0;
+            }
+            self.inner.flush()
+        }
+    }
+
+    impl<W, P> Drop for ProgressWrite<W, P>
+    where
+        P: Progress,
+    {
+        fn drop(&mut self) {
+            if self.pending != 0 {
+                self.progress.inc_by(self.pending);
+            }
+        }
+    }
+}
 
 /// A unidirectional pipe for bytes, analogous to a unix pipe. Available with the `io-pipe` feature toggle.
 #[cfg(feature = "io-pipe")]
@@ -14,7 +14,6 @@ pub mod cache;
 pub mod fs;
 pub mod hash;
 pub mod interrupt;
-#[cfg(feature = "io-pipe")]
 pub mod io;
 pub mod parallel;
 #[cfg(feature = "progress")]
@@ -1,4 +1,4 @@
-use crate::parallel::{num_threads, Reduce};
+use crate::parallel::{num_threads, serial, Reduce};
 
 /// Runs `left` and `right` in parallel, returning their output when both are done.
 pub fn join<O1: Send, O2: Send>(left: impl FnOnce() -> O1 + Send, right: impl FnOnce() -> O2 + Send) -> (O1, O2) {
@@ -15,6 +15,7 @@ pub fn join<O1: Send, O2: Send>(left: impl FnOnce() -> O1 + Send, right: impl Fn
 /// aggregate these outputs into the final result returned by this function with the benefit of not having to be thread-safe.
 ///
 /// * if `thread_limit` is `Some`, the given amount of threads will be used. If `None`, all logical cores will be used.
+///   If it's `Some(1)`, no thread is spawned and all work is done in the calling thread.
 /// * `new_thread_state(thread_number) -> State` produces thread-local state once per thread to be based to `consume`
 /// * `consume(Item, &mut State) -> Output` produces an output given an input obtained by `input` along with mutable state initially
 ///   created by `new_thread_state(…)`.
@@ -32,6 +33,9 @@ where
     O: Send,
 {
     let num_threads = num_threads(thread_limit);
+    if num_threads == 1 {
+        return serial::in_parallel(input, thread_limit, new_thread_state, consume, reducer);
+    }
     let new_thread_state = &new_thread_state;
     let consume = &consume;
     crossbeam_utils::thread::scope(move |s| {
@@ -126,6 +126,27 @@ fn num_threads(thread_limit: Option<usize>) -> usize {
         .unwrap_or_else(logical_cores)
 }
 
+/// Return true if `thread_limit` allows only a single thread, in which case no threads should be spawned at all.
+///
+/// This is the case if it's `Some(1)`, or always if the `parallel` feature toggle is unset.
+pub fn is_single_threaded(thread_limit: Option<usize>) -> bool {
+    num_threads(thread_limit) == 1
+}
+
+/// Run `left` and `right` with [`join()`] unless `thread_limit` [allows only one thread][is_single_threaded()], in which case
+/// they run one after another in the calling thread.
+pub fn join_with_thread_limit<O1: Send, O2: Send>(
+    thread_limit: Option<usize>,
+    left: impl FnOnce() -> O1 + Send,
+    right: impl FnOnce() -> O2 + Send,
+) -> (O1, O2) {
+    if is_single_threaded(thread_limit) {
+        (left(), right())
+    } else {
+        join(left, right)
+    }
+}
+
 /// Run [`in_parallel()`] only if the given `condition()` returns true when eagerly evaluated.
 ///
 /// For parameters, see the documentation of [`in_parallel()`]
@@ -2,20 +2,26 @@
 mod stepped {
     use crate::parallel::num_threads;
 
+    /// Produces the next output using the given state, or `None` if the input is depleted.
+    type NextFn<O, State> = Box<dyn FnMut(&mut State) -> Option<O> + Send>;
+
     /// An iterator adaptor to allow running computations using [`in_parallel()`][crate::parallel::in_parallel()] in a step-wise manner, see the [module docs][crate::parallel]
     /// for details.
-    pub struct Stepwise<Reduce: super::Reduce> {
+    pub struct Stepwise<Reduce: super::Reduce, State> {
         /// This field is first to assure it's dropped first and cause threads that are dropped next to stop their loops
         /// as sending results fails when the receiver is dropped.
         receive_result: std::sync::mpsc::Receiver<Reduce::Input>,
         /// `join()` will be called on these guards to assure every thread tries to send through a closed channel. When
         /// that happens, they break out of their loops.
         _threads: Vec<std::thread::JoinHandle<()>>,
+        /// If only a single thread is allowed, no threads are spawned and results are produced with the given state in the thread
+        /// using the iterator instead.
+        serial: Option<(State, NextFn<Reduce::Input, State>)>,
         /// The reducer is called only in the thread using the iterator, dropping it has no side effects.
         reducer: Option<Reduce>,
     }
 
-    impl<Reduce: super::Reduce> Drop for Stepwise<Reduce> {
+    impl<Reduce: super::Reduce, State> Drop for Stepwise<Reduce, State> {
         fn drop(&mut self) {
             let (_, sink) = std::sync::mpsc::channel();
             drop(std::mem::replace(&mut self.receive_result, sink));
@@ -32,10 +38,11 @@ mod stepped {
         }
     }
 
-    impl<Reduce: super::Reduce> Stepwise<Reduce> {
-        /// Instantiate a new iterator and start working in threads.
+    impl<Reduce: super::Reduce, S> Stepwise<Reduce, S> {
+        /// Instantiate a new iterator and start working in threads, unless `thread_limit` allows only a single thread in which case
+        /// all work is done lazily in the thread calling `next()`.
         /// For a description of parameters, see [`in_parallel()`][crate::parallel::in_parallel()].
-        pub fn new<InputIter, ThreadStateFn, ConsumeFn, I, O, S>(
+        pub fn new<InputIter, ThreadStateFn, ConsumeFn, I, O>(
             input: InputIter,
             thread_limit: Option<usize>,
             new_thread_state: ThreadStateFn,
@@ -51,6 +58,18 @@ mod stepped {
             O: Send + 'static,
         {
             let num_threads = num_threads(thread_limit);
+            if num_threads == 1 {
+                let mut input = input;
+                return Stepwise {
+                    receive_result: std::sync::mpsc::channel().1,
+                    _threads: Vec::new(),
+                    serial: Some((
+                        new_thread_state(0),
+                        Box::new(move |state: &mut S| input.next().map(|item| consume(item, state))),
+                    )),
+                    reducer: Some(reducer),
+                };
+            }
             let mut threads = Vec::with_capacity(num_threads + 1);
             let receive_result = {
                 let (send_input, receive_input) = crossbeam_channel::bounded::<I>(num_threads);
@@ -84,6 +103,7 @@ mod stepped {
             Stepwise {
                 _threads: threads,
                 receive_result,
+                serial: None,
                 reducer: Some(reducer),
             }
         }
@@ -100,18 +120,19 @@ mod stepped {
         }
     }
 
-    impl<Reduce: super::Reduce> Iterator for Stepwise<Reduce> {
+    impl<Reduce: super::Reduce, State> Iterator for Stepwise<Reduce, State> {
         type Item = Result<Reduce::FeedProduce, Reduce::Error>;
 
         fn next(&mut self) -> Option<<Self as Iterator>::Item> {
-            self.receive_result
-                .recv()
-                .ok()
-                .and_then(|input| self.reducer.as_mut().map(|r| r.feed(input)))
+            match self.serial.as_mut() {
+                Some((state, next)) => next(state),
+                None => self.receive_result.recv().ok(),
+            }
+            .and_then(|input| self.reducer.as_mut().map(|r| r.feed(input)))
         }
     }
 
-    impl<R: super::Reduce> super::Finalize for Stepwise<R> {
+    impl<R: super::Reduce, State> super::Finalize for Stepwise<R, State> {
         type Reduce = R;
 
         fn finalize(
@@ -0,0 +1,245 @@
+use std::{
+    io::{BufRead, IoSlice, IoSliceMut, Read, Write},
+    sync::{Arc, Mutex},
+};
+
+use git_features::{
+    hash::Sha1,
+    io::{CountedRead, CountedWrite, HashingRead, HashingWrite, ProgressRead, ProgressWrite},
+    progress::{MessageLevel, Progress, Unit},
+};
+
+const HELLO_WORLD_SHA1: &str = "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed";
+
+fn hex(digest: [u8; 20]) -> String {
+    git_hash::ObjectId::from(digest).to_string()
+}
+
+/// A progress implementation recording each increment.
+#[derive(Default, Clone)]
+struct Increments(Arc<Mutex<Vec<usize>>>);
+
+impl Increments {
+    fn recorded(&self) -> Vec<usize> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl Progress for Increments {
+    type SubProgress = Self;
+
+    fn add_child(&mut self, _name: impl Into<String>) -> Self::SubProgress {
+        self.clone()
+    }
+    fn init(&mut self, _max: Option<usize>, _unit: Option<Unit>) {}
+    fn set(&mut self, _step: usize) {}
+    fn step(&self) -> usize {
+        self.0.lock().unwrap().iter().sum()
+    }
+    fn inc_by(&mut self, step: usize) {
+        self.0.lock().unwrap().push(step)
+    }
+    fn set_name(&mut self, _name: impl Into<String>) {}
+    fn name(&self) -> Option<String> {
+        None
+    }
+    fn message(&mut self, _level: MessageLevel, _message: impl Into<String>) {}
+}
+
+mod counted {
+    use super::*;
+
+    #[test]
+    fn read_counts_bytes_from_read_bufread_and_vectored_reads() {
+        let mut read = CountedRead::new(&b"hello world, and more"[..]);
+        let mut buf = [0u8; 5];
+        read.read_exact(&mut buf).unwrap();
+        assert_eq!(read.count, 5);
+
+        let (mut a, mut b) = ([0u8; 2], [0u8; 3]);
+        let bytes_read = read
+            .read_vectored(&mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)])
+            .unwrap();
+        assert_eq!(bytes_read, 5);
+        assert_eq!(read.count, 10);
+
+        let available = read.fill_buf().unwrap().len();
+        assert_eq!(read.count, 10, "filling the buffer doesn't count");
+        read.consume(available);
+        assert_eq!(read.count, 21);
+        assert_eq!(read.read(&mut buf).unwrap(), 0);
+        assert_eq!(read.count, 21);
+    }
+
+    #[test]
+    fn write_counts_bytes_from_write_and_vectored_writes() {
+        let mut write = CountedWrite::new(Vec::new());
+        write.write_all(b"hello").unwrap();
+        let written = write
+            .write_vectored(&[IoSlice::new(b" wor"), IoSlice::new(b"ld")])
+            .unwrap();
+        assert_eq!(written, 6);
+        assert_eq!(write.count, 11);
+        assert_eq!(write.inner, b"hello world");
+    }
+}
+
+mod hashing {
+    use super::*;
+
+    #[test]
+    fn read_hashes_bytes_from_read_bufread_and_vectored_reads() {
+        let mut read = HashingRead::new(&b"hello world"[..], git_hash::Kind::Sha1);
+        let mut buf = [0u8; 3];
+        read.read_exact(&mut buf).unwrap();
+
+        let (mut a, mut b) = ([0u8; 2], [0u8; 100]);
+        let bytes_read = read
+            .read_vectored(&mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)])
+            .unwrap();
+        assert_eq!(bytes_read, 8, "only the bytes that were read are hashed");
+        assert_eq!(hex(read.hash.digest()), HELLO_WORLD_SHA1);
+
+        let mut read = HashingRead::new(&b"hello world"[..], git_hash::Kind::Sha1);
+        read.consume(6);
+        let mut rest = String::new();
+        read.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "world");
+        assert_eq!(hex(read.hash.digest()), HELLO_WORLD_SHA1, "consumed bytes are hashed");
+    }
+
+    #[test]
+    fn write_hashes_bytes_from_write_and_vectored_writes() {
+        let mut write = HashingWrite::new(Vec::new(), git_hash::Kind::Sha1);
+        write.write_all(b"hel").unwrap();
+        let written = write
+            .write_vectored(&[IoSlice::new(b"lo "), IoSlice::new(b"world")])
+            .unwrap();
+        assert_eq!(written, 8);
+        assert_eq!(write.inner, b"hello world");
+        assert_eq!(hex(write.hash.digest()), HELLO_WORLD_SHA1);
+    }
+
+    #[test]
+    fn partial_writes_only_hash_what_was_written() {
+        let mut buf = [0u8; 5];
+        let mut write = HashingWrite::new(&mut buf[..], git_hash::Kind::Sha1);
+        assert_eq!(write.write(b"hello world").unwrap(), 5);
+        let mut expected = Sha1::default();
+        expected.update(b"hello");
+        assert_eq!(write.hash.digest(), expected.digest());
+    }
+}
+
+mod progress {
+    use super::*;
+
+    #[test]
+    fn read_increments_on_each_read_by_default() {
+        let progress = Increments::default();
+        let mut read = ProgressRead::new(&b"hello world"[..], progress.clone());
+        let mut buf = [0u8; 4];
+        while read.read(&mut buf).unwrap() != 0 {}
+        drop(read);
+        assert_eq!(progress.recorded(), vec![4, 4, 3]);
+    }
+
+    #[test]
+    fn read_increments_in_chunks_and_adds_the_rest_on_drop() {
+        let progress = Increments::default();
+        let mut read = ProgressRead::new(&b"hello world"[..], progress.clone()).with_chunk_size(5);
+        let mut buf = [0u8; 2];
+        read.read_exact(&mut buf).unwrap();
+        read.read_exact(&mut buf).unwrap();
+        assert!(progress.recorded().is_empty(), "4 bytes are less than a chunk");
+        read.read_exact(&mut buf).unwrap();
+        assert_eq!(progress.recorded(), vec![6]);
+        read.consume(3);
+        drop(read);
+        assert_eq!(progress.recorded(), vec![6, 3]);
+    }
+
+    #[test]
+    fn write_increments_in_chunks_and_adds_the_rest_on_flush() {
+        let progress = Increments::default();
+        let mut write = ProgressWrite::new(Vec::new(), progress.clone()).with_chunk_size(4);
+        write.write_all(b"hel").unwrap();
+        assert!(progress.recorded().is_empty(), "3 bytes are less than a chunk");
+        let written = write
+            .write_vectored(&[IoSlice::new(b"lo"), IoSlice::new(b" ")])
+            .unwrap();
+        assert_eq!(written, 3);
+        assert_eq!(progress.recorded(), vec![6]);
+        write.write_all(b"wor").unwrap();
+        write.flush().unwrap();
+        assert_eq!(progress.recorded(), vec![6, 3]);
+        write.write_all(b"ld").unwrap();
+        drop(write);
+        assert_eq!(progress.recorded(), vec![6, 3, 2]);
+    }
+}
+
+mod nesting {
+    use super::*;
+
+    #[test]
+    fn adaptors_can_be_nested_in_any_order() {
+        let progress = Increments::default();
+        let mut write = CountedWrite::new(HashingWrite::new(
+            ProgressWrite::new(Vec::new(), progress.clone()),
+            git_hash::Kind::Sha1,
+        ));
+        write.write_all(b"hello world").unwrap();
+        assert_eq!(write.count, 11);
+        assert_eq!(write.inner.inner.inner, b"hello world");
+        assert_eq!(progress.recorded(), vec![11]);
+        assert_eq!(hex(write.inner.hash.digest()), HELLO_WORLD_SHA1);
+
+        let progress = Increments::default();
+        let mut read = ProgressRead::new(
+            HashingRead::new(CountedRead::new(&b"hello world"[..]), git_hash::Kind::Sha1),
+            progress.clone(),
+        );
+        std::io::copy(&mut read, &mut std::io::sink()).unwrap();
+        assert_eq!(read.inner.inner.count, 11);
+        assert_eq!(hex(read.inner.hash.clone().digest()), HELLO_WORLD_SHA1);
+        drop(read);
+        assert_eq!(progress.step(), 11);
+    }
+
+    #[test]
+    fn nested_adaptors_see_the_same_bytes_as_doing_the_same_work_by_hand() {
+        let data: Vec<u8> = (0..4 * 1024 * 1024u32).map(|v| (v % 251) as u8).collect();
+        let mut buf = vec![0u8; 64 * 1024];
+
+        let expected = {
+            let mut progress = Increments::default();
+            let mut hash = Sha1::default();
+            let mut count = 0u64;
+            let mut read = &data[..];
+            loop {
+                let bytes_read = read.read(&mut buf).unwrap();
+                if bytes_read == 0 {
+                    break;
+                }
+                hash.update(&buf[..bytes_read]);
+                count += bytes_read as u64;
+                progress.inc_by(bytes_read);
+            }
+            assert_eq!(count, data.len() as u64);
+            hash.digest()
+        };
+
+        let actual = {
+            let mut read = HashingRead::new(
+                CountedRead::new(ProgressRead::new(&data[..], Increments::default())),
+                git_hash::Kind::Sha1,
+            );
+            while read.read(&mut buf).unwrap() != 0 {}
+            assert_eq!(read.inner.count, data.len() as u64);
+            read.hash.digest()
+        };
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -99,3 +99,53 @@ fn stepped_reduce_finalize() {
 
     assert_eq!(iter.finalize().expect("success"), 100);
 }
+
+#[test]
+fn a_single_thread_does_all_work_in_the_calling_thread() {
+    let caller = std::thread::current().id();
+    assert!(parallel::is_single_threaded(Some(1)));
+
+    let res = parallel::in_parallel(
+        std::iter::from_fn(|| Some(1)).take(100),
+        Some(1),
+        |_n| (),
+        |input, _state| {
+            assert_eq!(std::thread::current().id(), caller);
+            input
+        },
+        Adder::default(),
+    )
+    .expect("successful computation");
+    assert_eq!(res, 100);
+
+    let iter = parallel::reduce::Stepwise::new(
+        std::iter::from_fn(|| Some(1)).take(100),
+        Some(1),
+        |_n| (),
+        move |input, _state| {
+            assert_eq!(std::thread::current().id(), caller);
+            input
+        },
+        Adder::default(),
+    );
+    assert_eq!(iter.finalize().expect("success"), 100);
+
+    let iter = parallel::reduce::Stepwise::new(
+        std::iter::from_fn(|| Some(1)).take(100),
+        Some(1),
+        |_n| (),
+        |input, _state| input,
+        Adder::default(),
+    );
+    assert_eq!(
+        std::thread::spawn(move || iter.finalize().expect("success"))
+            .join()
+            .expect("no panic"),
+        100,
+        "the iterator can be sent to another thread even if no threads are used"
+    );
+
+    let (left, right) =
+        parallel::join_with_thread_limit(Some(1), || std::thread::current().id(), || std::thread::current().id());
+    assert_eq!((left, right), (caller, caller));
+}
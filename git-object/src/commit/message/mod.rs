@@ -9,6 +9,8 @@ use crate::{
 ///
 pub mod body;
 mod decode;
+///
+pub mod trailers;
 
 impl<'a> CommitRef<'a> {
     /// Return exactly the same message as [`MessageRef::summary()`].
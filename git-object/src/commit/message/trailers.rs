@@ -0,0 +1,207 @@
+use crate::{
+    bstr::{BStr, ByteSlice},
+    commit::{MessageTrailers, Trailer},
+};
+
+/// Prefixes of trailers generated by `git` itself, which allow the trailer block to contain other lines as well.
+const GIT_GENERATED_PREFIXES: &[&[u8]] = &[b"Signed-off-by: ", b"(cherry picked from commit "];
+/// The character starting comment lines, which are ignored.
+const COMMENT_CHAR: u8 = b'#';
+/// The line below which `git commit --cleanup=scissors` removes everything, preceded by a newline.
+const SCISSORS: &[u8] = b"\n# ------------------------ >8 ------------------------\n";
+
+/// An iterator over the trailers of a [`MessageTrailers`] instance.
+///
+/// Lines in the trailer block which aren't trailers themselves, like comments, are skipped.
+pub struct Iter<'a> {
+    block: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> MessageTrailers<'a> {
+    /// Find the trailers in the last paragraph of the commit `message`, following the rules of `git interpret-trailers`.
+    ///
+    /// * The title, i.e. the first paragraph, never contains trailers.
+    /// * A line starting with `---` followed by whitespace terminates the message, along with everything after it.
+    /// * Comment lines starting with `#` are ignored, and so are trailing `Conflicts:` listings and everything below
+    ///   a scissors line.
+    /// * Trailers are lines like `<token><separator><value>`, where the separator is `:` or `#`, optionally preceded
+    ///   by whitespace, and the token consists of alphanumeric characters and `-`. This matches `git` with
+    ///   `trailer.separators` set to `:#`.
+    /// * Lines starting with whitespace continue the value of the trailer before them, and are included in its value
+    ///   as they are.
+    /// * The last paragraph is only considered a trailer block if it consists of trailers and their continuation lines
+    ///   entirely, or if it contains a trailer generated by `git`, like `Signed-off-by: `, and at least 25% of its
+    ///   lines are trailers. Continuation lines of trailers are not counted in that case.
+    pub fn from_message(message: &'a BStr) -> Self {
+        MessageTrailers {
+            block: trailer_block(message).unwrap_or_default().as_bstr(),
+        }
+    }
+
+    /// Return an iterator over all trailers, in order of appearance.
+    pub fn iter(&self) -> Iter<'a> {
+        Iter {
+            block: self.block,
+            pos: 0,
+        }
+    }
+
+    /// Return true if there are no trailers.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+}
+
+impl<'a> IntoIterator for MessageTrailers<'a> {
+    type Item = Trailer<'a>;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = Trailer<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(line) = line_at(self.block, self.pos) {
+            let line_start = self.pos;
+            self.pos += line.len();
+            if let Some((token, value_offset)) = Some(line)
+                .filter(|line| !is_comment(line) && !is_continuation(line))
+                .and_then(split_trailer)
+            {
+                while let Some(continuation) = line_at(self.block, self.pos).filter(|l| is_continuation(l)) {
+                    self.pos += continuation.len();
+                }
+                return Some(Trailer {
+                    token,
+                    value: self.block[line_start + value_offset..self.pos].trim().as_bstr(),
+                });
+            }
+        }
+        None
+    }
+}
+
+/// Return the line starting at `pos` including its terminator, or `None` if there is no more line.
+fn line_at(buf: &[u8], pos: usize) -> Option<&[u8]> {
+    let rest = buf.get(pos..).filter(|rest| !rest.is_empty())?;
+    Some(rest.find_byte(b'\n').map_or(rest, |nl| &rest[..=nl]))
+}
+
+fn is_blank(line: &[u8]) -> bool {
+    line.trim().is_empty()
+}
+
+fn is_comment(line: &[u8]) -> bool {
+    line.first() == Some(&COMMENT_CHAR)
+}
+
+fn is_continuation(line: &[u8]) -> bool {
+    line.first().map_or(false, u8::is_ascii_whitespace)
+}
+
+fn is_divider(line: &[u8]) -> bool {
+    line.starts_with(b"---") && line.get(3).map_or(false, u8::is_ascii_whitespace)
+}
+
+/// Split `line` into its token and the offset at which its value starts, if it is a trailer.
+fn split_trailer(line: &[u8]) -> Option<(&BStr, usize)> {
+    let mut whitespace_found = false;
+    for (pos, byte) in line.iter().enumerate() {
+        match byte {
+            b':' | b'#' if pos != 0 => return Some((line[..pos].trim_end().as_bstr(), pos + 1)),
+            b if (b.is_ascii_alphanumeric() || *b == b'-') && !whitespace_found => {}
+            b' ' | b'\t' if pos != 0 => whitespace_found = true,
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Return an iterator over all lines in `buf` along with their offset, with line terminators included.
+fn lines_with_offset(buf: &[u8]) -> impl Iterator<Item = (usize, &[u8])> {
+    buf.lines_with_terminator().scan(0, |pos, line| {
+        let line_start = *pos;
+        *pos += line.len();
+        Some((line_start, line))
+    })
+}
+
+/// Return the offset at which the trailing comments, blank lines and conflict listings of `message` start, or where the
+/// scissors line cuts it off, like `ignore_non_trailer()` in `git`.
+fn non_trailer_start(message: &[u8]) -> usize {
+    let cutoff = if message.starts_with(&SCISSORS[1..]) {
+        0
+    } else {
+        message.find(SCISSORS).map_or(message.len(), |pos| pos + 1)
+    };
+    let (mut comments_start, mut in_conflicts_block) = (None, false);
+    for (pos, line) in lines_with_offset(&message[..cutoff]) {
+        if is_comment(line) || line == b"\n" {
+            comments_start.get_or_insert(pos);
+        } else if line == b"Conflicts:\n" {
+            in_conflicts_block = true;
+            comments_start.get_or_insert(pos);
+        } else if in_conflicts_block && line.first() == Some(&b'\t') {
+            // a path in the conflicts block
+        } else {
+            comments_start = None;
+            in_conflicts_block = false;
+        }
+    }
+    comments_start.unwrap_or(cutoff)
+}
+
+fn trailer_block(message: &[u8]) -> Option<&[u8]> {
+    let end = lines_with_offset(message)
+        .find(|(_, line)| is_divider(line))
+        .map_or(message.len(), |(pos, _)| pos);
+    let message = &message[..end];
+    let message = &message[..non_trailer_start(message)];
+
+    let end_of_title = lines_with_offset(message)
+        .filter(|(_, line)| !is_comment(line))
+        .find(|(_, line)| is_blank(line))
+        .map_or(message.len(), |(pos, _)| pos);
+    let body = &message[end_of_title..];
+
+    // Walk the lines backwards until the last blank line is reached, like `find_trailer_start()` in `git`.
+    let (mut trailer_lines, mut non_trailer_lines, mut possible_continuation_lines) = (0, 0, 0);
+    let (mut only_spaces, mut recognized_prefix) = (true, false);
+    for (pos, line) in lines_with_offset(body).collect::<Vec<_>>().into_iter().rev() {
+        if is_comment(line) {
+            non_trailer_lines += possible_continuation_lines;
+            possible_continuation_lines = 0;
+            continue;
+        }
+        if is_blank(line) {
+            if only_spaces {
+                continue;
+            }
+            non_trailer_lines += possible_continuation_lines;
+            let is_trailer_block = (recognized_prefix && trailer_lines * 3 >= non_trailer_lines)
+                || (trailer_lines > 0 && non_trailer_lines == 0);
+            return is_trailer_block.then(|| &body[pos + line.len()..]);
+        }
+        only_spaces = false;
+
+        if GIT_GENERATED_PREFIXES.iter().any(|prefix| line.starts_with(prefix)) {
+            trailer_lines += 1;
+            possible_continuation_lines = 0;
+            recognized_prefix = true;
+        } else if !is_continuation(line) && split_trailer(line).is_some() {
+            trailer_lines += 1;
+            possible_continuation_lines = 0;
+        } else if is_continuation(line) {
+            possible_continuation_lines += 1;
+        } else {
+            non_trailer_lines += 1 + possible_continuation_lines;
+            possible_continuation_lines = 0;
+        }
+    }
+    None
+}
@@ -21,10 +21,24 @@ pub struct MessageRef<'a> {
     pub body: Option<&'a BStr>,
 }
 
+/// The trailers of a commit message, found in its last paragraph as described in
+/// [git-interpret-trailers](https://git-scm.com/docs/git-interpret-trailers#_description).
+///
+/// Obtain it with [`MessageTrailers::from_message()`] and iterate the trailers with [`MessageTrailers::iter()`].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy, Default)]
+pub struct MessageTrailers<'a> {
+    /// The paragraph containing the trailers, or an empty slice if there is none.
+    block: &'a BStr,
+}
+
+/// A trailer as yielded by [`MessageTrailers::iter()`].
+pub type Trailer<'a> = message::body::TrailerRef<'a>;
+
 ///
 pub mod ref_iter;
 
-mod write;
+///
+pub mod write;
 
 impl<'a> CommitRef<'a> {
     /// Deserialize a commit from the given `data` bytes while avoiding most allocations.
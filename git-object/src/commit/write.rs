@@ -1,12 +1,62 @@
 use std::io;
 
-use bstr::ByteSlice;
+use bstr::{BString, ByteSlice};
+use quick_error::quick_error;
 
 use crate::{encode, encode::NL, Commit, CommitRef, Kind};
 
+quick_error! {
+    /// An Error used in [`Commit::write_to()`][crate::WriteTo::write_to()].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        InvalidSignature { header: &'static str } {
+            display("The {} name or email must not contain '<', '>' or \\n", header)
+        }
+        InvalidExtraHeaderName { name: BString } {
+            display("The extra header name {:?} must not be empty, contain spaces or newlines, or be the name of a standard header", name)
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        io::Error::new(io::ErrorKind::Other, err)
+    }
+}
+
+const STANDARD_HEADER_NAMES: &[&[u8]] = &[b"tree", b"parent", b"author", b"committer", b"encoding"];
+
+fn validate<'a>(
+    author: &git_actor::SignatureRef<'_>,
+    committer: &git_actor::SignatureRef<'_>,
+    mut extra_header_names: impl Iterator<Item = &'a [u8]>,
+) -> Result<(), Error> {
+    if !encode::is_valid_signature(author) {
+        return Err(Error::InvalidSignature { header: "author" });
+    }
+    if !encode::is_valid_signature(committer) {
+        return Err(Error::InvalidSignature { header: "committer" });
+    }
+    // Such names would be parsed as part of another header or value, or as a standard header, changing the commit.
+    if let Some(name) = extra_header_names
+        .find(|name| name.is_empty() || name.find_byteset(b" \n").is_some() || STANDARD_HEADER_NAMES.contains(name))
+    {
+        return Err(Error::InvalidExtraHeaderName { name: name.into() });
+    }
+    Ok(())
+}
+
 impl crate::WriteTo for Commit {
     /// Serializes this instance to `out` in the git serialization format.
+    ///
+    /// Nothing is written if the author or committer or the names of extra headers are invalid.
     fn write_to(&self, mut out: impl io::Write) -> io::Result<()> {
+        validate(
+            &self.author.to_ref(),
+            &self.committer.to_ref(),
+            self.extra_headers.iter().map(|(name, _)| name.as_slice()),
+        )?;
         encode::trusted_header_id(b"tree", &self.tree, &mut out)?;
         for parent in &self.parents {
             encode::trusted_header_id(b"parent", parent, &mut out)?;
@@ -35,7 +85,14 @@ impl crate::WriteTo for Commit {
 
 impl<'a> crate::WriteTo for CommitRef<'a> {
     /// Serializes this instance to `out` in the git serialization format.
+    ///
+    /// Nothing is written if the author or committer or the names of extra headers are invalid.
     fn write_to(&self, mut out: impl io::Write) -> io::Result<()> {
+        validate(
+            &self.author,
+            &self.committer,
+            self.extra_headers.iter().map(|(name, _)| name.as_bytes()),
+        )?;
         encode::trusted_header_id(b"tree", &self.tree(), &mut out)?;
         for parent in self.parents() {
             encode::trusted_header_id(b"parent", &parent, &mut out)?;
@@ -57,6 +57,13 @@ pub fn trusted_header_signature(
     out.write_all(NL)
 }
 
+/// Return true if `signature` can be written without corrupting the object, as its name and email
+/// mustn't contain the characters delimiting them or the header.
+pub fn is_valid_signature(signature: &git_actor::SignatureRef<'_>) -> bool {
+    let is_valid = |token: &bstr::BStr| token.find_byteset(b"<>\n").is_none();
+    is_valid(signature.name) && is_valid(signature.email)
+}
+
 pub fn trusted_header_id(name: &[u8], value: &git_hash::ObjectId, mut out: impl io::Write) -> io::Result<()> {
     out.write_all(name)?;
     out.write_all(&SPACE[..])?;
@@ -0,0 +1,282 @@
+//! Leniently decode the `author`, `committer` and `tagger` lines of commits and tags, which in real-world histories can be
+//! malformed in ways that strict parsing rejects, and report or repair these malformations.
+//!
+//! Objects with such lines still have to be readable and rewritable without changing their id, which is what
+//! [`Mode::Preserve`] is for, while [`Mode::Repair`] produces the form `git` would have written in the first place.
+use std::{io, ops::Range};
+
+use bstr::{BStr, ByteSlice};
+use quick_error::quick_error;
+
+/// A way in which an identity line deviates from `<name> <<email>> <timestamp> <+|-><HHMM>`.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum Malformation {
+    /// The `<` starting the email isn't preceded by a space, as in `Name<name@example.com>`.
+    MissingSpaceBeforeEmail,
+    /// The email is followed by one or more additional emails, as in `Name <one@example.com> <two@example.com>`.
+    ///
+    /// The first email is the one that counts.
+    MultipleEmails,
+    /// The `>` ending the email is missing, as in `Name <name@example.com 1136073600 +0000`.
+    MissingEmailEnd,
+    /// The timezone isn't a sign followed by four digits, or its hours or minutes are out of range.
+    ///
+    /// It's treated as `+0000` like `git` does if it can't parse it.
+    BadTimezone,
+}
+
+impl Malformation {
+    /// The id of the message `git fsck` reports this malformation with, which can be used to configure its severity
+    /// with `fsck.<msg-id>`.
+    pub fn fsck_msg_id(&self) -> &'static str {
+        match self {
+            Malformation::MissingSpaceBeforeEmail => "missingSpaceBeforeEmail",
+            // `git fsck` expects a date after the first email and fails to parse the second one as such.
+            Malformation::MultipleEmails => "badDate",
+            Malformation::MissingEmailEnd => "badEmail",
+            Malformation::BadTimezone => "badTimezone",
+        }
+    }
+}
+
+/// Determines how [`IdentRef::write_to()`] and [`Report::write_to()`] serialize identities.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum Mode {
+    /// Write the original bytes, which keeps object ids unchanged.
+    Preserve,
+    /// Write the identity as decoded, with all malformations corrected.
+    Repair,
+}
+
+quick_error! {
+    /// The error returned by [`IdentRef::from_bytes()`] and [`Report::from_bytes()`] for lines that can't be interpreted
+    /// even leniently.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        MissingEmail {
+            display("There is no email starting with '<'")
+        }
+        BadName {
+            display("The name must not contain '>'")
+        }
+        BadEmail {
+            display("The email must not contain '<' or be followed by anything but more emails")
+        }
+        BadDate {
+            display("The timestamp is missing or isn't a number")
+        }
+    }
+}
+
+/// An identity line as found in commits and tags, decoded leniently.
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdentRef<'a> {
+    /// The line as it was found, without the field name and the trailing newline.
+    #[cfg_attr(feature = "serde1", serde(borrow))]
+    pub raw: &'a BStr,
+    /// The identity as interpreted despite all malformations.
+    pub signature: git_actor::SignatureRef<'a>,
+    /// All malformations encountered, in the order of their position in `raw`. It's empty for well-formed lines.
+    pub malformations: Vec<Malformation>,
+}
+
+impl<'a> IdentRef<'a> {
+    /// Decode `raw`, an identity line without field name and trailing newline, while recording all malformations
+    /// encountered.
+    ///
+    /// Like `git`, the name ends before the first `<`, and the timestamp and timezone are the last two words of the line.
+    pub fn from_bytes(raw: &'a [u8]) -> Result<Self, Error> {
+        let mut malformations = Vec::new();
+        let email_start = raw.find_byte(b'<').ok_or(Error::MissingEmail)?;
+        let name = &raw[..email_start];
+        let name = match name.last() {
+            Some(b' ') => &name[..name.len() - 1],
+            _ => {
+                malformations.push(Malformation::MissingSpaceBeforeEmail);
+                name
+            }
+        };
+        if name.find_byte(b'>').is_some() {
+            return Err(Error::BadName);
+        }
+
+        let (person, tz) = split_last_word(&raw[email_start + 1..]).ok_or(Error::BadDate)?;
+        let (person, time) = split_last_word(person).ok_or(Error::BadDate)?;
+        let time = parse_digits(time)
+            .and_then(|time| time.parse::<u32>().ok())
+            .ok_or(Error::BadDate)?;
+
+        let email = match person.find_byte(b'>') {
+            Some(email_end) => {
+                let mut rest = &person[email_end + 1..];
+                while !rest.is_empty() {
+                    rest = match rest
+                        .strip_prefix(b" <")
+                        .and_then(|rest| rest.find_byte(b'>').map(|end| &rest[end + 1..]))
+                    {
+                        Some(rest) => rest,
+                        None => return Err(Error::BadEmail),
+                    };
+                    if malformations.last() != Some(&Malformation::MultipleEmails) {
+                        malformations.push(Malformation::MultipleEmails);
+                    }
+                }
+                &person[..email_end]
+            }
+            None => {
+                malformations.push(Malformation::MissingEmailEnd);
+                person
+            }
+        };
+        if email.find_byte(b'<').is_some() {
+            return Err(Error::BadEmail);
+        }
+
+        let (offset, sign) = parse_timezone(tz).unwrap_or_else(|| {
+            malformations.push(Malformation::BadTimezone);
+            (0, git_actor::Sign::Plus)
+        });
+        Ok(IdentRef {
+            raw: raw.as_bstr(),
+            signature: git_actor::SignatureRef {
+                name: name.as_bstr(),
+                email: email.as_bstr(),
+                time: git_actor::Time { time, offset, sign },
+            },
+            malformations,
+        })
+    }
+
+    /// Return true if at least one malformation was encountered.
+    pub fn is_malformed(&self) -> bool {
+        !self.malformations.is_empty()
+    }
+
+    /// Serialize this instance to `out` according to `mode`, without trailing newline.
+    pub fn write_to(&self, mode: Mode, mut out: impl io::Write) -> io::Result<()> {
+        match mode {
+            Mode::Preserve => out.write_all(self.raw),
+            Mode::Repair => self.signature.write_to(out),
+        }
+    }
+}
+
+/// Split `input` at its last space into everything before it and the word after it.
+fn split_last_word(input: &[u8]) -> Option<(&[u8], &[u8])> {
+    input.rfind_byte(b' ').map(|pos| (&input[..pos], &input[pos + 1..]))
+}
+
+fn parse_digits(input: &[u8]) -> Option<&str> {
+    if input.is_empty() || !input.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    std::str::from_utf8(input).ok()
+}
+
+/// Parse `+HHMM` or `-HHMM` into the offset in seconds and its sign, or return `None` if it's malformed or out of range.
+fn parse_timezone(tz: &[u8]) -> Option<(i32, git_actor::Sign)> {
+    const MAX_HOURS: i32 = 14;
+    if tz.len() != 5 {
+        return None;
+    }
+    let sign = match tz[0] {
+        b'+' => git_actor::Sign::Plus,
+        b'-' => git_actor::Sign::Minus,
+        _ => return None,
+    };
+    let hours = parse_digits(&tz[1..3])?.parse::<i32>().ok()?;
+    let minutes = parse_digits(&tz[3..])?.parse::<i32>().ok()?;
+    if hours > MAX_HOURS || minutes >= 60 {
+        return None;
+    }
+    let offset = hours * 3600 + minutes * 60;
+    Some((
+        if sign == git_actor::Sign::Minus {
+            -offset
+        } else {
+            offset
+        },
+        sign,
+    ))
+}
+
+/// An identity line of an object along with its position.
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Field<'a> {
+    /// The name of the header field, one of `author`, `committer` or `tagger`.
+    #[cfg_attr(feature = "serde1", serde(borrow))]
+    pub name: &'a BStr,
+    /// The decoded identity.
+    pub ident: IdentRef<'a>,
+    /// The range of [`ident.raw`][IdentRef::raw] in the object's data.
+    pub range: Range<usize>,
+}
+
+/// The identities of a commit or tag along with the malformations encountered in them, as created by [`Report::from_bytes()`].
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+pub struct Report<'a> {
+    data: &'a [u8],
+    /// All identity lines of the object in order of appearance, whether they are malformed or not.
+    pub fields: Vec<Field<'a>>,
+}
+
+impl<'a> Report<'a> {
+    /// Leniently decode the `author`, `committer` and `tagger` lines in the headers of the commit or tag `data`.
+    ///
+    /// Note that identities in the tags embedded into `mergetag` headers are not considered.
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, Error> {
+        const FIELDS: &[&[u8]] = &[b"author", b"committer", b"tagger"];
+        let mut fields = Vec::new();
+        let mut line_start = 0;
+        for line in data.lines_with_terminator() {
+            let start = line_start;
+            line_start += line.len();
+            let line = line.strip_suffix(b"\n").unwrap_or(line);
+            if line.is_empty() {
+                break;
+            }
+            for name in FIELDS {
+                if line.len() > name.len() && line.starts_with(name) && line[name.len()] == b' ' {
+                    let range = start + name.len() + 1..start + line.len();
+                    fields.push(Field {
+                        name: name.as_bstr(),
+                        ident: IdentRef::from_bytes(&data[range.clone()])?,
+                        range,
+                    });
+                }
+            }
+        }
+        Ok(Report { data, fields })
+    }
+
+    /// Return true if at least one identity is malformed.
+    pub fn is_malformed(&self) -> bool {
+        self.fields.iter().any(|field| field.ident.is_malformed())
+    }
+
+    /// Return all malformations along with the name of the field they were encountered in.
+    pub fn malformations(&self) -> impl Iterator<Item = (&'a BStr, Malformation)> + '_ {
+        self.fields
+            .iter()
+            .flat_map(|field| field.ident.malformations.iter().map(move |m| (field.name, *m)))
+    }
+
+    /// Write the object this report was created from to `out` with all identities serialized according to `mode`, leaving
+    /// everything else untouched.
+    ///
+    /// With [`Mode::Preserve`], the output is identical to the input.
+    pub fn write_to(&self, mode: Mode, mut out: impl io::Write) -> io::Result<()> {
+        let mut pos = 0;
+        for field in &self.fields {
+            out.write_all(&self.data[pos..field.range.start])?;
+            field.ident.write_to(mode, &mut out)?;
+            pos = field.range.end;
+        }
+        out.write_all(&self.data[pos..])
+    }
+}
@@ -12,8 +12,12 @@ use smallvec::SmallVec;
 
 ///
 pub mod commit;
+///
+pub mod ident;
 mod object;
 ///
+pub mod signature;
+///
 pub mod tag;
 ///
 pub mod tree;
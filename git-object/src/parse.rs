@@ -74,8 +74,24 @@ pub fn hex_hash<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&'a [u8], &
     .map(|(i, hex)| (i, hex.as_bstr()))
 }
 
+/// Parse the signature on the current line, and fall back to [lenient parsing][crate::ident::IdentRef::from_bytes()]
+/// if it is malformed, to be able to read objects in real-world histories.
 pub(crate) fn signature<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
     i: &'a [u8],
 ) -> IResult<&'a [u8], git_actor::SignatureRef<'a>, E> {
-    git_actor::signature::decode(i)
+    let line_end = i.find_byte(b'\n').unwrap_or(i.len());
+    let (line, i) = i.split_at(line_end);
+    let strict = git_actor::signature::decode(line);
+    if let Ok((rest, signature)) = &strict {
+        if rest.is_empty() {
+            return Ok((i, signature.clone()));
+        }
+    }
+    if let Ok(ident) = crate::ident::IdentRef::from_bytes(line) {
+        return Ok((i, ident.signature));
+    }
+    match strict {
+        Ok((rest, _)) => Err(nom::Err::Error(E::from_error_kind(rest, nom::error::ErrorKind::Eof))),
+        Err(err) => Err(err),
+    }
 }
@@ -0,0 +1,181 @@
+use bstr::ByteSlice;
+
+use super::Error;
+
+const SIGNATURE_PACKET: u8 = 2;
+const SUBPACKET_ISSUER: u8 = 16;
+const SUBPACKET_ISSUER_FINGERPRINT: u8 = 33;
+
+/// Turn an ascii-armored `OpenPGP` message into its binary form, verifying the checksum if present.
+pub fn dearmor(armored: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut lines = armored.lines().map(|line| line.trim_end());
+    lines
+        .by_ref()
+        .find(|line| line.starts_with(b"-----BEGIN PGP "))
+        .ok_or(Error::Armor("missing BEGIN line"))?;
+    // armor headers like 'Comment: …' are separated from the data by an empty line
+    for line in lines.by_ref() {
+        if line.is_empty() {
+            break;
+        }
+    }
+
+    let mut data = Vec::new();
+    let mut checksum = None;
+    let mut saw_end = false;
+    for line in lines {
+        if line.starts_with(b"-----END PGP ") {
+            saw_end = true;
+            break;
+        }
+        match line.strip_prefix(b"=") {
+            Some(encoded_checksum) if line.len() == 5 => checksum = Some(base64(encoded_checksum)?),
+            _ => data.extend_from_slice(line),
+        }
+    }
+    if !saw_end {
+        return Err(Error::Armor("missing END line"));
+    }
+    let data = base64(&data)?;
+    if let Some(checksum) = checksum {
+        let actual = crc24(&data);
+        if checksum.len() != 3 || checksum != actual.to_be_bytes()[1..] {
+            return Err(Error::Armor("checksum mismatch"));
+        }
+    }
+    Ok(data)
+}
+
+/// Return the issuer fingerprint stored in the binary `signature` packet, or its issuer key id if there is no fingerprint.
+pub fn issuer(signature: &[u8]) -> Result<Vec<u8>, Error> {
+    let body = signature_packet_body(signature).ok_or(Error::Armor("not a signature packet"))?;
+    match body.first() {
+        Some(3) => body
+            .get(7..15)
+            .map(ToOwned::to_owned)
+            .ok_or(Error::Armor("truncated signature packet")),
+        Some(4) | Some(5) => {
+            let count_len = if body[0] == 4 { 2 } else { 4 };
+            let hashed = body.get(4..).ok_or(Error::Armor("truncated signature packet"))?;
+            let (hashed, rest) = length_prefixed(hashed, count_len)?;
+            let (unhashed, _) = length_prefixed(rest, count_len)?;
+
+            let mut key_id = None;
+            for (kind, data) in subpackets(hashed).chain(subpackets(unhashed)) {
+                match kind {
+                    SUBPACKET_ISSUER_FINGERPRINT if data.len() > 1 => return Ok(data[1..].to_owned()),
+                    SUBPACKET_ISSUER if key_id.is_none() => key_id = Some(data.to_owned()),
+                    _ => {}
+                }
+            }
+            key_id.ok_or(Error::MissingIssuer)
+        }
+        _ => Err(Error::Armor("unsupported signature packet version")),
+    }
+}
+
+fn signature_packet_body(packet: &[u8]) -> Option<&[u8]> {
+    let (&header, rest) = packet.split_first()?;
+    if header & 0x80 == 0 {
+        return None;
+    }
+    let (tag, len, rest) = if header & 0x40 != 0 {
+        let (len, rest) = match *rest.first()? {
+            first @ 0..=191 => (first as usize, &rest[1..]),
+            first @ 192..=223 => (
+                (((first as usize) - 192) << 8) + *rest.get(1)? as usize + 192,
+                rest.get(2..)?,
+            ),
+            255 => (be_u32(rest.get(1..5)?) as usize, &rest[5..]),
+            _ => return None, // partial body lengths aren't used for signatures
+        };
+        (header & 0x3f, len, rest)
+    } else {
+        let (len, rest) = match header & 0b11 {
+            0 => (*rest.first()? as usize, &rest[1..]),
+            1 => ((rest.get(0..2)?[0] as usize) << 8 | rest[1] as usize, &rest[2..]),
+            2 => (be_u32(rest.get(0..4)?) as usize, &rest[4..]),
+            _ => (rest.len(), rest),
+        };
+        ((header >> 2) & 0x0f, len, rest)
+    };
+    (tag == SIGNATURE_PACKET).then(|| rest.get(..len)).flatten()
+}
+
+fn length_prefixed(data: &[u8], count_len: usize) -> Result<(&[u8], &[u8]), Error> {
+    let count = data
+        .get(..count_len)
+        .ok_or(Error::Armor("truncated signature packet"))?
+        .iter()
+        .fold(0usize, |acc, b| acc << 8 | *b as usize);
+    let data = &data[count_len..];
+    if data.len() < count {
+        return Err(Error::Armor("truncated signature packet"));
+    }
+    Ok(data.split_at(count))
+}
+
+fn subpackets(mut data: &[u8]) -> impl Iterator<Item = (u8, &[u8])> {
+    std::iter::from_fn(move || {
+        let (len, rest) = match *data.first()? {
+            first @ 0..=191 => (first as usize, &data[1..]),
+            first @ 192..=254 => (
+                (((first as usize) - 192) << 8) + *data.get(1)? as usize + 192,
+                data.get(2..)?,
+            ),
+            255 => (be_u32(data.get(1..5)?) as usize, &data[5..]),
+        };
+        if len == 0 || rest.len() < len {
+            return None;
+        }
+        let (subpacket, rest) = rest.split_at(len);
+        data = rest;
+        Some((subpacket[0] & 0x7f, &subpacket[1..]))
+    })
+}
+
+fn be_u32(b: &[u8]) -> u32 {
+    u32::from_be_bytes([b[0], b[1], b[2], b[3]])
+}
+
+fn base64(encoded: &[u8]) -> Result<Vec<u8>, Error> {
+    fn value(c: u8) -> Option<u32> {
+        Some(match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        } as u32)
+    }
+    let encoded = encoded.trim_end_with(|c| c == '=');
+    let mut out = Vec::with_capacity(encoded.len() * 3 / 4);
+    let (mut acc, mut bits) = (0u32, 0);
+    for &c in encoded {
+        acc = acc << 6 | value(c).ok_or(Error::Armor("invalid base64 character"))?;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Ok(out)
+}
+
+fn crc24(data: &[u8]) -> u32 {
+    const INIT: u32 = 0x00b7_04ce;
+    const POLY: u32 = 0x0186_4cfb;
+    let mut crc = INIT;
+    for &byte in data {
+        crc ^= (byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x0100_0000 != 0 {
+                crc ^= POLY;
+            }
+        }
+    }
+    crc & 0x00ff_ffff
+}
@@ -0,0 +1,154 @@
+use std::borrow::Cow;
+
+use bstr::BStr;
+use quick_error::quick_error;
+
+use crate::{CommitRef, TagRef, WriteTo};
+
+mod decode;
+
+quick_error! {
+    /// The error returned by [`verify_commit_signature()`] and [`verify_tag_signature()`].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        Unsigned {
+            display("The object does not carry a signature")
+        }
+        Armor(message: &'static str) {
+            display("The ascii-armored signature could not be decoded: {}", message)
+        }
+        MissingIssuer {
+            display("The signature does not name the key it was created with")
+        }
+        UnknownKey { issuer: Vec<u8> } {
+            display("The key {} is not in the keyring", hex::encode(issuer))
+        }
+        BadSignature { key_id: Vec<u8> } {
+            display("The signature made with key {} does not match the object", hex::encode(key_id))
+        }
+        Payload(err: std::io::Error) {
+            display("The signed payload could not be reconstructed")
+            from()
+            source(err)
+        }
+    }
+}
+
+/// The level of trust placed into a [`PublicKey`] by its owner, following the levels known by `gpg`.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+#[allow(missing_docs)]
+pub enum Trust {
+    Never,
+    Undefined,
+    Marginal,
+    Full,
+    Ultimate,
+}
+
+/// A public key as provided by a [`Keyring`], able to verify signatures made with its private counterpart.
+pub trait PublicKey {
+    /// The id of the key, typically its fingerprint.
+    fn id(&self) -> &[u8];
+    /// The trust level of the key.
+    fn trust(&self) -> Trust;
+    /// Return true if `signature`, a binary (i.e. not ascii-armored) `OpenPGP` signature packet, was made over `payload`
+    /// with this key.
+    fn verify(&self, payload: &[u8], signature: &[u8]) -> bool;
+}
+
+/// A collection of public keys, to be implemented by `OpenPGP` backends like `sequoia-openpgp` or `gpg`.
+pub trait Keyring {
+    /// The kind of key we return.
+    type PublicKey: PublicKey;
+
+    /// Return the key with the given `fingerprint`, which is the 20 byte fingerprint of the signing key if the signature
+    /// contains it, or its 8 byte key id otherwise.
+    fn find_key(&self, fingerprint: &[u8]) -> Option<Self::PublicKey>;
+}
+
+impl<K> Keyring for &K
+where
+    K: Keyring + ?Sized,
+{
+    type PublicKey = K::PublicKey;
+
+    fn find_key(&self, fingerprint: &[u8]) -> Option<Self::PublicKey> {
+        (*self).find_key(fingerprint)
+    }
+}
+
+/// The result of a successful signature verification.
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct VerifyOutcome {
+    /// The id of the key that made the signature, as provided by [`PublicKey::id()`].
+    pub key_id: Vec<u8>,
+    /// The trust level of the key that made the signature.
+    pub trust: Trust,
+}
+
+/// Verify the `gpgsig` header of `commit` with a key from `keyring`.
+pub fn verify_commit_signature(commit: &CommitRef<'_>, keyring: impl Keyring) -> Result<VerifyOutcome, Error> {
+    let (payload, signature) = commit_payload(commit)?.ok_or(Error::Unsigned)?;
+    verify(&payload, signature.as_ref(), keyring)
+}
+
+/// Verify the signature trailing the message of `tag` with a key from `keyring`.
+pub fn verify_tag_signature(tag: &TagRef<'_>, keyring: impl Keyring) -> Result<VerifyOutcome, Error> {
+    let (payload, signature) = tag_payload(tag)?.ok_or(Error::Unsigned)?;
+    verify(&payload, signature, keyring)
+}
+
+/// Return the bytes of `commit` that were signed along with its ascii-armored signature, or `None` if it is unsigned.
+///
+/// Like git, the payload is the serialized commit without its `gpgsig` header.
+#[allow(clippy::type_complexity)]
+pub fn commit_payload<'a>(commit: &CommitRef<'a>) -> Result<Option<(Vec<u8>, Cow<'a, BStr>)>, Error> {
+    let signature = match commit.extra_headers.iter().find(|(name, _)| *name == "gpgsig") {
+        Some((_, signature)) => signature.clone(),
+        None => return Ok(None),
+    };
+    let mut unsigned = commit.clone();
+    unsigned.extra_headers.retain(|(name, _)| *name != "gpgsig");
+    let mut payload = Vec::new();
+    unsigned.write_to(&mut payload)?;
+    Ok(Some((payload, signature)))
+}
+
+/// Return the bytes of `tag` that were signed along with its ascii-armored signature, or `None` if it is unsigned.
+///
+/// Like git, the payload is the serialized tag up to the beginning of its signature.
+pub fn tag_payload<'a>(tag: &TagRef<'a>) -> Result<Option<(Vec<u8>, &'a BStr)>, Error> {
+    let signature = match tag.pgp_signature {
+        Some(signature) => signature,
+        None => return Ok(None),
+    };
+    let mut unsigned = tag.clone();
+    unsigned.pgp_signature = None;
+    let mut payload = Vec::new();
+    unsigned.write_to(&mut payload)?;
+    payload.push(b'\n');
+    Ok(Some((payload, signature)))
+}
+
+/// Return the fingerprint of the key that created the ascii-armored `signature`, or its key id if the fingerprint isn't present.
+pub fn issuer(signature: &[u8]) -> Result<Vec<u8>, Error> {
+    decode::issuer(&decode::dearmor(signature)?)
+}
+
+fn verify(payload: &[u8], armored_signature: &[u8], keyring: impl Keyring) -> Result<VerifyOutcome, Error> {
+    let signature = decode::dearmor(armored_signature)?;
+    let issuer = decode::issuer(&signature)?;
+    let key = keyring.find_key(&issuer).ok_or(Error::UnknownKey { issuer })?;
+    if !key.verify(payload, &signature) {
+        return Err(Error::BadSignature {
+            key_id: key.id().to_owned(),
+        });
+    }
+    Ok(VerifyOutcome {
+        key_id: key.id().to_owned(),
+        trust: key.trust(),
+    })
+}
@@ -13,6 +13,9 @@ quick_error! {
         StartsWithDash {
             display("Tags must not start with a dash: '-'")
         }
+        InvalidTagger {
+            display("The tagger name or email must not contain '<', '>' or \\n")
+        }
         InvalidRefName(err: git_validate::tag::name::Error) {
             display("The tag name was no valid reference name")
             from()
@@ -29,9 +32,11 @@ impl From<Error> for io::Error {
 
 impl crate::WriteTo for Tag {
     fn write_to(&self, mut out: impl io::Write) -> io::Result<()> {
+        let name = validated_name(self.name.as_ref())?;
+        validate_tagger(self.tagger.as_ref().map(|t| t.to_ref()).as_ref())?;
         encode::trusted_header_id(b"object", &self.target, &mut out)?;
         encode::trusted_header_field(b"type", self.target_kind.as_bytes(), &mut out)?;
-        encode::header_field(b"tag", validated_name(self.name.as_ref())?, &mut out)?;
+        encode::header_field(b"tag", name, &mut out)?;
         if let Some(tagger) = &self.tagger {
             encode::trusted_header_signature(b"tagger", &tagger.to_ref(), &mut out)?;
         }
@@ -54,9 +59,11 @@ impl crate::WriteTo for Tag {
 
 impl<'a> crate::WriteTo for TagRef<'a> {
     fn write_to(&self, mut out: impl io::Write) -> io::Result<()> {
+        let name = validated_name(self.name)?;
+        validate_tagger(self.tagger.as_ref())?;
         encode::trusted_header_id(b"object", &self.target(), &mut out)?;
         encode::trusted_header_field(b"type", self.target_kind.as_bytes(), &mut out)?;
-        encode::header_field(b"tag", validated_name(self.name)?, &mut out)?;
+        encode::header_field(b"tag", name, &mut out)?;
         if let Some(tagger) = &self.tagger {
             encode::trusted_header_signature(b"tagger", tagger, &mut out)?;
         }
@@ -77,6 +84,13 @@ impl<'a> crate::WriteTo for TagRef<'a> {
     }
 }
 
+fn validate_tagger(tagger: Option<&git_actor::SignatureRef<'_>>) -> Result<(), Error> {
+    match tagger {
+        Some(tagger) if !encode::is_valid_signature(tagger) => Err(Error::InvalidTagger),
+        _ => Ok(()),
+    }
+}
+
 fn validated_name(name: &BStr) -> Result<&BStr, Error> {
     git_validate::tag::name(name)?;
     if name[0] == b'-' {
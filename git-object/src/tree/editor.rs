@@ -0,0 +1,208 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use bstr::{BString, ByteSlice};
+use git_hash::ObjectId;
+use quick_error::quick_error;
+
+use crate::{
+    tree::{verify::cmp_names, Entry, EntryMode},
+    Tree,
+};
+
+quick_error! {
+    /// The error returned by [`Editor::upsert()`] and [`Editor::remove()`].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        EmptyPathComponent { path: BString } {
+            display("The path '{}' is empty or has an empty component", path)
+        }
+        MissingTree { path: BString, id: ObjectId } {
+            display("The tree {} at '{}' could not be found", id, path)
+        }
+    }
+}
+
+/// Add, replace and remove entries of a tree and its subtrees by their slash-separated path, keeping all entries in
+/// the order git expects, and [write][Editor::write()] all changed trees.
+///
+/// Subtrees that aren't changed are not loaded, and those on the way to a changed entry are obtained with `find`.
+pub struct Editor<Find> {
+    find: Find,
+    /// The trees on the way to changed entries by their path, with the root tree at the empty path.
+    trees: HashMap<BString, Tree>,
+}
+
+impl<Find> Editor<Find>
+where
+    Find: FnMut(&git_hash::oid) -> Option<Tree>,
+{
+    /// Start editing the `root` tree, using `find` to obtain its subtrees by id once they need to be changed.
+    ///
+    /// Use [`Tree::empty()`] as `root` to create a tree from scratch.
+    pub fn new(root: Tree, find: Find) -> Self {
+        let mut trees = HashMap::new();
+        trees.insert(BString::default(), root);
+        Editor { find, trees }
+    }
+
+    /// Add an entry with `mode` and `id` at the slash-separated `path`, or replace the entry that is already there.
+    ///
+    /// Trees on the way to `path` are created if they don't exist, replacing non-tree entries of the same name.
+    /// If a tree is replaced, all changes made to entries within it are discarded.
+    pub fn upsert(
+        &mut self,
+        path: impl AsRef<[u8]>,
+        mode: EntryMode,
+        id: impl Into<ObjectId>,
+    ) -> Result<&mut Self, Error> {
+        let (parents, name) = split(path.as_ref())?;
+        let mut dir = BString::default();
+        for component in parents {
+            let child = join(&dir, component);
+            if !self.trees.contains_key(&child) {
+                let parent = self.trees.get_mut(&dir).expect("all parents are present");
+                let subtree = match parent.entries.iter().position(|e| e.filename == component) {
+                    Some(idx) if parent.entries[idx].mode.is_tree() => {
+                        let id = parent.entries[idx].oid;
+                        (self.find)(&id).ok_or_else(|| Error::MissingTree {
+                            path: child.clone(),
+                            id,
+                        })?
+                    }
+                    existing => {
+                        if let Some(idx) = existing {
+                            parent.entries.remove(idx);
+                        }
+                        insert(
+                            parent,
+                            Entry {
+                                mode: EntryMode::Tree,
+                                filename: component.into(),
+                                // The id is set once the tree is written.
+                                oid: ObjectId::null_sha1(),
+                            },
+                        );
+                        Tree::empty()
+                    }
+                };
+                self.trees.insert(child.clone(), subtree);
+            }
+            dir = child;
+        }
+
+        let parent = self.trees.get_mut(&dir).expect("all parents are present");
+        if let Some(idx) = parent.entries.iter().position(|e| e.filename == name) {
+            parent.entries.remove(idx);
+        }
+        insert(
+            parent,
+            Entry {
+                mode,
+                filename: name.into(),
+                oid: id.into(),
+            },
+        );
+        self.forget_trees_at(&join(&dir, name));
+        Ok(self)
+    }
+
+    /// Remove the entry at the slash-separated `path`, along with all entries within it if it's a tree.
+    ///
+    /// Nothing happens if there is no such entry.
+    pub fn remove(&mut self, path: impl AsRef<[u8]>) -> Result<&mut Self, Error> {
+        let (parents, name) = split(path.as_ref())?;
+        let mut dir = BString::default();
+        for component in parents {
+            let child = join(&dir, component);
+            if !self.trees.contains_key(&child) {
+                let parent = self.trees.get_mut(&dir).expect("all parents are present");
+                let id = match parent.entries.iter().find(|e| e.filename == component) {
+                    Some(entry) if entry.mode.is_tree() => entry.oid,
+                    _ => return Ok(self),
+                };
+                let subtree = (self.find)(&id).ok_or_else(|| Error::MissingTree {
+                    path: child.clone(),
+                    id,
+                })?;
+                self.trees.insert(child.clone(), subtree);
+            }
+            dir = child;
+        }
+
+        let parent = self.trees.get_mut(&dir).expect("all parents are present");
+        if let Some(idx) = parent.entries.iter().position(|e| e.filename == name) {
+            parent.entries.remove(idx);
+        }
+        self.forget_trees_at(&join(&dir, name));
+        Ok(self)
+    }
+
+    /// Pass all changed trees to `out`, subtrees before the trees containing them, to write them and return their id,
+    /// and return the id of the root tree.
+    ///
+    /// Subtrees that became empty are removed instead of being written, but the root tree is always written.
+    pub fn write<E>(mut self, mut out: impl FnMut(&Tree) -> Result<ObjectId, E>) -> Result<ObjectId, E> {
+        let mut paths: Vec<_> = self.trees.keys().filter(|path| !path.is_empty()).cloned().collect();
+        paths.sort_by(|a, b| depth(b).cmp(&depth(a)).then_with(|| a.cmp(b)));
+        for path in paths {
+            let tree = self.trees.remove(&path).expect("paths are keys");
+            let (dir, name) = match path.rfind_byte(b'/') {
+                Some(pos) => (BString::from(&path[..pos]), &path[pos + 1..]),
+                None => (BString::default(), &path[..]),
+            };
+            let parent = self
+                .trees
+                .get_mut(&dir)
+                .expect("parents are written after their subtrees");
+            let idx = parent
+                .entries
+                .iter()
+                .position(|e| e.filename == name)
+                .expect("trees are referenced by their parent");
+            if tree.entries.is_empty() {
+                parent.entries.remove(idx);
+            } else {
+                parent.entries[idx].oid = out(&tree)?;
+            }
+        }
+        out(&self.trees[&BString::default()])
+    }
+
+    /// Drop the changes to the tree at `path` and all trees within it, as its entry was replaced or removed.
+    fn forget_trees_at(&mut self, path: &[u8]) {
+        self.trees.retain(|tree_path, _| {
+            !(tree_path.starts_with(path) && matches!(tree_path.get(path.len()), None | Some(b'/')))
+        });
+    }
+}
+
+/// Insert `entry` into `tree` at the position git expects.
+fn insert(tree: &mut Tree, entry: Entry) {
+    let pos = tree.entries.partition_point(|e| {
+        cmp_names(&e.filename, e.mode.is_tree(), &entry.filename, entry.mode.is_tree()) == Ordering::Less
+    });
+    tree.entries.insert(pos, entry);
+}
+
+fn split(path: &[u8]) -> Result<(Vec<&[u8]>, &[u8]), Error> {
+    let mut components: Vec<_> = path.split(|b| *b == b'/').collect();
+    if components.iter().any(|component| component.is_empty()) {
+        return Err(Error::EmptyPathComponent { path: path.into() });
+    }
+    let name = components.pop().expect("split yields at least one component");
+    Ok((components, name))
+}
+
+fn join(dir: &[u8], name: &[u8]) -> BString {
+    let mut path = BString::from(dir);
+    if !path.is_empty() {
+        path.push(b'/');
+    }
+    path.extend_from_slice(name);
+    path
+}
+
+fn depth(path: &BString) -> usize {
+    path.iter().filter(|b| **b == b'/').count()
+}
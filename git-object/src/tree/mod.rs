@@ -5,8 +5,18 @@ use crate::{
     tree,
 };
 
+///
+pub mod editor;
+#[doc(inline)]
+pub use editor::Editor;
 mod ref_iter;
 ///
+pub mod stream;
+///
+pub mod verify;
+#[doc(inline)]
+pub use verify::verify_entry;
+///
 pub mod write;
 
 /// The mode of items storable in a tree, similar to the file mode on a unix file system.
@@ -38,6 +48,25 @@ impl EntryMode {
     pub fn is_no_tree(&self) -> bool {
         *self != EntryMode::Tree
     }
+
+    /// Return true if this entry mode represents a file, executable or not.
+    pub fn is_blob(&self) -> bool {
+        matches!(self, EntryMode::Blob | EntryMode::BlobExecutable)
+    }
+
+    /// Return the mode as bits like the ones of a unix file mode, for example `0o100644` for a [`Blob`][EntryMode::Blob].
+    pub fn to_mode_bits(&self) -> u32 {
+        *self as u16 as u32
+    }
+}
+
+impl std::fmt::Display for EntryMode {
+    /// Display the mode as zero-padded six-digit octal number, like `git ls-tree` does, for example `040000` for trees.
+    ///
+    /// Note that trees are stored without leading zero, see [`as_bytes()`][EntryMode::as_bytes()].
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:06o}", self.to_mode_bits())
+    }
 }
 
 /// An element of a [`TreeRef`][crate::TreeRef::entries].
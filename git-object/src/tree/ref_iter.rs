@@ -1,7 +1,5 @@
 use std::convert::TryFrom;
 
-use nom::error::ParseError;
-
 use crate::{tree, tree::EntryRef, TreeRef, TreeRefIter};
 
 impl<'a> TreeRefIter<'a> {
@@ -12,9 +10,11 @@ impl<'a> TreeRefIter<'a> {
 }
 
 impl<'a> TreeRef<'a> {
-    /// Deserialize a Tree from `data`.
+    /// Deserialize a Tree from `data`, using the same rules as [`TreeRefIter`].
     pub fn from_bytes(data: &'a [u8]) -> Result<TreeRef<'a>, crate::decode::Error> {
-        decode::tree(data).map(|(_, t)| t).map_err(crate::decode::Error::from)
+        Ok(TreeRef {
+            entries: TreeRefIter::from_bytes(data).entries()?,
+        })
     }
 
     /// Create an instance of the empty tree.
@@ -45,19 +45,14 @@ impl<'a> Iterator for TreeRefIter<'a> {
         if self.data.is_empty() {
             return None;
         }
-        match decode::fast_entry(self.data) {
-            Some((data_left, entry)) => {
+        match tree::stream::decode_entry(self.data, false) {
+            Ok(Some((entry, data_left))) => {
                 self.data = data_left;
                 Some(Ok(entry))
             }
-            None => {
+            Ok(None) | Err(_) => {
                 self.data = &[];
-                #[allow(clippy::unit_arg)]
-                Some(Err(nom::Err::Error(crate::decode::ParseError::from_error_kind(
-                    &[] as &[u8],
-                    nom::error::ErrorKind::MapRes,
-                ))
-                .into()))
+                Some(Err(tree::stream::decode_error()))
             }
         }
     }
@@ -66,17 +61,19 @@ impl<'a> Iterator for TreeRefIter<'a> {
 impl<'a> TryFrom<&'a [u8]> for tree::EntryMode {
     type Error = &'a [u8];
 
+    /// Parse the octal `mode` as stored in a tree, accepting legacy forms like the zero-padded `040000` as well.
     fn try_from(mode: &'a [u8]) -> Result<Self, Self::Error> {
-        Ok(match mode {
-            b"40000" => tree::EntryMode::Tree,
-            b"100644" => tree::EntryMode::Blob,
-            b"100755" => tree::EntryMode::BlobExecutable,
-            b"120000" => tree::EntryMode::Link,
-            b"160000" => tree::EntryMode::Commit,
-            b"100664" => tree::EntryMode::Blob, // rare and found in the linux kernel
-            b"100640" => tree::EntryMode::Blob, // rare and found in the Rust repo
-            _ => return Err(mode),
-        })
+        if mode.is_empty() || mode.len() > 7 {
+            return Err(mode);
+        }
+        let mut bits = 0u32;
+        for b in mode {
+            if *b < b'0' || *b > b'7' {
+                return Err(mode);
+            }
+            bits = (bits << 3) + (b - b'0') as u32;
+        }
+        tree::EntryMode::try_from(bits).map_err(|_| mode)
     }
 }
 
@@ -96,73 +93,3 @@ impl TryFrom<u32> for tree::EntryMode {
         })
     }
 }
-
-mod decode {
-    use std::convert::TryFrom;
-
-    use bstr::ByteSlice;
-    use nom::{
-        bytes::complete::{tag, take, take_while1, take_while_m_n},
-        character::is_digit,
-        combinator::all_consuming,
-        error::ParseError,
-        multi::many0,
-        sequence::terminated,
-        IResult,
-    };
-
-    use crate::{parse::SPACE, tree, tree::EntryRef, TreeRef};
-
-    const NULL: &[u8] = b"\0";
-
-    pub fn fast_entry(i: &[u8]) -> Option<(&[u8], EntryRef<'_>)> {
-        let mut mode = 0u32;
-        let mut spacer_pos = 1;
-        for b in i.iter().take_while(|b| **b != b' ') {
-            if *b < b'0' || *b > b'7' {
-                return None;
-            }
-            mode = (mode << 3) + (b - b'0') as u32;
-            spacer_pos += 1;
-        }
-        let (_, i) = i.split_at(spacer_pos);
-        let mode = tree::EntryMode::try_from(mode).ok()?;
-        let (filename, i) = i.split_at(i.find_byte(0)?);
-        let i = &i[1..];
-        const HASH_LEN_FIXME: usize = 20; // TODO: know actual /desired length or we may overshoot
-        let (oid, i) = match i.len() {
-            len if len < HASH_LEN_FIXME => return None,
-            _ => i.split_at(20),
-        };
-        Some((
-            i,
-            EntryRef {
-                mode,
-                filename: filename.as_bstr(),
-                oid: git_hash::oid::try_from(oid).expect("we counted exactly 20 bytes"),
-            },
-        ))
-    }
-
-    pub fn entry<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&[u8], EntryRef<'_>, E> {
-        let (i, mode) = terminated(take_while_m_n(5, 6, is_digit), tag(SPACE))(i)?;
-        let mode = tree::EntryMode::try_from(mode)
-            .map_err(|invalid| nom::Err::Error(E::from_error_kind(invalid, nom::error::ErrorKind::MapRes)))?;
-        let (i, filename) = terminated(take_while1(|b| b != NULL[0]), tag(NULL))(i)?;
-        let (i, oid) = take(20u8)(i)?; // TODO: make this compatible with other hash lengths
-
-        Ok((
-            i,
-            EntryRef {
-                mode,
-                filename: filename.as_bstr(),
-                oid: git_hash::oid::try_from(oid).expect("we counted exactly 20 bytes"),
-            },
-        ))
-    }
-
-    pub fn tree<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&'a [u8], TreeRef<'a>, E> {
-        let (i, entries) = all_consuming(many0(entry))(i)?;
-        Ok((i, TreeRef { entries }))
-    }
-}
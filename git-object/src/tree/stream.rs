@@ -0,0 +1,159 @@
+//! Decode trees from chunks of data as they arrive, for example while inflating them from a pack, without buffering them.
+use std::convert::TryFrom;
+
+use bstr::ByteSlice;
+use nom::error::ParseError;
+
+use crate::{
+    tree,
+    tree::{
+        verify::{verify_entry, Error, Severity},
+        EntryRef,
+    },
+};
+
+const HASH_LEN: usize = 20; // TODO: make this compatible with other hash lengths
+
+/// A push-style parser which decodes tree entries from chunks of arbitrary size and calls a function for each completed entry.
+///
+/// Only the part of an entry which is split across chunks is buffered, so memory usage doesn't depend on the size of the tree.
+/// [`TreeRefIter`][crate::TreeRefIter] and [`TreeRef::from_bytes_strict()`][crate::TreeRef::from_bytes_strict()] decode
+/// entries the same way, so all of them produce the same entries and errors for the same data.
+#[derive(Default, Debug, Clone)]
+pub struct Parser {
+    /// The beginning of an entry which wasn't complete at the end of the last chunk.
+    partial: Vec<u8>,
+    strict: bool,
+    failed: bool,
+}
+
+impl Parser {
+    /// Create a parser which only fails if entries can't be decoded, just like [`TreeRefIter`][crate::TreeRefIter].
+    pub fn new() -> Self {
+        Parser::default()
+    }
+
+    /// If `toggle` is true, fail on entries with problems of [error severity][Severity::Error] according to [`verify_entry()`],
+    /// just like [`TreeRef::from_bytes_strict()`][crate::TreeRef::from_bytes_strict()].
+    pub fn strict(mut self, toggle: bool) -> Self {
+        self.strict = toggle;
+        self
+    }
+
+    /// Decode all entries completed by `chunk` and call `on_entry` with each of them, in order.
+    ///
+    /// Data belonging to an entry which isn't complete yet is kept until the next call.
+    /// Once an error was returned, the parser fails on all subsequent calls.
+    pub fn push(&mut self, mut chunk: &[u8], mut on_entry: impl FnMut(EntryRef<'_>)) -> Result<(), Error> {
+        if self.failed {
+            return Err(Error::Decode(decode_error()));
+        }
+        while !self.partial.is_empty() {
+            let res = decode_entry(&self.partial, self.strict);
+            self.failed = res.is_err();
+            match res? {
+                Some((entry, rest)) => {
+                    debug_assert!(rest.is_empty(), "we never take more bytes than the entry needs");
+                    on_entry(entry);
+                    self.partial.clear();
+                }
+                None if chunk.is_empty() => return Ok(()),
+                None => {
+                    let missing = match self.partial.find_byte(0) {
+                        Some(nul) => nul + 1 + HASH_LEN - self.partial.len(),
+                        None => chunk.find_byte(0).map_or(chunk.len(), |nul| nul + 1 + HASH_LEN),
+                    };
+                    let (head, tail) = chunk.split_at(missing.min(chunk.len()));
+                    self.partial.extend_from_slice(head);
+                    chunk = tail;
+                }
+            }
+        }
+        while !chunk.is_empty() {
+            let res = decode_entry(chunk, self.strict);
+            self.failed = res.is_err();
+            match res? {
+                Some((entry, rest)) => {
+                    on_entry(entry);
+                    chunk = rest;
+                }
+                None => {
+                    self.partial.extend_from_slice(chunk);
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Signal that all data was pushed, and fail if the last entry is incomplete.
+    pub fn finish(self) -> Result<(), Error> {
+        if self.failed || !self.partial.is_empty() {
+            return Err(Error::Decode(decode_error()));
+        }
+        Ok(())
+    }
+}
+
+/// Decode the entry at the beginning of `data` and return it along with the data following it, or `None` if `data` ends before
+/// the entry is complete.
+///
+/// If `strict` is true, the entry is checked with [`verify_entry()`] as soon as its name is complete.
+pub(crate) fn decode_entry(data: &[u8], strict: bool) -> Result<Option<(EntryRef<'_>, &[u8])>, Error> {
+    let mut mode = 0u32;
+    let mut mode_len = 0;
+    loop {
+        match data.get(mode_len) {
+            None => return Ok(None),
+            Some(b' ') => break,
+            Some(b) if (b'0'..=b'7').contains(b) && mode_len < 7 => {
+                mode = (mode << 3) + (b - b'0') as u32;
+                mode_len += 1;
+            }
+            Some(_) => return Err(Error::Decode(decode_error())),
+        }
+    }
+    let raw_mode = &data[..mode_len];
+    let mode = tree::EntryMode::try_from(mode).map_err(|_| Error::Decode(decode_error()))?;
+
+    let data = &data[mode_len + 1..];
+    let nul = match data.find_byte(0) {
+        Some(pos) => pos,
+        None => return Ok(None),
+    };
+    let filename = data[..nul].as_bstr();
+    if strict {
+        if let Some(finding) = verify_entry(filename, raw_mode.as_bstr())
+            .into_iter()
+            .find(|f| f.severity == Severity::Error)
+        {
+            return Err(Error::InvalidEntry {
+                filename: filename.to_owned(),
+                finding,
+            });
+        }
+    }
+
+    let data = &data[nul + 1..];
+    if data.len() < HASH_LEN {
+        return Ok(None);
+    }
+    let (oid, data) = data.split_at(HASH_LEN);
+    Ok(Some((
+        EntryRef {
+            mode,
+            filename,
+            oid: git_hash::oid::try_from(oid).expect("we counted exactly 20 bytes"),
+        },
+        data,
+    )))
+}
+
+pub(crate) fn decode_error() -> crate::decode::Error {
+    #[allow(clippy::unit_arg)]
+    nom::Err::Error(crate::decode::ParseError::from_error_kind(
+        &[] as &[u8],
+        nom::error::ErrorKind::MapRes,
+    ))
+    .into()
+}
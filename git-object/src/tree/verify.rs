@@ -0,0 +1,345 @@
+use std::{cmp::Ordering, collections::HashSet};
+
+use bstr::{BStr, BString, ByteSlice};
+use quick_error::quick_error;
+
+use crate::{tree, Tree, TreeRef, TreeRefIter};
+
+/// How severe a [`Finding`] is.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum Severity {
+    /// The entry is unusual but harmless, and was created by older versions of git or other tools.
+    Warning,
+    /// The entry is invalid and may be harmful when checked out.
+    Error,
+}
+
+/// The kind of problem with a tree entry, named after the message ids used by `git fsck`.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum MessageId {
+    /// The name is empty.
+    EmptyName,
+    /// The name contains a slash, making it a path.
+    FullPathname,
+    /// The name is `.`.
+    HasDot,
+    /// The name is `..`.
+    HasDotdot,
+    /// The name is `.git` when compared case-insensitively, or would be treated as such on HFS+ or NTFS, like `.g\u{200c}it` or `git~1`.
+    HasDotgit,
+    /// The mode is not one of the modes git writes, like the group-writable mode `100664` written by old versions of git.
+    BadFilemode,
+    /// The mode has a leading zero, like `040000` written by old versions of git.
+    ZeroPaddedFilemode,
+    /// The name contains a NUL byte, which makes the entry impossible to encode.
+    BadTree,
+}
+
+impl MessageId {
+    /// Return the message id as used by `git fsck` and the `fsck.<msg-id>` configuration.
+    pub fn as_str(&self) -> &'static str {
+        use MessageId::*;
+        match self {
+            EmptyName => "emptyName",
+            FullPathname => "fullPathname",
+            HasDot => "hasDot",
+            HasDotdot => "hasDotdot",
+            HasDotgit => "hasDotgit",
+            BadFilemode => "badFilemode",
+            ZeroPaddedFilemode => "zeroPaddedFilemode",
+            BadTree => "badTree",
+        }
+    }
+}
+
+/// A problem found by [`verify_entry()`].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Finding {
+    /// What's wrong with the entry.
+    pub id: MessageId,
+    /// How severe the problem is.
+    pub severity: Severity,
+}
+
+impl Finding {
+    fn error(id: MessageId) -> Self {
+        Finding {
+            id,
+            severity: Severity::Error,
+        }
+    }
+
+    fn warning(id: MessageId) -> Self {
+        Finding {
+            id,
+            severity: Severity::Warning,
+        }
+    }
+}
+
+quick_error! {
+    /// The error returned by [`TreeRef::from_bytes_strict()`].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        Decode(err: crate::decode::Error) {
+            display("The tree could not be decoded")
+            from()
+            source(err)
+        }
+        InvalidEntry { filename: BString, finding: Finding } {
+            display("The tree entry named {:?} is invalid: {}", filename, finding.id.as_str())
+        }
+    }
+}
+
+/// Check the `name` and the `mode` of a tree entry, with `mode` being the octal mode as stored in the tree, like `100644`.
+///
+/// All problems are returned, or an empty vector if the entry is valid. Names like `.git` and its lookalikes, or names that are paths,
+/// are [errors][Severity::Error] as they are harmful when checked out, while unusual modes like the zero-padded `040000` or
+/// the group-writable `100664` are [warnings][Severity::Warning] as they were written by older versions of git.
+/// Unlike `git fsck`, a NUL byte in `name` is detected as well to allow checking entries before they are written.
+pub fn verify_entry(name: &BStr, mode: &BStr) -> Vec<Finding> {
+    use MessageId::*;
+    let mut out = Vec::new();
+    if name.is_empty() {
+        out.push(Finding::error(EmptyName));
+    }
+    if name.contains(&b'/') {
+        out.push(Finding::error(FullPathname));
+    }
+    if name.contains(&0) {
+        out.push(Finding::error(BadTree));
+    }
+    if name == "." {
+        out.push(Finding::error(HasDot));
+    }
+    if name == ".." {
+        out.push(Finding::error(HasDotdot));
+    }
+    if name.eq_ignore_ascii_case(b".git") || is_hfs_dot_git(name) || is_ntfs_dot_git(name) {
+        out.push(Finding::error(HasDotgit));
+    }
+
+    if mode.starts_with(b"0") {
+        out.push(Finding::warning(ZeroPaddedFilemode));
+    }
+    match mode.trim_start_with(|c| c == '0').as_bytes() {
+        b"100644" | b"100755" | b"120000" | b"40000" | b"160000" => {}
+        _ => out.push(Finding::warning(BadFilemode)),
+    }
+    out
+}
+
+/// Return true if `name` is `.git` on HFS+, which ignores case as well as certain zero-width unicode code points.
+fn is_hfs_dot_git(name: &BStr) -> bool {
+    let mut chars = name
+        .chars()
+        .filter(|c| !matches!(*c as u32, 0x200c..=0x200f | 0x202a..=0x202e | 0x206a..=0x206f | 0xfeff));
+    for expected in ".git".chars() {
+        match chars.next() {
+            Some(c) if c.to_ascii_lowercase() == expected => {}
+            _ => return false,
+        }
+    }
+    chars.next().is_none()
+}
+
+/// Return true if `name` is `.git` on NTFS, which ignores trailing spaces and periods and supports the short name `git~1`.
+fn is_ntfs_dot_git(name: &BStr) -> bool {
+    let len = name
+        .iter()
+        .position(|b| matches!(b, b'\\' | b'/' | b':'))
+        .unwrap_or(name.len());
+    let only_spaces_and_periods_after =
+        |skip: usize| len >= skip && name[skip..len].iter().all(|b| matches!(b, b' ' | b'.'));
+    (only_spaces_and_periods_after(4) && name[..4].eq_ignore_ascii_case(b".git"))
+        || (only_spaces_and_periods_after(5) && name[..5].eq_ignore_ascii_case(b"git~1"))
+}
+
+impl<'a> TreeRef<'a> {
+    /// Like [`from_bytes()`][TreeRef::from_bytes()], but fail if any entry has a problem of [error severity][Severity::Error]
+    /// according to [`verify_entry()`].
+    ///
+    /// Use it for trees received from untrusted sources.
+    pub fn from_bytes_strict(mut data: &'a [u8]) -> Result<TreeRef<'a>, Error> {
+        let mut entries = Vec::new();
+        while !data.is_empty() {
+            match tree::stream::decode_entry(data, true)? {
+                Some((entry, data_left)) => {
+                    entries.push(entry);
+                    data = data_left;
+                }
+                None => return Err(Error::Decode(tree::stream::decode_error())),
+            }
+        }
+        Ok(TreeRef { entries })
+    }
+}
+
+/// Return the undecoded mode and name of the entry at the beginning of `data`, if there is one.
+fn raw_mode_and_name(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let space = data.find_byte(b' ')?;
+    let (mode, rest) = (&data[..space], &data[space + 1..]);
+    let nul = rest.find_byte(0)?;
+    Some((mode, &rest[..nul]))
+}
+
+/// The kind of problem with a tree as a whole, as found by [`Tree::verify()`] or [`TreeRefIter::verify()`].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum IssueKind {
+    /// Another entry with the same name was seen before.
+    DuplicateName,
+    /// The entry sorts before the previous one, making lookups by name unreliable.
+    UnsortedEntries,
+    /// The name contains a NUL byte, which makes the entry impossible to encode.
+    NullInName,
+    /// The name is `.`, `..`, or `.git` or one of its lookalikes, which is harmful when checked out.
+    DotGitName,
+    /// The name is empty or contains a slash.
+    InvalidName,
+    /// The mode is not one of the modes git writes, like the zero-padded `040000` or the group-writable `100664`.
+    BadMode,
+}
+
+impl IssueKind {
+    /// Return how severe issues of this kind are, following the severity of the corresponding [`verify_entry()`] findings.
+    pub fn severity(&self) -> Severity {
+        match self {
+            IssueKind::BadMode => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    fn from_finding(finding: &Finding) -> Self {
+        use MessageId::*;
+        match finding.id {
+            EmptyName | FullPathname => IssueKind::InvalidName,
+            HasDot | HasDotdot | HasDotgit => IssueKind::DotGitName,
+            BadTree => IssueKind::NullInName,
+            BadFilemode | ZeroPaddedFilemode => IssueKind::BadMode,
+        }
+    }
+}
+
+/// A problem with an entry of a tree, as found by [`Tree::verify()`] or [`TreeRefIter::verify()`].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Issue {
+    /// The byte offset at which the offending entry starts in the serialized tree.
+    pub offset: usize,
+    /// The name of the offending entry.
+    pub filename: BString,
+    /// What's wrong with the entry.
+    pub kind: IssueKind,
+}
+
+/// Compare tree entry names the way git sorts them, with trees sorting as if their name had a trailing slash.
+pub(crate) fn cmp_names(lhs: &[u8], lhs_is_tree: bool, rhs: &[u8], rhs_is_tree: bool) -> Ordering {
+    let len = lhs.len().min(rhs.len());
+    lhs[..len].cmp(&rhs[..len]).then_with(|| {
+        let terminator =
+            |name: &[u8], is_tree: bool| name.get(len).copied().unwrap_or(if is_tree { b'/' } else { b'\0' });
+        terminator(lhs, lhs_is_tree).cmp(&terminator(rhs, rhs_is_tree))
+    })
+}
+
+/// Checks that entry names are unique and sorted, fed in the order they appear in a tree.
+#[derive(Default)]
+pub(crate) struct Order<'a> {
+    names: HashSet<&'a [u8]>,
+    previous: Option<(&'a [u8], bool)>,
+}
+
+impl<'a> Order<'a> {
+    /// Return the kind of issue if the entry with `name` is a duplicate or sorts before the previous entry.
+    pub(crate) fn check(&mut self, name: &'a [u8], is_tree: bool) -> Option<IssueKind> {
+        let issue = if !self.names.insert(name) {
+            Some(IssueKind::DuplicateName)
+        } else {
+            self.previous
+                .filter(|(previous, previous_is_tree)| {
+                    cmp_names(previous, *previous_is_tree, name, is_tree) != Ordering::Less
+                })
+                .map(|_| IssueKind::UnsortedEntries)
+        };
+        self.previous = Some((name, is_tree));
+        issue
+    }
+}
+
+/// Collects [issues][Issue] of entries in the order they appear in a tree.
+#[derive(Default)]
+struct Issues<'a> {
+    order: Order<'a>,
+    out: Vec<Issue>,
+}
+
+impl<'a> Issues<'a> {
+    fn push(&mut self, offset: usize, filename: &[u8], kind: IssueKind) {
+        self.out.push(Issue {
+            offset,
+            filename: filename.into(),
+            kind,
+        })
+    }
+
+    fn check_order(&mut self, offset: usize, name: &'a [u8], is_tree: bool) {
+        if let Some(kind) = self.order.check(name, is_tree) {
+            self.push(offset, name, kind);
+        }
+    }
+
+    fn check_entry(&mut self, offset: usize, name: &[u8], mode: &[u8]) {
+        for finding in verify_entry(name.as_bstr(), mode.as_bstr()) {
+            self.push(offset, name, IssueKind::from_finding(&finding));
+        }
+    }
+}
+
+impl Tree {
+    /// Check all entries for [problems][IssueKind] like duplicate names or incorrect ordering, similar to what `git fsck` does,
+    /// with offsets referring to the serialized form of this tree.
+    ///
+    /// All issues are returned, or an empty vector if the tree is valid and can be written.
+    pub fn verify(&self) -> Vec<Issue> {
+        let mut issues = Issues::default();
+        let mut offset = 0;
+        for tree::Entry { mode, filename, oid } in &self.entries {
+            issues.check_entry(offset, filename, mode.as_bytes());
+            issues.check_order(offset, filename, mode.is_tree());
+            offset += mode.as_bytes().len() + 1 + filename.len() + 1 + oid.as_bytes().len();
+        }
+        issues.out
+    }
+}
+
+impl<'a> TreeRefIter<'a> {
+    /// Check all entries for [problems][IssueKind] like duplicate names, incorrect ordering or unusual modes, similar
+    /// to what `git fsck` does, with offsets referring to the tree data this iterator was created from.
+    ///
+    /// All issues are returned, or an empty vector if the tree is valid, or an error if the tree couldn't be decoded.
+    /// Unlike [`Tree::verify()`], modes are checked as they are stored, before they are normalized while decoding.
+    pub fn verify(mut self) -> Result<Vec<Issue>, crate::decode::Error> {
+        let mut issues = Issues::default();
+        let start = self.data;
+        loop {
+            let offset = start.len() - self.data.len();
+            if let Some((mode, name)) = raw_mode_and_name(self.data) {
+                issues.check_entry(offset, name, mode);
+            }
+            match self.next() {
+                Some(entry) => {
+                    let entry = entry?;
+                    issues.check_order(offset, entry.filename, entry.mode.is_tree());
+                }
+                None => break,
+            }
+        }
+        Ok(issues.out)
+    }
+}
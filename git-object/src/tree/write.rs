@@ -5,7 +5,7 @@ use quick_error::quick_error;
 
 use crate::{
     encode::SPACE,
-    tree::{Entry, EntryRef},
+    tree::{verify, Entry, EntryRef},
     Kind, Tree, TreeRef,
 };
 
@@ -17,6 +17,12 @@ quick_error! {
         NewlineInFilename(name: BString) {
             display("Newlines are invalid in file paths: {:?}", name)
         }
+        DuplicateName(name: BString) {
+            display("The entry named {:?} exists more than once", name)
+        }
+        UnsortedEntries(name: BString) {
+            display("The entry named {:?} sorts before the previous entry", name)
+        }
     }
 }
 
@@ -26,19 +32,12 @@ impl From<Error> for io::Error {
     }
 }
 
-/// Serialization
-impl crate::WriteTo for Tree {
-    /// Serialize this tree to `out` in the git internal format.
-    fn write_to(&self, mut out: impl io::Write) -> io::Result<()> {
-        debug_assert_eq!(
-            &{
-                let mut entries_sorted = self.entries.clone();
-                entries_sorted.sort();
-                entries_sorted
-            },
-            &self.entries,
-            "entries for serialization must be sorted by filename"
-        );
+impl Tree {
+    /// Like [`write_to()`][crate::WriteTo::write_to()], but write entries in the order they are in even if they are unsorted or
+    /// contain duplicate names.
+    ///
+    /// Use it to write trees as they were read, even if they were invalid as it's the case with some historical trees.
+    pub fn write_to_unchecked(&self, mut out: impl io::Write) -> io::Result<()> {
         for Entry { mode, filename, oid } in &self.entries {
             out.write_all(mode.as_bytes())?;
             out.write_all(SPACE)?;
@@ -54,6 +53,30 @@ impl crate::WriteTo for Tree {
         Ok(())
     }
 
+    fn check_order(&self) -> Result<(), Error> {
+        let mut order = verify::Order::default();
+        for entry in &self.entries {
+            match order.check(&entry.filename, entry.mode.is_tree()) {
+                Some(verify::IssueKind::DuplicateName) => return Err(Error::DuplicateName(entry.filename.clone())),
+                Some(_) => return Err(Error::UnsortedEntries(entry.filename.clone())),
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Serialization
+impl crate::WriteTo for Tree {
+    /// Serialize this tree to `out` in the git internal format, failing without writing anything if entries are
+    /// unsorted or if names are duplicated.
+    ///
+    /// Use [`write_to_unchecked()`][Tree::write_to_unchecked()] to write such trees anyway.
+    fn write_to(&self, out: impl io::Write) -> io::Result<()> {
+        self.check_order()?;
+        self.write_to_unchecked(out)
+    }
+
     fn kind(&self) -> Kind {
         Kind::Tree
     }
@@ -47,6 +47,27 @@ mod tag {
         "tag/with-newlines.txt",
         "tag/signed.txt"
     );
+
+    #[test]
+    fn invalid_tagger_is_rejected_before_writing_anything() -> crate::Result {
+        use git_object::{bstr::ByteSlice, TagRef, WriteTo};
+
+        let data = crate::fixture_bytes("tag/signed.txt");
+        let mut tag = TagRef::from_bytes(&data)?;
+        let mut tagger = tag.tagger.clone().expect("present");
+        tagger.name = b"Sebastian\nThiel".as_bstr();
+        tag.tagger = Some(tagger);
+
+        let mut out = Vec::new();
+        let err = tag.write_to(&mut out).unwrap_err();
+        assert!(matches!(
+            err.get_ref()
+                .and_then(|err| err.downcast_ref::<git_object::tag::write::Error>()),
+            Some(git_object::tag::write::Error::InvalidTagger)
+        ));
+        assert!(out.is_empty());
+        Ok(())
+    }
 }
 
 mod commit {
@@ -57,13 +78,127 @@ mod commit {
         "commit/two-multiline-headers.txt",
         "commit/mergetag.txt",
         "commit/merge.txt",
+        "commit/message-with-footer.txt",
         "commit/signed.txt",
         "commit/signed-singleline.txt",
         "commit/signed-with-encoding.txt",
         "commit/unsigned.txt",
+        "commit/unknown-headers.txt",
         "commit/whitespace.txt",
         "commit/with-encoding.txt"
     );
+
+    #[test]
+    fn invalid_signatures_are_rejected_before_writing_anything() -> crate::Result {
+        use git_object::{commit::write::Error, Commit, CommitRef, WriteTo};
+
+        let data = crate::fixture_bytes("commit/signed.txt");
+        let commit: Commit = CommitRef::from_bytes(&data)?.into();
+        for (name, email, expected_header) in &[
+            ("Sebastian <Thiel>", "sebastian.thiel@icloud.com", "author"),
+            ("Sebastian Thiel", "sebastian.thiel@icloud.com\n", "author"),
+            ("Sebastian Thiel", "sebastian.thiel@icloud.com>", "committer"),
+        ] {
+            let mut commit = commit.clone();
+            let actor = if *expected_header == "author" {
+                &mut commit.author
+            } else {
+                &mut commit.committer
+            };
+            actor.name = (*name).into();
+            actor.email = (*email).into();
+
+            let mut out = Vec::new();
+            let err = commit.write_to(&mut out).unwrap_err();
+            match err.get_ref().and_then(|err| err.downcast_ref::<Error>()) {
+                Some(Error::InvalidSignature { header }) => assert_eq!(header, expected_header),
+                _ => panic!("unexpected error: {:?}", err),
+            }
+            assert!(out.is_empty(), "nothing is written to avoid creating corrupt objects");
+        }
+        Ok(())
+    }
+
+    fn signature(time: u32) -> git_actor::Signature {
+        git_actor::Signature {
+            name: "Sebastian Thiel".into(),
+            email: "sebastian.thiel@icloud.com".into(),
+            time: git_actor::Time {
+                time,
+                offset: 28800,
+                sign: git_actor::Sign::Plus,
+            },
+        }
+    }
+
+    #[test]
+    fn commits_created_from_scratch_serialize_exactly_like_git() -> crate::Result {
+        use git_object::{bstr::ByteSlice, Commit, WriteTo};
+
+        use crate::hex_to_id;
+
+        let unsigned = Commit {
+            tree: hex_to_id("1b2dfb4ac5e42080b682fc676e9738c94ce6d54d"),
+            parents: Default::default(),
+            author: signature(1592437401),
+            committer: signature(1592437401),
+            encoding: None,
+            message: "without sig".into(),
+            extra_headers: Vec::new(),
+        };
+        let merge = Commit {
+            tree: hex_to_id("0cf16ce8e229b59a761198975f0c0263229faf82"),
+            parents: vec![
+                hex_to_id("6a6054db4ce3c1e4e6a37f8c4d7acb63a4d6ad71"),
+                hex_to_id("c91d592913d47ac4e4a76daf16fd649b276e211e"),
+            ]
+            .into(),
+            author: signature(1592454703),
+            committer: signature(1592454738),
+            encoding: Some("ISO-8859-1".into()),
+            message: "Merge branch 'branch'".into(),
+            extra_headers: Vec::new(),
+        };
+        for (commit, fixture) in &[(unsigned, "commit/unsigned.txt"), (merge, "commit/merge.txt")] {
+            let mut out = Vec::new();
+            commit.write_to(&mut out)?;
+            assert_eq!(
+                out.as_bstr(),
+                crate::fixture_bytes(fixture).as_bstr(),
+                "the bytes are what git hashes, so they must match exactly"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_extra_header_names_are_rejected_before_writing_anything() -> crate::Result {
+        use git_object::{commit::write::Error, Commit, CommitRef, WriteTo};
+
+        let data = crate::fixture_bytes("commit/unknown-headers.txt");
+        for name in &[
+            "",
+            "with space",
+            "with\nnewline",
+            "tree",
+            "parent",
+            "author",
+            "committer",
+            "encoding",
+        ] {
+            let mut commit: Commit = CommitRef::from_bytes(&data)?.into();
+            commit.extra_headers.push(((*name).into(), "value".into()));
+
+            let mut out = Vec::new();
+            let err = commit.write_to(&mut out).unwrap_err();
+            match err.get_ref().and_then(|err| err.downcast_ref::<Error>()) {
+                Some(Error::InvalidExtraHeaderName { name: actual }) => assert_eq!(actual, name),
+                _ => panic!("unexpected error: {:?}", err),
+            }
+            assert!(out.is_empty(), "nothing is written to avoid creating corrupt objects");
+        }
+        Ok(())
+    }
 }
 
 mod tree {
@@ -0,0 +1,294 @@
+use git_actor::{Sign, Time};
+use git_object::{
+    bstr::ByteSlice,
+    ident::{Error, IdentRef, Malformation, Mode, Report},
+    CommitRef, CommitRefIter, TagRef,
+};
+
+use crate::fixture_bytes;
+
+fn write(report: &Report<'_>, mode: Mode) -> Vec<u8> {
+    let mut out = Vec::new();
+    report.write_to(mode, &mut out).expect("write to memory works");
+    out
+}
+
+mod ident_ref {
+    use super::*;
+
+    fn malformations(raw: &str) -> Vec<Malformation> {
+        IdentRef::from_bytes(raw.as_bytes())
+            .expect("lenient parsing works")
+            .malformations
+    }
+
+    fn repaired(raw: &str) -> String {
+        let mut out = Vec::new();
+        IdentRef::from_bytes(raw.as_bytes())
+            .expect("lenient parsing works")
+            .write_to(Mode::Repair, &mut out)
+            .expect("write to memory works");
+        out.to_str().expect("valid UTF-8").to_owned()
+    }
+
+    #[test]
+    fn well_formed_lines_are_decoded_like_strict_parsing_does() {
+        let raw = b"Sebastian Thiel <byronimo@gmail.com> 1528473343 -0230";
+        let ident = IdentRef::from_bytes(raw).unwrap();
+        assert!(!ident.is_malformed());
+        assert_eq!(ident.signature, git_actor::SignatureRef::from_bytes::<()>(raw).unwrap());
+        assert_eq!(
+            repaired("Sebastian Thiel <byronimo@gmail.com> 1528473343 -0230"),
+            raw.to_str_lossy()
+        );
+    }
+
+    #[test]
+    fn each_malformation_is_reported_and_repaired() {
+        for (raw, expected, expected_repaired) in &[
+            (
+                "Jane Doe<jane@example.com> 1136073600 +0100",
+                vec![Malformation::MissingSpaceBeforeEmail],
+                "Jane Doe <jane@example.com> 1136073600 +0100",
+            ),
+            (
+                "<jane@example.com> 1136073600 +0100",
+                vec![Malformation::MissingSpaceBeforeEmail],
+                " <jane@example.com> 1136073600 +0100",
+            ),
+            (
+                "Jane Doe <jane@example.com> <jane@example.org> <jd@example.net> 1136073600 +0100",
+                vec![Malformation::MultipleEmails],
+                "Jane Doe <jane@example.com> 1136073600 +0100",
+            ),
+            (
+                "Jane Doe <jane@example.com 1136073600 +0100",
+                vec![Malformation::MissingEmailEnd],
+                "Jane Doe <jane@example.com> 1136073600 +0100",
+            ),
+            (
+                "Jane Doe <jane@example.com> 1136073600 +2500",
+                vec![Malformation::BadTimezone],
+                "Jane Doe <jane@example.com> 1136073600 +0000",
+            ),
+            (
+                "Jane Doe <jane@example.com> 1136073600 -0160",
+                vec![Malformation::BadTimezone],
+                "Jane Doe <jane@example.com> 1136073600 +0000",
+            ),
+            (
+                "Jane Doe <jane@example.com> 1136073600 +051800",
+                vec![Malformation::BadTimezone],
+                "Jane Doe <jane@example.com> 1136073600 +0000",
+            ),
+            (
+                "Jane Doe<jane@example.com 1136073600 0100",
+                vec![
+                    Malformation::MissingSpaceBeforeEmail,
+                    Malformation::MissingEmailEnd,
+                    Malformation::BadTimezone,
+                ],
+                "Jane Doe <jane@example.com> 1136073600 +0000",
+            ),
+        ] {
+            assert_eq!(&malformations(raw), expected, "{}", raw);
+            assert_eq!(repaired(raw), *expected_repaired, "{}", raw);
+
+            let mut preserved = Vec::new();
+            IdentRef::from_bytes(raw.as_bytes())
+                .unwrap()
+                .write_to(Mode::Preserve, &mut preserved)
+                .unwrap();
+            assert_eq!(
+                preserved.as_bstr(),
+                raw.as_bytes().as_bstr(),
+                "preserving writes the original"
+            );
+        }
+    }
+
+    #[test]
+    fn malformations_map_to_fsck_msg_ids() {
+        assert_eq!(
+            [
+                Malformation::MissingSpaceBeforeEmail,
+                Malformation::MultipleEmails,
+                Malformation::MissingEmailEnd,
+                Malformation::BadTimezone
+            ]
+            .iter()
+            .map(Malformation::fsck_msg_id)
+            .collect::<Vec<_>>(),
+            vec!["missingSpaceBeforeEmail", "badDate", "badEmail", "badTimezone"]
+        );
+    }
+
+    #[test]
+    fn lines_beyond_repair_are_errors() {
+        for (raw, expected) in &[
+            ("Jane Doe jane@example.com 1136073600 +0100", "MissingEmail"),
+            ("Jane > Doe <jane@example.com> 1136073600 +0100", "BadName"),
+            ("Jane Doe <jane <jane@example.com> 1136073600 +0100", "BadEmail"),
+            ("Jane Doe <jane@example.com> and more 1136073600 +0100", "BadEmail"),
+            ("Jane Doe <jane@example.com> +0100", "BadDate"),
+            ("Jane Doe <jane@example.com> yesterday +0100", "BadDate"),
+        ] {
+            let err = IdentRef::from_bytes(raw.as_bytes()).expect_err(raw);
+            assert_eq!(
+                match err {
+                    Error::MissingEmail => "MissingEmail",
+                    Error::BadName => "BadName",
+                    Error::BadEmail => "BadEmail",
+                    Error::BadDate => "BadDate",
+                },
+                *expected,
+                "{}",
+                raw
+            );
+        }
+    }
+}
+
+mod report {
+    use super::*;
+
+    #[test]
+    fn well_formed_objects_have_no_malformations() -> crate::Result {
+        let data = fixture_bytes("commit/unsigned.txt");
+        let report = Report::from_bytes(&data)?;
+        assert_eq!(
+            report.fields.iter().map(|f| f.name).collect::<Vec<_>>(),
+            vec!["author", "committer"]
+        );
+        assert!(!report.is_malformed());
+        assert_eq!(write(&report, Mode::Repair), data, "nothing to repair");
+        Ok(())
+    }
+
+    #[test]
+    fn commit_with_malformed_emails() -> crate::Result {
+        let data = fixture_bytes("commit/malformed-emails.txt");
+        let report = Report::from_bytes(&data)?;
+        assert_eq!(
+            report.malformations().collect::<Vec<_>>(),
+            vec![
+                ("author".into(), Malformation::MultipleEmails),
+                ("committer".into(), Malformation::MissingSpaceBeforeEmail)
+            ]
+        );
+        assert_eq!(write(&report, Mode::Preserve), data, "preserving keeps the object id");
+        assert_eq!(
+            write(&report, Mode::Repair).as_bstr(),
+            "tree 1b2dfb4ac5e42080b682fc676e9738c94ce6d54d\n\
+             parent e31ba8e4bbfc7b02f3d0a1ec7a4c9f5e49d8a8d1\n\
+             author Jane Doe <jane@example.com> 1136073600 -0800\n\
+             committer Jane Doe <jane@example.com> 1136073600 -0800\n\
+             \n\
+             two emails and a missing space\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn commit_with_missing_bracket_and_bad_timezone() -> crate::Result {
+        let data = fixture_bytes("commit/malformed-bracket-and-timezone.txt");
+        let report = Report::from_bytes(&data)?;
+        assert_eq!(
+            report.malformations().collect::<Vec<_>>(),
+            vec![
+                ("author".into(), Malformation::MissingEmailEnd),
+                ("committer".into(), Malformation::BadTimezone)
+            ]
+        );
+        assert_eq!(write(&report, Mode::Preserve), data, "preserving keeps the object id");
+        let repaired = write(&report, Mode::Repair);
+        assert_eq!(
+            repaired.as_bstr(),
+            "tree 1b2dfb4ac5e42080b682fc676e9738c94ce6d54d\n\
+             author John Doe <john@example.com> 1136073600 +0100\n\
+             committer John Doe <john@example.com> 1136073600 +0000\n\
+             \n\
+             missing bracket and timezone out of range\n"
+        );
+        assert!(
+            !Report::from_bytes(&repaired)?.is_malformed(),
+            "repaired objects are well-formed"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn tag_with_bad_timezone() -> crate::Result {
+        let data = fixture_bytes("tag/malformed-timezone.txt");
+        let report = Report::from_bytes(&data)?;
+        assert_eq!(
+            report.malformations().collect::<Vec<_>>(),
+            vec![("tagger".into(), Malformation::BadTimezone)]
+        );
+        let field = &report.fields[0];
+        assert_eq!(
+            data[field.range.clone()].as_bstr(),
+            "Jane Doe <jane@example.com> 1136073600 +2500",
+            "the range points to the identity"
+        );
+        assert_eq!(write(&report, Mode::Preserve), data);
+        assert_eq!(
+            write(&report, Mode::Repair).as_bstr(),
+            "object c39ae07f393806ccf406ef966e9a15afc43cc36a\n\
+             type commit\n\
+             tag v0.99\n\
+             tagger Jane Doe <jane@example.com> 1136073600 +0000\n\
+             \n\
+             A tagger with a timezone out of range\n"
+        );
+        Ok(())
+    }
+}
+
+mod decode {
+    use super::*;
+
+    #[test]
+    fn malformed_commits_can_be_decoded_and_iterated() -> crate::Result {
+        let data = fixture_bytes("commit/malformed-emails.txt");
+        let commit = CommitRef::from_bytes(&data)?;
+        assert_eq!(commit.author.email, "jane@example.com");
+        assert_eq!(commit.committer.name, "Jane Doe");
+        assert_eq!(
+            commit.committer.time,
+            Time {
+                time: 1136073600,
+                offset: -8 * 3600,
+                sign: Sign::Minus
+            }
+        );
+        assert_eq!(commit.message, "two emails and a missing space\n");
+        assert_eq!(
+            CommitRefIter::from_bytes(&data).signatures().collect::<Vec<_>>(),
+            vec![commit.author.clone(), commit.committer.clone()]
+        );
+
+        let data = fixture_bytes("commit/malformed-bracket-and-timezone.txt");
+        let commit = CommitRef::from_bytes(&data)?;
+        assert_eq!(commit.author.email, "john@example.com");
+        assert_eq!(commit.committer.time.offset, 0, "bad timezones are treated as UTC");
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_tags_can_be_decoded() -> crate::Result {
+        let data = fixture_bytes("tag/malformed-timezone.txt");
+        let tag = TagRef::from_bytes(&data)?;
+        assert_eq!(
+            tag.tagger.map(|t| t.time),
+            Some(Time {
+                time: 1136073600,
+                offset: 25 * 3600,
+                sign: Sign::Plus
+            }),
+            "timezones out of range have the right format and are kept by strict parsing, only the report flags them"
+        );
+        assert_eq!(tag.message, "A tagger with a timezone out of range\n");
+        Ok(())
+    }
+}
@@ -82,6 +82,56 @@ fn mergetag() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn unknown_headers_are_kept_in_order() -> crate::Result {
+    let fixture = fixture_bytes("commit", "unknown-headers.txt");
+    let commit = CommitRef::from_bytes(&fixture)?;
+    assert_eq!(
+        commit,
+        CommitRef {
+            tree: b"1b2dfb4ac5e42080b682fc676e9738c94ce6d54d".as_bstr(),
+            parents: SmallVec::default(),
+            author: signature(1592437401),
+            committer: signature(1592437401),
+            encoding: None,
+            message: b"with headers unknown to git\n".as_bstr(),
+            extra_headers: vec![
+                (
+                    b"HG:extra".as_bstr(),
+                    b"rebase_source:7d3f2a2a3a1b0a3d0c1e9b8a7f6e5d4c3b2a1f0e"
+                        .as_bstr()
+                        .into()
+                ),
+                (b"nonce".as_bstr(), b"1592437401-a1b2c3d4".as_bstr().into()),
+                (
+                    b"HG:extra".as_bstr(),
+                    std::borrow::Cow::Owned(
+                        b"convert_revision:svn:1234\nfirst continuation\n\nafter an empty continuation"[..].into()
+                    )
+                ),
+            ]
+        }
+    );
+    assert_eq!(
+        commit.extra_headers().find("HG:extra"),
+        Some(b"rebase_source:7d3f2a2a3a1b0a3d0c1e9b8a7f6e5d4c3b2a1f0e".as_bstr()),
+        "the first of multiple headers with the same name is found"
+    );
+    assert_eq!(commit.extra_headers().find_all("HG:extra").count(), 2);
+    assert_eq!(
+        commit.extra_headers().find("nonce"),
+        Some(b"1592437401-a1b2c3d4".as_bstr())
+    );
+    assert_eq!(commit.extra_headers().find("gpgsig"), None);
+
+    let owned: git_object::Commit = commit.clone().into();
+    assert_eq!(
+        owned.extra_headers().find_all("HG:extra").collect::<Vec<_>>(),
+        commit.extra_headers().find_all("HG:extra").collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
 #[test]
 fn signed() -> crate::Result {
     assert_eq!(
@@ -154,6 +154,39 @@ fn mergetag() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn unknown_headers() -> crate::Result {
+    assert_eq!(
+        CommitRefIter::from_bytes(&fixture_bytes("commit", "unknown-headers.txt")).collect::<Result<Vec<_>, _>>()?,
+        vec![
+            Token::Tree {
+                id: hex_to_id("1b2dfb4ac5e42080b682fc676e9738c94ce6d54d")
+            },
+            Token::Author {
+                signature: signature(1592437401)
+            },
+            Token::Committer {
+                signature: signature(1592437401)
+            },
+            Token::ExtraHeader((
+                b"HG:extra".as_bstr(),
+                b"rebase_source:7d3f2a2a3a1b0a3d0c1e9b8a7f6e5d4c3b2a1f0e"
+                    .as_bstr()
+                    .into()
+            )),
+            Token::ExtraHeader((b"nonce".as_bstr(), b"1592437401-a1b2c3d4".as_bstr().into())),
+            Token::ExtraHeader((
+                b"HG:extra".as_bstr(),
+                b"convert_revision:svn:1234\nfirst continuation\n\nafter an empty continuation"
+                    .as_bstr()
+                    .into()
+            )),
+            Token::Message(b"with headers unknown to git\n".as_bstr()),
+        ]
+    );
+    Ok(())
+}
+
 mod method {
     use git_object::CommitRefIter;
 
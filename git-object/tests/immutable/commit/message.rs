@@ -220,6 +220,169 @@ mod body {
     }
 }
 
+mod trailers {
+    use git_object::{
+        bstr::ByteSlice,
+        commit::{MessageTrailers, Trailer},
+        CommitRef,
+    };
+
+    use crate::immutable::fixture_bytes;
+
+    fn trailers(message: &str) -> Vec<(String, String)> {
+        MessageTrailers::from_message(message.as_bytes().as_bstr())
+            .iter()
+            .map(|t| (t.token.to_string(), t.value.to_string()))
+            .collect()
+    }
+
+    fn pairs(input: &[(&str, &str)]) -> Vec<(String, String)> {
+        input.iter().map(|(t, v)| (t.to_string(), v.to_string())).collect()
+    }
+
+    fn fixture_trailers(name: &str) -> crate::Result<Vec<(String, String)>> {
+        let backing = fixture_bytes("commit", name);
+        let commit = CommitRef::from_bytes(&backing)?;
+        Ok(MessageTrailers::from_message(commit.message)
+            .iter()
+            .map(|t| (t.token.to_string(), t.value.to_string()))
+            .collect())
+    }
+
+    #[test]
+    fn from_commit_fixtures() -> crate::Result {
+        let backing = fixture_bytes("commit", "message-with-footer.txt");
+        let commit = CommitRef::from_bytes(&backing)?;
+        assert_eq!(
+            MessageTrailers::from_message(commit.message).iter().collect::<Vec<_>>(),
+            vec![
+                Trailer {
+                    token: "Signed-off-by".into(),
+                    value: "Sebastian Thiel <sebastian.thiel@icloud.com>".into()
+                },
+                Trailer {
+                    token: "Signed-off-by".into(),
+                    value: "Kim Altintop <kim@eagain.st>".into()
+                }
+            ]
+        );
+        assert_eq!(
+            fixture_trailers("two-multiline-headers.txt")?,
+            pairs(&[("Signed-off-by", "Maxime Ripard <maxime@cerno.tech>")])
+        );
+        for name in &["mergetag.txt", "signed-whitespace.txt", "unsigned.txt", "merge.txt"] {
+            assert_eq!(fixture_trailers(name)?, vec![], "{} has no trailers", name);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn the_title_never_contains_trailers() {
+        assert!(MessageTrailers::from_message("fix: something".into()).is_empty());
+        assert!(MessageTrailers::from_message("fix: something\n\n\n".into()).is_empty());
+        assert_eq!(
+            trailers("fix: something\n\nFixes: #123\n"),
+            pairs(&[("Fixes", "#123")]),
+            "the body may consist of a trailer paragraph only"
+        );
+    }
+
+    #[test]
+    fn only_the_last_paragraph_is_considered() {
+        assert_eq!(
+            trailers("title\n\nA: b\n\nbody\n\nC: d\nE-f #42\n\n  \n"),
+            pairs(&[("C", "d"), ("E-f", "42")]),
+            "trailing blank lines are ignored, and '#' is a separator as well"
+        );
+        assert_eq!(trailers("title\n\nA: b\n\nbody"), vec![]);
+    }
+
+    #[test]
+    fn tokens_are_alphanumeric_with_dashes_and_may_be_followed_by_whitespace() {
+        assert_eq!(
+            trailers("title\n\nToken : value  \nCo-authored-by:name"),
+            pairs(&[("Token", "value"), ("Co-authored-by", "name")])
+        );
+        for invalid in &["title\n\nno trailer: value", "title\n\n[link]: url", "title\n\n: value"] {
+            assert_eq!(trailers(invalid), vec![], "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn values_can_span_continuation_lines() {
+        assert_eq!(
+            trailers("title\n\nA: first\n  second\r\n\tthird\nB: c"),
+            pairs(&[("A", "first\n  second\r\n\tthird"), ("B", "c")])
+        );
+    }
+
+    #[test]
+    fn mixed_paragraphs_need_a_git_generated_trailer_and_a_quarter_of_trailer_lines() {
+        let mixed = "title\n\nsome text\nmore text\nA: b\n";
+        assert_eq!(trailers(mixed), vec![], "not all lines are trailers");
+        assert_eq!(
+            trailers(&format!("{}Signed-off-by: me\n", mixed)),
+            pairs(&[("A", "b"), ("Signed-off-by", "me")]),
+            "non-trailer lines are skipped"
+        );
+        assert_eq!(
+            trailers("title\n\n1\n2\n3\n4\n5\n6\n7\nSigned-off-by: me\nA: b"),
+            vec![],
+            "less than 25% of the lines are trailers"
+        );
+        assert_eq!(
+            trailers("title\n\n1\n2\n3\n4\n5\n6\nSigned-off-by: me\n  continued\n"),
+            vec![],
+            "continuation lines don't count as trailer lines"
+        );
+        assert_eq!(
+            trailers("title\n\ntext\n# comment\n  continued\nSigned-off-by: me\n"),
+            pairs(&[("Signed-off-by", "me")]),
+            "continuation lines after a comment count as other lines"
+        );
+    }
+
+    #[test]
+    fn comments_and_what_git_adds_below_the_message_are_ignored() {
+        assert_eq!(
+            trailers("title\n\nA: b\n# comment\nC: d\n"),
+            pairs(&[("A", "b"), ("C", "d")])
+        );
+        assert_eq!(
+            trailers("title\n\nA: b\n\n# comment\n#more\n"),
+            pairs(&[("A", "b")]),
+            "trailing comments are removed along with the blank lines before them"
+        );
+        assert_eq!(trailers("title\n\nA: b\nConflicts:\n\tfile\n"), pairs(&[("A", "b")]));
+        assert_eq!(
+            trailers("title\n\nA: b\n# ------------------------ >8 ------------------------\nnot a trailer\n"),
+            pairs(&[("A", "b")]),
+            "everything below the scissors line is ignored"
+        );
+    }
+
+    #[test]
+    fn a_divider_line_terminates_the_message() {
+        assert_eq!(
+            trailers("title\n\nA: b\n---\nC: d\n"),
+            pairs(&[("A", "b")]),
+            "everything after the divider is ignored, like a patch"
+        );
+        assert_eq!(
+            trailers("title\n\nA: b\n--- a/file\n+++ b/file\n"),
+            pairs(&[("A", "b")])
+        );
+        for not_a_divider in &["title\n\nA: b\n---not-a-divider", "title\n\nA: b\n---"] {
+            assert_eq!(
+                trailers(not_a_divider),
+                vec![],
+                "dashes must be followed by whitespace: {:?}",
+                not_a_divider
+            );
+        }
+    }
+}
+
 mod summary {
     use std::borrow::Cow;
 
@@ -56,7 +56,7 @@ mod iter {
 }
 
 mod from_bytes {
-    use git_object::{bstr::ByteSlice, tree, tree::EntryRef, TreeRef};
+    use git_object::{bstr::ByteSlice, tree, tree::EntryRef, TreeRef, TreeRefIter};
 
     use crate::{hex_to_id, immutable::fixture_bytes};
 
@@ -107,6 +107,20 @@ mod from_bytes {
         Ok(())
     }
 
+    #[test]
+    fn entries_are_decoded_like_the_iterator_does() -> crate::Result {
+        let mut data = b"100644 \0".to_vec();
+        data.extend_from_slice(&[1; 20]);
+        let entries = TreeRef::from_bytes(&data)?.entries;
+        assert_eq!(entries.len(), 1, "empty filenames are left for verification to reject");
+        assert_eq!(entries, TreeRefIter::from_bytes(&data).entries()?);
+
+        data.pop();
+        assert!(TreeRef::from_bytes(&data).is_err(), "truncated entries are an error");
+        assert!(TreeRefIter::from_bytes(&data).entries().is_err());
+        Ok(())
+    }
+
     #[test]
     fn maybe_special() -> crate::Result {
         assert_eq!(
@@ -131,6 +145,8 @@ mod from_bytes {
 }
 
 mod entry_mode {
+    use std::convert::TryFrom;
+
     use git_object::tree::EntryMode;
 
     #[test]
@@ -141,4 +157,625 @@ mod entry_mode {
             "it should not change without notiice"
         );
     }
+
+    #[test]
+    fn mode_bits_round_trip_and_display_as_zero_padded_octal() {
+        for (mode, bits, display) in [
+            (EntryMode::Tree, 0o040000, "040000"),
+            (EntryMode::Blob, 0o100644, "100644"),
+            (EntryMode::BlobExecutable, 0o100755, "100755"),
+            (EntryMode::Link, 0o120000, "120000"),
+            (EntryMode::Commit, 0o160000, "160000"),
+        ] {
+            assert_eq!(mode.to_mode_bits(), bits);
+            assert_eq!(EntryMode::try_from(bits), Ok(mode));
+            assert_eq!(mode.to_string(), display);
+        }
+    }
+
+    #[test]
+    fn invalid_mode_bits_are_rejected() {
+        for bits in [0, 0o100600, 0o040755, 0o644] {
+            assert_eq!(EntryMode::try_from(bits), Err(bits));
+        }
+    }
+
+    #[test]
+    fn octal_bytes_are_parsed_with_legacy_forms_normalized() {
+        for (bytes, mode) in [
+            (&b"40000"[..], EntryMode::Tree),
+            (b"040000", EntryMode::Tree),
+            (b"100644", EntryMode::Blob),
+            (b"0100644", EntryMode::Blob),
+            (b"100664", EntryMode::Blob),
+            (b"100640", EntryMode::Blob),
+            (b"100755", EntryMode::BlobExecutable),
+            (b"120000", EntryMode::Link),
+            (b"160000", EntryMode::Commit),
+        ] {
+            assert_eq!(EntryMode::try_from(bytes), Ok(mode), "{:?}", bytes);
+        }
+        for bytes in [&b""[..], b"100600", b"100648", b"00000100644", b"1006 44"] {
+            assert_eq!(EntryMode::try_from(bytes), Err(bytes));
+        }
+    }
+
+    #[test]
+    fn kinds() {
+        assert!(EntryMode::Blob.is_blob() && EntryMode::BlobExecutable.is_blob());
+        assert!(!EntryMode::Link.is_blob() && !EntryMode::Commit.is_blob() && !EntryMode::Tree.is_blob());
+        assert!(EntryMode::Tree.is_tree());
+    }
+}
+
+mod verify {
+    use std::process::Command;
+
+    use git_object::{
+        bstr::{BString, ByteSlice},
+        tree,
+        tree::{
+            verify::{Error, Finding, Issue, IssueKind, MessageId, Severity},
+            verify_entry,
+        },
+        Tree, TreeRef, TreeRefIter, WriteTo,
+    };
+
+    use crate::immutable::fixture_bytes;
+
+    const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+    const EMPTY_BLOB: &str = "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391";
+
+    fn ids(name: &str, mode: &str) -> Vec<MessageId> {
+        verify_entry(name.into(), mode.into())
+            .into_iter()
+            .map(|f| f.id)
+            .collect()
+    }
+
+    fn tree_bytes(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for (mode, name) in entries {
+            let id = if mode.ends_with("40000") {
+                EMPTY_TREE
+            } else {
+                EMPTY_BLOB
+            };
+            out.extend_from_slice(mode.as_bytes());
+            out.push(b' ');
+            out.extend_from_slice(name.as_bytes());
+            out.push(0);
+            out.extend_from_slice(git_testtools::hex_to_id(id).as_bytes());
+        }
+        out
+    }
+
+    #[test]
+    fn valid_entries_have_no_findings() {
+        for (name, mode) in &[
+            ("file", "100644"),
+            ("exe", "100755"),
+            ("link", "120000"),
+            ("dir", "40000"),
+            ("submodule", "160000"),
+            (".gitignore", "100644"),
+            (".github", "40000"),
+            ("git~2", "100644"),
+            ("...", "100644"),
+            (".g\u{200b}it", "100644"),
+        ] {
+            assert_eq!(ids(name, mode), vec![], "{} {}", mode, name);
+        }
+    }
+
+    #[test]
+    fn invalid_names_are_errors() {
+        for (name, expected) in &[
+            ("", MessageId::EmptyName),
+            ("a/b", MessageId::FullPathname),
+            (".", MessageId::HasDot),
+            ("..", MessageId::HasDotdot),
+            (".git", MessageId::HasDotgit),
+            (".GIT", MessageId::HasDotgit),
+            (".Git. . ", MessageId::HasDotgit),
+            ("GIT~1", MessageId::HasDotgit),
+            ("git~1.", MessageId::HasDotgit),
+            (".git:stream", MessageId::HasDotgit),
+            (".g\u{200c}it", MessageId::HasDotgit),
+            ("\u{feff}.GI\u{206a}T", MessageId::HasDotgit),
+            ("a\0b", MessageId::BadTree),
+        ] {
+            assert_eq!(
+                verify_entry((*name).into(), "100644".into()),
+                vec![Finding {
+                    id: *expected,
+                    severity: Severity::Error
+                }],
+                "{:?}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn modes() {
+        let warning = |id| Finding {
+            id,
+            severity: Severity::Warning,
+        };
+        assert_eq!(
+            verify_entry("dir".into(), "040000".into()),
+            vec![warning(MessageId::ZeroPaddedFilemode)]
+        );
+        assert_eq!(
+            verify_entry("file".into(), "100664".into()),
+            vec![warning(MessageId::BadFilemode)]
+        );
+        assert_eq!(
+            verify_entry("file".into(), "0100644".into()),
+            vec![warning(MessageId::ZeroPaddedFilemode)],
+            "the numeric value of the mode is what matters"
+        );
+        for mode in &["100640", "100600", "100000", "140000", "1"] {
+            assert_eq!(
+                verify_entry("file".into(), mode.as_bytes().as_bstr()),
+                vec![warning(MessageId::BadFilemode)],
+                "{}",
+                mode
+            );
+        }
+    }
+
+    #[test]
+    fn strict_parsing_fails_on_errors_but_not_on_warnings() -> crate::Result {
+        let tree = tree_bytes(&[("100640", "a"), ("100664", "b")]);
+        assert_eq!(TreeRef::from_bytes_strict(&tree)?, TreeRef::from_bytes(&tree)?);
+        assert_eq!(
+            TreeRef::from_bytes_strict(&tree_bytes(&[("040000", "dir")]))?.entries[0].mode,
+            tree::EntryMode::Tree
+        );
+
+        let tree = tree_bytes(&[("100644", "a"), ("100644", ".GIT")]);
+        assert!(TreeRef::from_bytes(&tree).is_ok(), "lenient parsing accepts these");
+        match TreeRef::from_bytes_strict(&tree) {
+            Err(Error::InvalidEntry { filename, finding }) => {
+                assert_eq!(filename, BString::from(".GIT"));
+                assert_eq!(finding.id, MessageId::HasDotgit);
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        let tree = tree_bytes(&[("100644", "a/b")]);
+        assert!(
+            matches!(TreeRef::from_bytes_strict(&tree), Err(Error::InvalidEntry { .. })),
+            "the entry is checked before it is decoded"
+        );
+        let tree = tree_bytes(&[("100600", "file")]);
+        assert!(
+            matches!(TreeRef::from_bytes_strict(&tree), Err(Error::Decode(_))),
+            "modes unknown to git-object are decode errors"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn strict_parsing_accepts_fixtures() -> crate::Result {
+        for name in &["everything.tree", "maybe-special.tree", "definitely-special.tree"] {
+            let data = fixture_bytes("tree", name);
+            assert_eq!(TreeRef::from_bytes_strict(&data)?, TreeRef::from_bytes(&data)?);
+        }
+        Ok(())
+    }
+
+    /// Run `git fsck --strict` on a tree with a single entry and return the message ids it reports for it.
+    fn git_fsck_ids(mode: &str, name: &str) -> crate::Result<Vec<String>> {
+        let dir = git_testtools::tempfile::tempdir()?;
+        let git = |args: &[&str]| {
+            let mut cmd = Command::new("git");
+            cmd.current_dir(dir.path()).args(args);
+            cmd
+        };
+        assert!(git(&["init", "--quiet"]).status()?.success());
+        let mut child = git(&["hash-object", "-t", "tree", "--literally", "-w", "--stdin"])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()?;
+        std::io::Write::write_all(&mut child.stdin.take().expect("piped"), &tree_bytes(&[(mode, name)]))?;
+        assert!(child.wait_with_output()?.status.success());
+        let kind = if mode.ends_with("40000") { "tree" } else { "blob" };
+        assert!(git(&["hash-object", "-t", kind, "-w", "/dev/null"])
+            .output()?
+            .status
+            .success());
+
+        let output = git(&["fsck", "--strict", "--no-dangling"]).output()?;
+        let mut ids: Vec<_> = output
+            .stderr
+            .lines()
+            .chain(output.stdout.lines())
+            .filter_map(|line| {
+                let line = line.to_str().ok()?;
+                let rest = line
+                    .strip_prefix("error in tree ")
+                    .or_else(|| line.strip_prefix("warning in tree "))?;
+                Some(rest.split(": ").nth(1)?.to_owned())
+            })
+            .collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    #[test]
+    fn findings_match_git_fsck() -> crate::Result {
+        for (mode, name) in &[
+            ("100644", "file"),
+            ("040000", "dir"),
+            ("100664", "file"),
+            ("100644", ".GIT"),
+            ("100644", "git~1"),
+            ("100644", ".git. "),
+            ("100644", ".g\u{200c}it"),
+            ("100644", ".."),
+            ("100644", "."),
+            ("100644", "a/b"),
+            ("100600", "file"),
+        ] {
+            let mut expected: Vec<_> = verify_entry(name.as_bytes().as_bstr(), mode.as_bytes().as_bstr())
+                .into_iter()
+                .map(|f| f.id.as_str().to_owned())
+                .collect();
+            expected.sort();
+            assert_eq!(git_fsck_ids(mode, name)?, expected, "{} {:?}", mode, name);
+        }
+        Ok(())
+    }
+
+    fn issue(offset: usize, filename: &str, kind: IssueKind) -> Issue {
+        Issue {
+            offset,
+            filename: filename.into(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn trees_with_legacy_modes_parse_leniently_and_are_written_with_canonical_modes() -> crate::Result {
+        let data = fixture_bytes("tree", "legacy-modes.tree");
+        let tree = TreeRef::from_bytes(&data)?;
+        assert_eq!(
+            tree.entries.iter().map(|e| e.mode).collect::<Vec<_>>(),
+            vec![
+                tree::EntryMode::Tree,
+                tree::EntryMode::BlobExecutable,
+                tree::EntryMode::Blob
+            ]
+        );
+        assert_eq!(TreeRefIter::from_bytes(&data).entries()?, tree.entries);
+
+        assert_eq!(
+            TreeRefIter::from_bytes(&data).verify()?,
+            vec![
+                issue(0, "dir", IssueKind::BadMode),
+                issue(62, "file", IssueKind::BadMode)
+            ]
+        );
+        assert!(IssueKind::BadMode.severity() == Severity::Warning);
+
+        let tree: Tree = tree.into();
+        assert_eq!(tree.verify(), vec![], "modes are normalized when decoding");
+        let mut out = Vec::new();
+        tree.write_to(&mut out)?;
+        assert_eq!(
+            out,
+            tree_bytes(&[("40000", "dir"), ("100755", "exe"), ("100644", "file")]),
+            "canonical modes are written"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn trees_with_duplicate_names_parse_leniently_but_are_not_written_unless_unchecked() -> crate::Result {
+        let data = fixture_bytes("tree", "duplicate-names.tree");
+        let tree = TreeRef::from_bytes(&data)?;
+        assert_eq!(tree.entries.len(), 5);
+
+        let expected = vec![
+            issue(60, "a", IssueKind::DuplicateName),
+            issue(117, "b", IssueKind::DuplicateName),
+        ];
+        assert_eq!(TreeRefIter::from_bytes(&data).verify()?, expected);
+        let tree: Tree = tree.into();
+        assert_eq!(tree.verify(), expected);
+
+        let mut out = Vec::new();
+        let err = tree.write_to(&mut out).unwrap_err();
+        assert!(matches!(
+            err.get_ref()
+                .and_then(|err| err.downcast_ref::<tree::write::Error>()),
+            Some(tree::write::Error::DuplicateName(name)) if name == "a"
+        ));
+        assert!(out.is_empty(), "nothing is written");
+
+        tree.write_to_unchecked(&mut out)?;
+        assert_eq!(out.as_bstr(), data.as_bstr(), "broken trees round-trip byte-for-byte");
+        Ok(())
+    }
+
+    #[test]
+    fn unsorted_trees_parse_leniently_but_are_not_written_unless_unchecked() -> crate::Result {
+        let data = fixture_bytes("tree", "unsorted.tree");
+        let expected = vec![issue(29, "a", IssueKind::UnsortedEntries)];
+        assert_eq!(TreeRefIter::from_bytes(&data).verify()?, expected);
+        let tree: Tree = TreeRef::from_bytes(&data)?.into();
+        assert_eq!(tree.verify(), expected);
+
+        let mut out = Vec::new();
+        let err = tree.write_to(&mut out).unwrap_err();
+        assert!(matches!(
+            err.get_ref()
+                .and_then(|err| err.downcast_ref::<tree::write::Error>()),
+            Some(tree::write::Error::UnsortedEntries(name)) if name == "a"
+        ));
+        assert!(out.is_empty(), "nothing is written");
+
+        tree.write_to_unchecked(&mut out)?;
+        assert_eq!(out.as_bstr(), data.as_bstr());
+        Ok(())
+    }
+
+    #[test]
+    fn trees_sort_like_git_with_trees_sorting_as_if_they_had_a_trailing_slash() {
+        let tree = |entries: &[(&str, &str)]| -> Tree { TreeRef::from_bytes(&tree_bytes(entries)).unwrap().into() };
+        assert_eq!(
+            tree(&[
+                ("100644", "a"),
+                ("100644", "a-b"),
+                ("40000", "a.d"),
+                ("40000", "a0"),
+                ("100644", "a0b")
+            ])
+            .verify(),
+            vec![]
+        );
+        assert_eq!(
+            tree(&[("100644", "a-b"), ("40000", "a")]).verify(),
+            vec![],
+            "'a/' sorts after 'a-b'"
+        );
+        assert_eq!(
+            tree(&[("40000", "a"), ("100644", "a-b")]).verify(),
+            vec![issue(28, "a-b", IssueKind::UnsortedEntries)]
+        );
+    }
+
+    #[test]
+    fn entry_issues_are_reported_with_their_offset() -> crate::Result {
+        let data = tree_bytes(&[("100644", ".."), ("100644", ".GIT"), ("100644", "a/b")]);
+        assert_eq!(
+            TreeRefIter::from_bytes(&data).verify()?,
+            vec![
+                issue(0, "..", IssueKind::DotGitName),
+                issue(30, ".GIT", IssueKind::DotGitName),
+                issue(62, "a/b", IssueKind::InvalidName)
+            ]
+        );
+
+        let tree = Tree {
+            entries: vec![tree::Entry {
+                mode: tree::EntryMode::Blob,
+                filename: "a\0b".into(),
+                oid: git_testtools::hex_to_id(EMPTY_BLOB),
+            }],
+        };
+        assert_eq!(tree.verify(), vec![issue(0, "a\0b", IssueKind::NullInName)]);
+        assert!(IssueKind::NullInName.severity() == Severity::Error);
+        Ok(())
+    }
+
+    #[test]
+    fn fixtures_have_no_errors() -> crate::Result {
+        for name in &["everything.tree", "maybe-special.tree", "definitely-special.tree"] {
+            let data = fixture_bytes("tree", name);
+            assert!(
+                TreeRefIter::from_bytes(&data)
+                    .verify()?
+                    .iter()
+                    .all(|issue| issue.kind.severity() == Severity::Warning),
+                "{} only has unusual modes, like 100664",
+                name
+            );
+            assert_eq!(Tree::from(TreeRef::from_bytes(&data)?).verify(), vec![], "{}", name);
+        }
+        Ok(())
+    }
+}
+
+mod stream {
+    use git_object::{
+        bstr::BString,
+        tree,
+        tree::{
+            stream::Parser,
+            verify::{Error, Finding},
+        },
+        TreeRef, TreeRefIter,
+    };
+
+    use crate::immutable::fixture_bytes;
+
+    const EMPTY_BLOB: &str = "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391";
+
+    #[derive(Debug, PartialEq)]
+    enum Failure {
+        Decode,
+        InvalidEntry { filename: BString, finding: Finding },
+    }
+
+    impl From<Error> for Failure {
+        fn from(err: Error) -> Self {
+            match err {
+                Error::Decode(_) => Failure::Decode,
+                Error::InvalidEntry { filename, finding } => Failure::InvalidEntry { filename, finding },
+            }
+        }
+    }
+
+    type Outcome = (Vec<tree::Entry>, Option<Failure>);
+    type Chunking = (&'static str, fn() -> Box<dyn Iterator<Item = usize>>);
+
+    fn streamed(mut data: &[u8], chunk_sizes: impl Iterator<Item = usize>, strict: bool) -> Outcome {
+        let mut parser = Parser::new().strict(strict);
+        let mut entries = Vec::new();
+        for size in chunk_sizes {
+            if data.is_empty() {
+                break;
+            }
+            let (chunk, rest) = data.split_at(size.min(data.len()));
+            if let Err(err) = parser.push(chunk, |entry| entries.push(entry.into())) {
+                return (entries, Some(err.into()));
+            }
+            data = rest;
+        }
+        (entries, parser.finish().err().map(Into::into))
+    }
+
+    fn sliced(data: &[u8]) -> Outcome {
+        let mut entries = Vec::new();
+        for entry in TreeRefIter::from_bytes(data) {
+            match entry {
+                Ok(entry) => entries.push(entry.into()),
+                Err(_) => return (entries, Some(Failure::Decode)),
+            }
+        }
+        (entries, None)
+    }
+
+    /// Chunk sizes between 1 and 64 bytes from a simple linear congruential generator, to be reproducible.
+    fn random_sizes(mut seed: u64) -> impl Iterator<Item = usize> {
+        std::iter::repeat_with(move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % 64 + 1
+        })
+    }
+
+    fn assert_same_as_slice_parsers(data: &[u8]) {
+        let lenient = sliced(data);
+        let strict = TreeRef::from_bytes_strict(data);
+        let chunkings: Vec<Chunking> = vec![
+            ("whole", || Box::new(std::iter::repeat(usize::MAX))),
+            ("1-byte", || Box::new(std::iter::repeat(1))),
+            ("random-1", || Box::new(random_sizes(1))),
+            ("random-2", || Box::new(random_sizes(2))),
+            ("random-3", || Box::new(random_sizes(3))),
+        ];
+        for (name, sizes) in chunkings {
+            assert_eq!(streamed(data, sizes(), false), lenient, "lenient {} {:?}", name, data);
+
+            let (entries, failure) = streamed(data, sizes(), true);
+            match &strict {
+                Ok(tree) => {
+                    assert_eq!(failure, None, "strict {}", name);
+                    assert_eq!(
+                        entries,
+                        tree.entries
+                            .iter()
+                            .cloned()
+                            .map(Into::into)
+                            .collect::<Vec<tree::Entry>>(),
+                        "strict {}",
+                        name
+                    );
+                }
+                Err(err) => {
+                    let expected = match err {
+                        Error::Decode(_) => Failure::Decode,
+                        Error::InvalidEntry { filename, finding } => Failure::InvalidEntry {
+                            filename: filename.clone(),
+                            finding: *finding,
+                        },
+                    };
+                    assert_eq!(failure, Some(expected), "strict {} {:?}", name, data);
+                    assert_eq!(
+                        entries[..],
+                        lenient.0[..entries.len()],
+                        "entries before the error are the same"
+                    );
+                }
+            }
+        }
+    }
+
+    fn entry(mode: &str, name: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(mode.as_bytes());
+        out.push(b' ');
+        out.extend_from_slice(name);
+        out.push(0);
+        out.extend_from_slice(git_testtools::hex_to_id(EMPTY_BLOB).as_bytes());
+        out
+    }
+
+    #[test]
+    fn fixtures_yield_the_same_entries_in_chunks_of_any_size() {
+        for name in &[
+            "everything.tree",
+            "maybe-special.tree",
+            "definitely-special.tree",
+            "duplicate-names.tree",
+            "unsorted.tree",
+            "legacy-modes.tree",
+        ] {
+            let data = fixture_bytes("tree", name);
+            let (entries, failure) = streamed(&data, std::iter::repeat(1), true);
+            assert_eq!(failure, None, "{}", name);
+            assert!(!entries.is_empty(), "{}", name);
+            assert_same_as_slice_parsers(&data);
+        }
+        assert_same_as_slice_parsers(&[]);
+    }
+
+    #[test]
+    fn truncated_trees_fail_like_the_slice_parsers() {
+        let data = fixture_bytes("tree", "everything.tree");
+        for len in 0..data.len() {
+            assert_same_as_slice_parsers(&data[..len]);
+        }
+    }
+
+    #[test]
+    fn invalid_entries_fail_like_the_slice_parsers() {
+        let valid = entry("100644", b"a");
+        for invalid in &[
+            entry("100644", b".git"),
+            entry("40000", b"a/b"),
+            entry("100644", b".."),
+            entry("100644", b""),
+            entry("100600", b"file"),
+            entry("10a644", b"file"),
+            entry("01006440", b"file"),
+            entry("", b"file"),
+            entry("100644", b".GIT")[..12].to_vec(),
+        ] {
+            let mut data = valid.clone();
+            data.extend_from_slice(invalid);
+            data.extend_from_slice(&valid);
+            assert_same_as_slice_parsers(&data);
+        }
+    }
+
+    #[test]
+    fn the_parser_keeps_failing_after_an_error() {
+        let mut parser = Parser::new().strict(true);
+        let mut count = 0;
+        assert!(matches!(
+            parser.push(&entry("100644", b".git"), |_| count += 1),
+            Err(Error::InvalidEntry { .. })
+        ));
+        assert!(matches!(
+            parser.push(&entry("100644", b"a"), |_| count += 1),
+            Err(Error::Decode(_))
+        ));
+        assert!(parser.finish().is_err());
+        assert_eq!(count, 0, "no entry was emitted");
+    }
 }
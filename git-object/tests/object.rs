@@ -1,7 +1,10 @@
 use std::path::PathBuf;
 
 mod encode;
+mod ident;
 mod immutable;
+mod signature;
+mod tree;
 
 type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
 
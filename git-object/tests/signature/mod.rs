@@ -0,0 +1,143 @@
+use git_object::{
+    bstr::ByteSlice,
+    signature::{self, Keyring, PublicKey, Trust},
+    CommitRef, TagRef,
+};
+
+use crate::fixture_bytes;
+
+struct Key {
+    fingerprint: Vec<u8>,
+    signed_payload: Vec<u8>,
+}
+
+impl PublicKey for &Key {
+    fn id(&self) -> &[u8] {
+        &self.fingerprint
+    }
+
+    fn trust(&self) -> Trust {
+        Trust::Full
+    }
+
+    fn verify(&self, payload: &[u8], signature: &[u8]) -> bool {
+        // A real backend would check the signature packet cryptographically.
+        payload == self.signed_payload.as_slice() && matches!(signature.first(), Some(b) if b & 0x80 != 0)
+    }
+}
+
+impl<'a> Keyring for &'a Key {
+    type PublicKey = &'a Key;
+
+    fn find_key(&self, fingerprint: &[u8]) -> Option<Self::PublicKey> {
+        if fingerprint == self.fingerprint.as_slice() {
+            Some(*self)
+        } else {
+            None
+        }
+    }
+}
+
+fn key(hex_fingerprint: &str, signed_payload_fixture: &str) -> Key {
+    Key {
+        fingerprint: hex::decode(hex_fingerprint).expect("valid hex"),
+        signed_payload: fixture_bytes(signed_payload_fixture),
+    }
+}
+
+mod commit {
+    use super::*;
+
+    #[test]
+    fn payload_is_the_commit_without_its_signature_header() -> crate::Result {
+        let data = fixture_bytes("commit/signed.txt");
+        let commit = CommitRef::from_bytes(&data)?;
+        let (payload, armored_signature) = signature::commit_payload(&commit)?.expect("signed");
+        assert_eq!(payload.as_bstr(), fixture_bytes("commit/signed.payload.txt").as_bstr());
+        assert!(armored_signature.starts_with(b"-----BEGIN PGP SIGNATURE-----\n"));
+        assert_eq!(
+            signature::issuer(armored_signature.as_ref())?,
+            hex::decode("763629fec8788fc35128b5f6ee029d1e5eb40300")?,
+            "the fingerprint is used if present"
+        );
+
+        let data = fixture_bytes("commit/unsigned.txt");
+        assert!(signature::commit_payload(&CommitRef::from_bytes(&data)?)?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn verify_with_key_from_keyring() -> crate::Result {
+        let data = fixture_bytes("commit/signed.txt");
+        let commit = CommitRef::from_bytes(&data)?;
+        let key = key("763629fec8788fc35128b5f6ee029d1e5eb40300", "commit/signed.payload.txt");
+        let outcome = signature::verify_commit_signature(&commit, &key)?;
+        assert_eq!(outcome.key_id, key.fingerprint);
+        assert_eq!(outcome.trust, Trust::Full);
+
+        let mut altered = commit.clone();
+        altered.message = b"altered message".as_bstr();
+        assert!(matches!(
+            signature::verify_commit_signature(&altered, &key),
+            Err(signature::Error::BadSignature { .. })
+        ));
+
+        let other_key = super::key("b3bf8e90a192bf1db125a27ac501b0fceff5cdd5", "commit/signed.payload.txt");
+        assert!(matches!(
+            signature::verify_commit_signature(&commit, &other_key),
+            Err(signature::Error::UnknownKey { .. })
+        ));
+
+        let data = fixture_bytes("commit/unsigned.txt");
+        assert!(matches!(
+            signature::verify_commit_signature(&CommitRef::from_bytes(&data)?, &key),
+            Err(signature::Error::Unsigned)
+        ));
+        Ok(())
+    }
+}
+
+mod tag {
+    use super::*;
+
+    #[test]
+    fn payload_is_everything_before_the_signature() -> crate::Result {
+        let data = fixture_bytes("tag/signed.txt");
+        let tag = TagRef::from_bytes(&data)?;
+        let (payload, armored_signature) = signature::tag_payload(&tag)?.expect("signed");
+        assert_eq!(payload.as_bstr(), fixture_bytes("tag/signed.payload.txt").as_bstr());
+        assert_eq!(
+            signature::issuer(armored_signature)?,
+            hex::decode("c3bc52bd76e2c23bac6ec06a665f99fa9d99966c")?,
+            "armor headers like 'Comment' are skipped"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn verify_with_key_from_keyring() -> crate::Result {
+        let data = fixture_bytes("tag/signed.txt");
+        let tag = TagRef::from_bytes(&data)?;
+        let key = key("c3bc52bd76e2c23bac6ec06a665f99fa9d99966c", "tag/signed.payload.txt");
+        assert_eq!(signature::verify_tag_signature(&tag, &key)?.key_id, key.fingerprint);
+
+        let data = fixture_bytes("tag/empty.txt");
+        assert!(matches!(
+            signature::verify_tag_signature(&TagRef::from_bytes(&data)?, &key),
+            Err(signature::Error::Unsigned)
+        ));
+        Ok(())
+    }
+}
+
+#[test]
+fn damaged_armor_is_detected_by_its_checksum() {
+    let data = fixture_bytes("commit/signed.txt");
+    let commit = CommitRef::from_bytes(&data).expect("valid");
+    let (_, armored_signature) = signature::commit_payload(&commit).expect("valid").expect("signed");
+    let damaged = armored_signature.replace("iQEz", "iQE0");
+    assert!(matches!(
+        signature::issuer(&damaged),
+        Err(signature::Error::Armor("checksum mismatch"))
+    ));
+}
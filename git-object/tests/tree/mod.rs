@@ -0,0 +1,201 @@
+mod editor {
+    use std::{
+        collections::{hash_map::DefaultHasher, HashMap},
+        hash::{Hash, Hasher},
+    };
+
+    use git_hash::ObjectId;
+    use git_object::{
+        bstr::{BString, ByteSlice},
+        tree::{editor, Editor, EntryMode},
+        Tree, WriteTo,
+    };
+
+    use crate::hex_to_id;
+
+    fn blob() -> ObjectId {
+        hex_to_id("b4f17b61de71d9b2e54ac9e62b1629ae2d97a6a7")
+    }
+
+    fn other_blob() -> ObjectId {
+        hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391")
+    }
+
+    /// Trees by an id derived from their serialization, which fails if entries aren't sorted like git expects.
+    #[derive(Default)]
+    struct Trees(HashMap<ObjectId, Tree>);
+
+    impl Trees {
+        fn write(&mut self, tree: &Tree) -> std::io::Result<ObjectId> {
+            let mut buf = Vec::new();
+            tree.write_to(&mut buf)?;
+            let mut hasher = DefaultHasher::new();
+            buf.hash(&mut hasher);
+            let mut id = [0u8; 20];
+            id[..8].copy_from_slice(&hasher.finish().to_be_bytes());
+            let id = ObjectId::from_20_bytes(&id);
+            self.0.insert(id, tree.clone());
+            Ok(id)
+        }
+
+        /// All entries of the tree with `id` and its subtrees as `<mode> <path>`, in the order they are stored.
+        fn listing(&self, id: ObjectId) -> Vec<String> {
+            let mut out = Vec::new();
+            self.list(id, "", &mut out);
+            out
+        }
+
+        fn list(&self, id: ObjectId, dir: &str, out: &mut Vec<String>) {
+            for entry in &self.0[&id].entries {
+                let path = format!("{}{}", dir, entry.filename.to_str_lossy());
+                out.push(format!("{} {}", entry.mode, path));
+                if entry.mode.is_tree() {
+                    self.list(entry.oid, &format!("{}/", path), out);
+                }
+            }
+        }
+    }
+
+    fn no_find(_: &git_hash::oid) -> Option<Tree> {
+        None
+    }
+
+    fn tree_with(trees: &mut Trees, paths: &[&str]) -> crate::Result<ObjectId> {
+        let mut editor = Editor::new(Tree::empty(), no_find);
+        for path in paths {
+            editor.upsert(path, EntryMode::Blob, blob())?;
+        }
+        Ok(editor.write(|tree| trees.write(tree))?)
+    }
+
+    #[test]
+    fn nested_trees_are_created_with_entries_in_git_order() -> crate::Result {
+        let mut trees = Trees::default();
+        let mut editor = Editor::new(Tree::empty(), no_find);
+        editor
+            .upsert("z", EntryMode::Blob, blob())?
+            .upsert("a/c/d", EntryMode::BlobExecutable, blob())?
+            .upsert("a.txt", EntryMode::Blob, blob())?
+            .upsert("a/b", EntryMode::Link, blob())?
+            .upsert("a-b", EntryMode::Blob, blob())?;
+        let root = editor.write(|tree| trees.write(tree))?;
+        assert_eq!(
+            trees.listing(root),
+            [
+                "100644 a-b",
+                "100644 a.txt",
+                "040000 a",
+                "120000 a/b",
+                "040000 a/c",
+                "100755 a/c/d",
+                "100644 z"
+            ],
+            "trees sort as if their name had a trailing slash"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn only_trees_on_the_way_to_changed_entries_are_loaded_and_written() -> crate::Result {
+        let mut trees = Trees::default();
+        let base = tree_with(&mut trees, &["a/c/d", "a.txt", "other/x", "z"])?;
+        let mut found = Vec::new();
+        let mut editor = Editor::new(trees.0[&base].clone(), |id: &git_hash::oid| {
+            let tree = trees.0.get(id).cloned();
+            found.push(tree.as_ref().map(|tree| tree.entries[0].filename.clone()));
+            tree
+        });
+        editor
+            .upsert("a/c/e", EntryMode::Blob, other_blob())?
+            .upsert("a.txt", EntryMode::Blob, other_blob())?
+            .remove("z")?;
+        let mut new_trees = Trees::default();
+        let mut written = 0;
+        let root = editor.write(|tree| {
+            written += 1;
+            new_trees.write(tree)
+        })?;
+        assert_eq!(
+            found,
+            [Some(BString::from("c")), Some(BString::from("d"))],
+            "the trees at 'a' and 'a/c' were loaded, but not the one at 'other'"
+        );
+        assert_eq!(written, 3, "'a/c', 'a' and the root tree are written");
+        trees.0.extend(new_trees.0);
+        assert_eq!(
+            trees.listing(root),
+            [
+                "100644 a.txt",
+                "040000 a",
+                "040000 a/c",
+                "100644 a/c/d",
+                "100644 a/c/e",
+                "040000 other",
+                "100644 other/x"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn entries_on_the_way_are_replaced_by_trees_and_replacing_trees_discards_their_changes() -> crate::Result {
+        let mut trees = Trees::default();
+        let mut editor = Editor::new(Tree::empty(), no_find);
+        editor
+            .upsert("a", EntryMode::Blob, blob())?
+            .upsert("a/b", EntryMode::Blob, blob())?;
+        let root = editor.write(|tree| trees.write(tree))?;
+        assert_eq!(trees.listing(root), ["040000 a", "100644 a/b"]);
+
+        let mut editor = Editor::new(Tree::empty(), no_find);
+        editor
+            .upsert("a/b/c", EntryMode::Blob, blob())?
+            .upsert("a", EntryMode::Blob, other_blob())?;
+        let root = editor.write(|tree| trees.write(tree))?;
+        assert_eq!(trees.listing(root), ["100644 a"]);
+        assert_eq!(trees.0[&root].entries[0].oid, other_blob());
+        Ok(())
+    }
+
+    #[test]
+    fn trees_which_become_empty_are_removed_but_the_root_tree_is_written() -> crate::Result {
+        let mut trees = Trees::default();
+        let base = tree_with(&mut trees, &["a/b/c", "d"])?;
+        let mut editor = Editor::new(trees.0[&base].clone(), |id: &git_hash::oid| trees.0.get(id).cloned());
+        editor
+            .remove("a/b/c")?
+            .remove("does-not-exist/x")?
+            .remove("d/is-no-tree")?;
+        let mut new_trees = Trees::default();
+        let root = editor.write(|tree| new_trees.write(tree))?;
+        assert_eq!(new_trees.listing(root), ["100644 d"]);
+
+        let mut editor = Editor::new(new_trees.0[&root].clone(), no_find);
+        editor.remove("d")?;
+        let root = editor.write(|tree| new_trees.write(tree))?;
+        assert!(new_trees.0[&root].entries.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_paths_and_missing_trees_are_errors() -> crate::Result {
+        let mut trees = Trees::default();
+        let base = tree_with(&mut trees, &["a/b"])?;
+        let mut editor = Editor::new(trees.0[&base].clone(), no_find);
+        for path in &["", "/a", "a/", "a//b"] {
+            assert!(
+                matches!(
+                    editor.upsert(path, EntryMode::Blob, blob()),
+                    Err(editor::Error::EmptyPathComponent { .. })
+                ),
+                "{:?}",
+                path
+            );
+        }
+        assert!(matches!(
+            editor.remove("a/b"),
+            Err(editor::Error::MissingTree { path, .. }) if path == "a"
+        ));
+        Ok(())
+    }
+}
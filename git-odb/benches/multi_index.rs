@@ -0,0 +1,43 @@
+//! Compare opening a store with 50 packs and finding all of its objects with and without a multi-pack index.
+//!
+//! Note that all pack indices are opened in either case, so only lookups are expected to benefit from the multi-pack index.
+use criterion::{criterion_group, criterion_main, Criterion};
+use git_odb::{compound::Store, pack};
+
+fn multi_index(c: &mut Criterion) {
+    let with_index = git_testtools::scripted_fixture_repo_writable("make_repo_multi_index.sh")
+        .expect("fixture script runs successfully");
+    let without_index = git_testtools::scripted_fixture_repo_writable("make_repo_multi_index.sh")
+        .expect("fixture script runs successfully");
+    std::fs::remove_file(without_index.path().join(".git/objects/pack/multi-pack-index"))
+        .expect("multi-pack index can be removed");
+
+    let mut group = c.benchmark_group("50 packs");
+    for (name, repo) in &[
+        ("with multi-pack index", &with_index),
+        ("without multi-pack index", &without_index),
+    ] {
+        let objects = repo.path().join(".git").join("objects");
+        group.bench_function(format!("open store {}", name), |b| {
+            b.iter(|| Store::at(&objects).expect("valid object database"))
+        });
+
+        let db = Store::at(&objects).expect("valid object database");
+        assert_eq!(db.multi_index.is_some(), *name == "with multi-pack index");
+        let ids: Vec<_> = db.bundles.iter().flat_map(|b| b.index.iter().map(|e| e.oid)).collect();
+        let mut buf = Vec::new();
+        group.bench_function(format!("find all objects {}", name), |b| {
+            b.iter(|| {
+                for id in &ids {
+                    db.try_find(id, &mut buf, &mut pack::cache::Never)
+                        .expect("no read error")
+                        .expect("object is present");
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, multi_index);
+criterion_main!(benches);
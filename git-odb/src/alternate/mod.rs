@@ -2,17 +2,17 @@
 //!
 //! This inherently makes alternates read-only.
 //!
-//! An alternate file in `<git-dir>/info/alternates` can look as follows:
+//! An alternate file in `<git-dir>/objects/info/alternates` can look as follows:
 //!
 //! ```text
 //! # a comment, empty lines are also allowed
-//! # relative paths resolve relative to the parent git repository
-//! ../path/relative/to/repo/.git
-//! /absolute/path/to/repo/.git
+//! # relative paths resolve relative to the objects directory containing this file
+//! ../../path/relative/to/repo/.git/objects
+//! /absolute/path/to/repo/.git/objects
 //!
-//! "/a/ansi-c-quoted/path/with/tabs\t/.git"
+//! "/a/ansi-c-quoted/path/with/tabs\t/.git/objects"
 //!
-//! # each .git directory should indeed be a directory, and not a file
+//! # each objects directory should indeed be a directory, and not a file
 //! ```
 //!
 //! Based on the [canonical implementation](https://github.com/git/git/blob/master/sha1-file.c#L598:L609).
@@ -25,45 +25,75 @@ pub mod parse;
 #[allow(missing_docs)]
 pub mod unquote;
 
+/// The maximum amount of nested alternates, matching the limit used by git.
+pub const MAX_DEPTH: usize = 5;
+
 /// Returned by [`resolve()`]
 #[derive(thiserror::Error, Debug)]
 #[allow(missing_docs)]
 pub enum Error {
     #[error(transparent)]
     Io(#[from] io::Error),
+    #[error("The alternate object directory at '{}' could not be read", .path.display())]
+    Unreadable { path: PathBuf, source: io::Error },
     #[error(transparent)]
     Parse(#[from] parse::Error),
     #[error(transparent)]
     Init(#[from] compound::init::Error),
-    #[error("Alternates form a cycle: {} -> {}", .0.iter().map(|p| format!("'{}'", p.display())).collect::<Vec<_>>().join(" -> "), .0.first().expect("more than one directories").display())]
-    Cycle(Vec<PathBuf>),
+    #[error("Alternates in '{}' are nested more than {} levels deep", .path.display(), .max_depth)]
+    Depth { path: PathBuf, max_depth: usize },
 }
 
 /// Given an objects directory, try to resolve alternate object directories possibly located in the
-/// `./info/alternates` file.
+/// `./info/alternates` file, recursively and in the order in which they are listed.
 /// If no alternate object database was resolved, the resulting `Vec` is empty (it is not an error
 /// if there are no alternates).
-/// It is an error once a repository is seen again as it would lead to a cycle.
+///
+/// Relative paths are resolved relative to the objects directory containing the alternates file.
+/// Object directories which were seen before are skipped to avoid cycles, and it is an error if alternates
+/// are nested more than [`MAX_DEPTH`] levels deep or if an alternate object directory can't be accessed.
 pub fn resolve(objects_directory: impl Into<PathBuf>) -> Result<Vec<PathBuf>, Error> {
-    let relative_base = objects_directory.into();
-    let mut dirs = vec![(0, relative_base.clone())];
+    resolve_with_max_depth(objects_directory, MAX_DEPTH)
+}
+
+/// Like [`resolve()`], but fails if alternates are nested more than `max_depth` levels deep instead of [`MAX_DEPTH`].
+pub fn resolve_with_max_depth(objects_directory: impl Into<PathBuf>, max_depth: usize) -> Result<Vec<PathBuf>, Error> {
+    let objects_directory = objects_directory.into();
+    let mut seen = vec![objects_directory.canonicalize()?];
+    let mut dirs = vec![(0, objects_directory)];
     let mut out = Vec::new();
-    let mut seen = vec![relative_base.canonicalize()?];
     while let Some((depth, dir)) = dirs.pop() {
-        match fs::read(dir.join("info").join("alternates")) {
+        let alternates_path = dir.join("info").join("alternates");
+        match fs::read(&alternates_path) {
             Ok(input) => {
+                if depth > max_depth {
+                    return Err(Error::Depth {
+                        path: alternates_path,
+                        max_depth,
+                    });
+                }
+                let mut alternates = Vec::new();
                 for path in parse::content(&input)?.into_iter() {
-                    let path = relative_base.join(path);
-                    let path_canonicalized = path.canonicalize()?;
+                    let path = dir.join(path);
+                    let path_canonicalized = path.canonicalize().map_err(|err| Error::Unreadable {
+                        path: path.clone(),
+                        source: err,
+                    })?;
                     if seen.contains(&path_canonicalized) {
-                        return Err(Error::Cycle(seen));
+                        continue;
                     }
                     seen.push(path_canonicalized);
-                    dirs.push((depth + 1, path));
+                    alternates.push((depth + 1, path));
                 }
+                dirs.extend(alternates.into_iter().rev());
             }
             Err(err) if err.kind() == io::ErrorKind::NotFound => {}
-            Err(err) => return Err(err.into()),
+            Err(err) => {
+                return Err(Error::Unreadable {
+                    path: alternates_path,
+                    source: err,
+                })
+            }
         };
         if depth != 0 {
             out.push(dir);
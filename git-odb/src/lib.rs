@@ -19,9 +19,11 @@ pub use git_pack as pack;
 pub use pack::{data, Find, FindExt};
 
 mod store;
-pub use store::{compound, linked, loose, sink, Sink};
+pub use store::{compound, generation, linked, loose, sink, Sink};
 
 pub mod alternate;
 
+pub mod verify;
+
 mod write;
-pub use write::Write;
+pub use write::{Verify, Write};
@@ -13,6 +13,8 @@ pub enum Error {
     Loose(#[from] loose::find::Error),
     #[error("An error occurred while obtaining an object from the packed object store")]
     Pack(#[from] pack::data::decode_entry::Error),
+    #[error("The multi-pack index is corrupt")]
+    MultiIndex(#[from] pack::multi_index::access::Error),
 }
 
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
@@ -32,11 +34,13 @@ impl compound::Store {
         pack_cache: &mut impl pack::cache::DecodeEntry,
     ) -> Result<Option<data::Object<'a>>, Error> {
         let id = id.as_ref();
-        for bundle in &self.bundles {
-            if let Some(idx) = find_pack_index(bundle, id) {
-                let object = bundle.get_object_by_index(idx, buffer, pack_cache)?;
-                return Ok(Some(object));
-            }
+        if let Some(PackLocation {
+            bundle_index,
+            entry_index,
+        }) = self.internal_find_packed(id)?
+        {
+            let object = self.bundles[bundle_index].get_object_by_index(entry_index, buffer, pack_cache)?;
+            return Ok(Some(object));
         }
         if self.loose.contains(id) {
             return self.loose.try_find(id, buffer).map_err(Into::into);
@@ -44,22 +48,72 @@ impl compound::Store {
         Ok(None)
     }
 
+    /// Return true if the given object `id` is contained in the store.
+    ///
+    /// If the multi-pack index is corrupt, all packs are searched instead.
+    pub fn contains(&self, id: impl AsRef<git_hash::oid>) -> bool {
+        let id = id.as_ref();
+        self.internal_find_packed_or_search_all(id).is_some() || self.loose.contains(id)
+    }
+
+    /// Return the kind and size of the object identified by [`ObjectId`][git_hash::ObjectId] without decoding it,
+    /// or `None` if it is not contained in any of the object databases.
+    ///
+    /// Loose objects are decompressed only as far as their header, and for deltified packed objects only the beginning
+    /// of the delta is decompressed to learn the size of the resulting object.
+    pub fn try_find_header(&self, id: impl AsRef<git_hash::oid>) -> Result<Option<(git_object::Kind, u64)>, Error> {
+        let id = id.as_ref();
+        if let Some(PackLocation {
+            bundle_index,
+            entry_index,
+        }) = self.internal_find_packed(id)?
+        {
+            let header = self.bundles[bundle_index].get_header_by_index(entry_index)?;
+            return Ok(Some((header.kind, header.object_size)));
+        }
+        self.loose.try_find_header(id).map_err(Into::into)
+    }
+
     /// Internal-use function to look up a packed object index or loose object.
     /// Used to avoid double-lookups in linked::Store::locate.
     /// (The polonius borrow-checker would support this via the locate
     /// function, so this can be [simplified](https://github.com/Byron/gitoxide/blob/0c5f4043da4615820cb180804a81c2d4fe75fe5e/git-odb/src/compound/locate.rs#L47)
     /// once polonius is stable.)
-    pub(crate) fn internal_find_packed(&self, id: impl AsRef<git_hash::oid>) -> Option<PackLocation> {
+    pub(crate) fn internal_find_packed(
+        &self,
+        id: impl AsRef<git_hash::oid>,
+    ) -> Result<Option<PackLocation>, pack::multi_index::access::Error> {
         let id = id.as_ref();
-        for (bundle_index, bundle) in self.bundles.iter().enumerate() {
-            if let Some(idx) = find_pack_index(bundle, id) {
-                return Some(PackLocation {
-                    bundle_index,
-                    entry_index: idx,
-                });
+        if let Some(multi_index) = &self.multi_index {
+            // The multi-pack index tells us the pack right away, leaving only a single lookup in its index to get the entry.
+            if let Some((pack_index, _pack_offset)) = multi_index.file.find(id)? {
+                let bundle_index = multi_index.bundle_indices[pack_index as usize];
+                return Ok(self.find_in_bundle(bundle_index, id));
             }
+            return Ok(multi_index
+                .uncovered_bundle_indices
+                .iter()
+                .find_map(|&bundle_index| self.find_in_bundle(bundle_index, id)));
         }
-        None
+        Ok(self.search_all_bundles(id))
+    }
+
+    /// Like [`internal_find_packed()`][compound::Store::internal_find_packed()], but search all bundles if the multi-pack
+    /// index is corrupt, for use where errors can't be returned.
+    pub(crate) fn internal_find_packed_or_search_all(&self, id: &git_hash::oid) -> Option<PackLocation> {
+        self.internal_find_packed(id)
+            .unwrap_or_else(|_| self.search_all_bundles(id))
+    }
+
+    fn search_all_bundles(&self, id: &git_hash::oid) -> Option<PackLocation> {
+        (0..self.bundles.len()).find_map(|bundle_index| self.find_in_bundle(bundle_index, id))
+    }
+
+    fn find_in_bundle(&self, bundle_index: usize, id: &git_hash::oid) -> Option<PackLocation> {
+        find_pack_index(&self.bundles[bundle_index], id).map(|entry_index| PackLocation {
+            bundle_index,
+            entry_index,
+        })
     }
 
     pub(crate) fn internal_get_packed_object_by_index<'a>(
@@ -1,10 +1,17 @@
-use std::path::PathBuf;
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU32, Ordering},
+};
 
 use crate::{
     pack,
     store::{compound, loose},
 };
 
+/// The id of the next pack to be loaded, unique within the process so caches keyed by pack ids never confuse packs of different
+/// stores, including the ones loaded when [refreshing][crate::linked::Store::refresh()] a stale store.
+static NEXT_PACK_ID: AtomicU32 = AtomicU32::new(0);
+
 /// Returned by [`compound::Store::at()`]
 #[derive(thiserror::Error, Debug)]
 #[allow(missing_docs)]
@@ -28,7 +35,9 @@ impl compound::Store {
         if !loose_objects.is_dir() {
             return Err(Error::Inaccessible(loose_objects));
         }
-        let packs = match std::fs::read_dir(loose_objects.join("pack")) {
+        let generation = crate::generation::current(&loose_objects);
+        let pack_directory = loose_objects.join("pack");
+        let packs = match std::fs::read_dir(&pack_directory) {
             Ok(entries) => {
                 let mut packs_and_sizes = entries
                     .filter_map(Result::ok)
@@ -37,13 +46,12 @@ impl compound::Store {
                     .filter(|(p, _)| p.extension().unwrap_or_default() == "idx")
                     // TODO: make this configurable, git for instance sorts by modification date
                     //       https://github.com/libgit2/libgit2/blob/main/src/odb_pack.c#L41-L158
-                    .enumerate()
-                    .map(|(idx, (p, md))| {
+                    .map(|(p, md)| {
                         pack::Bundle::at(p).map(|mut b| {
                             (
                                 {
                                     // don't rely on crc32 for producing non-clashing ids. It's the kind of bug we don't want
-                                    b.pack.id = idx as u32;
+                                    b.pack.id = NEXT_PACK_ID.fetch_add(1, Ordering::Relaxed);
                                     b
                                 },
                                 md.len(),
@@ -57,9 +65,42 @@ impl compound::Store {
             Err(_) => Vec::new(),
         };
 
+        let multi_index = load_multi_index(&pack_directory, &packs);
+        let loaded = compound::stale::Loaded::new(
+            &loose_objects,
+            generation,
+            packs
+                .iter()
+                .filter_map(|b: &pack::Bundle| b.index.path().file_name().map(ToOwned::to_owned))
+                .collect(),
+        );
+
         Ok(compound::Store {
             loose: loose::Store::at(loose_objects),
             bundles: packs,
+            multi_index,
+            loaded,
         })
     }
 }
+
+/// Load the multi-pack index in `pack_directory` and associate it with `bundles`, or return `None` if there is none, if it is
+/// corrupt or if it refers to packs that don't exist anymore. In any of these cases lookups fall back to searching all bundles.
+fn load_multi_index(pack_directory: &Path, bundles: &[pack::Bundle]) -> Option<compound::MultiIndex> {
+    let file = pack::MultiIndex::at(pack_directory.join("multi-pack-index")).ok()?;
+    let bundle_indices = file
+        .index_names()
+        .iter()
+        .map(|name| {
+            bundles
+                .iter()
+                .position(|b| b.index.path().file_name() == Some(name.as_os_str()))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let uncovered_bundle_indices = (0..bundles.len()).filter(|idx| !bundle_indices.contains(idx)).collect();
+    Some(compound::MultiIndex {
+        file,
+        bundle_indices,
+        uncovered_bundle_indices,
+    })
+}
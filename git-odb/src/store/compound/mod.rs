@@ -5,6 +5,9 @@ use crate::{pack, store::loose};
 pub mod find;
 ///
 pub mod init;
+mod stale;
+///
+pub mod verify;
 mod write;
 
 /// An object database with tiered lookup packs and loose objects.
@@ -14,4 +17,18 @@ pub struct Store {
     pub loose: loose::Store,
     /// All packs in the `objects/packs` directory
     pub bundles: Vec<pack::Bundle>,
+    /// The multi-pack index in the `objects/packs` directory, if present and usable, to find packed objects with a single lookup.
+    pub multi_index: Option<MultiIndex>,
+    /// What was loaded from disk, to learn if this store [is stale][Store::is_stale()].
+    pub(crate) loaded: stale::Loaded,
+}
+
+/// A multi-pack index along with the information needed to use it with the [bundles][Store::bundles] of a [`Store`].
+pub struct MultiIndex {
+    /// The multi-pack index file
+    pub file: pack::MultiIndex,
+    /// For each pack in `file`, the position of its bundle in [`Store::bundles`].
+    pub bundle_indices: Vec<usize>,
+    /// The position of all bundles in [`Store::bundles`] which aren't covered by `file`, as they were added after it was written.
+    pub uncovered_bundle_indices: Vec<usize>,
 }
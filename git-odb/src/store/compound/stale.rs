@@ -0,0 +1,108 @@
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::SystemTime,
+};
+
+use crate::store::{compound, generation};
+
+/// Files derived from the packs which are read along with them, and which are removed or rewritten when packs change.
+const DERIVED_FILES: &[&str] = &[
+    "pack/multi-pack-index",
+    "info/commit-graph",
+    "info/commit-graphs/commit-graph-chain",
+];
+
+/// The size and modification time of a file.
+type Stamp = (u64, Option<SystemTime>);
+
+/// The generation and the state of the files on disk when a [`compound::Store`] was loaded, to learn if it is stale.
+pub(crate) struct Loaded {
+    /// The path identifying the objects directory in the [generations][generation] of this process.
+    key: PathBuf,
+    generation: u64,
+    /// The names of all pack indices in the `pack` directory, sorted.
+    index_names: Vec<OsString>,
+    /// The stamp of each of the [`DERIVED_FILES`], or `None` if it didn't exist.
+    derived_files: Vec<Option<Stamp>>,
+    /// True once the disk was looked at [after a miss][compound::Store::is_stale_after_miss()].
+    checked_disk_after_miss: AtomicBool,
+}
+
+impl Loaded {
+    /// Obtain the generation of `objects_dir` before calling this function, to be sure the generation is never newer than
+    /// the files that are read afterwards.
+    pub(crate) fn new(objects_dir: &Path, generation: u64, mut index_names: Vec<OsString>) -> Self {
+        index_names.sort();
+        Loaded {
+            key: generation::key(objects_dir),
+            generation,
+            index_names,
+            derived_files: derived_file_stamps(objects_dir),
+            checked_disk_after_miss: AtomicBool::new(false),
+        }
+    }
+}
+
+impl compound::Store {
+    /// Return true if the packs, the multi-pack index or the commit-graph changed since this store was loaded, as
+    /// [announced][crate::generation::bump()] by this process or as observed on disk, which is the case after repacking.
+    ///
+    /// Looking at the disk costs listing the `pack` directory and a few calls to `stat()`, so it's best to call it after
+    /// maintenance, not before each lookup. If an object can't be found, use
+    /// [`is_stale_after_miss()`][compound::Store::is_stale_after_miss()] instead.
+    ///
+    /// Changes observed on disk are announced to all stores of this process, so they know they are stale without looking.
+    pub fn is_stale(&self) -> bool {
+        self.is_stale_by_generation() || self.changed_on_disk()
+    }
+
+    /// Like [`is_stale()`][compound::Store::is_stale()], but look at the disk only the first time it's called since this store
+    /// was loaded and rely on the generations announced within this process afterwards, which is cheap enough to do
+    /// each time an object can't be found.
+    pub fn is_stale_after_miss(&self) -> bool {
+        self.is_stale_by_generation()
+            || (!self.loaded.checked_disk_after_miss.swap(true, Ordering::Relaxed) && self.changed_on_disk())
+    }
+
+    fn is_stale_by_generation(&self) -> bool {
+        generation::current_by_key(&self.loaded.key) != self.loaded.generation
+    }
+
+    fn changed_on_disk(&self) -> bool {
+        let loaded = &self.loaded;
+        let changed = index_names(&self.loose.path) != loaded.index_names
+            || derived_file_stamps(&self.loose.path) != loaded.derived_files;
+        if changed {
+            generation::bump_by_key(&loaded.key);
+        }
+        changed
+    }
+}
+
+/// The names of all pack indices in the `pack` directory of `objects_dir`, sorted.
+fn index_names(objects_dir: &Path) -> Vec<OsString> {
+    let mut names: Vec<_> = std::fs::read_dir(objects_dir.join("pack"))
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.file_name())
+                .filter(|name| Path::new(name).extension().map_or(false, |ext| ext == "idx"))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+fn derived_file_stamps(objects_dir: &Path) -> Vec<Option<Stamp>> {
+    DERIVED_FILES
+        .iter()
+        .map(|path| {
+            std::fs::metadata(objects_dir.join(path))
+                .ok()
+                .map(|meta| (meta.len(), meta.modified().ok()))
+        })
+        .collect()
+}
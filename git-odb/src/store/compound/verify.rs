@@ -0,0 +1,85 @@
+use std::{
+    path::PathBuf,
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use git_features::progress::Progress;
+
+use crate::{
+    pack,
+    store::{compound, loose},
+    verify::{Mode, Statistics},
+};
+
+/// Returned by [`compound::Store::verify_integrity()`]
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The pack at '{path}' failed to verify")]
+    Pack {
+        path: PathBuf,
+        source: pack::index::traverse::Error<pack::index::verify::Error>,
+    },
+    #[error(transparent)]
+    Loose(#[from] loose::verify::Error),
+}
+
+/// The successful result of [`compound::Store::verify_integrity()`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outcome {
+    /// Statistics over all packed and loose objects.
+    pub statistics: Statistics,
+    /// The path of each verified pack along with the detailed outcome of its traversal.
+    pub packs: Vec<(PathBuf, pack::index::traverse::Outcome)>,
+}
+
+/// Integrity verification
+impl compound::Store {
+    /// Verify the integrity of all packs and loose objects, using up to `thread_limit` threads for pack traversal.
+    ///
+    /// Each pack is checked for the checksums of its data and index file, the CRC32 of each entry and the hash of each
+    /// object, and in [`Mode::Full`] all commits, trees and tags are decoded as well. Loose objects are checked as described
+    /// in [`loose::Store::verify_integrity()`].
+    /// Packs are traversed by looking up each index entry, so that errors refer to the offset and id of the failing object.
+    /// The multi-pack index, if present, isn't verified.
+    ///
+    /// `progress` receives one child per pack and one for loose objects, and `should_interrupt` aborts the operation
+    /// with an error once set.
+    pub fn verify_integrity(
+        &self,
+        mode: Mode,
+        thread_limit: Option<usize>,
+        mut progress: impl Progress,
+        should_interrupt: Arc<AtomicBool>,
+    ) -> Result<Outcome, Error> {
+        let mut outcome = Outcome {
+            statistics: Statistics::default(),
+            packs: Vec::with_capacity(self.bundles.len()),
+        };
+        for bundle in &self.bundles {
+            let path = bundle.pack.path().to_owned();
+            let (_, pack_outcome, _) = bundle
+                .verify_integrity(
+                    mode.into(),
+                    pack::index::traverse::Algorithm::Lookup,
+                    || pack::cache::Never,
+                    thread_limit,
+                    Some(progress.add_child(format!("{}", path.display()))),
+                    should_interrupt.clone(),
+                )
+                .map_err(|err| Error::Pack {
+                    path: path.clone(),
+                    source: err,
+                })?;
+            let pack_outcome = pack_outcome.expect("outcome is present if a pack is verified");
+            outcome.statistics.add_pack(&pack_outcome);
+            outcome.packs.push((path, pack_outcome));
+        }
+        let loose = self
+            .loose
+            .verify_integrity(mode, progress.add_child("loose objects"), &should_interrupt)?;
+        outcome.statistics.merge(&loose);
+        Ok(outcome)
+    }
+}
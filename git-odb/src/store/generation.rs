@@ -0,0 +1,38 @@
+//! Announce changes to the packs, the multi-pack index or the commit-graph of an object database to all stores of this process,
+//! so they know they are [stale][crate::linked::Store::is_stale()] without having to look at the files on disk.
+use std::path::{Path, PathBuf};
+
+/// The generation of each objects directory which was bumped at least once, identified by its canonical path.
+static GENERATIONS: parking_lot::Mutex<Vec<(PathBuf, u64)>> = parking_lot::const_mutex(Vec::new());
+
+/// Return the path identifying `objects_dir`, which is the same no matter how it's spelled.
+pub(crate) fn key(objects_dir: &Path) -> PathBuf {
+    std::fs::canonicalize(objects_dir).unwrap_or_else(|_| objects_dir.to_owned())
+}
+
+/// Announce that the packs, the multi-pack index or the commit-graph in `objects_dir` were changed, like after repacking,
+/// which makes all stores reading from it [stale][crate::linked::Store::is_stale()].
+pub fn bump(objects_dir: impl AsRef<Path>) {
+    bump_by_key(&key(objects_dir.as_ref()))
+}
+
+pub(crate) fn bump_by_key(key: &Path) {
+    let mut generations = GENERATIONS.lock();
+    match generations.iter_mut().find(|(path, _)| path == key) {
+        Some((_, generation)) => *generation += 1,
+        None => generations.push((key.to_owned(), 1)),
+    }
+}
+
+/// Return the current generation of `objects_dir`, which is `0` if it was never [bumped][bump()].
+pub fn current(objects_dir: impl AsRef<Path>) -> u64 {
+    current_by_key(&key(objects_dir.as_ref()))
+}
+
+pub(crate) fn current_by_key(key: &Path) -> u64 {
+    GENERATIONS
+        .lock()
+        .iter()
+        .find(|(path, _)| path == key)
+        .map_or(0, |(_, generation)| *generation)
+}
@@ -13,13 +13,38 @@ impl linked::Store {
     /// Return true if the given object `id` is contained in the store.
     pub fn contains(&self, id: impl AsRef<oid>) -> bool {
         let id = id.as_ref();
+        if self.negative_cache.contains(id) {
+            return false;
+        }
         for db in self.dbs.iter() {
-            if db.internal_find_packed(id).is_some() || db.loose.contains(id) {
+            if db.contains(id) {
                 return true;
             }
         }
+        self.negative_cache.insert(id);
         false
     }
+
+    /// Return the kind and size of the object identified by [`ObjectId`][git_hash::ObjectId] without decoding it,
+    /// or `None` if it is not contained in any of the linked object databases.
+    ///
+    /// See [`compound::Store::try_find_header()`] for details.
+    pub fn try_find_header(
+        &self,
+        id: impl AsRef<oid>,
+    ) -> Result<Option<(git_object::Kind, u64)>, compound::find::Error> {
+        let id = id.as_ref();
+        if self.negative_cache.contains(id) {
+            return Ok(None);
+        }
+        for db in self.dbs.iter() {
+            if let Some(header) = db.try_find_header(id)? {
+                return Ok(Some(header));
+            }
+        }
+        self.negative_cache.insert(id);
+        Ok(None)
+    }
 }
 
 impl crate::Find for linked::Store {
@@ -32,8 +57,11 @@ impl crate::Find for linked::Store {
         pack_cache: &mut impl pack::cache::DecodeEntry,
     ) -> Result<Option<Object<'a>>, Self::Error> {
         let id = id.as_ref();
+        if self.negative_cache.contains(id) {
+            return Ok(None);
+        }
         for db in self.dbs.iter() {
-            match db.internal_find_packed(id) {
+            match db.internal_find_packed(id)? {
                 Some(compound::find::PackLocation {
                     bundle_index: pack_id,
                     entry_index,
@@ -50,6 +78,7 @@ impl crate::Find for linked::Store {
                 }
             }
         }
+        self.negative_cache.insert(id);
         Ok(None)
     }
 
@@ -59,7 +88,7 @@ impl crate::Find for linked::Store {
             if let Some(compound::find::PackLocation {
                 bundle_index,
                 entry_index,
-            }) = db.internal_find_packed(id)
+            }) = db.internal_find_packed_or_search_all(id)
             {
                 let bundle = &db.bundles[bundle_index];
                 let pack_offset = bundle.index.pack_offset_at_index(entry_index);
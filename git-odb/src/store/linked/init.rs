@@ -2,7 +2,10 @@ use std::path::PathBuf;
 
 use crate::{
     alternate,
-    store::{compound, linked},
+    store::{
+        compound,
+        linked::{self, negative_cache},
+    },
 };
 
 /// The error returned by [`linked::Store::at()`]
@@ -28,18 +31,94 @@ impl linked::Store {
             !dbs.is_empty(),
             "we can rely on at least one compound database to be present"
         );
-        Ok(linked::Store { dbs })
+        Ok(linked::Store {
+            dbs,
+            negative_cache: Default::default(),
+        })
     }
 
     /// Efficiently refresh the stable data like memory maps of packs or linked repositories to reflect the changed state on disk.
+    ///
+    /// This also clears the [negative cache][linked::Store::negative_cache] as objects may have been added.
     pub fn refresh(&mut self) -> Result<&mut Self, Error> {
         // TODO: actually do this efficiently by only loading or discarding what changed. Probably redirect the non-alternates impl
         //       to the compound db to deal with pack refreshing.
         let first_db = self.dbs.remove(0);
+        let read_only = first_db.loose.read_only;
+        let verify = first_db.loose.verify;
         let base_path = first_db.loose.path;
+        let negative_cache = std::mem::take(&mut self.negative_cache);
+        negative_cache.clear();
         *self = Self::at(base_path)?;
+        self.negative_cache = negative_cache;
+        self.set_read_only(read_only).set_verify(verify);
         Ok(self)
     }
+
+    /// Return a new instance reflecting the current state on disk with the same settings as this one, which is like
+    /// [refreshing][linked::Store::refresh()] a copy for when this instance can't be borrowed mutably.
+    ///
+    /// The [negative cache][linked::Store::negative_cache] starts out empty with the same capacity.
+    pub fn to_refreshed(&self) -> Result<Self, Error> {
+        let mut store = Self::at(self.dbs[0].loose.path.clone())?;
+        store.negative_cache = negative_cache::Cache::with_capacity(self.negative_cache.capacity());
+        store.set_read_only(self.is_read_only()).set_verify(self.verify());
+        Ok(store)
+    }
+
+    /// Return true if any of the [databases][linked::Store::dbs] [is stale][compound::Store::is_stale()], which is when their
+    /// packs, multi-pack index or commit-graph changed since they were loaded.
+    pub fn is_stale(&self) -> bool {
+        self.dbs.iter().any(compound::Store::is_stale)
+    }
+
+    /// Return true if any of the [databases][linked::Store::dbs] [is stale][compound::Store::is_stale_after_miss()] while
+    /// looking at the disk at most once per database since it was loaded, which is meant to be called when objects can't be found.
+    pub fn is_stale_after_miss(&self) -> bool {
+        self.dbs.iter().any(compound::Store::is_stale_after_miss)
+    }
+
+    /// [Refresh][linked::Store::refresh()] this instance if it [is stale][linked::Store::is_stale()], and return true
+    /// if it was refreshed.
+    ///
+    /// As this requires exclusive access, it can't happen while objects are read, and objects obtained before remain valid
+    /// as their data lives in buffers of the caller.
+    pub fn refresh_if_stale(&mut self) -> Result<bool, Error> {
+        Ok(if self.is_stale() {
+            self.refresh()?;
+            true
+        } else {
+            false
+        })
+    }
+
+    /// If `toggle` is true, make all writes through this instance or any of its [databases][linked::Store::dbs] fail with
+    /// a [`ReadOnly`][crate::loose::write::Error::ReadOnly] error, which is retained when [refreshing][linked::Store::refresh()].
+    pub fn set_read_only(&mut self, toggle: bool) -> &mut Self {
+        for db in self.dbs.iter_mut() {
+            db.loose.read_only = toggle;
+        }
+        self
+    }
+
+    /// Return true if writes through this instance [are rejected][linked::Store::set_read_only()].
+    pub fn is_read_only(&self) -> bool {
+        self.dbs[0].loose.read_only
+    }
+
+    /// Verify writes through this instance or any of its [databases][linked::Store::dbs] according to `verify`, which is
+    /// retained when [refreshing][linked::Store::refresh()].
+    pub fn set_verify(&mut self, verify: crate::Verify) -> &mut Self {
+        for db in self.dbs.iter_mut() {
+            db.loose.verify = verify;
+        }
+        self
+    }
+
+    /// Return the way writes through this instance are [verified][linked::Store::set_verify()].
+    pub fn verify(&self) -> crate::Verify {
+        self.dbs[0].loose.verify
+    }
 }
 
 impl std::convert::TryFrom<PathBuf> for linked::Store {
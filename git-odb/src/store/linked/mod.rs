@@ -7,13 +7,23 @@ use crate::store::compound;
 pub struct Store {
     /// The compound databases containing the actual objects.
     pub dbs: Vec<compound::Store>,
+    /// Object ids known to be missing from all `dbs`, to speed up repeated lookups of objects that don't exist.
+    ///
+    /// It's disabled by default, enable it by setting a [cache with capacity][negative_cache::Cache::with_capacity()].
+    /// It's cleared on [`refresh()`][Store::refresh()] and ids are removed from it once they are written through this instance.
+    /// Objects written by other means, like other processes, will not be visible until the store is refreshed.
+    pub negative_cache: negative_cache::Cache,
 }
 
+pub mod negative_cache;
+
 ///
 pub mod init;
 
 mod find;
 
+mod verify;
+
 ///
 mod write;
 
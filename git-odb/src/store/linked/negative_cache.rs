@@ -0,0 +1,105 @@
+//! A bounded cache of object ids known to be missing from a [`linked::Store`][crate::linked::Store].
+use std::collections::{HashSet, VecDeque};
+
+use git_hash::{oid, ObjectId};
+
+/// Counters to learn how effective the [`Cache`] is.
+#[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Statistics {
+    /// The amount of lookups answered by the cache, avoiding a search in all loose object directories and packs.
+    pub hits: usize,
+    /// The amount of lookups the cache couldn't answer, requiring a full search.
+    pub misses: usize,
+    /// The amount of missing object ids currently remembered.
+    pub len: usize,
+}
+
+/// A bounded set of object ids which are known to not exist, forgetting the oldest ids first once it is full.
+///
+/// It must be invalidated whenever objects are added to the underlying store, which is why it's disabled by default.
+pub struct Cache {
+    state: parking_lot::Mutex<State>,
+}
+
+struct State {
+    ids: HashSet<ObjectId>,
+    insertion_order: VecDeque<ObjectId>,
+    capacity: usize,
+    stats: Statistics,
+}
+
+impl Default for Cache {
+    /// Create a disabled cache which doesn't remember any missing ids.
+    fn default() -> Self {
+        Cache::with_capacity(0)
+    }
+}
+
+impl Cache {
+    /// Create a new instance remembering up to `capacity` missing ids, or none at all if it is 0.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Cache {
+            state: parking_lot::Mutex::new(State {
+                ids: Default::default(),
+                insertion_order: Default::default(),
+                capacity,
+                stats: Default::default(),
+            }),
+        }
+    }
+
+    /// Return the amount of missing ids this instance remembers at most, which is 0 if it's disabled.
+    pub fn capacity(&self) -> usize {
+        self.state.lock().capacity
+    }
+
+    /// Return true if `id` is known to be missing, counting hits and misses.
+    pub fn contains(&self, id: &oid) -> bool {
+        let mut state = self.state.lock();
+        let hit = state.ids.contains(id);
+        if hit {
+            state.stats.hits += 1;
+        } else {
+            state.stats.misses += 1;
+        }
+        hit
+    }
+
+    /// Remember that `id` is missing.
+    pub fn insert(&self, id: &oid) {
+        let mut state = self.state.lock();
+        if state.capacity == 0 || !state.ids.insert(id.to_owned()) {
+            return;
+        }
+        state.insertion_order.push_back(id.to_owned());
+        if state.insertion_order.len() > state.capacity {
+            let oldest = state.insertion_order.pop_front().expect("non-empty");
+            state.ids.remove(&oldest);
+        }
+    }
+
+    /// Forget that `id` is missing, typically because it was just written.
+    pub fn remove(&self, id: &oid) {
+        let mut state = self.state.lock();
+        if state.ids.remove(id) {
+            state.insertion_order.retain(|existing| existing.as_ref() != id);
+        }
+    }
+
+    /// Forget all missing ids, but keep the statistics.
+    pub fn clear(&self) {
+        let mut state = self.state.lock();
+        state.ids.clear();
+        state.insertion_order.clear();
+    }
+
+    /// Return the current statistics.
+    pub fn statistics(&self) -> Statistics {
+        let state = self.state.lock();
+        Statistics {
+            len: state.ids.len(),
+            ..state.stats
+        }
+    }
+}
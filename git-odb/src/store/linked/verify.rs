@@ -0,0 +1,39 @@
+use std::sync::{atomic::AtomicBool, Arc};
+
+use git_features::progress::Progress;
+
+use crate::{
+    store::{compound, linked},
+    verify::Mode,
+};
+
+/// Integrity verification
+impl linked::Store {
+    /// Verify the integrity of all objects in all linked databases, as described in
+    /// [`compound::Store::verify_integrity()`], and return the combined outcome of all of them.
+    ///
+    /// Databases are verified in order, each with its own child of `progress`.
+    pub fn verify_integrity(
+        &self,
+        mode: Mode,
+        thread_limit: Option<usize>,
+        mut progress: impl Progress,
+        should_interrupt: Arc<AtomicBool>,
+    ) -> Result<compound::verify::Outcome, compound::verify::Error> {
+        let mut outcome = compound::verify::Outcome {
+            statistics: Default::default(),
+            packs: Vec::new(),
+        };
+        for db in &self.dbs {
+            let db_outcome = db.verify_integrity(
+                mode,
+                thread_limit,
+                progress.add_child(format!("{}", db.loose.path.display())),
+                should_interrupt.clone(),
+            )?;
+            outcome.statistics.merge(&db_outcome.statistics);
+            outcome.packs.extend(db_outcome.packs);
+        }
+        Ok(outcome)
+    }
+}
@@ -8,11 +8,15 @@ impl crate::write::Write for linked::Store {
     type Error = loose::write::Error;
 
     fn write(&self, object: impl git_object::WriteTo, hash: git_hash::Kind) -> Result<git_hash::ObjectId, Self::Error> {
-        self.dbs[0].loose.write(object, hash)
+        let id = self.dbs[0].loose.write(object, hash)?;
+        self.negative_cache.remove(&id);
+        Ok(id)
     }
 
     fn write_buf(&self, object: Kind, from: &[u8], hash: git_hash::Kind) -> Result<git_hash::ObjectId, Self::Error> {
-        self.dbs[0].loose.write_buf(object, from, hash)
+        let id = self.dbs[0].loose.write_buf(object, from, hash)?;
+        self.negative_cache.remove(&id);
+        Ok(id)
     }
 
     fn write_stream(
@@ -22,6 +26,8 @@ impl crate::write::Write for linked::Store {
         from: impl Read,
         hash: git_hash::Kind,
     ) -> Result<git_hash::ObjectId, Self::Error> {
-        self.dbs[0].loose.write_stream(kind, size, from, hash)
+        let id = self.dbs[0].loose.write_stream(kind, size, from, hash)?;
+        self.negative_cache.remove(&id);
+        Ok(id)
     }
 }
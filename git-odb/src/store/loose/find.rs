@@ -66,6 +66,62 @@ impl Store {
         }
     }
 
+    /// Return the kind and size of the object identified by the given [`ObjectId`][git_hash::ObjectId] if present in this database.
+    ///
+    /// Only as much of the object is decompressed as needed to read its header.
+    /// Returns `Ok<None>` if there was no such object.
+    pub fn try_find_header(&self, id: impl AsRef<git_hash::oid>) -> Result<Option<(git_object::Kind, u64)>, Error> {
+        let path = sha1_path(id.as_ref(), self.path.clone());
+        let mut istream = match fs::File::open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(Error::Io {
+                    source: err,
+                    action: Self::OPEN_ACTION,
+                    path,
+                })
+            }
+        };
+
+        let mut inflate = zlib::Inflate::default();
+        let mut input = [0u8; 256];
+        let mut output = [0u8; HEADER_READ_UNCOMPRESSED_BYTES];
+        let mut num_decompressed = 0;
+        'read: loop {
+            let bytes_read = istream.read(&mut input).map_err(|e| Error::Io {
+                source: e,
+                action: "read",
+                path: path.to_owned(),
+            })?;
+            let mut input = &input[..bytes_read];
+            loop {
+                let (status, consumed_in, consumed_out) = inflate
+                    .once(input, &mut output[num_decompressed..])
+                    .map_err(|e| Error::DecompressFile {
+                        source: e,
+                        path: path.to_owned(),
+                    })?;
+                num_decompressed += consumed_out;
+                input = &input[consumed_in..];
+                if status == zlib::Status::StreamEnd
+                    || num_decompressed == output.len()
+                    || output[..num_decompressed].contains(&0)
+                {
+                    break 'read;
+                }
+                if input.is_empty() {
+                    if bytes_read == 0 {
+                        break 'read;
+                    }
+                    break;
+                }
+            }
+        }
+        let (kind, size, _header_size) = header::decode(&output[..num_decompressed])?;
+        Ok(Some((kind, size)))
+    }
+
     fn find_inner<'a>(&self, id: &git_hash::oid, buf: &'a mut Vec<u8>) -> Result<data::Object<'a>, Error> {
         let path = sha1_path(id, self.path.clone());
 
@@ -6,6 +6,10 @@ use std::path::PathBuf;
 pub struct Store {
     /// The directory in which objects are stored, containing 256 folders representing the hashes first byte.
     pub path: PathBuf,
+    /// If true, writing objects fails with a [`ReadOnly`][write::Error::ReadOnly] error instead of creating files in `path`.
+    pub read_only: bool,
+    /// Determines if written objects are [read back and verified][Store::verify_written()] before writes succeed.
+    pub verify: crate::Verify,
 }
 
 /// Initialization
@@ -17,6 +21,8 @@ impl Store {
     pub fn at(objects_directory: impl Into<PathBuf>) -> Store {
         Store {
             path: objects_directory.into(),
+            read_only: false,
+            verify: crate::Verify::Never,
         }
     }
 }
@@ -40,4 +46,6 @@ pub mod iter;
 #[doc(inline)]
 pub use iter::Iter;
 ///
+pub mod verify;
+///
 pub mod write;
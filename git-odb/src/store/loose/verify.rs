@@ -0,0 +1,100 @@
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use git_features::progress::Progress;
+
+use crate::{
+    store::loose::{self, sha1_path, Store},
+    verify::{Mode, Statistics},
+};
+
+/// Returned by [`Store::verify_integrity()`]
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The loose object directory could not be traversed")]
+    Iter(#[from] loose::iter::Error),
+    #[error("The loose object {id} could not be read")]
+    Find {
+        id: git_hash::ObjectId,
+        source: loose::find::Error,
+    },
+    #[error("{kind} object at '{path}' is named {expected}, but its content hashes to {actual}")]
+    ObjectHashMismatch {
+        kind: git_object::Kind,
+        path: PathBuf,
+        expected: git_hash::ObjectId,
+        actual: git_hash::ObjectId,
+    },
+    #[error("{kind} object {id} at '{path}' could not be decoded")]
+    ObjectDecode {
+        source: git_object::decode::Error,
+        kind: git_object::Kind,
+        id: git_hash::ObjectId,
+        path: PathBuf,
+    },
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// Integrity verification
+impl Store {
+    /// Check that the content of each loose object hashes to the id encoded in its path, and if `mode` is [`Mode::Full`],
+    /// that all commits, trees and tags can be decoded.
+    ///
+    /// Return statistics about all objects on success, while `progress` receives one step per object. Set `should_interrupt`
+    /// to abort the operation with an error.
+    pub fn verify_integrity(
+        &self,
+        mode: Mode,
+        mut progress: impl Progress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Statistics, Error> {
+        let mut stats = Statistics::default();
+        let mut buf = Vec::new();
+        progress.init(None, git_features::progress::count("objects"));
+        for id in self.iter() {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            let id = id?;
+            let object = match self
+                .try_find(id, &mut buf)
+                .map_err(|err| Error::Find { id, source: err })?
+            {
+                Some(object) => object,
+                None => continue,
+            };
+
+            let mut hasher = git_features::hash::Sha1::default();
+            let mut header_buf = Vec::with_capacity(32);
+            git_pack::loose::object::header::encode(object.kind, object.data.len() as u64, &mut header_buf)
+                .expect("write to memory works");
+            hasher.update(&header_buf);
+            hasher.update(object.data);
+            let actual = git_hash::ObjectId::new_sha1(hasher.digest());
+            if actual != id {
+                return Err(Error::ObjectHashMismatch {
+                    kind: object.kind,
+                    path: sha1_path(&id, self.path.clone()),
+                    expected: id,
+                    actual,
+                });
+            }
+            if mode == Mode::Full && object.kind != git_object::Kind::Blob {
+                object.decode().map_err(|err| Error::ObjectDecode {
+                    source: err,
+                    kind: object.kind,
+                    id,
+                    path: sha1_path(&id, self.path.clone()),
+                })?;
+            }
+
+            stats.add_object(object.kind, object.data.len() as u64);
+            progress.inc();
+        }
+        Ok(stats)
+    }
+}
@@ -1,7 +1,7 @@
 use std::{fs, io, io::Write, path::PathBuf};
 
-use git_features::{hash, zlib::stream::deflate};
-use tempfile::NamedTempFile;
+use git_features::{io::HashingWrite, zlib::stream::deflate};
+use git_tempfile::{handle::Writable, AutoRemove, ContainingDirectory};
 
 use super::Store;
 use crate::store::loose;
@@ -20,9 +20,23 @@ pub enum Error {
     IoRaw(#[from] io::Error),
     #[error("Could not turn temporary file into persisted file at '{target}'")]
     Persist {
-        source: tempfile::PersistError,
+        source: git_tempfile::handle::persist::Error<Writable>,
         target: PathBuf,
     },
+    #[error("Could not write an object into '{}' as the object database is read-only", .path.display())]
+    ReadOnly { path: PathBuf },
+    #[error("The object written to '{}' was removed as it hashes to {actual} instead of {expected} when read back", .path.display())]
+    VerificationFailed {
+        path: PathBuf,
+        expected: git_hash::ObjectId,
+        actual: git_hash::ObjectId,
+    },
+    #[error("The object {id} written to '{}' was removed as it couldn't be read back", .path.display())]
+    VerificationRead {
+        source: loose::find::Error,
+        path: PathBuf,
+        id: git_hash::ObjectId,
+    },
 }
 
 impl crate::write::Write for Store {
@@ -46,7 +60,7 @@ impl crate::write::Write for Store {
                     path: self.path.to_owned(),
                 })?;
                 to.flush()?;
-                self.finalize_object(to)
+                self.finalize_and_verify(to)
             }
         }
     }
@@ -70,13 +84,68 @@ impl crate::write::Write for Store {
                     path: self.path.to_owned(),
                 })?;
                 to.flush()?;
-                self.finalize_object(to)
+                self.finalize_and_verify(to)
+            }
+        }
+    }
+}
+
+/// Verification
+impl Store {
+    /// Read the object with `id` back through the normal read path, decompress and hash it, and compare the hash to `id`.
+    ///
+    /// If the hashes don't match or the object can't be read, it's removed to not leave a corrupt object behind and an error
+    /// is returned. This is done after each write if [`verify`][Store::verify] is set accordingly.
+    pub fn verify_written(&self, id: &git_hash::oid) -> Result<(), Error> {
+        let path = loose::sha1_path(id, self.path.clone());
+        let mut buf = Vec::new();
+        let actual = match self.try_find(id, &mut buf) {
+            Ok(Some(object)) => {
+                let mut hasher = git_features::hash::Sha1::default();
+                let mut header_buf = Vec::with_capacity(32);
+                git_pack::loose::object::header::encode(object.kind, object.data.len() as u64, &mut header_buf)
+                    .expect("write to memory works");
+                hasher.update(&header_buf);
+                hasher.update(object.data);
+                git_hash::ObjectId::new_sha1(hasher.digest())
+            }
+            Ok(None) => {
+                return Err(Error::Io {
+                    source: io::ErrorKind::NotFound.into(),
+                    message: "read back written object at",
+                    path,
+                })
+            }
+            Err(source) => {
+                remove_corrupt_object(&path)?;
+                return Err(Error::VerificationRead {
+                    source,
+                    path,
+                    id: id.to_owned(),
+                });
             }
+        };
+        if actual != id {
+            remove_corrupt_object(&path)?;
+            return Err(Error::VerificationFailed {
+                path,
+                expected: id.to_owned(),
+                actual,
+            });
         }
+        Ok(())
     }
 }
 
-type CompressedTempfile = deflate::Write<NamedTempFile>;
+fn remove_corrupt_object(path: &std::path::Path) -> Result<(), Error> {
+    fs::remove_file(path).map_err(|err| Error::Io {
+        source: err,
+        message: "remove corrupt object at",
+        path: path.to_owned(),
+    })
+}
+
+type CompressedTempfile = deflate::Write<git_tempfile::Handle<Writable>>;
 
 impl Store {
     fn write_header(
@@ -84,13 +153,22 @@ impl Store {
         kind: git_object::Kind,
         size: u64,
         hash: git_hash::Kind,
-    ) -> Result<hash::Write<CompressedTempfile>, Error> {
-        let mut to = hash::Write::new(
-            deflate::Write::new(NamedTempFile::new_in(&self.path).map_err(|err| Error::Io {
-                source: err,
-                message: "create named temp file in",
+    ) -> Result<HashingWrite<CompressedTempfile>, Error> {
+        if self.read_only {
+            return Err(Error::ReadOnly {
                 path: self.path.to_owned(),
-            })?),
+            });
+        }
+        let mut to = HashingWrite::new(
+            deflate::Write::new(
+                git_tempfile::new(&self.path, ContainingDirectory::Exists, AutoRemove::Tempfile).map_err(|err| {
+                    Error::Io {
+                        source: err,
+                        message: "create named temp file in",
+                        path: self.path.to_owned(),
+                    }
+                })?,
+            ),
             hash,
         );
 
@@ -102,9 +180,17 @@ impl Store {
         Ok(to)
     }
 
+    fn finalize_and_verify(&self, to: HashingWrite<CompressedTempfile>) -> Result<git_hash::ObjectId, Error> {
+        let id = self.finalize_object(to)?;
+        if self.verify.loose_objects() {
+            self.verify_written(&id)?;
+        }
+        Ok(id)
+    }
+
     fn finalize_object(
         &self,
-        hash::Write { hash, inner: file }: hash::Write<CompressedTempfile>,
+        HashingWrite { hash, inner: file }: HashingWrite<CompressedTempfile>,
     ) -> Result<git_hash::ObjectId, Error> {
         let id = git_hash::ObjectId::from(hash.digest());
         let object_path = loose::sha1_path(&id, self.path.clone());
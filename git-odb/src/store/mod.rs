@@ -1,6 +1,7 @@
 pub use sink::{sink, Sink};
 
 pub mod compound;
+pub mod generation;
 pub mod linked;
 pub mod loose;
 
@@ -0,0 +1,99 @@
+//! Types shared by the integrity checks of all object databases, like [`compound::Store::verify_integrity()`][crate::compound::Store::verify_integrity()].
+use std::collections::BTreeMap;
+
+use git_pack::index;
+
+/// Determines how thoroughly objects are checked.
+#[derive(Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum Mode {
+    /// Check the checksums of pack and index files, the CRC32 of each pack entry and the hash of each object,
+    /// without parsing objects.
+    Sha1CrcOnly,
+    /// Like [`Sha1CrcOnly`][Mode::Sha1CrcOnly], but also decode each commit, tree and tag to assure it can be parsed.
+    Full,
+}
+
+impl Default for Mode {
+    fn default() -> Self {
+        Mode::Full
+    }
+}
+
+impl From<Mode> for index::verify::Mode {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Sha1CrcOnly => index::verify::Mode::Sha1Crc32,
+            Mode::Full => index::verify::Mode::Sha1Crc32Decode,
+        }
+    }
+}
+
+/// Statistics about all objects seen during verification, summed up over all packs and loose objects.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Statistics {
+    /// The amount of commits.
+    pub num_commits: u32,
+    /// The amount of trees.
+    pub num_trees: u32,
+    /// The amount of tags.
+    pub num_tags: u32,
+    /// The amount of blobs.
+    pub num_blobs: u32,
+    /// The amount of bytes occupied by all objects once they are decompressed and all deltas are resolved.
+    pub total_object_size: u64,
+    /// The length of the longest delta chain, or 0 if there are no deltified objects.
+    pub max_chain_length: u32,
+    /// A mapping of the length of the delta chain to the amount of objects at that length, with loose objects
+    /// and undeltified packed objects having a length of 0.
+    pub objects_per_chain_length: BTreeMap<u32, u32>,
+}
+
+impl Statistics {
+    /// The amount of all objects.
+    pub fn num_objects(&self) -> u32 {
+        self.num_commits + self.num_trees + self.num_tags + self.num_blobs
+    }
+
+    pub(crate) fn add_object(&mut self, kind: git_object::Kind, size: u64) {
+        use git_object::Kind::*;
+        match kind {
+            Commit => self.num_commits += 1,
+            Tree => self.num_trees += 1,
+            Tag => self.num_tags += 1,
+            Blob => self.num_blobs += 1,
+        }
+        self.total_object_size += size;
+        *self.objects_per_chain_length.entry(0).or_default() += 1;
+    }
+
+    pub(crate) fn add_pack(&mut self, outcome: &index::traverse::Outcome) {
+        self.num_commits += outcome.num_commits;
+        self.num_trees += outcome.num_trees;
+        self.num_tags += outcome.num_tags;
+        self.num_blobs += outcome.num_blobs;
+        self.total_object_size += outcome.total_object_size;
+        for (chain_length, count) in &outcome.objects_per_chain_length {
+            *self.objects_per_chain_length.entry(*chain_length).or_default() += count;
+        }
+        self.max_chain_length = self
+            .objects_per_chain_length
+            .keys()
+            .next_back()
+            .copied()
+            .unwrap_or_default();
+    }
+
+    pub(crate) fn merge(&mut self, other: &Statistics) {
+        self.num_commits += other.num_commits;
+        self.num_trees += other.num_trees;
+        self.num_tags += other.num_tags;
+        self.num_blobs += other.num_blobs;
+        self.total_object_size += other.total_object_size;
+        for (chain_length, count) in &other.objects_per_chain_length {
+            *self.objects_per_chain_length.entry(*chain_length).or_default() += count;
+        }
+        self.max_chain_length = self.max_chain_length.max(other.max_chain_length);
+    }
+}
@@ -35,3 +35,35 @@ pub trait Write {
         hash: git_hash::Kind,
     ) -> Result<git_hash::ObjectId, Self::Error>;
 }
+
+/// Determines which writes to the object database are verified by reading back what was written, for storage that can't
+/// be trusted to retain what was written to it.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum Verify {
+    /// Trust that written objects can be read back, which costs nothing.
+    Never,
+    /// Read back each loose object after writing it, decompress and hash it, and compare the hash with its id.
+    LooseOnly,
+    /// Like [`LooseOnly`][Verify::LooseOnly], and additionally verify the integrity of all objects in packs written
+    /// on behalf of the object database.
+    Always,
+}
+
+impl Default for Verify {
+    fn default() -> Self {
+        Verify::Never
+    }
+}
+
+impl Verify {
+    /// Return true if written loose objects are verified.
+    pub fn loose_objects(&self) -> bool {
+        matches!(self, Verify::LooseOnly | Verify::Always)
+    }
+
+    /// Return true if written packs are verified.
+    pub fn packs(&self) -> bool {
+        matches!(self, Verify::Always)
+    }
+}
@@ -0,0 +1,21 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q a
+(cd a
+  git config commit.gpgsign false
+  echo a > a
+  git add a
+  git commit -q -m "a"
+)
+
+git clone -q --shared a b
+echo "../../../a/.git/objects" > b/.git/objects/info/alternates
+(cd b
+  git config commit.gpgsign false
+  echo b > b
+  git add b
+  git commit -q -m "b"
+)
+
+echo "../../../b/.git/objects" >> a/.git/objects/info/alternates
@@ -0,0 +1,14 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+echo "packed content" > packed
+git add packed
+git commit -q -m "packed"
+git -c pack.compression=0 repack -adq
+
+echo "loose content" > loose
+git add loose
+git commit -q -m "loose"
@@ -0,0 +1 @@
+../../../git-pack/tests/fixtures/make_repo_multi_index.sh
\ No newline at end of file
@@ -1,7 +1,4 @@
-use std::{
-    fs, io,
-    path::{Path, PathBuf},
-};
+use std::{fs, io, path::PathBuf};
 
 use git_odb::alternate;
 
@@ -34,24 +31,93 @@ fn alternate_with(
     Ok((at, to))
 }
 
+fn file_names(paths: Vec<PathBuf>) -> Vec<String> {
+    paths
+        .into_iter()
+        .map(|p| p.file_name().expect("non-root").to_str().expect("utf8").to_owned())
+        .collect()
+}
+
 #[test]
-fn circular_alternates_are_detected_with_relative_paths() -> crate::Result {
+fn circular_alternates_are_skipped_with_relative_paths() -> crate::Result {
     let tmp = git_testtools::tempfile::TempDir::new()?;
     let (from, _) = alternate(tmp.path().join("a"), tmp.path().join("b"))?;
-    alternate(tmp.path().join("b"), Path::new("..").join("a"))?;
+    fs::create_dir_all(tmp.path().join("b").join("info"))?;
+    fs::write(tmp.path().join("b").join("info").join("alternates"), "../a")?;
 
-    match alternate::resolve(&from) {
-        Err(alternate::Error::Cycle(chain)) => {
-            assert_eq!(
-                chain
-                    .into_iter()
-                    .map(|p| p.file_name().expect("non-root").to_str().expect("utf8").to_owned())
-                    .collect::<Vec<_>>(),
-                vec!["a", "b"]
-            );
+    assert_eq!(file_names(alternate::resolve(&from)?), vec!["b"]);
+    Ok(())
+}
+
+#[test]
+fn nested_relative_paths_resolve_relative_to_the_directory_containing_them_in_order() -> crate::Result {
+    let tmp = git_testtools::tempfile::TempDir::new()?;
+    for (dir, alternates) in &[
+        ("a", Some("../b\n../c\n")),
+        ("b", Some("../nested/d")),
+        ("c", None),
+        ("nested/d", None),
+    ] {
+        let info = tmp.path().join(dir).join("info");
+        fs::create_dir_all(&info)?;
+        if let Some(alternates) = alternates {
+            fs::write(info.join("alternates"), alternates)?;
+        }
+    }
+    let from = tmp.path().join("a");
+
+    assert_eq!(file_names(alternate::resolve(&from)?), vec!["b", "d", "c"]);
+    Ok(())
+}
+
+#[test]
+fn alternates_nested_too_deeply_are_an_error() -> crate::Result {
+    let tmp = git_testtools::tempfile::TempDir::new()?;
+    let dirs: Vec<_> = (0..=alternate::MAX_DEPTH + 1)
+        .map(|level| tmp.path().join(level.to_string()))
+        .collect();
+    for pair in dirs.windows(2) {
+        alternate(&pair[0], &pair[1])?;
+    }
+    assert_eq!(
+        alternate::resolve(&dirs[1])?.len(),
+        alternate::MAX_DEPTH,
+        "the maximum amount of levels is fine"
+    );
+
+    let deepest = tmp.path().join("too-deep");
+    alternate(dirs.last().expect("non-empty"), &deepest)?;
+    match alternate::resolve(&dirs[0]) {
+        Err(alternate::Error::Depth { path, max_depth }) => {
+            assert_eq!(path, dirs[alternate::MAX_DEPTH + 1].join("info").join("alternates"));
+            assert_eq!(max_depth, alternate::MAX_DEPTH);
         }
         res => unreachable!("should be a specific kind of error: {:?}", res),
     }
+
+    assert_eq!(
+        alternate::resolve_with_max_depth(&dirs[0], alternate::MAX_DEPTH + 1)?.len(),
+        alternate::MAX_DEPTH + 2,
+        "the limit is configurable"
+    );
+    assert!(matches!(
+        alternate::resolve_with_max_depth(&dirs[0], 1),
+        Err(alternate::Error::Depth { max_depth: 1, .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn missing_alternate_directories_are_unreadable() -> crate::Result {
+    let tmp = git_testtools::tempfile::TempDir::new()?;
+    let from = tmp.path().join("a");
+    fs::create_dir_all(from.join("info"))?;
+    fs::write(from.join("info").join("alternates"), "../missing")?;
+
+    match alternate::resolve(&from) {
+        Err(alternate::Error::Unreadable { path, .. }) => assert_eq!(path, from.join("../missing")),
+        res => unreachable!("should be a specific kind of error: {:?}", res),
+    }
     Ok(())
 }
 
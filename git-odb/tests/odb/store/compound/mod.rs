@@ -2,6 +2,8 @@ use git_odb::compound::Store;
 
 use crate::fixture_path;
 
+mod verify;
+
 fn db() -> Store {
     Store::at(fixture_path("objects")).expect("valid object path")
 }
@@ -40,3 +42,191 @@ mod locate {
         can_locate(&db(), "dd25c539efbb0ab018caa4cda2d133285634e9b5"); // pack c043
     }
 }
+
+mod multi_index {
+    use std::convert::TryInto;
+
+    use git_odb::compound::Store;
+
+    use crate::scripted_fixture_repo_writable;
+
+    fn all_packed_ids(db: &Store) -> Vec<git_hash::ObjectId> {
+        db.bundles.iter().flat_map(|b| b.index.iter().map(|e| e.oid)).collect()
+    }
+
+    fn assert_all_found(db: &Store, ids: &[git_hash::ObjectId]) {
+        let mut buf = Vec::new();
+        for id in ids {
+            assert!(db
+                .try_find(id, &mut buf, &mut git_pack::cache::Never)
+                .expect("no read error")
+                .is_some());
+        }
+        assert!(db
+            .try_find(
+                crate::hex_to_id("ffffffffffffffffffffffffffffffffffffffff"),
+                &mut buf,
+                &mut git_pack::cache::Never
+            )
+            .expect("no read error")
+            .is_none());
+    }
+
+    #[test]
+    fn is_used_to_find_packed_objects() -> crate::Result {
+        let tmp = scripted_fixture_repo_writable("make_repo_multi_index.sh")?;
+        let db = Store::at(tmp.path().join(".git/objects"))?;
+        assert_eq!(db.bundles.len(), 50);
+        let multi_index = db.multi_index.as_ref().expect("a valid multi-pack index is loaded");
+        assert_eq!(multi_index.file.num_packs(), 50);
+        assert!(multi_index.uncovered_bundle_indices.is_empty());
+
+        let ids = all_packed_ids(&db);
+        assert_eq!(ids.len(), 150);
+        assert_all_found(&db, &ids);
+        Ok(())
+    }
+
+    #[test]
+    fn corrupt_or_stale_indices_are_ignored() -> crate::Result {
+        let tmp = scripted_fixture_repo_writable("make_repo_multi_index.sh")?;
+        let objects_dir = tmp.path().join(".git/objects");
+        let midx_path = objects_dir.join("pack/multi-pack-index");
+        let data = std::fs::read(&midx_path)?;
+        std::fs::write(&midx_path, &data[..data.len() / 2])?;
+
+        let db = Store::at(&objects_dir)?;
+        assert!(db.multi_index.is_none(), "truncated files are ignored");
+        assert_all_found(&db, &all_packed_ids(&db));
+
+        std::fs::write(&midx_path, &data)?;
+        let removed_index = db.bundles[0].index.path().to_owned();
+        std::fs::remove_file(removed_index.with_extension("pack"))?;
+        std::fs::remove_file(&removed_index)?;
+
+        let db = Store::at(&objects_dir)?;
+        assert_eq!(db.bundles.len(), 49);
+        assert!(
+            db.multi_index.is_none(),
+            "indices referring to missing packs are ignored"
+        );
+        assert_all_found(&db, &all_packed_ids(&db));
+        Ok(())
+    }
+
+    #[test]
+    fn corrupt_entries_yield_errors_instead_of_panicking() -> crate::Result {
+        let tmp = scripted_fixture_repo_writable("make_repo_multi_index.sh")?;
+        let objects_dir = tmp.path().join(".git/objects");
+        let midx_path = objects_dir.join("pack/multi-pack-index");
+        let mut data = std::fs::read(&midx_path)?;
+        let num_chunks = data[6] as usize;
+        let offsets = data[12..][..num_chunks * 12]
+            .chunks(12)
+            .find(|entry| &entry[..4] == b"OOFF")
+            .map(|entry| u64::from_be_bytes(entry[4..].try_into().expect("8 bytes")) as usize)
+            .expect("offsets chunk");
+        data[offsets..][..4].copy_from_slice(&u32::MAX.to_be_bytes());
+        std::fs::write(&midx_path, &data)?;
+
+        let db = Store::at(&objects_dir)?;
+        let corrupt_id = db
+            .multi_index
+            .as_ref()
+            .expect("it can still be opened")
+            .file
+            .oid_at_index(0)
+            .to_owned();
+        let mut buf = Vec::new();
+        assert!(matches!(
+            db.try_find(corrupt_id, &mut buf, &mut git_pack::cache::Never),
+            Err(git_odb::compound::find::Error::MultiIndex(_))
+        ));
+        assert!(db.try_find_header(corrupt_id).is_err());
+        assert!(
+            db.contains(corrupt_id),
+            "all packs are searched if errors can't be returned"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn packs_added_after_writing_it_are_searched_too() -> crate::Result {
+        let tmp = scripted_fixture_repo_writable("make_repo_multi_index.sh")?;
+        let objects_dir = tmp.path().join(".git/objects");
+        for (name, content) in [("a", "new content"), ("b", "more new content")] {
+            std::fs::write(tmp.path().join(name), content)?;
+        }
+        let status = std::process::Command::new("bash")
+            .arg("-c")
+            .arg("git add a b && git -c commit.gpgsign=false commit -q -m new && git repack -q")
+            .current_dir(tmp.path())
+            .env("GIT_AUTHOR_NAME", "author")
+            .env("GIT_AUTHOR_EMAIL", "author@example.com")
+            .env("GIT_COMMITTER_NAME", "committer")
+            .env("GIT_COMMITTER_EMAIL", "committer@example.com")
+            .status()?;
+        assert!(status.success());
+
+        let db = Store::at(&objects_dir)?;
+        assert_eq!(db.bundles.len(), 51);
+        let multi_index = db
+            .multi_index
+            .as_ref()
+            .expect("the index is still valid for the packs it knows");
+        assert_eq!(multi_index.uncovered_bundle_indices.len(), 1);
+        let ids = all_packed_ids(&db);
+        assert_eq!(ids.len(), 150 + 4, "two blobs, a tree and a commit were added");
+        assert_all_found(&db, &ids);
+        Ok(())
+    }
+
+    #[test]
+    fn stores_holding_it_become_stale_when_repacking_and_refresh_to_find_all_objects() -> crate::Result {
+        let tmp = scripted_fixture_repo_writable("make_repo_multi_index.sh")?;
+        let objects_dir = tmp.path().join(".git/objects");
+        let mut db = git_odb::linked::Store::at(&objects_dir)?;
+        let other = git_odb::linked::Store::at(&objects_dir)?;
+        assert!(db.dbs[0].multi_index.is_some());
+        assert!(!db.is_stale());
+        assert!(!other.is_stale_after_miss());
+        let ids = all_packed_ids(&db.dbs[0]);
+
+        let status = std::process::Command::new("git")
+            .args(["repack", "-adq"])
+            .current_dir(tmp.path())
+            .status()?;
+        assert!(status.success());
+        assert!(
+            !objects_dir.join("pack/multi-pack-index").exists(),
+            "the index referred to packs that were removed"
+        );
+
+        assert!(
+            !other.is_stale_after_miss(),
+            "the disk is only looked at after the first miss, so the change isn't noticed yet"
+        );
+        assert!(db.is_stale_after_miss(), "changes on disk are detected");
+        assert!(
+            other.is_stale_after_miss(),
+            "changes seen on disk are announced to all stores of the process"
+        );
+        assert!(db.refresh_if_stale()?);
+        assert!(!db.is_stale());
+        assert!(!db.is_stale_after_miss());
+        assert_eq!(db.dbs[0].bundles.len(), 1);
+        assert!(db.dbs[0].multi_index.is_none());
+        assert_all_found(&db.dbs[0], &ids);
+        assert!(!db.refresh_if_stale()?, "nothing changed since");
+
+        git_odb::generation::bump(objects_dir.join("pack").join(".."));
+        assert!(
+            db.is_stale_after_miss(),
+            "changes announced within the process are detected no matter how the path is spelled"
+        );
+        assert!(db.refresh_if_stale()?);
+        assert!(!db.is_stale());
+        assert_all_found(&db.dbs[0], &ids);
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use git_features::progress;
+use git_odb::{
+    compound::{verify, Store},
+    loose,
+    pack::index::traverse,
+    verify::Mode,
+};
+
+use crate::{hex_to_id, scripted_fixture_repo_read_only, scripted_fixture_repo_writable};
+
+fn verify(db: &Store, mode: Mode) -> Result<verify::Outcome, verify::Error> {
+    db.verify_integrity(mode, None, progress::Discard, Arc::new(AtomicBool::new(false)))
+}
+
+fn pack_paths(objects_dir: &Path) -> crate::Result<(PathBuf, PathBuf)> {
+    let db = Store::at(objects_dir)?;
+    let bundle = &db.bundles[0];
+    Ok((bundle.pack.path().to_owned(), bundle.index.path().to_owned()))
+}
+
+/// Pack and index files are read-only, but can be replaced.
+fn overwrite(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    std::fs::remove_file(path)?;
+    std::fs::write(path, data)
+}
+
+fn write_with_trailer(path: &Path, mut data: Vec<u8>) -> crate::Result<git_hash::ObjectId> {
+    let trailer_start = data.len() - 20;
+    let mut hasher = git_features::hash::Sha1::default();
+    hasher.update(&data[..trailer_start]);
+    let checksum = hasher.digest();
+    data[trailer_start..].copy_from_slice(&checksum);
+    overwrite(path, &data)?;
+    Ok(git_hash::ObjectId::from(checksum))
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + u32::from(*byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+/// Flip a bit in the uncompressed content of the only blob in the pack, and if `fix_checksums` is true, update the checksums of
+/// the pack and its index so that only the object itself is corrupt.
+fn corrupt_packed_blob(objects_dir: &Path, fix_checksums: bool) -> crate::Result<PathBuf> {
+    let (pack_path, index_path) = pack_paths(objects_dir)?;
+    let mut pack = std::fs::read(&pack_path)?;
+    let pos = pack
+        .windows(b"packed content".len())
+        .position(|w| w == b"packed content")
+        .expect("the pack is not compressed");
+    pack[pos] ^= 0b0010_0000;
+    // The stored zlib stream of the blob ends with the adler32 checksum of its content, which has to match as well.
+    let content_end = pos + b"packed content\n".len();
+    let adler32 = adler32(&pack[pos..content_end]);
+    pack[content_end..][..4].copy_from_slice(&adler32.to_be_bytes());
+    if fix_checksums {
+        let pack_checksum = write_with_trailer(&pack_path, pack)?;
+        let mut index = std::fs::read(&index_path)?;
+        let pack_checksum_start = index.len() - 40;
+        index[pack_checksum_start..][..20].copy_from_slice(pack_checksum.as_bytes());
+        write_with_trailer(&index_path, index)?;
+    } else {
+        overwrite(&pack_path, &pack)?;
+    }
+    Ok(pack_path)
+}
+
+#[test]
+fn intact_repositories_yield_statistics_over_packed_and_loose_objects() -> crate::Result {
+    let dir = scripted_fixture_repo_read_only("make_repo_for_verification.sh")?;
+    let db = Store::at(dir.join(".git/objects"))?;
+    for mode in [Mode::Full, Mode::Sha1CrcOnly] {
+        let outcome = verify(&db, mode)?;
+        let stats = &outcome.statistics;
+        assert_eq!(
+            (stats.num_commits, stats.num_trees, stats.num_tags, stats.num_blobs),
+            (2, 2, 0, 2)
+        );
+        assert_eq!(stats.num_objects(), 6);
+        assert_eq!(
+            stats.total_object_size, 507,
+            "the size of all objects as reported by git"
+        );
+        assert_eq!(stats.max_chain_length, 0, "there are no deltas");
+        assert_eq!(
+            stats.objects_per_chain_length.clone().into_iter().collect::<Vec<_>>(),
+            vec![(0, 6)]
+        );
+
+        assert_eq!(outcome.packs.len(), 1);
+        assert_eq!(outcome.packs[0].0, db.bundles[0].pack.path());
+        assert_eq!(
+            outcome.packs[0].1.num_blobs, 1,
+            "the details of each pack are available too"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn loose_objects_whose_content_does_not_match_their_name_are_reported_with_their_path() -> crate::Result {
+    let dir = scripted_fixture_repo_writable("make_repo_for_verification.sh")?;
+    let objects_dir = dir.path().join(".git/objects");
+    let blob = hex_to_id("732ded09522e596f73cd2858d06e9cdb64001b07");
+    let tree = hex_to_id("222b4ee898b9621a288140308d60ddb77686d388");
+    let (blob_path, tree_path) = (
+        objects_dir.join("73/2ded09522e596f73cd2858d06e9cdb64001b07"),
+        objects_dir.join("22/2b4ee898b9621a288140308d60ddb77686d388"),
+    );
+    std::fs::remove_file(&tree_path)?;
+    std::fs::copy(&blob_path, &tree_path)?;
+
+    let db = Store::at(&objects_dir)?;
+    for mode in [Mode::Full, Mode::Sha1CrcOnly] {
+        match verify(&db, mode) {
+            Err(verify::Error::Loose(loose::verify::Error::ObjectHashMismatch {
+                kind,
+                path,
+                expected,
+                actual,
+            })) => {
+                assert_eq!(kind, git_object::Kind::Blob);
+                assert_eq!(path, tree_path);
+                assert_eq!(expected, tree);
+                assert_eq!(actual, blob);
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn corrupt_packed_objects_are_reported_with_pack_path_offset_and_ids() -> crate::Result {
+    let dir = scripted_fixture_repo_writable("make_repo_for_verification.sh")?;
+    let objects_dir = dir.path().join(".git/objects");
+    let pack_path = corrupt_packed_blob(&objects_dir, true)?;
+
+    let db = Store::at(&objects_dir)?;
+    let blob = hex_to_id("5e4999f3bfe35be914c4bba7b0a362112cd4474c");
+    let index = &db.bundles[0].index;
+    let blob_offset = index.pack_offset_at_index(index.lookup(blob).expect("blob is packed"));
+    match verify(&db, Mode::Sha1CrcOnly) {
+        Err(verify::Error::Pack {
+            path,
+            source:
+                traverse::Error::PackObjectMismatch {
+                    expected,
+                    actual,
+                    offset,
+                    kind,
+                },
+        }) => {
+            assert_eq!(path, pack_path);
+            assert_eq!(offset, blob_offset);
+            assert_eq!(kind, git_object::Kind::Blob);
+            assert_eq!(expected, blob);
+            assert_eq!(
+                actual,
+                hex_to_id("1d213c4908639f67ca7f35b3e83d9d6fb078765b"),
+                "the hash of 'Packed content\\n'"
+            );
+        }
+        res => panic!("unexpected result: {:?}", res),
+    }
+    Ok(())
+}
+
+#[test]
+fn corrupt_packs_fail_their_checksum_before_objects_are_checked() -> crate::Result {
+    let dir = scripted_fixture_repo_writable("make_repo_for_verification.sh")?;
+    let objects_dir = dir.path().join(".git/objects");
+    let pack_path = corrupt_packed_blob(&objects_dir, false)?;
+
+    let db = Store::at(&objects_dir)?;
+    match verify(&db, Mode::Full) {
+        Err(verify::Error::Pack {
+            path,
+            source: traverse::Error::PackChecksum(_),
+        }) => assert_eq!(path, pack_path),
+        res => panic!("unexpected result: {:?}", res),
+    }
+    Ok(())
+}
+
+#[test]
+fn interruptions_abort_verification() -> crate::Result {
+    let dir = scripted_fixture_repo_read_only("make_repo_for_verification.sh")?;
+    let db = Store::at(dir.join(".git/objects"))?;
+    match db.verify_integrity(Mode::Full, None, progress::Discard, Arc::new(AtomicBool::new(true))) {
+        Err(verify::Error::Pack {
+            source: traverse::Error::Interrupted,
+            ..
+        }) => {}
+        res => panic!("unexpected result: {:?}", res),
+    }
+
+    let res = db
+        .loose
+        .verify_integrity(Mode::Full, progress::Discard, &AtomicBool::new(true));
+    assert!(matches!(res, Err(loose::verify::Error::Interrupted)), "{:?}", res);
+    Ok(())
+}
@@ -19,6 +19,15 @@ fn refresh() {
     assert_eq!(db.iter().count(), 146, "calling this multiple times is ok");
 }
 
+#[test]
+fn read_only_is_retained_across_refreshes() {
+    let mut db = db();
+    assert!(!db.is_read_only());
+    db.set_read_only(true).refresh().unwrap();
+    assert!(db.is_read_only());
+    assert!(db.dbs.iter().all(|db| db.loose.read_only));
+}
+
 mod iter {
     use crate::odb::store::linked::db;
 
@@ -89,6 +98,33 @@ mod init {
         Ok(())
     }
 
+    #[test]
+    fn objects_of_alternates_are_found_even_with_circular_alternates() -> crate::Result {
+        let dir = crate::scripted_fixture_repo_read_only("make_alternates_repos.sh")?;
+        let objects_a = dir.join("a").join(".git").join("objects");
+        let objects_b = dir.join("b").join(".git").join("objects");
+        let (a, b) = (linked::Store::at(&objects_a)?, linked::Store::at(&objects_b)?);
+        assert_eq!(a.dbs.len(), 2, "a and b refer to each other");
+        assert_eq!(b.dbs.len(), 2);
+
+        let own_ids = |objects_dir: &std::path::Path| -> Result<Vec<_>, Box<dyn std::error::Error>> {
+            Ok(git_odb::loose::Store::at(objects_dir)
+                .iter()
+                .collect::<Result<Vec<_>, _>>()?)
+        };
+        let (ids_a, ids_b) = (own_ids(&objects_a)?, own_ids(&objects_b)?);
+        assert_eq!(ids_a.len(), 3, "blob, tree and commit");
+        assert_eq!(ids_b.len(), 3, "only new objects are stored in the clone");
+        for id in ids_a.iter().chain(ids_b.iter()) {
+            assert!(
+                a.contains(id) && b.contains(id),
+                "{} is found in any of the linked stores",
+                id
+            );
+        }
+        Ok(())
+    }
+
     #[test]
     fn a_linked_db_without_alternates() -> crate::Result {
         let tmp = git_testtools::tempfile::TempDir::new()?;
@@ -105,3 +141,212 @@ mod init {
         assert_eq!(db.dbs[0].bundles.len(), 3)
     }
 }
+
+mod negative_cache {
+    use git_odb::{linked, linked::negative_cache::Statistics, pack, Find, Write};
+
+    fn store_with_cache(path: &std::path::Path) -> crate::Result<linked::Store> {
+        let mut db = linked::Store::at(path)?;
+        db.negative_cache = linked::negative_cache::Cache::with_capacity(16);
+        Ok(db)
+    }
+
+    #[test]
+    fn it_is_disabled_by_default() -> crate::Result {
+        let tmp = git_testtools::tempfile::TempDir::new()?;
+        let db = linked::Store::at(tmp.path())?;
+        let missing = git_hash::ObjectId::null_sha1();
+        assert!(!db.contains(missing));
+        assert!(!db.contains(missing));
+        assert_eq!(
+            db.negative_cache.statistics(),
+            Statistics {
+                hits: 0,
+                misses: 2,
+                len: 0
+            },
+            "objects written by other processes are found right away"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn repeated_misses_are_answered_by_the_cache_until_the_object_is_written() -> crate::Result {
+        let tmp = git_testtools::tempfile::TempDir::new()?;
+        let db = store_with_cache(tmp.path())?;
+        let data = b"hello world";
+        let id = git_odb::sink().write_buf(git_object::Kind::Blob, data, git_hash::Kind::Sha1)?;
+
+        let mut buf = Vec::new();
+        for _ in 0..3 {
+            assert!(db.try_find(id, &mut buf, &mut pack::cache::Never)?.is_none());
+        }
+        assert!(!db.contains(id));
+        assert_eq!(
+            db.negative_cache.statistics(),
+            Statistics {
+                hits: 3,
+                misses: 1,
+                len: 1
+            },
+            "only the first lookup had to search the store"
+        );
+
+        assert_eq!(db.write_buf(git_object::Kind::Blob, data, git_hash::Kind::Sha1)?, id);
+        assert_eq!(
+            db.negative_cache.statistics().len,
+            0,
+            "writes invalidate the written id"
+        );
+        assert!(
+            db.try_find(id, &mut buf, &mut pack::cache::Never)?.is_some(),
+            "the written object is never hidden by a stale negative result"
+        );
+        assert_eq!(db.negative_cache.statistics().misses, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn refresh_clears_the_cache_but_keeps_statistics() -> crate::Result {
+        let tmp = git_testtools::tempfile::TempDir::new()?;
+        let mut db = store_with_cache(tmp.path())?;
+        let missing = git_hash::ObjectId::null_sha1();
+        assert!(!db.contains(missing));
+        assert!(!db.contains(missing));
+
+        db.refresh()?;
+        assert_eq!(
+            db.negative_cache.statistics(),
+            Statistics {
+                hits: 1,
+                misses: 1,
+                len: 0
+            }
+        );
+        assert!(!db.contains(missing));
+        assert_eq!(
+            db.negative_cache.statistics().len,
+            1,
+            "the cache stays enabled after a refresh"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn capacity_bounds_the_amount_of_remembered_ids() {
+        let cache = linked::negative_cache::Cache::with_capacity(1);
+        let (a, b) = (git_hash::ObjectId::null_sha1(), git_hash::ObjectId::from([1; 20]));
+        cache.insert(&a);
+        cache.insert(&b);
+        assert!(!cache.contains(&a), "the oldest entry was evicted");
+        assert!(cache.contains(&b));
+
+        let disabled = linked::negative_cache::Cache::with_capacity(0);
+        disabled.insert(&a);
+        assert_eq!(disabled.statistics().len, 0);
+    }
+}
+
+mod find_header {
+    use std::convert::TryInto;
+
+    use git_odb::{pack, Find};
+
+    use crate::{fixture_path, hex_to_id, odb::store::linked::db};
+
+    fn git_cat_file_batch_check() -> crate::Result<Vec<(git_hash::ObjectId, git_object::Kind, u64)>> {
+        let git_dir = git_testtools::tempfile::TempDir::new()?;
+        let run = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .env("GIT_DIR", git_dir.path())
+                .env("GIT_OBJECT_DIRECTORY", fixture_path("objects"))
+                .output()
+        };
+        assert!(run(&["init", "--bare", "-q"])?.status.success());
+        let out = run(&["cat-file", "--batch-all-objects", "--batch-check"])?;
+        assert!(out.status.success());
+        Ok(std::str::from_utf8(&out.stdout)?
+            .lines()
+            .map(|line| {
+                let mut tokens = line.split(' ');
+                let id = hex_to_id(tokens.next().expect("id"));
+                let kind = git_object::Kind::from_bytes(tokens.next().expect("kind").as_bytes()).expect("valid kind");
+                let size = tokens.next().expect("size").parse().expect("valid size");
+                (id, kind, size)
+            })
+            .collect())
+    }
+
+    #[test]
+    fn matches_git_for_all_loose_and_packed_objects() -> crate::Result {
+        let db = db();
+        let expected = git_cat_file_batch_check()?;
+        assert_eq!(expected.len(), 146, "git sees all loose and packed objects");
+
+        let mut buf = Vec::new();
+        for (id, kind, size) in expected {
+            assert_eq!(db.try_find_header(id)?, Some((kind, size)), "{}", id);
+            assert!(db.contains(id));
+
+            let object = db
+                .try_find(id, &mut buf, &mut pack::cache::Never)?
+                .expect("object is present");
+            assert_eq!(object.kind, kind);
+            assert_eq!(object.data.len(), size.try_into().expect("fits into memory"));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn missing_objects_are_none() -> crate::Result {
+        let db = db();
+        let id = hex_to_id("ffffffffffffffffffffffffffffffffffffffff");
+        assert_eq!(db.try_find_header(id)?, None);
+        assert!(!db.contains(id));
+        assert_eq!(db.dbs[0].try_find_header(id)?, None);
+        assert!(!db.dbs[0].contains(id));
+        assert_eq!(db.dbs[0].loose.try_find_header(id)?, None);
+        Ok(())
+    }
+}
+
+mod verify_integrity {
+    use std::sync::{atomic::AtomicBool, Arc};
+
+    use git_odb::{linked::Store, verify::Mode};
+
+    use crate::scripted_fixture_repo_read_only;
+
+    #[test]
+    fn statistics_of_all_linked_databases_are_combined() -> crate::Result {
+        let dir = scripted_fixture_repo_read_only("make_repo_for_verification.sh")?;
+        let mut db = Store::at(dir.join(".git/objects"))?;
+        let single = db.verify_integrity(
+            Mode::Full,
+            None,
+            git_features::progress::Discard,
+            Arc::new(AtomicBool::new(false)),
+        )?;
+        assert_eq!(single.statistics.num_objects(), 6);
+
+        db.dbs.push(git_odb::compound::Store::at(dir.join(".git/objects"))?);
+        let outcome = db.verify_integrity(
+            Mode::Sha1CrcOnly,
+            None,
+            git_features::progress::Discard,
+            Arc::new(AtomicBool::new(false)),
+        )?;
+        assert_eq!(
+            outcome.statistics.num_objects(),
+            12,
+            "objects are counted once per database"
+        );
+        assert_eq!(
+            outcome.statistics.total_object_size,
+            2 * single.statistics.total_object_size
+        );
+        assert_eq!(outcome.packs.len(), 2);
+        Ok(())
+    }
+}
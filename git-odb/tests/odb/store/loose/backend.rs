@@ -58,6 +58,187 @@ mod write {
         }
         Ok(())
     }
+
+    #[test]
+    fn read_only_stores_reject_writes_without_touching_the_directory() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut db = loose::Store::at(dir.path());
+        db.read_only = true;
+        let mut buf = Vec::new();
+        let obj = locate_oid(object_ids()[0], &mut buf);
+
+        assert!(matches!(
+            db.write_buf(obj.kind, obj.data, git_hash::Kind::Sha1),
+            Err(loose::write::Error::ReadOnly { .. })
+        ));
+        assert!(matches!(
+            db.write(&obj.decode()?, git_hash::Kind::Sha1),
+            Err(loose::write::Error::ReadOnly { .. })
+        ));
+        assert_eq!(
+            std::fs::read_dir(dir.path())?.count(),
+            0,
+            "not even a tempfile was created"
+        );
+        Ok(())
+    }
+
+    mod verify {
+        use std::{io, path::PathBuf};
+
+        use git_odb::{loose, Verify, Write};
+
+        use crate::{
+            fixture_path,
+            store::loose::backend::{locate_oid, object_ids},
+        };
+
+        /// How [`Corrupting`] changes objects after they were written.
+        #[derive(Clone, Copy)]
+        enum Fault {
+            /// Replace the object with the given one, which decompresses fine but hashes to a different id.
+            ReplaceWith(git_hash::ObjectId),
+            /// Cut the compressed data in half so it can't be decompressed anymore.
+            Truncate,
+        }
+
+        /// A store which corrupts objects right after writing them, like storage which doesn't retain what was written to it.
+        struct Corrupting {
+            store: loose::Store,
+            fault: Fault,
+        }
+
+        impl Corrupting {
+            fn object_path(&self, id: &git_hash::oid) -> PathBuf {
+                let hex = id.to_string();
+                self.store.path.join(&hex[..2]).join(&hex[2..])
+            }
+        }
+
+        impl Write for Corrupting {
+            type Error = loose::write::Error;
+
+            fn write_stream(
+                &self,
+                kind: git_object::Kind,
+                size: u64,
+                from: impl io::Read,
+                hash: git_hash::Kind,
+            ) -> Result<git_hash::ObjectId, Self::Error> {
+                let unverified = loose::Store::at(&self.store.path);
+                let id = unverified.write_stream(kind, size, from, hash)?;
+                let path = self.object_path(&id);
+                let corrupt = match self.fault {
+                    Fault::ReplaceWith(other) => {
+                        let hex = other.to_string();
+                        std::fs::read(fixture_path("objects").join(&hex[..2]).join(&hex[2..]))?
+                    }
+                    Fault::Truncate => {
+                        let mut data = std::fs::read(&path)?;
+                        data.truncate(data.len() / 2);
+                        data
+                    }
+                };
+                std::fs::write(&path, corrupt)?;
+                if self.store.verify.loose_objects() {
+                    self.store.verify_written(&id)?;
+                }
+                Ok(id)
+            }
+        }
+
+        fn corrupting(dir: &tempfile::TempDir, verify: Verify, fault: Fault) -> Corrupting {
+            let mut store = loose::Store::at(dir.path());
+            store.verify = verify;
+            Corrupting { store, fault }
+        }
+
+        #[test]
+        fn verified_writes_of_intact_objects_succeed() -> crate::Result {
+            let dir = tempfile::tempdir()?;
+            let mut db = loose::Store::at(dir.path());
+            db.verify = Verify::Always;
+            let mut buf = Vec::new();
+            for oid in object_ids() {
+                let obj = locate_oid(oid, &mut buf);
+                assert_eq!(db.write_buf(obj.kind, obj.data, git_hash::Kind::Sha1)?, oid);
+                assert_eq!(db.write(&obj.decode()?, git_hash::Kind::Sha1)?, oid);
+                assert!(db.contains(oid));
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn objects_hashing_differently_when_read_back_fail_verification_and_are_removed() -> crate::Result {
+            let dir = tempfile::tempdir()?;
+            let (expected, other) = (object_ids()[0], object_ids()[1]);
+            let db = corrupting(&dir, Verify::LooseOnly, Fault::ReplaceWith(other));
+            let mut buf = Vec::new();
+            let obj = locate_oid(expected, &mut buf);
+
+            for use_stream in &[false, true] {
+                let res = if *use_stream {
+                    db.write_stream(obj.kind, obj.data.len() as u64, obj.data, git_hash::Kind::Sha1)
+                } else {
+                    db.write_buf(obj.kind, obj.data, git_hash::Kind::Sha1)
+                };
+                match res {
+                    Err(loose::write::Error::VerificationFailed {
+                        path,
+                        expected: e,
+                        actual,
+                    }) => {
+                        assert_eq!(e, expected);
+                        assert_eq!(actual, other, "the hash of what was read back");
+                        assert_eq!(path, db.object_path(&expected));
+                    }
+                    res => panic!("unexpected result: {:?}", res),
+                }
+                assert!(!db.store.contains(expected), "the corrupt object was removed");
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn objects_which_cannot_be_read_back_fail_verification_and_are_removed() -> crate::Result {
+            let dir = tempfile::tempdir()?;
+            let id = object_ids()[0];
+            let db = corrupting(&dir, Verify::Always, Fault::Truncate);
+            let mut buf = Vec::new();
+            let obj = locate_oid(id, &mut buf);
+
+            assert!(matches!(
+                db.write_buf(obj.kind, obj.data, git_hash::Kind::Sha1),
+                Err(loose::write::Error::VerificationRead { id: failed, .. }) if failed == id
+            ));
+            assert!(!db.store.contains(id), "the corrupt object was removed");
+            Ok(())
+        }
+
+        #[test]
+        fn without_verification_corruption_goes_unnoticed() -> crate::Result {
+            let dir = tempfile::tempdir()?;
+            let id = object_ids()[0];
+            let db = corrupting(&dir, Verify::Never, Fault::ReplaceWith(object_ids()[1]));
+            let mut buf = Vec::new();
+            let obj = locate_oid(id, &mut buf);
+
+            assert_eq!(db.write_buf(obj.kind, obj.data, git_hash::Kind::Sha1)?, id);
+            assert!(db.store.contains(id));
+            assert_eq!(
+                db.store.verify_written(&id).map_err(|err| err.to_string()),
+                Err(format!(
+                    "The object written to '{}' was removed as it hashes to {} instead of {} when read back",
+                    db.object_path(&id).display(),
+                    object_ids()[1],
+                    id
+                )),
+                "verification can be performed later"
+            );
+            assert!(!db.store.contains(id));
+            Ok(())
+        }
+    }
 }
 
 mod locate {
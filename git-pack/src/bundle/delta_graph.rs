@@ -0,0 +1,269 @@
+//! Inspect the delta relationships of all objects in a pack to learn which objects are bases, how long delta chains are
+//! and how much space deltas save, as obtained by [`Bundle::delta_graph()`][crate::Bundle::delta_graph()].
+use std::{collections::BTreeMap, io};
+
+use git_hash::ObjectId;
+
+use crate::data::entry::Header;
+
+/// The edge from a delta to the object it's based on.
+#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Edge {
+    /// The index of the base object in [`Graph::nodes`].
+    pub base: usize,
+    /// The size of the decompressed delta instructions which produce the object from its base.
+    pub delta_size: u64,
+}
+
+/// An object in the pack.
+#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Node {
+    /// The id of the object.
+    pub id: ObjectId,
+    /// The offset of the object's entry in the pack.
+    pub pack_offset: u64,
+    /// The kind of the object, which for deltas is the kind of the object at the end of their chain.
+    pub kind: git_object::Kind,
+    /// The size of the entry in the pack, including its header.
+    pub compressed_size: u64,
+    /// The size of the object once it's decompressed and resolved.
+    pub object_size: u64,
+    /// The amount of deltas to apply to obtain the object, which is 0 for objects that aren't deltas.
+    pub depth: u32,
+    /// The object this one is a delta of, or `None` if it's stored as is.
+    pub base: Option<Edge>,
+}
+
+impl Node {
+    /// The amount of bytes saved by storing the object compressed and possibly as delta instead of storing it as is.
+    pub fn saved_bytes(&self) -> u64 {
+        self.object_size.saturating_sub(self.compressed_size)
+    }
+}
+
+/// Statistics about all objects that are deltas of the same base object, directly or indirectly.
+#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chain {
+    /// The index of the base object in [`Graph::nodes`], which isn't a delta itself.
+    pub base: usize,
+    /// The amount of deltas based on `base`.
+    pub num_deltas: usize,
+    /// The depth of the longest delta chain.
+    pub max_depth: u32,
+    /// The sum of the sizes of all entries in the pack, including the one of `base`.
+    pub compressed_size: u64,
+    /// The sum of the sizes of all resolved objects, including `base`.
+    pub object_size: u64,
+}
+
+impl Chain {
+    /// The amount of bytes saved by storing all objects compressed and as delta instead of storing them as is.
+    pub fn saved_bytes(&self) -> u64 {
+        self.object_size.saturating_sub(self.compressed_size)
+    }
+}
+
+/// The delta relationships between all objects of a pack.
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Graph {
+    /// All objects of the pack ordered by their offset in the pack.
+    pub nodes: Vec<Node>,
+}
+
+mod error {
+    use git_hash::ObjectId;
+
+    /// The error returned by [`Bundle::delta_graph()`][crate::Bundle::delta_graph()].
+    #[derive(thiserror::Error, Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The size of the object produced by the delta at offset {pack_offset} could not be decoded")]
+        DeltaHeader {
+            pack_offset: u64,
+            source: crate::data::decode_entry::Error,
+        },
+        #[error("The delta at offset {pack_offset} refers to base object {base_id} which isn't contained in the pack")]
+        UnresolvedRefDelta { pack_offset: u64, base_id: ObjectId },
+        #[error(
+            "The delta at offset {pack_offset} refers to a base at offset {base_pack_offset} where no object starts"
+        )]
+        UnknownBaseOffset { pack_offset: u64, base_pack_offset: u64 },
+        #[error("The delta chain of the object at offset {pack_offset} forms a cycle")]
+        Cycle { pack_offset: u64 },
+    }
+}
+pub use error::Error;
+
+impl crate::Bundle {
+    /// Read the headers of all entries in the pack once to produce a [`Graph`] of delta relationships, along with the sizes
+    /// of all objects, for analysis and visualization.
+    ///
+    /// Only the first bytes of deltas are decompressed to learn the size of the object they produce, and the memory used
+    /// is proportional to the amount of objects in the pack, not to their size.
+    pub fn delta_graph(&self) -> Result<Graph, Error> {
+        let mut entries: Vec<_> = self.index.iter().map(|e| (e.pack_offset, e.oid)).collect();
+        entries.sort_by_key(|(pack_offset, _)| *pack_offset);
+        let node_at_offset = |pack_offset: u64| entries.binary_search_by_key(&pack_offset, |(ofs, _)| *ofs).ok();
+
+        let pack_end = self.pack.pack_end() as u64;
+        let mut nodes = Vec::with_capacity(entries.len());
+        let mut kinds = Vec::with_capacity(entries.len());
+        for (idx, (pack_offset, id)) in entries.iter().enumerate() {
+            let pack_offset = *pack_offset;
+            let next_offset = entries.get(idx + 1).map_or(pack_end, |(ofs, _)| *ofs);
+            let entry = self.pack.entry(pack_offset);
+            let base_pack_offset = match entry.header {
+                Header::OfsDelta { base_distance } => Some(entry.base_pack_offset(base_distance)),
+                Header::RefDelta { base_id } => Some(
+                    self.index
+                        .lookup(base_id)
+                        .map(|idx| self.index.pack_offset_at_index(idx))
+                        .ok_or(Error::UnresolvedRefDelta { pack_offset, base_id })?,
+                ),
+                Header::Commit | Header::Tree | Header::Blob | Header::Tag => None,
+            };
+            let (object_size, base) = match base_pack_offset {
+                Some(base_pack_offset) => (
+                    self.pack
+                        .decode_delta_object_size(&entry)
+                        .map_err(|source| Error::DeltaHeader { pack_offset, source })?,
+                    Some(Edge {
+                        base: node_at_offset(base_pack_offset).ok_or(Error::UnknownBaseOffset {
+                            pack_offset,
+                            base_pack_offset,
+                        })?,
+                        delta_size: entry.decompressed_size,
+                    }),
+                ),
+                None => (entry.decompressed_size, None),
+            };
+            kinds.push(entry.header.as_kind());
+            nodes.push(Node {
+                id: *id,
+                pack_offset,
+                // resolved below
+                kind: git_object::Kind::Blob,
+                compressed_size: next_offset - pack_offset,
+                object_size,
+                depth: 0,
+                base,
+            });
+        }
+        drop(entries);
+
+        // Deltas may refer to bases that come later in the pack, so resolve depths and kinds once all edges are known.
+        let mut resolved = vec![false; nodes.len()];
+        let mut chain = Vec::new();
+        for idx in 0..nodes.len() {
+            let mut cursor = idx;
+            while !resolved[cursor] {
+                match nodes[cursor].base {
+                    Some(edge) => {
+                        if chain.len() == nodes.len() {
+                            return Err(Error::Cycle {
+                                pack_offset: nodes[idx].pack_offset,
+                            });
+                        }
+                        chain.push(cursor);
+                        cursor = edge.base;
+                    }
+                    None => {
+                        nodes[cursor].kind = kinds[cursor].expect("non-delta objects have a kind");
+                        resolved[cursor] = true;
+                    }
+                }
+            }
+            let (mut depth, kind) = (nodes[cursor].depth, nodes[cursor].kind);
+            for delta in chain.drain(..).rev() {
+                depth += 1;
+                nodes[delta].depth = depth;
+                nodes[delta].kind = kind;
+                resolved[delta] = true;
+            }
+        }
+        Ok(Graph { nodes })
+    }
+}
+
+impl Graph {
+    /// Return the index of the object at the end of the delta chain of the object at `node`, which is `node` itself if it's not a delta.
+    ///
+    /// # Panics
+    ///
+    /// If `node` is out of bounds.
+    pub fn base_of(&self, mut node: usize) -> usize {
+        while let Some(edge) = self.nodes[node].base {
+            node = edge.base;
+        }
+        node
+    }
+
+    /// Return statistics for each object which is the base of at least one delta, ordered by the offset of the base object.
+    pub fn chains(&self) -> Vec<Chain> {
+        let mut chains = BTreeMap::new();
+        for (idx, node) in self.nodes.iter().enumerate().filter(|(_, node)| node.base.is_some()) {
+            let base = self.base_of(idx);
+            let chain = chains.entry(base).or_insert_with(|| {
+                let base_node = &self.nodes[base];
+                Chain {
+                    base,
+                    num_deltas: 0,
+                    max_depth: 0,
+                    compressed_size: base_node.compressed_size,
+                    object_size: base_node.object_size,
+                }
+            });
+            chain.num_deltas += 1;
+            chain.max_depth = chain.max_depth.max(node.depth);
+            chain.compressed_size += node.compressed_size;
+            chain.object_size += node.object_size;
+        }
+        chains.into_values().collect()
+    }
+
+    /// Return the amount of objects for each depth of delta chains, with the amount of objects which aren't deltas at index 0,
+    /// similar to the histogram printed by `git verify-pack -v`.
+    pub fn depth_histogram(&self) -> Vec<usize> {
+        let mut histogram = Vec::new();
+        for node in &self.nodes {
+            let depth = node.depth as usize;
+            if histogram.len() <= depth {
+                histogram.resize(depth + 1, 0);
+            }
+            histogram[depth] += 1;
+        }
+        histogram
+    }
+
+    /// Write the graph in the DOT format to `out` for visualization with [graphviz](https://graphviz.org), with a node
+    /// labelled with kind, abbreviated id and sizes for each object, and an edge labelled with the delta size from each delta to its base.
+    pub fn write_dot(&self, mut out: impl io::Write) -> io::Result<()> {
+        writeln!(out, "digraph pack {{")?;
+        writeln!(out, "  node [shape=box];")?;
+        for node in &self.nodes {
+            writeln!(
+                out,
+                "  \"{}\" [label=\"{} {}\\n{} bytes, {} in pack\"];",
+                node.id,
+                node.kind,
+                &node.id.to_sha1_hex_string()[..7],
+                node.object_size,
+                node.compressed_size
+            )?;
+        }
+        for node in &self.nodes {
+            if let Some(edge) = node.base {
+                writeln!(
+                    out,
+                    "  \"{}\" -> \"{}\" [label=\"{}\"];",
+                    node.id, self.nodes[edge.base].id, edge.delta_size
+                )?;
+            }
+        }
+        writeln!(out, "}}")
+    }
+}
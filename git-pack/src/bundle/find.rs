@@ -57,4 +57,36 @@ impl crate::Bundle {
                 }),
             })
     }
+
+    /// Find the object with the given [`ObjectId`][git_hash::ObjectId] and return its kind and size without decoding it.
+    ///
+    /// **Note** that ref deltas are automatically resolved within this pack only.
+    pub fn find_header(
+        &self,
+        id: impl AsRef<git_hash::oid>,
+    ) -> Result<Option<data::decode_header::Outcome>, crate::data::decode_entry::Error> {
+        let idx = match self.index.lookup(id) {
+            Some(idx) => idx,
+            None => return Ok(None),
+        };
+        self.get_header_by_index(idx).map(Some)
+    }
+
+    /// Like [`get_object_by_index()`][crate::Bundle::get_object_by_index()], but only returns the kind and size of the object
+    /// at `idx` without decoding it.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn get_header_by_index(
+        &self,
+        idx: u32,
+    ) -> Result<data::decode_header::Outcome, crate::data::decode_entry::Error> {
+        let ofs = self.index.pack_offset_at_index(idx);
+        self.pack.decode_header(self.pack.entry(ofs), |id| {
+            self.index
+                .lookup(id)
+                .map(|idx| self.pack.entry(self.index.pack_offset_at_index(idx)))
+        })
+    }
 }
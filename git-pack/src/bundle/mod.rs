@@ -19,6 +19,9 @@ impl Location {
     }
 }
 
+#[cfg(feature = "analysis")]
+pub mod delta_graph;
+
 ///
 pub mod init;
 
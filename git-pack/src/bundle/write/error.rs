@@ -2,7 +2,9 @@ use std::io;
 
 use git_tempfile::handle::Writable;
 
+/// The error returned by [`Bundle::write_to_directory()`][crate::Bundle::write_to_directory()] and its eager variant.
 #[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
 pub enum Error {
     #[error("An IO error occurred when reading the pack or creating a temporary file")]
     Io(#[from] io::Error),
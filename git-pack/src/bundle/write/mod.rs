@@ -11,7 +11,7 @@ use git_tempfile::{handle::Writable, AutoRemove, ContainingDirectory};
 use crate::data;
 
 mod error;
-use error::Error;
+pub use error::Error;
 
 mod types;
 use types::{LockWriter, PassThrough};
@@ -47,10 +47,7 @@ impl crate::Bundle {
     ) -> Result<Outcome, Error> {
         let mut read_progress = progress.add_child("read pack");
         read_progress.init(None, progress::bytes());
-        let pack = progress::Read {
-            reader: pack,
-            progress: progress::ThroughputOnDrop::new(read_progress),
-        };
+        let pack = git_features::io::ProgressRead::new(pack, progress::ThroughputOnDrop::new(read_progress));
 
         let data_file = Arc::new(parking_lot::Mutex::new(match directory.as_ref() {
             Some(directory) => git_tempfile::new(directory, ContainingDirectory::Exists, AutoRemove::Tempfile)?,
@@ -131,6 +128,8 @@ impl crate::Bundle {
     /// As it sends portions of the input to a thread it requires the 'static lifetime for the interrupt flags. This can only
     /// be satisfied by a static AtomicBool which is only suitable for programs that only run one of these operations at a time
     /// or don't mind that all of them abort when the flag is set.
+    ///
+    /// The pack is read in the calling thread if [`Options::thread_limit`] is `Some(1)`.
     pub fn write_to_directory_eagerly(
         pack: impl io::Read + Send + 'static,
         pack_size: Option<u64>,
@@ -142,10 +141,7 @@ impl crate::Bundle {
     ) -> Result<Outcome, Error> {
         let mut read_progress = progress.add_child("read pack");
         read_progress.init(pack_size.map(|s| s as usize), progress::bytes());
-        let pack = progress::Read {
-            reader: pack,
-            progress: progress::ThroughputOnDrop::new(read_progress),
-        };
+        let pack = git_features::io::ProgressRead::new(pack, progress::ThroughputOnDrop::new(read_progress));
 
         let data_file = Arc::new(parking_lot::Mutex::new(match directory.as_ref() {
             Some(directory) => git_tempfile::new(directory, ContainingDirectory::Exists, AutoRemove::Tempfile)?,
@@ -192,8 +188,13 @@ impl crate::Bundle {
             }
         };
         let num_objects = pack_entries_iter.size_hint().0;
-        let pack_entries_iter =
-            git_features::parallel::EagerIterIf::new(move || num_objects > 25_000, pack_entries_iter, 5_000, 5);
+        let thread_limit = options.thread_limit;
+        let pack_entries_iter = git_features::parallel::EagerIterIf::new(
+            move || num_objects > 25_000 && !git_features::parallel::is_single_threaded(thread_limit),
+            pack_entries_iter,
+            5_000,
+            5,
+        );
 
         let (outcome, data_path, index_path) = crate::Bundle::inner_write(
             directory,
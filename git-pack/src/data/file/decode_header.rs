@@ -0,0 +1,78 @@
+use std::convert::TryInto;
+
+use git_features::zlib;
+
+use crate::data::{decode_entry::Error, delta, File};
+
+/// The largest possible size of the delta header, consisting of the base size and the result size encoded as variable-length integers.
+const DELTA_HEADER_MAX_SIZE: usize = 2 * 10;
+
+/// Information about an object produced by [`File::decode_header()`], obtained without decoding it.
+#[derive(Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outcome {
+    /// The kind of resolved object
+    pub kind: git_object::Kind,
+    /// The size of the resolved object in bytes
+    pub object_size: u64,
+    /// The amount of deltas in the chain of objects that would have to be resolved to decode the object.
+    pub num_deltas: u32,
+}
+
+/// Obtaining object information without decoding objects
+impl File {
+    /// Return the kind and size of the object stored at `entry` without decoding it or any of its delta bases.
+    ///
+    /// For deltified objects, only the first bytes of the delta of `entry` are decompressed to learn the size of the resulting object,
+    /// while the kind is obtained by following the chain of delta bases through their entry headers.
+    ///
+    /// `resolve` is a function to lookup entries in this pack with the given [`ObjectId`][git_hash::ObjectId], in case the full
+    /// object id is used to refer to a base object, instead of an in-pack offset.
+    pub fn decode_header(
+        &self,
+        mut entry: crate::data::Entry,
+        resolve: impl Fn(&git_hash::oid) -> Option<crate::data::Entry>,
+    ) -> Result<Outcome, Error> {
+        use crate::data::entry::Header::*;
+        let mut num_deltas = 0;
+        let mut object_size = None;
+        loop {
+            match entry.header {
+                Tree | Blob | Commit | Tag => {
+                    return Ok(Outcome {
+                        kind: entry.header.as_kind().expect("a non-delta entry"),
+                        object_size: object_size.unwrap_or(entry.decompressed_size),
+                        num_deltas,
+                    })
+                }
+                OfsDelta { base_distance } => {
+                    if object_size.is_none() {
+                        object_size = Some(self.decode_delta_object_size(&entry)?);
+                    }
+                    num_deltas += 1;
+                    entry = self.entry(entry.base_pack_offset(base_distance));
+                }
+                RefDelta { base_id } => {
+                    if object_size.is_none() {
+                        object_size = Some(self.decode_delta_object_size(&entry)?);
+                    }
+                    num_deltas += 1;
+                    entry = resolve(base_id.as_ref()).ok_or(Error::DeltaBaseUnresolved(base_id))?;
+                }
+            }
+        }
+    }
+
+    /// Decompress just enough of the delta data in `entry` to read the size of the object it produces.
+    pub(crate) fn decode_delta_object_size(&self, entry: &crate::data::Entry) -> Result<u64, Error> {
+        let offset: usize = entry.data_offset.try_into().expect("offset representable by machine");
+        assert!(offset < self.data.len(), "entry offset out of bounds");
+
+        let mut buf = [0u8; DELTA_HEADER_MAX_SIZE];
+        let (_status, _consumed_in, consumed_out) = zlib::Inflate::default().once(&self.data[offset..], &mut buf)?;
+        let buf = &buf[..consumed_out];
+        let (_base_size, consumed) = delta::decode_header_size(buf);
+        let (result_size, _consumed) = delta::decode_header_size(&buf[consumed..]);
+        Ok(result_size)
+    }
+}
@@ -1,5 +1,7 @@
 ///
 pub mod decode_entry;
+///
+pub mod decode_header;
 mod init;
 ///
 pub mod verify;
@@ -28,7 +28,7 @@ pub struct Object<'a> {
 }
 
 mod file;
-pub use file::{decode_entry, verify, ResolvedBase};
+pub use file::{decode_entry, decode_header, verify, ResolvedBase};
 ///
 pub mod header;
 
@@ -42,6 +42,7 @@ pub mod changes {
 
         fn visit(&mut self, change: Change) -> Action {
             match change {
+                Change::Modification { previous_oid, oid, .. } if previous_oid == oid => {}
                 Change::Addition { oid, entry_mode } | Change::Modification { oid, entry_mode, .. } => {
                     if entry_mode == EntryMode::Commit {
                         return Action::Continue;
@@ -1,6 +1,8 @@
-use std::{cmp::Ordering, sync::Arc};
+use std::{cmp::Ordering, collections::HashMap, sync::Arc};
 
 use git_features::{parallel, progress::Progress};
+use git_hash::ObjectId;
+use git_object::bstr::BStr;
 
 use crate::data::{output, output::ChunkId};
 
@@ -50,6 +52,7 @@ pub fn iter_from_counts<Find, Cache>(
         allow_thin_pack,
         thread_limit,
         chunk_size,
+        deterministic,
     }: Options,
 ) -> impl Iterator<Item = Result<(ChunkId, Vec<output::Entry>), Error<Find::Error>>>
        + parallel::reduce::Finalize<Reduce = reduce::Statistics<Error<Find::Error>>>
@@ -106,7 +109,12 @@ where
         .expect("infallible - we ignore none-existing objects");
         progress.lock().show_throughput(start);
     }
+    let mut canonical_entries = None;
     let counts_range_by_pack_id = match mode {
+        _ if deterministic => {
+            canonical_entries = Some(sort_canonically(&mut counts, &db, make_cache(), &mut progress));
+            Vec::new()
+        }
         Mode::PackCopyAndBaseObjects => {
             let mut progress = progress.add_child("sorting");
             progress.init(Some(counts.len()), git_features::progress::count("counts"));
@@ -144,6 +152,7 @@ where
         }
     };
     let counts = Arc::new(counts);
+    let canonical_entries = Arc::new(parking_lot::Mutex::new(canonical_entries));
     let progress = Arc::new(parking_lot::Mutex::new(progress));
 
     parallel::reduce::Stepwise::new(
@@ -163,17 +172,33 @@ where
             let counts = Arc::clone(&counts);
             move |(chunk_id, chunk_range): (ChunkId, std::ops::Range<usize>), (buf, cache, progress)| {
                 let mut out = Vec::new();
-                let chunk = &counts[chunk_range];
+                let chunk = &counts[chunk_range.clone()];
                 let mut stats = Outcome::default();
                 let mut pack_offsets_to_id = None;
                 progress.init(Some(chunk.len()), git_features::progress::count("objects"));
 
+                if let Some(entries) = canonical_entries.lock().as_mut() {
+                    for entry in &mut entries[chunk_range] {
+                        match std::mem::replace(entry, Ok(None))? {
+                            Some(entry) => {
+                                stats.decoded_and_recompressed_objects += 1;
+                                out.push(entry);
+                            }
+                            None => {
+                                stats.missing_objects += 1;
+                                out.push(output::Entry::invalid());
+                            }
+                        }
+                        progress.inc();
+                    }
+                    return Ok((chunk_id, out, stats));
+                }
                 for count in chunk.iter() {
-                    out.push(match count
+                    let pack_entry = count
                         .entry_pack_location
                         .as_ref()
-                        .and_then(|l| db.entry_by_location(l).map(|pe| (l, pe)))
-                    {
+                        .and_then(|l| db.entry_by_location(l).map(|pe| (l, pe)));
+                    out.push(match pack_entry {
                         Some((location, pack_entry)) => {
                             if let Some((cached_pack_id, _)) = &pack_offsets_to_id {
                                 if *cached_pack_id != location.pack_id {
@@ -253,6 +278,93 @@ where
     )
 }
 
+/// Sort `counts` by the kind of their object, commits first followed by tags, trees and blobs, then by the [hash][name_hash()]
+/// of the name under which they are found in the trees among `counts`, then by the size of their object with the largest first,
+/// and finally by their id, with objects that can't be found at the very end. Objects not found in any tree, like commits,
+/// have a name hash of `0`, and of all names of objects found under multiple names, the one with the smallest hash is used.
+///
+/// Objects have to be decoded for that, which is done in the calling thread to not depend on the amount of threads, and as
+/// they are compressed right away to not decode them again, the entry for each of the sorted `counts` is returned, or `None`
+/// if its object couldn't be found.
+#[allow(clippy::type_complexity)]
+fn sort_canonically<Find, Cache>(
+    counts: &mut Vec<output::Count>,
+    db: &Find,
+    mut cache: Cache,
+    progress: &mut impl Progress,
+) -> Vec<Result<Option<output::Entry>, Error<Find::Error>>>
+where
+    Find: crate::Find,
+    Cache: crate::cache::DecodeEntry,
+{
+    let mut progress = progress.add_child("sorting");
+    progress.init(Some(counts.len()), git_features::progress::count("counts"));
+    let start = std::time::Instant::now();
+
+    let rank = |kind: git_object::Kind| match kind {
+        git_object::Kind::Commit => 0,
+        git_object::Kind::Tag => 1,
+        git_object::Kind::Tree => 2,
+        git_object::Kind::Blob => 3,
+    };
+    let mut buf = Vec::new();
+    let mut name_hashes = HashMap::<ObjectId, u32>::new();
+    let mut keyed: Vec<_> = counts
+        .drain(..)
+        .map(|count| {
+            let (key, entry) = match db.try_find(count.id, &mut buf, &mut cache) {
+                Ok(Some(obj)) => {
+                    if obj.kind == git_object::Kind::Tree {
+                        for entry in git_object::TreeRefIter::from_bytes(obj.data).flatten() {
+                            let hash = name_hash(entry.filename);
+                            name_hashes
+                                .entry(entry.oid.to_owned())
+                                .and_modify(|existing| *existing = hash.min(*existing))
+                                .or_insert(hash);
+                        }
+                    }
+                    (
+                        Some((rank(obj.kind), obj.data.len())),
+                        output::Entry::from_data(&count, &obj).map(Some).map_err(Error::from),
+                    )
+                }
+                Ok(None) => (None, Ok(None)),
+                Err(err) => (None, Err(Error::FindExisting(err))),
+            };
+            progress.inc();
+            (key, count, entry)
+        })
+        .collect();
+    keyed.sort_by_cached_key(|(key, count, _)| {
+        (
+            key.is_none(),
+            key.map(|(rank, size)| {
+                (
+                    rank,
+                    name_hashes.get(&count.id).copied().unwrap_or(0),
+                    std::cmp::Reverse(size),
+                )
+            }),
+            count.id,
+        )
+    });
+    let mut entries = Vec::with_capacity(keyed.len());
+    for (_, count, entry) in keyed {
+        counts.push(count);
+        entries.push(entry);
+    }
+    progress.show_throughput(start);
+    entries
+}
+
+/// Hash a `name` like git does to sort objects found under similar names next to each other, with the last characters
+/// affecting the hash the most and whitespace being ignored.
+fn name_hash(name: &BStr) -> u32 {
+    name.iter()
+        .filter(|b| !matches!(b, b' ' | b'\t' | b'\n' | b'\x0b' | b'\x0c' | b'\r'))
+        .fold(0u32, |hash, b| (hash >> 2).wrapping_add((*b as u32) << 24))
+}
+
 mod util {
     #[derive(Clone)]
     pub struct ChunkRanges {
@@ -394,6 +506,17 @@ mod types {
         pub chunk_size: usize,
         /// The pack data version to produce for each entry
         pub version: crate::data::Version,
+        /// If set, the same counts always produce the same entries, and thus the same pack, no matter how objects are stored,
+        /// how many threads are used or in which order the counts are provided.
+        ///
+        /// To achieve this, entries are ordered by the kind of their object, commits first followed by tags, trees and blobs,
+        /// then by the hash of the name they have in the trees among the counts like git does, then by object size with the
+        /// largest first, with ties broken by object id. Nothing is copied from existing packs as their deltas and compression
+        /// depend on how and when they were written. Instead all objects are decoded and compressed as base objects in the
+        /// calling thread while sorting them, which makes packs larger, takes more time and holds all entries in memory.
+        ///
+        /// Note that the output is deterministic only if the chunks are written [in order][crate::data::output::InOrderIter].
+        pub deterministic: bool,
     }
 
     impl Default for Options {
@@ -404,6 +527,7 @@ mod types {
                 allow_thin_pack: false,
                 chunk_size: 10,
                 version: Default::default(),
+                deterministic: false,
             }
         }
     }
@@ -39,7 +39,8 @@ impl index::File {
         ) -> Result<(), E>,
         E: std::error::Error + Send + Sync + 'static,
     {
-        let (verify_result, traversal_result) = parallel::join(
+        let (verify_result, traversal_result) = parallel::join_with_thread_limit(
+            thread_limit,
             {
                 let pack_progress = progress.add_child("SHA1 of pack");
                 let index_progress = progress.add_child("SHA1 of index");
@@ -48,6 +49,7 @@ impl index::File {
                     let res = self.possibly_verify(
                         pack,
                         check,
+                        thread_limit,
                         pack_progress,
                         index_progress,
                         Arc::clone(&should_interrupt),
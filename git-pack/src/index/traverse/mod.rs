@@ -123,6 +123,7 @@ impl index::File {
         &self,
         pack: &crate::data::File,
         check: SafetyCheck,
+        thread_limit: Option<usize>,
         pack_progress: impl Progress,
         index_progress: impl Progress,
         should_interrupt: Arc<AtomicBool>,
@@ -137,7 +138,8 @@ impl index::File {
                     expected: self.pack_checksum(),
                 });
             }
-            let (pack_res, id) = parallel::join(
+            let (pack_res, id) = parallel::join_with_thread_limit(
+                thread_limit,
                 {
                     let should_interrupt = Arc::clone(&should_interrupt);
                     move || pack.verify_checksum(pack_progress, &should_interrupt)
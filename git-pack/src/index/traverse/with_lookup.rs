@@ -69,7 +69,8 @@ impl index::File {
             &mut <<P as Progress>::SubProgress as Progress>::SubProgress,
         ) -> Result<(), E>,
     {
-        let (verify_result, traversal_result) = parallel::join(
+        let (verify_result, traversal_result) = parallel::join_with_thread_limit(
+            thread_limit,
             {
                 let pack_progress = progress.add_child("SHA1 of pack");
                 let index_progress = progress.add_child("SHA1 of index");
@@ -78,6 +79,7 @@ impl index::File {
                     let res = self.possibly_verify(
                         pack,
                         check,
+                        thread_limit,
                         pack_progress,
                         index_progress,
                         Arc::clone(&should_interrupt),
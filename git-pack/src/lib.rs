@@ -38,3 +38,6 @@ pub mod index;
 
 ///
 pub mod loose;
+
+pub mod multi_index;
+pub use multi_index::File as MultiIndex;
@@ -0,0 +1,137 @@
+use std::convert::TryInto;
+
+use byteorder::{BigEndian, ByteOrder};
+use git_hash::SIZE_OF_SHA1_DIGEST as SHA1_SIZE;
+
+use crate::{index::access::PackOffset, multi_index};
+
+const N32_SIZE: usize = std::mem::size_of::<u32>();
+const N64_SIZE: usize = std::mem::size_of::<u64>();
+const N32_HIGH_BIT: u32 = 1 << 31;
+
+/// Returned by [`multi_index::File::find()`] and [`multi_index::File::pack_index_and_pack_offset_at_index()`] if the
+/// file is corrupt.
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Object {index} refers to pack {pack_index}, but there are only {num_packs} packs")]
+    PackIndexOutOfBounds {
+        index: u32,
+        pack_index: u32,
+        num_packs: u32,
+    },
+    #[error("Object {index} refers to large offset {large_offset_index}, but there are only {num_large_offsets} large offsets")]
+    LargeOffsetOutOfBounds {
+        index: u32,
+        large_offset_index: u32,
+        num_large_offsets: u32,
+    },
+}
+
+/// Represents an entry within a multi-pack index file, mapping an object id to a pack and the location of the object in it.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entry {
+    /// The ID of the object
+    pub oid: git_hash::ObjectId,
+    /// The index of the pack containing the object, usable with [`index_names()`][multi_index::File::index_names()].
+    pub pack_index: u32,
+    /// The offset to the object's header in the pack data file
+    pub pack_offset: PackOffset,
+}
+
+/// Iteration and access
+impl multi_index::File {
+    /// Returns the object id at the given index in our list of (sorted) object ids.
+    /// The index ranges from 0 to self.num_objects()
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn oid_at_index(&self, index: u32) -> &git_hash::oid {
+        let start = self.lookup_ofs + index as usize * SHA1_SIZE;
+        git_hash::oid::try_from(&self.data[start..start + SHA1_SIZE]).expect("20 bytes SHA1 to be alright")
+    }
+
+    /// Returns the index of the pack containing the object at `index` along with the offset into that pack at which
+    /// to start reading the object, or an error if the file refers to packs or large offsets it doesn't have.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn pack_index_and_pack_offset_at_index(&self, index: u32) -> Result<(u32, PackOffset), Error> {
+        let object_index = index;
+        let index: usize = index
+            .try_into()
+            .expect("an architecture able to hold 32 bits of integer");
+        let start = self.offsets_ofs + index * N32_SIZE * 2;
+        let pack_index = BigEndian::read_u32(&self.data[start..][..N32_SIZE]);
+        if pack_index >= self.num_packs() {
+            return Err(Error::PackIndexOutOfBounds {
+                index: object_index,
+                pack_index,
+                num_packs: self.num_packs(),
+            });
+        }
+        let ofs32 = BigEndian::read_u32(&self.data[start + N32_SIZE..][..N32_SIZE]);
+        let pack_offset = if ofs32 & N32_HIGH_BIT == N32_HIGH_BIT {
+            let large_offset_index = ofs32 ^ N32_HIGH_BIT;
+            let large_offsets = self.large_offsets.clone().unwrap_or(0..0);
+            let num_large_offsets = (large_offsets.len() / N64_SIZE) as u32;
+            if large_offset_index >= num_large_offsets {
+                return Err(Error::LargeOffsetOutOfBounds {
+                    index: object_index,
+                    large_offset_index,
+                    num_large_offsets,
+                });
+            }
+            let from = large_offsets.start + large_offset_index as usize * N64_SIZE;
+            BigEndian::read_u64(&self.data[from..][..N64_SIZE])
+        } else {
+            ofs32 as u64
+        };
+        Ok((pack_index, pack_offset))
+    }
+
+    /// Returns the `index` of the given object id for use with [`oid_at_index()`][multi_index::File::oid_at_index()]
+    /// or [`pack_index_and_pack_offset_at_index()`][multi_index::File::pack_index_and_pack_offset_at_index()].
+    pub fn lookup(&self, id: impl AsRef<git_hash::oid>) -> Option<u32> {
+        let id = id.as_ref();
+        let first_byte = id.first_byte() as usize;
+        let mut upper_bound = self.fan[first_byte];
+        let mut lower_bound = if first_byte != 0 { self.fan[first_byte - 1] } else { 0 };
+
+        while lower_bound < upper_bound {
+            let mid = (lower_bound + upper_bound) / 2;
+            let mid_sha = self.oid_at_index(mid);
+
+            use std::cmp::Ordering::*;
+            match id.cmp(mid_sha) {
+                Less => upper_bound = mid,
+                Equal => return Some(mid),
+                Greater => lower_bound = mid + 1,
+            }
+        }
+        None
+    }
+
+    /// Find the object with `id` and return the index of the pack containing it along with the offset into that pack,
+    /// or `None` if it isn't contained in any of our packs.
+    pub fn find(&self, id: impl AsRef<git_hash::oid>) -> Result<Option<(u32, PackOffset)>, Error> {
+        self.lookup(id)
+            .map(|index| self.pack_index_and_pack_offset_at_index(index))
+            .transpose()
+    }
+
+    /// An iterator over all [`Entries`][Entry] of this index file, sorted by object id.
+    pub fn iter(&self) -> impl Iterator<Item = Result<Entry, Error>> + '_ {
+        (0..self.num_objects).map(move |index| {
+            let (pack_index, pack_offset) = self.pack_index_and_pack_offset_at_index(index)?;
+            Ok(Entry {
+                oid: self.oid_at_index(index).to_owned(),
+                pack_index,
+                pack_offset,
+            })
+        })
+    }
+}
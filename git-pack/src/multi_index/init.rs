@@ -0,0 +1,176 @@
+use std::{convert::TryFrom, path::Path};
+
+use byteorder::{BigEndian, ByteOrder};
+use filebuffer::FileBuffer;
+use git_hash::SIZE_OF_SHA1_DIGEST as SHA1_SIZE;
+
+use crate::multi_index::{self, Version, FAN_LEN};
+
+/// Returned by [`multi_index::File::at()`].
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not open multi-pack index file at '{path}'")]
+    Io {
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+    #[error("{message}")]
+    Corrupt { message: String },
+    #[error("Unsupported multi-pack index version: {version}")]
+    UnsupportedVersion { version: u8 },
+    #[error("Unsupported hash kind: {kind}")]
+    UnsupportedHashKind { kind: u8 },
+}
+
+const SIGNATURE: &[u8] = b"MIDX";
+const HEADER_LEN: usize = 12;
+const CHUNK_LOOKUP_ENTRY_LEN: usize = 12;
+
+const CHUNK_PACK_NAMES: u32 = u32::from_be_bytes(*b"PNAM");
+const CHUNK_FAN: u32 = u32::from_be_bytes(*b"OIDF");
+const CHUNK_LOOKUP: u32 = u32::from_be_bytes(*b"OIDL");
+const CHUNK_OFFSETS: u32 = u32::from_be_bytes(*b"OOFF");
+const CHUNK_LARGE_OFFSETS: u32 = u32::from_be_bytes(*b"LOFF");
+
+/// Instantiation
+impl multi_index::File {
+    /// Open the multi-pack index file at the given `path`.
+    pub fn at(path: impl AsRef<Path>) -> Result<multi_index::File, Error> {
+        Self::try_from(path.as_ref())
+    }
+}
+
+impl TryFrom<&Path> for multi_index::File {
+    type Error = Error;
+
+    fn try_from(path: &Path) -> Result<Self, Self::Error> {
+        let data = FileBuffer::open(path).map_err(|e| Error::Io {
+            source: e,
+            path: path.to_owned(),
+        })?;
+        let corrupt = |message: String| Error::Corrupt { message };
+        if data.len() < HEADER_LEN || &data[..SIGNATURE.len()] != SIGNATURE {
+            return Err(corrupt("Not a multi-pack index file".into()));
+        }
+        let version = match data[4] {
+            1 => Version::V1,
+            version => return Err(Error::UnsupportedVersion { version }),
+        };
+        let hash_kind = match data[5] {
+            1 => git_hash::Kind::Sha1,
+            kind => return Err(Error::UnsupportedHashKind { kind }),
+        };
+        let hash_len = SHA1_SIZE;
+        let num_chunks = data[6] as usize;
+        let num_base_files = data[7];
+        if num_base_files != 0 {
+            return Err(corrupt(format!(
+                "Incremental multi-pack indices with {} base files are not supported",
+                num_base_files
+            )));
+        }
+        let num_packs = BigEndian::read_u32(&data[8..12]) as usize;
+
+        let chunk_table_end = HEADER_LEN + (num_chunks + 1) * CHUNK_LOOKUP_ENTRY_LEN;
+        if data.len() < chunk_table_end + hash_len {
+            return Err(corrupt("The chunk table is truncated".into()));
+        }
+        let mut chunks = Vec::with_capacity(num_chunks);
+        for entry in data[HEADER_LEN..chunk_table_end].chunks(CHUNK_LOOKUP_ENTRY_LEN) {
+            chunks.push((
+                BigEndian::read_u32(&entry[..4]),
+                BigEndian::read_u64(&entry[4..]) as usize,
+            ));
+        }
+        let chunk_range = |id: u32| -> Option<Result<std::ops::Range<usize>, Error>> {
+            let pos = chunks[..num_chunks].iter().position(|(chunk_id, _)| *chunk_id == id)?;
+            let (start, end) = (chunks[pos].1, chunks[pos + 1].1);
+            Some(if start > end || end > data.len() - hash_len {
+                Err(Error::Corrupt {
+                    message: format!("Chunk {} is out of bounds", chunk_name(id)),
+                })
+            } else {
+                Ok(start..end)
+            })
+        };
+        let required_chunk = |id: u32| {
+            chunk_range(id).unwrap_or_else(|| {
+                Err(Error::Corrupt {
+                    message: format!("Required chunk {} is missing", chunk_name(id)),
+                })
+            })
+        };
+
+        let index_names = {
+            let range = required_chunk(CHUNK_PACK_NAMES)?;
+            let names: Vec<_> = data[range]
+                .split(|b| *b == 0)
+                .filter(|name| !name.is_empty())
+                .map(|name| {
+                    std::str::from_utf8(name)
+                        .map(std::path::PathBuf::from)
+                        .map_err(|_| Error::Corrupt {
+                            message: "Pack names must be valid UTF-8".into(),
+                        })
+                })
+                .collect::<Result<_, _>>()?;
+            if names.len() != num_packs {
+                return Err(corrupt(format!(
+                    "Expected {} pack names, but found {}",
+                    num_packs,
+                    names.len()
+                )));
+            }
+            names
+        };
+
+        let fan = {
+            let range = required_chunk(CHUNK_FAN)?;
+            if range.len() != FAN_LEN * 4 {
+                return Err(corrupt("The fan-out table has an unexpected size".into()));
+            }
+            let mut fan = [0; FAN_LEN];
+            for (c, f) in data[range].chunks(4).zip(fan.iter_mut()) {
+                *f = BigEndian::read_u32(c);
+            }
+            if fan.windows(2).any(|w| w[0] > w[1]) {
+                return Err(corrupt("The fan-out table is not sorted".into()));
+            }
+            fan
+        };
+        let num_objects = fan[FAN_LEN - 1];
+
+        let lookup = required_chunk(CHUNK_LOOKUP)?;
+        if lookup.len() != num_objects as usize * hash_len {
+            return Err(corrupt(
+                "The object id table does not match the amount of objects".into(),
+            ));
+        }
+        let offsets = required_chunk(CHUNK_OFFSETS)?;
+        if offsets.len() != num_objects as usize * 8 {
+            return Err(corrupt("The offset table does not match the amount of objects".into()));
+        }
+        let large_offsets = chunk_range(CHUNK_LARGE_OFFSETS).transpose()?;
+        if matches!(large_offsets, Some(ref r) if r.len() % 8 != 0) {
+            return Err(corrupt("The large offset table has an unexpected size".into()));
+        }
+
+        Ok(multi_index::File {
+            data,
+            path: path.to_owned(),
+            version,
+            hash_kind,
+            num_objects,
+            index_names,
+            fan,
+            lookup_ofs: lookup.start,
+            offsets_ofs: offsets.start,
+            large_offsets,
+        })
+    }
+}
+
+fn chunk_name(id: u32) -> String {
+    String::from_utf8_lossy(&id.to_be_bytes()).into_owned()
+}
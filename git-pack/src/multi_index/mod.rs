@@ -0,0 +1,71 @@
+//! A multi-pack index which maps object ids to locations in many packs at once, as written by `git multi-pack-index write`.
+use std::path::PathBuf;
+
+use filebuffer::FileBuffer;
+
+/// The version of a multi-pack index file
+#[derive(PartialEq, Eq, Ord, PartialOrd, Debug, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+#[allow(missing_docs)]
+pub enum Version {
+    V1 = 1,
+}
+
+impl Default for Version {
+    fn default() -> Self {
+        Version::V1
+    }
+}
+
+const FAN_LEN: usize = 256;
+
+/// A representation of a multi-pack index file, commonly `objects/pack/multi-pack-index`.
+pub struct File {
+    data: FileBuffer,
+    path: PathBuf,
+    version: Version,
+    hash_kind: git_hash::Kind,
+    num_objects: u32,
+    index_names: Vec<PathBuf>,
+    fan: [u32; FAN_LEN],
+    lookup_ofs: usize,
+    offsets_ofs: usize,
+    large_offsets: Option<std::ops::Range<usize>>,
+}
+
+/// Basic file information
+impl File {
+    /// The version of the multi-pack index
+    pub fn version(&self) -> Version {
+        self.version
+    }
+    /// The path of the opened multi-pack index file
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+    /// The kind of hash used for all object ids
+    pub fn hash_kind(&self) -> git_hash::Kind {
+        self.hash_kind
+    }
+    /// The amount of objects in all packs
+    pub fn num_objects(&self) -> u32 {
+        self.num_objects
+    }
+    /// The amount of packs covered by this index
+    pub fn num_packs(&self) -> u32 {
+        self.index_names.len() as u32
+    }
+    /// The names of all pack index files covered by this index, relative to the directory containing it and sorted by name.
+    ///
+    /// The position of each name is the pack index returned by [`find()`][File::find()].
+    pub fn index_names(&self) -> &[PathBuf] {
+        &self.index_names
+    }
+}
+
+///
+pub mod init;
+
+///
+pub mod access;
+pub use access::Entry;
@@ -0,0 +1,14 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+for round in $(seq 50); do
+  seq "$round" > "file-$round"
+  git add "file-$round"
+  git commit -q -m "round $round"
+  git repack -q
+done
+
+git multi-pack-index write
@@ -160,3 +160,151 @@ mod write_to_directory {
         .map_err(Into::into)
     }
 }
+
+#[cfg(feature = "analysis")]
+mod delta_graph {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use git_odb::pack;
+
+    use crate::{
+        fixture_path, hex_to_id,
+        pack::{INDEX_V1, INDEX_V2, SMALL_PACK_INDEX},
+    };
+
+    /// The lines of `git verify-pack -v` for each object, keyed by object id.
+    fn verify_pack(index_path: &str) -> crate::Result<BTreeMap<git_hash::ObjectId, Vec<String>>> {
+        let output = std::process::Command::new("git")
+            .arg("verify-pack")
+            .arg("-v")
+            .arg(fixture_path(index_path))
+            .output()?;
+        assert!(output.status.success(), "git verify-pack failed");
+        Ok(String::from_utf8(output.stdout)?
+            .lines()
+            .map(|line| line.split_whitespace().map(ToOwned::to_owned).collect::<Vec<_>>())
+            .filter(|tokens| tokens.len() == 5 || tokens.len() == 7)
+            .map(|tokens| (hex_to_id(&tokens[0]), tokens))
+            .collect())
+    }
+
+    #[test]
+    fn depths_bases_and_sizes_match_git_verify_pack() -> crate::Result {
+        for (index_path, expected_histogram) in [
+            (SMALL_PACK_INDEX, vec![30, 6, 6]),
+            (INDEX_V1, vec![64, 3]),
+            (INDEX_V2, vec![18, 4, 3, 1, 2, 1, 1]),
+        ] {
+            let bundle = pack::Bundle::at(fixture_path(index_path))?;
+            let graph = bundle.delta_graph()?;
+            assert_eq!(graph.nodes.len(), bundle.index.num_objects() as usize);
+            assert_eq!(graph.depth_histogram(), expected_histogram, "{}", index_path);
+
+            let expected = verify_pack(index_path)?;
+            let mut buf = Vec::new();
+            for node in &graph.nodes {
+                let tokens = &expected[&node.id];
+                assert_eq!(node.kind.to_string(), tokens[1]);
+                assert_eq!(node.compressed_size.to_string(), tokens[3]);
+                assert_eq!(node.pack_offset.to_string(), tokens[4]);
+                match node.base {
+                    Some(edge) => {
+                        assert_eq!(edge.delta_size.to_string(), tokens[2], "git shows the size of deltas");
+                        assert_eq!(node.depth.to_string(), tokens[5]);
+                        assert_eq!(graph.nodes[edge.base].id, hex_to_id(&tokens[6]));
+                    }
+                    None => {
+                        assert_eq!(node.object_size.to_string(), tokens[2]);
+                        assert_eq!(node.depth, 0);
+                    }
+                }
+                let object = bundle
+                    .find(node.id, &mut buf, &mut pack::cache::Never)?
+                    .expect("object present");
+                assert_eq!(object.data.len() as u64, node.object_size);
+                assert_eq!(object.kind, node.kind);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn chains_summarize_all_deltas_of_a_base() -> crate::Result {
+        let bundle = pack::Bundle::at(fixture_path(INDEX_V2))?;
+        let graph = bundle.delta_graph()?;
+        let chains = graph.chains();
+
+        let num_deltas = graph.nodes.iter().filter(|node| node.base.is_some()).count();
+        assert_eq!(chains.iter().map(|chain| chain.num_deltas).sum::<usize>(), num_deltas);
+        assert_eq!(chains.iter().map(|chain| chain.max_depth).max(), Some(6));
+        for chain in &chains {
+            let base = &graph.nodes[chain.base];
+            assert!(base.base.is_none(), "chains start at objects which aren't deltas");
+            let members: Vec<_> = (0..graph.nodes.len())
+                .filter(|idx| graph.base_of(*idx) == chain.base)
+                .map(|idx| &graph.nodes[idx])
+                .collect();
+            assert_eq!(members.len(), chain.num_deltas + 1);
+            assert_eq!(
+                members.iter().map(|node| node.compressed_size).sum::<u64>(),
+                chain.compressed_size
+            );
+            assert_eq!(
+                members.iter().map(|node| node.object_size).sum::<u64>(),
+                chain.object_size
+            );
+            assert_eq!(
+                chain.saved_bytes(),
+                chain.object_size.saturating_sub(chain.compressed_size)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn dot_output_declares_all_nodes_and_edges_between_them() -> crate::Result {
+        let bundle = pack::Bundle::at(fixture_path(SMALL_PACK_INDEX))?;
+        let graph = bundle.delta_graph()?;
+        let mut dot = Vec::new();
+        graph.write_dot(&mut dot)?;
+        let dot = String::from_utf8(dot)?;
+
+        let mut lines = dot.lines();
+        assert_eq!(lines.next(), Some("digraph pack {"));
+        assert_eq!(lines.next_back(), Some("}"));
+        let (mut nodes, mut edges) = (BTreeSet::new(), Vec::new());
+        for line in lines {
+            let statement = line
+                .trim()
+                .strip_suffix(';')
+                .expect("statements are terminated by a semicolon");
+            if statement.starts_with("node ") {
+                continue;
+            }
+            let (target, attributes) = statement.split_once(" [").expect("all statements have attributes");
+            assert!(attributes.starts_with("label=\"") && attributes.ends_with("\"]"));
+            assert_eq!(
+                attributes.matches('"').count(),
+                2,
+                "labels are quoted and don't contain quotes"
+            );
+            let quoted_id = |s: &str| {
+                hex_to_id(
+                    s.strip_prefix('"')
+                        .and_then(|s| s.strip_suffix('"'))
+                        .expect("quoted ids"),
+                )
+            };
+            match target.split_once(" -> ") {
+                Some((delta, base)) => edges.push((quoted_id(delta), quoted_id(base))),
+                None => assert!(nodes.insert(quoted_id(target)), "nodes are declared once"),
+            }
+        }
+        assert_eq!(nodes.len(), graph.nodes.len());
+        assert_eq!(edges.len(), 12);
+        for (delta, base) in edges {
+            assert!(nodes.contains(&delta) && nodes.contains(&base));
+        }
+        Ok(())
+    }
+}
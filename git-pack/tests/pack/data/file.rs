@@ -104,6 +104,56 @@ mod decode_entry {
     }
 }
 
+/// All hardcoded offsets are obtained via `git pack-verify --verbose  tests/fixtures/packs/pack-a2bf8e71d8c18879e499335762dd95119d93d9f1.idx`
+mod decode_header {
+    use git_object::Kind;
+    use git_pack::data::decode_header::Outcome;
+
+    use crate::pack::{data::file::pack_at, SMALL_PACK};
+
+    #[test]
+    fn commit() {
+        assert_eq!(
+            decode_header_at_offset(1968),
+            Outcome {
+                kind: Kind::Commit,
+                object_size: 187,
+                num_deltas: 0
+            }
+        );
+    }
+
+    #[test]
+    fn blob_ofs_delta_two_links() {
+        assert_eq!(
+            decode_header_at_offset(3033),
+            Outcome {
+                kind: Kind::Blob,
+                object_size: 173,
+                num_deltas: 2
+            }
+        );
+    }
+
+    #[test]
+    fn blob_ofs_delta_single_link() {
+        assert_eq!(
+            decode_header_at_offset(3569),
+            Outcome {
+                kind: Kind::Blob,
+                object_size: 1163,
+                num_deltas: 1
+            }
+        );
+    }
+
+    fn decode_header_at_offset(offset: u64) -> Outcome {
+        let p = pack_at(SMALL_PACK);
+        p.decode_header(p.entry(offset), |_oid| panic!("should not want to resolve an id here"))
+            .expect("valid offset provides valid entry")
+    }
+}
+
 mod decompress_entry {
     use git_object::bstr::ByteSlice;
 
@@ -143,7 +143,7 @@ fn traversals() -> crate::Result {
             output::count::objects::Outcome {
                 input_objects: 1,
                 expanded_objects: 102,
-                decoded_objects: 18,
+                decoded_objects: 10,
                 total_objects: 103,
             },
             output::entry::iter_from_counts::Outcome {
@@ -176,7 +176,7 @@ fn traversals() -> crate::Result {
             output::count::objects::Outcome {
                 input_objects: 1,
                 expanded_objects: 102,
-                decoded_objects: 18,
+                decoded_objects: 10,
                 total_objects: 103,
             },
             output::entry::iter_from_counts::Outcome {
@@ -218,7 +218,7 @@ fn traversals() -> crate::Result {
             output::count::objects::Outcome {
                 input_objects: 16,
                 expanded_objects: 866,
-                decoded_objects: 208,
+                decoded_objects: 74,
                 total_objects: 868,
             },
             output::entry::iter_from_counts::Outcome {
@@ -403,3 +403,179 @@ fn write_and_verify(
 
     Ok(())
 }
+
+mod deterministic {
+    use std::{
+        collections::HashMap,
+        convert::Infallible,
+        sync::{atomic::AtomicBool, Arc},
+    };
+
+    use git_features::progress;
+    use git_odb::{compound, linked, pack, FindExt};
+    use git_pack::data::output;
+    use git_traverse::commit;
+
+    use crate::pack::{
+        data::output::{db, DbKind},
+        hex_to_id,
+    };
+
+    fn counts(db: &Arc<linked::Store>) -> crate::Result<Vec<output::Count>> {
+        let head = hex_to_id("dfcb5e39ac6eb30179808bbab721e8a28ce1b52e");
+        let commits = commit::Ancestors::new(Some(head), commit::ancestors::State::default(), {
+            let db = Arc::clone(db);
+            move |oid, buf| db.find_commit_iter(oid, buf, &mut pack::cache::Never).ok()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+        Ok(output::count::objects(
+            Arc::clone(db),
+            || (pack::cache::Never, pack::cache::object::Never),
+            commits
+                .into_iter()
+                .chain(std::iter::once(hex_to_id("e3fb53cbb4c346d48732a24f09cf445e49bc63d6")))
+                .map(Ok::<_, Infallible>),
+            progress::Discard,
+            &AtomicBool::new(false),
+            output::count::objects::Options {
+                input_object_expansion: output::count::objects::ObjectExpansion::TreeContents,
+                thread_limit: Some(1),
+                ..Default::default()
+            },
+        )?
+        .0)
+    }
+
+    fn pack(
+        db: &Arc<linked::Store>,
+        counts: Vec<output::Count>,
+        thread_limit: usize,
+        deterministic: bool,
+    ) -> crate::Result<(Vec<git_hash::ObjectId>, Vec<u8>)> {
+        let num_entries = counts.len() as u32;
+        let entries: Vec<_> = output::InOrderIter::from(output::entry::iter_from_counts(
+            counts,
+            Arc::clone(db),
+            || pack::cache::Never,
+            progress::Discard,
+            output::entry::iter_from_counts::Options {
+                thread_limit: Some(thread_limit),
+                chunk_size: 1 + thread_limit,
+                deterministic,
+                ..Default::default()
+            },
+        ))
+        .collect::<Result<Vec<_>, _>>()?;
+        let mut ids: Vec<_> = entries.iter().flatten().map(|entry| entry.id).collect();
+        ids.sort();
+
+        let mut data = Vec::new();
+        for written in output::bytes::FromEntriesIter::new(
+            entries
+                .into_iter()
+                .map(Ok::<_, output::entry::iter_from_counts::Error<compound::find::Error>>),
+            &mut data,
+            num_entries,
+            pack::data::Version::V2,
+            git_hash::Kind::Sha1,
+        ) {
+            written?;
+        }
+        Ok((ids, data))
+    }
+
+    #[test]
+    fn the_same_objects_yield_the_same_pack_no_matter_the_threads_or_the_order_of_counts() -> crate::Result {
+        let db = db(DbKind::DeterministicGeneratedContent)?;
+        let counts = counts(&db)?;
+        let mut reversed_counts = counts.clone();
+        reversed_counts.reverse();
+
+        let (ids, data) = pack(&db, counts.clone(), 1, true)?;
+        let (other_ids, other_data) = pack(&db, reversed_counts.clone(), 4, true)?;
+        assert_eq!(ids, other_ids);
+        assert!(data == other_data, "packs are byte-identical");
+
+        let (unordered_ids, _) = pack(&db, counts, 1, false)?;
+        let (other_unordered_ids, _) = pack(&db, reversed_counts, 4, false)?;
+        assert_eq!(unordered_ids, ids, "the same objects are packed either way");
+        assert_eq!(other_unordered_ids, ids);
+        Ok(())
+    }
+
+    /// Like git's `pack_name_hash()`, mostly affected by the last characters of `name`, ignoring whitespace.
+    fn name_hash(name: &[u8]) -> u32 {
+        let mut hash = 0u32;
+        for b in name.iter().filter(|b| !b" \t\n\x0b\x0c\r".contains(b)) {
+            hash = (hash >> 2).wrapping_add((*b as u32) << 24);
+        }
+        hash
+    }
+
+    #[test]
+    fn objects_are_ordered_by_kind_then_name_hash_then_size_then_id() -> crate::Result {
+        let db = db(DbKind::DeterministicGeneratedContent)?;
+        let mut counts = counts(&db)?;
+        counts.reverse();
+        let entries: Vec<_> = output::InOrderIter::from(output::entry::iter_from_counts(
+            counts,
+            Arc::clone(&db),
+            || pack::cache::Never,
+            progress::Discard,
+            output::entry::iter_from_counts::Options {
+                deterministic: true,
+                ..Default::default()
+            },
+        ))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+
+        let mut name_hashes = HashMap::<git_hash::ObjectId, u32>::new();
+        let mut buf = Vec::new();
+        for entry in &entries {
+            if entry.kind != output::entry::Kind::Base(git_object::Kind::Tree) {
+                continue;
+            }
+            for tree_entry in db.find_tree_iter(entry.id, &mut buf, &mut pack::cache::Never)? {
+                let tree_entry = tree_entry?;
+                let hash = name_hash(tree_entry.filename);
+                let min = name_hashes.entry(tree_entry.oid.to_owned()).or_insert(hash);
+                *min = hash.min(*min);
+            }
+        }
+
+        let rank = |kind: git_object::Kind| match kind {
+            git_object::Kind::Commit => 0,
+            git_object::Kind::Tag => 1,
+            git_object::Kind::Tree => 2,
+            git_object::Kind::Blob => 3,
+        };
+        let keys: Vec<_> = entries
+            .iter()
+            .map(|entry| match entry.kind {
+                output::entry::Kind::Base(kind) => (
+                    rank(kind),
+                    name_hashes.get(&entry.id).copied().unwrap_or(0),
+                    std::cmp::Reverse(entry.decompressed_size),
+                    entry.id,
+                ),
+                output::entry::Kind::DeltaRef { .. } | output::entry::Kind::DeltaOid { .. } => {
+                    unreachable!("no deltas are copied")
+                }
+            })
+            .collect();
+        let mut sorted_keys = keys.clone();
+        sorted_keys.sort();
+        assert_eq!(keys, sorted_keys);
+        assert!(
+            keys.iter().any(|key| key.0 == 0) && keys.iter().any(|key| key.0 == 3),
+            "commits and blobs are present"
+        );
+        let blob_name_hashes: std::collections::HashSet<_> =
+            keys.iter().filter(|key| key.0 == 3).map(|key| key.1).collect();
+        assert!(blob_name_hashes.len() > 1, "blobs are ordered by their names");
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+use std::{convert::TryInto, path::PathBuf};
+
+use git_pack::{index, MultiIndex};
+
+use crate::{hex_to_id, scripted_fixture_repo_read_only};
+
+fn pack_directory() -> crate::Result<PathBuf> {
+    Ok(scripted_fixture_repo_read_only("make_repo_multi_index.sh")?.join(".git/objects/pack"))
+}
+
+#[test]
+fn access() -> crate::Result {
+    let dir = pack_directory()?;
+    let file = MultiIndex::at(dir.join("multi-pack-index"))?;
+    assert_eq!(file.version(), git_pack::multi_index::Version::V1);
+    assert_eq!(file.hash_kind(), git_hash::Kind::Sha1);
+    assert_eq!(file.num_packs(), 50);
+    assert_eq!(file.num_objects(), 150, "each round adds a blob, a tree and a commit");
+
+    let mut index_names: Vec<_> = std::fs::read_dir(&dir)?
+        .map(|e| e.map(|e| PathBuf::from(e.file_name())))
+        .filter(|p| p.as_ref().map_or(true, |p| p.extension().unwrap_or_default() == "idx"))
+        .collect::<Result<_, _>>()?;
+    index_names.sort();
+    assert_eq!(file.index_names(), index_names);
+
+    let indices = file
+        .index_names()
+        .iter()
+        .map(|name| index::File::at(dir.join(name)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut num_entries = 0;
+    for entry in file.iter() {
+        let entry = entry?;
+        let (pack_index, pack_offset) = file.find(entry.oid)?.expect("all iterated objects can be found");
+        assert_eq!((entry.pack_index, entry.pack_offset), (pack_index, pack_offset));
+        let index = &indices[pack_index as usize];
+        let entry_index = index
+            .lookup(&entry.oid)
+            .expect("the object is in the pack we were told");
+        assert_eq!(index.pack_offset_at_index(entry_index), pack_offset);
+        num_entries += 1;
+    }
+    assert_eq!(num_entries, file.num_objects());
+
+    assert_eq!(file.find(hex_to_id("ffffffffffffffffffffffffffffffffffffffff"))?, None);
+    assert_eq!(file.find(hex_to_id("0000000000000000000000000000000000000000"))?, None);
+    Ok(())
+}
+
+#[test]
+fn corrupt_files_yield_errors() -> crate::Result {
+    let data = std::fs::read(pack_directory()?.join("multi-pack-index"))?;
+    let tmp = tempfile::tempdir()?;
+    let path = tmp.path().join("multi-pack-index");
+    for (corrupt, expected) in [
+        (&data[..data.len() / 2], "out of bounds"),
+        (&data[..8], "Not a multi-pack index file"),
+        (&b"MIDX\x02\x01\x05\x00\x00\x00\x00\x32"[..], "version: 2"),
+    ] {
+        std::fs::write(&path, corrupt)?;
+        let err = MultiIndex::at(&path).err().expect("corrupt files can't be opened");
+        assert!(
+            err.to_string().contains(expected),
+            "{} should contain {:?}",
+            err,
+            expected
+        );
+    }
+    assert!(MultiIndex::at(tmp.path().join("does-not-exist")).is_err());
+    Ok(())
+}
+
+/// Return the offset of the chunk with `id` in the multi-pack index `data`.
+fn chunk_offset(data: &[u8], id: &[u8; 4]) -> usize {
+    let num_chunks = data[6] as usize;
+    let entry = data[12..][..num_chunks * 12]
+        .chunks(12)
+        .find(|entry| &entry[..4] == id)
+        .expect("chunk is present");
+    u64::from_be_bytes(entry[4..].try_into().expect("8 bytes")) as usize
+}
+
+#[test]
+fn corrupt_fan_out_tables_yield_errors() -> crate::Result {
+    let mut data = std::fs::read(pack_directory()?.join("multi-pack-index"))?;
+    let fan = chunk_offset(&data, b"OIDF");
+    data[fan..][..4].copy_from_slice(&u32::MAX.to_be_bytes());
+    let tmp = tempfile::tempdir()?;
+    let path = tmp.path().join("multi-pack-index");
+    std::fs::write(&path, &data)?;
+    let err = MultiIndex::at(&path)
+        .err()
+        .expect("unsorted fan-out tables are rejected");
+    assert!(err.to_string().contains("fan-out"), "{}", err);
+    Ok(())
+}
+
+#[test]
+fn entries_referring_to_missing_packs_or_large_offsets_yield_errors_on_lookup() -> crate::Result {
+    let data = std::fs::read(pack_directory()?.join("multi-pack-index"))?;
+    let offsets = chunk_offset(&data, b"OOFF");
+    let tmp = tempfile::tempdir()?;
+    let path = tmp.path().join("multi-pack-index");
+    for (field, value, expected) in [
+        (0, 50, "refers to pack 50, but there are only 50 packs"),
+        (
+            4,
+            1 << 31 | 3,
+            "refers to large offset 3, but there are only 0 large offsets",
+        ),
+    ] {
+        let mut data = data.clone();
+        data[offsets + field..][..4].copy_from_slice(&u32::to_be_bytes(value));
+        std::fs::write(&path, &data)?;
+        let file = MultiIndex::at(&path)?;
+        let id = file.oid_at_index(0).to_owned();
+        let err = file.find(id).expect_err("the first entry is corrupt");
+        assert_eq!(err.to_string(), format!("Object 0 {}", expected));
+        assert!(file.iter().next().expect("an entry").is_err());
+        assert!(file.find(file.oid_at_index(1))?.is_some(), "other entries are fine");
+    }
+    Ok(())
+}
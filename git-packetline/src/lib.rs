@@ -85,6 +85,8 @@ pub struct StreamingPeekableIter<T> {
     delimiters: &'static [PacketLineRef<'static>],
     is_done: bool,
     stopped_at: Option<PacketLineRef<'static>>,
+    #[cfg(any(feature = "blocking-io", feature = "async-io"))]
+    limits: read::limits::State,
 }
 
 /// Utilities to help decoding packet lines
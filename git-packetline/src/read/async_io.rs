@@ -6,7 +6,7 @@ use futures_lite::AsyncReadExt;
 
 use crate::{
     decode,
-    read::{ExhaustiveOutcome, WithSidebands},
+    read::{limits, ExhaustiveOutcome, WithSidebands},
     PacketLineRef, StreamingPeekableIter, MAX_LINE_LEN, U16_HEX_BYTES,
 };
 
@@ -15,21 +15,42 @@ impl<T> StreamingPeekableIter<T>
 where
     T: AsyncRead + Unpin,
 {
+    /// Like [`read_exact()`][AsyncReadExt::read_exact()], but observes each read if `limits` measure the throughput.
+    async fn read_exact_observed(reader: &mut T, mut buf: &mut [u8], limits: &mut limits::State) -> io::Result<()> {
+        if !limits.watches_throughput() {
+            return reader.read_exact(buf).await;
+        }
+        while !buf.is_empty() {
+            match reader.read(buf).await {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => {
+                    limits.on_read(n)?;
+                    buf = &mut buf[n..];
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
     #[allow(clippy::needless_lifetimes)] // TODO: remove once this is clippy false positive is fixed
     async fn read_line_inner<'a>(
         reader: &mut T,
         buf: &'a mut Vec<u8>,
+        limits: &mut limits::State,
     ) -> io::Result<Result<PacketLineRef<'a>, decode::Error>> {
         let (hex_bytes, data_bytes) = buf.split_at_mut(4);
-        reader.read_exact(hex_bytes).await?;
+        Self::read_exact_observed(reader, hex_bytes, limits).await?;
         let num_data_bytes = match decode::hex_prefix(hex_bytes) {
             Ok(decode::PacketLineOrWantedSize::Line(line)) => return Ok(Ok(line)),
             Ok(decode::PacketLineOrWantedSize::Wanted(additional_bytes)) => additional_bytes as usize,
             Err(err) => return Ok(Err(err)),
         };
 
+        limits.on_line(num_data_bytes)?;
         let (data_bytes, _) = data_bytes.split_at_mut(num_data_bytes);
-        reader.read_exact(data_bytes).await?;
+        Self::read_exact_observed(reader, data_bytes, limits).await?;
         match decode::to_data_line(data_bytes) {
             Ok(line) => Ok(Ok(line)),
             Err(err) => Ok(Err(err)),
@@ -41,6 +62,7 @@ where
     async fn read_line_inner_exhaustive<'a>(
         reader: &mut T,
         buf: &'a mut Vec<u8>,
+        limits: &mut limits::State,
         delimiters: &[PacketLineRef<'static>],
         fail_on_err_lines: bool,
         buf_resize: bool,
@@ -48,7 +70,7 @@ where
         (
             false,
             None,
-            Some(match Self::read_line_inner(reader, buf).await {
+            Some(match Self::read_line_inner(reader, buf, limits).await {
                 Ok(Ok(line)) => {
                     if delimiters.contains(&line) {
                         let stopped_at = delimiters.iter().find(|l| **l == line).cloned();
@@ -111,6 +133,7 @@ where
             let (is_done, stopped_at, res) = Self::read_line_inner_exhaustive(
                 &mut self.read,
                 &mut self.buf,
+                &mut self.limits,
                 self.delimiters,
                 self.fail_on_err_lines,
                 false,
@@ -134,6 +157,7 @@ where
             let (is_done, stopped_at, res) = Self::read_line_inner_exhaustive(
                 &mut self.read,
                 &mut self.peek_buf,
+                &mut self.limits,
                 self.delimiters,
                 self.fail_on_err_lines,
                 true,
@@ -4,7 +4,7 @@ use bstr::ByteSlice;
 
 use crate::{
     decode,
-    read::{ExhaustiveOutcome, WithSidebands},
+    read::{limits, ExhaustiveOutcome, WithSidebands},
     PacketLineRef, StreamingPeekableIter, MAX_LINE_LEN, U16_HEX_BYTES,
 };
 
@@ -13,20 +13,41 @@ impl<T> StreamingPeekableIter<T>
 where
     T: io::Read,
 {
+    /// Like [`read_exact()`][io::Read::read_exact()], but observes each read if `limits` measure the throughput.
+    fn read_exact_observed(reader: &mut T, mut buf: &mut [u8], limits: &mut limits::State) -> io::Result<()> {
+        if !limits.watches_throughput() {
+            return reader.read_exact(buf);
+        }
+        while !buf.is_empty() {
+            match reader.read(buf) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => {
+                    limits.on_read(n)?;
+                    buf = &mut buf[n..];
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
     fn read_line_inner<'a>(
         reader: &mut T,
         buf: &'a mut Vec<u8>,
+        limits: &mut limits::State,
     ) -> io::Result<Result<PacketLineRef<'a>, decode::Error>> {
         let (hex_bytes, data_bytes) = buf.split_at_mut(4);
-        reader.read_exact(hex_bytes)?;
+        Self::read_exact_observed(reader, hex_bytes, limits)?;
         let num_data_bytes = match decode::hex_prefix(hex_bytes) {
             Ok(decode::PacketLineOrWantedSize::Line(line)) => return Ok(Ok(line)),
             Ok(decode::PacketLineOrWantedSize::Wanted(additional_bytes)) => additional_bytes as usize,
             Err(err) => return Ok(Err(err)),
         };
 
+        limits.on_line(num_data_bytes)?;
         let (data_bytes, _) = data_bytes.split_at_mut(num_data_bytes);
-        reader.read_exact(data_bytes)?;
+        Self::read_exact_observed(reader, data_bytes, limits)?;
         match decode::to_data_line(data_bytes) {
             Ok(line) => Ok(Ok(line)),
             Err(err) => Ok(Err(err)),
@@ -38,6 +59,7 @@ where
     fn read_line_inner_exhaustive<'a>(
         reader: &mut T,
         buf: &'a mut Vec<u8>,
+        limits: &mut limits::State,
         delimiters: &[PacketLineRef<'static>],
         fail_on_err_lines: bool,
         buf_resize: bool,
@@ -45,7 +67,7 @@ where
         (
             false,
             None,
-            Some(match Self::read_line_inner(reader, buf) {
+            Some(match Self::read_line_inner(reader, buf, limits) {
                 Ok(Ok(line)) => {
                     if delimiters.contains(&line) {
                         let stopped_at = delimiters.iter().find(|l| **l == line).cloned();
@@ -108,6 +130,7 @@ where
             let (is_done, stopped_at, res) = Self::read_line_inner_exhaustive(
                 &mut self.read,
                 &mut self.buf,
+                &mut self.limits,
                 self.delimiters,
                 self.fail_on_err_lines,
                 false,
@@ -130,6 +153,7 @@ where
             let (is_done, stopped_at, res) = Self::read_line_inner_exhaustive(
                 &mut self.read,
                 &mut self.peek_buf,
+                &mut self.limits,
                 self.delimiters,
                 self.fail_on_err_lines,
                 true,
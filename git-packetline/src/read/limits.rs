@@ -0,0 +1,136 @@
+//! Limits to protect readers against remotes which send too much data, or send it too slowly.
+use std::time::Duration;
+#[cfg(any(feature = "blocking-io", feature = "async-io"))]
+use std::time::Instant;
+
+use quick_error::quick_error;
+
+/// A minimal amount of bytes to receive per second, measured over a window of time.
+#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy)]
+pub struct Throughput {
+    /// The least amount of bytes per second to receive on average within `window`.
+    pub bytes_per_second: u64,
+    /// The duration over which the throughput is averaged before it is compared to `bytes_per_second`.
+    pub window: Duration,
+}
+
+/// Limits on the data read by a [`StreamingPeekableIter`][crate::StreamingPeekableIter], with `None` meaning unlimited.
+///
+/// Data is considered _payload_ once it's read in [raw mode][crate::read::WithSidebands::enable_raw_mode()] or
+/// received on the data band while side-bands are decoded, which typically is the case for pack data.
+#[derive(Default, PartialEq, Eq, Debug, Hash, Clone, Copy)]
+pub struct Limits {
+    /// The maximum amount of data bytes in a single packet line, which can't be more than 65516 bytes anyway.
+    pub max_data_len: Option<usize>,
+    /// The maximum amount of bytes, including the line length prefixes, to receive before the first payload.
+    pub max_bytes_before_payload: Option<usize>,
+    /// The throughput to maintain while receiving payload.
+    ///
+    /// It's checked each time data arrives, hence a remote which sends nothing at all is only detected if the underlying
+    /// reader times out.
+    pub min_payload_throughput: Option<Throughput>,
+}
+
+quick_error! {
+    /// The error returned when one of the [`Limits`] is exceeded, as wrapped into an `std::io::Error` by all reading methods.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        DataLength { length: usize, limit: usize } {
+            display("A packet line with {} data bytes exceeded the limit of {} bytes per line", length, limit)
+        }
+        BytesBeforePayload { limit: usize } {
+            display("More than {} bytes were received before the payload", limit)
+        }
+        Throughput { bytes: u64, elapsed: Duration, limit: Throughput } {
+            display("Only {} bytes were received in {:.1}s, which is less than the minimum throughput of {} bytes per second", bytes, elapsed.as_secs_f64(), limit.bytes_per_second)
+        }
+    }
+}
+
+impl From<Error> for std::io::Error {
+    fn from(err: Error) -> Self {
+        let kind = match err {
+            Error::Throughput { .. } => std::io::ErrorKind::TimedOut,
+            Error::DataLength { .. } | Error::BytesBeforePayload { .. } => std::io::ErrorKind::InvalidData,
+        };
+        std::io::Error::new(kind, err)
+    }
+}
+
+/// The state needed to enforce [`Limits`] across multiple lines and reads.
+#[cfg(any(feature = "blocking-io", feature = "async-io"))]
+#[derive(Default, Debug, Clone)]
+pub(crate) struct State {
+    limits: Limits,
+    bytes_before_payload: usize,
+    /// The start of the current throughput window, along with the bytes received within it, once payload was received.
+    payload_window: Option<(Instant, u64)>,
+}
+
+#[cfg(any(feature = "blocking-io", feature = "async-io"))]
+impl State {
+    pub(crate) fn new(limits: Limits) -> Self {
+        State {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// Return true if reads have to be observed with [`on_read()`][State::on_read()] to measure throughput.
+    pub(crate) fn watches_throughput(&self) -> bool {
+        self.payload_window.is_some() && self.limits.min_payload_throughput.is_some()
+    }
+
+    /// Call with the amount of data bytes of a line before reading them.
+    pub(crate) fn on_line(&mut self, data_len: usize) -> Result<(), Error> {
+        if let Some(limit) = self.limits.max_data_len {
+            if data_len > limit {
+                return Err(Error::DataLength {
+                    length: data_len,
+                    limit,
+                });
+            }
+        }
+        if self.payload_window.is_none() {
+            self.bytes_before_payload += crate::U16_HEX_BYTES + data_len;
+            if let Some(limit) = self.limits.max_bytes_before_payload {
+                if self.bytes_before_payload > limit {
+                    return Err(Error::BytesBeforePayload { limit });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Signal that payload is being received, which stops counting bytes and starts measuring throughput.
+    pub(crate) fn on_payload(&mut self) {
+        if self.payload_window.is_none() {
+            self.payload_window = Some((Instant::now(), 0));
+        }
+    }
+
+    /// Call with the amount of `bytes` obtained by a single read while receiving payload.
+    pub(crate) fn on_read(&mut self, bytes: usize) -> Result<(), Error> {
+        let limit = match self.limits.min_payload_throughput {
+            Some(limit) => limit,
+            None => return Ok(()),
+        };
+        if let Some((start, received)) = self.payload_window.as_mut() {
+            *received += bytes as u64;
+            let elapsed = start.elapsed();
+            if elapsed >= limit.window {
+                if (*received as f64) < limit.bytes_per_second as f64 * elapsed.as_secs_f64() {
+                    return Err(Error::Throughput {
+                        bytes: *received,
+                        elapsed,
+                        limit,
+                    });
+                }
+                *start = Instant::now();
+                *received = 0;
+            }
+        }
+        Ok(())
+    }
+}
@@ -44,6 +44,8 @@ impl<T> StreamingPeekableIter<T> {
             fail_on_err_lines: false,
             is_done: false,
             stopped_at: None,
+            #[cfg(any(feature = "blocking-io", feature = "async-io"))]
+            limits: Default::default(),
         }
     }
 
@@ -53,10 +55,15 @@ impl<T> StreamingPeekableIter<T> {
     /// This is useful if you would want to remove 'special bytes' hidden behind, say a NULL byte to disappear and allow
     /// standard line readers to read the next line as usual.
     ///
-    /// **Note** that `position` does not include the 4 bytes prefix (they are invisible outside the reader)
+    /// **Note** that `position` does not include the 4 bytes prefix (they are invisible outside the reader).
+    /// If `position` is the length of the line, `replace_with` is appended instead.
     pub fn peek_buffer_replace_and_truncate(&mut self, position: usize, replace_with: u8) {
         let position = position + U16_HEX_BYTES;
-        self.peek_buf[position] = replace_with;
+        if position == self.peek_buf.len() {
+            self.peek_buf.push(replace_with);
+        } else {
+            self.peek_buf[position] = replace_with;
+        }
 
         let new_len = position + 1;
         self.peek_buf.truncate(new_len);
@@ -92,6 +99,14 @@ impl<T> StreamingPeekableIter<T> {
         self.fail_on_err_lines = value;
     }
 
+    /// Enforce `limits` on all data read from now on, starting to count the bytes received before the payload from zero.
+    ///
+    /// Once one of the limits is exceeded, reading fails with an IO error wrapping a [`limits::Error`].
+    #[cfg(any(feature = "blocking-io", feature = "async-io"))]
+    pub fn set_limits(&mut self, limits: limits::Limits) {
+        self.limits = limits::State::new(limits);
+    }
+
     /// Replace the reader used with the given `read`, resetting all other iteration state as well.
     pub fn replace(&mut self, read: T) -> T {
         let prev = std::mem::replace(&mut self.read, read);
@@ -112,6 +127,9 @@ mod blocking_io;
 #[cfg(all(not(feature = "blocking-io"), feature = "async-io"))]
 mod async_io;
 
+///
+pub mod limits;
+
 mod sidebands;
 #[cfg(any(feature = "blocking-io", feature = "async-io"))]
 pub use sidebands::WithSidebands;
@@ -7,7 +7,7 @@ use std::{
 use futures_io::{AsyncBufRead, AsyncRead};
 use futures_lite::ready;
 
-use crate::{decode, BandRef, PacketLineRef, StreamingPeekableIter, TextRef, U16_HEX_BYTES};
+use crate::{decode, BandRef, PacketLineRef, StreamingPeekableIter, TextRef, MAX_LINE_LEN, U16_HEX_BYTES};
 
 type ReadLineResult<'a> = Option<std::io::Result<Result<PacketLineRef<'a>, decode::Error>>>;
 /// An implementor of [`AsyncBufRead`] yielding packet lines on each call to [`read_line()`][AsyncBufRead::read_line()].
@@ -21,6 +21,7 @@ where
     handle_progress: Option<F>,
     pos: usize,
     cap: usize,
+    raw: bool,
 }
 
 impl<'a, T, F> Drop for WithSidebands<'a, T, F>
@@ -45,6 +46,7 @@ where
             handle_progress: None,
             pos: 0,
             cap: 0,
+            raw: false,
         }
     }
 }
@@ -98,6 +100,7 @@ where
             handle_progress: Some(handle_progress),
             pos: 0,
             cap: 0,
+            raw: false,
         }
     }
 
@@ -108,6 +111,7 @@ where
             handle_progress: None,
             pos: 0,
             cap: 0,
+            raw: false,
         }
     }
 
@@ -131,6 +135,14 @@ where
         self.handle_progress = handle_progress;
     }
 
+    /// Stop decoding packet lines and return all following bytes as they are read from the parent's underlying reader.
+    ///
+    /// This is useful if the remote sends data without packet line encoding, like a pack without side-band.
+    /// **Note** that no line must have been peeked, as its data would be lost.
+    pub fn enable_raw_mode(&mut self) {
+        self.raw = true;
+    }
+
     /// Effectively forwards to the parent [StreamingPeekableIter::peek_line()], allowing to see what would be returned
     /// next on a call to [`read_line()`][io::BufRead::read_line()].
     pub async fn peek_data_line(&mut self) -> Option<std::io::Result<Result<&[u8], crate::decode::Error>>> {
@@ -191,7 +203,21 @@ where
         use futures_lite::FutureExt;
         {
             let this = self.as_mut().get_mut();
-            if this.pos >= this.cap {
+            if this.raw && this.pos >= this.cap {
+                let parent = match this.state {
+                    State::Idle { ref mut parent } => parent.as_mut().expect("parent to be present here"),
+                    State::ReadLine { .. } => unreachable!("lines are never read in raw mode"),
+                };
+                debug_assert!(parent.peek_buf.is_empty(), "peeked lines can't be read raw");
+                if parent.buf.len() != MAX_LINE_LEN {
+                    parent.buf.resize(MAX_LINE_LEN, 0);
+                }
+                parent.limits.on_payload();
+                this.cap = ready!(Pin::new(&mut parent.read).poll_read(cx, &mut parent.buf))?;
+                parent.limits.on_read(this.cap)?;
+                this.pos = 0;
+            } else if this.pos >= this.cap {
+                let mut is_payload = false;
                 let (ofs, cap) = loop {
                     match this.state {
                         State::Idle { ref mut parent } => {
@@ -234,7 +260,10 @@ where
                                         .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
                                     const ENCODED_BAND: usize = 1;
                                     match band {
-                                        BandRef::Data(d) => break (U16_HEX_BYTES + ENCODED_BAND, d.len()),
+                                        BandRef::Data(d) => {
+                                            is_payload = true;
+                                            break (U16_HEX_BYTES + ENCODED_BAND, d.len());
+                                        }
                                         BandRef::Progress(d) => {
                                             let text = TextRef::from(d).0;
                                             handle_progress(false, text);
@@ -260,6 +289,11 @@ where
                         }
                     }
                 };
+                if is_payload {
+                    if let State::Idle { parent: Some(parent) } = &mut this.state {
+                        parent.limits.on_payload();
+                    }
+                }
                 this.cap = cap + ofs;
                 this.pos = ofs;
             }
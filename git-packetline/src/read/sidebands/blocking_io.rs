@@ -1,6 +1,6 @@
 use std::{io, io::BufRead};
 
-use crate::{BandRef, PacketLineRef, StreamingPeekableIter, TextRef, U16_HEX_BYTES};
+use crate::{BandRef, PacketLineRef, StreamingPeekableIter, TextRef, MAX_LINE_LEN, U16_HEX_BYTES};
 
 /// An implementor of [`BufRead`][io::BufRead] yielding packet lines on each call to [`read_line()`][io::BufRead::read_line()].
 /// It's also possible to hide the underlying packet lines using the [`Read`][io::Read] implementation which is useful
@@ -13,6 +13,7 @@ where
     handle_progress: Option<F>,
     pos: usize,
     cap: usize,
+    raw: bool,
 }
 
 impl<'a, T, F> Drop for WithSidebands<'a, T, F>
@@ -35,6 +36,7 @@ where
             handle_progress: None,
             pos: 0,
             cap: 0,
+            raw: false,
         }
     }
 }
@@ -54,6 +56,7 @@ where
             handle_progress: Some(handle_progress),
             pos: 0,
             cap: 0,
+            raw: false,
         }
     }
 
@@ -64,6 +67,7 @@ where
             handle_progress: None,
             pos: 0,
             cap: 0,
+            raw: false,
         }
     }
 
@@ -82,6 +86,14 @@ where
         self.handle_progress = handle_progress;
     }
 
+    /// Stop decoding packet lines and return all following bytes as they are read from the parent's underlying reader.
+    ///
+    /// This is useful if the remote sends data without packet line encoding, like a pack without side-band.
+    /// **Note** that no line must have been peeked, as its data would be lost.
+    pub fn enable_raw_mode(&mut self) {
+        self.raw = true;
+    }
+
     /// Effectively forwards to the parent [StreamingPeekableIter::peek_line()], allowing to see what would be returned
     /// next on a call to [`read_line()`][io::BufRead::read_line()].
     pub fn peek_data_line(&mut self) -> Option<io::Result<Result<&[u8], crate::decode::Error>>> {
@@ -100,7 +112,21 @@ where
     F: FnMut(bool, &[u8]),
 {
     fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.raw {
+            if self.pos >= self.cap {
+                debug_assert!(self.parent.peek_buf.is_empty(), "peeked lines can't be read raw");
+                if self.parent.buf.len() != MAX_LINE_LEN {
+                    self.parent.buf.resize(MAX_LINE_LEN, 0);
+                }
+                self.parent.limits.on_payload();
+                self.cap = self.parent.read.read(&mut self.parent.buf)?;
+                self.parent.limits.on_read(self.cap)?;
+                self.pos = 0;
+            }
+            return Ok(&self.parent.buf[self.pos..self.cap]);
+        }
         if self.pos >= self.cap {
+            let mut is_payload = false;
             let (ofs, cap) = loop {
                 let line = match self.parent.read_line() {
                     Some(line) => line?.map_err(|err| io::Error::new(io::ErrorKind::Other, err))?,
@@ -113,7 +139,10 @@ where
                             .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
                         const ENCODED_BAND: usize = 1;
                         match band {
-                            BandRef::Data(d) => break (U16_HEX_BYTES + ENCODED_BAND, d.len()),
+                            BandRef::Data(d) => {
+                                is_payload = true;
+                                break (U16_HEX_BYTES + ENCODED_BAND, d.len());
+                            }
                             BandRef::Progress(d) => {
                                 let text = TextRef::from(d).0;
                                 handle_progress(false, text);
@@ -137,6 +166,9 @@ where
                     }
                 }
             };
+            if is_payload {
+                self.parent.limits.on_payload();
+            }
             self.cap = cap + ofs;
             self.pos = ofs;
         }
@@ -0,0 +1,117 @@
+#[cfg(feature = "blocking-io")]
+use std::io::Read;
+use std::{io, time::Duration};
+
+#[cfg(all(not(feature = "blocking-io"), feature = "async-io"))]
+use futures_lite::io::AsyncReadExt;
+use git_packetline::{
+    read::limits::{self, Limits, Throughput},
+    PacketLineRef, StreamingPeekableIter,
+};
+
+fn limit_error(err: &io::Error) -> &limits::Error {
+    err.get_ref()
+        .and_then(|err| err.downcast_ref::<limits::Error>())
+        .expect("a limits error")
+}
+
+#[maybe_async::test(feature = "blocking-io", async(feature = "async-io", async_std::test))]
+async fn data_length_is_checked_before_reading_the_line() -> crate::Result {
+    let mut rd = StreamingPeekableIter::new(&b"0008abcd0009abcde"[..], &[PacketLineRef::Flush]);
+    rd.set_limits(Limits {
+        max_data_len: Some(4),
+        ..Default::default()
+    });
+    let line = rd.read_line().await;
+    assert_eq!(line.expect("line")??, PacketLineRef::Data(b"abcd"));
+    let err = rd.read_line().await.expect("line").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(matches!(
+        limit_error(&err),
+        limits::Error::DataLength { length: 5, limit: 4 }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "A packet line with 5 data bytes exceeded the limit of 4 bytes per line"
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-io", async(feature = "async-io", async_std::test))]
+async fn bytes_before_payload_include_the_line_length_prefix() -> crate::Result {
+    let mut rd = StreamingPeekableIter::new(&b"0008abcd0008efgh"[..], &[PacketLineRef::Flush]);
+    rd.set_limits(Limits {
+        max_bytes_before_payload: Some(15),
+        ..Default::default()
+    });
+    let line = rd.read_line().await;
+    assert_eq!(line.expect("line")??, PacketLineRef::Data(b"abcd"));
+    let err = rd.read_line().await.expect("line").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(matches!(
+        limit_error(&err),
+        limits::Error::BytesBeforePayload { limit: 15 }
+    ));
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-io", async(feature = "async-io", async_std::test))]
+async fn payload_does_not_count_towards_the_bytes_before_it() -> crate::Result {
+    let mut rd = StreamingPeekableIter::new(
+        &b"0008abcd00000009\x01pack0009\x01data0000"[..],
+        &[PacketLineRef::Flush],
+    );
+    rd.set_limits(Limits {
+        max_bytes_before_payload: Some(17),
+        ..Default::default()
+    });
+    let mut out = Vec::new();
+    rd.as_read().read_to_end(&mut out).await?;
+    assert_eq!(out, b"abcd");
+
+    rd.reset();
+    out.clear();
+    rd.as_read_with_sidebands(|_is_err, _text| {})
+        .read_to_end(&mut out)
+        .await?;
+    assert_eq!(
+        out, b"packdata",
+        "the line starting the payload is the last one to count, for 8 + 9 bytes"
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-io", async(feature = "async-io", async_std::test))]
+async fn throughput_is_only_measured_once_payload_arrived() -> crate::Result {
+    let mut rd = StreamingPeekableIter::new(
+        &b"0008abcd00000009\x01pack0009\x01data0000"[..],
+        &[PacketLineRef::Flush],
+    );
+    let limit = Throughput {
+        bytes_per_second: u64::MAX,
+        window: Duration::from_millis(1),
+    };
+    rd.set_limits(Limits {
+        min_payload_throughput: Some(limit),
+        ..Default::default()
+    });
+    std::thread::sleep(limit.window * 2);
+    let mut out = Vec::new();
+    rd.as_read().read_to_end(&mut out).await?;
+    assert_eq!(out, b"abcd", "no payload yet, no matter how slowly it arrives");
+
+    rd.reset();
+    let mut rd = rd.as_read_with_sidebands(|_is_err, _text| {});
+    let mut buf = [0u8; 4];
+    rd.read_exact(&mut buf).await?;
+    assert_eq!(&buf, b"pack", "the first payload starts the measurement");
+
+    std::thread::sleep(limit.window * 2);
+    let err = rd.read_exact(&mut buf).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    assert!(matches!(
+        limit_error(&err),
+        limits::Error::Throughput { bytes: 4, limit: actual, .. } if *actual == limit
+    ));
+    Ok(())
+}
@@ -1,3 +1,4 @@
+mod limits;
 mod sideband;
 
 pub mod streaming_peek_iter {
@@ -141,6 +141,27 @@ async fn read_line_trait_method_reads_one_packet_line_at_a_time() -> crate::Resu
     Ok(())
 }
 
+#[maybe_async::test(feature = "blocking-io", async(feature = "async-io", async_std::test))]
+async fn raw_mode_reads_data_without_packet_line_encoding() -> crate::Result {
+    let buf = b"0008NAK\nPACK\0\0\0\x02raw data\n0000".to_vec();
+    let mut rd = git_packetline::StreamingPeekableIter::new(&buf[..], &[PacketLineRef::Flush]);
+
+    let mut r = rd.as_read();
+    let mut out = String::new();
+    r.read_line(&mut out).await?;
+    assert_eq!(out, "NAK\n");
+
+    r.enable_raw_mode();
+    let mut out = Vec::new();
+    r.read_to_end(&mut out).await?;
+    assert_eq!(
+        out.as_bstr(),
+        b"PACK\0\0\0\x02raw data\n0000".as_bstr(),
+        "everything is passed through as is, including what looks like packet lines"
+    );
+    Ok(())
+}
+
 #[maybe_async::test(feature = "blocking-io", async(feature = "async-io", async_std::test))]
 async fn peek_past_an_actual_eof_is_an_error() -> crate::Result {
     let input = b"0009ERR e";
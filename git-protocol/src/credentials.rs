@@ -58,6 +58,16 @@ pub struct NextAction {
     previous_output: Vec<u8>,
 }
 
+impl From<Vec<u8>> for NextAction {
+    /// Create a handle from the `output` of a credentials helper which performed [`Action::Fill`], useful for
+    /// implementing [`Action`] handlers which don't use [`helper()`].
+    fn from(output: Vec<u8>) -> Self {
+        NextAction {
+            previous_output: output,
+        }
+    }
+}
+
 impl NextAction {
     /// Approve the result of the previous [Action].
     pub fn approve(self) -> Action<'static> {
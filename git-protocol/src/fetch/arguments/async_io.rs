@@ -9,9 +9,6 @@ impl Arguments {
         transport: &'a mut T,
         add_done_argument: bool,
     ) -> Result<Box<dyn client::ExtendedBufRead + Unpin + 'a>, client::Error> {
-        if self.haves.is_empty() {
-            assert!(add_done_argument, "If there are no haves, is_done must be true.");
-        }
         match self.version {
             git_transport::Protocol::V1 => {
                 let (on_into_read, retained_state) = self.prepare_v1(
@@ -36,16 +33,12 @@ impl Arguments {
                 Ok(line_writer.into_read().await?)
             }
             git_transport::Protocol::V2 => {
-                let retained_state = self.args.clone();
-                self.args.append(&mut self.haves);
-                if add_done_argument {
-                    self.args.push("done".into());
-                }
+                let args = self.prepare_v2(add_done_argument);
                 transport
                     .invoke(
                         Command::Fetch.as_str(),
                         self.features.iter().filter(|(_, v)| v.is_some()).cloned(),
-                        Some(std::mem::replace(&mut self.args, retained_state).into_iter()),
+                        Some(args.into_iter()),
                     )
                     .await
             }
@@ -2,6 +2,9 @@ use std::fmt;
 
 use bstr::{BStr, BString, ByteVec};
 
+/// The amount of `have` lines to send in the first round of negotiation.
+const INITIAL_WINDOW_SIZE: usize = 16;
+
 /// The arguments passed to a server command.
 pub struct Arguments {
     /// The active features/capabilities of the fetch invocation
@@ -10,6 +13,8 @@ pub struct Arguments {
 
     args: Vec<BString>,
     haves: Vec<BString>,
+    common: Vec<git_hash::ObjectId>,
+    window_size: usize,
 
     filter: bool,
     shallow: bool,
@@ -66,13 +71,39 @@ impl Arguments {
         self.ref_in_want
     }
 
+    /// Return the amount of `have` lines to send in the current round of negotiation.
+    ///
+    /// It starts out at 16 and grows with each round like it does in `git`, doubling until it reaches a limit which is lower
+    /// for stateful V1 connections than it is for V2 or stateless ones.
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+    /// Return the ids of all objects the server acknowledged to have in common in previous rounds of negotiation.
+    ///
+    /// Unless the server keeps the state of the negotiation, which only happens in V1 with stateful connections, these are sent
+    /// as `have` lines again with each request automatically.
+    pub fn common(&self) -> &[git_hash::ObjectId] {
+        &self.common
+    }
+
     /// Add the given `id` pointing to a commit to the 'want' list.
     ///
     /// As such it should be included in the server response as it's not present on the client.
+    /// Adding the same `id` more than once has no effect, and wants only need to be added in the first round of negotiation
+    /// as they are repeated automatically if the server doesn't keep state between requests.
     pub fn want(&mut self, id: impl AsRef<git_hash::oid>) {
+        let id = id.as_ref();
+        let line = format!("want {}", id);
+        if self
+            .args
+            .iter()
+            .any(|arg| arg.starts_with(line.as_bytes()) && matches!(arg.get(line.len()), None | Some(b' ')))
+        {
+            return;
+        }
         match self.features_for_first_want.take() {
-            Some(features) => self.prefixed("want ", format!("{} {}", id.as_ref(), features.join(" "))),
-            None => self.prefixed("want ", id.as_ref()),
+            Some(features) if !features.is_empty() => self.prefixed("want ", format!("{} {}", id, features.join(" "))),
+            _ => self.prefixed("want ", id),
         }
     }
     /// Add the given ref to the 'want-ref' list.
@@ -87,6 +118,8 @@ impl Arguments {
     /// Add the given `id` pointing to a commit to the 'have' list.
     ///
     /// As such it should _not_ be included in the server response as it's already present on the client.
+    /// Haves are sent only once, with the request of the current round, but those that the server acknowledged as
+    /// [`common`][Arguments::common()] are repeated if needed.
     pub fn have(&mut self, id: impl AsRef<git_hash::oid>) {
         self.haves.push(format!("have {}", id.as_ref()).into());
     }
@@ -157,6 +190,8 @@ impl Arguments {
             version,
             args: initial_arguments,
             haves: Vec::new(),
+            common: Vec::new(),
+            window_size: INITIAL_WINDOW_SIZE,
             filter,
             shallow,
             deepen_not,
@@ -173,9 +208,33 @@ mod shared {
     use bstr::{BString, ByteSlice};
     use git_transport::{client, client::MessageKind};
 
-    use crate::fetch::Arguments;
+    use crate::fetch::{response::Acknowledgement, Arguments};
 
     impl Arguments {
+        /// Remember the ids of all common objects in `acks` as received in response to the previous request.
+        pub(crate) fn record_acknowledgements(&mut self, acks: &[Acknowledgement]) {
+            for id in acks.iter().filter_map(Acknowledgement::id) {
+                if !self.common.contains(id) {
+                    self.common.push(*id);
+                }
+            }
+        }
+
+        /// Put `have` lines for all common objects in front of the haves of this round unless the server keeps the
+        /// state of the negotiation, and grow the window for the next round.
+        pub(in crate::fetch::arguments) fn prepare_haves(&mut self, server_keeps_state: bool) {
+            if !server_keeps_state && !self.common.is_empty() {
+                let mut haves: Vec<BString> = self.common.iter().map(|id| format!("have {}", id).into()).collect();
+                for have in std::mem::take(&mut self.haves) {
+                    if !haves.contains(&have) {
+                        haves.push(have);
+                    }
+                }
+                self.haves = haves;
+            }
+            self.window_size = next_window_size(self.window_size, server_keeps_state);
+        }
+
         pub(in crate::fetch::arguments) fn prepare_v1(
             &mut self,
             transport_is_stateful: bool,
@@ -194,12 +253,44 @@ mod shared {
             } else {
                 Some(self.args.clone())
             };
+            self.prepare_haves(transport_is_stateful);
 
             if let Some(first_arg_position) = self.args.iter().position(|l| l.starts_with_str("want ")) {
                 self.args.swap(first_arg_position, 0);
             }
             Ok((on_into_read, retained_state))
         }
+
+        pub(in crate::fetch::arguments) fn prepare_v2(&mut self, add_done_argument: bool) -> Vec<BString> {
+            if self.haves.is_empty() {
+                assert!(add_done_argument, "If there are no haves, is_done must be true.");
+            }
+            // The server never keeps state between V2 requests, so wants and common objects have to be repeated each time.
+            self.prepare_haves(false);
+            let mut args = self.args.clone();
+            args.append(&mut self.haves);
+            if add_done_argument {
+                args.push("done".into());
+            }
+            args
+        }
+    }
+
+    /// Compute the size of the next window of haves from the `current` one, like `next_flush()` in `git`.
+    fn next_window_size(current: usize, server_keeps_state: bool) -> usize {
+        const PIPE_SAFE_WINDOW_SIZE: usize = 32;
+        const LARGE_WINDOW_SIZE: usize = 16384;
+        if server_keeps_state {
+            if current < PIPE_SAFE_WINDOW_SIZE {
+                current * 2
+            } else {
+                current + PIPE_SAFE_WINDOW_SIZE
+            }
+        } else if current < LARGE_WINDOW_SIZE {
+            current * 2
+        } else {
+            current * 11 / 10
+        }
     }
 }
 
@@ -5,6 +5,8 @@ pub enum Command {
     LsRefs,
     /// Fetch a pack.
     Fetch,
+    /// Query information about objects, like their size, without fetching them.
+    ObjectInfo,
 }
 
 /// A key value pair of values known at compile time.
@@ -16,6 +18,7 @@ impl Command {
         match self {
             Command::LsRefs => "ls-refs",
             Command::Fetch => "fetch",
+            Command::ObjectInfo => "object-info",
         }
     }
 }
@@ -54,12 +57,15 @@ mod with_io {
                     // packfile-uris feature
                     "packfile-uris ", // protocols
                 ],
+                Command::ObjectInfo => &[
+                    "size", "oid ", // hex oid
+                ],
             }
         }
 
         fn all_features(&self, version: git_transport::Protocol) -> &'static [&'static str] {
             match self {
-                Command::LsRefs => &[],
+                Command::LsRefs | Command::ObjectInfo => &[],
                 Command::Fetch => match version {
                     git_transport::Protocol::V1 => &[
                         "multi_ack",
@@ -101,6 +107,7 @@ mod with_io {
                     )
                     .collect(),
                 Command::LsRefs => vec![b"symrefs".as_bstr().to_owned(), b"peel".as_bstr().to_owned()],
+                Command::ObjectInfo => vec![b"size".as_bstr().to_owned()],
             }
         }
 
@@ -124,7 +131,8 @@ mod with_io {
                                 feature => server_capabilities.contains(feature),
                             })
                             .map(|s| (s, None))
-                            .chain(Some(agent()))
+                            // Like any other capability, the agent can only be sent if the server advertised it.
+                            .chain(server_capabilities.contains("agent").then(agent))
                             .collect()
                     }
                     git_transport::Protocol::V2 => {
@@ -147,7 +155,7 @@ mod with_io {
                             .collect()
                     }
                 },
-                Command::LsRefs => vec![agent()],
+                Command::LsRefs | Command::ObjectInfo => vec![agent()],
             }
         }
         /// Panics if the given arguments and features don't match what's statically known. It's considered a bug in the delegate.
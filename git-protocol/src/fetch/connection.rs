@@ -0,0 +1,328 @@
+use std::{collections::BTreeMap, io};
+
+use git_features::{progress, progress::Progress};
+use git_transport::{
+    client,
+    client::{Capabilities, SetServiceResponse, TransportV2Ext},
+    Protocol, Service,
+};
+use maybe_async::maybe_async;
+
+use crate::{
+    credentials,
+    fetch::{refs, Action, Arguments, Command, Delegate, DelegateBlocking, Error, LsRefsAction, Ref, Response},
+};
+
+/// A connection to a server on which the handshake was performed, ready to [list references][Connection::ls_refs()] and to
+/// [fetch packs][Connection::fetch()], as often as the protocol allows, without performing the handshake again.
+///
+/// With protocol V2 over stateful transports like `ssh://`, `git://` or `file://`, any amount of commands can be sent
+/// over the same connection, as the server advertises its capabilities only once in response to the handshake.
+/// With protocol V1, the references are advertised as part of the handshake and the server closes the connection after
+/// sending a pack, making further fetches fail with [`Error::ConnectionClosed`].
+///
+/// [`close()`][Connection::close()] should be called once no more commands are sent to let the server know the interaction ended.
+pub struct Connection<T> {
+    transport: T,
+    protocol_version: Protocol,
+    capabilities: Capabilities,
+    /// The references advertised in response to a V1 handshake.
+    v1_refs: Option<Vec<Ref>>,
+    /// True if the server doesn't expect any further interaction, as the end of it was indicated or a V1 pack was received.
+    closed: bool,
+}
+
+impl<T> Connection<T> {
+    /// The protocol version the server chose during the handshake.
+    pub fn protocol_version(&self) -> Protocol {
+        self.protocol_version
+    }
+
+    /// The capabilities the server advertised during the handshake.
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    /// Return the targets of all symbolic refs advertised by the server, either in the capabilities of the handshake or among
+    /// the `refs` [listed by it][Connection::ls_refs()], keyed by the name of the symbolic ref.
+    ///
+    /// See [`refs::symrefs()`] for details.
+    pub fn symrefs(&self, refs: &[Ref]) -> Result<BTreeMap<git_ref::FullName, git_ref::FullName>, refs::Error> {
+        refs::symrefs(&self.capabilities, refs)
+    }
+}
+
+impl<T> Connection<T>
+where
+    T: client::Transport,
+{
+    /// Perform the handshake with the server at the other end of `transport`, sending `extra_parameters` along with it.
+    ///
+    /// `authenticate(operation_to_perform)` is used to obtain credentials if the server denies access, see
+    /// [`fetch()`][crate::fetch()] for details. `progress` is used to emit progress messages.
+    #[maybe_async]
+    pub async fn handshake<F>(
+        mut transport: T,
+        extra_parameters: &[(&str, Option<&str>)],
+        mut authenticate: F,
+        progress: &mut impl Progress,
+    ) -> Result<Self, Error>
+    where
+        F: FnMut(credentials::Action<'_>) -> credentials::Result,
+    {
+        let (protocol_version, v1_refs, capabilities) = {
+            progress.init(None, progress::steps());
+            progress.set_name("handshake");
+            progress.step();
+
+            let supported_versions: Vec<_> = transport.supported_protocol_versions().into();
+
+            let result = transport.handshake(Service::UploadPack, extra_parameters).await;
+            let SetServiceResponse {
+                actual_protocol,
+                capabilities,
+                refs,
+                ..
+            } = match result {
+                Ok(v) => Ok(v),
+                Err(ref err) if is_permission_denied(err) => {
+                    drop(result); // needed to workaround this: https://github.com/rust-lang/rust/issues/76149
+                    let url = transport.to_url();
+                    progress.set_name("authentication");
+                    let credentials::Outcome { identity, next } =
+                        authenticate(credentials::Action::Fill(&url))?.ok_or(Error::CredentialsMissing)?;
+                    transport.set_identity(identity)?;
+                    progress.step();
+                    progress.set_name("handshake (authenticated)");
+                    match transport.handshake(Service::UploadPack, extra_parameters).await {
+                        Ok(v) => {
+                            authenticate(next.approve())?;
+                            Ok(v)
+                        }
+                        // Still no permission? Reject the credentials.
+                        Err(err) if is_permission_denied(&err) => {
+                            authenticate(next.reject())?;
+                            return Err(Error::AuthenticationRefused(err));
+                        }
+                        // Otherwise, do nothing, as we don't know if it actually got to try the credentials.
+                        // If they were previously stored, they remain. In the worst case, the user has to enter them again
+                        // next time they try.
+                        Err(err) => Err(err),
+                    }
+                }
+                Err(err) => Err(err),
+            }?;
+
+            if !supported_versions.is_empty() && !supported_versions.contains(&actual_protocol) {
+                return Err(Error::TransportProtocolPolicyViolation {
+                    actual_version: actual_protocol,
+                });
+            }
+
+            let v1_refs = match refs {
+                Some(mut refs) => {
+                    assert_eq!(actual_protocol, Protocol::V1, "Only V1 auto-responds with refs");
+                    Some(
+                        refs::from_v1_refs_received_as_part_of_handshake_and_capabilities(
+                            &mut refs,
+                            capabilities.iter(),
+                        )
+                        .await?,
+                    )
+                }
+                None => None,
+            };
+            (actual_protocol, v1_refs, capabilities)
+        }; // this scope is needed, see https://github.com/rust-lang/rust/issues/76149
+
+        Ok(Connection {
+            transport,
+            protocol_version,
+            capabilities,
+            v1_refs,
+            closed: false,
+        })
+    }
+
+    /// Return the references of the server, which with protocol V2 are obtained with the `ls-refs` command as prepared by
+    /// `delegate`, and with protocol V1 are the ones advertised during the handshake.
+    ///
+    /// If `delegate` fails to prepare the command, the end of the interaction is indicated to the server.
+    #[maybe_async]
+    pub async fn ls_refs(
+        &mut self,
+        delegate: &mut impl DelegateBlocking,
+        progress: &mut impl Progress,
+    ) -> Result<Vec<Ref>, Error> {
+        if let Some(refs) = &self.v1_refs {
+            return Ok(refs.clone());
+        }
+        self.assert_open()?;
+        assert_eq!(
+            self.protocol_version,
+            Protocol::V2,
+            "Only V2 needs a separate request to get specific refs"
+        );
+
+        let ls_refs = Command::LsRefs;
+        let mut ls_features = ls_refs.default_features(self.protocol_version, &self.capabilities);
+        let mut ls_args = ls_refs.initial_arguments(&ls_features);
+        match delegate.prepare_ls_refs(&self.capabilities, &mut ls_args, &mut ls_features) {
+            Ok(LsRefsAction::Skip) => Ok(Vec::new()),
+            Ok(LsRefsAction::Continue) => {
+                ls_refs.validate_argument_prefixes_or_panic(
+                    self.protocol_version,
+                    &self.capabilities,
+                    &ls_args,
+                    &ls_features,
+                );
+
+                progress.step();
+                progress.set_name("list refs");
+                let mut remote_refs = self
+                    .transport
+                    .invoke(
+                        ls_refs.as_str(),
+                        ls_features.into_iter(),
+                        if ls_args.is_empty() {
+                            None
+                        } else {
+                            Some(ls_args.into_iter())
+                        },
+                    )
+                    .await?;
+                Ok(refs::from_v2_refs(&mut remote_refs).await?)
+            }
+            Err(err) => {
+                self.close().await?;
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Negotiate and receive a pack with `delegate` given the `refs` [of the server][Connection::ls_refs()], reporting to
+    /// `progress`, and return true if a fetch was performed or false if `delegate` cancelled it.
+    ///
+    /// With protocol V2 this can be repeated, whereas with protocol V1 the connection can't be used anymore once a
+    /// pack was received. If `delegate` fails to prepare the fetch, the end of the interaction is indicated to the server.
+    ///
+    /// The [limits][client::Limits] of the transport protect against misbehaving servers, with the amount of negotiation rounds
+    /// being limited here and causing [`Error::NegotiationRoundsExceeded`].
+    #[maybe_async]
+    pub async fn fetch(
+        &mut self,
+        delegate: &mut impl Delegate,
+        refs: &[Ref],
+        mut progress: impl Progress,
+    ) -> Result<bool, Error> {
+        self.assert_open()?;
+        let protocol_version = self.protocol_version;
+        let fetch = Command::Fetch;
+        let mut fetch_features = fetch.default_features(protocol_version, &self.capabilities);
+        match delegate.prepare_fetch(protocol_version, &self.capabilities, &mut fetch_features, refs) {
+            Ok(Action::Cancel) => return Ok(false),
+            Ok(Action::Continue) => {
+                fetch.validate_argument_prefixes_or_panic(protocol_version, &self.capabilities, &[], &fetch_features);
+            }
+            Err(err) => {
+                self.close().await?;
+                return Err(err.into());
+            }
+        }
+
+        let sideband_all = fetch_features.iter().any(|(n, _)| *n == "sideband-all");
+        let pack_without_sideband = protocol_version == Protocol::V1
+            && !fetch_features
+                .iter()
+                .any(|(n, _)| *n == "side-band" || *n == "side-band-64k");
+        let mut arguments = Arguments::new(protocol_version, fetch_features);
+        let mut previous_response = None::<Response>;
+        let mut round = 1;
+        let max_rounds = self.transport.limits().max_negotiation_rounds;
+        'negotiation: loop {
+            if let Some(limit) = max_rounds.filter(|limit| round > *limit) {
+                return Err(Error::NegotiationRoundsExceeded { limit });
+            }
+            progress.step();
+            progress.set_name(format!("negotiate (round {})", round));
+            round += 1;
+            let action = delegate.negotiate(refs, &mut arguments, previous_response.as_ref())?;
+            let mut reader = arguments.send(&mut self.transport, action == Action::Cancel).await?;
+            if sideband_all {
+                setup_remote_progress(&mut progress, &mut reader);
+            }
+            let response = if pack_without_sideband && action == Action::Cancel {
+                let response = Response::from_line_reader_without_sideband(&mut reader).await?;
+                reader.enable_raw_mode();
+                response
+            } else {
+                Response::from_line_reader(protocol_version, &mut reader).await?
+            };
+            previous_response = if response.has_pack() {
+                progress.step();
+                progress.set_name("receiving pack");
+                if !sideband_all {
+                    setup_remote_progress(&mut progress, &mut reader);
+                }
+                delegate.receive_pack(reader, progress, refs, &response).await?;
+                break 'negotiation;
+            } else {
+                match action {
+                    Action::Cancel => break 'negotiation,
+                    Action::Continue => {
+                        arguments.record_acknowledgements(response.acknowledgements());
+                        Some(response)
+                    }
+                }
+            }
+        }
+        if protocol_version == Protocol::V1 {
+            self.closed = true;
+        }
+        Ok(true)
+    }
+
+    /// Indicate the end of the interaction to the server, which is only needed for stateful transports, unless it
+    /// already ended.
+    #[maybe_async]
+    pub async fn close(&mut self) -> Result<(), Error> {
+        if self.closed {
+            return Ok(());
+        }
+        self.closed = true;
+        // An empty request marks the (early) end of the interaction. Only relevant in stateful transports though.
+        if self.transport.connection_persists_across_multiple_requests() {
+            self.transport
+                .request(client::WriteMode::Binary, client::MessageKind::Flush)?
+                .into_read()
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn assert_open(&self) -> Result<(), Error> {
+        if self.closed {
+            return Err(Error::ConnectionClosed {
+                actual_version: self.protocol_version,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn setup_remote_progress(
+    progress: &mut impl Progress,
+    reader: &mut Box<dyn git_transport::client::ExtendedBufRead + Unpin + '_>,
+) {
+    reader.set_progress_handler(Some(Box::new({
+        let mut remote_progress = progress.add_child("remote");
+        move |is_err: bool, data: &[u8]| {
+            crate::RemoteProgress::translate_to_progress(is_err, data, &mut remote_progress)
+        }
+    }) as git_transport::client::HandleProgress));
+}
+
+/// Return true if `err` indicates that the server requires authentication, or that the identity used to authenticate was refused.
+fn is_permission_denied(err: &client::Error) -> bool {
+    matches!(err, client::Error::Io { err } if err.kind() == io::ErrorKind::PermissionDenied)
+}
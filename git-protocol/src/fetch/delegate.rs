@@ -103,8 +103,13 @@ pub trait DelegateBlocking {
     ///
     /// Populate `arguments` with the objects you `have` starting from the tips of _your_ refs, taking into consideration
     /// the `previous_response` response of the server to see which objects they acknowledged to have. You have to maintain
-    /// enough state to be able to walk down from your tips on each call, if they are not in common, and keep setting `have`
-    /// for those which are in common if that helps teaching the server about our state and to acknowledge their existence on _their_ end.
+    /// enough state to be able to walk down from your tips on each call, if they are not in common, and stop walking down
+    /// the ancestry of those which are.
+    /// Objects acknowledged in previous rounds are available as [`arguments.common()`][Arguments::common()] and are sent
+    /// again automatically if the server doesn't keep state between requests, as is the case in V2 and with stateless V1
+    /// connections like HTTP. The same is true for `wants`, which must not be added again.
+    ///
+    /// Send up to [`arguments.window_size()`][Arguments::window_size()] haves per round to let the window grow like it does in `git`.
     /// This method is called until the other side signals they are ready to send a pack.
     /// Return `Action::Close` if you want to give up before finding a common base. This can happen if the remote repository
     /// has radically changed so there are no bases, or they are very far in the past, causing all objects to be sent.
@@ -9,7 +9,7 @@ use crate::{
 };
 
 quick_error! {
-    /// The error used in [`fetch()`][super::fetch].
+    /// The error used in [`fetch()`][crate::fetch()] and by [`Connection`][super::Connection].
     #[derive(Debug)]
     #[allow(missing_docs)]
     pub enum Error {
@@ -23,6 +23,13 @@ quick_error! {
             from()
             source(err)
         }
+        CredentialsMissing {
+            display("The server requires authentication, but no credentials were provided")
+        }
+        AuthenticationRefused(err: client::Error) {
+            display("The server denied access even though credentials were provided")
+            source(err)
+        }
         Transport(err: client::Error) {
             display("An error occurred on the transport layer while fetching data")
             from()
@@ -34,6 +41,12 @@ quick_error! {
         TransportProtocolPolicyViolation{actual_version: git_transport::Protocol} {
             display("The transport didn't accept the advertised server version {:?} and closed the connection client side", actual_version)
         }
+        ConnectionClosed{actual_version: git_transport::Protocol} {
+            display("The interaction with the server ended, which with protocol {:?} happens after receiving a pack, and no more commands can be sent", actual_version)
+        }
+        NegotiationRoundsExceeded{limit: usize} {
+            display("The server still wasn't ready to send a pack after {} rounds of negotiation", limit)
+        }
         Ref(err: refs::Error) {
             display("A reference could not be parsed or invariants were not met")
             from()
@@ -46,3 +59,24 @@ quick_error! {
         }
     }
 }
+
+impl Error {
+    /// Return the [limit of the transport][client::Limits] which was exceeded if it caused this error.
+    ///
+    /// Note that exceeding the limit of [negotiation rounds][client::Limits::max_negotiation_rounds] is indicated by
+    /// [`Error::NegotiationRoundsExceeded`] instead.
+    pub fn transport_limit_exceeded(&self) -> Option<&client::limits::Error> {
+        let mut source: Option<&(dyn std::error::Error + 'static)> = Some(self);
+        while let Some(err) = source {
+            if let Some(err) = err
+                .downcast_ref::<io::Error>()
+                .and_then(|err| err.get_ref())
+                .and_then(|err| err.downcast_ref::<client::limits::Error>())
+            {
+                return Some(err);
+            }
+            source = err.source();
+        }
+        None
+    }
+}
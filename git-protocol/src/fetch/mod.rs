@@ -16,6 +16,11 @@ pub mod delegate;
 pub use delegate::Delegate;
 pub use delegate::{Action, DelegateBlocking, LsRefsAction};
 
+#[cfg(any(feature = "async-client", feature = "blocking-client"))]
+mod connection;
+#[cfg(any(feature = "async-client", feature = "blocking-client"))]
+pub use connection::Connection;
+
 mod error;
 pub use error::Error;
 ///
@@ -1,4 +1,4 @@
-use std::io;
+use std::{collections::BTreeMap, io};
 
 use bstr::BString;
 use quick_error::quick_error;
@@ -25,11 +25,15 @@ quick_error! {
             display("'{}' could not be parsed. A V1 ref line should be '<hex-hash> <path>'.", line)
         }
         MalformedV2RefLine(line: String) {
-            display("'{}' could not be parsed. A V2 ref line should be '<hex-hash> <path>[ (peeled|symref-target):<value>'.", line)
+            display("'{}' could not be parsed. A V2 ref line should be '<hex-hash> <path>[ (peeled|symref-target):<value>]*'.", line)
         }
         UnkownAttribute(attribute: String, line: String) {
             display("The ref attribute '{}' is unknown. Found in line '{}'", attribute, line)
         }
+        InvalidSymrefName(name: BString, err: git_ref::name::Error) {
+            display("The symbolic ref '{}' or its target isn't a valid reference name", name)
+            source(err)
+        }
         InvariantViolation(message: &'static str) {
             display("{}", message)
         }
@@ -80,6 +84,52 @@ impl Ref {
     }
 }
 
+/// Return the targets of all symbolic refs advertised by the server, keyed by the path of the symbolic ref, like `HEAD` pointing
+/// to `refs/heads/main`.
+///
+/// In protocol V1 these are taken from the `symref=<path>:<target>` entries of the `server` capabilities, which includes
+/// symbolic refs whose path isn't among the advertised `refs`. In protocol V2 they are taken from the `symref-target`
+/// attributes of `refs` as received via `ls-refs`.
+/// Symbolic refs whose target is `(null)` as it's in another namespace than the one set on the server are not included.
+/// Fails with [`Error::InvalidSymrefName`] if a symbolic ref or its target isn't a valid reference name.
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub fn symrefs(
+    server: &git_transport::client::Capabilities,
+    refs: &[Ref],
+) -> Result<BTreeMap<git_ref::FullName, git_ref::FullName>, Error> {
+    use std::convert::TryFrom;
+
+    use bstr::ByteSlice;
+    let to_names = |path: BString, target: BString| -> Result<_, Error> {
+        match (
+            git_ref::FullName::try_from(path.as_bstr()),
+            git_ref::FullName::try_from(target.as_bstr()),
+        ) {
+            (Ok(path), Ok(target)) => Ok((path, target)),
+            (Err(err), _) => Err(Error::InvalidSymrefName(path, err)),
+            (_, Err(err)) => Err(Error::InvalidSymrefName(target, err)),
+        }
+    };
+    let mut out = refs
+        .iter()
+        .filter_map(|r| match r {
+            Ref::Symbolic { path, target, .. } => Some(to_names(path.to_owned(), target.to_owned())),
+            Ref::Direct { .. } | Ref::Peeled { .. } => None,
+        })
+        .collect::<Result<BTreeMap<_, _>, _>>()?;
+    for symref in shared::from_capabilities(server.iter())? {
+        if let shared::InternalRef::SymbolicForLookup {
+            path,
+            target: Some(target),
+        } = symref
+        {
+            let (path, target) = to_names(path, target)?;
+            out.insert(path, target);
+        }
+    }
+    Ok(out)
+}
+
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
 pub(crate) mod shared {
     use bstr::{BString, ByteSlice};
@@ -145,6 +195,16 @@ pub(crate) mod shared {
         }
     }
 
+    /// Convert the refs parsed from a V1 handshake, dropping symbolic refs from the capabilities whose path wasn't advertised
+    /// as they lack an object. These are available through [`refs::symrefs()`] instead.
+    pub(in crate::fetch::refs) fn into_refs(out_refs: Vec<InternalRef>) -> Vec<Ref> {
+        out_refs
+            .into_iter()
+            .filter(|r| !matches!(r, InternalRef::SymbolicForLookup { .. }))
+            .map(Into::into)
+            .collect()
+    }
+
     pub(crate) fn from_capabilities<'a>(
         capabilities: impl Iterator<Item = git_transport::client::capabilities::Capability<'a>>,
     ) -> Result<Vec<InternalRef>, refs::Error> {
@@ -238,14 +298,15 @@ pub(crate) mod shared {
 
     pub(in crate::fetch::refs) fn parse_v2(line: &str) -> Result<Ref, refs::Error> {
         let trimmed = line.trim_end();
-        let mut tokens = trimmed.splitn(3, ' ');
+        let mut tokens = trimmed.split(' ');
         match (tokens.next(), tokens.next()) {
             (Some(hex_hash), Some(path)) => {
                 let id = git_hash::ObjectId::from_hex(hex_hash.as_bytes())?;
                 if path.is_empty() {
                     return Err(refs::Error::MalformedV2RefLine(trimmed.to_owned()));
                 }
-                Ok(if let Some(attribute) = tokens.next() {
+                let (mut symref_target, mut peeled) = (None, None);
+                for attribute in tokens {
                     let mut tokens = attribute.splitn(2, ':');
                     match (tokens.next(), tokens.next()) {
                         (Some(attribute), Some(value)) => {
@@ -253,22 +314,8 @@ pub(crate) mod shared {
                                 return Err(refs::Error::MalformedV2RefLine(trimmed.to_owned()));
                             }
                             match attribute {
-                                "peeled" => Ref::Peeled {
-                                    path: path.into(),
-                                    object: git_hash::ObjectId::from_hex(value.as_bytes())?,
-                                    tag: id,
-                                },
-                                "symref-target" => match value {
-                                    "(null)" => Ref::Direct {
-                                        path: path.into(),
-                                        object: id,
-                                    },
-                                    name => Ref::Symbolic {
-                                        path: path.into(),
-                                        object: id,
-                                        target: name.into(),
-                                    },
-                                },
+                                "peeled" => peeled = Some(git_hash::ObjectId::from_hex(value.as_bytes())?),
+                                "symref-target" => symref_target = Some(value),
                                 _ => {
                                     return Err(refs::Error::UnkownAttribute(attribute.to_owned(), trimmed.to_owned()))
                                 }
@@ -276,11 +323,23 @@ pub(crate) mod shared {
                         }
                         _ => return Err(refs::Error::MalformedV2RefLine(trimmed.to_owned())),
                     }
-                } else {
-                    Ref::Direct {
+                }
+                // A symbolic ref pointing to a tag is peeled as well, but `Ref` can only represent it as symbolic ref.
+                Ok(match (symref_target, peeled) {
+                    (Some(name), _) if name != "(null)" => Ref::Symbolic {
+                        path: path.into(),
                         object: id,
+                        target: name.into(),
+                    },
+                    (_, Some(object)) => Ref::Peeled {
                         path: path.into(),
-                    }
+                        object,
+                        tag: id,
+                    },
+                    (_, None) => Ref::Direct {
+                        object: id,
+                        path: path.into(),
+                    },
                 })
             }
             _ => Err(refs::Error::MalformedV2RefLine(trimmed.to_owned())),
@@ -333,7 +392,7 @@ mod async_io {
             }
             refs::shared::parse_v1(number_of_possible_symbolic_refs_for_lookup, &mut out_refs, &line)?;
         }
-        Ok(out_refs.into_iter().map(Into::into).collect())
+        Ok(refs::shared::into_refs(out_refs))
     }
 }
 #[cfg(feature = "async-client")]
@@ -383,7 +442,7 @@ mod blocking_io {
             }
             refs::shared::parse_v1(number_of_possible_symbolic_refs_for_lookup, &mut out_refs, &line)?;
         }
-        Ok(out_refs.into_iter().map(Into::into).collect())
+        Ok(refs::shared::into_refs(out_refs))
     }
 }
 #[cfg(feature = "blocking-client")]
@@ -133,4 +133,44 @@ impl Response {
             }
         }
     }
+
+    /// Parse a V1 response from `reader` which is followed by a pack sent without side-band, as happens if neither `side-band`
+    /// nor `side-band-64k` were negotiated, after `done` was sent.
+    ///
+    /// As the pack isn't encoded as packet lines, only lines up to and including the final `NAK` or `ACK` are read,
+    /// leaving `reader` at the start of the pack. Use [`enable_raw_mode()`][client::ExtendedBufRead::enable_raw_mode()]
+    /// to read it.
+    pub async fn from_line_reader_without_sideband(
+        reader: &mut (impl client::ExtendedBufRead + Unpin),
+    ) -> Result<Response, response::Error> {
+        let mut line = String::new();
+        let mut acks = Vec::<Acknowledgement>::new();
+        let mut shallows = Vec::<ShallowUpdate>::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).await? == 0 {
+                if reader.stopped_at() == Some(client::MessageKind::Flush) {
+                    // the end of the shallow section
+                    reader.reset(Protocol::V1);
+                    continue;
+                }
+                return Err(response::Error::Io(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Could not read the final acknowledgement before the pack",
+                )));
+            }
+            if Response::parse_v1_ack_or_shallow_or_assume_pack(&mut acks, &mut shallows, &line) {
+                return Err(response::Error::UnknownLineType(line));
+            }
+            if Response::is_final_v1_acknowledgement(&line) {
+                break;
+            }
+        }
+        Ok(Response {
+            acks,
+            shallows,
+            wanted_refs: vec![],
+            has_pack: true,
+        })
+    }
 }
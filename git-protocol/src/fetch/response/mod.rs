@@ -118,7 +118,7 @@ impl Acknowledgement {
                     };
                     if let Some(description) = description {
                         match description {
-                            "common" => {}
+                            "common" | "continue" => {}
                             "ready" => return Ok(Acknowledgement::Ready),
                             _ => return Err(Error::UnknownLineType(line.to_owned())),
                         }
@@ -169,20 +169,15 @@ impl Response {
 
     /// Return an error if the given `features` don't contain the required ones for the given `version` of the protocol.
     ///
-    /// Even though technically any set of features supported by the server could work, we only implement the ones that
-    /// make it easy to maintain all versions with a single code base that aims to be and remain maintainable.
+    /// Note that [`fetch()`][crate::fetch()] doesn't need any of these anymore, but callers may still choose to only talk to
+    /// V1 servers supporting `multi_ack_detailed` and a side-band.
     pub fn check_required_features(version: Protocol, features: &[Feature]) -> Result<(), Error> {
         match version {
             Protocol::V1 => {
                 let has = |name: &str| features.iter().any(|f| f.0 == name);
-                // Let's focus on V2 standards, and simply not support old servers to keep our code simpler
                 if !has("multi_ack_detailed") {
                     return Err(Error::MissingServerCapability("multi_ack_detailed"));
                 }
-                // It's easy to NOT do sideband for us, but then again, everyone supports it.
-                // CORRECTION: If side-band is off, it would send the packfile without packet line encoding,
-                // which is nothing we ever want to deal with (despite it being more efficient). In V2, this
-                // is not even an option anymore, sidebands are always present.
                 if !has("side-band") && !has("side-band-64k") {
                     return Err(Error::MissingServerCapability("side-band OR side-band-64k"));
                 }
@@ -210,6 +205,13 @@ impl Response {
 
 #[cfg(any(feature = "async-client", feature = "blocking-client"))]
 impl Response {
+    /// Return true if `line` is the last acknowledgement the server sends in V1 after receiving `done`, which is either a `NAK`
+    /// or an `ACK` without status. A pack follows right after.
+    fn is_final_v1_acknowledgement(line: &str) -> bool {
+        let line = line.trim_end();
+        line == "NAK" || (line.starts_with("ACK ") && line.split(' ').count() == 2)
+    }
+
     /// with a friendly server, we just assume that a non-ack line is a pack line
     /// which is our hint to stop here.
     fn parse_v1_ack_or_shallow_or_assume_pack(
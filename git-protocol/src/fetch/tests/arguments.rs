@@ -127,7 +127,10 @@ fn id(hex: &str) -> git_hash::ObjectId {
 mod v1 {
     use bstr::ByteSlice;
 
-    use crate::fetch::tests::arguments::{arguments_v1, id, transport};
+    use crate::fetch::{
+        response::Acknowledgement,
+        tests::arguments::{arguments_v1, id, transport},
+    };
 
     #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
     async fn haves_and_wants_for_clone() {
@@ -208,12 +211,80 @@ mod v1 {
             .as_bstr()
         );
     }
+
+    #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+    async fn common_objects_are_repeated_only_if_the_connection_is_stateless() {
+        for is_stateful in &[false, true] {
+            let mut out = Vec::new();
+            let mut t = transport(&mut out, *is_stateful);
+            let mut arguments = arguments_v1(None);
+
+            arguments.want(id("7b333369de1221f9bfbbe03a3a13e9a09bc1c907"));
+            arguments.want(id("7b333369de1221f9bfbbe03a3a13e9a09bc1c907"));
+            arguments.have(id("0000000000000000000000000000000000000000"));
+            arguments.have(id("2222222222222222222222222222222222222222"));
+            arguments.send(&mut t, false).await.expect("sending to buffer to work");
+            arguments.record_acknowledgements(&[
+                Acknowledgement::Common(id("2222222222222222222222222222222222222222")),
+                Acknowledgement::Nak,
+            ]);
+            assert_eq!(arguments.common(), &[id("2222222222222222222222222222222222222222")]);
+
+            arguments.have(id("1111111111111111111111111111111111111111"));
+            arguments.have(id("2222222222222222222222222222222222222222"));
+            arguments.send(&mut t, true).await.expect("sending to buffer to work");
+            let expected: &[u8] = if *is_stateful {
+                b"0032want 7b333369de1221f9bfbbe03a3a13e9a09bc1c907
+00000032have 0000000000000000000000000000000000000000
+0032have 2222222222222222222222222222222222222222
+00000032have 1111111111111111111111111111111111111111
+0032have 2222222222222222222222222222222222222222
+0009done
+"
+            } else {
+                b"0032want 7b333369de1221f9bfbbe03a3a13e9a09bc1c907
+00000032have 0000000000000000000000000000000000000000
+0032have 2222222222222222222222222222222222222222
+00000032want 7b333369de1221f9bfbbe03a3a13e9a09bc1c907
+00000032have 2222222222222222222222222222222222222222
+0032have 1111111111111111111111111111111111111111
+0009done
+"
+            };
+            assert_eq!(
+                out.as_bstr(),
+                expected.as_bstr(),
+                "wants are sent once, and stateless connections repeat them along with the common objects first"
+            );
+        }
+    }
+
+    #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+    async fn window_grows_more_slowly_if_the_connection_is_stateful() {
+        for (is_stateful, expected) in &[(true, [16, 32, 64, 96, 128]), (false, [16, 32, 64, 128, 256])] {
+            let mut out = Vec::new();
+            let mut t = transport(&mut out, *is_stateful);
+            let mut arguments = arguments_v1(None);
+            arguments.want(id("7b333369de1221f9bfbbe03a3a13e9a09bc1c907"));
+
+            let mut window_sizes = Vec::new();
+            for _round in 0..expected.len() {
+                window_sizes.push(arguments.window_size());
+                arguments.have(id("0000000000000000000000000000000000000000"));
+                arguments.send(&mut t, false).await.expect("sending to buffer to work");
+            }
+            assert_eq!(&window_sizes, expected);
+        }
+    }
 }
 
 mod v2 {
     use bstr::ByteSlice;
 
-    use crate::fetch::tests::arguments::{arguments_v2, id, transport};
+    use crate::fetch::{
+        response::Acknowledgement,
+        tests::arguments::{arguments_v2, id, transport},
+    };
 
     #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
     async fn haves_and_wants_for_clone_stateful() {
@@ -289,6 +360,51 @@ mod v2 {
         }
     }
 
+    #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+    async fn common_objects_are_repeated_even_if_the_connection_is_stateful() {
+        for is_stateful in &[false, true] {
+            let mut out = Vec::new();
+            let mut t = transport(&mut out, *is_stateful);
+            let mut arguments = arguments_v2(None);
+
+            arguments.want(id("7b333369de1221f9bfbbe03a3a13e9a09bc1c907"));
+            arguments.have(id("2222222222222222222222222222222222222222"));
+            assert_eq!(arguments.window_size(), 16);
+            arguments.send(&mut t, false).await.expect("sending to buffer to work");
+            arguments
+                .record_acknowledgements(&[Acknowledgement::Common(id("2222222222222222222222222222222222222222"))]);
+
+            arguments.want(id("7b333369de1221f9bfbbe03a3a13e9a09bc1c907"));
+            arguments.have(id("1111111111111111111111111111111111111111"));
+            assert_eq!(arguments.window_size(), 32);
+            arguments.send(&mut t, false).await.expect("sending to buffer to work");
+            assert_eq!(
+                arguments.window_size(),
+                64,
+                "the window grows the same no matter the connection"
+            );
+            assert_eq!(
+                out.as_bstr(),
+                b"0012command=fetch
+0001000ethin-pack
+0010include-tag
+000eofs-delta
+0032want 7b333369de1221f9bfbbe03a3a13e9a09bc1c907
+0032have 2222222222222222222222222222222222222222
+00000012command=fetch
+0001000ethin-pack
+0010include-tag
+000eofs-delta
+0032want 7b333369de1221f9bfbbe03a3a13e9a09bc1c907
+0032have 2222222222222222222222222222222222222222
+0032have 1111111111111111111111111111111111111111
+0000"
+                    .as_bstr(),
+                "the server never keeps state in V2, and wants aren't duplicated even if they are added again"
+            );
+        }
+    }
+
     #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
     async fn ref_in_want() {
         let mut out = Vec::new();
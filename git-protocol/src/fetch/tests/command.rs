@@ -19,12 +19,35 @@ mod v1 {
                 assert_eq!(
                     Command::Fetch.default_features(
                         git_transport::Protocol::V1,
-                        &capabilities("multi_ack side-band side-band-64k multi_ack_detailed")
+                        &capabilities("multi_ack side-band side-band-64k multi_ack_detailed agent=git/2.28.0")
                     ),
                     &[("side-band-64k", None), ("multi_ack_detailed", None), fetch::agent()]
                 );
             }
 
+            #[test]
+            fn it_chooses_nothing_if_the_server_has_no_capabilities() {
+                let no_null_byte = git_transport::client::Capabilities::from_bytes(b"no null byte")
+                    .expect("always valid")
+                    .0;
+                for caps in &[capabilities(""), no_null_byte] {
+                    assert!(
+                        Command::Fetch
+                            .default_features(git_transport::Protocol::V1, caps)
+                            .is_empty(),
+                        "not even our agent as the server didn't agree to receiving it"
+                    );
+                }
+            }
+
+            #[test]
+            fn it_sends_no_agent_if_the_server_does_not_advertise_it() {
+                assert_eq!(
+                    Command::Fetch.default_features(git_transport::Protocol::V1, &capabilities("ofs-delta")),
+                    &[("ofs-delta", None)]
+                );
+            }
+
             #[test]
             fn it_chooses_all_supported_non_stacking_capabilities_and_leaves_no_progress() {
                 assert_eq!(
@@ -154,4 +177,34 @@ mod v2 {
             }
         }
     }
+
+    mod object_info {
+        use bstr::ByteSlice;
+
+        use crate::fetch::{self, tests::command::v2::capabilities, Command};
+
+        #[test]
+        fn the_size_is_requested_with_the_agent_as_only_feature() {
+            let features =
+                Command::ObjectInfo.default_features(git_transport::Protocol::V2, &capabilities("object-info", "size"));
+            assert_eq!(features, &[fetch::agent()]);
+            assert_eq!(
+                Command::ObjectInfo.initial_arguments(&features),
+                &[b"size".as_bstr().to_owned()]
+            );
+        }
+
+        #[test]
+        fn oids_can_be_passed() {
+            Command::ObjectInfo.validate_argument_prefixes_or_panic(
+                git_transport::Protocol::V2,
+                &capabilities("object-info", "size"),
+                &[
+                    b"size".as_bstr().into(),
+                    b"oid 5e4999f3bfe35be914c4bba7b0a362112cd4474c".as_bstr().into(),
+                ],
+                &[fetch::agent()],
+            );
+        }
+    }
 }
@@ -3,6 +3,10 @@ use git_transport::{client, client::Capabilities};
 
 use crate::fetch::{refs, refs::shared::InternalRef, Ref};
 
+fn name(name: &str) -> git_ref::FullName {
+    std::convert::TryInto::try_into(name).expect("valid reference name")
+}
+
 #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
 async fn extract_references_from_v2_refs() {
     let input = &mut "808e50d724f604f69ab93c6da2919c014667bedb HEAD symref-target:refs/heads/main
@@ -118,3 +122,106 @@ fn extract_symbolic_references_from_capabilities() -> Result<(), client::Error>
     );
     Ok(())
 }
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn extract_references_from_v2_refs_with_multiple_attributes() {
+    let input = &mut "7fe1b98b39423b71e14217aa299a03b7c937d656 HEAD symref-target:refs/tags/foo peeled:808e50d724f604f69ab93c6da2919c014667bedb
+7fe1b98b39423b71e14217aa299a03b7c937d656 refs/tags/foo peeled:808e50d724f604f69ab93c6da2919c014667bedb
+"
+    .as_bytes();
+
+    let out = refs::from_v2_refs(input).await.expect("no failure on valid input");
+
+    assert_eq!(
+        out,
+        vec![
+            Ref::Symbolic {
+                path: "HEAD".into(),
+                target: "refs/tags/foo".into(),
+                object: oid("7fe1b98b39423b71e14217aa299a03b7c937d656")
+            },
+            Ref::Peeled {
+                path: "refs/tags/foo".into(),
+                tag: oid("7fe1b98b39423b71e14217aa299a03b7c937d656"),
+                object: oid("808e50d724f604f69ab93c6da2919c014667bedb")
+            },
+        ]
+    )
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn v1_symbolic_references_whose_path_is_not_advertised_are_skipped() {
+    let input = &mut "73a6868963993a3328e7d8fe94e5a6ac5078a944 HEAD
+73a6868963993a3328e7d8fe94e5a6ac5078a944 refs/heads/main"
+        .as_bytes();
+    let out = refs::from_v1_refs_received_as_part_of_handshake_and_capabilities(
+        input,
+        Capabilities::from_bytes(
+            b"\0symref=HEAD:refs/heads/main symref=refs/remotes/origin/HEAD:refs/remotes/origin/main",
+        )
+        .expect("valid capabilities")
+        .0
+        .iter(),
+    )
+    .await
+    .expect("no failure from valid input");
+    assert_eq!(
+        out,
+        vec![
+            Ref::Symbolic {
+                path: "HEAD".into(),
+                target: "refs/heads/main".into(),
+                object: oid("73a6868963993a3328e7d8fe94e5a6ac5078a944")
+            },
+            Ref::Direct {
+                path: "refs/heads/main".into(),
+                object: oid("73a6868963993a3328e7d8fe94e5a6ac5078a944")
+            },
+        ]
+    )
+}
+
+#[test]
+fn symrefs_are_obtained_from_capabilities_and_refs() -> Result<(), client::Error> {
+    let caps = client::Capabilities::from_bytes(
+        b"\0symref=HEAD:refs/heads/main symref=refs/remotes/origin/HEAD:refs/remotes/origin/main symref=MISSING_NAMESPACE_TARGET:(null)",
+    )?
+    .0;
+    let refs = vec![
+        Ref::Symbolic {
+            path: "refs/heads/alias".into(),
+            target: "refs/heads/main".into(),
+            object: oid("73a6868963993a3328e7d8fe94e5a6ac5078a944"),
+        },
+        Ref::Direct {
+            path: "refs/heads/main".into(),
+            object: oid("73a6868963993a3328e7d8fe94e5a6ac5078a944"),
+        },
+    ];
+    let out = refs::symrefs(&caps, &refs).expect("valid symrefs");
+    assert_eq!(
+        out.into_iter().collect::<Vec<_>>(),
+        vec![
+            (name("HEAD"), name("refs/heads/main")),
+            (name("refs/heads/alias"), name("refs/heads/main")),
+            (name("refs/remotes/origin/HEAD"), name("refs/remotes/origin/main")),
+        ],
+        "symbolic refs without target are skipped"
+    );
+    Ok(())
+}
+
+#[test]
+fn symrefs_with_invalid_names_are_rejected() -> Result<(), client::Error> {
+    for caps in [
+        &b"\0symref=HEAD:refs/heads/../../config"[..],
+        b"\0symref=../HEAD:refs/heads/main",
+    ] {
+        let caps = client::Capabilities::from_bytes(caps)?.0;
+        assert!(matches!(
+            refs::symrefs(&caps, &[]),
+            Err(refs::Error::InvalidSymrefName(..))
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,40 @@
+//! Invoke protocol V2 commands which aren't known to this crate, like those added by server implementations, and receive their
+//! response structured into sections.
+use std::io;
+
+use bstr::BString;
+use git_transport::client;
+use quick_error::quick_error;
+
+quick_error! {
+    /// The error returned by [`invoke()`][crate::invoke()].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        Io(err: io::Error) {
+            display("Could not send the request or read the response")
+            from()
+            source(err)
+        }
+        Transport(err: client::Error) {
+            display("An error occurred on the transport layer while invoking a command")
+            from()
+            source(err)
+        }
+        Unsupported { command: String, actual_version: git_transport::Protocol } {
+            display("The server doesn't support the '{}' command with protocol {:?}", command, actual_version)
+        }
+    }
+}
+
+/// A part of the response to a command, separated from the following one by a delimiter packet.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Section {
+    /// The first line of the section, which names it in responses structured into sections like the one of `fetch`.
+    ///
+    /// Responses without section headers have their first line here instead.
+    pub name: BString,
+    /// All lines following the first one, without their trailing newline.
+    pub lines: Vec<BString>,
+}
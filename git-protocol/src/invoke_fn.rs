@@ -0,0 +1,112 @@
+use bstr::BString;
+#[cfg(feature = "async-client")]
+use futures_lite::AsyncBufReadExt;
+use git_transport::{
+    client,
+    client::{SetServiceResponse, TransportV2Ext},
+    Protocol, Service,
+};
+use maybe_async::maybe_async;
+#[cfg(feature = "blocking-client")]
+use std::io::BufRead;
+
+use crate::invoke::{Error, Section};
+
+/// Send the protocol V2 `command` along with the given `capabilities` and `arguments` to the server at the other end of `transport`
+/// and return all sections of its response.
+///
+/// This is the way to use commands which are advertised by a server but not known to this crate, and the way
+/// [`object_info()`][crate::object_info()] is implemented. Neither `capabilities` nor `arguments` are validated,
+/// and the `agent` capability is only sent if it's part of `capabilities`.
+/// If `handle_progress` is set, each line is expected to be encoded in side-bands, with `handle_progress(is_error, text)`
+/// receiving the messages of the progress and error bands.
+///
+/// If the server doesn't advertise `command` or doesn't support protocol V2, [`Error::Unsupported`] is returned.
+/// The connection is terminated in any case, and the function is `async` with the `async-client` feature.
+#[maybe_async]
+pub async fn invoke<'a, T>(
+    mut transport: T,
+    command: &str,
+    capabilities: impl IntoIterator<Item = (&'a str, Option<&'a str>)>,
+    arguments: impl IntoIterator<Item = BString>,
+    handle_progress: Option<client::HandleProgress>,
+) -> Result<Vec<Section>, Error>
+where
+    T: client::Transport,
+{
+    let (protocol_version, is_advertised) = {
+        let SetServiceResponse {
+            actual_protocol,
+            capabilities,
+            ..
+        } = transport.handshake(Service::UploadPack, &[]).await?;
+        (actual_protocol, capabilities.contains(command))
+    }; // this scope is needed, see https://github.com/rust-lang/rust/issues/76149
+
+    if protocol_version != Protocol::V2 || !is_advertised {
+        indicate_end_of_interaction(transport).await?;
+        return Err(Error::Unsupported {
+            command: command.into(),
+            actual_version: protocol_version,
+        });
+    }
+
+    let capabilities: Vec<_> = capabilities.into_iter().collect();
+    let arguments: Vec<_> = arguments.into_iter().collect();
+    let sections = {
+        let mut response = transport
+            .invoke(command, capabilities.into_iter(), Some(arguments.into_iter()))
+            .await?;
+        if handle_progress.is_some() {
+            response.set_progress_handler(handle_progress);
+        }
+        read_sections(&mut response).await?
+    };
+    indicate_end_of_interaction(transport).await?;
+    Ok(sections)
+}
+
+#[maybe_async]
+async fn read_sections(reader: &mut Box<dyn client::ExtendedBufRead + Unpin + '_>) -> Result<Vec<Section>, Error> {
+    // Stop at delimiters as well to know where sections end.
+    reader.reset(Protocol::V2);
+    let mut sections = Vec::new();
+    let mut section: Option<Section> = None;
+    loop {
+        let line = reader.fill_buf().await?;
+        if line.is_empty() {
+            sections.extend(section.take());
+            if reader.stopped_at() == Some(client::MessageKind::Delimiter) {
+                reader.reset(Protocol::V2);
+                continue;
+            }
+            break;
+        }
+        // Servers don't always terminate lines with a newline.
+        let text: BString = line.strip_suffix(b"\n").unwrap_or(line).into();
+        match section.as_mut() {
+            Some(section) => section.lines.push(text),
+            None => {
+                section = Some(Section {
+                    name: text,
+                    lines: Vec::new(),
+                })
+            }
+        }
+        let consumed = line.len();
+        reader.consume(consumed);
+    }
+    Ok(sections)
+}
+
+#[maybe_async]
+async fn indicate_end_of_interaction(mut transport: impl client::Transport) -> Result<(), Error> {
+    // An empty request marks the end of the interaction. Only relevant in stateful transports though.
+    if transport.connection_persists_across_multiple_requests() {
+        transport
+            .request(client::WriteMode::Binary, client::MessageKind::Flush)?
+            .into_read()
+            .await?;
+    }
+    Ok(())
+}
@@ -19,6 +19,22 @@ mod fetch_fn;
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
 pub use fetch_fn::{fetch, FetchConnection};
 
+///
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub mod object_info;
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+mod object_info_fn;
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub use object_info_fn::object_info;
+
+///
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub mod invoke;
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+mod invoke_fn;
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub use invoke_fn::invoke;
+
 mod remote_progress;
 pub use remote_progress::RemoteProgress;
 
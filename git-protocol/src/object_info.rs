@@ -0,0 +1,80 @@
+//! Query information about objects on the server without fetching them, using the protocol V2 `object-info` command.
+use std::io;
+
+use git_transport::client;
+use quick_error::quick_error;
+
+quick_error! {
+    /// The error returned by [`object_info()`][crate::object_info()].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        Io(err: io::Error) {
+            display("Could not send the request or read the response")
+            from()
+            source(err)
+        }
+        Transport(err: client::Error) {
+            display("An error occurred on the transport layer while querying object information")
+            from()
+            source(err)
+        }
+        Unsupported { actual_version: git_transport::Protocol } {
+            display("The server doesn't support the 'object-info' command with protocol {:?}", actual_version)
+        }
+        Id(err: git_hash::decode::Error) {
+            display("Failed to hex-decode object hash")
+            from()
+            source(err)
+        }
+        MalformedAttributes(line: String) {
+            display("'{}' could not be parsed. The first line of the response should list the requested attributes, 'size'.", line)
+        }
+        MalformedLine(line: String) {
+            display("'{}' could not be parsed. An object-info line should be '<hex-hash> [<size>]'.", line)
+        }
+    }
+}
+
+impl From<crate::invoke::Error> for Error {
+    fn from(err: crate::invoke::Error) -> Self {
+        match err {
+            crate::invoke::Error::Io(err) => Error::Io(err),
+            crate::invoke::Error::Transport(err) => Error::Transport(err),
+            crate::invoke::Error::Unsupported { actual_version, .. } => Error::Unsupported { actual_version },
+        }
+    }
+}
+
+/// An object id along with its size in bytes, or `None` if the server doesn't have the object.
+pub type Info = (git_hash::ObjectId, Option<u64>);
+
+pub(crate) mod shared {
+    use bstr::ByteSlice;
+
+    use crate::object_info::{Error, Info};
+
+    fn to_str(line: &[u8]) -> &str {
+        // Servers don't always terminate lines with a newline.
+        line.trim_end_with(|c| c == '\n').to_str().unwrap_or_default()
+    }
+
+    pub(crate) fn parse_attributes(line: &[u8]) -> Result<(), Error> {
+        if to_str(line) == "size" {
+            Ok(())
+        } else {
+            Err(Error::MalformedAttributes(line.to_str_lossy().into_owned()))
+        }
+    }
+
+    pub(crate) fn parse_line(line: &[u8]) -> Result<Info, Error> {
+        let malformed = || Error::MalformedLine(line.to_str_lossy().into_owned());
+        let (hex_hash, size) = to_str(line).split_once(' ').ok_or_else(malformed)?;
+        let id = git_hash::ObjectId::from_hex(hex_hash.as_bytes())?;
+        let size = match size {
+            "" => None,
+            size => Some(size.parse().map_err(|_| malformed())?),
+        };
+        Ok((id, size))
+    }
+}
@@ -0,0 +1,38 @@
+use bstr::BString;
+use maybe_async::maybe_async;
+
+use crate::{
+    fetch::Command,
+    object_info::{shared, Error, Info},
+};
+
+/// Query the size of the objects with the given `ids` from the server at the other end of `transport`, without fetching them,
+/// returning them in the order the server sent them.
+///
+/// This requires a server which supports protocol V2 and advertises the `object-info` capability, otherwise
+/// [`Error::Unsupported`] is returned and callers can resort to fetching the objects instead.
+/// The connection is terminated in any case, and the function is `async` with the `async-client` feature.
+#[maybe_async]
+pub async fn object_info<T>(transport: T, ids: impl IntoIterator<Item = git_hash::ObjectId>) -> Result<Vec<Info>, Error>
+where
+    T: git_transport::client::Transport,
+{
+    let command = Command::ObjectInfo;
+    // The agent is the only capability, and the server can't be asked for it before the handshake performed by `invoke()`.
+    let features = vec![crate::fetch::agent()];
+    let arguments: Vec<BString> = command
+        .initial_arguments(&features)
+        .into_iter()
+        .chain(ids.into_iter().map(|id| format!("oid {}", id).into()))
+        .collect();
+
+    let sections = crate::invoke(transport, command.as_str(), features, arguments, None).await?;
+    let mut out = Vec::new();
+    if let Some(section) = sections.first() {
+        shared::parse_attributes(&section.name)?;
+        for line in &section.lines {
+            out.push(shared::parse_line(line)?);
+        }
+    }
+    Ok(out)
+}
@@ -1,8 +1,8 @@
-use std::io;
+use std::{collections::BTreeMap, io};
 
 use bstr::{BString, ByteSlice};
-use git_protocol::fetch::{self, Action, Arguments, LsRefsAction, Ref, Response};
-use git_transport::client::Capabilities;
+use git_protocol::fetch::{self, response::Acknowledgement, Action, Arguments, LsRefsAction, Ref, Response};
+use git_transport::client::{self, Capabilities, Identity};
 
 use crate::fixture_bytes;
 
@@ -89,9 +89,57 @@ impl fetch::DelegateBlocking for CloneRefInWantDelegate {
     }
 }
 
+/// A delegate which negotiates by sending its `haves` in order, as many per round as the window allows.
+#[derive(Default)]
+pub struct NegotiatingDelegate {
+    /// The objects we have, in the order they should be sent.
+    haves: Vec<git_hash::ObjectId>,
+    /// The amount of `haves` sent so far.
+    haves_sent: usize,
+    /// The size of the window of each round.
+    window_sizes: Vec<usize>,
+    /// The acknowledgements received in response to each round, including the last one which is followed by a pack.
+    acks: Vec<Vec<Acknowledgement>>,
+    /// The common objects known to the arguments of each round.
+    common: Vec<Vec<git_hash::ObjectId>>,
+    pack_bytes: usize,
+}
+
+impl fetch::DelegateBlocking for NegotiatingDelegate {
+    fn negotiate(
+        &mut self,
+        refs: &[Ref],
+        arguments: &mut Arguments,
+        previous_response: Option<&Response>,
+    ) -> io::Result<Action> {
+        match previous_response {
+            None => {
+                for r in refs {
+                    arguments.want(r.unpack().1);
+                }
+            }
+            Some(response) => self.acks.push(response.acknowledgements().to_owned()),
+        }
+        self.window_sizes.push(arguments.window_size());
+        self.common.push(arguments.common().to_owned());
+
+        let window = &self.haves[self.haves_sent..];
+        let window = &window[..window.len().min(arguments.window_size())];
+        if window.is_empty() {
+            return Ok(Action::Cancel);
+        }
+        for id in window {
+            arguments.have(id);
+        }
+        self.haves_sent += window.len();
+        Ok(Action::Continue)
+    }
+}
+
 #[derive(Default)]
 pub struct LsRemoteDelegate {
     refs: Vec<fetch::Ref>,
+    symrefs: BTreeMap<git_ref::FullName, git_ref::FullName>,
     abort_with: Option<std::io::Error>,
 }
 
@@ -113,11 +161,12 @@ impl fetch::DelegateBlocking for LsRemoteDelegate {
     fn prepare_fetch(
         &mut self,
         _version: git_transport::Protocol,
-        _server: &Capabilities,
+        server: &Capabilities,
         _features: &mut Vec<(&str, Option<&str>)>,
         refs: &[fetch::Ref],
     ) -> io::Result<fetch::Action> {
         self.refs = refs.to_owned();
+        self.symrefs = fetch::refs::symrefs(server, refs).map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
         Ok(fetch::Action::Cancel)
     }
     fn negotiate(
@@ -130,6 +179,119 @@ impl fetch::DelegateBlocking for LsRemoteDelegate {
     }
 }
 
+/// A transport which denies access during the handshake until the `expected` identity is set, like a server responding with 401.
+pub struct RequiresIdentity<T> {
+    inner: T,
+    expected: Identity,
+    identity: Option<Identity>,
+}
+
+impl<T> RequiresIdentity<T> {
+    pub fn new(inner: T, expected: Identity) -> Self {
+        RequiresIdentity {
+            inner,
+            expected,
+            identity: None,
+        }
+    }
+
+    fn check_identity(&self) -> Result<(), client::Error> {
+        if self.identity.as_ref() == Some(&self.expected) {
+            Ok(())
+        } else {
+            Err(client::Error::Io {
+                err: io::Error::new(io::ErrorKind::PermissionDenied, "Received HTTP status 401"),
+            })
+        }
+    }
+}
+
+impl<T: client::TransportWithoutIO> client::TransportWithoutIO for RequiresIdentity<T> {
+    fn set_identity(&mut self, identity: Identity) -> Result<(), client::Error> {
+        self.identity = Some(identity);
+        Ok(())
+    }
+
+    fn request(
+        &mut self,
+        write_mode: client::WriteMode,
+        on_into_read: client::MessageKind,
+    ) -> Result<client::RequestWriter<'_>, client::Error> {
+        self.inner.request(write_mode, on_into_read)
+    }
+
+    fn to_url(&self) -> String {
+        "https://example.com/repo".into()
+    }
+
+    fn supported_protocol_versions(&self) -> &[git_transport::Protocol] {
+        self.inner.supported_protocol_versions()
+    }
+
+    fn connection_persists_across_multiple_requests(&self) -> bool {
+        self.inner.connection_persists_across_multiple_requests()
+    }
+}
+
+/// A transport which doesn't keep the connection between requests, like HTTP.
+pub struct Stateless<T>(pub T);
+
+impl<T: client::TransportWithoutIO> client::TransportWithoutIO for Stateless<T> {
+    fn request(
+        &mut self,
+        write_mode: client::WriteMode,
+        on_into_read: client::MessageKind,
+    ) -> Result<client::RequestWriter<'_>, client::Error> {
+        self.0.request(write_mode, on_into_read)
+    }
+
+    fn to_url(&self) -> String {
+        self.0.to_url()
+    }
+
+    fn supported_protocol_versions(&self) -> &[git_transport::Protocol] {
+        self.0.supported_protocol_versions()
+    }
+
+    fn connection_persists_across_multiple_requests(&self) -> bool {
+        false
+    }
+}
+
+/// A transport which counts how often the handshake is performed.
+pub struct CountHandshakes<T> {
+    pub inner: T,
+    pub handshakes: usize,
+}
+
+impl<T> CountHandshakes<T> {
+    pub fn new(inner: T) -> Self {
+        CountHandshakes { inner, handshakes: 0 }
+    }
+}
+
+impl<T: client::TransportWithoutIO> client::TransportWithoutIO for CountHandshakes<T> {
+    fn request(
+        &mut self,
+        write_mode: client::WriteMode,
+        on_into_read: client::MessageKind,
+    ) -> Result<client::RequestWriter<'_>, client::Error> {
+        self.inner.request(write_mode, on_into_read)
+    }
+
+    fn to_url(&self) -> String {
+        self.inner.to_url()
+    }
+
+    fn supported_protocol_versions(&self) -> &[git_transport::Protocol] {
+        self.inner.supported_protocol_versions()
+    }
+
+    fn connection_persists_across_multiple_requests(&self) -> bool {
+        self.inner.connection_persists_across_multiple_requests()
+    }
+}
+
 #[cfg(feature = "blocking-client")]
 mod blocking_io {
     use std::io;
@@ -140,7 +302,58 @@ mod blocking_io {
         fetch::{Ref, Response},
     };
 
-    use crate::fetch::{CloneDelegate, CloneRefInWantDelegate, LsRemoteDelegate};
+    use git_transport::{client, Service};
+
+    use crate::fetch::{
+        CloneDelegate, CloneRefInWantDelegate, CountHandshakes, LsRemoteDelegate, NegotiatingDelegate,
+        RequiresIdentity, Stateless,
+    };
+
+    impl<T: client::Transport> client::Transport for CountHandshakes<T> {
+        fn handshake<'a>(
+            &mut self,
+            service: Service,
+            extra_parameters: &'a [(&'a str, Option<&'a str>)],
+        ) -> Result<client::SetServiceResponse<'_>, client::Error> {
+            self.handshakes += 1;
+            self.inner.handshake(service, extra_parameters)
+        }
+    }
+
+    impl<T: client::Transport> client::Transport for RequiresIdentity<T> {
+        fn handshake<'a>(
+            &mut self,
+            service: Service,
+            extra_parameters: &'a [(&'a str, Option<&'a str>)],
+        ) -> Result<client::SetServiceResponse<'_>, client::Error> {
+            self.check_identity()?;
+            self.inner.handshake(service, extra_parameters)
+        }
+    }
+
+    impl<T: client::Transport> client::Transport for Stateless<T> {
+        fn handshake<'a>(
+            &mut self,
+            service: Service,
+            extra_parameters: &'a [(&'a str, Option<&'a str>)],
+        ) -> Result<client::SetServiceResponse<'_>, client::Error> {
+            self.0.handshake(service, extra_parameters)
+        }
+    }
+
+    impl fetch::Delegate for NegotiatingDelegate {
+        fn receive_pack(
+            &mut self,
+            mut input: impl io::BufRead,
+            _progress: impl Progress,
+            _refs: &[Ref],
+            response: &Response,
+        ) -> io::Result<()> {
+            self.acks.push(response.acknowledgements().to_owned());
+            self.pack_bytes = io::copy(&mut input, &mut io::sink())? as usize;
+            Ok(())
+        }
+    }
 
     impl fetch::Delegate for CloneDelegate {
         fn receive_pack(
@@ -199,7 +412,62 @@ mod async_io {
         fetch::{Ref, Response},
     };
 
-    use crate::fetch::{CloneDelegate, CloneRefInWantDelegate, LsRemoteDelegate};
+    use git_transport::{client, Service};
+
+    use crate::fetch::{
+        CloneDelegate, CloneRefInWantDelegate, CountHandshakes, LsRemoteDelegate, NegotiatingDelegate,
+        RequiresIdentity, Stateless,
+    };
+
+    #[async_trait(?Send)]
+    impl<T: client::Transport> client::Transport for CountHandshakes<T> {
+        async fn handshake<'a>(
+            &mut self,
+            service: Service,
+            extra_parameters: &'a [(&'a str, Option<&'a str>)],
+        ) -> Result<client::SetServiceResponse<'_>, client::Error> {
+            self.handshakes += 1;
+            self.inner.handshake(service, extra_parameters).await
+        }
+    }
+
+    #[async_trait(?Send)]
+    impl<T: client::Transport> client::Transport for RequiresIdentity<T> {
+        async fn handshake<'a>(
+            &mut self,
+            service: Service,
+            extra_parameters: &'a [(&'a str, Option<&'a str>)],
+        ) -> Result<client::SetServiceResponse<'_>, client::Error> {
+            self.check_identity()?;
+            self.inner.handshake(service, extra_parameters).await
+        }
+    }
+
+    #[async_trait(?Send)]
+    impl<T: client::Transport> client::Transport for Stateless<T> {
+        async fn handshake<'a>(
+            &mut self,
+            service: Service,
+            extra_parameters: &'a [(&'a str, Option<&'a str>)],
+        ) -> Result<client::SetServiceResponse<'_>, client::Error> {
+            self.0.handshake(service, extra_parameters).await
+        }
+    }
+
+    #[async_trait(?Send)]
+    impl fetch::Delegate for NegotiatingDelegate {
+        async fn receive_pack(
+            &mut self,
+            mut input: impl AsyncBufRead + Unpin + 'async_trait,
+            _progress: impl Progress,
+            _refs: &[Ref],
+            response: &Response,
+        ) -> io::Result<()> {
+            self.acks.push(response.acknowledgements().to_owned());
+            self.pack_bytes = futures_lite::io::copy(&mut input, &mut futures_lite::io::sink()).await? as usize;
+            Ok(())
+        }
+    }
 
     #[async_trait(?Send)]
     impl fetch::Delegate for CloneDelegate {
@@ -253,6 +521,10 @@ pub fn oid(hex_sha: &str) -> git_hash::ObjectId {
     git_hash::ObjectId::from_hex(hex_sha.as_bytes()).expect("valid input")
 }
 
+pub fn name(name: &str) -> git_ref::FullName {
+    std::convert::TryInto::try_into(name).expect("valid reference name")
+}
+
 #[cfg(feature = "async-client")]
 pub fn transport<W: futures_io::AsyncWrite + Unpin>(
     out: W,
@@ -106,6 +106,80 @@ mod v1 {
             Ok(())
         }
     }
+
+    mod from_line_reader_without_sideband {
+        #[cfg(feature = "blocking-client")]
+        use std::io::Read;
+
+        use bstr::ByteSlice;
+        #[cfg(feature = "async-client")]
+        use futures_lite::io::AsyncReadExt;
+        use git_protocol::fetch::{
+            self,
+            response::{Acknowledgement, ShallowUpdate},
+        };
+
+        use crate::fetch::{response::id, Cursor};
+
+        #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+        async fn reads_up_to_the_final_ack_and_leaves_the_raw_pack() -> crate::Result {
+            let input = b"0035shallow 808e50d724f604f69ab93c6da2919c014667bedb\n0000\
+                          003aACK 47ee0b7fe4f3a7d776c78794873e6467e1c47e59 continue\n\
+                          0031ACK 47ee0b7fe4f3a7d776c78794873e6467e1c47e59\n\
+                          PACK raw data\n0000";
+            let mut provider = git_packetline::StreamingPeekableIter::new(
+                Cursor::new(input.to_vec()),
+                &[git_packetline::PacketLineRef::Flush],
+            );
+            let mut reader = provider.as_read_without_sidebands();
+            let r = fetch::Response::from_line_reader_without_sideband(&mut reader).await?;
+            assert_eq!(
+                r.shallow_updates(),
+                &[ShallowUpdate::Shallow(id("808e50d724f604f69ab93c6da2919c014667bedb"))]
+            );
+            assert_eq!(
+                r.acknowledgements(),
+                &[Acknowledgement::Common(id("47ee0b7fe4f3a7d776c78794873e6467e1c47e59"))],
+                "acknowledgements of the same commit are only listed once"
+            );
+            assert!(r.has_pack());
+
+            reader.enable_raw_mode();
+            let mut buf = Vec::new();
+            reader.read_to_end(&mut buf).await?;
+            assert_eq!(buf.as_bstr(), b"PACK raw data\n0000".as_bstr());
+            Ok(())
+        }
+
+        #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+        async fn it_is_an_error_if_the_final_ack_is_missing() {
+            let mut provider = git_packetline::StreamingPeekableIter::new(
+                Cursor::new(b"003aACK 47ee0b7fe4f3a7d776c78794873e6467e1c47e59 continue\n".to_vec()),
+                &[git_packetline::PacketLineRef::Flush],
+            );
+            let mut reader = provider.as_read_without_sidebands();
+            let res = fetch::Response::from_line_reader_without_sideband(&mut reader).await;
+            assert!(res.is_err(), "input ends before the final acknowledgement");
+        }
+    }
+
+    #[test]
+    fn required_features_can_still_be_checked_even_though_fetch_does_not_need_them() {
+        use git_protocol::fetch::{self, response};
+        use git_transport::Protocol;
+        let err = fetch::Response::check_required_features(Protocol::V1, &[("multi_ack_detailed", None)])
+            .expect_err("no side-band");
+        assert!(matches!(
+            err,
+            response::Error::MissingServerCapability("side-band OR side-band-64k")
+        ));
+        assert!(fetch::Response::check_required_features(
+            Protocol::V1,
+            &[("multi_ack_detailed", None), ("side-band", None)]
+        )
+        .is_ok());
+        assert!(fetch::Response::check_required_features(Protocol::V2, &[]).is_ok());
+    }
 }
 mod v2 {
     mod from_line_reader {
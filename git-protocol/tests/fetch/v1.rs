@@ -1,9 +1,9 @@
 use bstr::ByteSlice;
 use git_features::progress;
-use git_protocol::{fetch, FetchConnection};
-use git_transport::Protocol;
+use git_protocol::{credentials, fetch, FetchConnection};
+use git_transport::{client::Identity, Protocol};
 
-use crate::fetch::{oid, transport, CloneDelegate, LsRemoteDelegate};
+use crate::fetch::{name, oid, transport, CloneDelegate, CountHandshakes, LsRemoteDelegate, RequiresIdentity};
 
 #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
 async fn clone() -> crate::Result {
@@ -26,6 +26,85 @@ async fn clone() -> crate::Result {
     Ok(())
 }
 
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn connection_allows_only_one_fetch() -> crate::Result {
+    let mut transport = CountHandshakes::new(transport(
+        Vec::new(),
+        "v1/clone.response",
+        Protocol::V1,
+        git_transport::client::git::ConnectMode::Daemon,
+    ));
+    let mut connection = fetch::Connection::handshake(
+        &mut transport,
+        &[],
+        git_protocol::credentials::helper,
+        &mut progress::Discard,
+    )
+    .await?;
+    let mut dlg = CloneDelegate::default();
+    let refs = connection.ls_refs(&mut dlg, &mut progress::Discard).await?;
+    assert_eq!(
+        refs.len(),
+        2,
+        "the references advertised during the handshake are returned"
+    );
+    assert_eq!(
+        connection.symrefs(&refs)?.into_iter().collect::<Vec<_>>(),
+        vec![(name("HEAD"), name("refs/heads/master"))],
+        "symbolic refs are available on the connection as well"
+    );
+
+    let fetched = connection.fetch(&mut dlg, &refs, progress::Discard).await?;
+    assert!(fetched);
+    assert_eq!(dlg.pack_bytes, 876);
+    let res = connection.fetch(&mut dlg, &refs, progress::Discard).await;
+    assert!(
+        matches!(
+            res,
+            Err(fetch::Error::ConnectionClosed {
+                actual_version: Protocol::V1
+            })
+        ),
+        "the server ends the interaction after sending a pack"
+    );
+    connection.close().await?;
+    drop(connection);
+    assert_eq!(transport.handshakes, 1);
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn clone_from_server_without_capabilities() -> crate::Result {
+    let out = Vec::new();
+    let mut dlg = CloneDelegate::default();
+    let mut transport = transport(
+        out,
+        "v1/clone-no-capabilities.response",
+        Protocol::V1,
+        git_transport::client::git::ConnectMode::Process,
+    );
+    git_protocol::fetch(
+        &mut transport,
+        &mut dlg,
+        git_protocol::credentials::helper,
+        progress::Discard,
+        FetchConnection::TerminateOnSuccessfulCompletion,
+    )
+    .await?;
+    assert_eq!(dlg.pack_bytes, 876, "the pack is read as is without side-band");
+    let out = transport.into_inner().1;
+    assert!(
+        out.ends_with(b"00000009done\n"),
+        "the wants are followed by a flush and 'done' as there is nothing to negotiate"
+    );
+    assert_eq!(
+        out.as_bstr(),
+        b"0032want 808e50d724f604f69ab93c6da2919c014667bedb\n00000009done\n".as_bstr(),
+        "no capabilities are requested as the server didn't advertise any, and HEAD and its target are wanted only once as they point to the same object"
+    );
+    Ok(())
+}
+
 #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
 async fn ls_remote() -> crate::Result {
     let out = Vec::new();
@@ -67,6 +146,35 @@ async fn ls_remote() -> crate::Result {
     Ok(())
 }
 
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn ls_remote_with_multiple_symrefs() -> crate::Result {
+    let mut delegate = LsRemoteDelegate::default();
+    git_protocol::fetch(
+        transport(
+            Vec::new(),
+            "v1/ls-remote-symrefs.response",
+            Protocol::V1,
+            git_transport::client::git::ConnectMode::Daemon,
+        ),
+        &mut delegate,
+        git_protocol::credentials::helper,
+        progress::Discard,
+        FetchConnection::AllowReuse,
+    )
+    .await?;
+
+    assert_eq!(
+        delegate.symrefs.into_iter().collect::<Vec<_>>(),
+        vec![
+            (name("HEAD"), name("refs/heads/main")),
+            (name("refs/heads/alias"), name("refs/heads/main")),
+            (name("refs/remotes/origin/HEAD"), name("refs/remotes/origin/main")),
+        ],
+        "all symrefs are taken from the capabilities, even if their path isn't advertised, unless their target is in another namespace"
+    );
+    Ok(())
+}
+
 #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
 async fn ls_remote_handshake_failure_due_to_downgrade() -> crate::Result {
     let out = Vec::new();
@@ -95,3 +203,113 @@ async fn ls_remote_handshake_failure_due_to_downgrade() -> crate::Result {
     );
     Ok(())
 }
+
+fn identity(username: &str) -> Identity {
+    Identity::Account {
+        username: username.into(),
+        password: "pass".into(),
+    }
+}
+
+/// Record all `actions` and answer the `Fill` action with `provided`, if set.
+fn authenticate<'a>(
+    actions: &'a mut Vec<String>,
+    provided: Option<Identity>,
+) -> impl FnMut(credentials::Action<'_>) -> credentials::Result + 'a {
+    move |action| {
+        actions.push(match &action {
+            credentials::Action::Fill(url) => format!("fill {}", url),
+            credentials::Action::Approve(previous) => format!("approve {}", previous.as_bstr()),
+            credentials::Action::Reject(previous) => format!("reject {}", previous.as_bstr()),
+        });
+        Ok(match action {
+            credentials::Action::Fill(_) => provided.clone().map(|identity| credentials::Outcome {
+                identity,
+                next: b"username=user".to_vec().into(),
+            }),
+            credentials::Action::Approve(_) | credentials::Action::Reject(_) => None,
+        })
+    }
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn ls_remote_retries_once_with_credentials_if_access_is_denied_and_approves_them() -> crate::Result {
+    let mut delegate = LsRemoteDelegate::default();
+    let mut actions = Vec::new();
+    git_protocol::fetch(
+        RequiresIdentity::new(
+            transport(
+                Vec::new(),
+                "v1/clone.response",
+                Protocol::V1,
+                git_transport::client::git::ConnectMode::Daemon,
+            ),
+            identity("user"),
+        ),
+        &mut delegate,
+        authenticate(&mut actions, Some(identity("user"))),
+        progress::Discard,
+        FetchConnection::AllowReuse,
+    )
+    .await?;
+
+    assert_eq!(delegate.refs.len(), 2, "the fetch succeeds with the second handshake");
+    assert_eq!(actions, ["fill https://example.com/repo", "approve username=user"]);
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn ls_remote_rejects_credentials_if_access_is_still_denied() -> crate::Result {
+    let mut actions = Vec::new();
+    let err = git_protocol::fetch(
+        RequiresIdentity::new(
+            transport(
+                Vec::new(),
+                "v1/clone.response",
+                Protocol::V1,
+                git_transport::client::git::ConnectMode::Daemon,
+            ),
+            identity("user"),
+        ),
+        LsRemoteDelegate::default(),
+        authenticate(&mut actions, Some(identity("wrong-user"))),
+        progress::Discard,
+        FetchConnection::AllowReuse,
+    )
+    .await
+    .expect_err("the server denies access with the wrong identity");
+
+    assert!(matches!(err, fetch::Error::AuthenticationRefused(_)), "{:?}", err);
+    assert_eq!(
+        actions,
+        ["fill https://example.com/repo", "reject username=user"],
+        "there is only one retry"
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn ls_remote_fails_if_access_is_denied_and_no_credentials_are_provided() -> crate::Result {
+    let mut actions = Vec::new();
+    let err = git_protocol::fetch(
+        RequiresIdentity::new(
+            transport(
+                Vec::new(),
+                "v1/clone.response",
+                Protocol::V1,
+                git_transport::client::git::ConnectMode::Daemon,
+            ),
+            identity("user"),
+        ),
+        LsRemoteDelegate::default(),
+        authenticate(&mut actions, None),
+        progress::Discard,
+        FetchConnection::AllowReuse,
+    )
+    .await
+    .expect_err("no identity means no access");
+
+    assert!(matches!(err, fetch::Error::CredentialsMissing), "{:?}", err);
+    assert_eq!(actions, ["fill https://example.com/repo"]);
+    Ok(())
+}
@@ -1,9 +1,9 @@
 use bstr::ByteSlice;
 use git_features::progress;
 use git_protocol::{fetch, FetchConnection};
-use git_transport::Protocol;
+use git_transport::{client, client::TransportWithoutIO, Protocol};
 
-use crate::fetch::{oid, transport, CloneDelegate, CloneRefInWantDelegate, LsRemoteDelegate};
+use crate::fetch::{name, oid, transport, CloneDelegate, CloneRefInWantDelegate, LsRemoteDelegate, Stateless};
 
 #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
 async fn clone_abort_prep() -> crate::Result {
@@ -102,6 +102,35 @@ async fn ls_remote() -> crate::Result {
     Ok(())
 }
 
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn ls_remote_with_multiple_symrefs() -> crate::Result {
+    let mut delegate = LsRemoteDelegate::default();
+    git_protocol::fetch(
+        transport(
+            Vec::new(),
+            "v2/ls-remote-symrefs.response",
+            Protocol::V2,
+            git_transport::client::git::ConnectMode::Daemon,
+        ),
+        &mut delegate,
+        git_protocol::credentials::helper,
+        progress::Discard,
+        FetchConnection::AllowReuse,
+    )
+    .await?;
+
+    assert_eq!(
+        delegate.symrefs.into_iter().collect::<Vec<_>>(),
+        vec![
+            (name("HEAD"), name("refs/heads/main")),
+            (name("refs/heads/alias"), name("refs/heads/main")),
+            (name("refs/tags/latest"), name("refs/tags/v1.0")),
+        ],
+        "all symref-target attributes are used, even if the ref is peeled as well, unless their target is in another namespace"
+    );
+    Ok(())
+}
+
 #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
 async fn ls_remote_abort_in_prep_ls_refs() -> crate::Result {
     let out = Vec::new();
@@ -191,3 +220,305 @@ async fn ref_in_want() -> crate::Result {
 
     Ok(())
 }
+
+mod negotiation {
+    use git_protocol::fetch::response::Acknowledgement;
+
+    use crate::fetch::{oid, NegotiatingDelegate};
+
+    /// The roots of the `main` and `other` branches as created by `make_repo_for_negotiation.sh`.
+    const A1: &str = "805b19d8aa85627e4e0a8ee3fab3ebb9d0319bce";
+    const B1: &str = "c69e7bee30bdd63acc70dc0f9c0729574e0007e0";
+    const A3: &str = "154dc078e33a26e15a0348ad3357c1906d81e5c4";
+    const B3: &str = "f9e12bf825e736a8fefeb9981196ee51b4a62f6c";
+
+    /// A delegate which has 57 objects the server doesn't know, and the roots of both branches of the server which have
+    /// nothing in common. `A1` is sent in the second round and `B1` in the third, after which the server is ready.
+    pub fn delegate() -> NegotiatingDelegate {
+        let client_only = |range: std::ops::Range<u32>| range.map(|i| oid(&format!("{:040x}", i)));
+        NegotiatingDelegate {
+            haves: client_only(1..48)
+                .chain(Some(oid(A1)))
+                .chain(client_only(48..58))
+                .chain(Some(oid(B1)))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn assert_rounds(delegate: &NegotiatingDelegate) {
+        assert_eq!(
+            delegate.window_sizes,
+            vec![16, 32, 64],
+            "the window doubles with each round"
+        );
+        assert_eq!(
+            delegate.acks,
+            vec![
+                vec![Acknowledgement::Nak],
+                vec![Acknowledgement::Common(oid(A1))],
+                vec![
+                    Acknowledgement::Common(oid(A1)),
+                    Acknowledgement::Common(oid(B1)),
+                    Acknowledgement::Ready
+                ]
+            ],
+            "the server acknowledges A1 again as we resend it"
+        );
+        assert_eq!(delegate.common, vec![vec![], vec![], vec![oid(A1)]]);
+        assert!(delegate.pack_bytes > 0, "the server sent a pack once it was ready");
+    }
+
+    fn pkt(line: &str) -> String {
+        format!("{:04x}{}\n", line.len() + 5, line)
+    }
+
+    /// The requests the client is expected to send, with all wants and the common `A1` repeated with each fetch request
+    /// as V2 servers never keep state, no matter if the connection is stateful or not.
+    pub fn expected_requests(agent: &str) -> String {
+        let mut out = "002fgit-upload-pack does/not/matter\0\0version=2\0".to_string();
+        out.push_str(&pkt("command=ls-refs"));
+        out.push_str(&pkt(agent));
+        out.push_str("0001");
+        out.push_str(&pkt("symrefs"));
+        out.push_str(&pkt("peel"));
+        out.push_str("0000");
+
+        let delegate = delegate();
+        let haves: Vec<_> = delegate.haves.iter().map(ToString::to_string).collect();
+        for (common, round) in &[(None, &haves[..16]), (None, &haves[16..48]), (Some(A1), &haves[48..])] {
+            out.push_str(&pkt("command=fetch"));
+            out.push_str(&pkt(agent));
+            out.push_str("0001");
+            for feature in &["thin-pack", "include-tag", "ofs-delta"] {
+                out.push_str(&pkt(feature));
+            }
+            for want in &[A3, B3] {
+                out.push_str(&pkt(&format!("want {}", want)));
+            }
+            for have in common.iter().copied().chain(round.iter().map(String::as_str)) {
+                out.push_str(&pkt(&format!("have {}", have)));
+            }
+            out.push_str("0000");
+        }
+        out
+    }
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn fetch_with_multiple_rounds_of_negotiation() -> crate::Result {
+    let mut delegate = negotiation::delegate();
+    let mut transport = transport(
+        Vec::new(),
+        "v2/fetch-negotiation.response",
+        Protocol::V2,
+        git_transport::client::git::ConnectMode::Daemon,
+    );
+    git_protocol::fetch(
+        &mut transport,
+        &mut delegate,
+        git_protocol::credentials::helper,
+        progress::Discard,
+        FetchConnection::TerminateOnSuccessfulCompletion,
+    )
+    .await?;
+
+    negotiation::assert_rounds(&delegate);
+    let agent = format!("agent={}", fetch::agent().1.expect("value set"));
+    assert_eq!(
+        transport.into_inner().1.as_bstr(),
+        format!("{}0000", negotiation::expected_requests(&agent))
+            .as_bytes()
+            .as_bstr(),
+        "the persistent connection is terminated with a flush"
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn fetch_with_multiple_rounds_of_negotiation_over_stateless_connection() -> crate::Result {
+    let mut delegate = negotiation::delegate();
+    let mut transport = Stateless(transport(
+        Vec::new(),
+        "v2/fetch-negotiation.response",
+        Protocol::V2,
+        git_transport::client::git::ConnectMode::Daemon,
+    ));
+    git_protocol::fetch(
+        &mut transport,
+        &mut delegate,
+        git_protocol::credentials::helper,
+        progress::Discard,
+        FetchConnection::TerminateOnSuccessfulCompletion,
+    )
+    .await?;
+
+    negotiation::assert_rounds(&delegate);
+    let agent = format!("agent={}", fetch::agent().1.expect("value set"));
+    assert_eq!(
+        transport.0.into_inner().1.as_bstr(),
+        negotiation::expected_requests(&agent).as_bytes().as_bstr(),
+        "each request stands on its own, and there is no connection to terminate"
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn fetch_fails_if_the_server_is_not_ready_within_the_maximum_of_negotiation_rounds() -> crate::Result {
+    let mut delegate = negotiation::delegate();
+    let mut transport = transport(
+        Vec::new(),
+        "v2/fetch-negotiation.response",
+        Protocol::V2,
+        git_transport::client::git::ConnectMode::Daemon,
+    );
+    transport.set_limits(client::Limits {
+        max_negotiation_rounds: Some(2),
+        ..Default::default()
+    })?;
+    let err = git_protocol::fetch(
+        &mut transport,
+        &mut delegate,
+        git_protocol::credentials::helper,
+        progress::Discard,
+        FetchConnection::TerminateOnSuccessfulCompletion,
+    )
+    .await
+    .expect_err("the server needs three rounds");
+    assert!(matches!(err, fetch::Error::NegotiationRoundsExceeded { limit: 2 }));
+    assert!(err.transport_limit_exceeded().is_none());
+    assert_eq!(delegate.window_sizes, vec![16, 32], "two rounds were negotiated");
+    assert_eq!(delegate.pack_bytes, 0);
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn fetch_fails_if_the_transport_limits_are_exceeded() -> crate::Result {
+    let mut delegate = negotiation::delegate();
+    let mut transport = transport(
+        Vec::new(),
+        "v2/fetch-negotiation.response",
+        Protocol::V2,
+        git_transport::client::git::ConnectMode::Daemon,
+    );
+    transport.set_limits(client::Limits {
+        max_advertisement_bytes: Some(200),
+        ..Default::default()
+    })?;
+    let err = git_protocol::fetch(
+        &mut transport,
+        &mut delegate,
+        git_protocol::credentials::helper,
+        progress::Discard,
+        FetchConnection::TerminateOnSuccessfulCompletion,
+    )
+    .await
+    .expect_err("the advertised refs and negotiation exceed the limit");
+    assert!(matches!(
+        err.transport_limit_exceeded(),
+        Some(client::limits::Error::BytesBeforePayload { limit: 200 })
+    ));
+    assert_eq!(delegate.pack_bytes, 0);
+    Ok(())
+}
+
+#[cfg(feature = "blocking-client")]
+#[test]
+fn fetch_with_multiple_rounds_of_negotiation_from_upload_pack_spawned_by_ext_transport() -> crate::Result {
+    let dir = git_testtools::scripted_fixture_repo_read_only("make_repo_for_negotiation.sh")?;
+    let transport = git_transport::client::ext::connect(
+        &format!(
+            "ext::git %s {}",
+            dir.join("repo.git")
+                .display()
+                .to_string()
+                .replace('%', "%%")
+                .replace(' ', "% ")
+        ),
+        Protocol::V2,
+    )?;
+    let mut delegate = negotiation::delegate();
+    git_protocol::fetch(
+        transport,
+        &mut delegate,
+        git_protocol::credentials::helper,
+        progress::Discard,
+        FetchConnection::TerminateOnSuccessfulCompletion,
+    )?;
+    negotiation::assert_rounds(&delegate);
+    Ok(())
+}
+
+#[cfg(feature = "blocking-client")]
+#[test]
+fn clone_from_upload_pack_spawned_by_ext_transport() -> crate::Result {
+    let dir = git_testtools::scripted_fixture_repo_read_only("make_repo_to_fetch_from.sh")?;
+    let transport = git_transport::client::ext::connect(
+        &format!(
+            "ext::git %s {}",
+            dir.join("repo.git")
+                .display()
+                .to_string()
+                .replace('%', "%%")
+                .replace(' ', "% ")
+        ),
+        Protocol::V2,
+    )?;
+    let mut dlg = CloneDelegate::default();
+    git_protocol::fetch(
+        transport,
+        &mut dlg,
+        git_protocol::credentials::helper,
+        progress::Discard,
+        FetchConnection::TerminateOnSuccessfulCompletion,
+    )?;
+    assert!(
+        dlg.pack_bytes > 0,
+        "a pack was received from the actual upload-pack process"
+    );
+    Ok(())
+}
+
+#[cfg(feature = "blocking-client")]
+#[test]
+fn clone_and_fetch_again_over_a_single_connection_to_upload_pack_spawned_by_ext_transport() -> crate::Result {
+    use crate::fetch::CountHandshakes;
+    let dir = git_testtools::scripted_fixture_repo_read_only("make_repo_to_fetch_from.sh")?;
+    let mut transport = CountHandshakes::new(git_transport::client::ext::connect(
+        &format!(
+            "ext::git %s {}",
+            dir.join("repo.git")
+                .display()
+                .to_string()
+                .replace('%', "%%")
+                .replace(' ', "% ")
+        ),
+        Protocol::V2,
+    )?);
+    let mut connection = fetch::Connection::handshake(
+        &mut transport,
+        &[],
+        git_protocol::credentials::helper,
+        &mut progress::Discard,
+    )?;
+    assert_eq!(connection.protocol_version(), Protocol::V2);
+    let mut dlg = CloneDelegate::default();
+    let refs = connection.ls_refs(&mut dlg, &mut progress::Discard)?;
+    assert!(!refs.is_empty(), "the server advertised its references");
+
+    assert!(connection.fetch(&mut dlg, &refs, progress::Discard)?);
+    let first_pack_bytes = dlg.pack_bytes;
+    assert!(first_pack_bytes > 0, "a pack was received");
+
+    let mut dlg = CloneDelegate::default();
+    assert!(connection.fetch(&mut dlg, &refs, progress::Discard)?);
+    assert_eq!(
+        dlg.pack_bytes, first_pack_bytes,
+        "the same pack is received again over the same connection"
+    );
+    connection.close()?;
+    drop(connection);
+
+    assert_eq!(transport.handshakes, 1, "the handshake was performed only once");
+    Ok(())
+}
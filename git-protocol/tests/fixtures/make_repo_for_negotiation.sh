@@ -0,0 +1,23 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q --bare repo.git
+git init -q
+git config commit.gpgsign false
+
+git checkout -q -b main
+for name in a1 a2 a3; do
+  echo $name > a
+  git add a && git commit -q -m $name
+done
+
+git checkout -q --orphan other
+git rm -q -r --cached .
+rm a
+for name in b1 b2 b3; do
+  echo $name > b
+  git add b && git commit -q -m $name
+done
+
+git push -q repo.git main other
+git -C repo.git symbolic-ref HEAD refs/heads/main
@@ -0,0 +1,11 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q --bare repo.git
+git init -q
+git checkout -q -b main
+echo content > file
+git add file
+git commit -q -m "first"
+git push -q repo.git main
+git -C repo.git symbolic-ref HEAD refs/heads/main
@@ -0,0 +1,104 @@
+use std::{cell::RefCell, rc::Rc};
+
+use bstr::{BString, ByteSlice};
+use git_protocol::invoke::{self, Section};
+use git_transport::Protocol;
+
+use crate::fetch::transport;
+
+fn section(name: &str, lines: &[&str]) -> Section {
+    Section {
+        name: name.into(),
+        lines: lines.iter().map(|l| (*l).into()).collect(),
+    }
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn unknown_commands_can_be_sent_with_capabilities_and_arguments() -> crate::Result {
+    let mut transport = transport(
+        Vec::new(),
+        "v2/x-inventory.response",
+        Protocol::V2,
+        git_transport::client::git::ConnectMode::Daemon,
+    );
+    let sections = git_protocol::invoke(
+        &mut transport,
+        "x-inventory",
+        Some(("x-mode", Some("ripe"))),
+        vec![BString::from("fruits"), "vegetables".into()],
+        None,
+    )
+    .await?;
+    assert_eq!(
+        sections,
+        vec![section("fruits", &["apple", "pear"]), section("vegetables", &["leek"])],
+        "sections are separated by delimiters, and lines lose their trailing newline if there is one"
+    );
+
+    assert_eq!(
+        transport.into_inner().1.as_bstr(),
+        b"002fgit-upload-pack does/not/matter\0\0version=2\00018command=x-inventory
+0010x-mode=ripe
+0001000bfruits
+000fvegetables
+00000000"
+            .as_bstr(),
+        "the command is sent as is, and the connection is terminated once the response was received"
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn responses_can_be_decoded_from_sidebands() -> crate::Result {
+    let mut transport = transport(
+        Vec::new(),
+        "v2/x-inventory-sideband.response",
+        Protocol::V2,
+        git_transport::client::git::ConnectMode::Daemon,
+    );
+    let messages = Rc::new(RefCell::new(Vec::<(bool, BString)>::new()));
+    let sections = git_protocol::invoke(
+        &mut transport,
+        "x-inventory",
+        None,
+        Some("vegetables".into()),
+        Some(Box::new({
+            let messages = Rc::clone(&messages);
+            move |is_err: bool, text: &[u8]| messages.borrow_mut().push((is_err, text.into()))
+        })),
+    )
+    .await?;
+    assert_eq!(sections, vec![section("vegetables", &["leek"])]);
+    assert_eq!(
+        messages.borrow().as_slice(),
+        &[(false, "counting vegetables".into()), (false, "done".into())],
+        "progress messages are passed to the handler"
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn commands_which_are_not_advertised_are_unsupported() -> crate::Result {
+    let mut transport = transport(
+        Vec::new(),
+        "v2/x-inventory.response",
+        Protocol::V2,
+        git_transport::client::git::ConnectMode::Daemon,
+    );
+    let err = git_protocol::invoke(&mut transport, "x-unknown", None, None, None)
+        .await
+        .expect_err("the command isn't advertised");
+    assert!(matches!(
+        err,
+        invoke::Error::Unsupported {
+            ref command,
+            actual_version: Protocol::V2
+        } if command == "x-unknown"
+    ));
+    assert_eq!(
+        transport.into_inner().1.as_bstr(),
+        b"002fgit-upload-pack does/not/matter\0\0version=2\00000".as_bstr(),
+        "no command is sent, and the connection is terminated"
+    );
+    Ok(())
+}
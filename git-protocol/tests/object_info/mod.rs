@@ -0,0 +1,89 @@
+use bstr::ByteSlice;
+use git_protocol::{fetch, object_info};
+use git_transport::Protocol;
+
+use crate::fetch::{oid, transport};
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn sizes_of_present_and_missing_objects() -> crate::Result {
+    let mut transport = transport(
+        Vec::new(),
+        "v2/object-info.response",
+        Protocol::V2,
+        git_transport::client::git::ConnectMode::Daemon,
+    );
+    let ids = vec![
+        oid("5e4999f3bfe35be914c4bba7b0a362112cd4474c"),
+        oid("732ded09522e596f73cd2858d06e9cdb64001b07"),
+        oid("0000000000000000000000000000000000000001"),
+    ];
+    let info = git_protocol::object_info(&mut transport, ids.clone()).await?;
+    assert_eq!(
+        info,
+        vec![(ids[0], Some(15)), (ids[1], Some(14)), (ids[2], None)],
+        "objects the server doesn't have have no size"
+    );
+
+    assert_eq!(
+        transport.into_inner().1.as_bstr(),
+        format!(
+            "002fgit-upload-pack does/not/matter\0\0version=2\00018command=object-info
+001bagent={}
+00010009size
+0031oid 5e4999f3bfe35be914c4bba7b0a362112cd4474c
+0031oid 732ded09522e596f73cd2858d06e9cdb64001b07
+0031oid 0000000000000000000000000000000000000001
+00000000",
+            fetch::agent().1.expect("value set")
+        )
+        .as_bytes()
+        .as_bstr(),
+        "the connection is terminated with a flush once the response was received"
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn servers_without_object_info_capability_are_unsupported() -> crate::Result {
+    let mut transport = transport(
+        Vec::new(),
+        "v2/clone.response",
+        Protocol::V2,
+        git_transport::client::git::ConnectMode::Daemon,
+    );
+    let err = git_protocol::object_info(&mut transport, Some(oid("808e50d724f604f69ab93c6da2919c014667bedb")))
+        .await
+        .expect_err("the capability isn't advertised");
+    assert!(matches!(
+        err,
+        object_info::Error::Unsupported {
+            actual_version: Protocol::V2
+        }
+    ));
+    assert_eq!(
+        transport.into_inner().1.as_bstr(),
+        b"002fgit-upload-pack does/not/matter\0\0version=2\00000".as_bstr(),
+        "no command is sent, and the connection is terminated"
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn protocol_v1_is_unsupported() -> crate::Result {
+    let mut transport = transport(
+        Vec::new(),
+        "v1/clone.response",
+        Protocol::V1,
+        git_transport::client::git::ConnectMode::Daemon,
+    );
+    let err = git_protocol::object_info(&mut transport, Some(oid("808e50d724f604f69ab93c6da2919c014667bedb")))
+        .await
+        .expect_err("object-info is a V2 command");
+    assert!(matches!(
+        err,
+        object_info::Error::Unsupported {
+            actual_version: Protocol::V1
+        }
+    ));
+    Ok(())
+}
@@ -14,8 +14,10 @@
 //!     * one reference maps to a file on disk
 //!   * **packed**
 //!     * references are stored in a single human-readable file, along with their targets if they are symbolic.
-//! * **ref-table**
+//! * **[ref-table][reftable::Store]**
 //!   * supersedes all of the above to allow handling hundreds of thousands of references.
+//!
+//! All stores can be used through the [`Backend`][backend::Backend] trait.
 #![forbid(unsafe_code)]
 #![deny(missing_docs, rust_2018_idioms)]
 use git_hash::{oid, ObjectId};
@@ -23,7 +25,7 @@ pub use git_object::bstr;
 use git_object::bstr::{BStr, BString};
 
 mod store;
-pub use store::{file, packed};
+pub use store::{backend, file, packed, reftable};
 
 mod fullname;
 ///
@@ -49,6 +51,7 @@ pub mod peel;
 /// Indicate that the given BString is a validate reference name or path that can be used as path on disk or written as target
 /// of a symbolic reference
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
 pub struct FullName(pub(crate) BString);
 
 /// A validated and potentially partial reference name - it can safely be used for common operations.
@@ -76,6 +79,7 @@ pub enum Kind {
 
 /// Denotes a ref target, equivalent to [`Kind`], but with mutable data.
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
 pub enum Target {
     /// A ref that points to an object id
     Peeled(ObjectId),
@@ -1,6 +1,6 @@
 use std::{
     borrow::Cow,
-    convert::TryInto,
+    convert::{TryFrom, TryInto},
     path::{Path, PathBuf},
 };
 
@@ -33,6 +33,24 @@ impl Namespace {
     }
 }
 
+impl TryFrom<&str> for Namespace {
+    type Error = git_validate::refname::Error;
+
+    /// Expand `namespace` like [`expand()`] does.
+    fn try_from(namespace: &str) -> Result<Self, Self::Error> {
+        expand(namespace)
+    }
+}
+
+impl TryFrom<&BStr> for Namespace {
+    type Error = git_validate::refname::Error;
+
+    /// Expand `namespace` like [`expand()`] does.
+    fn try_from(namespace: &BStr) -> Result<Self, Self::Error> {
+        expand(namespace)
+    }
+}
+
 /// Given a `namespace` 'foo we output 'refs/namespaces/foo', and given 'foo/bar' we output 'refs/namespaces/foo/refs/namespaces/bar'.
 ///
 /// For more information, consult the [git namespace documentation](https://git-scm.com/docs/gitnamespaces).
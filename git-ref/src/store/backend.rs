@@ -0,0 +1,53 @@
+//! A trait for the operations all reference stores support, to use references without knowing how they are stored.
+//!
+//! It's implemented by the [file store][crate::file::Store], which keeps references in loose files and the `packed-refs` file,
+//! and the [reftable store][crate::reftable::Store], which can read but not yet write references.
+use std::path::Path;
+
+use crate::{log, transaction::RefEdit, FullNameRef, PartialNameRef, Reference};
+
+/// The operations a storage backend for references supports.
+///
+/// All results reflect the state of the store at the time of the call. Results are collected as stores differ in what has to
+/// stay alive while iterating, like the `packed-refs` buffer of the file store.
+pub trait Backend {
+    /// The error returned when reading references and reflogs.
+    type Error: std::error::Error + 'static;
+    /// The transaction to change references with.
+    type Transaction: Transaction;
+
+    /// Find the reference with the `partial` name, following the lookup rules of git for [partial names][PartialNameRef],
+    /// or return `None` if it doesn't exist.
+    fn try_find_reference(&self, partial: PartialNameRef<'_>) -> Result<Option<Reference>, Self::Error>;
+
+    /// Return all references in the `refs/` namespace sorted by their name, excluding `HEAD`.
+    fn references(&self) -> Result<Vec<Reference>, Self::Error>;
+
+    /// Return all references whose name starts with `prefix`, like `refs/heads/`, sorted by their name.
+    fn references_prefixed(&self, prefix: &Path) -> Result<Vec<Reference>, Self::Error>;
+
+    /// Return the lines of the reflog of the reference `name` from the oldest to the most recent, or `None` if there is none.
+    fn reflog_lines(&self, name: FullNameRef<'_>) -> Result<Option<Vec<log::Line>>, Self::Error>;
+
+    /// Return a transaction to edit references and their reflogs, which can be appended to without changing the reference
+    /// with [`RefLog::Only`][crate::transaction::RefLog::Only].
+    fn to_transaction(&self) -> Self::Transaction;
+}
+
+/// A transaction to change references in two phases, which either succeeds or has no effect.
+pub trait Transaction: Sized {
+    /// The error returned by [`prepare()`][Transaction::prepare()].
+    type PrepareError: std::error::Error + 'static;
+    /// The error returned by [`commit()`][Transaction::commit()].
+    type CommitError: std::error::Error + 'static;
+
+    /// Validate `edits` and lock the references they change, failing according to `lock_fail_mode` if a lock is held elsewhere.
+    fn prepare(
+        self,
+        edits: impl IntoIterator<Item = RefEdit>,
+        lock_fail_mode: git_lock::acquire::Fail,
+    ) -> Result<Self, Self::PrepareError>;
+
+    /// Apply all prepared edits, writing reflog entries with `committer`, and return the edits as performed.
+    fn commit(self, committer: &git_actor::Signature) -> Result<Vec<RefEdit>, Self::CommitError>;
+}
@@ -0,0 +1,106 @@
+use std::path::Path;
+
+use crate::{backend, log::Line, store::file, transaction::RefEdit, FullNameRef, PartialNameRef, Reference};
+
+impl backend::Backend for file::Store {
+    type Error = Error;
+    type Transaction = file::Transaction<'static>;
+
+    fn try_find_reference(&self, partial: PartialNameRef<'_>) -> Result<Option<Reference>, Self::Error> {
+        let packed = self.packed_buffer()?;
+        Ok(self.try_find(partial, packed.as_ref())?)
+    }
+
+    fn references(&self) -> Result<Vec<Reference>, Self::Error> {
+        let packed = self.packed_buffer()?;
+        let references = self.iter(packed.as_ref())?.collect::<Result<_, _>>()?;
+        Ok(references)
+    }
+
+    fn references_prefixed(&self, prefix: &Path) -> Result<Vec<Reference>, Self::Error> {
+        let packed = self.packed_buffer()?;
+        let references = self.iter_prefixed(packed.as_ref(), prefix)?.collect::<Result<_, _>>()?;
+        Ok(references)
+    }
+
+    fn reflog_lines(&self, name: FullNameRef<'_>) -> Result<Option<Vec<Line>>, Self::Error> {
+        let mut buf = Vec::new();
+        match self.reflog_iter(name, &mut buf)? {
+            Some(lines) => Ok(Some(
+                lines
+                    .map(|line| line.map(|line| line.to_owned()))
+                    .collect::<Result<_, _>>()?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn to_transaction(&self) -> Self::Transaction {
+        self.clone().into_transaction()
+    }
+}
+
+impl backend::Transaction for file::Transaction<'static> {
+    type PrepareError = file::transaction::prepare::Error;
+    type CommitError = file::transaction::commit::Error;
+
+    fn prepare(
+        self,
+        edits: impl IntoIterator<Item = RefEdit>,
+        lock_fail_mode: git_lock::acquire::Fail,
+    ) -> Result<Self, Self::PrepareError> {
+        file::Transaction::prepare(self, edits, lock_fail_mode)
+    }
+
+    fn commit(self, committer: &git_actor::Signature) -> Result<Vec<RefEdit>, Self::CommitError> {
+        file::Transaction::commit(self, committer)
+    }
+}
+
+mod error {
+    use quick_error::quick_error;
+
+    use crate::store::{
+        file::{find, iter::loose_then_packed, log},
+        packed,
+    };
+
+    quick_error! {
+        /// The error returned when using the file store as [`Backend`][crate::backend::Backend].
+        #[derive(Debug)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            PackedOpen(err: packed::buffer::open::Error) {
+                display("The packed-refs file could not be opened")
+                from()
+                source(err)
+            }
+            Find(err: find::Error) {
+                display("A reference could not be found")
+                from()
+                source(err)
+            }
+            IterInit(err: std::io::Error) {
+                display("The iteration over references could not be started")
+                from()
+                source(err)
+            }
+            Iter(err: loose_then_packed::Error) {
+                display("A reference could not be read while iterating")
+                from()
+                source(err)
+            }
+            ReflogOpen(err: log::Error) {
+                display("The reflog could not be opened")
+                from()
+                source(err)
+            }
+            ReflogDecode(err: log::iter::decode::Error) {
+                display("A line of the reflog could not be decoded")
+                from()
+                source(err)
+            }
+        }
+    }
+}
+pub use error::Error;
@@ -13,7 +13,7 @@ use crate::{
         file::{loose, path_to_name},
         packed,
     },
-    FullName, PartialNameRef, Reference,
+    FullName, Namespace, PartialNameRef, Reference,
 };
 
 enum Transform {
@@ -32,9 +32,9 @@ impl file::Store {
     /// ### Note
     ///
     /// * The lookup algorithm follows the one in [the git documentation][git-lookup-docs].
-    /// * Namespaced names will only be found if they are fully qualified. They can, however, be found during iteration.
-    ///   This shortcoming can be fixed if there is demand by introducing `try_find_in_namespace(…)` or by letting `PartialNameRef` have
-    ///   a namespace reference, too.
+    /// * With a [namespace][file::Store::namespace] set, all names within `refs/` are looked up within the namespace and returned
+    ///   without it. `HEAD` and other names outside of `refs/` aren't namespaced, and names starting with `refs/namespaces/`
+    ///   are used as is.
     ///
     /// [git-lookup-docs]: https://github.com/git/git/blob/5d5b1473453400224ebb126bf3947e0a3276bdf5/Documentation/revisions.txt#L34-L46
     pub fn try_find<'a, Name, E>(
@@ -121,7 +121,7 @@ impl file::Store {
             None => {
                 if is_definitely_absolute {
                     if let Some(packed) = packed {
-                        let full_name = path_to_name(match &self.namespace {
+                        let full_name = path_to_name(match self.namespace_for(&relative_path) {
                             None => relative_path,
                             Some(namespace) => namespace.to_owned().into_namespaced_prefix(relative_path),
                         });
@@ -157,12 +157,21 @@ impl file::Store {
 impl file::Store {
     /// Implements the logic required to transform a fully qualified refname into a filesystem path
     pub(crate) fn reference_path(&self, name: &Path) -> PathBuf {
-        match &self.namespace {
-            None => self.base.join(name),
-            Some(namespace) => self.base.join(namespace.to_path()).join(name),
+        let base = self.base_dir_for(name);
+        match self.namespace_for(name) {
+            None => base.join(name),
+            Some(namespace) => base.join(namespace.to_path()).join(name),
         }
     }
 
+    /// Return our namespace if it applies to the fully qualified `name`, which is only the case for names within `refs/`
+    /// that don't explicitly point into a namespace already.
+    pub(crate) fn namespace_for(&self, name: &Path) -> Option<&Namespace> {
+        self.namespace
+            .as_ref()
+            .filter(|_| name.starts_with("refs") && !name.starts_with(Path::new("refs").join("namespaces")))
+    }
+
     /// Read the file contents with a verified full reference path and return it in the given vector if possible.
     pub(crate) fn ref_contents(&self, relative_path: &Path) -> std::io::Result<Option<Vec<u8>>> {
         let mut buf = Vec::new();
@@ -0,0 +1,341 @@
+//! An opt-in journal of committed reference updates, retaining the time at which references were created as well as tombstones
+//! for deleted ones, which is information that loose references and packed references don't keep.
+use std::{
+    convert::TryFrom,
+    io::{Read, Seek, SeekFrom, Write},
+    ops::RangeBounds,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use git_hash::ObjectId;
+use git_object::bstr::{BString, ByteSlice};
+use quick_error::quick_error;
+
+use crate::{
+    store::{file, file::transaction::observe},
+    FullName,
+};
+
+/// The name of the journal file within the [`base`][file::Store::base] directory of the store.
+pub const FILE_NAME: &str = "refs-journal";
+/// The suffix of the journal file once it was rotated.
+pub const ROTATED_SUFFIX: &str = ".1";
+
+/// Configure how the [`Journal`] is written.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub struct Options {
+    /// Once the journal file is at least this large, it's rotated before appending the next transaction, replacing the
+    /// previously rotated one.
+    pub rotate_after_bytes: u64,
+    /// If true, reject transactions whose records can't be written, which rolls them back.
+    ///
+    /// Otherwise failures are collected to be [retrieved][Journal::take_errors()] later and don't affect the transaction.
+    pub strict: bool,
+    /// How to deal with the journal being locked by a concurrent transaction.
+    pub lock_mode: git_lock::acquire::Fail,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            rotate_after_bytes: 1024 * 1024,
+            strict: false,
+            lock_mode: git_lock::acquire::Fail::AfterDurationWithBackoff(Duration::from_secs(1)),
+        }
+    }
+}
+
+/// A single update of a reference as recorded in the journal, formatted as line of
+/// `<seconds-since-epoch> <transaction> <previous-id> <new-id or -> <name>`.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+pub struct Record {
+    /// The time at which the transaction was prepared, in seconds since the unix epoch.
+    pub seconds_since_unix_epoch: u64,
+    /// The id of the transaction the update was part of, counting up from 1 with each transaction.
+    pub transaction: u64,
+    /// The full name of the reference.
+    pub name: FullName,
+    /// The object id the reference pointed to before, or the null id if it didn't exist or wasn't peeled.
+    pub previous: ObjectId,
+    /// The object id the reference points to now, the null id if it became a symbolic reference, or `None` if it was deleted.
+    pub new: Option<ObjectId>,
+}
+
+impl Record {
+    /// Parse a single `line` without trailing newline as written by the [`Journal`].
+    pub fn from_line(line: &[u8]) -> Option<Self> {
+        let mut tokens = line.splitn(5, |b| *b == b' ');
+        let mut number = || -> Option<u64> { tokens.next()?.to_str().ok()?.parse().ok() };
+        let seconds_since_unix_epoch = number()?;
+        let transaction = number()?;
+        let previous = ObjectId::from_hex(tokens.next()?).ok()?;
+        let new = match tokens.next()? {
+            b"-" => None,
+            hex => Some(ObjectId::from_hex(hex).ok()?),
+        };
+        let name = FullName::try_from(tokens.next()?.as_bstr()).ok()?;
+        Some(Record {
+            seconds_since_unix_epoch,
+            transaction,
+            name,
+            previous,
+            new,
+        })
+    }
+
+    fn write_to(&self, mut out: impl Write) -> std::io::Result<()> {
+        write!(
+            out,
+            "{} {} {} ",
+            self.seconds_since_unix_epoch, self.transaction, self.previous
+        )?;
+        match self.new {
+            Some(id) => write!(out, "{}", id)?,
+            None => out.write_all(b"-")?,
+        }
+        out.write_all(b" ")?;
+        out.write_all(self.name.as_bstr())?;
+        out.write_all(b"\n")
+    }
+}
+
+quick_error! {
+    /// The error returned when reading or writing the journal.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        Io { path: PathBuf, err: std::io::Error } {
+            display("The journal at '{}' could not be read or written", path.display())
+            source(err)
+        }
+        Lock(err: git_lock::acquire::Error) {
+            display("The journal could not be locked")
+            from()
+            source(err)
+        }
+        Parse { path: PathBuf, line_number: usize, line: BString } {
+            display("Line {} of the journal at '{}' could not be parsed: {:?}", line_number, path.display(), line)
+        }
+    }
+}
+
+struct Pending {
+    _lock: git_lock::Marker,
+    /// The end of the last complete line of the journal before the records of the pending transaction were appended.
+    end: u64,
+}
+
+/// An [`Observer`][observe::Observer] appending a [`Record`] for each update of every committed transaction it's attached to,
+/// as obtained with [`Store::journal()`][file::Store::journal()], or attached to all transactions of a store by setting its
+/// [`journal`][file::Store::journal] field.
+///
+/// Once the transaction is prepared, the journal is locked and the records are appended to it, before any reference is
+/// committed. Only the last line is read to learn the id of the previous transaction. The journal stays locked until the
+/// transaction is committed, or truncated to its previous length if the transaction is aborted.
+/// A partial last line left behind by an interrupted append is skipped when reading and overwritten by the next transaction.
+pub struct Journal {
+    path: PathBuf,
+    options: Options,
+    pending: Option<Pending>,
+    errors: Vec<Error>,
+}
+
+impl Journal {
+    /// Return all errors that occurred so far in non-strict mode or when truncating the journal of aborted transactions,
+    /// leaving none behind.
+    pub fn take_errors(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.errors)
+    }
+
+    fn rotated_path(&self) -> PathBuf {
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(ROTATED_SUFFIX);
+        rotated.into()
+    }
+
+    fn io_err(&self, err: std::io::Error) -> Error {
+        Error::Io {
+            path: self.path.clone(),
+            err,
+        }
+    }
+
+    fn append(&mut self, updates: &[observe::Update]) -> Result<Pending, Error> {
+        let lock = git_lock::Marker::acquire_to_hold_resource(&self.path, self.options.lock_mode, None)?;
+        let (mut end, mut previous_line) = last_line(&self.path).map_err(|err| self.io_err(err))?;
+        if end >= self.options.rotate_after_bytes {
+            std::fs::rename(&self.path, self.rotated_path()).map_err(|err| self.io_err(err))?;
+            end = 0;
+        }
+        if end == 0 {
+            // Keep counting transactions after rotating, even if the transaction that rotated was aborted.
+            previous_line = last_line(&self.rotated_path()).map_err(|err| self.io_err(err))?.1;
+        }
+        let transaction = previous_line
+            .as_deref()
+            .and_then(Record::from_line)
+            .map_or(1, |r| r.transaction + 1);
+        let seconds_since_unix_epoch = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+
+        let mut records = Vec::new();
+        for update in updates {
+            Record {
+                seconds_since_unix_epoch,
+                transaction,
+                name: update.name.clone(),
+                previous: update.previous,
+                new: if update.deleted { None } else { Some(update.new) },
+            }
+            .write_to(&mut records)
+            .expect("writing to memory never fails");
+        }
+
+        let pending = Pending { _lock: lock, end };
+        let mut journal = std::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(&self.path)
+            .map_err(|err| self.io_err(err))?;
+        if let Err(err) = journal
+            .set_len(end)
+            .and_then(|_| journal.seek(SeekFrom::Start(end)))
+            .and_then(|_| journal.write_all(&records))
+        {
+            // Remove what was written so far, the next transaction overwrites it otherwise.
+            journal.set_len(end).ok();
+            return Err(self.io_err(err));
+        }
+        Ok(pending)
+    }
+
+    fn truncate(&mut self, pending: Pending) -> Result<(), Error> {
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(&self.path)
+            .and_then(|journal| journal.set_len(pending.end))
+            .map_err(|err| self.io_err(err))
+    }
+}
+
+/// Return the end of the last complete line of the journal at `path` along with that line without its newline, reading only as
+/// much from the end of the file as needed, or `(0, None)` if there is no such line.
+fn last_line(path: &Path) -> std::io::Result<(u64, Option<Vec<u8>>)> {
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok((0, None)),
+        Err(err) => return Err(err),
+    };
+    let len = file.metadata()?.len();
+    let mut window = 4096;
+    loop {
+        let start = len.saturating_sub(window);
+        file.seek(SeekFrom::Start(start))?;
+        let mut buf = Vec::new();
+        (&file).take(len - start).read_to_end(&mut buf)?;
+        if let Some(end) = buf.rfind_byte(b'\n') {
+            let lines = &buf[..end];
+            match lines.rfind_byte(b'\n') {
+                Some(pos) => return Ok((start + end as u64 + 1, Some(lines[pos + 1..].to_owned()))),
+                None if start == 0 => return Ok((end as u64 + 1, Some(lines.to_owned()))),
+                None => {}
+            }
+        } else if start == 0 {
+            return Ok((0, None));
+        }
+        window *= 2;
+    }
+}
+
+impl observe::Observer for Journal {
+    fn prepared(
+        &mut self,
+        updates: &[observe::Update],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.pending = None;
+        if updates.is_empty() {
+            return Ok(());
+        }
+        match self.append(updates) {
+            Ok(pending) => self.pending = Some(pending),
+            Err(err) if self.options.strict => return Err(err.into()),
+            Err(err) => self.errors.push(err),
+        }
+        Ok(())
+    }
+
+    fn committed(&mut self, _updates: &[observe::Update]) {
+        self.pending = None;
+    }
+
+    fn aborted(&mut self, _updates: &[observe::Update]) {
+        if let Some(pending) = self.pending.take() {
+            if let Err(err) = self.truncate(pending) {
+                self.errors.push(err);
+            }
+        }
+    }
+}
+
+impl file::Store {
+    /// Return the path to the journal, which is written only by transactions [observed][file::Transaction::observer()] by a
+    /// [`Journal`].
+    pub fn journal_path(&self) -> PathBuf {
+        self.base.join(FILE_NAME)
+    }
+
+    /// Create a [`Journal`] configured with `options` to be [attached][file::Transaction::observer()] to transactions
+    /// whose updates should be recorded, which allows to [retrieve][Journal::take_errors()] the errors it collected.
+    pub fn journal(&self, options: Options) -> Journal {
+        Journal {
+            path: self.journal_path(),
+            options,
+            pending: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Read all records of the rotated and the current journal whose time in seconds since the unix epoch is within `time`,
+    /// from oldest to newest.
+    ///
+    /// Missing journal files are treated as empty.
+    pub fn journal_records(&self, time: impl RangeBounds<u64>) -> Result<Vec<Record>, Error> {
+        let path = self.journal_path();
+        let mut rotated = path.clone().into_os_string();
+        rotated.push(ROTATED_SUFFIX);
+
+        let mut out = Vec::new();
+        for path in [PathBuf::from(rotated), path].iter() {
+            read_records(path, &time, &mut out)?;
+        }
+        Ok(out)
+    }
+}
+
+fn read_records(path: &Path, time: &impl RangeBounds<u64>, out: &mut Vec<Record>) -> Result<(), Error> {
+    let mut buf = Vec::new();
+    match std::fs::File::open(path).and_then(|mut f| f.read_to_end(&mut buf)) {
+        Ok(_) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => {
+            return Err(Error::Io {
+                path: path.to_owned(),
+                err,
+            })
+        }
+    }
+    // A partial last line is left behind by an interrupted append and skipped, as the next transaction overwrites it.
+    let complete_lines = buf.rfind_byte(b'\n').map_or(0, |pos| pos + 1);
+    for (line_number, line) in buf[..complete_lines].lines().enumerate() {
+        let record = Record::from_line(line).ok_or_else(|| Error::Parse {
+            path: path.to_owned(),
+            line_number: line_number + 1,
+            line: line.into(),
+        })?;
+        if time.contains(&record.seconds_since_unix_epoch) {
+            out.push(record);
+        }
+    }
+    Ok(())
+}
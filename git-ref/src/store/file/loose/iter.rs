@@ -152,19 +152,20 @@ impl file::Store {
         if !refs.is_dir() {
             return Err(std::io::ErrorKind::NotFound.into());
         }
-        Ok(Loose::at_root(refs, self.base.clone()))
+        Ok(Loose::at_root(refs, self.common_dir_resolved().to_owned()))
     }
 
     /// Return an iterator over all loose references that start with the given `prefix`.
     ///
     /// Otherwise it's similar to [`loose_iter()`][file::Store::loose_iter()].
     pub fn loose_iter_prefixed(&self, prefix: impl AsRef<Path>) -> std::io::Result<Loose> {
-        let (root, remainder) = self.validate_prefix(&self.base, prefix.as_ref())?;
-        Ok(Loose::at_root_with_filename_prefix(root, self.base.clone(), remainder))
+        let base = self.common_dir_resolved();
+        let (root, remainder) = self.validate_prefix(base, prefix.as_ref())?;
+        Ok(Loose::at_root_with_filename_prefix(root, base.to_owned(), remainder))
     }
 
     pub(in crate::store::file) fn refs_dir(&self) -> PathBuf {
-        self.base.join("refs")
+        self.common_dir_resolved().join("refs")
     }
     pub(in crate::store::file) fn validate_prefix(
         &self,
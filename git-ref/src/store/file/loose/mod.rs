@@ -37,6 +37,26 @@ mod init {
                 base: git_dir.into(),
                 write_reflog,
                 namespace: None,
+                common_dir: None,
+                read_only: false,
+                journal: None,
+            }
+        }
+
+        /// Create a new instance for a linked worktree, whose private `git_dir` usually is `<common_dir>/worktrees/<name>`,
+        /// with all references but the ones private to the worktree, like `HEAD`, being stored in `common_dir`.
+        pub fn for_linked_worktree(
+            git_dir: impl Into<PathBuf>,
+            common_dir: impl Into<PathBuf>,
+            write_reflog: crate::file::WriteReflog,
+        ) -> Self {
+            file::Store {
+                base: git_dir.into(),
+                write_reflog,
+                namespace: None,
+                common_dir: Some(common_dir.into()),
+                read_only: false,
+                journal: None,
             }
         }
     }
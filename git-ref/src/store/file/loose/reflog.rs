@@ -178,30 +178,25 @@ pub mod create_or_update {
         }
 
         fn reflock_resource_full_name(&self, reflock: &git_lock::Marker) -> PathBuf {
-            reflock
-                .resource_path()
-                .strip_prefix(&self.base)
+            let path = reflock.resource_path();
+            path.strip_prefix(&self.base)
+                .or_else(|_| path.strip_prefix(self.common_dir_resolved()))
                 .expect("lock must be held within this store")
                 .to_owned()
         }
 
         fn reflock_resource_to_log_path(&self, reflock: &git_lock::Marker) -> PathBuf {
-            self.reflog_path_inner(
-                reflock
-                    .resource_path()
-                    .strip_prefix(&self.base)
-                    .expect("lock must be held within this store"),
-            )
+            self.reflog_path_inner(&self.reflock_resource_full_name(reflock))
         }
 
         /// Returns the base and a full path (including the base) to the reflog for a ref of the given `full_name`
         pub(in crate::store::file::loose::reflog) fn reflog_path_inner(&self, full_name: &Path) -> PathBuf {
-            self.reflog_root().join(full_name)
+            self.reflog_root(full_name).join(full_name)
         }
 
-        /// Returns the base paths for all reflogs
-        pub(in crate::store::file) fn reflog_root(&self) -> PathBuf {
-            self.base.join("logs")
+        /// Returns the base path of all reflogs stored alongside the reference with the given `full_name`
+        pub(in crate::store::file) fn reflog_root(&self, full_name: &Path) -> PathBuf {
+            self.base_dir_for(full_name).join("logs")
         }
     }
 
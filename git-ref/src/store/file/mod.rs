@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// The way a file store handles the reflog
 #[derive(Debug, PartialOrd, PartialEq, Ord, Eq, Hash, Clone, Copy)]
@@ -29,17 +29,65 @@ pub struct Store {
     pub write_reflog: WriteReflog,
     /// The namespace to use for edits and reads
     pub namespace: Option<Namespace>,
+    /// The directory containing references shared by all worktrees, or `None` if it is the same as `base`.
+    ///
+    /// It's only set for stores of linked worktrees, whose `base` is the private directory at `<common-dir>/worktrees/<name>`.
+    pub(crate) common_dir: Option<PathBuf>,
+    /// If true, all operations which would write to the store fail with a `ReadOnly` error instead, including the preparation
+    /// of [transactions][Store::transaction()].
+    pub read_only: bool,
+    /// If set, every [transaction][Store::transaction()] records its updates in the [`Journal`] configured with these options
+    /// once it is prepared, in addition to informing its other [observers][Transaction::observer()].
+    ///
+    /// Unless the journal is `strict`, failures to write it are ignored. Attach a journal [created][Store::journal()]
+    /// by hand to retrieve them instead.
+    pub journal: Option<journal::Options>,
 }
 
 /// A transaction on a file store
 pub struct Transaction<'s> {
-    store: &'s Store,
+    store: std::borrow::Cow<'s, Store>,
     packed_transaction: Option<crate::store::packed::Transaction>,
     updates: Option<Vec<transaction::Edit>>,
     packed_refs: transaction::PackedRefs,
+    observers: Vec<Box<dyn transaction::observe::Observer + Send + 's>>,
+}
+
+impl Store {
+    /// Return the directory containing references shared by all worktrees if this store is for a linked worktree,
+    /// or `None` if it's the same as [`base`][Store::base].
+    pub fn common_dir(&self) -> Option<&Path> {
+        self.common_dir.as_deref()
+    }
+
+    /// Return the directory containing references shared by all worktrees, which is [`base`][Store::base] unless this
+    /// store is for a linked worktree.
+    pub fn common_dir_resolved(&self) -> &Path {
+        self.common_dir.as_deref().unwrap_or(&self.base)
+    }
+
+    /// Return the directory holding the reference or reflog at the relative path `name`, which is [`base`][Store::base]
+    /// for references private to a worktree, like `HEAD` or those in `refs/worktree/`, and the
+    /// [common directory][Store::common_dir_resolved()] for all others.
+    pub(in crate::store::file) fn base_dir_for(&self, name: &Path) -> &Path {
+        if is_per_worktree(name) {
+            &self.base
+        } else {
+            self.common_dir_resolved()
+        }
+    }
 }
 
-pub(in crate::store::file) fn path_to_name(path: impl Into<PathBuf>) -> git_object::bstr::BString {
+/// Return true if the reference or reflog at the relative path `name` is private to a worktree, like `HEAD` or those in
+/// `refs/worktree/`.
+pub(in crate::store::file) fn is_per_worktree(name: &Path) -> bool {
+    !name.starts_with("refs")
+        || name.starts_with("refs/worktree")
+        || name.starts_with("refs/bisect")
+        || name.starts_with("refs/rewritten")
+}
+
+pub(in crate::store) fn path_to_name(path: impl Into<PathBuf>) -> git_object::bstr::BString {
     use os_str_bytes::OsStringBytes;
     let path = path.into().into_raw_vec();
     #[cfg(windows)]
@@ -79,6 +127,19 @@ pub mod transaction;
 ///
 pub mod packed;
 
+///
+pub mod pack_refs;
+
+///
+pub mod backend;
+
+///
+pub mod journal;
+pub use journal::Journal;
+
+///
+pub mod structure;
+
 mod raw_ext;
 pub use raw_ext::ReferenceExt;
 
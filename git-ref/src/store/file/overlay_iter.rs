@@ -6,7 +6,7 @@ use std::{
 };
 
 use crate::{
-    file::{loose, path_to_name},
+    file::{is_per_worktree, loose, path_to_name},
     store::{file, packed},
     FullName, Namespace, Reference,
 };
@@ -16,13 +16,54 @@ use crate::{
 ///
 /// All errors will be returned verbatim, while packed errors are depleted first if loose refs also error.
 pub struct LooseThenPacked<'p, 's> {
-    base: &'s Path,
     packed: Option<Peekable<packed::Iter<'p>>>,
-    loose: Peekable<loose::iter::SortedLoosePaths>,
+    loose: Peekable<LoosePaths>,
     buf: Vec<u8>,
     namespace: Option<&'s Namespace>,
 }
 
+/// The sorted paths of loose references in the common directory, merged with the ones private to a linked worktree
+/// which take the place of the ones of the main worktree.
+struct LoosePaths {
+    common: Peekable<loose::iter::SortedLoosePaths>,
+    worktree: Option<Peekable<loose::iter::SortedLoosePaths>>,
+}
+
+impl Iterator for LoosePaths {
+    type Item = std::io::Result<(PathBuf, FullName)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let worktree = match self.worktree.as_mut() {
+            Some(worktree) => worktree,
+            None => return self.common.next(),
+        };
+        while let Some(Ok((_, name))) = self.common.peek() {
+            if !is_per_worktree(&name.to_path()) {
+                break;
+            }
+            self.common.next();
+        }
+        while let Some(Ok((_, name))) = worktree.peek() {
+            if is_per_worktree(&name.to_path()) {
+                break;
+            }
+            worktree.next();
+        }
+        match (self.common.peek(), worktree.peek()) {
+            (None, None) => None,
+            (Some(_), None) | (Some(Err(_)), Some(_)) => self.common.next(),
+            (None, Some(_)) | (Some(Ok(_)), Some(Err(_))) => worktree.next(),
+            (Some(Ok(common)), Some(Ok(private))) => {
+                if common.1.as_bstr() < private.1.as_bstr() {
+                    self.common.next()
+                } else {
+                    worktree.next()
+                }
+            }
+        }
+    }
+}
+
 impl<'p, 's> LooseThenPacked<'p, 's> {
     fn strip_namespace(&self, mut r: Reference) -> Reference {
         if let Some(namespace) = &self.namespace {
@@ -58,11 +99,9 @@ impl<'p, 's> LooseThenPacked<'p, 's> {
                 f.read_to_end(&mut self.buf)
             })
             .map_err(Error::ReadFileContents)?;
+        let relative_path = name.to_path().into_owned();
         loose::Reference::try_from_path(name, &self.buf)
-            .map_err(|err| Error::ReferenceCreation {
-                err,
-                relative_path: refpath.strip_prefix(&self.base).expect("base contains path").into(),
-            })
+            .map_err(|err| Error::ReferenceCreation { err, relative_path })
             .map(Into::into)
             .map(|r| self.strip_namespace(r))
     }
@@ -115,11 +154,15 @@ impl file::Store {
     /// continues.
     ///
     /// Errors are returned similarly to what would happen when loose and packed refs where iterated by themeselves.
+    ///
+    /// For linked worktrees, the references private to the worktree, like those in `refs/worktree/`, are read from its
+    /// private directory instead of the common one.
     pub fn iter<'p, 's>(&'s self, packed: Option<&'p packed::Buffer>) -> std::io::Result<LooseThenPacked<'p, 's>> {
         match &self.namespace {
-            Some(namespace) => self.iter_prefixed_unvalidated(packed, namespace.to_path(), (None, None)),
+            Some(namespace) => {
+                self.iter_prefixed_unvalidated(packed, namespace.to_path(), (None, None), Some(namespace))
+            }
             None => Ok(LooseThenPacked {
-                base: &self.base,
                 packed: match packed {
                     Some(packed) => Some(
                         packed
@@ -129,8 +172,7 @@ impl file::Store {
                     ),
                     None => None,
                 },
-                loose: loose::iter::SortedLoosePaths::at_root_with_names(self.refs_dir(), self.base.to_owned(), None)
-                    .peekable(),
+                loose: self.loose_paths("refs".as_ref(), (self.refs_dir(), None))?.peekable(),
                 buf: Vec::new(),
                 namespace: None,
             }),
@@ -139,21 +181,22 @@ impl file::Store {
 
     /// As [`iter(…)`][file::Store::iter()], but filters by `prefix`, i.e. "refs/heads".
     ///
-    /// Please note that "refs/heads` or "refs\\heads" is equivalent to "refs/heads/"
+    /// Please note that "refs/heads` or "refs\\heads" is equivalent to "refs/heads/".
+    /// Prefixes starting with `refs/namespaces/` are not subject to our [namespace][file::Store::namespace].
     pub fn iter_prefixed<'p, 's>(
         &'s self,
         packed: Option<&'p packed::Buffer>,
         prefix: impl AsRef<Path>,
     ) -> std::io::Result<LooseThenPacked<'p, 's>> {
-        match &self.namespace {
+        match self.namespace_for(prefix.as_ref()) {
             None => {
-                let (root, remainder) = self.validate_prefix(&self.base, prefix.as_ref())?;
-                self.iter_prefixed_unvalidated(packed, prefix, (root.into(), remainder))
+                let (root, remainder) = self.validate_prefix(self.common_dir_resolved(), prefix.as_ref())?;
+                self.iter_prefixed_unvalidated(packed, prefix, (root.into(), remainder), None)
             }
             Some(namespace) => {
                 let prefix = namespace.to_owned().into_namespaced_prefix(prefix);
-                let (root, remainder) = self.validate_prefix(&self.base, &prefix)?;
-                self.iter_prefixed_unvalidated(packed, prefix, (root.into(), remainder))
+                let (root, remainder) = self.validate_prefix(self.common_dir_resolved(), &prefix)?;
+                self.iter_prefixed_unvalidated(packed, prefix, (root.into(), remainder), Some(namespace))
             }
         }
     }
@@ -163,10 +206,11 @@ impl file::Store {
         packed: Option<&'p packed::Buffer>,
         prefix: impl AsRef<Path>,
         loose_root_and_filename_prefix: (Option<PathBuf>, Option<OsString>),
+        namespace: Option<&'s Namespace>,
     ) -> std::io::Result<LooseThenPacked<'p, 's>> {
-        let packed_prefix = path_to_name(prefix.as_ref());
+        let prefix = prefix.as_ref();
+        let packed_prefix = path_to_name(prefix);
         Ok(LooseThenPacked {
-            base: &self.base,
             packed: match packed {
                 Some(packed) => Some(
                     packed
@@ -176,16 +220,51 @@ impl file::Store {
                 ),
                 None => None,
             },
-            loose: loose::iter::SortedLoosePaths::at_root_with_names(
-                loose_root_and_filename_prefix
-                    .0
-                    .unwrap_or_else(|| self.base.join(prefix)),
-                self.base.to_owned(),
-                loose_root_and_filename_prefix.1,
+            loose: self
+                .loose_paths(
+                    prefix,
+                    (
+                        loose_root_and_filename_prefix
+                            .0
+                            .unwrap_or_else(|| self.common_dir_resolved().join(prefix)),
+                        loose_root_and_filename_prefix.1,
+                    ),
+                )?
+                .peekable(),
+            buf: Vec::new(),
+            namespace,
+        })
+    }
+
+    /// Return the sorted paths of loose references in the common directory at `root` whose file names start with
+    /// `filename_prefix`, merged with the ones private to a linked worktree that start with `prefix`.
+    fn loose_paths(
+        &self,
+        prefix: &Path,
+        (root, filename_prefix): (PathBuf, Option<OsString>),
+    ) -> std::io::Result<LoosePaths> {
+        let worktree = match self.common_dir {
+            Some(_) => {
+                let (root, filename_prefix) = self.validate_prefix(&self.base, prefix)?;
+                if root.is_dir() && root.starts_with(self.base.join("refs")) {
+                    Some(
+                        loose::iter::SortedLoosePaths::at_root_with_names(root, self.base.clone(), filename_prefix)
+                            .peekable(),
+                    )
+                } else {
+                    None
+                }
+            }
+            None => None,
+        };
+        Ok(LoosePaths {
+            common: loose::iter::SortedLoosePaths::at_root_with_names(
+                root,
+                self.common_dir_resolved().to_owned(),
+                filename_prefix,
             )
             .peekable(),
-            buf: Vec::new(),
-            namespace: self.namespace.as_ref(),
+            worktree,
         })
     }
 }
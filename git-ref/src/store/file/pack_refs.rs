@@ -0,0 +1,142 @@
+use crate::{
+    file::{self, iter::loose},
+    store::file::transaction::{FindObjectFn, PackedRefs},
+    transaction::{Change, LogChange, PreviousValue, RefEdit},
+    FullName, Kind,
+};
+
+/// Determines which loose references are moved into `packed-refs` by [`file::Store::pack_refs()`].
+#[derive(Debug, PartialOrd, PartialEq, Ord, Eq, Hash, Clone, Copy)]
+pub enum Mode {
+    /// Pack all loose references under `refs/` which point to an object, like `git pack-refs --all`.
+    AllRefs,
+    /// Only pack tags and loose references which are already packed, like `git pack-refs` does by default.
+    ///
+    /// This keeps branches which are likely to change often as loose references.
+    TagsAndAlreadyPacked,
+}
+
+impl Default for Mode {
+    fn default() -> Self {
+        Mode::TagsAndAlreadyPacked
+    }
+}
+
+impl file::Store {
+    /// Move loose references selected by `mode` into the `packed-refs` file and return their names, using `find` to peel
+    /// annotated tags to the object they point to, and `lock_mode` to determine how to wait for locks.
+    ///
+    /// Symbolic references and references outside of `refs/`, like `HEAD`, always stay loose.
+    /// Loose references which can't be parsed are skipped.
+    ///
+    /// All affected loose references are locked while the new `packed-refs` file is written and moved into place,
+    /// and only then they are deleted. Thus, if the operation is interrupted, loose references may remain along with their
+    /// packed counterparts, which is a valid state as they have the same value.
+    pub fn pack_refs(
+        &self,
+        mode: Mode,
+        lock_mode: git_lock::acquire::Fail,
+        find: Box<FindObjectFn>,
+    ) -> Result<Vec<FullName>, Error> {
+        if self.read_only {
+            return Err(Error::ReadOnly);
+        }
+        let packed = self.packed_buffer()?;
+        let loose_refs = match self.loose_iter() {
+            Ok(iter) => iter,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        let mut edits = Vec::new();
+        for reference in loose_refs {
+            let reference = match reference {
+                Ok(reference) => reference,
+                Err(loose::Error::ReferenceCreation { .. }) => continue,
+                Err(err) => return Err(err.into()),
+            };
+            if reference.kind() != Kind::Peeled {
+                continue;
+            }
+            let is_selected = match mode {
+                Mode::AllRefs => true,
+                Mode::TagsAndAlreadyPacked => {
+                    reference.name.as_bstr().starts_with(b"refs/tags/")
+                        || packed
+                            .as_ref()
+                            .map_or(false, |packed| packed.find(reference.name.to_ref()).is_ok())
+                }
+            };
+            if is_selected {
+                edits.push(RefEdit {
+                    change: Change::Update {
+                        log: LogChange::default(),
+                        expected: PreviousValue::MustExistAndMatch(reference.target.clone()),
+                        new: reference.target,
+                    },
+                    name: reference.name,
+                    deref: false,
+                });
+            }
+        }
+        drop(packed);
+        if edits.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Reflogs aren't touched as the previous value always matches the new one.
+        let edits = self
+            .transaction()
+            .packed_refs(PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(
+                find,
+            ))
+            .prepare(edits, lock_mode)?
+            .commit(&git_actor::Signature::empty())?;
+        Ok(edits.into_iter().map(|edit| edit.name).collect())
+    }
+}
+
+mod error {
+    use quick_error::quick_error;
+
+    use crate::store::{
+        file::{iter::loose, transaction},
+        packed,
+    };
+
+    quick_error! {
+        /// The error returned by [`file::Store::pack_refs()`][crate::file::Store::pack_refs()].
+        #[derive(Debug)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            PackedOpen(err: packed::buffer::open::Error) {
+                display("The existing packed-refs file could not be opened")
+                from()
+                source(err)
+            }
+            Io(err: std::io::Error) {
+                display("Loose references could not be traversed")
+                from()
+                source(err)
+            }
+            LooseIter(err: loose::Error) {
+                display("A loose reference could not be read")
+                from()
+                source(err)
+            }
+            Prepare(err: transaction::prepare::Error) {
+                display("The loose references could not be locked, or the packed-refs file could not be prepared")
+                from()
+                source(err)
+            }
+            Commit(err: transaction::commit::Error) {
+                display("The packed-refs file could not be written, or the loose references could not be deleted")
+                from()
+                source(err)
+            }
+            ReadOnly {
+                display("The reference store is read-only")
+            }
+        }
+    }
+}
+pub use error::Error;
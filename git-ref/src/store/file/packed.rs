@@ -5,10 +5,16 @@ use crate::store::{file, packed};
 impl file::Store {
     /// Return a packed transaction ready to receive updates. Use this to create or update `packed-refs`.
     /// Note that if you already have a [`packed::Buffer`] then use its [`packed::Buffer::into_transaction()`] method instead.
-    pub(crate) fn packed_transaction(
+    ///
+    /// The `packed-refs.lock` file is acquired according to `lock_mode` and held until the transaction is committed or dropped,
+    /// preventing other writers from changing `packed-refs` in the mean time.
+    pub fn packed_transaction(
         &self,
         lock_mode: git_lock::acquire::Fail,
     ) -> Result<packed::Transaction, transaction::Error> {
+        if self.read_only {
+            return Err(transaction::Error::ReadOnly);
+        }
         let lock = git_lock::File::acquire_to_update_resource(self.packed_refs_path(), lock_mode, None)?;
         Ok(match self.packed_buffer()? {
             Some(packed) => packed::Transaction::new_from_pack_and_lock(packed, lock),
@@ -28,7 +34,7 @@ impl file::Store {
 
     /// Return the path at which packed-refs would usually be stored
     pub fn packed_refs_path(&self) -> PathBuf {
-        self.base.join("packed-refs")
+        self.common_dir_resolved().join("packed-refs")
     }
 }
 
@@ -53,6 +59,9 @@ pub mod transaction {
                 source(err)
                 from()
             }
+            ReadOnly {
+                display("The reference store is read-only")
+            }
         }
     }
 }
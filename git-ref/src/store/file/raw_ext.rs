@@ -35,7 +35,12 @@ pub trait ReferenceExt: Sealed {
     /// For details, see [Reference::log_exists()].
     fn log_exists(&self, store: &file::Store) -> bool;
 
-    /// For details, see [Reference::peel_to_id_in_place()].
+    /// Follow all symbolic targets this reference might point to and peel the underlying object to the end of the chain
+    /// using `find` to lookup objects, and return it, setting this reference to the last one in the chain.
+    ///
+    /// Like `git`, no more than 5 references are followed, and cycles are detected early. Symbolic references pointing to
+    /// references that don't exist cause a [`Follow`][peel::to_id::Error::Follow] error. If the last reference was read from
+    /// `packed` references along with its peeled id, no object is looked up.
     fn peel_to_id_in_place<E: std::error::Error + Send + Sync + 'static>(
         &mut self,
         store: &file::Store,
@@ -83,30 +88,34 @@ impl ReferenceExt for Reference {
         packed: Option<&packed::Buffer>,
         mut find: impl FnMut(git_hash::ObjectId, &mut Vec<u8>) -> Result<Option<(git_object::Kind, &[u8])>, E>,
     ) -> Result<ObjectId, peel::to_id::Error> {
+        if self.target.kind() == crate::Kind::Symbolic {
+            let mut seen = BTreeSet::new();
+            let cursor = &mut *self;
+            while cursor.target.kind() == crate::Kind::Symbolic {
+                let next = cursor
+                    .follow(store, packed)
+                    .expect("symbolic references can always be followed")?;
+                if seen.contains(&next.name) {
+                    return Err(peel::to_id::Error::Cycle(
+                        store.reference_path(cursor.name.to_path().as_ref()),
+                    ));
+                }
+                *cursor = next;
+                seen.insert(cursor.name.clone());
+                const MAX_REF_DEPTH: usize = 5;
+                if seen.len() == MAX_REF_DEPTH {
+                    return Err(peel::to_id::Error::DepthLimitExceeded {
+                        max_depth: MAX_REF_DEPTH,
+                    });
+                }
+            }
+        };
         match self.peeled {
             Some(peeled) => {
                 self.target = Target::Peeled(peeled.to_owned());
                 Ok(peeled)
             }
             None => {
-                if self.target.kind() == crate::Kind::Symbolic {
-                    let mut seen = BTreeSet::new();
-                    let cursor = &mut *self;
-                    while let Some(next) = cursor.follow(store, packed) {
-                        let next = next?;
-                        if seen.contains(&next.name) {
-                            return Err(peel::to_id::Error::Cycle(store.base.join(cursor.name.to_path())));
-                        }
-                        *cursor = next;
-                        seen.insert(cursor.name.clone());
-                        const MAX_REF_DEPTH: usize = 5;
-                        if seen.len() == MAX_REF_DEPTH {
-                            return Err(peel::to_id::Error::DepthLimitExceeded {
-                                max_depth: MAX_REF_DEPTH,
-                            });
-                        }
-                    }
-                };
                 let mut buf = Vec::new();
                 let mut oid = self.target.as_id().expect("peeled ref").to_owned();
                 let peeled_id = loop {
@@ -0,0 +1,126 @@
+use std::{io, path::PathBuf};
+
+use git_object::bstr::{BString, ByteSlice};
+use quick_error::quick_error;
+
+use crate::{file, store::file::loose, FullName, Target};
+
+/// The directories every reference store is expected to have, relative to its base.
+const DIRECTORIES: &[&str] = &["refs", "refs/heads", "refs/tags"];
+
+quick_error! {
+    /// The error returned by [`file::Store::verify_or_create_structure()`].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        Io(err: io::Error, path: PathBuf) {
+            display("Could not inspect or create '{}'", path.display())
+            source(err)
+        }
+        ReadOnly(path: PathBuf) {
+            display("Could not create '{}' as the reference store is read-only", path.display())
+        }
+    }
+}
+
+/// A problem with the layout of a reference store which is reported, but not repaired as doing so could lose information.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum Anomaly {
+    /// A non-directory was found at `path` where a directory was expected, like a `refs` file.
+    NotADirectory {
+        /// The path at which a directory was expected.
+        path: PathBuf,
+    },
+    /// A directory was found at `path` where a file was expected, like a `HEAD` directory.
+    NotAFile {
+        /// The path at which a file was expected.
+        path: PathBuf,
+    },
+    /// The `HEAD` file doesn't exist.
+    MissingHead,
+    /// The `HEAD` file couldn't be parsed or is symbolic without pointing into `refs/`.
+    InvalidHead {
+        /// The contents of the `HEAD` file.
+        content: BString,
+    },
+}
+
+/// The result of [`file::Store::verify_or_create_structure()`].
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outcome {
+    /// The directories that were missing and thus created, in order.
+    pub created: Vec<PathBuf>,
+    /// All problems that were found but not fixed.
+    pub anomalies: Vec<Anomaly>,
+}
+
+impl Outcome {
+    /// Returns true if no anomaly was found, making this a valid reference store.
+    pub fn is_valid(&self) -> bool {
+        self.anomalies.is_empty()
+    }
+}
+
+impl file::Store {
+    /// Check that the directories of a reference store exist and create them if they are missing, and validate
+    /// that `HEAD` exists and points to a valid object id or reference.
+    ///
+    /// Anomalies like a `refs` file where a directory is expected are reported in the returned [`Outcome`],
+    /// but never repaired.
+    /// Note that the base directory of the store must exist, and that missing directories are an error if the store is
+    /// [read-only][file::Store::read_only].
+    pub fn verify_or_create_structure(&self) -> Result<Outcome, Error> {
+        let mut out = Outcome::default();
+        for dir in DIRECTORIES {
+            let path = self.common_dir_resolved().join(dir);
+            if out
+                .anomalies
+                .iter()
+                .any(|a| matches!(a, Anomaly::NotADirectory { path: parent } if path.starts_with(parent)))
+            {
+                continue;
+            }
+            match std::fs::metadata(&path) {
+                Ok(md) if md.is_dir() => {}
+                Ok(_) => out.anomalies.push(Anomaly::NotADirectory { path }),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    if self.read_only {
+                        return Err(Error::ReadOnly(path));
+                    }
+                    std::fs::create_dir(&path).map_err(|err| Error::Io(err, path.clone()))?;
+                    out.created.push(path);
+                }
+                Err(err) => return Err(Error::Io(err, path)),
+            }
+        }
+
+        let packed_refs = self.packed_refs_path();
+        if packed_refs.is_dir() {
+            out.anomalies.push(Anomaly::NotAFile { path: packed_refs });
+        }
+
+        let head = self.base.join("HEAD");
+        match std::fs::read(&head) {
+            Ok(content) => {
+                let is_valid = match loose::Reference::try_from_path(FullName("HEAD".into()), &content) {
+                    Ok(reference) => match reference.target {
+                        Target::Peeled(_) => true,
+                        Target::Symbolic(name) => name.as_bstr().starts_with_str("refs/"),
+                    },
+                    Err(_) => false,
+                };
+                if !is_valid {
+                    out.anomalies.push(Anomaly::InvalidHead {
+                        content: content.into(),
+                    });
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => out.anomalies.push(Anomaly::MissingHead),
+            Err(_) if head.is_dir() => out.anomalies.push(Anomaly::NotAFile { path: head }),
+            Err(err) => return Err(Error::Io(err, head)),
+        }
+        Ok(out)
+    }
+}
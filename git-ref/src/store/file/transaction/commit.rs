@@ -1,5 +1,8 @@
 use crate::{
-    store::file::{transaction::PackedRefs, Transaction},
+    store::file::{
+        transaction::{observe, PackedRefs},
+        Transaction,
+    },
     transaction::{Change, LogChange, RefEdit, RefLog},
     Target,
 };
@@ -11,7 +14,7 @@ impl<'s> Transaction<'s> {
     /// `committer` is used in the reflog.
     ///
     /// On error the transaction may have been performed partially, depending on the nature of the error, and no attempt to roll back
-    /// partial changes is made.
+    /// partial changes is made. All observers are informed that the transaction was aborted.
     ///
     /// In this stage, we perform the following operations:
     ///
@@ -23,8 +26,9 @@ impl<'s> Transaction<'s> {
     ///   along with empty parent directories
     ///
     /// Note that transactions will be prepared automatically as needed.
-    pub fn commit(self, committer: &git_actor::Signature) -> Result<Vec<RefEdit>, Error> {
-        let mut updates = self.updates.expect("BUG: must call prepare before commit");
+    pub fn commit(mut self, committer: &git_actor::Signature) -> Result<Vec<RefEdit>, Error> {
+        // Updates stay in place until the end so that returning early informs the observers that the transaction was aborted.
+        let updates = self.updates.as_mut().expect("BUG: must call prepare before commit");
         let delete_loose_refs = matches!(
             self.packed_refs,
             PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(_)
@@ -43,14 +47,29 @@ impl<'s> Transaction<'s> {
                     };
                     if update_reflog {
                         match new {
-                            Target::Symbolic(_) => {} // no reflog for symref changes
+                            Target::Symbolic(_) => {
+                                // symbolic refs only get a reflog entry if they end up pointing to an object,
+                                // like HEAD after switching to an existing branch.
+                                if let Some(new_oid) = change.leaf_referent_new_oid {
+                                    self.store.reflog_create_or_append(
+                                        &lock,
+                                        change.leaf_referent_previous_oid,
+                                        &new_oid,
+                                        committer,
+                                        log.message.as_ref(),
+                                        log.force_create_reflog,
+                                    )?;
+                                }
+                            }
                             Target::Peeled(new_oid) => {
                                 let previous = match expected {
                                     PreviousValue::MustExistAndMatch(Target::Peeled(oid)) => Some(oid.to_owned()),
                                     _ => None,
                                 }
                                 .or(change.leaf_referent_previous_oid);
-                                let do_update = previous.as_ref().map_or(true, |previous| previous != new_oid);
+                                // A null id means nothing is written, like for edits which only check the previous value.
+                                let do_update = !new_oid.is_null()
+                                    && previous.as_ref().map_or(true, |previous| previous != new_oid);
                                 if do_update {
                                     self.store.reflog_create_or_append(
                                         &lock,
@@ -96,7 +115,6 @@ impl<'s> Transaction<'s> {
             }
         }
 
-        let reflog_root = self.store.reflog_root();
         for change in updates.iter_mut() {
             match &change.update.change {
                 Change::Update { .. } => {}
@@ -114,7 +132,7 @@ impl<'s> Transaction<'s> {
                     } else {
                         git_tempfile::remove_dir::empty_upward_until_boundary(
                             reflog_path.parent().expect("never without parent"),
-                            &reflog_root,
+                            &self.store.reflog_root(change.update.name.to_path().as_ref()),
                         )
                         .ok();
                     }
@@ -122,7 +140,7 @@ impl<'s> Transaction<'s> {
             }
         }
 
-        if let Some(t) = self.packed_transaction {
+        if let Some(t) = self.packed_transaction.take() {
             t.commit().map_err(Error::PackedTransactionCommit)?;
         }
 
@@ -148,6 +166,13 @@ impl<'s> Transaction<'s> {
                 drop(lock)
             }
         }
+        let updates = self.updates.take().expect("still present");
+        if !self.observers.is_empty() {
+            let observed = observe::updates(&updates);
+            for observer in &mut self.observers {
+                observer.committed(&observed);
+            }
+        }
         Ok(updates.into_iter().map(|edit| edit.update).collect())
     }
 }
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use git_hash::ObjectId;
 use git_object::bstr::BString;
 
@@ -9,11 +11,12 @@ use crate::{
 /// A function receiving an object id to resolve, returning its decompressed bytes.
 ///
 /// Resolution means to follow tag objects until the end of the chain.
-pub type FindObjectFn =
-    dyn FnMut(
+/// It must be `Send` as it's owned by [transactions][Transaction] which may be sent to other threads.
+pub type FindObjectFn = dyn FnMut(
         git_hash::ObjectId,
         &mut Vec<u8>,
-    ) -> Result<Option<git_object::Kind>, Box<dyn std::error::Error + Send + Sync + 'static>>;
+    ) -> Result<Option<git_object::Kind>, Box<dyn std::error::Error + Send + Sync + 'static>>
+    + Send;
 
 /// How to handle packed refs during a transaction
 pub enum PackedRefs {
@@ -42,6 +45,9 @@ pub(in crate::store::file) struct Edit {
     /// For symbolic refs, this is the previous OID to put into the reflog instead of our own previous value. It's the
     /// peeled value of the leaf referent.
     leaf_referent_previous_oid: Option<ObjectId>,
+    /// For updates setting a new symbolic target, this is the peeled value of the new leaf referent, if it exists, to put into
+    /// the reflog.
+    leaf_referent_new_oid: Option<ObjectId>,
 }
 
 impl Edit {
@@ -72,10 +78,23 @@ impl file::Store {
     /// The transaction inherits the parent namespace.
     pub fn transaction(&self) -> Transaction<'_> {
         Transaction {
-            store: self,
+            store: Cow::Borrowed(self),
             packed_transaction: None,
             updates: None,
             packed_refs: PackedRefs::default(),
+            observers: Vec::new(),
+        }
+    }
+
+    /// Like [`transaction()`][file::Store::transaction()], but the transaction owns this store, which allows to keep it
+    /// prepared independently of the store it was created from, or to send it to another thread.
+    pub fn into_transaction(self) -> Transaction<'static> {
+        Transaction {
+            store: Cow::Owned(self),
+            packed_transaction: None,
+            updates: None,
+            packed_refs: PackedRefs::default(),
+            observers: Vec::new(),
         }
     }
 }
@@ -86,6 +105,33 @@ impl<'s> Transaction<'s> {
         self.packed_refs = packed_refs;
         self
     }
+
+    /// Add the `observer` to be informed about the edits of this transaction once it is prepared, committed or aborted,
+    /// and to possibly reject it.
+    ///
+    /// Observers are informed in the order they were added, followed by the [journal][file::Store::journal] of the store
+    /// if one is configured.
+    pub fn observer(mut self, observer: Box<dyn observe::Observer + Send + 's>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if self.observers.is_empty() {
+            return;
+        }
+        if let Some(edits) = self.updates.take() {
+            let updates = observe::updates(&edits);
+            // Roll back by releasing all locks before informing the observers, like `git` does.
+            drop(edits);
+            self.packed_transaction.take();
+            for observer in &mut self.observers {
+                observer.aborted(&updates);
+            }
+        }
+    }
 }
 
 ///
@@ -93,3 +139,9 @@ pub mod prepare;
 
 ///
 pub mod commit;
+
+///
+pub mod observe;
+
+///
+pub mod validate;
@@ -0,0 +1,188 @@
+use git_hash::ObjectId;
+
+use crate::{
+    store::file::transaction::Edit,
+    transaction::{Change, LogChange, PreviousValue, RefLog},
+    FullName, Target,
+};
+
+/// The change of a single reference as seen by an [`Observer`], carrying the same information as the lines passed to the
+/// `reference-transaction` hook.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+pub struct Update {
+    /// The object id the reference pointed to before the transaction, or the null id if it didn't exist or wasn't peeled.
+    pub previous: ObjectId,
+    /// The object id the reference will point to, or the null id if it is deleted or becomes a symbolic reference.
+    pub new: ObjectId,
+    /// The full name of the reference.
+    pub name: FullName,
+    /// True if the reference is deleted, which can't be told apart from becoming a symbolic reference by `new` alone.
+    pub deleted: bool,
+}
+
+impl std::fmt::Display for Update {
+    /// Format this update like a line of input to the `reference-transaction` hook, without the trailing newline.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.previous, self.new, self.name.0)
+    }
+}
+
+/// An observer of the stages of a [`Transaction`][crate::file::Transaction], allowing to audit or reject edits without
+/// spawning processes.
+///
+/// Updates are passed in the order of the edits of the transaction, followed by the edits of referents of dereferenced
+/// symbolic references. Edits which only write a reflog, like those of the dereferenced symbolic references themselves,
+/// are not observed.
+pub trait Observer {
+    /// Called once all references are locked and their previous values are known, right before
+    /// [`prepare(…)`][crate::file::Transaction::prepare()] returns.
+    ///
+    /// Return an error to reject the transaction, which is then rolled back and [aborted][Observer::aborted()].
+    fn prepared(&mut self, updates: &[Update]) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>;
+    /// Called after all `updates` were [committed][crate::file::Transaction::commit()] successfully.
+    fn committed(&mut self, _updates: &[Update]) {}
+    /// Called if a prepared transaction is rolled back, either because it was rejected or because it was dropped
+    /// without being committed.
+    fn aborted(&mut self, _updates: &[Update]) {}
+}
+
+impl<T> Observer for &mut T
+where
+    T: Observer + ?Sized,
+{
+    fn prepared(&mut self, updates: &[Update]) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        (**self).prepared(updates)
+    }
+
+    fn committed(&mut self, updates: &[Update]) {
+        (**self).committed(updates)
+    }
+
+    fn aborted(&mut self, updates: &[Update]) {
+        (**self).aborted(updates)
+    }
+}
+
+pub(in crate::store::file) fn updates(edits: &[Edit]) -> Vec<Update> {
+    edits
+        .iter()
+        .filter_map(|edit| {
+            let (expected, new) = match &edit.update.change {
+                Change::Update {
+                    log: LogChange { mode: RefLog::Only, .. },
+                    ..
+                }
+                | Change::Delete { log: RefLog::Only, .. } => return None,
+                Change::Update { expected, new, .. } => (expected, Some(new)),
+                Change::Delete { expected, .. } => (expected, None),
+            };
+            let peeled = |target: Option<&Target>| match target {
+                Some(Target::Peeled(oid)) => *oid,
+                _ => ObjectId::null_sha1(),
+            };
+            Some(Update {
+                previous: peeled(match expected {
+                    PreviousValue::MustExistAndMatch(previous) => Some(previous),
+                    _ => None,
+                }),
+                deleted: new.is_none(),
+                new: peeled(new),
+                name: edit.update.name.clone(),
+            })
+        })
+        .collect()
+}
+
+///
+pub mod hook {
+    use std::{
+        io::Write,
+        path::PathBuf,
+        process::{Command, Stdio},
+    };
+
+    use quick_error::quick_error;
+
+    use super::{Observer, Update};
+
+    quick_error! {
+        /// The error returned by the [`Hook`] when rejecting a transaction.
+        #[derive(Debug)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            Spawn{ path: PathBuf, err: std::io::Error } {
+                display("The hook at '{}' could not be run", path.display())
+                source(err)
+            }
+            Rejected{ path: PathBuf, status: std::process::ExitStatus } {
+                display("The hook at '{}' rejected the transaction with {}", path.display(), status)
+            }
+        }
+    }
+
+    /// An [`Observer`] which runs the `reference-transaction` hook script at `path` for each stage, passing the stage name
+    /// as argument and the updates on standard input.
+    ///
+    /// A non-zero exit status of the script rejects a prepared transaction, but is ignored in the other stages,
+    /// just like `git` does.
+    #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+    pub struct Hook {
+        /// The path to the executable script, usually `<git-dir>/hooks/reference-transaction`.
+        pub path: PathBuf,
+    }
+
+    impl Hook {
+        /// Create a new instance to run the script at `path` in the current working directory.
+        pub fn new(path: impl Into<PathBuf>) -> Self {
+            Hook { path: path.into() }
+        }
+
+        fn run(&self, stage: &str, updates: &[Update]) -> Result<(), Error> {
+            let spawn_err = |err| Error::Spawn {
+                path: self.path.clone(),
+                err,
+            };
+            let mut child = Command::new(&self.path)
+                .arg(stage)
+                .stdin(Stdio::piped())
+                .spawn()
+                .map_err(spawn_err)?;
+            {
+                let mut stdin = child.stdin.take().expect("configured");
+                for update in updates {
+                    if let Err(err) = writeln!(stdin, "{}", update) {
+                        // Hooks don't have to read their input.
+                        if err.kind() == std::io::ErrorKind::BrokenPipe {
+                            break;
+                        }
+                        return Err(spawn_err(err));
+                    }
+                }
+            }
+            let status = child.wait().map_err(spawn_err)?;
+            if status.success() {
+                Ok(())
+            } else {
+                Err(Error::Rejected {
+                    path: self.path.clone(),
+                    status,
+                })
+            }
+        }
+    }
+
+    impl Observer for Hook {
+        fn prepared(&mut self, updates: &[Update]) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            self.run("prepared", updates).map_err(Into::into)
+        }
+
+        fn committed(&mut self, updates: &[Update]) {
+            self.run("committed", updates).ok();
+        }
+
+        fn aborted(&mut self, updates: &[Update]) {
+            self.run("aborted", updates).ok();
+        }
+    }
+}
+pub use hook::Hook;
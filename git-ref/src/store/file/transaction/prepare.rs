@@ -1,15 +1,17 @@
+use git_hash::ObjectId;
+
 use crate::{
     packed,
     store::{
         file,
         file::{
             loose,
-            transaction::{Edit, PackedRefs},
+            transaction::{observe, Edit, PackedRefs},
             Transaction,
         },
     },
     transaction::{Change, LogChange, RefEdit, RefEditsExt, RefLog},
-    Reference, Target,
+    FullName, Reference, Target,
 };
 
 impl<'s> Transaction<'s> {
@@ -55,7 +57,7 @@ impl<'s> Transaction<'s> {
                 let lock = git_lock::Marker::acquire_to_hold_resource(
                     store.reference_path(&relative_path),
                     lock_fail_mode,
-                    Some(store.base.to_owned()),
+                    Some(store.base_dir_for(&relative_path).to_owned()),
                 )
                 .map_err(|err| Error::LockAcquire {
                     err,
@@ -101,7 +103,7 @@ impl<'s> Transaction<'s> {
                 let mut lock = git_lock::File::acquire_to_update_resource(
                     store.reference_path(&relative_path),
                     lock_fail_mode,
-                    Some(store.base.to_owned()),
+                    Some(store.base_dir_for(&relative_path).to_owned()),
                 )
                 .map_err(|err| Error::LockAcquire {
                     err,
@@ -132,7 +134,13 @@ impl<'s> Transaction<'s> {
                         PreviousValue::MustExistAndMatch(previous) | PreviousValue::ExistingMustMatch(previous),
                         Some(existing),
                     ) => {
-                        if *previous != existing.target {
+                        let peeled_existing_matches = match (previous, &existing.target) {
+                            (Target::Peeled(previous), Target::Symbolic(referent)) => {
+                                peeled_id(store, packed, referent).as_ref() == Some(previous)
+                            }
+                            _ => false,
+                        };
+                        if *previous != existing.target && !peeled_existing_matches {
                             let actual = existing.target.clone();
                             let expected = previous.to_owned();
                             let full_name = change.name();
@@ -180,7 +188,10 @@ impl<'s> Transaction<'s> {
         lock_fail_mode: git_lock::acquire::Fail,
     ) -> Result<Self, Error> {
         assert!(self.updates.is_none(), "BUG: Must not call prepare(…) multiple times");
-        let store = self.store;
+        let store = &*self.store;
+        if store.read_only {
+            return Err(Error::ReadOnly);
+        }
         let mut updates: Vec<_> = edits
             .into_iter()
             .map(|update| Edit {
@@ -188,6 +199,7 @@ impl<'s> Transaction<'s> {
                 lock: None,
                 parent_index: None,
                 leaf_referent_previous_oid: None,
+                leaf_referent_new_oid: None,
             })
             .collect();
         updates
@@ -201,6 +213,7 @@ impl<'s> Transaction<'s> {
                     lock: None,
                     parent_index: Some(idx),
                     leaf_referent_previous_oid: None,
+                    leaf_referent_new_oid: None,
                 },
             )
             .map_err(Error::PreprocessingFailed)?;
@@ -261,6 +274,7 @@ impl<'s> Transaction<'s> {
                             file::packed::transaction::Error::TransactionLock(err) => {
                                 Error::PackedTransactionAcquire(err)
                             }
+                            file::packed::transaction::Error::ReadOnly => Error::ReadOnly,
                         })?
                         .into()
                 } else {
@@ -278,12 +292,13 @@ impl<'s> Transaction<'s> {
                     self.packed_transaction = Some(match &mut self.packed_refs {
                         PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(f)
                         | PackedRefs::DeletionsAndNonSymbolicUpdates(f) => {
-                            transaction.prepare(edits_for_packed_transaction, f)?
+                            transaction.prepare_inner(edits_for_packed_transaction, Some(f), false)?
                         }
-                        PackedRefs::DeletionsOnly => transaction
-                            .prepare(edits_for_packed_transaction, &mut |_, _| {
-                                unreachable!("BUG: deletions never trigger object lookups")
-                            })?,
+                        PackedRefs::DeletionsOnly => transaction.prepare_inner(
+                            edits_for_packed_transaction,
+                            Some(&mut |_, _| unreachable!("BUG: deletions never trigger object lookups")),
+                            false,
+                        )?,
                     });
                 }
             }
@@ -292,7 +307,7 @@ impl<'s> Transaction<'s> {
         for cid in 0..updates.len() {
             let change = &mut updates[cid];
             if let Err(err) = Self::lock_ref_and_apply_change(
-                self.store,
+                &self.store,
                 lock_fail_mode,
                 self.packed_transaction.as_ref().and_then(|t| t.buffer()),
                 change,
@@ -332,11 +347,91 @@ impl<'s> Transaction<'s> {
                 }
             }
         }
+
+        // Symbolic refs which are retargeted get reflog entries with the peeled ids of their previous and new referents,
+        // similar to HEAD when switching branches.
+        let packed = self.packed_transaction.as_ref().and_then(|t| t.buffer());
+        for cid in 0..updates.len() {
+            let (previous_referent, new_referent) = match &updates[cid].update.change {
+                Change::Update {
+                    log,
+                    expected,
+                    new: Target::Symbolic(new),
+                } if log.mode == RefLog::AndReference => (
+                    match expected {
+                        PreviousValue::MustExistAndMatch(Target::Symbolic(previous)) => Some(previous),
+                        _ => None,
+                    },
+                    new,
+                ),
+                _ => continue,
+            };
+            if previous_referent == Some(new_referent) {
+                continue;
+            }
+            let new_oid_in_transaction = updates.iter().find_map(|edit| match &edit.update.change {
+                Change::Update {
+                    new: Target::Peeled(oid),
+                    ..
+                } if edit.update.name == *new_referent => Some(*oid),
+                _ => None,
+            });
+            let new_oid = new_oid_in_transaction.or_else(|| peeled_id(store, packed, new_referent));
+            let previous_oid = match previous_referent {
+                Some(previous_referent) => peeled_id(store, packed, previous_referent),
+                None => match &updates[cid].update.change {
+                    Change::Update {
+                        expected: PreviousValue::MustExistAndMatch(Target::Peeled(oid)),
+                        ..
+                    } => Some(*oid),
+                    _ => None,
+                },
+            };
+            let change = &mut updates[cid];
+            change.leaf_referent_new_oid = new_oid;
+            change.leaf_referent_previous_oid = previous_oid;
+        }
+        if let Some(options) = store.journal {
+            let journal = store.journal(options);
+            self.observers.push(Box::new(journal));
+        }
+        let observed = if self.observers.is_empty() {
+            Vec::new()
+        } else {
+            observe::updates(&updates)
+        };
         self.updates = Some(updates);
+        let rejection = self
+            .observers
+            .iter_mut()
+            .enumerate()
+            .find_map(|(idx, observer)| observer.prepared(&observed).err().map(|err| (idx, err)));
+        if let Some((idx, err)) = rejection {
+            // A rejected transaction is aborted when dropped, which only concerns the observers that saw it prepared.
+            self.observers.truncate(idx + 1);
+            return Err(Error::Rejected(err));
+        }
         Ok(self)
     }
 }
 
+/// Follow the symbolic reference `name` to the object id it ultimately points to, if it exists.
+pub(in crate::store::file) fn peeled_id(
+    store: &file::Store,
+    packed: Option<&packed::Buffer>,
+    name: &FullName,
+) -> Option<ObjectId> {
+    const MAX_REF_DEPTH: usize = 5;
+    let mut name = name.clone();
+    for _ in 0..MAX_REF_DEPTH {
+        match store.try_find(name.to_partial(), packed).ok()??.target {
+            Target::Peeled(oid) => return Some(oid),
+            Target::Symbolic(referent) => name = referent,
+        }
+    }
+    None
+}
+
 mod error {
     use git_object::bstr::BString;
     use quick_error::quick_error;
@@ -395,11 +490,18 @@ mod error {
             ReferenceOutOfDate { full_name: BString, expected: Target, actual: Target } {
                 display("The reference '{}' should have content {}, actual content was {}", full_name, expected, actual)
             }
+            Rejected(err: Box<dyn std::error::Error + Send + Sync + 'static>) {
+                display("The transaction was rejected by its observer")
+                source(&**err)
+            }
             ReferenceDecode(err: file::loose::reference::decode::Error) {
                 display("Could not read reference")
                 from()
                 source(err)
             }
+            ReadOnly {
+                display("The reference store is read-only")
+            }
         }
     }
 }
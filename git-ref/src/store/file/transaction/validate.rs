@@ -0,0 +1,261 @@
+use std::{borrow::Borrow, collections::BTreeSet, convert::TryFrom};
+
+use git_object::bstr::{BStr, ByteSlice};
+
+use crate::{
+    packed,
+    store::{file, file::transaction::prepare::peeled_id},
+    transaction::{Change, PreviousValue, RefEdit, RefLog},
+    FullName, PartialNameRef, Reference, Target,
+};
+
+/// The reason an edit would fail if it was part of a transaction.
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+pub enum Reason {
+    /// The reference was supposed to exist, but didn't.
+    MustExist,
+    /// The reference was not supposed to exist, but it did with `actual` as value.
+    MustNotExist {
+        /// The current value of the reference.
+        actual: Target,
+    },
+    /// The current value of the reference isn't the `expected` one.
+    OutOfDate {
+        /// The value the edit expects the reference to have.
+        expected: Target,
+        /// The current value of the reference.
+        actual: Target,
+    },
+    /// The reference can't be created as the `other` reference exists or is created by another edit, and one of the names
+    /// is a directory containing the other, like `refs/heads/a` and `refs/heads/a/b`.
+    NameConflict {
+        /// The name of the reference in the way.
+        other: FullName,
+    },
+    /// A deletion required the reference not to exist, which can never succeed.
+    DeletionOfNonExistingReference,
+}
+
+impl std::fmt::Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::MustExist => f.write_str("the reference was supposed to exist, but didn't"),
+            Reason::MustNotExist { actual } => {
+                write!(f, "the reference was not supposed to exist, but has value {}", actual)
+            }
+            Reason::OutOfDate { expected, actual } => {
+                write!(
+                    f,
+                    "the reference should have value {}, but has value {}",
+                    expected, actual
+                )
+            }
+            Reason::NameConflict { other } => write!(f, "the name conflicts with reference '{}'", other.as_bstr()),
+            Reason::DeletionOfNonExistingReference => {
+                f.write_str("a deletion can't require the reference not to exist")
+            }
+        }
+    }
+}
+
+/// An edit which would fail, along with the reason.
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+pub struct Failure {
+    /// The index of the edit in the list of validated edits.
+    pub index: usize,
+    /// The name of the reference the edit applies to.
+    pub name: FullName,
+    /// The reason for the failure.
+    pub reason: Reason,
+}
+
+impl file::Store {
+    /// Check `edits` against the current state of references, including `packed` ones, without changing anything.
+    ///
+    /// Use [`RefEditsExt::validate()`][crate::transaction::RefEditsExt::validate()] to call this conveniently.
+    pub(crate) fn validate_edits<E: Borrow<RefEdit>>(
+        &self,
+        edits: &[E],
+        packed: Option<&packed::Buffer>,
+    ) -> Result<(), Error> {
+        let deleted: BTreeSet<&BStr> = edits
+            .iter()
+            .map(Borrow::borrow)
+            .filter(|edit| {
+                matches!(
+                    edit.change,
+                    Change::Delete {
+                        log: RefLog::AndReference,
+                        ..
+                    }
+                )
+            })
+            .map(|edit| edit.name.as_bstr())
+            .collect();
+        let created: BTreeSet<&BStr> = edits
+            .iter()
+            .map(Borrow::borrow)
+            .filter(|edit| matches!(edit.change, Change::Update { .. }))
+            .map(|edit| edit.name.as_bstr())
+            .collect();
+
+        let mut failures = Vec::new();
+        for (index, edit) in edits.iter().map(Borrow::borrow).enumerate() {
+            let existing = self.try_find(edit.name.to_partial(), packed)?.map(|r| r.target);
+            let reason = match &edit.change {
+                Change::Delete { expected, .. } => match (expected, &existing) {
+                    (PreviousValue::MustNotExist, _) => Some(Reason::DeletionOfNonExistingReference),
+                    (PreviousValue::ExistingMustMatch(_), None)
+                    | (PreviousValue::MustExist, Some(_))
+                    | (PreviousValue::Any, None | Some(_)) => None,
+                    (PreviousValue::MustExist | PreviousValue::MustExistAndMatch(_), None) => Some(Reason::MustExist),
+                    (
+                        PreviousValue::MustExistAndMatch(previous) | PreviousValue::ExistingMustMatch(previous),
+                        Some(actual),
+                    ) => (previous != actual).then(|| Reason::OutOfDate {
+                        expected: previous.clone(),
+                        actual: actual.clone(),
+                    }),
+                },
+                Change::Update { expected, new, .. } => match (expected, &existing) {
+                    (PreviousValue::Any | PreviousValue::MustExist, Some(_)) => None,
+                    (PreviousValue::MustExist | PreviousValue::MustExistAndMatch(_), None) => Some(Reason::MustExist),
+                    (PreviousValue::MustNotExist, Some(actual)) => {
+                        (actual != new).then(|| Reason::MustNotExist { actual: actual.clone() })
+                    }
+                    (
+                        PreviousValue::MustExistAndMatch(previous) | PreviousValue::ExistingMustMatch(previous),
+                        Some(actual),
+                    ) => {
+                        let peeled_actual_matches = match (previous, actual) {
+                            (Target::Peeled(previous), Target::Symbolic(referent)) => {
+                                peeled_id(self, packed, referent).as_ref() == Some(previous)
+                            }
+                            _ => false,
+                        };
+                        (previous != actual && !peeled_actual_matches).then(|| Reason::OutOfDate {
+                            expected: previous.clone(),
+                            actual: actual.clone(),
+                        })
+                    }
+                    (PreviousValue::Any | PreviousValue::MustNotExist | PreviousValue::ExistingMustMatch(_), None) => {
+                        self.conflicting_name(edit.name.as_bstr(), packed, &deleted, &created)?
+                            .map(|other| Reason::NameConflict { other })
+                    }
+                },
+            };
+            if let Some(reason) = reason {
+                failures.push(Failure {
+                    index,
+                    name: edit.name.clone(),
+                    reason,
+                });
+            }
+        }
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Invalid { failures })
+        }
+    }
+
+    /// Return the name of a reference that would prevent a reference called `name` from being created as one of them
+    /// would have to be a directory, ignoring references that are `deleted` and considering the ones that are `created`.
+    fn conflicting_name(
+        &self,
+        name: &BStr,
+        packed: Option<&packed::Buffer>,
+        deleted: &BTreeSet<&BStr>,
+        created: &BTreeSet<&BStr>,
+    ) -> Result<Option<FullName>, Error> {
+        for pos in name.iter().enumerate().filter(|(_, b)| **b == b'/').map(|(pos, _)| pos) {
+            let parent = name[..pos].as_bstr();
+            if deleted.contains(parent) {
+                continue;
+            }
+            if created.contains(parent) {
+                return Ok(Some(FullName(parent.to_owned())));
+            }
+            let parent_name = match PartialNameRef::try_from(parent) {
+                Ok(name) => name,
+                Err(_) => continue,
+            };
+            if let Some(existing) = self.try_find(parent_name, packed)? {
+                if existing.name.as_bstr() == parent {
+                    return Ok(Some(existing.name));
+                }
+            }
+        }
+
+        let mut prefix = name.to_owned();
+        prefix.push(b'/');
+        if let Some(child) = created.iter().find(|created| created.starts_with(&prefix)) {
+            return Ok(Some(FullName((*child).to_owned())));
+        }
+        let path = FullName(name.to_owned()).to_path().into_owned();
+        if self.reference_path(&path).is_dir() {
+            for child in self.iter_prefixed(packed, path)? {
+                let child = child?;
+                if child.name.as_bstr().starts_with(&prefix) && !deleted.contains(child.name.as_bstr()) {
+                    return Ok(Some(child.name));
+                }
+            }
+        } else if let Some(packed) = packed {
+            let mut packed_prefix = self
+                .namespace
+                .as_ref()
+                .map(|namespace| namespace.as_bstr().to_owned())
+                .unwrap_or_default();
+            packed_prefix.extend_from_slice(&prefix);
+            for child in packed.iter_prefixed(packed_prefix)? {
+                let mut child: Reference = child?.into();
+                if let Some(namespace) = &self.namespace {
+                    child.strip_namespace(namespace);
+                }
+                if !deleted.contains(child.name.as_bstr()) {
+                    return Ok(Some(child.name));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+mod error {
+    use quick_error::quick_error;
+
+    use super::Failure;
+    use crate::store::{file, packed};
+
+    quick_error! {
+        /// The error returned by [`RefEditsExt::validate()`][crate::transaction::RefEditsExt::validate()].
+        #[derive(Debug)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            Find(err: file::find::Error) {
+                display("A reference could not be read")
+                from()
+                source(err)
+            }
+            Io(err: std::io::Error) {
+                display("References could not be listed")
+                from()
+                source(err)
+            }
+            Iter(err: file::iter::loose_then_packed::Error) {
+                display("A reference could not be read while listing references")
+                from()
+                source(err)
+            }
+            PackedIter(err: packed::iter::Error) {
+                display("A packed reference could not be read while listing references")
+                from()
+                source(err)
+            }
+            Invalid { failures: Vec<Failure> } {
+                display("{} edit(s) would fail: {}", failures.len(), failures.iter().map(|f| format!("'{}': {}", f.name.as_bstr(), f.reason)).collect::<Vec<_>>().join(", "))
+            }
+        }
+    }
+}
+pub use error::Error;
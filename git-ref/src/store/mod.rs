@@ -1,5 +1,10 @@
+pub mod backend;
+
 ///
 pub mod file;
 
 ///
 pub mod packed;
+
+///
+pub mod reftable;
@@ -29,11 +29,20 @@ struct Edit {
     peeled: Option<ObjectId>,
 }
 
-/// A transaction for editing packed references
-pub(crate) struct Transaction {
+/// A transaction for editing packed references, obtained with [`file::Store::packed_transaction()`][crate::file::Store::packed_transaction()]
+/// or [`Transaction::new()`].
+///
+/// All changes are written into the `packed-refs.lock` file which is held for the lifetime of the transaction, and moved
+/// over the `packed-refs` file in one atomic operation when committing. Dropping the transaction instead leaves
+/// `packed-refs` untouched.
+pub struct Transaction {
     /// Probably soon private and returned as part of a commit
     buffer: Option<Buffer>,
     edits: Option<Vec<Edit>>,
+    /// Edits queued with [`upsert()`][Transaction::upsert()] and [`delete()`][Transaction::delete()], to be prepared along with all others.
+    queued: Vec<RefEdit>,
+    /// If true, the peeled value of each updated reference was looked up and is known.
+    fully_peeled: bool,
     lock: Option<git_lock::File>,
     #[allow(dead_code)] // It just has to be kept alive, hence no reads
     closed_lock: Option<git_lock::Marker>,
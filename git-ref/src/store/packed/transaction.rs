@@ -1,12 +1,16 @@
 use std::io::Write;
 
+use git_hash::ObjectId;
+
 use crate::{
-    store::{file::transaction::FindObjectFn, packed, packed::Edit},
-    transaction::{Change, RefEdit},
-    Target,
+    store::{file, file::transaction::FindObjectFn, packed, packed::Edit},
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefEditsExt, RefLog},
+    FullNameRef, Target,
 };
 
 pub(crate) const HEADER_LINE: &[u8] = b"# pack-refs with: peeled fully-peeled sorted \n";
+/// The header to use if the peeled values of updated references are unknown, which makes readers peel them on demand.
+const HEADER_LINE_WITHOUT_PEELED_VALUES: &[u8] = b"# pack-refs with: sorted \n";
 
 /// Access and instantiation
 impl packed::Transaction {
@@ -16,6 +20,8 @@ impl packed::Transaction {
         packed::Transaction {
             buffer: None,
             edits: None,
+            queued: Vec::new(),
+            fully_peeled: true,
             lock: Some(lock),
             closed_lock: None,
         }
@@ -25,12 +31,51 @@ impl packed::Transaction {
         packed::Transaction {
             buffer: Some(buffer),
             edits: None,
+            queued: Vec::new(),
+            fully_peeled: true,
             lock: Some(lock),
             closed_lock: None,
         }
     }
 }
 
+/// Queuing edits
+impl packed::Transaction {
+    /// Obtain a transaction for the `packed-refs` file of `store`, failing immediately if its lock is held by another writer.
+    ///
+    /// Use [`file::Store::packed_transaction()`] to control how to acquire the lock.
+    pub fn new(store: &file::Store) -> Result<Self, file::packed::transaction::Error> {
+        store.packed_transaction(git_lock::acquire::Fail::Immediately)
+    }
+
+    /// Queue an edit to set the reference `name` to point to `id`, creating it if it doesn't exist yet.
+    pub fn upsert(&mut self, name: FullNameRef<'_>, id: ObjectId) -> &mut Self {
+        self.queued.push(RefEdit {
+            change: Change::Update {
+                log: LogChange::default(),
+                expected: PreviousValue::Any,
+                new: Target::Peeled(id),
+            },
+            name: name.to_owned(),
+            deref: false,
+        });
+        self
+    }
+
+    /// Queue an edit to delete the reference `name` if it exists.
+    pub fn delete(&mut self, name: FullNameRef<'_>) -> &mut Self {
+        self.queued.push(RefEdit {
+            change: Change::Delete {
+                expected: PreviousValue::Any,
+                log: RefLog::AndReference,
+            },
+            name: name.to_owned(),
+            deref: false,
+        });
+        self
+    }
+}
+
 /// Access
 impl packed::Transaction {
     /// Returns our packed buffer
@@ -41,14 +86,53 @@ impl packed::Transaction {
 
 /// Lifecycle
 impl packed::Transaction {
-    /// Prepare the transaction by checking all edits for applicability.
+    /// Prepare the transaction by checking all `edits` along with all queued ones for applicability.
+    ///
+    /// Each reference may only be edited once, and updates must not set symbolic targets as these can't be stored in `packed-refs`.
+    /// The expected previous value of each edit is checked against the `packed-refs` file as it was when the lock was obtained.
+    /// Peeled targets are resolved using `find` to store the object tags ultimately point to.
     pub fn prepare(
-        mut self,
+        self,
         edits: impl IntoIterator<Item = RefEdit>,
         find: &mut FindObjectFn,
+    ) -> Result<Self, prepare::Error> {
+        self.prepare_inner(edits, Some(find), true)
+    }
+
+    /// Like [`prepare()`][packed::Transaction::prepare()], but without checking previous values which is done by loose ref
+    /// transactions before passing their edits on. Without `find`, peeled values remain unknown.
+    pub(crate) fn prepare_inner(
+        mut self,
+        edits: impl IntoIterator<Item = RefEdit>,
+        find: Option<&mut FindObjectFn>,
+        check_previous_values: bool,
     ) -> Result<Self, prepare::Error> {
         assert!(self.edits.is_none(), "BUG: cannot call prepare(…) more than once");
+        let edits: Vec<RefEdit> = std::mem::take(&mut self.queued).into_iter().chain(edits).collect();
+        edits
+            .assure_one_name_has_one_edit()
+            .map_err(|name| prepare::Error::DuplicateEdit { name })?;
+        if let Some(edit) = edits.iter().find(|edit| {
+            matches!(
+                edit.change,
+                Change::Update {
+                    new: Target::Symbolic(_),
+                    ..
+                }
+            )
+        }) {
+            return Err(prepare::Error::SymbolicTarget {
+                name: edit.name.as_bstr().to_owned(),
+            });
+        }
+
         let buffer = &self.buffer;
+        if check_previous_values {
+            for edit in &edits {
+                check_previous_value(buffer.as_ref(), edit)?;
+            }
+        }
+
         // Remove all edits which are deletions that aren't here in the first place
         let mut edits: Vec<Edit> = edits
             .into_iter()
@@ -65,6 +149,13 @@ impl packed::Transaction {
             })
             .collect();
 
+        let find = match find {
+            Some(find) => find,
+            None => {
+                self.fully_peeled = false;
+                return self.finish_prepare(edits);
+            }
+        };
         let mut buf = Vec::new();
         for edit in edits.iter_mut() {
             if let Change::Update {
@@ -96,6 +187,10 @@ impl packed::Transaction {
             }
         }
 
+        self.finish_prepare(edits)
+    }
+
+    fn finish_prepare(mut self, edits: Vec<Edit>) -> Result<Self, prepare::Error> {
         if edits.is_empty() {
             self.closed_lock = self
                 .lock
@@ -103,10 +198,6 @@ impl packed::Transaction {
                 .map(|l| l.close())
                 .transpose()
                 .map_err(prepare::Error::CloseLock)?;
-        } else {
-            // NOTE that we don't do any additional checks here but apply all edits unconditionally.
-            // This is because this transaction system is internal and will be used correctly from the
-            // loose ref store transactions, which do the necessary checking.
         }
         self.edits = Some(edits);
         Ok(self)
@@ -114,10 +205,16 @@ impl packed::Transaction {
 
     /// Commit the prepared transaction.
     ///
+    /// If [`prepare()`][packed::Transaction::prepare()] wasn't called, all queued edits are prepared first without
+    /// looking up peeled values, leaving it to readers to peel updated references on demand.
+    ///
     /// Please note that actual edits invalidated existing packed buffers.
     /// Note: There is the potential to write changes into memory and return such a packed-refs buffer for reuse.
-    pub fn commit(self) -> Result<(), commit::Error> {
-        let mut edits = self.edits.expect("BUG: cannot call commit() before prepare(…)");
+    pub fn commit(mut self) -> Result<(), commit::Error> {
+        if self.edits.is_none() {
+            self = self.prepare_inner(std::iter::empty(), None, true)?;
+        }
+        let mut edits = self.edits.expect("prepared edits");
         if edits.is_empty() {
             return Ok(());
         }
@@ -134,7 +231,12 @@ impl packed::Transaction {
         edits.sort_by(|l, r| l.inner.name.as_bstr().cmp(r.inner.name.as_bstr()));
         let mut peekable_sorted_edits = edits.iter().peekable();
 
-        file.with_mut(|f| f.write_all(HEADER_LINE))?;
+        let header = if self.fully_peeled {
+            HEADER_LINE
+        } else {
+            HEADER_LINE_WITHOUT_PEELED_VALUES
+        };
+        file.with_mut(|f| f.write_all(header))?;
 
         let mut num_written_lines = 0;
         loop {
@@ -187,6 +289,45 @@ impl packed::Transaction {
     }
 }
 
+fn check_previous_value(buffer: Option<&packed::Buffer>, edit: &RefEdit) -> Result<(), prepare::Error> {
+    let expected = match &edit.change {
+        Change::Update { expected, .. } | Change::Delete { expected, .. } => expected,
+    };
+    let existing = match buffer {
+        Some(buffer) => buffer
+            .try_find(edit.name.to_ref())
+            .map_err(prepare::Error::Find)?
+            .map(|r| Target::Peeled(r.target())),
+        None => None,
+    };
+    let name = || edit.name.as_bstr().to_owned();
+    match (expected, existing) {
+        (PreviousValue::Any, _)
+        | (PreviousValue::MustExist, Some(_))
+        | (PreviousValue::MustNotExist | PreviousValue::ExistingMustMatch(_), None) => Ok(()),
+        (PreviousValue::MustExist, None) => Err(prepare::Error::MustExist {
+            name: name(),
+            expected: None,
+        }),
+        (PreviousValue::MustExistAndMatch(expected), None) => Err(prepare::Error::MustExist {
+            name: name(),
+            expected: Some(expected.clone()),
+        }),
+        (PreviousValue::MustNotExist, Some(actual)) => Err(prepare::Error::MustNotExist { name: name(), actual }),
+        (PreviousValue::MustExistAndMatch(expected) | PreviousValue::ExistingMustMatch(expected), Some(actual)) => {
+            if *expected == actual {
+                Ok(())
+            } else {
+                Err(prepare::Error::ReferenceOutOfDate {
+                    name: name(),
+                    expected: expected.clone(),
+                    actual,
+                })
+            }
+        }
+    }
+}
+
 fn write_packed_ref(file: &mut git_lock::File, pref: packed::Reference<'_>) -> std::io::Result<()> {
     file.with_mut(|out| {
         write!(out, "{} ", pref.target)?;
@@ -227,23 +368,21 @@ fn write_edit(file: &mut git_lock::File, edit: &Edit, lines_written: &mut i32) -
 
 impl packed::Buffer {
     /// Convert this buffer to be used as the basis for a transaction.
-    pub(crate) fn into_transaction(
+    pub fn into_transaction(
         self,
         lock_mode: git_lock::acquire::Fail,
     ) -> Result<packed::Transaction, git_lock::acquire::Error> {
         let lock = git_lock::File::acquire_to_update_resource(&self.path, lock_mode, None)?;
-        Ok(packed::Transaction {
-            buffer: Some(self),
-            lock: Some(lock),
-            closed_lock: None,
-            edits: None,
-        })
+        Ok(packed::Transaction::new_from_pack_and_lock(self, lock))
     }
 }
 
 ///
 pub mod prepare {
+    use git_object::bstr::BString;
     use quick_error::quick_error;
+
+    use crate::{store::packed, Target};
     quick_error! {
         /// The error used in [`Transaction::prepare(…)`][super::packed::Transaction::prepare()].
         #[derive(Debug)]
@@ -258,6 +397,25 @@ pub mod prepare {
                 from()
                 source(&**err)
             }
+            DuplicateEdit { name: BString } {
+                display("A reference named '{}' has multiple edits", name)
+            }
+            SymbolicTarget { name: BString } {
+                display("The reference '{}' cannot be stored in packed-refs as its new target is symbolic", name)
+            }
+            Find(err: packed::find::Error) {
+                display("The current value of a reference could not be read from packed-refs")
+                source(err)
+            }
+            MustExist { name: BString, expected: Option<Target> } {
+                display("Reference '{}' was supposed to exist{}, but didn't", name, expected.as_ref().map(|t| format!(" with value {}", t)).unwrap_or_default())
+            }
+            MustNotExist { name: BString, actual: Target } {
+                display("Reference '{}' was not supposed to exist, but its value was {}", name, actual)
+            }
+            ReferenceOutOfDate { name: BString, expected: Target, actual: Target } {
+                display("The reference '{}' should have content {}, actual content was {}", name, expected, actual)
+            }
         }
     }
 }
@@ -273,6 +431,11 @@ pub mod commit {
         #[derive(Debug)]
         #[allow(missing_docs)]
         pub enum Error {
+            Prepare(err: packed::transaction::prepare::Error) {
+                display("The queued edits could not be prepared")
+                from()
+                source(err)
+            }
             Commit(err: git_lock::commit::Error<git_lock::File>) {
                 display("Changes to the resource could not be comitted")
                 from()
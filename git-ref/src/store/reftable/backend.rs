@@ -0,0 +1,33 @@
+use std::path::Path;
+
+use crate::{
+    backend,
+    log::Line,
+    store::reftable::{self, decode},
+    FullNameRef, PartialNameRef, Reference,
+};
+
+impl backend::Backend for reftable::Store {
+    type Error = decode::Error;
+    type Transaction = reftable::Transaction;
+
+    fn try_find_reference(&self, partial: PartialNameRef<'_>) -> Result<Option<Reference>, Self::Error> {
+        self.find_one(partial)
+    }
+
+    fn references(&self) -> Result<Vec<Reference>, Self::Error> {
+        reftable::Store::references(self)
+    }
+
+    fn references_prefixed(&self, prefix: &Path) -> Result<Vec<Reference>, Self::Error> {
+        reftable::Store::references_prefixed(self, prefix)
+    }
+
+    fn reflog_lines(&self, name: FullNameRef<'_>) -> Result<Option<Vec<Line>>, Self::Error> {
+        self.reflog(name)
+    }
+
+    fn to_transaction(&self) -> Self::Transaction {
+        self.transaction()
+    }
+}
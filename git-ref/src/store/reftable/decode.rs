@@ -0,0 +1,523 @@
+use std::{borrow::Cow, convert::TryFrom};
+
+use git_hash::ObjectId;
+use git_object::bstr::BString;
+
+use crate::{log, store::reftable};
+
+pub(crate) const MAGIC: &[u8] = b"REFT";
+pub(crate) const BLOCK_REFS: u8 = b'r';
+pub(crate) const BLOCK_LOGS: u8 = b'g';
+
+/// The amount of bytes a block header takes, the block kind followed by its length.
+const BLOCK_HEADER_LEN: usize = 4;
+/// The amount of bytes in the footer following the copy of the file header.
+const FOOTER_LEN_WITHOUT_HEADER: usize = 5 * 8 + 4;
+
+mod error {
+    use quick_error::quick_error;
+
+    quick_error! {
+        /// The error returned when decoding reftables.
+        #[derive(Debug)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            UnexpectedEnd { offset: usize } {
+                display("The table ended unexpectedly when reading at offset {}", offset)
+            }
+            Signature {
+                display("The table doesn't start with the 'REFT' signature")
+            }
+            UnsupportedVersion(version: u8) {
+                display("Version {} of the reftable format is not supported", version)
+            }
+            UnsupportedHash(id: u32) {
+                display("Object ids of hash function {:#x} are not supported", id)
+            }
+            FooterMismatch {
+                display("The header copied into the footer differs from the table header")
+            }
+            Checksum { expected: u32, actual: u32 } {
+                display("The footer checksum was expected to be {:#x}, but was {:#x}", expected, actual)
+            }
+            Corrupt { offset: usize, message: &'static str } {
+                display("The block at offset {} is corrupt: {}", offset, message)
+            }
+            Inflate(err: git_features::zlib::inflate::Error) {
+                display("A log block could not be decompressed")
+                from()
+                source(err)
+            }
+            RefName(err: git_validate::refname::Error) {
+                display("A reference name in the table is invalid")
+                from()
+                source(err)
+            }
+        }
+    }
+}
+pub use error::Error;
+
+/// Decode the header and footer of `data`, validating the checksum of the footer.
+pub(crate) fn table(data: Vec<u8>, path: std::path::PathBuf) -> Result<reftable::Table, Error> {
+    let mut pos = 0;
+    if take(&data, &mut pos, MAGIC.len())? != MAGIC {
+        return Err(Error::Signature);
+    }
+    let version = byte(&data, &mut pos)?;
+    let header_len = match version {
+        1 => 24,
+        2 => 28,
+        _ => return Err(Error::UnsupportedVersion(version)),
+    };
+    let block_size = be(&data, &mut pos, 3)? as usize;
+    let min_update_index = be(&data, &mut pos, 8)?;
+    let max_update_index = be(&data, &mut pos, 8)?;
+    if version == 2 {
+        let hash_id = be(&data, &mut pos, 4)? as u32;
+        if hash_id != u32::from_be_bytes(*b"sha1") {
+            return Err(Error::UnsupportedHash(hash_id));
+        }
+    }
+
+    let footer_len = header_len + FOOTER_LEN_WITHOUT_HEADER;
+    let footer_offset = data
+        .len()
+        .checked_sub(footer_len)
+        .filter(|offset| *offset >= header_len)
+        .ok_or(Error::UnexpectedEnd { offset: data.len() })?;
+    let footer = &data[footer_offset..];
+    if footer[..header_len] != data[..header_len] {
+        return Err(Error::FooterMismatch);
+    }
+    let (checked, checksum) = footer.split_at(footer_len - 4);
+    let expected = u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]);
+    let actual = git_features::hash::crc32(checked);
+    if expected != actual {
+        return Err(Error::Checksum { expected, actual });
+    }
+    let mut pos = header_len + 3 * 8;
+    let log_offset = be(footer, &mut pos, 8)? as usize;
+    if log_offset >= footer_offset {
+        return Err(Error::Corrupt {
+            offset: footer_offset,
+            message: "the log section starts past the footer",
+        });
+    }
+
+    let mut table = reftable::Table {
+        data,
+        path,
+        header_len,
+        block_size,
+        min_update_index,
+        max_update_index,
+        footer_offset,
+        log_offset: None,
+    };
+    // Logs are only written at the beginning of the table if there are no references, which is when the offset is 0.
+    table.log_offset = match log_offset {
+        0 => table.block(0)?.filter(|block| block.kind == BLOCK_LOGS).map(|_| 0),
+        offset => Some(offset),
+    };
+    Ok(table)
+}
+
+/// A block of records, with the restart points at its end.
+pub(crate) struct Block<'a> {
+    pub kind: u8,
+    /// The data of the block from its start, which includes the file header for the very first block, decompressed for logs.
+    data: Cow<'a, [u8]>,
+    /// The offset of the block in the table, for error messages.
+    offset: usize,
+    /// The offset at which the first record begins.
+    records_start: usize,
+    /// The offset at which the table of restart points begins, which is where the records end.
+    restarts_start: usize,
+    restart_count: usize,
+    /// The offset of the next block in the table.
+    pub next_offset: usize,
+}
+
+impl reftable::Table {
+    /// Return the block at `offset` or `None` if there is no block as the footer begins.
+    pub(crate) fn block(&self, offset: usize) -> Result<Option<Block<'_>>, Error> {
+        let header_len = if offset == 0 { self.header_len } else { 0 };
+        if offset + header_len >= self.footer_offset {
+            return Ok(None);
+        }
+        let data = &self.data[..self.footer_offset];
+        let mut pos = offset + header_len;
+        let kind = byte(data, &mut pos)?;
+        let block_len = be(data, &mut pos, 3)? as usize;
+        let records_start = header_len + BLOCK_HEADER_LEN;
+        if block_len < records_start + 2 {
+            return Err(Error::Corrupt {
+                offset,
+                message: "the block is too short",
+            });
+        }
+
+        let (block, next_offset) = if kind == BLOCK_LOGS {
+            let mut inflated = Vec::with_capacity(block_len + 1);
+            inflated.extend_from_slice(&data[offset..pos]);
+            inflated.resize(block_len + 1, 0);
+            let mut inflate = git_features::zlib::Inflate::default();
+            let (mut total_in, mut total_out) = (0, 0);
+            loop {
+                let (status, consumed, written) =
+                    inflate.once(&data[pos + total_in..], &mut inflated[records_start + total_out..])?;
+                total_in += consumed;
+                total_out += written;
+                match status {
+                    git_features::zlib::Status::StreamEnd => break,
+                    git_features::zlib::Status::Ok if consumed + written > 0 => continue,
+                    _ => {
+                        return Err(Error::Corrupt {
+                            offset,
+                            message: "the compressed log records end unexpectedly",
+                        })
+                    }
+                }
+            }
+            if records_start + total_out != block_len {
+                return Err(Error::Corrupt {
+                    offset,
+                    message: "the log records don't decompress to the size of the block",
+                });
+            }
+            inflated.truncate(block_len);
+            (Cow::Owned(inflated), pos + total_in)
+        } else {
+            let end = offset + block_len;
+            if end > data.len() {
+                return Err(Error::UnexpectedEnd { offset: data.len() });
+            }
+            // Blocks are padded with zeroes to the block size, unless they are written unaligned.
+            let next_offset = if self.block_size == 0 || block_len >= self.block_size || data.get(end) != Some(&0) {
+                end
+            } else {
+                (offset + self.block_size).min(self.footer_offset)
+            };
+            (Cow::Borrowed(&data[offset..end]), next_offset)
+        };
+
+        let restart_count = u16::from_be_bytes([block[block_len - 2], block[block_len - 1]]) as usize;
+        let restarts_start = (block_len - 2)
+            .checked_sub(3 * restart_count)
+            .filter(|start| *start >= records_start)
+            .ok_or(Error::Corrupt {
+                offset,
+                message: "the restart points overlap with the records",
+            })?;
+        Ok(Some(Block {
+            kind,
+            data: block,
+            offset,
+            records_start,
+            restarts_start,
+            restart_count,
+            next_offset,
+        }))
+    }
+
+    /// Return all blocks of `kind` in the section starting at `offset`.
+    pub(crate) fn blocks(&self, offset: Option<usize>, kind: u8) -> Blocks<'_> {
+        Blocks {
+            table: self,
+            offset,
+            kind,
+        }
+    }
+}
+
+pub(crate) struct Blocks<'a> {
+    table: &'a reftable::Table,
+    offset: Option<usize>,
+    kind: u8,
+}
+
+impl<'a> Iterator for Blocks<'a> {
+    type Item = Result<Block<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.offset.take()?;
+        match self.table.block(offset) {
+            Ok(Some(block)) if block.kind == self.kind => {
+                self.offset = Some(block.next_offset);
+                Some(Ok(block))
+            }
+            Ok(_) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+impl<'a> Block<'a> {
+    /// Return the records of this block, starting at the last restart point whose key is not larger than `key`, or at
+    /// the beginning if `key` is `None`.
+    pub(crate) fn records(&self, key: Option<&[u8]>) -> Result<Records<'_>, Error> {
+        let mut start = self.records_start;
+        if let Some(key) = key {
+            // Binary search for the first restart point past `key`, the records at restart points have no shared prefix.
+            let (mut lo, mut hi) = (0, self.restart_count);
+            while lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                let mut records = self.records_at(self.restart_offset(mid)?);
+                if records.next_key()?.is_none() {
+                    return Err(self.corrupt("a restart point doesn't point to a record"));
+                }
+                if records.key() <= key {
+                    lo = mid + 1;
+                } else {
+                    hi = mid;
+                }
+            }
+            if lo > 0 {
+                start = self.restart_offset(lo - 1)?;
+            }
+        }
+        Ok(self.records_at(start))
+    }
+
+    fn records_at(&self, pos: usize) -> Records<'_> {
+        Records {
+            block: self,
+            pos,
+            key: Vec::new(),
+        }
+    }
+
+    fn restart_offset(&self, index: usize) -> Result<usize, Error> {
+        let mut pos = self.restarts_start + 3 * index;
+        let offset = be(&self.data, &mut pos, 3)? as usize;
+        if offset < self.records_start || offset >= self.restarts_start {
+            return Err(self.corrupt("a restart point is out of bounds"));
+        }
+        Ok(offset)
+    }
+
+    fn corrupt(&self, message: &'static str) -> Error {
+        Error::Corrupt {
+            offset: self.offset,
+            message,
+        }
+    }
+}
+
+/// Records of a block, decoded one by one by obtaining the key and the kind of value, and then decoding the value.
+pub(crate) struct Records<'a> {
+    block: &'a Block<'a>,
+    pos: usize,
+    key: Vec<u8>,
+}
+
+/// A reference as stored in a reftable.
+pub(crate) struct RefRecord {
+    pub name: BString,
+    pub value: RefValue,
+}
+
+pub(crate) enum RefValue {
+    /// The reference was deleted, hiding it in older tables.
+    Deletion,
+    Peeled {
+        target: ObjectId,
+        peeled: Option<ObjectId>,
+    },
+    Symbolic(BString),
+}
+
+impl RefRecord {
+    /// Convert this record into a reference, or `None` if it is a deletion.
+    pub(crate) fn into_reference(self) -> Result<Option<crate::Reference>, Error> {
+        let name = crate::FullName::try_from(self.name)?;
+        Ok(match self.value {
+            RefValue::Deletion => None,
+            RefValue::Peeled { target, peeled } => Some(crate::Reference {
+                name,
+                target: crate::Target::Peeled(target),
+                peeled,
+            }),
+            RefValue::Symbolic(target) => Some(crate::Reference {
+                name,
+                target: crate::Target::Symbolic(crate::FullName::try_from(target)?),
+                peeled: None,
+            }),
+        })
+    }
+}
+
+/// A reflog entry as stored in a reftable.
+pub(crate) struct LogRecord {
+    pub name: BString,
+    pub update_index: u64,
+    /// The line of the reflog, or `None` if it was deleted to hide it in older tables.
+    pub line: Option<log::Line>,
+}
+
+impl<'a> Records<'a> {
+    /// Decode the key of the next record and return the kind of its value, or `None` if there are no more records.
+    pub(crate) fn next_key(&mut self) -> Result<Option<u8>, Error> {
+        if self.pos >= self.block.restarts_start {
+            return Ok(None);
+        }
+        let prefix_len = self.varint()? as usize;
+        let suffix_len_and_kind = self.varint()?;
+        if prefix_len > self.key.len() {
+            return Err(self
+                .block
+                .corrupt("a key shares more bytes with the previous key than it has"));
+        }
+        let suffix_len = (suffix_len_and_kind >> 3) as usize;
+        self.key.truncate(prefix_len);
+        let suffix = self.take(suffix_len)?;
+        self.key.extend_from_slice(suffix);
+        Ok(Some((suffix_len_and_kind & 0x7) as u8))
+    }
+
+    /// The key of the current record.
+    pub(crate) fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// Decode the value of the reference record whose key was just decoded and which is of `kind`.
+    pub(crate) fn ref_value(&mut self, kind: u8) -> Result<RefRecord, Error> {
+        let _update_index_delta = self.varint()?;
+        let value = match kind {
+            0 => RefValue::Deletion,
+            1 => RefValue::Peeled {
+                target: self.id()?,
+                peeled: None,
+            },
+            2 => RefValue::Peeled {
+                target: self.id()?,
+                peeled: Some(self.id()?),
+            },
+            3 => {
+                let len = self.varint()? as usize;
+                RefValue::Symbolic(self.take(len)?.into())
+            }
+            _ => return Err(self.block.corrupt("unknown kind of reference")),
+        };
+        Ok(RefRecord {
+            name: self.key.clone().into(),
+            value,
+        })
+    }
+
+    /// Decode the value of the log record whose key was just decoded and which is of `kind`.
+    pub(crate) fn log_value(&mut self, kind: u8) -> Result<LogRecord, Error> {
+        // The key is the name of the reference, a null byte and the update index subtracted from its maximum value,
+        // which sorts the most recent entry first.
+        let name_len = self
+            .key
+            .len()
+            .checked_sub(1 + 8)
+            .filter(|len| self.key[*len] == 0)
+            .ok_or_else(|| self.block.corrupt("the key of a log record is invalid"))?;
+        let mut pos = name_len + 1;
+        let update_index = u64::MAX - be(&self.key, &mut pos, 8)?;
+        let line = match kind {
+            0 => None,
+            1 => {
+                let previous_oid = self.id()?;
+                let new_oid = self.id()?;
+                let len = self.varint()? as usize;
+                let name = self.take(len)?.into();
+                let len = self.varint()? as usize;
+                let email = self.take(len)?.into();
+                let time = u32::try_from(self.varint()?)
+                    .map_err(|_| self.block.corrupt("the time of a log record is out of range"))?;
+                let tz = self.take(2)?;
+                let tz = i16::from_be_bytes([tz[0], tz[1]]);
+                let len = self.varint()? as usize;
+                let mut message = self.take(len)?;
+                // Messages are stored with a trailing newline, which reflog files don't keep as part of the message.
+                if message.last() == Some(&b'\n') {
+                    message = &message[..message.len() - 1];
+                }
+                let hours_and_minutes = i32::from(tz).abs();
+                let offset = (hours_and_minutes / 100) * 3600 + (hours_and_minutes % 100) * 60;
+                Some(log::Line {
+                    previous_oid,
+                    new_oid,
+                    signature: git_actor::Signature {
+                        name,
+                        email,
+                        time: git_actor::Time {
+                            time,
+                            offset: if tz < 0 { -offset } else { offset },
+                            sign: if tz < 0 {
+                                git_actor::Sign::Minus
+                            } else {
+                                git_actor::Sign::Plus
+                            },
+                        },
+                    },
+                    message: message.into(),
+                })
+            }
+            _ => return Err(self.block.corrupt("unknown kind of log record")),
+        };
+        Ok(LogRecord {
+            name: self.key[..name_len].into(),
+            update_index,
+            line,
+        })
+    }
+
+    fn id(&mut self) -> Result<ObjectId, Error> {
+        Ok(ObjectId::from_20_bytes(
+            self.take(git_hash::Kind::Sha1.len_in_hex() / 2)?,
+        ))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let block: &'a Block<'a> = self.block;
+        let data = &block.data[..block.restarts_start];
+        take(data, &mut self.pos, len).map_err(|_| self.block.corrupt("a record ends past the records of its block"))
+    }
+
+    fn varint(&mut self) -> Result<u64, Error> {
+        let block: &'a Block<'a> = self.block;
+        let data = &block.data[..block.restarts_start];
+        varint(data, &mut self.pos).map_err(|_| self.block.corrupt("a number in a record is invalid"))
+    }
+}
+
+/// Decode a variable-length integer as written by git, where each continuation adds one to avoid redundant encodings.
+fn varint(data: &[u8], pos: &mut usize) -> Result<u64, Error> {
+    let mut c = byte(data, pos)?;
+    let mut value = u64::from(c & 0x7f);
+    while c & 0x80 != 0 {
+        c = byte(data, pos)?;
+        value = value
+            .checked_add(1)
+            .filter(|value| value.leading_zeros() >= 7)
+            .map(|value| (value << 7) | u64::from(c & 0x7f))
+            .ok_or(Error::Corrupt {
+                offset: *pos,
+                message: "a number is too large",
+            })?;
+    }
+    Ok(value)
+}
+
+fn byte(data: &[u8], pos: &mut usize) -> Result<u8, Error> {
+    Ok(take(data, pos, 1)?[0])
+}
+
+fn be(data: &[u8], pos: &mut usize, len: usize) -> Result<u64, Error> {
+    Ok(take(data, pos, len)?
+        .iter()
+        .fold(0, |value, byte| (value << 8) | u64::from(*byte)))
+}
+
+fn take<'a>(data: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8], Error> {
+    let bytes = data
+        .get(*pos..*pos + len)
+        .ok_or(Error::UnexpectedEnd { offset: data.len() })?;
+    *pos += len;
+    Ok(bytes)
+}
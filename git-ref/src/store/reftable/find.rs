@@ -0,0 +1,124 @@
+use std::convert::TryInto;
+
+use git_object::bstr::BString;
+
+pub use error::Error;
+
+use crate::{
+    store::reftable::{
+        self,
+        decode::{self, RefRecord},
+    },
+    PartialNameRef, Reference,
+};
+
+impl reftable::Store {
+    /// Find a single reference by the given `partial` name, following the same lookup rules as
+    /// [`file::Store::try_find()`][crate::file::Store::try_find()], or return `Ok(None)` if no such reference exists.
+    pub fn try_find<'a, Name, E>(&self, partial: Name) -> Result<Option<Reference>, Error>
+    where
+        Name: TryInto<PartialNameRef<'a>, Error = E>,
+        Error: From<E>,
+    {
+        Ok(self.find_one(partial.try_into()?)?)
+    }
+
+    pub(crate) fn find_one(&self, partial: PartialNameRef<'_>) -> Result<Option<Reference>, decode::Error> {
+        for name in candidates(partial) {
+            if let Some(reference) = self.find_exact(&name)? {
+                return Ok(Some(reference));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Find the reference with the full `name`, with the newest table containing it deciding if it exists.
+    fn find_exact(&self, name: &[u8]) -> Result<Option<Reference>, decode::Error> {
+        for table in self.tables.iter().rev() {
+            if let Some(record) = table.ref_record(name)? {
+                return record.into_reference();
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl reftable::Table {
+    /// Return the record of the reference named `name`, including deletions.
+    fn ref_record(&self, name: &[u8]) -> Result<Option<RefRecord>, decode::Error> {
+        for block in self.blocks(Some(0), decode::BLOCK_REFS) {
+            let block = block?;
+            let mut records = block.records(Some(name))?;
+            while let Some(kind) = records.next_key()? {
+                match records.key().cmp(name) {
+                    std::cmp::Ordering::Less => {
+                        records.ref_value(kind)?;
+                    }
+                    std::cmp::Ordering::Equal => return Ok(Some(records.ref_value(kind)?)),
+                    std::cmp::Ordering::Greater => return Ok(None),
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// The full names to try in order for `partial`, like [the file store does][crate::file::Store::try_find()].
+fn candidates(partial: PartialNameRef<'_>) -> Vec<BString> {
+    let name = partial.as_bstr();
+    let mut candidates = Vec::new();
+    if !name.contains(&b'/') && name.iter().all(|b| b.is_ascii_uppercase()) {
+        candidates.push(name.to_owned());
+    }
+    let base: &[u8] = if name == "refs" || name.starts_with(b"refs/") {
+        b""
+    } else {
+        b"refs"
+    };
+    let join = |components: &[&[u8]]| -> BString {
+        components
+            .iter()
+            .filter(|component| !component.is_empty())
+            .copied()
+            .collect::<Vec<_>>()
+            .join(&b'/')
+            .into()
+    };
+    for inbetween in &[&b""[..], b"tags", b"heads", b"remotes"] {
+        candidates.push(join(&[base, inbetween, name]));
+    }
+    candidates.push(join(&[base, b"remotes", name, b"HEAD"]));
+    candidates
+}
+
+mod error {
+    use std::convert::Infallible;
+
+    use quick_error::quick_error;
+
+    use crate::store::reftable::decode;
+
+    quick_error! {
+        /// The error returned by [`reftable::Store::try_find()`][crate::reftable::Store::try_find()].
+        #[derive(Debug)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            RefnameValidation(err: crate::name::Error) {
+                display("The ref name or path is not a valid ref name")
+                from()
+                source(err)
+            }
+            Decode(err: decode::Error) {
+                display("A table could not be decoded")
+                from()
+                source(err)
+            }
+        }
+    }
+
+    impl From<Infallible> for Error {
+        fn from(_: Infallible) -> Self {
+            unreachable!("this impl is needed to allow passing a known valid partial path as parameter")
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::{collections::BTreeMap, path::Path};
+
+use git_object::bstr::BString;
+
+use crate::{
+    store::{
+        file::path_to_name,
+        reftable::{
+            self,
+            decode::{self, RefRecord},
+        },
+    },
+    Reference,
+};
+
+impl reftable::Store {
+    /// Return all references in the `refs/` namespace sorted by their name, which excludes `HEAD` like
+    /// [`file::Store::iter()`][crate::file::Store::iter()] does.
+    pub fn references(&self) -> Result<Vec<Reference>, decode::Error> {
+        self.references_with_prefix(b"refs/")
+    }
+
+    /// Return all references whose name starts with `prefix`, like `refs/heads/`, sorted by their name.
+    pub fn references_prefixed(&self, prefix: impl AsRef<Path>) -> Result<Vec<Reference>, decode::Error> {
+        self.references_with_prefix(&path_to_name(prefix.as_ref()))
+    }
+
+    fn references_with_prefix(&self, prefix: &[u8]) -> Result<Vec<Reference>, decode::Error> {
+        let mut records = BTreeMap::<BString, RefRecord>::new();
+        for table in &self.tables {
+            'blocks: for block in table.blocks(Some(0), decode::BLOCK_REFS) {
+                let block = block?;
+                let mut block_records = block.records(Some(prefix))?;
+                while let Some(kind) = block_records.next_key()? {
+                    if block_records.key() < prefix {
+                        block_records.ref_value(kind)?;
+                        continue;
+                    }
+                    if !block_records.key().starts_with(prefix) {
+                        break 'blocks;
+                    }
+                    // Newer tables come later and replace the records of older ones.
+                    let record = block_records.ref_value(kind)?;
+                    records.insert(record.name.clone(), record);
+                }
+            }
+        }
+        records
+            .into_values()
+            .filter_map(|record| record.into_reference().transpose())
+            .collect()
+    }
+}
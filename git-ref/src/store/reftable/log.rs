@@ -0,0 +1,36 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    log::Line,
+    store::reftable::{self, decode},
+    FullNameRef,
+};
+
+impl reftable::Store {
+    /// Return the reflog of the reference `name` from the oldest to the most recent entry, or `None` if there is none.
+    ///
+    /// The trailing newline of each message is removed, just like it's not part of the message in reflog files.
+    pub fn reflog(&self, name: FullNameRef<'_>) -> Result<Option<Vec<Line>>, decode::Error> {
+        let name = name.as_bstr();
+        let mut lines = BTreeMap::new();
+        for table in &self.tables {
+            'blocks: for block in table.blocks(table.log_offset, decode::BLOCK_LOGS) {
+                let block = block?;
+                let mut records = block.records(Some(name))?;
+                while let Some(kind) = records.next_key()? {
+                    let record = records.log_value(kind)?;
+                    match record.name.as_slice().cmp(name) {
+                        std::cmp::Ordering::Less => continue,
+                        std::cmp::Ordering::Equal => {
+                            // Newer tables come later and replace the entries of older ones, or delete them.
+                            lines.insert(record.update_index, record.line);
+                        }
+                        std::cmp::Ordering::Greater => break 'blocks,
+                    }
+                }
+            }
+        }
+        let lines: Vec<_> = lines.into_values().flatten().collect();
+        Ok(if lines.is_empty() { None } else { Some(lines) })
+    }
+}
@@ -0,0 +1,88 @@
+//! Read references and reflogs stored in [reftables](https://git-scm.com/docs/reftable), as created with
+//! `git init --ref-format=reftable`.
+//!
+//! A repository keeps a stack of tables in its `reftable` directory, listed from oldest to newest in `tables.list`.
+//! Newer tables override the references and reflog entries of older ones, including deletions which hide them.
+//!
+//! Writing tables isn't supported yet, and [transactions][Transaction] fail accordingly.
+use std::path::PathBuf;
+
+/// A stack of reftables as found in the `reftable` directory of a repository, read entirely into memory when opened.
+///
+/// Namespaces and references private to worktrees are not supported yet.
+pub struct Store {
+    /// The directory containing the tables.
+    path: PathBuf,
+    /// All tables from the oldest to the newest.
+    tables: Vec<Table>,
+}
+
+/// A single reftable, with references in blocks at the beginning followed by blocks of compressed reflog entries.
+///
+/// Only the header and footer are decoded when opening the table, blocks are decoded only as needed. Index blocks aren't
+/// used to speed up lookups.
+pub struct Table {
+    data: Vec<u8>,
+    path: PathBuf,
+    header_len: usize,
+    /// The size of blocks that references are padded to, or 0 if they are not aligned.
+    block_size: usize,
+    min_update_index: u64,
+    max_update_index: u64,
+    /// The offset at which the footer begins, which is where the last block ends.
+    footer_offset: usize,
+    /// The offset of the first block of reflog entries, if there is one.
+    log_offset: Option<usize>,
+}
+
+/// A transaction on a reftable store, which fails to prepare as writing reftables isn't supported yet.
+pub struct Transaction {
+    _private: (),
+}
+
+impl Store {
+    /// The directory containing the tables.
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// All tables from the oldest to the newest.
+    pub fn tables(&self) -> &[Table] {
+        &self.tables
+    }
+}
+
+impl Table {
+    /// The path from which the table was read.
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// The smallest update index of the references and reflog entries in this table.
+    pub fn min_update_index(&self) -> u64 {
+        self.min_update_index
+    }
+
+    /// The largest update index of the references and reflog entries in this table.
+    pub fn max_update_index(&self) -> u64 {
+        self.max_update_index
+    }
+}
+
+///
+pub mod decode;
+
+///
+pub mod open;
+
+///
+pub mod find;
+
+mod iter;
+
+mod log;
+
+///
+pub mod transaction;
+
+mod backend;
@@ -0,0 +1,66 @@
+use std::path::PathBuf;
+
+pub use error::Error;
+
+use crate::store::reftable::{self, decode};
+
+impl reftable::Store {
+    /// Read all tables listed in the `tables.list` file in the reftable directory at `path`, typically `.git/reftable`.
+    pub fn at(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+        let list_path = path.join("tables.list");
+        let list = std::fs::read(&list_path).map_err(|err| Error::Io { err, path: list_path })?;
+        let tables = list
+            .split(|b| *b == b'\n')
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                let name = std::str::from_utf8(name).map_err(|_| Error::TableName {
+                    name: name.to_owned().into(),
+                })?;
+                reftable::Table::at(path.join(name))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(reftable::Store { path, tables })
+    }
+}
+
+impl reftable::Table {
+    /// Read the table at `path` and decode its header and footer.
+    pub fn at(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+        let data = std::fs::read(&path).map_err(|err| Error::Io {
+            err,
+            path: path.clone(),
+        })?;
+        decode::table(data, path.clone()).map_err(|err| Error::Decode { err, path })
+    }
+}
+
+mod error {
+    use std::path::PathBuf;
+
+    use git_object::bstr::BString;
+    use quick_error::quick_error;
+
+    use crate::store::reftable::decode;
+
+    quick_error! {
+        /// The error returned by [`reftable::Store::at()`][crate::reftable::Store::at()] and
+        /// [`reftable::Table::at()`][crate::reftable::Table::at()].
+        #[derive(Debug)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            Io { err: std::io::Error, path: PathBuf } {
+                display("The file at '{}' could not be read", path.display())
+                source(err)
+            }
+            TableName { name: BString } {
+                display("The table name '{}' is not valid UTF-8", name)
+            }
+            Decode { err: decode::Error, path: PathBuf } {
+                display("The table at '{}' could not be decoded", path.display())
+                source(err)
+            }
+        }
+    }
+}
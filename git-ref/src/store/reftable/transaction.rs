@@ -0,0 +1,42 @@
+use crate::{backend, store::reftable, transaction::RefEdit};
+
+pub use error::Error;
+
+impl reftable::Store {
+    /// Return a transaction which fails to [prepare][backend::Transaction::prepare()] as writing reftables isn't supported yet.
+    pub fn transaction(&self) -> reftable::Transaction {
+        reftable::Transaction { _private: () }
+    }
+}
+
+impl backend::Transaction for reftable::Transaction {
+    type PrepareError = Error;
+    type CommitError = Error;
+
+    fn prepare(
+        self,
+        _edits: impl IntoIterator<Item = RefEdit>,
+        _lock_fail_mode: git_lock::acquire::Fail,
+    ) -> Result<Self, Self::PrepareError> {
+        Err(Error::WriteUnsupported)
+    }
+
+    fn commit(self, _committer: &git_actor::Signature) -> Result<Vec<RefEdit>, Self::CommitError> {
+        Err(Error::WriteUnsupported)
+    }
+}
+
+mod error {
+    use quick_error::quick_error;
+
+    quick_error! {
+        /// The error returned by [transactions][crate::reftable::Transaction] on reftable stores.
+        #[derive(Debug)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            WriteUnsupported {
+                display("Writing reftables is not supported yet")
+            }
+        }
+    }
+}
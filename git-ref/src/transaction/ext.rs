@@ -1,6 +1,8 @@
 use git_object::bstr::BString;
 
 use crate::{
+    packed,
+    store::file,
     transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog, Target},
     PartialNameRef,
 };
@@ -15,13 +17,26 @@ where
 
     /// Split all symbolic refs into updates for the symbolic ref as well as all their referents if the `deref` flag is enabled.
     ///
-    /// Note no action is performed if deref isn't specified.
+    /// Note no action is performed if deref isn't specified, or if the update sets a new symbolic target as this
+    /// retargets the symbolic ref itself.
     fn extend_with_splits_of_symbolic_refs(
         &mut self,
         find: impl FnMut(PartialNameRef<'_>) -> Option<Target>,
         make_entry: impl FnMut(usize, RefEdit) -> T,
     ) -> Result<(), std::io::Error>;
 
+    /// Check all edits against the current state of references in `store` and its `packed` references without changing
+    /// anything, and return all edits that would fail along with the reason.
+    ///
+    /// This checks the expected previous values of references as well as whether new references would conflict
+    /// with existing ones, like `refs/heads/a` with `refs/heads/a/b`, to give early feedback before a transaction is
+    /// prepared. Symbolic references aren't followed, which is why it's best called after [`pre_process()`][RefEditsExt::pre_process()].
+    fn validate(
+        &self,
+        store: &file::Store,
+        packed: Option<&packed::Buffer>,
+    ) -> Result<(), file::transaction::validate::Error>;
+
     /// All processing steps in one and in the correct order.
     ///
     /// Users call this to assure derefs are honored and duplicate checks are done.
@@ -53,6 +68,14 @@ where
         }
     }
 
+    fn validate(
+        &self,
+        store: &file::Store,
+        packed: Option<&packed::Buffer>,
+    ) -> Result<(), file::transaction::validate::Error> {
+        store.validate_edits(self, packed)
+    }
+
     fn extend_with_splits_of_symbolic_refs(
         &mut self,
         mut find: impl FnMut(PartialNameRef<'_>) -> Option<Target>,
@@ -72,6 +95,14 @@ where
                 // In any case, we don't want the following algorithms to try dereffing it and assume they deal with
                 // broken refs gracefully.
                 edit.deref = false;
+                if let Change::Update {
+                    new: Target::Symbolic(_),
+                    ..
+                } = edit.change
+                {
+                    // Symbolic refs are retargeted themselves, the reference they previously pointed to must stay untouched.
+                    continue;
+                }
                 if let Some(Target::Symbolic(referent)) = find(edit.name.to_partial()) {
                     new_edits.push(make_entry(
                         eid,
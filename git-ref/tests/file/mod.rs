@@ -23,3 +23,4 @@ mod log;
 mod reference;
 mod store;
 mod transaction;
+mod worktree;
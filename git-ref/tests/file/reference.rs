@@ -169,6 +169,77 @@ mod peel {
         assert_eq!(r.name.as_bstr(), "refs/loop-a", "the ref is not changed on error");
         Ok(())
     }
+
+    mod chains {
+        use git_ref::{
+            file::{self, ReferenceExt},
+            peel,
+        };
+
+        use crate::file::store_at;
+
+        fn no_lookup(
+            _id: git_hash::ObjectId,
+            _buf: &mut Vec<u8>,
+        ) -> Result<Option<(git_object::Kind, &[u8])>, std::convert::Infallible> {
+            unreachable!("the packed peeled id is used instead of looking up objects")
+        }
+
+        #[test]
+        fn symbolic_chain_to_packed_tag_uses_peeled_id_from_packed_refs() -> crate::Result {
+            let store = store_at("make_repository_for_peeling.sh")?;
+            let packed = store.packed_buffer()?;
+            let commit = store.find("main", packed.as_ref())?.target.into_id();
+
+            let mut r = store.find("chain-1", packed.as_ref())?;
+            assert_eq!(r.peel_to_id_in_place(&store, packed.as_ref(), no_lookup)?, commit);
+            assert_eq!(
+                r.name.as_bstr(),
+                "refs/tags/nested",
+                "the last reference of the chain of three is the tag of a tag"
+            );
+            assert_eq!(r.target.into_id(), commit);
+            Ok(())
+        }
+
+        #[test]
+        fn at_most_five_references_are_read_like_git_does() -> crate::Result {
+            let store = store_at("make_repository_for_peeling.sh")?;
+            let packed = store.packed_buffer()?;
+            let commit = store.find("main", packed.as_ref())?.target.into_id();
+
+            let mut r = store.find("deep-2", packed.as_ref())?;
+            assert_eq!(r.peel_to_id_in_place(&store, packed.as_ref(), peel::none)?, commit);
+
+            let mut r = store.find("deep-1", packed.as_ref())?;
+            assert!(matches!(
+                r.peel_to_id_in_place(&store, packed.as_ref(), peel::none),
+                Err(peel::to_id::Error::DepthLimitExceeded { max_depth: 5 })
+            ));
+            Ok(())
+        }
+
+        #[test]
+        fn cycles_and_dangling_references_are_distinct_errors() -> crate::Result {
+            let store = store_at("make_repository_for_peeling.sh")?;
+            let packed = store.packed_buffer()?;
+
+            let mut r = store.find("cycle-a", packed.as_ref())?;
+            assert!(matches!(
+                r.peel_to_id_in_place(&store, packed.as_ref(), peel::none),
+                Err(peel::to_id::Error::Cycle(_))
+            ));
+
+            let mut r = store.find("dangling", packed.as_ref())?;
+            match r.peel_to_id_in_place(&store, packed.as_ref(), peel::none) {
+                Err(peel::to_id::Error::Follow(file::find::existing::Error::NotFound(path))) => {
+                    assert_eq!(path, std::path::Path::new("refs/heads/does-not-exist"))
+                }
+                res => panic!("unexpected result: {:?}", res),
+            }
+            Ok(())
+        }
+    }
 }
 
 mod parse {
@@ -15,6 +15,62 @@ mod existing {
     }
 }
 
+mod with_namespace {
+    use std::convert::TryFrom;
+
+    use git_ref::Namespace;
+
+    use crate::file::store_at;
+
+    #[test]
+    fn the_same_lookups_yield_namespaced_references_only_within_refs() -> crate::Result {
+        let mut store = store_at("make_namespaced_packed_ref_repository.sh")?;
+        let packed = store.packed_buffer()?;
+        for (namespace, expectations) in &[
+            (
+                None,
+                [
+                    ("HEAD", Some("HEAD")),
+                    ("main", Some("refs/heads/main")),
+                    ("d1", Some("refs/heads/d1")),
+                    ("origin/main", None),
+                    ("origin", None),
+                    ("refs/namespaces/foo/refs/d1", Some("refs/namespaces/foo/refs/d1")),
+                ],
+            ),
+            (
+                Some("foo"),
+                [
+                    ("HEAD", Some("HEAD")),
+                    ("main", None),
+                    ("d1", Some("refs/d1")),
+                    ("origin/main", Some("refs/remotes/origin/main")),
+                    ("origin", Some("refs/remotes/origin/HEAD")),
+                    ("refs/namespaces/foo/refs/d1", Some("refs/d1")),
+                ],
+            ),
+        ] {
+            store.namespace = namespace.map(Namespace::try_from).transpose()?;
+            for (partial_name, expected_name) in expectations {
+                let reference = store.try_find(*partial_name, packed.as_ref())?;
+                assert_eq!(
+                    reference.as_ref().map(|r| r.name.as_bstr().to_string()).as_deref(),
+                    *expected_name,
+                    "{} in namespace {:?}",
+                    partial_name,
+                    namespace
+                );
+            }
+            assert_eq!(
+                store.find_loose("HEAD")?.target.as_name().map(ToOwned::to_owned),
+                Some("refs/heads/main".into()),
+                "HEAD is never namespaced"
+            );
+        }
+        Ok(())
+    }
+}
+
 mod loose {
     use crate::file::store;
 
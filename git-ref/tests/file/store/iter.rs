@@ -159,11 +159,12 @@ mod with_namespace {
                 fullname,
                 "it finds namespaced items by fully qualified name, excluding namespace"
             );
-            assert!(
+            assert_eq!(
                 ns_store
-                    .try_find(fullname.clone().prefix_namespace(&ns_two).to_partial(), packed.as_ref())?
-                    .is_none(),
-                "it won't find namespaced items by their store-relative name with namespace"
+                    .find(fullname.clone().prefix_namespace(&ns_two).to_partial(), packed.as_ref())?
+                    .name,
+                fullname,
+                "names explicitly within a namespace aren't prefixed once more"
             );
             assert_eq!(
                 ns_store
@@ -197,6 +198,21 @@ mod with_namespace {
             "loose iterators have no namespace support at all"
         );
 
+        assert_eq!(
+            ns_store
+                .iter_prefixed(packed.as_ref(), ns_two.to_path())?
+                .map(Result::unwrap)
+                .map(|r: git_ref::Reference| r.name.into_inner())
+                .collect::<Vec<_>>(),
+            [
+                "refs/namespaces/bar/refs/heads/multi-link-target1",
+                "refs/namespaces/bar/refs/multi-link",
+                "refs/namespaces/bar/refs/remotes/origin/multi-link-target3",
+                "refs/namespaces/bar/refs/tags/multi-link-target2",
+            ],
+            "prefixes within a namespace aren't subject to the store namespace, which isn't stripped either"
+        );
+
         let ns_one = git_ref::namespace::expand("foo")?;
         ns_store.namespace = ns_one.into();
 
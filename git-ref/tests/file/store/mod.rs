@@ -1,3 +1,6 @@
 mod find;
 mod iter;
+mod pack_refs;
+mod packed;
 mod reflog;
+mod structure;
@@ -0,0 +1,189 @@
+use std::{convert::TryInto, path::Path};
+
+use git_lock::acquire::Fail;
+use git_ref::{
+    file,
+    file::pack_refs::Mode,
+    transaction::{Change, PreviousValue, RefEdit, RefLog},
+    FullName,
+};
+
+use crate::file::store_writable;
+
+fn find_in(store: &file::Store) -> crate::Result<Box<file::transaction::FindObjectFn>> {
+    let odb = git_odb::compound::Store::at(store.base.join("objects"))?;
+    Ok(Box::new(move |oid, buf| {
+        odb.try_find(oid, buf, &mut git_odb::pack::cache::Never)
+            .map(|obj| obj.map(|obj| obj.kind))
+            .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync>)
+    }))
+}
+
+fn git(worktree: &Path, args: &[&str]) -> crate::Result<String> {
+    let out = std::process::Command::new("git")
+        .arg("-C")
+        .arg(worktree)
+        .args(args)
+        .output()?;
+    assert!(out.status.success(), "{:?} failed: {:?}", args, out);
+    Ok(String::from_utf8(out.stdout)?)
+}
+
+fn for_each_ref(worktree: &Path) -> crate::Result<String> {
+    git(
+        worktree,
+        &[
+            "for-each-ref",
+            "--format=%(objectname) %(objecttype) %(refname) %(symref)",
+        ],
+    )
+}
+
+fn loose_names(store: &file::Store) -> crate::Result<Vec<String>> {
+    let mut names = store
+        .loose_iter()?
+        .map(|r| r.map(|r| r.name.as_bstr().to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    names.sort();
+    Ok(names)
+}
+
+fn names(names: Vec<FullName>) -> Vec<String> {
+    let mut names: Vec<_> = names.into_iter().map(|name| name.as_bstr().to_string()).collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn all_refs_are_packed_like_git_does_without_changing_what_git_sees() -> crate::Result {
+    let (dir, store) = store_writable("make_repo_for_pack_refs.sh")?;
+    let before = for_each_ref(dir.path())?;
+
+    let packed = store.pack_refs(Mode::AllRefs, Fail::Immediately, find_in(&store)?)?;
+    assert_eq!(
+        names(packed),
+        vec![
+            "refs/heads/loose-branch",
+            "refs/heads/main",
+            "refs/heads/packed-branch",
+            "refs/tags/loose-annotated"
+        ]
+    );
+    assert_eq!(
+        loose_names(&store)?,
+        vec!["refs/remotes/origin/HEAD"],
+        "only symbolic refs remain loose"
+    );
+    assert!(
+        store.find_loose("HEAD").is_ok(),
+        "references outside of refs/ are never packed"
+    );
+    assert_eq!(for_each_ref(dir.path())?, before);
+    assert!(
+        store.base.join("logs/refs/heads/main").is_file(),
+        "reflogs are kept, and aren't written to either"
+    );
+
+    let (git_dir, git_store) = store_writable("make_repo_for_pack_refs.sh")?;
+    git(git_dir.path(), &["pack-refs", "--all", "--prune"])?;
+    assert_eq!(
+        std::fs::read(store.packed_refs_path())?,
+        std::fs::read(git_store.packed_refs_path())?,
+        "git agrees on the packed-refs file, including the peeled objects of annotated tags"
+    );
+
+    assert!(
+        store
+            .pack_refs(Mode::AllRefs, Fail::Immediately, find_in(&store)?)?
+            .is_empty(),
+        "there is nothing left to pack"
+    );
+    Ok(())
+}
+
+#[test]
+fn by_default_only_tags_and_already_packed_refs_are_packed() -> crate::Result {
+    let (dir, store) = store_writable("make_repo_for_pack_refs.sh")?;
+    let before = for_each_ref(dir.path())?;
+
+    let packed = store.pack_refs(Mode::default(), Fail::Immediately, find_in(&store)?)?;
+    assert_eq!(
+        names(packed),
+        vec![
+            "refs/heads/main",
+            "refs/heads/packed-branch",
+            "refs/tags/loose-annotated"
+        ]
+    );
+    assert_eq!(
+        loose_names(&store)?,
+        vec!["refs/heads/loose-branch", "refs/remotes/origin/HEAD"]
+    );
+    assert_eq!(for_each_ref(dir.path())?, before);
+    Ok(())
+}
+
+#[test]
+fn interrupted_packing_leaves_loose_refs_which_resolve_like_their_packed_counterparts() -> crate::Result {
+    let (dir, store) = store_writable("make_repo_for_pack_refs.sh")?;
+    let before = for_each_ref(dir.path())?;
+
+    // Simulate an interruption after writing packed-refs, but before the loose refs were deleted.
+    git(dir.path(), &["pack-refs", "--all", "--no-prune"])?;
+    let packed = store.packed_buffer()?.expect("packed refs were written");
+    for name in &["main", "loose-branch", "loose-annotated"] {
+        assert_eq!(
+            store.find_loose(*name)?.target.into_id(),
+            packed.find(*name)?.target(),
+            "loose and packed refs agree"
+        );
+        assert_eq!(
+            store.find(*name, Some(&packed))?.target.into_id(),
+            packed.find(*name)?.target()
+        );
+    }
+    assert_eq!(for_each_ref(dir.path())?, before);
+    drop(packed);
+
+    let packed = store.pack_refs(Mode::AllRefs, Fail::Immediately, find_in(&store)?)?;
+    assert_eq!(
+        names(packed),
+        vec![
+            "refs/heads/loose-branch",
+            "refs/heads/main",
+            "refs/heads/packed-branch",
+            "refs/tags/loose-annotated"
+        ],
+        "all loose refs are packed again and removed"
+    );
+    assert_eq!(loose_names(&store)?, vec!["refs/remotes/origin/HEAD"]);
+    assert_eq!(for_each_ref(dir.path())?, before);
+    Ok(())
+}
+
+#[test]
+fn deletions_after_packing_remove_packed_refs() -> crate::Result {
+    let (dir, store) = store_writable("make_repo_for_pack_refs.sh")?;
+    store.pack_refs(Mode::AllRefs, Fail::Immediately, find_in(&store)?)?;
+
+    store
+        .transaction()
+        .prepare(
+            Some(RefEdit {
+                change: Change::Delete {
+                    expected: PreviousValue::MustExist,
+                    log: RefLog::AndReference,
+                },
+                name: "refs/heads/loose-branch".try_into()?,
+                deref: false,
+            }),
+            Fail::Immediately,
+        )?
+        .commit(&git_actor::Signature::empty())?;
+
+    let packed = store.packed_buffer()?.expect("still present");
+    assert!(packed.find("loose-branch").is_err(), "the packed ref was removed");
+    assert!(store.try_find("loose-branch", Some(&packed))?.is_none());
+    assert!(!for_each_ref(dir.path())?.contains("refs/heads/loose-branch"));
+    Ok(())
+}
@@ -0,0 +1,263 @@
+mod transaction {
+    use std::convert::TryInto;
+
+    use git_lock::acquire::Fail;
+    use git_ref::{
+        packed,
+        transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+        Target,
+    };
+
+    use crate::file::store_writable;
+
+    fn update(name: &str, target: Target) -> RefEdit {
+        RefEdit {
+            change: Change::Update {
+                log: LogChange::default(),
+                expected: PreviousValue::Any,
+                new: target,
+            },
+            name: name.try_into().expect("valid name"),
+            deref: false,
+        }
+    }
+
+    fn delete(name: &str) -> RefEdit {
+        RefEdit {
+            change: Change::Delete {
+                expected: PreviousValue::Any,
+                log: RefLog::AndReference,
+            },
+            name: name.try_into().expect("valid name"),
+            deref: false,
+        }
+    }
+
+    fn all_objects_are_commits(
+        _id: git_hash::ObjectId,
+        _buf: &mut Vec<u8>,
+    ) -> Result<Option<git_object::Kind>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        Ok(Some(git_object::Kind::Commit))
+    }
+
+    #[test]
+    fn changes_are_written_to_a_lock_file_which_replaces_packed_refs_on_commit() -> crate::Result {
+        let (_keep, store) = store_writable("make_packed_ref_repository.sh")?;
+        let main = store.packed_buffer()?.expect("packed refs").find("main")?.target();
+        let lock_path = store.base.join("packed-refs.lock");
+
+        let transaction = store.packed_transaction(Fail::Immediately)?;
+        assert!(lock_path.is_file(), "the lock is held while the transaction is alive");
+        let transaction = transaction.prepare(
+            vec![delete("refs/heads/d1"), update("refs/heads/new", Target::Peeled(main))],
+            &mut all_objects_are_commits,
+        )?;
+        assert!(
+            store
+                .packed_buffer()?
+                .expect("packed refs")
+                .find("refs/heads/d1")
+                .is_ok(),
+            "nothing changes before the commit"
+        );
+
+        transaction.commit()?;
+        assert!(!lock_path.exists(), "the lock was moved into place");
+        let buffer = store.packed_buffer()?.expect("packed refs");
+        assert!(buffer.try_find("refs/heads/d1")?.is_none());
+        assert_eq!(buffer.find("new")?.target(), main);
+        assert_eq!(buffer.find("main")?.target(), main, "other refs are retained");
+        Ok(())
+    }
+
+    #[test]
+    fn dropping_a_transaction_leaves_packed_refs_untouched() -> crate::Result {
+        let (_keep, store) = store_writable("make_packed_ref_repository.sh")?;
+        let before = std::fs::read(store.packed_refs_path())?;
+        let transaction = store
+            .packed_transaction(Fail::Immediately)?
+            .prepare(vec![delete("refs/heads/d1")], &mut all_objects_are_commits)?;
+        drop(transaction);
+
+        assert_eq!(std::fs::read(store.packed_refs_path())?, before);
+        assert!(!store.base.join("packed-refs.lock").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn concurrent_writers_are_serialized_by_the_lock() -> crate::Result {
+        let (_keep, store) = store_writable("make_packed_ref_repository.sh")?;
+        let main = store.packed_buffer()?.expect("packed refs").find("main")?.target();
+
+        let first = store.packed_transaction(Fail::Immediately)?;
+        assert!(
+            matches!(
+                store.packed_transaction(Fail::Immediately),
+                Err(git_ref::file::packed::transaction::Error::TransactionLock(_))
+            ),
+            "a second writer can't obtain the lock while the first one holds it"
+        );
+        first
+            .prepare(
+                vec![update("refs/heads/first", Target::Peeled(main))],
+                &mut all_objects_are_commits,
+            )?
+            .commit()?;
+
+        store
+            .packed_transaction(Fail::Immediately)?
+            .prepare(
+                vec![update("refs/heads/second", Target::Peeled(main))],
+                &mut all_objects_are_commits,
+            )?
+            .commit()?;
+
+        let buffer = store.packed_buffer()?.expect("packed refs");
+        assert!(
+            buffer.iter()?.all(|r| r.is_ok()),
+            "the file is never corrupted by competing writers"
+        );
+        assert_eq!(
+            buffer.find("first")?.target(),
+            main,
+            "the first writer's changes were kept"
+        );
+        assert_eq!(buffer.find("second")?.target(), main);
+        Ok(())
+    }
+
+    #[test]
+    fn multiple_edits_of_the_same_name_are_rejected() -> crate::Result {
+        let (_keep, store) = store_writable("make_packed_ref_repository.sh")?;
+        let err = store
+            .packed_transaction(Fail::Immediately)?
+            .prepare(
+                vec![delete("refs/heads/d1"), delete("refs/heads/d1")],
+                &mut all_objects_are_commits,
+            )
+            .err()
+            .expect("duplicate names fail");
+        assert!(matches!(err, packed::transaction::prepare::Error::DuplicateEdit { .. }));
+        assert_eq!(err.to_string(), "A reference named 'refs/heads/d1' has multiple edits");
+        Ok(())
+    }
+
+    #[test]
+    fn symbolic_targets_are_rejected() -> crate::Result {
+        let (_keep, store) = store_writable("make_packed_ref_repository.sh")?;
+        let err = store
+            .packed_transaction(Fail::Immediately)?
+            .prepare(
+                vec![update(
+                    "refs/heads/symbolic",
+                    Target::Symbolic("refs/heads/main".try_into()?),
+                )],
+                &mut all_objects_are_commits,
+            )
+            .err()
+            .expect("symbolic refs can't be packed");
+        assert!(matches!(
+            err,
+            packed::transaction::prepare::Error::SymbolicTarget { .. }
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn previous_values_are_checked_against_packed_refs_while_holding_the_lock() -> crate::Result {
+        let (_keep, store) = store_writable("make_packed_ref_repository.sh")?;
+        let main = store.packed_buffer()?.expect("packed refs").find("main")?.target();
+        let null = git_hash::ObjectId::null_sha1();
+        let with_expected = |mut edit: RefEdit, previous: PreviousValue| {
+            match &mut edit.change {
+                Change::Update { expected, .. } | Change::Delete { expected, .. } => *expected = previous,
+            }
+            edit
+        };
+
+        for (edit, expected_err) in [
+            (
+                with_expected(
+                    update("refs/heads/main", Target::Peeled(null)),
+                    PreviousValue::MustExistAndMatch(Target::Peeled(null)),
+                ),
+                "The reference 'refs/heads/main' should have content 0000000000000000000000000000000000000000, actual content was 134385f6d781b7e97062102c6a483440bfda2a03",
+            ),
+            (
+                with_expected(delete("refs/heads/does-not-exist"), PreviousValue::MustExist),
+                "Reference 'refs/heads/does-not-exist' was supposed to exist, but didn't",
+            ),
+            (
+                with_expected(
+                    update("refs/heads/main", Target::Peeled(null)),
+                    PreviousValue::MustNotExist,
+                ),
+                "Reference 'refs/heads/main' was not supposed to exist, but its value was 134385f6d781b7e97062102c6a483440bfda2a03",
+            ),
+        ] {
+            let err = store
+                .packed_transaction(Fail::Immediately)?
+                .prepare(Some(edit), &mut all_objects_are_commits)
+                .err()
+                .expect("the previous value doesn't match");
+            assert_eq!(err.to_string(), expected_err);
+        }
+
+        store
+            .packed_transaction(Fail::Immediately)?
+            .prepare(
+                vec![
+                    with_expected(
+                        update("refs/heads/main", Target::Peeled(null)),
+                        PreviousValue::MustExistAndMatch(Target::Peeled(main)),
+                    ),
+                    with_expected(
+                        update("refs/heads/new", Target::Peeled(main)),
+                        PreviousValue::ExistingMustMatch(Target::Peeled(null)),
+                    ),
+                ],
+                &mut all_objects_are_commits,
+            )?
+            .commit()?;
+        let buffer = store.packed_buffer()?.expect("packed refs");
+        assert_eq!(buffer.find("main")?.target(), null);
+        assert_eq!(buffer.find("new")?.target(), main);
+        Ok(())
+    }
+
+    #[test]
+    fn queued_upserts_and_deletions_are_committed_without_peeling() -> crate::Result {
+        let (_keep, store) = store_writable("make_packed_ref_repository.sh")?;
+        let main = store.packed_buffer()?.expect("packed refs").find("main")?.target();
+
+        let mut transaction = packed::Transaction::new(&store)?;
+        transaction
+            .upsert("refs/heads/new".try_into()?, main)
+            .delete("refs/heads/d1".try_into()?);
+        transaction.commit()?;
+
+        let buffer = store.packed_buffer()?.expect("packed refs");
+        assert!(buffer.try_find("refs/heads/d1")?.is_none());
+        assert_eq!(buffer.find("new")?.target(), main);
+        assert_eq!(buffer.find("main")?.target(), main, "other refs are retained");
+        assert!(
+            std::fs::read(store.packed_refs_path())?.starts_with(b"# pack-refs with: sorted \n"),
+            "peeled values of new refs are unknown, so readers must not rely on them"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn queued_edits_of_the_same_name_are_rejected_on_commit() -> crate::Result {
+        let (_keep, store) = store_writable("make_packed_ref_repository.sh")?;
+        let before = std::fs::read(store.packed_refs_path())?;
+        let mut transaction = packed::Transaction::new(&store)?;
+        transaction
+            .delete("refs/heads/d1".try_into()?)
+            .delete("refs/heads/d1".try_into()?);
+        let err = transaction.commit().expect_err("duplicate names fail");
+        assert_eq!(err.to_string(), "The queued edits could not be prepared");
+        assert_eq!(std::fs::read(store.packed_refs_path())?, before, "nothing was changed");
+        Ok(())
+    }
+}
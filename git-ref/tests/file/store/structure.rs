@@ -0,0 +1,150 @@
+use std::path::Path;
+
+use git_ref::file::{
+    self,
+    structure::{Anomaly, Outcome},
+};
+
+fn store_with(setup: impl FnOnce(&Path) -> std::io::Result<()>) -> crate::Result<(tempfile::TempDir, file::Store)> {
+    let dir = tempfile::tempdir()?;
+    setup(dir.path())?;
+    let store = file::Store::at(dir.path(), Default::default());
+    Ok((dir, store))
+}
+
+fn valid_head(base: &Path) -> std::io::Result<()> {
+    std::fs::write(base.join("HEAD"), b"ref: refs/heads/main\n")
+}
+
+#[test]
+fn missing_directories_are_created() -> crate::Result {
+    let (dir, store) = store_with(valid_head)?;
+    let outcome = store.verify_or_create_structure()?;
+    assert_eq!(
+        outcome,
+        Outcome {
+            created: vec![
+                dir.path().join("refs"),
+                dir.path().join("refs/heads"),
+                dir.path().join("refs/tags")
+            ],
+            anomalies: vec![]
+        }
+    );
+    assert!(outcome.is_valid());
+    assert!(dir.path().join("refs/heads").is_dir());
+    assert!(dir.path().join("refs/tags").is_dir());
+
+    let outcome = store.verify_or_create_structure()?;
+    assert_eq!(outcome, Outcome::default(), "the second run has nothing to do");
+    Ok(())
+}
+
+#[test]
+fn packed_refs_with_empty_refs_directory_is_repaired() -> crate::Result {
+    let (dir, store) = store_with(|base| {
+        valid_head(base)?;
+        std::fs::create_dir(base.join("refs"))?;
+        std::fs::write(
+            base.join("packed-refs"),
+            b"# pack-refs with: peeled fully-peeled sorted\n134385f6d781b7e97062102c6a483440bfda2a03 refs/heads/main\n",
+        )
+    })?;
+    let outcome = store.verify_or_create_structure()?;
+    assert_eq!(
+        outcome.created,
+        vec![dir.path().join("refs/heads"), dir.path().join("refs/tags")]
+    );
+    assert!(outcome.is_valid());
+    let packed = store.packed_buffer()?;
+    assert!(
+        store.try_find("main", packed.as_ref())?.is_some(),
+        "packed references remain accessible"
+    );
+    Ok(())
+}
+
+#[test]
+fn refs_file_is_reported_and_kept() -> crate::Result {
+    let (dir, store) = store_with(|base| {
+        valid_head(base)?;
+        std::fs::write(base.join("refs"), b"not a directory")
+    })?;
+    let outcome = store.verify_or_create_structure()?;
+    assert_eq!(
+        outcome,
+        Outcome {
+            created: vec![],
+            anomalies: vec![Anomaly::NotADirectory {
+                path: dir.path().join("refs")
+            }]
+        }
+    );
+    assert!(dir.path().join("refs").is_file(), "anomalies are never repaired");
+    Ok(())
+}
+
+#[test]
+fn heads_file_is_reported_and_tags_directory_created() -> crate::Result {
+    let (dir, store) = store_with(|base| {
+        valid_head(base)?;
+        std::fs::create_dir(base.join("refs"))?;
+        std::fs::write(base.join("refs/heads"), b"")
+    })?;
+    let outcome = store.verify_or_create_structure()?;
+    assert_eq!(
+        outcome,
+        Outcome {
+            created: vec![dir.path().join("refs/tags")],
+            anomalies: vec![Anomaly::NotADirectory {
+                path: dir.path().join("refs/heads")
+            }]
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn missing_head_is_reported() -> crate::Result {
+    let (_dir, store) = store_with(|_| Ok(()))?;
+    let outcome = store.verify_or_create_structure()?;
+    assert_eq!(outcome.anomalies, vec![Anomaly::MissingHead]);
+    assert_eq!(outcome.created.len(), 3, "directories are created nonetheless");
+    Ok(())
+}
+
+#[test]
+fn head_directory_is_reported() -> crate::Result {
+    let (dir, store) = store_with(|base| std::fs::create_dir(base.join("HEAD")))?;
+    let outcome = store.verify_or_create_structure()?;
+    assert_eq!(
+        outcome.anomalies,
+        vec![Anomaly::NotAFile {
+            path: dir.path().join("HEAD")
+        }]
+    );
+    Ok(())
+}
+
+#[test]
+fn invalid_head_is_reported() -> crate::Result {
+    for content in [&b"garbage\n"[..], b"ref: refs/heads/a..b\n", b"ref: HEAD\n", b""] {
+        let (_dir, store) = store_with(|base| std::fs::write(base.join("HEAD"), content))?;
+        let outcome = store.verify_or_create_structure()?;
+        assert_eq!(
+            outcome.anomalies,
+            vec![Anomaly::InvalidHead {
+                content: content.into()
+            }]
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn detached_head_is_valid() -> crate::Result {
+    let (_dir, store) =
+        store_with(|base| std::fs::write(base.join("HEAD"), b"134385f6d781b7e97062102c6a483440bfda2a03\n"))?;
+    assert!(store.verify_or_create_structure()?.is_valid());
+    Ok(())
+}
@@ -44,4 +44,12 @@ mod prepare_and_commit {
     mod create_or_update;
 
     mod delete;
+
+    mod journal;
+
+    mod observe;
+
+    mod read_only;
 }
+
+mod validate;
@@ -269,6 +269,10 @@ fn namespaced_updates_or_deletions_are_transparent_and_not_observable() -> crate
             }
         ]
     );
+    assert!(
+        store.base.join("HEAD").is_file(),
+        "HEAD is never namespaced, unlike references within refs/"
+    );
     Ok(())
 }
 
@@ -771,3 +775,169 @@ fn packed_refs_creation_with_packed_refs_mode_leave_keeps_original_loose_refs()
     );
     Ok(())
 }
+
+#[test]
+fn symbolic_head_created_along_with_its_referent_in_empty_repository_writes_reflog() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    let new_oid = hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242");
+    let log = LogChange {
+        mode: RefLog::AndReference,
+        force_create_reflog: false,
+        message: "init".into(),
+    };
+    store
+        .transaction()
+        .prepare(
+            vec![
+                RefEdit {
+                    change: Change::Update {
+                        log: log.clone(),
+                        expected: PreviousValue::MustNotExist,
+                        new: Target::Peeled(new_oid),
+                    },
+                    name: "refs/heads/main".try_into()?,
+                    deref: false,
+                },
+                RefEdit {
+                    change: Change::Update {
+                        log,
+                        expected: PreviousValue::MustNotExist,
+                        new: Target::Symbolic("refs/heads/main".try_into()?),
+                    },
+                    name: "HEAD".try_into()?,
+                    deref: true,
+                },
+            ],
+            Fail::Immediately,
+        )?
+        .commit(&committer())?;
+
+    let head = store.find_loose("HEAD")?;
+    assert_eq!(head.target, Target::Symbolic("refs/heads/main".try_into()?));
+    assert_eq!(
+        reflog_lines(&store, "HEAD")?,
+        vec![log_line(ObjectId::null_sha1(), new_oid, "init")],
+        "the reflog of HEAD shows the object it now points to through its referent"
+    );
+    Ok(())
+}
+
+#[test]
+fn symbolic_head_switches_between_branches_without_touching_the_previous_branch() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    let main_id = hex_to_id("02a7a22d90d7c02fb494ed25551850b868e634f0");
+    let feature_id = hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03");
+    store
+        .transaction()
+        .prepare(
+            Some(RefEdit {
+                change: Change::Update {
+                    log: LogChange::default(),
+                    expected: PreviousValue::MustNotExist,
+                    new: Target::Peeled(feature_id),
+                },
+                name: "refs/heads/feature".try_into()?,
+                deref: false,
+            }),
+            Fail::Immediately,
+        )?
+        .commit(&committer())?;
+    let previous_head_reflog = reflog_lines(&store, "HEAD")?;
+    let previous_main_reflog = reflog_lines(&store, "refs/heads/main")?;
+
+    let edits = store
+        .transaction()
+        .prepare(
+            Some(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: "checkout: moving from main to feature".into(),
+                    },
+                    expected: PreviousValue::MustExistAndMatch(Target::Symbolic("refs/heads/main".try_into()?)),
+                    new: Target::Symbolic("refs/heads/feature".try_into()?),
+                },
+                name: "HEAD".try_into()?,
+                deref: true,
+            }),
+            Fail::Immediately,
+        )?
+        .commit(&committer())?;
+
+    assert_eq!(edits.len(), 1, "retargeting a symbolic ref never splits it");
+    assert_eq!(
+        store.find_loose("HEAD")?.target,
+        Target::Symbolic("refs/heads/feature".try_into()?)
+    );
+    assert_eq!(
+        store.find_loose("main")?.target,
+        Target::Peeled(main_id),
+        "the previous branch wasn't changed"
+    );
+    assert_eq!(reflog_lines(&store, "refs/heads/main")?, previous_main_reflog);
+
+    let head_reflog = reflog_lines(&store, "HEAD")?;
+    assert_eq!(head_reflog.len(), previous_head_reflog.len() + 1);
+    assert_eq!(
+        head_reflog.last().expect("at least one line"),
+        &log_line(main_id, feature_id, "checkout: moving from main to feature")
+    );
+
+    store
+        .transaction()
+        .prepare(
+            Some(RefEdit {
+                change: Change::Update {
+                    log: LogChange::default(),
+                    expected: PreviousValue::MustExistAndMatch(Target::Peeled(feature_id)),
+                    new: Target::Symbolic("refs/heads/main".try_into()?),
+                },
+                name: "HEAD".try_into()?,
+                deref: false,
+            }),
+            Fail::Immediately,
+        )?
+        .commit(&committer())?;
+    assert_eq!(
+        store.find_loose("HEAD")?.target,
+        Target::Symbolic("refs/heads/main".try_into()?),
+        "a peeled expectation is matched against the object the symbolic ref points to"
+    );
+    Ok(())
+}
+
+#[test]
+fn symbolic_head_switch_fails_if_its_previous_target_changed_concurrently() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    let res = store.transaction().prepare(
+        Some(RefEdit {
+            change: Change::Update {
+                log: LogChange::default(),
+                expected: PreviousValue::MustExistAndMatch(Target::Symbolic("refs/heads/other".try_into()?)),
+                new: Target::Symbolic("refs/heads/feature".try_into()?),
+            },
+            name: "HEAD".try_into()?,
+            deref: true,
+        }),
+        Fail::Immediately,
+    );
+    match res {
+        Err(transaction::prepare::Error::ReferenceOutOfDate {
+            full_name,
+            expected,
+            actual,
+        }) => {
+            assert_eq!(full_name, "HEAD");
+            assert_eq!(expected, Target::Symbolic("refs/heads/other".try_into()?));
+            assert_eq!(actual, Target::Symbolic("refs/heads/main".try_into()?));
+        }
+        _ => unreachable!("unexpected result"),
+    }
+    assert_eq!(
+        store.find_loose("HEAD")?.target,
+        Target::Symbolic("refs/heads/main".try_into()?),
+        "nothing was changed"
+    );
+    Ok(())
+}
@@ -0,0 +1,342 @@
+use std::{collections::BTreeMap, convert::TryInto};
+
+use git_hash::ObjectId;
+use git_lock::acquire::Fail;
+use git_ref::{
+    file,
+    file::{journal, transaction::prepare},
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+use git_testtools::hex_to_id;
+
+use crate::file::transaction::prepare_and_commit::{committer, empty_store};
+
+fn update(name: &str, new: ObjectId) -> crate::Result<RefEdit> {
+    Ok(RefEdit {
+        change: Change::Update {
+            log: LogChange::default(),
+            expected: PreviousValue::Any,
+            new: Target::Peeled(new),
+        },
+        name: name.try_into()?,
+        deref: false,
+    })
+}
+
+fn delete(name: &str) -> crate::Result<RefEdit> {
+    Ok(RefEdit {
+        change: Change::Delete {
+            expected: PreviousValue::MustExist,
+            log: RefLog::AndReference,
+        },
+        name: name.try_into()?,
+        deref: false,
+    })
+}
+
+fn commit(store: &file::Store, journal: &mut file::Journal, edits: Vec<RefEdit>) -> crate::Result {
+    store
+        .transaction()
+        .observer(Box::new(journal))
+        .prepare(edits, Fail::Immediately)?
+        .commit(&committer())?;
+    Ok(())
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("after epoch")
+        .as_secs()
+}
+
+fn replay(records: &[journal::Record]) -> BTreeMap<FullName, ObjectId> {
+    let mut state = BTreeMap::new();
+    for record in records {
+        match record.new {
+            Some(id) => state.insert(record.name.clone(), id),
+            None => state.remove(&record.name),
+        };
+    }
+    state
+}
+
+#[test]
+fn mixed_transactions_can_be_replayed_to_reconstruct_all_references() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    let (a, b) = (
+        hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242"),
+        hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03"),
+    );
+    let mut journal = store.journal(Default::default());
+    let start = now();
+
+    commit(
+        &store,
+        &mut journal,
+        vec![update("refs/heads/main", a)?, update("refs/heads/feature", a)?],
+    )?;
+    commit(
+        &store,
+        &mut journal,
+        vec![update("refs/heads/main", b)?, delete("refs/heads/feature")?],
+    )?;
+    let aborted = store
+        .transaction()
+        .observer(Box::new(&mut journal))
+        .prepare(Some(update("refs/heads/aborted", a)?), Fail::Immediately)?;
+    drop(aborted);
+    commit(
+        &store,
+        &mut journal,
+        vec![update("refs/tags/v1", b)?, update("refs/heads/feature", b)?],
+    )?;
+    commit(&store, &mut journal, vec![delete("refs/tags/v1")?])?;
+    assert!(journal.take_errors().is_empty());
+
+    let records = store.journal_records(..)?;
+    assert_eq!(
+        records.iter().map(|r| r.transaction).collect::<Vec<_>>(),
+        vec![1, 1, 2, 2, 3, 3, 4],
+        "aborted transactions aren't recorded and don't consume an id"
+    );
+    assert_eq!(records[2].previous, a, "previous values are known after preparation");
+    assert_eq!(records[6].new, None, "deletions leave a tombstone");
+    assert!(records
+        .iter()
+        .all(|r| r.seconds_since_unix_epoch >= start && r.seconds_since_unix_epoch <= now()));
+
+    let mut expected = BTreeMap::new();
+    for name in &[
+        "refs/heads/main",
+        "refs/heads/feature",
+        "refs/tags/v1",
+        "refs/heads/aborted",
+    ] {
+        if let Some(r) = store.try_find_loose(*name)? {
+            expected.insert(r.name, r.target.into_id());
+        }
+    }
+    assert_eq!(replay(&records), expected, "the journal reconstructs the final state");
+    assert_eq!(expected.len(), 2);
+
+    assert_eq!(store.journal_records(start..=now())?, records);
+    assert!(
+        store.journal_records(..start)?.is_empty(),
+        "records can be filtered by the time of their transaction"
+    );
+    Ok(())
+}
+
+#[test]
+fn journals_are_rotated_once_they_exceed_their_size_limit() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    let a = hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242");
+    let mut journal = store.journal(journal::Options {
+        rotate_after_bytes: 1,
+        ..Default::default()
+    });
+
+    for name in &["refs/heads/one", "refs/heads/two", "refs/heads/three"] {
+        commit(&store, &mut journal, vec![update(name, a)?])?;
+    }
+
+    let records = store.journal_records(..)?;
+    assert_eq!(
+        records
+            .iter()
+            .map(|r| (r.transaction, r.name.as_bstr().to_string()))
+            .collect::<Vec<_>>(),
+        vec![(2, "refs/heads/two".into()), (3, "refs/heads/three".into())],
+        "only the current and the previously rotated journal are kept, and ids continue across rotations"
+    );
+    Ok(())
+}
+
+#[test]
+fn records_are_appended_and_partial_lines_of_interrupted_appends_are_overwritten() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    let a = hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242");
+    let mut journal = store.journal(Default::default());
+    commit(&store, &mut journal, vec![update("refs/heads/one", a)?])?;
+    let first = std::fs::read(store.journal_path())?;
+
+    let mut interrupted = first.clone();
+    interrupted.extend_from_slice(b"1 2 28ce6a8b26aa");
+    std::fs::write(store.journal_path(), &interrupted)?;
+    assert_eq!(
+        store.journal_records(..)?.len(),
+        1,
+        "the partial line is skipped when reading"
+    );
+
+    commit(&store, &mut journal, vec![update("refs/heads/two", a)?])?;
+    assert!(journal.take_errors().is_empty());
+    let second = std::fs::read(store.journal_path())?;
+    assert!(second.starts_with(&first), "existing records are kept as they are");
+    assert_eq!(
+        store
+            .journal_records(..)?
+            .iter()
+            .map(|r| (r.transaction, r.name.as_bstr().to_string()))
+            .collect::<Vec<_>>(),
+        vec![(1, "refs/heads/one".into()), (2, "refs/heads/two".into())],
+        "the partial line was replaced by the records of the next transaction"
+    );
+
+    let many = (0..50)
+        .map(|n| update(&format!("refs/heads/many-{}", n), a))
+        .collect::<Result<Vec<_>, _>>()?;
+    commit(&store, &mut journal, many)?;
+    commit(&store, &mut journal, vec![update("refs/heads/last", a)?])?;
+    assert!(std::fs::metadata(store.journal_path())?.len() > 4096);
+    let records = store.journal_records(..)?;
+    assert_eq!(records.len(), 2 + 50 + 1);
+    assert_eq!(
+        records.last().map(|r| r.transaction),
+        Some(4),
+        "only the end of large journals is read to continue counting"
+    );
+    Ok(())
+}
+
+#[test]
+fn without_journal_nothing_is_written() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    store
+        .transaction()
+        .prepare(
+            Some(update(
+                "refs/heads/main",
+                hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242"),
+            )?),
+            Fail::Immediately,
+        )?
+        .commit(&committer())?;
+    assert!(!store.journal_path().exists());
+    assert!(store.journal_records(..)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn journal_failures_are_collected_or_roll_back_the_transaction_in_strict_mode() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    let a = hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242");
+    std::fs::create_dir(store.journal_path())?;
+
+    let mut journal = store.journal(journal::Options {
+        strict: true,
+        ..Default::default()
+    });
+    let res = store
+        .transaction()
+        .observer(Box::new(&mut journal))
+        .prepare(Some(update("refs/heads/main", a)?), Fail::Immediately)
+        .map(|_| ());
+    assert!(matches!(res, Err(prepare::Error::Rejected(_))));
+    assert!(
+        store.try_find_loose("main")?.is_none(),
+        "the transaction was rolled back"
+    );
+    assert!(journal.take_errors().is_empty(), "the error was returned instead");
+
+    let mut journal = store.journal(Default::default());
+    commit(&store, &mut journal, vec![update("refs/heads/main", a)?])?;
+    assert_eq!(
+        store.find_loose("main")?.target.into_id(),
+        a,
+        "the update was committed"
+    );
+    let errors = journal.take_errors();
+    assert_eq!(errors.len(), 1, "but the failure to write the journal is reported");
+    assert!(matches!(errors[0], journal::Error::Io { .. }));
+    Ok(())
+}
+
+#[test]
+fn records_are_written_before_committing_and_removed_if_the_transaction_is_aborted() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    let a = hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242");
+    let mut journal = store.journal(journal::Options {
+        rotate_after_bytes: 1,
+        ..Default::default()
+    });
+    commit(&store, &mut journal, vec![update("refs/heads/one", a)?])?;
+
+    let prepared = store
+        .transaction()
+        .observer(Box::new(&mut journal))
+        .prepare(Some(update("refs/heads/two", a)?), Fail::Immediately)?;
+    assert_eq!(
+        store.journal_records(..)?.len(),
+        2,
+        "records are written once the transaction is prepared"
+    );
+    drop(prepared);
+    assert_eq!(
+        store.journal_records(..)?.len(),
+        1,
+        "and removed again once it's aborted"
+    );
+
+    commit(&store, &mut journal, vec![update("refs/heads/three", a)?])?;
+    assert!(journal.take_errors().is_empty());
+    assert_eq!(
+        store
+            .journal_records(..)?
+            .iter()
+            .map(|r| (r.transaction, r.name.as_bstr().to_string()))
+            .collect::<Vec<_>>(),
+        vec![(1, "refs/heads/one".into()), (2, "refs/heads/three".into())],
+        "ids continue even if the rotating transaction was aborted"
+    );
+    Ok(())
+}
+
+#[test]
+fn a_journal_configured_on_the_store_records_all_of_its_transactions() -> crate::Result {
+    let (_keep, mut store) = empty_store()?;
+    let a = hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242");
+    store.journal = Some(journal::Options {
+        strict: true,
+        lock_mode: Fail::Immediately,
+        ..Default::default()
+    });
+
+    let mut journal = store.journal(Default::default());
+    let res = store
+        .transaction()
+        .observer(Box::new(&mut journal))
+        .prepare(Some(update("refs/heads/main", a)?), Fail::Immediately)
+        .map(|_| ());
+    assert!(
+        matches!(res, Err(prepare::Error::Rejected(_))),
+        "both journals lock the same file, so the store's journal can't be locked in strict mode…"
+    );
+    assert!(
+        store.journal_records(..)?.is_empty(),
+        "…and the transaction is aborted, removing the records of the other journal"
+    );
+    assert!(journal.take_errors().is_empty());
+
+    store
+        .transaction()
+        .prepare(Some(update("refs/heads/main", a)?), Fail::Immediately)?
+        .commit(&committer())?;
+    store.journal = None;
+    store
+        .transaction()
+        .prepare(Some(update("refs/heads/other", a)?), Fail::Immediately)?
+        .commit(&committer())?;
+    assert_eq!(
+        store
+            .journal_records(..)?
+            .iter()
+            .map(|r| r.name.as_bstr().to_string())
+            .collect::<Vec<_>>(),
+        vec!["refs/heads/main"],
+        "transactions are recorded without attaching an observer, as long as the journal is configured"
+    );
+    Ok(())
+}
@@ -0,0 +1,365 @@
+use crate::file::{
+    store_writable,
+    transaction::prepare_and_commit::{committer, empty_store},
+};
+use git_hash::ObjectId;
+use git_lock::acquire::Fail;
+use git_ref::{
+    file::transaction::{
+        observe::{Observer, Update},
+        prepare,
+    },
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+use git_testtools::hex_to_id;
+use std::convert::TryInto;
+
+#[derive(Default)]
+struct Recorder {
+    events: Vec<(&'static str, Vec<Update>)>,
+    reject: bool,
+}
+
+impl Observer for Recorder {
+    fn prepared(&mut self, updates: &[Update]) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.events.push(("prepared", updates.to_vec()));
+        if self.reject {
+            Err("rejected by policy".into())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn committed(&mut self, updates: &[Update]) {
+        self.events.push(("committed", updates.to_vec()));
+    }
+
+    fn aborted(&mut self, updates: &[Update]) {
+        self.events.push(("aborted", updates.to_vec()));
+    }
+}
+
+fn update(name: &str, expected: PreviousValue, new: ObjectId, deref: bool) -> crate::Result<RefEdit> {
+    Ok(RefEdit {
+        change: Change::Update {
+            log: LogChange::default(),
+            expected,
+            new: Target::Peeled(new),
+        },
+        name: name.try_into()?,
+        deref,
+    })
+}
+
+fn delete(name: &str) -> crate::Result<RefEdit> {
+    Ok(RefEdit {
+        change: Change::Delete {
+            expected: PreviousValue::MustExist,
+            log: RefLog::AndReference,
+        },
+        name: name.try_into()?,
+        deref: false,
+    })
+}
+
+fn observed(previous: ObjectId, new: ObjectId, name: &str) -> crate::Result<Update> {
+    Ok(Update {
+        previous,
+        new,
+        name: name.try_into()?,
+        deleted: new.is_null(),
+    })
+}
+
+#[test]
+fn committed_transactions_are_observed_with_previous_values_but_without_reflog_only_edits() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    let main = store.find_loose("main")?.target.into_id();
+    let new = hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242");
+    let mut recorder = Recorder::default();
+
+    store
+        .transaction()
+        .observer(Box::new(&mut recorder))
+        .prepare(
+            vec![
+                update("HEAD", PreviousValue::Any, new, true)?,
+                update("refs/heads/new", PreviousValue::MustNotExist, main, false)?,
+            ],
+            Fail::Immediately,
+        )?
+        .commit(&committer())?;
+
+    let expected = vec![
+        observed(ObjectId::null_sha1(), main, "refs/heads/new")?,
+        observed(main, new, "refs/heads/main")?,
+    ];
+    assert_eq!(
+        recorder.events,
+        vec![("prepared", expected.clone()), ("committed", expected)],
+        "HEAD only gets a reflog entry as it is dereferenced, and isn't observed, while its referent is updated last"
+    );
+    assert_eq!(
+        recorder.events[0].1[1].to_string(),
+        format!("{} {} refs/heads/main", main, new),
+        "updates display like lines passed to the reference-transaction hook"
+    );
+    assert_eq!(store.find_loose("main")?.target.into_id(), new);
+
+    let mut recorder = Recorder::default();
+    store
+        .transaction()
+        .observer(Box::new(&mut recorder))
+        .prepare(Some(delete("refs/heads/new")?), Fail::Immediately)?
+        .commit(&committer())?;
+    let expected = vec![observed(main, ObjectId::null_sha1(), "refs/heads/new")?];
+    assert_eq!(
+        recorder.events,
+        vec![("prepared", expected.clone()), ("committed", expected)],
+        "deletions have the null id as new value"
+    );
+    Ok(())
+}
+
+#[test]
+fn rejected_transactions_are_rolled_back_and_aborted() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    let new = hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242");
+    let mut recorder = Recorder {
+        reject: true,
+        ..Default::default()
+    };
+
+    let res = store
+        .transaction()
+        .observer(Box::new(&mut recorder))
+        .prepare(
+            Some(update("refs/heads/main", PreviousValue::Any, new, false)?),
+            Fail::Immediately,
+        )
+        .map(|_| ());
+    match res {
+        Err(err @ prepare::Error::Rejected(_)) => {
+            assert_eq!(err.to_string(), "The transaction was rejected by its observer");
+            assert_eq!(
+                std::error::Error::source(&err).expect("observer error").to_string(),
+                "rejected by policy"
+            );
+        }
+        _ => unreachable!("the observer rejects the transaction"),
+    }
+
+    let expected = vec![observed(ObjectId::null_sha1(), new, "refs/heads/main")?];
+    assert_eq!(
+        recorder.events,
+        vec![("prepared", expected.clone()), ("aborted", expected)]
+    );
+    assert!(store.try_find_loose("main")?.is_none(), "the reference wasn't written");
+
+    store
+        .transaction()
+        .prepare(
+            Some(update("refs/heads/main", PreviousValue::MustNotExist, new, false)?),
+            Fail::Immediately,
+        )?
+        .commit(&committer())?;
+    assert_eq!(
+        store.find_loose("main")?.target.into_id(),
+        new,
+        "all locks were released"
+    );
+    Ok(())
+}
+
+#[test]
+fn dropped_prepared_transactions_are_aborted() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    let new = hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242");
+    let mut recorder = Recorder::default();
+
+    let transaction = store.transaction().observer(Box::new(&mut recorder)).prepare(
+        Some(update("refs/heads/main", PreviousValue::Any, new, false)?),
+        Fail::Immediately,
+    )?;
+    drop(transaction);
+
+    let expected = vec![observed(ObjectId::null_sha1(), new, "refs/heads/main")?];
+    assert_eq!(
+        recorder.events,
+        vec![("prepared", expected.clone()), ("aborted", expected)]
+    );
+    assert!(store.try_find_loose("main")?.is_none());
+    Ok(())
+}
+
+#[test]
+fn all_observers_are_informed_until_one_rejects_the_transaction() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    let new = hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242");
+    let (mut first, mut rejecting, mut last) = (
+        Recorder::default(),
+        Recorder {
+            reject: true,
+            ..Default::default()
+        },
+        Recorder::default(),
+    );
+
+    let res = store
+        .transaction()
+        .observer(Box::new(&mut first))
+        .observer(Box::new(&mut rejecting))
+        .observer(Box::new(&mut last))
+        .prepare(
+            Some(update("refs/heads/main", PreviousValue::Any, new, false)?),
+            Fail::Immediately,
+        )
+        .map(|_| ());
+    assert!(matches!(res, Err(prepare::Error::Rejected(_))));
+
+    let expected = vec![observed(ObjectId::null_sha1(), new, "refs/heads/main")?];
+    let prepared_and_aborted = vec![("prepared", expected.clone()), ("aborted", expected)];
+    assert_eq!(first.events, prepared_and_aborted);
+    assert_eq!(rejecting.events, prepared_and_aborted);
+    assert!(
+        last.events.is_empty(),
+        "observers after the rejecting one never learn about the transaction"
+    );
+
+    let mut second = Recorder::default();
+    store
+        .transaction()
+        .observer(Box::new(&mut first))
+        .observer(Box::new(&mut second))
+        .prepare(
+            Some(update("refs/heads/main", PreviousValue::Any, new, false)?),
+            Fail::Immediately,
+        )?
+        .commit(&committer())?;
+    assert_eq!(first.events.len(), 4);
+    assert_eq!(
+        first.events[2..],
+        second.events[..],
+        "all observers see the same events"
+    );
+    Ok(())
+}
+
+#[test]
+fn transactions_failing_to_commit_are_aborted() -> crate::Result {
+    let (dir, store) = empty_store()?;
+    let blocking_dir = dir.path().join("refs").join("heads").join("main");
+    std::fs::create_dir_all(&blocking_dir)?;
+    std::fs::write(blocking_dir.join("file"), b"")?;
+    let new = hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242");
+    let mut recorder = Recorder::default();
+
+    let res = store
+        .transaction()
+        .observer(Box::new(&mut recorder))
+        .prepare(
+            Some(update("refs/heads/main", PreviousValue::Any, new, false)?),
+            Fail::Immediately,
+        )?
+        .commit(&committer());
+    assert!(
+        res.is_err(),
+        "a non-empty directory is in the way of the reference to create"
+    );
+
+    let expected = vec![observed(ObjectId::null_sha1(), new, "refs/heads/main")?];
+    assert_eq!(
+        recorder.events,
+        vec![("prepared", expected.clone()), ("aborted", expected)]
+    );
+    assert!(
+        !dir.path().join("refs").join("heads").join("main.lock").exists(),
+        "all locks were released"
+    );
+    Ok(())
+}
+
+#[test]
+fn transactions_failing_to_prepare_are_not_observed() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    let mut recorder = Recorder::default();
+
+    let res = store
+        .transaction()
+        .observer(Box::new(&mut recorder))
+        .prepare(Some(delete("refs/heads/main")?), Fail::Immediately)
+        .map(|_| ());
+    assert!(res.is_err(), "the reference to delete doesn't exist");
+    assert!(recorder.events.is_empty());
+    Ok(())
+}
+
+#[cfg(unix)]
+mod hook {
+    use crate::file::transaction::prepare_and_commit::{committer, empty_store};
+    use git_hash::ObjectId;
+    use git_lock::acquire::Fail;
+    use git_ref::{file::transaction::observe::Hook, transaction::PreviousValue};
+    use git_testtools::hex_to_id;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn hook_script(dir: &std::path::Path) -> crate::Result<Hook> {
+        let path = dir.join("reference-transaction");
+        std::fs::write(
+            &path,
+            r#"#!/bin/sh
+input=$(cat)
+printf '%s\n%s\n' "$1" "$input" >> "$0.log"
+case "$input" in
+  *refs/heads/rejected*) test "$1" != prepared;;
+esac
+"#,
+        )?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        Ok(Hook::new(path))
+    }
+
+    #[test]
+    fn scripts_receive_each_stage_and_can_reject_prepared_transactions() -> crate::Result {
+        let (dir, store) = empty_store()?;
+        let hook_dir = git_testtools::tempfile::tempdir()?;
+        let hook = hook_script(hook_dir.path())?;
+        let new = hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242");
+
+        store
+            .transaction()
+            .observer(Box::new(hook.clone()))
+            .prepare(
+                Some(super::update("refs/heads/main", PreviousValue::Any, new, false)?),
+                Fail::Immediately,
+            )?
+            .commit(&committer())?;
+
+        let err = store
+            .transaction()
+            .observer(Box::new(hook))
+            .prepare(
+                Some(super::update("refs/heads/rejected", PreviousValue::Any, new, false)?),
+                Fail::Immediately,
+            )
+            .map(|_| ())
+            .expect_err("the hook exits with non-zero status");
+        assert_eq!(err.to_string(), "The transaction was rejected by its observer");
+        assert!(!dir.path().join("refs/heads/rejected").exists());
+
+        let null = ObjectId::null_sha1();
+        assert_eq!(
+            std::fs::read_to_string(hook_dir.path().join("reference-transaction.log"))?,
+            format!(
+                "prepared\n{null} {new} refs/heads/main\n\
+                 committed\n{null} {new} refs/heads/main\n\
+                 prepared\n{null} {new} refs/heads/rejected\n\
+                 aborted\n{null} {new} refs/heads/rejected\n",
+                null = null,
+                new = new
+            )
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use std::convert::TryInto;
+
+use git_lock::acquire::Fail;
+use git_ref::{
+    file::{pack_refs, packed, structure, transaction::prepare},
+    transaction::{Change, LogChange, PreviousValue, RefEdit},
+    Target,
+};
+use git_testtools::hex_to_id;
+
+use crate::file::transaction::prepare_and_commit::empty_store;
+
+#[test]
+fn all_writes_fail_without_creating_any_file() -> crate::Result {
+    let (dir, mut store) = empty_store()?;
+    store.read_only = true;
+
+    let res = store.transaction().prepare(
+        Some(RefEdit {
+            change: Change::Update {
+                log: LogChange::default(),
+                expected: PreviousValue::Any,
+                new: Target::Peeled(hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242")),
+            },
+            name: "refs/heads/main".try_into()?,
+            deref: false,
+        }),
+        Fail::Immediately,
+    );
+    assert!(matches!(res, Err(prepare::Error::ReadOnly)));
+    assert!(matches!(
+        store.packed_transaction(Fail::Immediately),
+        Err(packed::transaction::Error::ReadOnly)
+    ));
+    assert!(matches!(
+        store.pack_refs(Default::default(), Fail::Immediately, Box::new(|_, _| Ok(None))),
+        Err(pack_refs::Error::ReadOnly)
+    ));
+    assert!(matches!(
+        store.verify_or_create_structure(),
+        Err(structure::Error::ReadOnly(path)) if path == dir.path().join("refs")
+    ));
+    assert_eq!(std::fs::read_dir(dir.path())?.count(), 0, "nothing was written");
+    Ok(())
+}
@@ -0,0 +1,226 @@
+use std::convert::TryInto;
+
+use git_hash::ObjectId;
+use git_ref::{
+    file::transaction::validate::{Error, Failure, Reason},
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefEditsExt, RefLog},
+    FullName, Target,
+};
+
+use crate::file::store_with_packed_refs;
+
+fn update(name: &str, expected: PreviousValue, new: Target) -> RefEdit {
+    RefEdit {
+        change: Change::Update {
+            log: LogChange::default(),
+            expected,
+            new,
+        },
+        name: name.try_into().expect("valid name"),
+        deref: false,
+    }
+}
+
+fn delete(name: &str, expected: PreviousValue) -> RefEdit {
+    RefEdit {
+        change: Change::Delete {
+            expected,
+            log: RefLog::AndReference,
+        },
+        name: name.try_into().expect("valid name"),
+        deref: false,
+    }
+}
+
+fn null() -> Target {
+    Target::Peeled(ObjectId::null_sha1())
+}
+
+fn failures(res: Result<(), Error>) -> Vec<Failure> {
+    match res {
+        Err(Error::Invalid { failures }) => failures,
+        res => panic!("expected validation failures, got {:?}", res),
+    }
+}
+
+fn name(name: &str) -> FullName {
+    name.try_into().expect("valid name")
+}
+
+#[test]
+fn edits_matching_the_current_state_are_valid_and_nothing_is_changed() -> crate::Result {
+    let store = store_with_packed_refs()?;
+    let packed = store.packed_buffer()?;
+    let main = store.find("main", packed.as_ref())?.target;
+
+    let edits = vec![
+        update(
+            "refs/heads/main",
+            PreviousValue::MustExistAndMatch(main.clone()),
+            null(),
+        ),
+        update("refs/heads/new", PreviousValue::MustNotExist, main.clone()),
+        update("refs/heads/d1", PreviousValue::MustExist, main.clone()),
+        update("refs/heads/main-2", PreviousValue::ExistingMustMatch(null()), main),
+        delete("refs/heads/dt1", PreviousValue::MustExist),
+        delete("refs/heads/does-not-exist", PreviousValue::Any),
+    ];
+    edits.validate(&store, packed.as_ref())?;
+
+    assert!(
+        store.try_find("refs/heads/new", packed.as_ref())?.is_none(),
+        "validation never writes anything"
+    );
+    assert!(store.try_find("refs/heads/dt1", packed.as_ref())?.is_some());
+    Ok(())
+}
+
+#[test]
+fn every_failing_edit_is_reported_with_its_reason() -> crate::Result {
+    let store = store_with_packed_refs()?;
+    let packed = store.packed_buffer()?;
+    let main = store.find("main", packed.as_ref())?.target;
+
+    let edits = vec![
+        delete("refs/heads/does-not-exist", PreviousValue::MustExist),
+        update("refs/heads/main", PreviousValue::MustExistAndMatch(null()), null()),
+        update("refs/heads/fine", PreviousValue::Any, null()),
+        update("refs/heads/dt1", PreviousValue::MustNotExist, null()),
+        update("refs/heads/d1", PreviousValue::MustNotExist, main.clone()),
+        update("refs/heads/does-not-exist", PreviousValue::MustExist, null()),
+        delete("refs/heads/d1", PreviousValue::MustNotExist),
+    ];
+    assert_eq!(
+        failures(edits.validate(&store, packed.as_ref())),
+        vec![
+            Failure {
+                index: 0,
+                name: name("refs/heads/does-not-exist"),
+                reason: Reason::MustExist
+            },
+            Failure {
+                index: 1,
+                name: name("refs/heads/main"),
+                reason: Reason::OutOfDate {
+                    expected: null(),
+                    actual: main.clone()
+                }
+            },
+            Failure {
+                index: 3,
+                name: name("refs/heads/dt1"),
+                reason: Reason::MustNotExist { actual: main }
+            },
+            Failure {
+                index: 5,
+                name: name("refs/heads/does-not-exist"),
+                reason: Reason::MustExist
+            },
+            Failure {
+                index: 6,
+                name: name("refs/heads/d1"),
+                reason: Reason::DeletionOfNonExistingReference
+            },
+        ],
+        "creating a reference with the value it already has is fine, like in transactions"
+    );
+    Ok(())
+}
+
+#[test]
+fn new_references_must_not_conflict_with_existing_directories_or_files() -> crate::Result {
+    let store = store_with_packed_refs()?;
+    let packed = store.packed_buffer()?;
+
+    let edits = vec![
+        update("refs/heads/main/sub", PreviousValue::Any, null()),
+        update("refs/remotes/origin", PreviousValue::Any, null()),
+        update("refs/heads/ma", PreviousValue::Any, null()),
+        update("refs/heads/main-2/sub", PreviousValue::Any, null()),
+    ];
+    let failures = failures(edits.validate(&store, packed.as_ref()));
+    assert_eq!(failures.len(), 2, "similar names which aren't directories are fine");
+    assert_eq!(
+        failures[0].reason,
+        Reason::NameConflict {
+            other: name("refs/heads/main")
+        }
+    );
+    assert_eq!(failures[1].index, 1);
+    assert!(
+        matches!(&failures[1].reason, Reason::NameConflict { other } if other.as_bstr().starts_with(b"refs/remotes/origin/")),
+        "existing references within the new name are in the way as well"
+    );
+    Ok(())
+}
+
+#[test]
+fn name_conflicts_consider_other_edits() -> crate::Result {
+    let store = store_with_packed_refs()?;
+    let packed = store.packed_buffer()?;
+
+    vec![
+        delete("refs/heads/main", PreviousValue::MustExist),
+        update("refs/heads/main/sub", PreviousValue::MustNotExist, null()),
+    ]
+    .validate(&store, packed.as_ref())?;
+
+    let failures = failures(
+        vec![
+            update("refs/heads/new", PreviousValue::MustNotExist, null()),
+            update("refs/heads/new/sub", PreviousValue::MustNotExist, null()),
+        ]
+        .validate(&store, packed.as_ref()),
+    );
+    assert_eq!(
+        failures.into_iter().map(|f| (f.index, f.reason)).collect::<Vec<_>>(),
+        vec![
+            (
+                0,
+                Reason::NameConflict {
+                    other: name("refs/heads/new/sub")
+                }
+            ),
+            (
+                1,
+                Reason::NameConflict {
+                    other: name("refs/heads/new")
+                }
+            )
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn packed_references_without_loose_directory_are_in_the_way_too() -> crate::Result {
+    let (_keep, store) = crate::file::store_writable("make_packed_ref_repository.sh")?;
+    let packed_refs = std::fs::read_to_string(store.packed_refs_path())?;
+    let main_line = packed_refs
+        .lines()
+        .find(|line| line.ends_with(" refs/heads/main"))
+        .expect("main is packed");
+    let packed_only_line = main_line.replace("refs/heads/main", "refs/packed-only/child");
+    std::fs::write(
+        store.packed_refs_path(),
+        packed_refs.replace(main_line, &format!("{}\n{}", main_line, packed_only_line)),
+    )?;
+    let packed = store.packed_buffer()?;
+    assert!(!store.base.join("refs/packed-only").exists());
+
+    let failures =
+        failures(vec![update("refs/packed-only", PreviousValue::Any, null())].validate(&store, packed.as_ref()));
+    assert_eq!(
+        failures[0].reason,
+        Reason::NameConflict {
+            other: name("refs/packed-only/child")
+        }
+    );
+
+    vec![
+        update("refs/packed-only", PreviousValue::Any, null()),
+        delete("refs/packed-only/child", PreviousValue::MustExist),
+    ]
+    .validate(&store, packed.as_ref())?;
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use std::{convert::TryInto, path::Path};
+
+use git_hash::ObjectId;
+use git_lock::acquire::Fail;
+use git_ref::{
+    file,
+    transaction::{Change, LogChange, PreviousValue, RefEdit},
+    Target,
+};
+
+fn stores(root: &Path) -> (file::Store, file::Store) {
+    let common_dir = root.join("main/.git");
+    (
+        file::Store::at(&common_dir, file::WriteReflog::Normal),
+        file::Store::for_linked_worktree(common_dir.join("worktrees/wt"), &common_dir, file::WriteReflog::Normal),
+    )
+}
+
+fn rev_parse(git_dir: &Path, spec: &str) -> crate::Result<ObjectId> {
+    let out = std::process::Command::new("git")
+        .arg("--git-dir")
+        .arg(git_dir)
+        .args(["rev-parse", spec])
+        .output()?;
+    assert!(out.status.success(), "{:?}", out);
+    Ok(ObjectId::from_hex(String::from_utf8(out.stdout)?.trim().as_bytes())?)
+}
+
+fn names(store: &file::Store) -> crate::Result<Vec<String>> {
+    let packed = store.packed_buffer()?;
+    Ok(store
+        .iter(packed.as_ref())?
+        .map(|r| r.map(|r| r.name.as_bstr().to_string()))
+        .collect::<Result<_, _>>()?)
+}
+
+#[test]
+fn linked_worktrees_have_their_own_head_and_share_all_other_references() -> crate::Result {
+    let root = git_testtools::scripted_fixture_repo_read_only("make_repo_with_worktree.sh")?;
+    let (main, wt) = stores(&root);
+    assert_eq!(wt.common_dir_resolved(), main.base);
+    assert_eq!(main.common_dir_resolved(), main.base);
+
+    assert_eq!(
+        main.find_loose("HEAD")?.target,
+        Target::Symbolic("refs/heads/main".try_into()?)
+    );
+    assert_eq!(
+        wt.find_loose("HEAD")?.target,
+        Target::Symbolic("refs/heads/wt-branch".try_into()?)
+    );
+    let wt_head = rev_parse(&wt.base, "HEAD")?;
+    for store in [&main, &wt] {
+        assert_eq!(store.find_loose("wt-branch")?.target.into_id(), wt_head);
+        assert_eq!(store.find_loose("main")?.target.into_id(), rev_parse(&main.base, "c1")?);
+    }
+
+    assert_eq!(
+        wt.find_loose("refs/worktree/private")?.target.into_id(),
+        wt_head,
+        "references in refs/worktree are private to the worktree"
+    );
+    assert!(main.try_find_loose("refs/worktree/private")?.is_none());
+
+    let mut expected = names(&main)?;
+    expected.push("refs/worktree/private".into());
+    expected.sort();
+    assert_eq!(
+        names(&wt)?,
+        expected,
+        "iteration lists shared references along with the ones private to the worktree"
+    );
+    assert!(names(&wt)?.contains(&"refs/heads/wt-branch".to_string()));
+    assert_eq!(
+        wt.iter_prefixed(None, "refs/worktree/")?
+            .map(|r| r.map(|r| r.name.as_bstr().to_string()))
+            .collect::<Result<Vec<_>, _>>()?,
+        vec!["refs/worktree/private"]
+    );
+    assert_eq!(main.iter_prefixed(None, "refs/worktree/")?.count(), 0);
+
+    let mut buf = Vec::new();
+    let messages = |store: &file::Store, name: &str, buf: &mut Vec<u8>| -> crate::Result<Vec<String>> {
+        Ok(store
+            .reflog_iter(name, buf)?
+            .expect("log exists")
+            .map(|line| line.map(|line| line.message.to_string()))
+            .collect::<Result<_, _>>()?)
+    };
+    assert_eq!(messages(&wt, "HEAD", &mut buf)?.last().expect("present"), "commit: c2");
+    assert_eq!(
+        messages(&main, "HEAD", &mut buf)?.last().expect("present"),
+        "commit (initial): c1"
+    );
+    assert_eq!(
+        messages(&wt, "refs/heads/wt-branch", &mut buf)?,
+        messages(&main, "refs/heads/wt-branch", &mut buf)?,
+        "reflogs of shared references are shared as well"
+    );
+    Ok(())
+}
+
+#[test]
+fn edits_in_linked_worktrees_write_shared_references_to_the_common_dir() -> crate::Result {
+    let dir = git_testtools::scripted_fixture_repo_writable("make_repo_with_worktree.sh")?;
+    let (main, wt) = stores(dir.path());
+    let c1 = rev_parse(&main.base, "c1")?;
+
+    let update = |name: &str, deref: bool| -> crate::Result<RefEdit> {
+        Ok(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    message: "reset to c1".into(),
+                    ..Default::default()
+                },
+                expected: PreviousValue::Any,
+                new: Target::Peeled(c1),
+            },
+            name: name.try_into()?,
+            deref,
+        })
+    };
+    wt.transaction()
+        .prepare(
+            vec![update("HEAD", true)?, update("refs/worktree/new", false)?],
+            Fail::Immediately,
+        )?
+        .commit(&git_actor::Signature::empty())?;
+
+    assert_eq!(rev_parse(&wt.base, "HEAD")?, c1);
+    assert_eq!(rev_parse(&main.base, "wt-branch")?, c1);
+    assert_eq!(rev_parse(&wt.base, "refs/worktree/new")?, c1);
+    assert!(main.base.join("refs/heads/wt-branch").is_file());
+    assert!(!wt.base.join("refs/heads").exists());
+    assert!(wt.base.join("refs/worktree/new").is_file());
+
+    for log in [wt.base.join("logs/HEAD"), main.base.join("logs/refs/heads/wt-branch")] {
+        assert!(
+            std::fs::read_to_string(&log)?.trim_end().ends_with("reset to c1"),
+            "{:?} was written",
+            log
+        );
+    }
+    assert!(
+        !wt.base.join("logs/refs/heads").exists(),
+        "no shared reflog is written to the worktree"
+    );
+    Ok(())
+}
@@ -0,0 +1,30 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# The references and reflogs of the 'reftable' fixture after merging both of its tables, stored in files instead.
+git init -q
+
+A=1111111111111111111111111111111111111111
+B=2222222222222222222222222222222222222222
+C=3333333333333333333333333333333333333333
+T=4444444444444444444444444444444444444444
+P=5555555555555555555555555555555555555555
+Z=0000000000000000000000000000000000000000
+
+echo "ref: refs/heads/main" > .git/HEAD
+mkdir -p .git/refs/heads
+echo $C > .git/refs/heads/main
+
+cat <<PACKED > .git/packed-refs
+# pack-refs with: peeled fully-peeled sorted 
+$B refs/heads/feature
+$A refs/remotes/origin/main
+$T refs/tags/v1
+^$P
+PACKED
+
+mkdir -p .git/logs/refs/heads
+for log in .git/logs/HEAD .git/logs/refs/heads/main; do
+  printf '%s %s A U Thor <author@example.com> 1112911993 +0100\tcommit (initial): first\n' $Z $A > $log
+  printf '%s %s A U Thor <author@example.com> 1112912113 -0730\tcommit: second\n' $A $C >> $log
+done
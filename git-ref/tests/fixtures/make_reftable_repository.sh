@@ -0,0 +1,18 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Write references and reflogs with `git` itself into a stack of reftables, which needs git 2.45 or newer.
+# The fixtures in the `reftable` directory were assembled by hand according to the specification, whereas this repository
+# is used to verify we read what `git` writes.
+git init -q --ref-format=reftable
+git config commit.gpgsign false
+
+git checkout -q -b main
+git commit -q --allow-empty -m first
+git branch feature
+git branch old
+git commit -q --allow-empty -m second
+
+git update-ref -m "fetch: storing head" refs/remotes/origin/main feature
+git tag -m "annotated" v1 feature
+git branch -D old >/dev/null
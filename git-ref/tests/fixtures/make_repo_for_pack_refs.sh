@@ -0,0 +1,22 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+git checkout -q -b main
+git commit -q --allow-empty -m c1
+git branch packed-branch
+git tag -m "annotated tag" annotated
+git tag lightweight
+
+git pack-refs --all --prune
+
+git commit -q --allow-empty -m c2
+packed_branch=$(git rev-parse packed-branch)
+echo "$packed_branch" > .git/refs/heads/packed-branch
+git branch loose-branch
+git tag -m "loose annotated tag" loose-annotated
+
+mkdir -p .git/refs/remotes/origin
+echo "ref: refs/heads/main" > .git/refs/remotes/origin/HEAD
@@ -0,0 +1,16 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q main
+(cd main
+  git config commit.gpgsign false
+  git checkout -q -b main
+  git commit -q --allow-empty -m c1
+  git tag c1
+  git worktree add -q ../wt -b wt-branch
+)
+
+(cd wt
+  git commit -q --allow-empty -m c2
+  git update-ref refs/worktree/private HEAD
+)
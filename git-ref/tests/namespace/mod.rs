@@ -10,6 +10,16 @@ fn into_namespaced_prefix() {
     )
 }
 
+#[test]
+fn try_from_str_validates_and_expands() {
+    use std::convert::TryFrom;
+    assert_eq!(
+        git_ref::Namespace::try_from("foo/bar").unwrap().as_bstr(),
+        "refs/namespaces/foo/refs/namespaces/bar/"
+    );
+    assert!(git_ref::Namespace::try_from("foo//bar").is_err());
+}
+
 mod expand {
     #[test]
     fn components_end_with_trailing_slash_to_help_with_prefix_stripping() {
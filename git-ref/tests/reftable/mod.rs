@@ -0,0 +1,296 @@
+use std::{convert::TryInto, path::Path};
+
+use git_ref::{
+    backend::{Backend, Transaction},
+    file, reftable,
+    transaction::{Change, LogChange, PreviousValue, RefEdit},
+    Reference, Target,
+};
+use git_testtools::hex_to_id;
+
+fn store() -> crate::Result<reftable::Store> {
+    Ok(reftable::Store::at(git_testtools::fixture_path("reftable"))?)
+}
+
+/// The references and reflogs of `backend` as seen through the backend trait.
+#[derive(Debug, PartialEq)]
+struct Snapshot {
+    found: Vec<Option<Reference>>,
+    all: Vec<Reference>,
+    heads: Vec<Reference>,
+    logs: Vec<Option<Vec<git_ref::log::Line>>>,
+}
+
+fn snapshot(backend: &impl Backend) -> crate::Result<Snapshot> {
+    let mut found = Vec::new();
+    for name in &[
+        "HEAD",
+        "main",
+        "heads/main",
+        "refs/heads/feature",
+        "v1",
+        "origin",
+        "old",
+        "missing",
+    ] {
+        found.push(backend.try_find_reference((*name).try_into()?)?);
+    }
+    let mut logs = Vec::new();
+    for name in &["HEAD", "refs/heads/main", "refs/heads/feature", "refs/heads/old"] {
+        logs.push(backend.reflog_lines((*name).try_into()?)?);
+    }
+    Ok(Snapshot {
+        found,
+        all: backend.references()?,
+        heads: backend.references_prefixed(Path::new("refs/heads/"))?,
+        logs,
+    })
+}
+
+#[test]
+fn references_and_reflogs_are_the_same_as_in_an_equivalent_file_store() -> crate::Result {
+    let files = file::Store::from(
+        git_testtools::scripted_fixture_repo_read_only("make_files_repository_for_reftable_parity.sh")?.join(".git"),
+    );
+    let expected = snapshot(&files)?;
+    assert_eq!(expected.all.len(), 4, "all references below refs/");
+    assert_eq!(snapshot(&store()?)?, expected);
+    Ok(())
+}
+
+#[test]
+fn newer_tables_update_and_delete_references_of_older_ones() -> crate::Result {
+    let store = store()?;
+    assert_eq!(
+        store
+            .tables()
+            .iter()
+            .map(|t| (t.min_update_index(), t.max_update_index()))
+            .collect::<Vec<_>>(),
+        vec![(1, 2), (3, 3)]
+    );
+
+    let main = store.try_find("main")?.expect("present");
+    assert_eq!(
+        main.target,
+        Target::Peeled(hex_to_id("3333333333333333333333333333333333333333")),
+        "the value of the newest table wins"
+    );
+    assert_eq!(main.peeled, None);
+    assert!(
+        store.try_find("old")?.is_none(),
+        "deletions hide references of older tables"
+    );
+
+    let head = store.try_find("HEAD")?.expect("present");
+    assert_eq!(head.target, Target::Symbolic("refs/heads/main".try_into()?));
+
+    let tag = store.try_find("v1")?.expect("present");
+    assert_eq!(
+        tag.target,
+        Target::Peeled(hex_to_id("4444444444444444444444444444444444444444"))
+    );
+    assert_eq!(
+        tag.peeled,
+        Some(hex_to_id("5555555555555555555555555555555555555555")),
+        "peeled values are stored along with the target"
+    );
+
+    assert_eq!(
+        store
+            .references_prefixed("refs/heads")?
+            .into_iter()
+            .map(|r| r.name.into_inner())
+            .collect::<Vec<_>>(),
+        vec!["refs/heads/feature", "refs/heads/main"]
+    );
+    assert!(store.references_prefixed("refs/notes/")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn reflogs_are_merged_across_tables_with_deletions() -> crate::Result {
+    let store = store()?;
+    let lines = store.reflog("refs/heads/main".try_into()?)?.expect("present");
+    assert_eq!(lines.len(), 2);
+    assert_eq!(
+        lines[0].message, "commit (initial): first",
+        "oldest first, without trailing newline"
+    );
+    assert_eq!(lines[1].message, "commit: second");
+    assert_eq!(
+        lines[1].previous_oid,
+        hex_to_id("1111111111111111111111111111111111111111")
+    );
+    assert_eq!(lines[1].new_oid, hex_to_id("3333333333333333333333333333333333333333"));
+    assert_eq!(lines[1].signature.name, "A U Thor");
+    assert_eq!(lines[1].signature.email, "author@example.com");
+    assert_eq!(lines[1].signature.time.time, 1112912113);
+    assert_eq!(
+        lines[1].signature.time.offset,
+        -(7 * 3600 + 30 * 60),
+        "time zones are stored as hours and minutes"
+    );
+    assert_eq!(lines[1].signature.time.sign, git_actor::Sign::Minus);
+
+    assert!(
+        store.reflog("refs/heads/feature".try_into()?)?.is_none(),
+        "the only entry was deleted by the newer table"
+    );
+    Ok(())
+}
+
+/// `git` can write reftables since version 2.45.
+fn git_writes_reftables() -> crate::Result<bool> {
+    let out = std::process::Command::new("git").arg("--version").output()?;
+    let version = String::from_utf8(out.stdout)?;
+    let mut numbers = version
+        .trim()
+        .trim_start_matches("git version ")
+        .split('.')
+        .map(|n| n.parse::<u32>().unwrap_or(0));
+    Ok((numbers.next().unwrap_or(0), numbers.next().unwrap_or(0)) >= (2, 45))
+}
+
+fn git(dir: &Path, args: &[&str]) -> crate::Result<String> {
+    let out = std::process::Command::new("git").args(args).current_dir(dir).output()?;
+    assert!(out.status.success(), "git {:?} failed", args);
+    Ok(String::from_utf8(out.stdout)?)
+}
+
+#[test]
+#[ignore = "needs git 2.45 or newer to write reftables, run with --ignored"]
+fn tables_written_by_git_are_read_like_git_reads_them() -> crate::Result {
+    assert!(
+        git_writes_reftables()?,
+        "git is too old to write reftables, which is required to verify compatibility"
+    );
+    let dir = git_testtools::scripted_fixture_repo_read_only("make_reftable_repository.sh")?;
+    let store = reftable::Store::at(dir.join(".git").join("reftable"))?;
+
+    let actual: Vec<_> = store
+        .references()?
+        .into_iter()
+        .map(|r| {
+            format!(
+                "{} {} {}",
+                r.name.as_bstr(),
+                r.target.as_id().expect("peeled"),
+                r.peeled.map(|id| id.to_string()).unwrap_or_default()
+            )
+        })
+        .collect();
+    let expected = git(
+        &dir,
+        &["for-each-ref", "--format=%(refname) %(objectname) %(*objectname)"],
+    )?;
+    assert_eq!(actual, expected.lines().collect::<Vec<_>>());
+    assert!(store.try_find("old")?.is_none(), "deleted references are gone");
+
+    let head = store.try_find("HEAD")?.expect("present");
+    assert_eq!(head.target, Target::Symbolic("refs/heads/main".try_into()?));
+
+    for name in &["refs/heads/main", "refs/remotes/origin/main"] {
+        let actual: Vec<_> = store
+            .reflog((*name).try_into()?)?
+            .expect("present")
+            .into_iter()
+            .rev()
+            .map(|line| format!("{} {}", line.new_oid, line.message))
+            .collect();
+        let expected = git(&dir, &["reflog", "show", "--format=%H %gs", name])?;
+        assert_eq!(actual, expected.lines().collect::<Vec<_>>());
+    }
+    Ok(())
+}
+
+#[test]
+fn transactions_are_not_supported_yet() -> crate::Result {
+    let store = store()?;
+    let err = match store
+        .to_transaction()
+        .prepare(None, git_lock::acquire::Fail::Immediately)
+    {
+        Ok(_) => unreachable!("writing isn't supported"),
+        Err(err) => err,
+    };
+    assert!(matches!(err, reftable::transaction::Error::WriteUnsupported));
+    Ok(())
+}
+
+#[test]
+fn corrupt_tables_are_detected() -> crate::Result {
+    let path = git_testtools::fixture_path("reftable").join("0x000000000003-0x000000000003-9b02d35e.ref");
+    let data = std::fs::read(&path)?;
+    let tmp = tempfile::tempdir()?;
+    let corrupt_path = tmp.path().join("corrupt.ref");
+
+    let mut corrupt = data.clone();
+    let checksum_pos = corrupt.len() - 1;
+    corrupt[checksum_pos] ^= 1;
+    std::fs::write(&corrupt_path, &corrupt)?;
+    assert!(matches!(
+        reftable::Table::at(&corrupt_path),
+        Err(reftable::open::Error::Decode {
+            err: reftable::decode::Error::Checksum { .. },
+            ..
+        })
+    ));
+
+    let mut corrupt = data;
+    corrupt[0] = b'X';
+    std::fs::write(&corrupt_path, &corrupt)?;
+    assert!(matches!(
+        reftable::Table::at(&corrupt_path),
+        Err(reftable::open::Error::Decode {
+            err: reftable::decode::Error::Signature,
+            ..
+        })
+    ));
+    Ok(())
+}
+
+#[test]
+fn file_store_edits_and_reflogs_can_be_written_through_the_backend_trait() -> crate::Result {
+    let dir = git_testtools::scripted_fixture_repo_writable("make_files_repository_for_reftable_parity.sh")?;
+    let store = file::Store::from(dir.path().join(".git"));
+    let new = hex_to_id("2222222222222222222222222222222222222222");
+    let edits = store
+        .to_transaction()
+        .prepare(
+            Some(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        message: "reset: moving to feature".into(),
+                        ..Default::default()
+                    },
+                    expected: PreviousValue::MustExistAndMatch(Target::Peeled(hex_to_id(
+                        "3333333333333333333333333333333333333333",
+                    ))),
+                    new: Target::Peeled(new),
+                },
+                name: "HEAD".try_into()?,
+                deref: true,
+            }),
+            git_lock::acquire::Fail::Immediately,
+        )?
+        .commit(&git_actor::Signature {
+            name: "committer".into(),
+            email: "committer@example.com".into(),
+            time: git_actor::Time {
+                time: 1234,
+                offset: 1800,
+                sign: git_actor::Sign::Plus,
+            },
+        })?;
+    assert_eq!(edits.len(), 2, "HEAD and the branch it points to");
+
+    assert_eq!(
+        store.try_find_reference("main".try_into()?)?.expect("present").target,
+        Target::Peeled(new)
+    );
+    let lines = store.reflog_lines("refs/heads/main".try_into()?)?.expect("present");
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[2].message, "reset: moving to feature");
+    Ok(())
+}
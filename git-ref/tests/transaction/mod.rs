@@ -172,6 +172,28 @@ mod refedit_ext {
                 .map_err(Into::into)
         }
 
+        #[test]
+        fn symbolic_updates_retarget_the_symbolic_ref_itself() -> crate::Result {
+            let store = MockStore::default();
+            let mut edits = vec![RefEdit {
+                change: Change::Update {
+                    expected: PreviousValue::MustExistAndMatch(Target::Symbolic("refs/heads/main".try_into()?)),
+                    log: LogChange::default(),
+                    new: Target::Symbolic("refs/heads/feature".try_into()?),
+                },
+                name: "HEAD".try_into()?,
+                deref: true,
+            }];
+
+            edits.extend_with_splits_of_symbolic_refs(
+                |n| store.find_existing(n),
+                |_, _| panic!("the previous referent must not be edited"),
+            )?;
+            assert_eq!(edits.len(), 1, "no edit was added");
+            assert!(!edits[0].deref, "the deref flag is turned off");
+            Ok(())
+        }
+
         #[test]
         fn symbolic_refs_cycles_are_handled_gracefully() -> crate::Result {
             #[derive(Default)]
@@ -0,0 +1,127 @@
+/*
+ * A minimal, stable C interface to read from git repositories with gitoxide.
+ *
+ * It is available if the `git-repository` crate is built as `cdylib` or `staticlib` with the `capi` cargo feature enabled,
+ * for instance with `cargo rustc -p git-repository --features capi --crate-type cdylib`.
+ *
+ * Conventions
+ *
+ * - All functions returning `gix_status` store a description of their failure, which can be obtained with
+ *   `gix_last_error_message()`.
+ * - Strings passed in are NUL-terminated. Paths are arbitrary bytes on unix and must be UTF-8 on windows, revision
+ *   specifications and reference prefixes are byte strings which typically are UTF-8.
+ * - Strings passed out, like reference names, are byte strings of the given length which are NOT NUL-terminated and
+ *   typically UTF-8. They are only valid for the duration of the callback receiving them.
+ * - Object ids are `GIX_ID_LEN` bytes of SHA1, passed as pointer to the first byte.
+ * - Handles are opaque and owned by the caller, who frees them with the matching `gix_*_free()` function. A handle may be
+ *   moved to another thread, but must not be used by multiple threads at the same time.
+ * - Pointers to handles and ids must not be NULL unless stated otherwise. Functions returning `gix_status` check for
+ *   NULL and return GIX_INVALID_ARGUMENT, while the accessors of `gix_object` don't check, so NULL is undefined behavior.
+ */
+#ifndef GIX_H
+#define GIX_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The length of an object id in bytes. */
+#define GIX_ID_LEN 20
+
+/* The outcome of a call. */
+typedef enum gix_status {
+    /* The call succeeded. */
+    GIX_OK = 0,
+    /* The repository, revision or object could not be found. */
+    GIX_NOT_FOUND = 1,
+    /* An argument was NULL or otherwise invalid. */
+    GIX_INVALID_ARGUMENT = 2,
+    /* Any other error, described by gix_last_error_message(). */
+    GIX_ERROR = 3,
+    /* A bug caused a panic, which was caught. The handles involved in the call should not be used anymore. */
+    GIX_PANIC = 4
+} gix_status;
+
+/* The kind of an object, with values matching the object type ids of git. */
+typedef enum gix_object_type {
+    GIX_OBJECT_COMMIT = 1,
+    GIX_OBJECT_TREE = 2,
+    GIX_OBJECT_BLOB = 3,
+    GIX_OBJECT_TAG = 4
+} gix_object_type;
+
+/* An open repository. */
+typedef struct gix_repository gix_repository;
+
+/* An object read from the object database along with its data. */
+typedef struct gix_object gix_object;
+
+/*
+ * Return the message describing the failure of the last call returning `gix_status` on this thread, or NULL if it
+ * succeeded.
+ *
+ * The message is NUL-terminated UTF-8 and valid until the next such call on this thread.
+ */
+const char *gix_last_error_message(void);
+
+/* Open the repository at `path`, which is either its work tree or its git directory, and store it in `out`. */
+gix_status gix_repository_open(const char *path, gix_repository **out);
+
+/* Find the repository containing `path` by searching it and its parent directories, and store it in `out`. */
+gix_status gix_repository_discover(const char *path, gix_repository **out);
+
+/* Free `repo`, which may be NULL. */
+void gix_repository_free(gix_repository *repo);
+
+/* Resolve the revision specification `spec`, like `HEAD~1` or `main:file`, and write its id to `out_id`. */
+gix_status gix_revision_resolve(gix_repository *repo, const char *spec, uint8_t *out_id);
+
+/* Read the object with the given `id` and store it in `out`, or return GIX_NOT_FOUND if it doesn't exist. */
+gix_status gix_object_find(gix_repository *repo, const uint8_t *id, gix_object **out);
+
+/* Return the kind of `object`, which must not be NULL as that is undefined behavior. */
+gix_object_type gix_object_kind(const gix_object *object);
+
+/* Return the size of the data of `object` in bytes. `object` must not be NULL as that is undefined behavior. */
+size_t gix_object_size(const gix_object *object);
+
+/*
+ * Return the data of `object`, which is valid as long as `object`. It is not NUL-terminated.
+ * `object` must not be NULL as that is undefined behavior.
+ */
+const uint8_t *gix_object_data(const gix_object *object);
+
+/* Free `object`, which may be NULL. */
+void gix_object_free(gix_object *object);
+
+/*
+ * Called for each reference with its full `name`, and either the `id` it points to or, if it is symbolic,
+ * the full name of the reference it points to as `target`, while the other is NULL.
+ * Return 0 to continue the iteration or any other value to stop it.
+ */
+typedef int (*gix_reference_cb)(const uint8_t *name, size_t name_len, const uint8_t *id, const uint8_t *target,
+                                size_t target_len, void *user_data);
+
+/*
+ * Call `cb` with `user_data` for each reference in the directory `prefix`, like `refs/heads/`, or for all references
+ * if `prefix` is NULL. `HEAD` and other references outside of `refs/` are not included.
+ */
+gix_status gix_references_foreach(gix_repository *repo, const char *prefix, gix_reference_cb cb, void *user_data);
+
+/*
+ * Called for each commit with its `id`.
+ * Return 0 to continue the traversal or any other value to stop it.
+ */
+typedef int (*gix_commit_cb)(const uint8_t *id, void *user_data);
+
+/* Call `cb` with `user_data` for the commit with `tip_id` and all of its ancestors, each commit exactly once. */
+gix_status gix_history_walk(gix_repository *repo, const uint8_t *tip_id, gix_commit_cb cb, void *user_data);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* GIX_H */
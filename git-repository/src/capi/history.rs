@@ -0,0 +1,37 @@
+use std::os::raw::{c_int, c_void};
+
+use crate::{
+    capi::{call, handle, id, Error, Repository, Status},
+    easy::Oid,
+};
+
+/// Called for each commit with its id. Return 0 to continue the traversal or any other value to stop it.
+pub type CommitCallback = Option<unsafe extern "C" fn(id: *const u8, user_data: *mut c_void) -> c_int>;
+
+/// Call `cb` with `user_data` for the commit with `tip_id` and all of its ancestors, each commit exactly once.
+#[no_mangle]
+pub unsafe extern "C" fn gix_history_walk(
+    repo: *mut Repository,
+    tip_id: *const u8,
+    cb: CommitCallback,
+    user_data: *mut c_void,
+) -> Status {
+    call(|| {
+        let repo = handle(repo, "repo")?;
+        let tip = id(tip_id, "tip_id")?;
+        let cb = cb.ok_or_else(|| Error::invalid_argument("'cb' must not be NULL"))?;
+        let mut ancestors = Oid::from_id(tip, &repo.easy).ancestors()?;
+        for commit in ancestors.all() {
+            let commit = commit.map_err(|err| match err {
+                git_traverse::commit::ancestors::Error::NotFound { .. } => {
+                    Error::from(err).with_status(Status::NotFound)
+                }
+                err => err.into(),
+            })?;
+            if cb(commit.as_ref().as_bytes().as_ptr(), user_data) != 0 {
+                break;
+            }
+        }
+        Ok(())
+    })
+}
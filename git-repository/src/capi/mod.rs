@@ -0,0 +1,160 @@
+//! A minimal and stable C interface to open repositories, resolve revisions, read objects, iterate references and walk
+//! the history of commits.
+//!
+//! The functions are declared in `include/gix.h`, which also documents the conventions for strings, ids and handles.
+//! Build this crate as `cdylib` or `staticlib` with the `capi` feature to use them, for instance with
+//! `cargo rustc -p git-repository --features capi --crate-type cdylib`.
+//!
+//! All functions are thin wrappers around the [`Access`][crate::easy::Access] extensions, and no panic unwinds into the caller.
+#![allow(unsafe_code, clippy::missing_safety_doc)]
+
+use std::{
+    cell::RefCell,
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    panic::AssertUnwindSafe,
+};
+
+use git_hash::ObjectId;
+
+mod history;
+mod object;
+mod reference;
+mod repository;
+
+pub use history::{gix_history_walk, CommitCallback};
+pub use object::{
+    gix_object_data, gix_object_find, gix_object_free, gix_object_kind, gix_object_size, Object, ObjectKind,
+};
+pub use reference::{gix_references_foreach, ReferenceCallback};
+pub use repository::{
+    gix_repository_discover, gix_repository_free, gix_repository_open, gix_revision_resolve, Repository,
+};
+
+/// The outcome of a call, with details available through [`gix_last_error_message()`] if it failed.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The call succeeded.
+    Ok = 0,
+    /// The repository, revision or object could not be found.
+    NotFound = 1,
+    /// An argument was NULL or otherwise invalid.
+    InvalidArgument = 2,
+    /// Any other error.
+    Error = 3,
+    /// A bug caused a panic, which was caught.
+    Panic = 4,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+/// Return the message describing the failure of the last call returning [`Status`] on this thread, or NULL if it succeeded.
+///
+/// The message is valid until the next such call on this thread.
+#[no_mangle]
+pub extern "C" fn gix_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// A failed call, to be turned into a [`Status`] and the last error message.
+pub(crate) struct Error {
+    status: Status,
+    message: String,
+}
+
+impl Error {
+    pub(crate) fn not_found(message: impl Into<String>) -> Self {
+        Error {
+            status: Status::NotFound,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn invalid_argument(message: impl Into<String>) -> Self {
+        Error {
+            status: Status::InvalidArgument,
+            message: message.into(),
+        }
+    }
+
+    /// Report this error as `status` instead.
+    pub(crate) fn with_status(mut self, status: Status) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+impl<E> From<E> for Error
+where
+    E: std::error::Error,
+{
+    fn from(err: E) -> Self {
+        let mut message = err.to_string();
+        let mut source = err.source();
+        while let Some(err) = source {
+            message.push_str(": ");
+            message.push_str(&err.to_string());
+            source = err.source();
+        }
+        Error {
+            status: Status::Error,
+            message,
+        }
+    }
+}
+
+/// Run `f`, catching panics, and record its outcome as last error of this thread.
+pub(crate) fn call(f: impl FnOnce() -> Result<(), Error>) -> Status {
+    let (status, message) = match std::panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => (Status::Ok, None),
+        Ok(Err(err)) => (err.status, Some(err.message)),
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".into());
+            (Status::Panic, Some(format!("panic: {}", message)))
+        }
+    };
+    LAST_ERROR.with(|last| {
+        *last.borrow_mut() =
+            message.map(|message| CString::new(message.replace('\0', "")).expect("NUL bytes were removed"))
+    });
+    status
+}
+
+/// Return the string at `ptr`, or fail if it is NULL.
+pub(crate) unsafe fn c_str<'a>(ptr: *const c_char, name: &str) -> Result<&'a [u8], Error> {
+    if ptr.is_null() {
+        return Err(Error::invalid_argument(format!("'{}' must not be NULL", name)));
+    }
+    Ok(CStr::from_ptr(ptr).to_bytes())
+}
+
+/// Return the handle at `ptr`, or fail if it is NULL.
+pub(crate) unsafe fn handle<'a, T>(ptr: *const T, name: &str) -> Result<&'a T, Error> {
+    ptr.as_ref()
+        .ok_or_else(|| Error::invalid_argument(format!("'{}' must not be NULL", name)))
+}
+
+/// Return the id at `ptr`, or fail if it is NULL.
+pub(crate) unsafe fn id(ptr: *const u8, name: &str) -> Result<ObjectId, Error> {
+    if ptr.is_null() {
+        return Err(Error::invalid_argument(format!("'{}' must not be NULL", name)));
+    }
+    Ok(ObjectId::from_20_bytes(std::slice::from_raw_parts(ptr, 20)))
+}
+
+/// Return the location at `ptr` to store a handle in, or fail if it is NULL.
+pub(crate) unsafe fn out_param<'a, T>(ptr: *mut *mut T) -> Result<&'a mut *mut T, Error> {
+    ptr.as_mut()
+        .ok_or_else(|| Error::invalid_argument("'out' must not be NULL"))
+}
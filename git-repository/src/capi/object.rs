@@ -0,0 +1,83 @@
+use crate::{
+    capi::{call, handle, id, out_param, Error, Repository, Status},
+    prelude::ObjectAccessExt,
+};
+
+/// An object read from the object database along with its data, `gix_object` in C.
+pub struct Object {
+    inner: crate::easy::Object,
+}
+
+/// The kind of an object, with values matching the object type ids of git, `gix_object_type` in C.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    /// A commit.
+    Commit = 1,
+    /// A tree.
+    Tree = 2,
+    /// A blob.
+    Blob = 3,
+    /// An annotated tag.
+    Tag = 4,
+}
+
+impl From<git_object::Kind> for ObjectKind {
+    fn from(kind: git_object::Kind) -> Self {
+        match kind {
+            git_object::Kind::Commit => ObjectKind::Commit,
+            git_object::Kind::Tree => ObjectKind::Tree,
+            git_object::Kind::Blob => ObjectKind::Blob,
+            git_object::Kind::Tag => ObjectKind::Tag,
+        }
+    }
+}
+
+/// Read the object with the given `id` and store it in `out`, or return [`Status::NotFound`] if it doesn't exist.
+#[no_mangle]
+pub unsafe extern "C" fn gix_object_find(repo: *mut Repository, id: *const u8, out: *mut *mut Object) -> Status {
+    call(|| {
+        let repo = handle(repo, "repo")?;
+        let id = self::id(id, "id")?;
+        let out = out_param(out)?;
+        let object = repo
+            .easy
+            .try_find_object(id)?
+            .ok_or_else(|| Error::not_found(format!("The object {} could not be found", id)))?
+            .detach();
+        *out = Box::into_raw(Box::new(Object { inner: object }));
+        Ok(())
+    })
+}
+
+/// Return the kind of `object`.
+///
+/// Unlike functions returning a [`Status`], it doesn't check `object` for NULL.
+#[no_mangle]
+pub unsafe extern "C" fn gix_object_kind(object: *const Object) -> ObjectKind {
+    (*object).inner.kind.into()
+}
+
+/// Return the size of the data of `object` in bytes.
+///
+/// Unlike functions returning a [`Status`], it doesn't check `object` for NULL.
+#[no_mangle]
+pub unsafe extern "C" fn gix_object_size(object: *const Object) -> usize {
+    (*object).inner.data.len()
+}
+
+/// Return the data of `object`, which is valid as long as `object`.
+///
+/// Unlike functions returning a [`Status`], it doesn't check `object` for NULL.
+#[no_mangle]
+pub unsafe extern "C" fn gix_object_data(object: *const Object) -> *const u8 {
+    (*object).inner.data.as_ptr()
+}
+
+/// Free `object`, which may be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn gix_object_free(object: *mut Object) {
+    if !object.is_null() {
+        drop(Box::from_raw(object));
+    }
+}
@@ -0,0 +1,75 @@
+use std::{
+    os::raw::{c_char, c_int, c_void},
+    path::Path,
+};
+
+use os_str_bytes::OsStrBytes;
+
+use crate::{
+    capi::{c_str, call, handle, Error, Repository, Status},
+    prelude::ReferenceAccessExt,
+};
+
+/// Called for each reference with its full name, and either the id it points to or, if it is symbolic, the full name of
+/// the reference it points to, while the other is NULL. Return 0 to continue the iteration or any other value to stop it.
+pub type ReferenceCallback = Option<
+    unsafe extern "C" fn(
+        name: *const u8,
+        name_len: usize,
+        id: *const u8,
+        target: *const u8,
+        target_len: usize,
+        user_data: *mut c_void,
+    ) -> c_int,
+>;
+
+/// Call `cb` with `user_data` for each reference in the directory `prefix`, or for all references if `prefix` is NULL.
+#[no_mangle]
+pub unsafe extern "C" fn gix_references_foreach(
+    repo: *mut Repository,
+    prefix: *const c_char,
+    cb: ReferenceCallback,
+    user_data: *mut c_void,
+) -> Status {
+    call(|| {
+        let repo = handle(repo, "repo")?;
+        let cb = cb.ok_or_else(|| Error::invalid_argument("'cb' must not be NULL"))?;
+        let platform = repo.easy.references()?;
+        let iter = if prefix.is_null() {
+            platform.all()?
+        } else {
+            let prefix = Path::from_raw_bytes(c_str(prefix, "prefix")?)
+                .map_err(|_| Error::invalid_argument("'prefix' is not valid for this platform"))?;
+            platform.prefixed(prefix)?
+        };
+        for reference in iter {
+            let reference = reference.map_err(|err| Error::from(&*err))?;
+            let name = reference.name().as_bstr();
+            let stop = match reference.target() {
+                git_ref::TargetRef::Peeled(id) => cb(
+                    name.as_ptr(),
+                    name.len(),
+                    id.as_bytes().as_ptr(),
+                    std::ptr::null(),
+                    0,
+                    user_data,
+                ),
+                git_ref::TargetRef::Symbolic(target) => {
+                    let target = target.as_bstr();
+                    cb(
+                        name.as_ptr(),
+                        name.len(),
+                        std::ptr::null(),
+                        target.as_ptr(),
+                        target.len(),
+                        user_data,
+                    )
+                }
+            };
+            if stop != 0 {
+                break;
+            }
+        }
+        Ok(())
+    })
+}
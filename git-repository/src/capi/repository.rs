@@ -0,0 +1,81 @@
+use std::{borrow::Cow, os::raw::c_char, path::Path};
+
+use git_object::bstr::ByteSlice;
+use os_str_bytes::OsStrBytes;
+
+use crate::{
+    capi::{c_str, call, handle, out_param, Error, Status},
+    prelude::ObjectAccessExt,
+};
+
+/// An open repository, `gix_repository` in C.
+pub struct Repository {
+    pub(crate) easy: crate::EasyArcExclusive,
+}
+
+/// Open the repository at `path`, which is either its work tree or its git directory, and store it in `out`.
+#[no_mangle]
+pub unsafe extern "C" fn gix_repository_open(path: *const c_char, out: *mut *mut Repository) -> Status {
+    call(|| {
+        let path = to_path(path)?;
+        let out = out_param(out)?;
+        let repo = crate::Repository::open(path.into_owned()).map_err(|err| match err {
+            crate::open::Error::NotARepository(_) => Error::from(err).with_status(Status::NotFound),
+            err => err.into(),
+        })?;
+        *out = into_handle(repo);
+        Ok(())
+    })
+}
+
+/// Find the repository containing `path` by searching it and its parent directories, and store it in `out`.
+#[no_mangle]
+pub unsafe extern "C" fn gix_repository_discover(path: *const c_char, out: *mut *mut Repository) -> Status {
+    call(|| {
+        let path = to_path(path)?;
+        let out = out_param(out)?;
+        let repo = crate::Repository::discover(path).map_err(|err| match err {
+            crate::discover::Error::Discover(_) => Error::from(err).with_status(Status::NotFound),
+            err => err.into(),
+        })?;
+        *out = into_handle(repo);
+        Ok(())
+    })
+}
+
+/// Free `repo`, which may be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn gix_repository_free(repo: *mut Repository) {
+    if !repo.is_null() {
+        drop(Box::from_raw(repo));
+    }
+}
+
+/// Resolve the revision specification `spec` and write its id to `out_id`.
+#[no_mangle]
+pub unsafe extern "C" fn gix_revision_resolve(repo: *mut Repository, spec: *const c_char, out_id: *mut u8) -> Status {
+    call(|| {
+        let repo = handle(repo, "repo")?;
+        let spec = c_str(spec, "spec")?;
+        if out_id.is_null() {
+            return Err(Error::invalid_argument("'out_id' must not be NULL"));
+        }
+        let id = repo.easy.rev_parse(spec.as_bstr()).map_err(|err| match err {
+            crate::easy::rev_parse::Error::UnknownName { .. } => Error::from(err).with_status(Status::NotFound),
+            err => err.into(),
+        })?;
+        std::ptr::copy_nonoverlapping(id.as_ref().as_bytes().as_ptr(), out_id, id.as_ref().as_bytes().len());
+        Ok(())
+    })
+}
+
+fn into_handle(repo: crate::Repository) -> *mut Repository {
+    Box::into_raw(Box::new(Repository {
+        easy: repo.into_easy_arc_exclusive(),
+    }))
+}
+
+unsafe fn to_path<'a>(path: *const c_char) -> Result<Cow<'a, Path>, Error> {
+    Path::from_raw_bytes(c_str(path, "path")?)
+        .map_err(|_| Error::invalid_argument("'path' is not valid for this platform"))
+}
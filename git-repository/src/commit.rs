@@ -1,2 +1,90 @@
+use crate::bstr::BString;
+
 /// An empty array of a type usable with the `git::easy` API to help declaring no parents should be used
 pub const NO_PARENT_IDS: [git_hash::ObjectId; 0] = [];
+
+/// The name and email of an actor, without the time at which it acted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Identity {
+    /// The actor's name.
+    pub name: BString,
+    /// The actor's email.
+    pub email: BString,
+}
+
+/// A policy to create commit and tag objects which only depend on explicitly provided inputs, for reproducible builds.
+///
+/// Use it with [`commit_deterministic()`][crate::easy::ext::ObjectAccessExt::commit_deterministic()] and
+/// [`tag_deterministic()`][crate::easy::ext::ObjectAccessExt::tag_deterministic()].
+///
+/// There is no way to pass a time zone or a full [`git_actor::Time`], all signatures produced by it use `time` with
+/// a `+0000` offset so neither the wall-clock nor the local time zone can affect the object id.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Deterministic {
+    /// The author of commits.
+    pub author: Identity,
+    /// The committer of commits and the tagger of tags.
+    pub committer: Identity,
+    /// The time in seconds since the unix epoch to use for all signatures.
+    pub time: u32,
+}
+
+impl Deterministic {
+    /// Create a new instance from the `SOURCE_DATE_EPOCH` environment variable with the given `author` and `committer`,
+    /// or return `None` if it isn't set or if `GIT_AUTHOR_DATE` or `GIT_COMMITTER_DATE` is set, which take precedence.
+    pub fn from_env(author: Identity, committer: Identity) -> Result<Option<Self>, source_date_epoch::Error> {
+        if std::env::var_os("GIT_AUTHOR_DATE").is_some() || std::env::var_os("GIT_COMMITTER_DATE").is_some() {
+            return Ok(None);
+        }
+        Ok(source_date_epoch::from_env()?.map(|time| Deterministic {
+            author,
+            committer,
+            time,
+        }))
+    }
+
+    /// The signature of the author at our `time` in UTC.
+    pub fn author_signature(&self) -> git_actor::Signature {
+        self.signature(&self.author)
+    }
+
+    /// The signature of the committer, or tagger, at our `time` in UTC.
+    pub fn committer_signature(&self) -> git_actor::Signature {
+        self.signature(&self.committer)
+    }
+
+    fn signature(&self, identity: &Identity) -> git_actor::Signature {
+        git_actor::Signature {
+            name: identity.name.clone(),
+            email: identity.email.clone(),
+            time: git_actor::Time {
+                time: self.time,
+                offset: 0,
+                sign: git_actor::Sign::Plus,
+            },
+        }
+    }
+}
+
+///
+pub mod source_date_epoch {
+    /// The error returned by [`from_env()`] and [`Deterministic::from_env()`][super::Deterministic::from_env()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("SOURCE_DATE_EPOCH must be the amount of seconds since the unix epoch, got {:?}", .value)]
+        Parse { value: std::ffi::OsString },
+    }
+
+    /// Return the seconds since the unix epoch stored in the `SOURCE_DATE_EPOCH` environment variable, or `None` if it is not set.
+    pub fn from_env() -> Result<Option<u32>, Error> {
+        match std::env::var_os("SOURCE_DATE_EPOCH") {
+            None => Ok(None),
+            Some(value) => value
+                .to_str()
+                .and_then(|v| v.trim().parse().ok())
+                .map(Some)
+                .ok_or(Error::Parse { value }),
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//!
+///
+pub mod create {
+    use std::{collections::HashSet, sync::Arc};
+
+    use git_hash::ObjectId;
+    use git_odb::{compound, pack, FindExt};
+    use git_pack::data::output;
+    use git_ref::Target;
+
+    use crate::{
+        easy,
+        easy::ext::{ObjectAccessExt, ReferenceAccessExt},
+    };
+
+    /// The error produced while creating pack entries for a bundle.
+    pub type EntriesError = output::entry::iter_from_counts::Error<compound::find::Error>;
+    /// The chunks of pack entries of a bundle created by [`RepositoryAccessExt::create_bundle()`][easy::ext::RepositoryAccessExt::create_bundle()].
+    pub type Entries = Box<dyn Iterator<Item = Result<Vec<output::Entry>, EntriesError>>>;
+
+    type CountError = output::count::objects::Error<
+        git_pack::find::existing::Error<compound::find::Error>,
+        git_traverse::commit::ancestors::Error,
+    >;
+
+    /// The error returned by [`RepositoryAccessExt::create_bundle()`][easy::ext::RepositoryAccessExt::create_bundle()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Refusing to create a bundle without references")]
+        Empty,
+        #[error(transparent)]
+        FindReference(#[from] easy::reference::find::existing::Error),
+        #[error("The symbolic reference '{}' does not point to an existing reference", .name)]
+        UnbornReference { name: crate::bstr::BString },
+        #[error(transparent)]
+        FindObject(#[from] easy::object::find::existing::Error),
+        #[error(transparent)]
+        PeelToCommit(#[from] easy::object::peel::to_kind::Error),
+        #[error(transparent)]
+        Decode(#[from] git_object::decode::Error),
+        #[error(transparent)]
+        OpenObjectDatabase(#[from] git_odb::linked::init::Error),
+        #[error(transparent)]
+        Traverse(#[from] git_traverse::commit::ancestors::Error),
+        #[error(transparent)]
+        Count(#[from] CountError),
+        #[error(transparent)]
+        BorrowRepo(#[from] easy::borrow::repo::Error),
+    }
+
+    /// The maximum amount of symbolic references to follow, like git does.
+    const MAX_SYMBOLIC_REFERENCE_DEPTH: usize = 5;
+
+    pub(crate) fn writer<A>(
+        access: &A,
+        refs: &[&str],
+        since: Option<ObjectId>,
+    ) -> Result<git_bundle::BundleWriter<Entries>, Error>
+    where
+        A: easy::Access + Sized,
+    {
+        if refs.is_empty() {
+            return Err(Error::Empty);
+        }
+        let mut bundle_refs = Vec::with_capacity(refs.len());
+        let mut tag_ids = Vec::new();
+        let mut commit_ids = Vec::with_capacity(refs.len());
+        for name in refs {
+            let reference = access.find_reference(*name)?;
+            let name = reference.name().as_bstr().to_owned();
+            let id = direct_target(access, reference.detach().target, name.as_ref())?;
+            let commit_id = access.find_object(id)?.peel_to_commit()?.id;
+            if commit_id != id {
+                tag_ids.push(id);
+            }
+            commit_ids.push(commit_id);
+            bundle_refs.push((id, name));
+        }
+
+        let prerequisites = match since {
+            Some(id) => {
+                let commit = access.find_object(id)?.peel_to_commit()?;
+                let comment = commit.commit()?.message_summary().into_owned();
+                vec![git_bundle::Prerequisite { id: commit.id, comment }]
+            }
+            None => Vec::new(),
+        };
+
+        let (db, thread_limit, deterministic) = {
+            let repo = access.repo()?;
+            (
+                Arc::new(git_odb::linked::Store::at(repo.objects_dir())?),
+                repo.object_access_parallelism().thread_limit(),
+                repo.deterministic_packs(),
+            )
+        };
+        let hidden: HashSet<_> = match prerequisites.first() {
+            Some(prerequisite) => git_traverse::commit::Ancestors::new(
+                Some(prerequisite.id),
+                git_traverse::commit::ancestors::State::default(),
+                find_commit(&db),
+            )
+            .collect::<Result<_, _>>()?,
+            None => HashSet::new(),
+        };
+        let ids = tag_ids
+            .into_iter()
+            .map(Ok)
+            .chain(git_traverse::commit::Ancestors::filtered(
+                commit_ids,
+                git_traverse::commit::ancestors::State::default(),
+                find_commit(&db),
+                |id| !hidden.contains(id),
+            ));
+        let (mut counts, _outcome) = output::count::objects_unthreaded(
+            Arc::clone(&db),
+            (&mut pack::cache::Never, &mut pack::cache::object::Never),
+            ids,
+            git_features::progress::Discard,
+            &std::sync::atomic::AtomicBool::default(),
+            output::count::objects::ObjectExpansion::TreeAdditionsComparedToAncestor,
+        )?;
+
+        // Counting adds the commits and trees the new commits are compared to, which are present on the receiving side already.
+        let hidden_trees: HashSet<_> = {
+            let mut buf = Vec::new();
+            let mut find_commit = find_commit(&db);
+            counts
+                .iter()
+                .filter(|count| hidden.contains(&count.id))
+                .filter_map(|count| find_commit(&count.id, &mut buf).and_then(|mut commit| commit.tree_id()))
+                .collect()
+        };
+        counts.retain(|count| !hidden.contains(&count.id) && !hidden_trees.contains(&count.id));
+
+        let num_entries = counts.len() as u32;
+        let entries = output::InOrderIter::from(output::entry::iter_from_counts(
+            counts,
+            db,
+            || pack::cache::Never,
+            git_features::progress::Discard,
+            output::entry::iter_from_counts::Options {
+                thread_limit,
+                deterministic,
+                ..Default::default()
+            },
+        ));
+        Ok(git_bundle::BundleWriter::new(
+            bundle_refs,
+            prerequisites,
+            Box::new(entries),
+            num_entries,
+        ))
+    }
+
+    fn find_commit(
+        db: &git_odb::linked::Store,
+    ) -> impl for<'a> FnMut(&git_hash::oid, &'a mut Vec<u8>) -> Option<git_object::CommitRefIter<'a>> + '_ {
+        move |id, buf| db.find_commit_iter(id, buf, &mut pack::cache::Never).ok()
+    }
+
+    fn direct_target<A>(access: &A, mut target: Target, name: &crate::bstr::BStr) -> Result<ObjectId, Error>
+    where
+        A: easy::Access + Sized,
+    {
+        for _ in 0..MAX_SYMBOLIC_REFERENCE_DEPTH {
+            match target {
+                Target::Peeled(id) => return Ok(id),
+                Target::Symbolic(symbolic_name) => match access
+                    .try_find_reference(symbolic_name.to_partial())
+                    .map_err(easy::reference::find::existing::Error::from)?
+                {
+                    Some(reference) => target = reference.detach().target,
+                    None => break,
+                },
+            }
+        }
+        Err(Error::UnbornReference { name: name.to_owned() })
+    }
+}
@@ -0,0 +1,249 @@
+//! Find the references whose commits contain a given commit in their history, like `git branch --contains` and
+//! `git tag --contains` do.
+use std::collections::HashMap;
+
+use git_hash::ObjectId;
+use git_ref::FullName;
+
+use crate::{
+    easy,
+    easy::ext::{ObjectAccessExt, ReferenceAccessExt},
+};
+
+/// The generation of commits which aren't in the commit-graph, which is larger than the one of all commits in it.
+const GENERATION_NUMBER_INFINITY: u32 = u32::MAX;
+
+/// Counters to learn how much work it took to find the containing references.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Statistics {
+    /// True if a commit-graph was available to obtain generation numbers and parents from.
+    pub used_commit_graph: bool,
+    /// The amount of commits whose parents were looked up.
+    pub commits_visited: usize,
+    /// The amount of commits that were not traversed as their generation number is lower than the one of the given commit,
+    /// which means they can't contain it.
+    pub commits_cut_off: usize,
+}
+
+/// The outcome of [`ReferenceAccessExt::branches_containing()`] and [`ReferenceAccessExt::tags_containing()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Outcome {
+    /// The full names of all references whose commit contains the given commit in its history, ordered by name.
+    pub names: Vec<FullName>,
+    /// Information about the traversal.
+    pub statistics: Statistics,
+}
+
+mod error {
+    use git_hash::ObjectId;
+
+    use crate::easy;
+
+    /// The error returned by [`ReferenceAccessExt::branches_containing()`][crate::easy::ext::ReferenceAccessExt::branches_containing()]
+    /// and [`ReferenceAccessExt::tags_containing()`][crate::easy::ext::ReferenceAccessExt::tags_containing()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Object {id} is not a commit")]
+        NotACommit { id: ObjectId },
+        #[error("Commit {id} could not be found")]
+        NotFound { id: ObjectId },
+        #[error(transparent)]
+        CommitGraph(#[from] git_commitgraph::graph::Error),
+        #[error(transparent)]
+        CommitGraphParent(#[from] git_commitgraph::file::commit::Error),
+        #[error(transparent)]
+        References(#[from] easy::reference::iter::Error),
+        #[error(transparent)]
+        ReferencesInit(#[from] easy::reference::iter::init::Error),
+        #[error("A reference could not be read")]
+        ReferenceIter(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+        #[error(transparent)]
+        FindExistingObject(#[from] easy::object::find::existing::Error),
+        #[error(transparent)]
+        FindObject(#[from] easy::object::find::Error),
+        #[error(transparent)]
+        PeelObject(#[from] easy::object::peel::to_kind::Error),
+        #[error("BUG: The repository could not be borrowed")]
+        BorrowRepo(#[from] easy::borrow::repo::Error),
+    }
+}
+pub use error::Error;
+
+/// Which references to consider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Kind {
+    LocalBranches,
+    Tags,
+}
+
+/// Return the names of all references of the given `kind` whose commit contains the commit `id` in its history.
+///
+/// All references are traversed at once, with each commit visited only once and the results shared between references.
+/// If a commit-graph is present, commits whose generation number is lower than the one of `id` aren't traversed as they
+/// can't contain it, which limits the traversal to the history above `id`.
+pub(crate) fn references_containing<A>(access: &A, id: ObjectId, kind: Kind) -> Result<Outcome, Error>
+where
+    A: easy::Access + Sized,
+{
+    let (graph, skip_missing) = {
+        let repo = access.repo()?;
+        let info_dir = repo.objects_dir().join("info");
+        let graph = if info_dir.join("commit-graph").is_file() || info_dir.join("commit-graphs").is_dir() {
+            Some(git_commitgraph::Graph::from_info_dir(&info_dir)?)
+        } else {
+            None
+        };
+        (graph, repo.is_shallow() || repo.is_partial())
+    };
+    let target = access.find_object(id)?.peel_to_kind(git_object::Kind::Commit)?.id;
+
+    let mut tips = Vec::new();
+    {
+        let references = access.references()?;
+        let iter = match kind {
+            Kind::LocalBranches => references.local_branches()?,
+            Kind::Tags => references.tags()?,
+        };
+        for reference in iter {
+            let reference = reference.map_err(Error::ReferenceIter)?.detach();
+            let tip = match reference.target {
+                git_ref::Target::Peeled(id) => id,
+                git_ref::Target::Symbolic(_) => continue,
+            };
+            let object = access.find_object(tip)?.peel_tags_to_end()?;
+            if object.kind == git_object::Kind::Commit {
+                tips.push((reference.name, object.id));
+            }
+        }
+    }
+
+    let mut traversal = Traversal {
+        access,
+        graph: graph.as_ref(),
+        skip_missing,
+        target,
+        target_generation: GENERATION_NUMBER_INFINITY,
+        contains: HashMap::new(),
+        statistics: Statistics {
+            used_commit_graph: graph.is_some(),
+            ..Default::default()
+        },
+    };
+    traversal.target_generation = traversal.generation(&target);
+    let mut names = Vec::new();
+    for (name, tip) in tips {
+        if traversal.contains(tip)? {
+            names.push(name);
+        }
+    }
+    names.sort();
+    Ok(Outcome {
+        names,
+        statistics: traversal.statistics,
+    })
+}
+
+struct Traversal<'a, A> {
+    access: &'a A,
+    graph: Option<&'a git_commitgraph::Graph>,
+    skip_missing: bool,
+    target: ObjectId,
+    target_generation: u32,
+    /// Whether a commit contains the target, for all commits whose parents were looked at.
+    contains: HashMap<ObjectId, bool>,
+    statistics: Statistics,
+}
+
+impl<'a, A> Traversal<'a, A>
+where
+    A: easy::Access + Sized,
+{
+    fn generation(&self, id: &ObjectId) -> u32 {
+        self.graph
+            .and_then(|graph| graph.commit_by_id(id))
+            .map_or(GENERATION_NUMBER_INFINITY, |commit| commit.generation())
+    }
+
+    /// Return the parents of the commit `id`, or `None` if it's missing and missing commits are expected.
+    fn parents(&self, id: &ObjectId) -> Result<Option<Vec<ObjectId>>, Error> {
+        if let Some(commit) = self.graph.and_then(|graph| graph.commit_by_id(id)) {
+            let graph = self.graph.expect("graph present if commit is");
+            let mut parents = Vec::new();
+            for pos in commit.iter_parents() {
+                parents.push(graph.id_at(pos?).to_owned());
+            }
+            return Ok(Some(parents));
+        }
+        let object = match self.access.try_find_object(*id)? {
+            Some(object) => object,
+            None if self.skip_missing => return Ok(None),
+            None => return Err(Error::NotFound { id: *id }),
+        };
+        if object.kind != git_object::Kind::Commit {
+            return Err(Error::NotACommit { id: *id });
+        }
+        Ok(Some(
+            object
+                .commit_iter()
+                .filter_map(|token| match token {
+                    Ok(git_object::commit::ref_iter::Token::Parent { id }) => Some(id),
+                    _ => None,
+                })
+                .collect(),
+        ))
+    }
+
+    /// Return true if the commit `tip` contains the target commit, visiting all commits above the target's generation
+    /// in its history depth-first unless they were visited before.
+    fn contains(&mut self, tip: ObjectId) -> Result<bool, Error> {
+        let mut pending_parents = HashMap::<ObjectId, Vec<ObjectId>>::new();
+        let mut stack = vec![tip];
+        while let Some(id) = stack.last().copied() {
+            if self.contains.contains_key(&id) {
+                stack.pop();
+                continue;
+            }
+            if id == self.target {
+                self.contains.insert(id, true);
+                stack.pop();
+                continue;
+            }
+            if self.generation(&id) < self.target_generation {
+                self.statistics.commits_cut_off += 1;
+                self.contains.insert(id, false);
+                stack.pop();
+                continue;
+            }
+            let parents = match pending_parents.remove(&id) {
+                Some(parents) => parents,
+                None => {
+                    self.statistics.commits_visited += 1;
+                    match self.parents(&id)? {
+                        Some(parents) => {
+                            let unknown: Vec<_> = parents
+                                .iter()
+                                .filter(|parent| !self.contains.contains_key(*parent))
+                                .copied()
+                                .collect();
+                            if !unknown.is_empty()
+                                && !parents.iter().any(|parent| self.contains.get(parent) == Some(&true))
+                            {
+                                stack.extend(unknown.iter().copied());
+                                pending_parents.insert(id, parents);
+                                continue;
+                            }
+                            parents
+                        }
+                        None => Vec::new(),
+                    }
+                }
+            };
+            let contains = parents.iter().any(|parent| self.contains.get(parent) == Some(&true));
+            self.contains.insert(id, contains);
+            stack.pop();
+        }
+        Ok(self.contains[&tip])
+    }
+}
@@ -1,7 +1,7 @@
 use std::{convert::TryInto, ops::DerefMut};
 
 use git_hash::ObjectId;
-use git_odb::{Find, FindExt};
+use git_odb::Find;
 use git_pack::cache::Object;
 use git_ref::{
     transaction::{LogChange, PreviousValue, RefLog},
@@ -9,13 +9,90 @@ use git_ref::{
 };
 
 use crate::{
+    bstr::BStr,
     easy,
-    easy::{commit, object, ObjectRef, Oid},
+    easy::{commit, ext::ReferenceAccessExt, object, ObjectRef, Oid, TreeRef},
     ext::ObjectIdExt,
 };
 
+/// Return the kind of the object with `id` if it is well-known and empty, like the empty tree or the empty blob.
+///
+/// These are assumed to always exist, just like in `git`, even if they are not present in the object database.
+fn well_known_empty_object_kind(id: &ObjectId) -> Option<git_object::Kind> {
+    let hash_kind = id.kind();
+    if *id == ObjectId::empty_tree(hash_kind) {
+        Some(git_object::Kind::Tree)
+    } else if *id == ObjectId::empty_blob(hash_kind) {
+        Some(git_object::Kind::Blob)
+    } else {
+        None
+    }
+}
+
+/// Call `find` with the object database to look up an object in, and if it's missing, refresh the object database if it's stale
+/// and call `find` again, as the object may have been moved into a new pack by maintenance.
+///
+/// The disk is looked at only once per loaded object database to learn if it's stale, so misses remain cheap.
+/// Failing to refresh leaves the current state, in which the object is missing.
+fn retry_if_stale<A: easy::Access, T, E>(
+    access: &A,
+    mut find: impl FnMut(&git_odb::linked::Store) -> Result<Option<T>, E>,
+) -> Result<Option<T>, E>
+where
+    E: From<easy::borrow::repo::Error> + From<easy::borrow::state::Error>,
+{
+    let mut find_in_current_odb = || -> Result<_, E> {
+        match access.state().try_borrow_odb()?.as_ref() {
+            Some(odb) => find(odb),
+            None => find(&access.repo()?.odb),
+        }
+    };
+    match find_in_current_odb()? {
+        None if refreshed_because_stale(access) => find_in_current_odb(),
+        found => Ok(found),
+    }
+}
+
+/// Refresh the object database of the repository if it's stale and can be borrowed mutably right away, or the one of
+/// this handle otherwise, and return true if it was refreshed.
+fn refreshed_because_stale<A: easy::Access>(access: &A) -> bool {
+    let mut own_odb = match access.state().try_borrow_mut_odb() {
+        Ok(odb) => odb,
+        Err(_) => return false,
+    };
+    if let Some(odb) = own_odb.as_mut() {
+        return odb.is_stale_after_miss() && odb.refresh().is_ok();
+    }
+    if !access.repo().map_or(false, |repo| repo.odb.is_stale_after_miss()) {
+        return false;
+    }
+    // Never wait for exclusive access, as this thread may hold a shared borrow of the repository, like an ancestor iterator.
+    match access.try_repo_mut() {
+        Some(mut repo) => {
+            let refreshed = repo.odb.refresh().is_ok();
+            repo.update_abbreviation();
+            refreshed
+        }
+        None => match access.repo().map(|repo| repo.odb.to_refreshed()) {
+            Ok(Ok(odb)) => {
+                *own_odb = Some(odb);
+                true
+            }
+            _ => false,
+        },
+    }
+}
+
 /// Methods related to object creation.
 pub trait ObjectAccessExt: easy::Access + Sized {
+    /// Resolve the revision specification `spec`, like `HEAD~2^2`, `v1.0^{commit}`, `a1b2c3` or `main:README.md`, to the
+    /// id of the object it refers to.
+    ///
+    /// See the [`rev_parse`][easy::rev_parse] module for the supported syntax.
+    fn rev_parse(&self, spec: &BStr) -> Result<Oid<'_, Self>, easy::rev_parse::Error> {
+        easy::rev_parse::resolve(self, spec)
+    }
+
     /// Find the object with `id` in the object database or return an error if it could not be found.
     ///
     /// There are various legitimate reasons for an object to not be present, which is why
@@ -31,6 +108,10 @@ pub trait ObjectAccessExt: easy::Access + Sized {
     ///
     /// In order to get the kind of the object, is must be fully decoded from storage if it is packed with deltas.
     /// Loose object could be partially decoded, even though that's not implemented.
+    ///
+    /// # Well-known objects
+    ///
+    /// The empty tree and the empty blob are always found, even if they are not contained in the object database.
     fn find_object(&self, id: impl Into<ObjectId>) -> Result<ObjectRef<'_, Self>, object::find::existing::Error> {
         let state = self.state();
         let id = id.into();
@@ -43,11 +124,21 @@ pub trait ObjectAccessExt: easy::Access + Sized {
                     return ObjectRef::from_current_buf(id, kind, self).map_err(Into::into);
                 }
             }
-            let kind = self
-                .repo()?
-                .odb
-                .find(&id, &mut buf, state.try_borrow_mut_pack_cache()?.deref_mut())?
-                .kind;
+            let kind = match retry_if_stale(self, |odb| -> Result<_, object::find::existing::Error> {
+                Ok(odb
+                    .try_find(&id, &mut buf, state.try_borrow_mut_pack_cache()?.deref_mut())
+                    .map_err(git_pack::find::existing::Error::Find)?
+                    .map(|obj| obj.kind))
+            })? {
+                Some(kind) => kind,
+                None => match well_known_empty_object_kind(&id) {
+                    Some(kind) => {
+                        buf.clear();
+                        kind
+                    }
+                    None => return Err(git_pack::find::existing::Error::NotFound { oid: id }.into()),
+                },
+            };
 
             if let Some(c) = object_cache.deref_mut() {
                 c.put(id, kind, &buf);
@@ -76,24 +167,73 @@ pub trait ObjectAccessExt: easy::Access + Sized {
                 return Ok(Some(ObjectRef::from_current_buf(id, kind, self)?));
             }
         }
-        match self
-            .repo()?
-            .odb
-            .try_find(&id, &mut buf, state.try_borrow_mut_pack_cache()?.deref_mut())?
-        {
-            Some(obj) => {
-                let kind = obj.kind;
-                drop(obj);
+        match retry_if_stale(self, |odb| -> Result<_, object::find::Error> {
+            Ok(odb
+                .try_find(&id, &mut buf, state.try_borrow_mut_pack_cache()?.deref_mut())?
+                .map(|obj| obj.kind))
+        })? {
+            Some(kind) => {
                 if let Some(c) = object_cache.deref_mut() {
                     c.put(id, kind, &buf);
                 }
                 drop(buf);
                 Ok(Some(ObjectRef::from_current_buf(id, kind, self)?))
             }
-            None => Ok(None),
+            None => match well_known_empty_object_kind(&id) {
+                Some(kind) => {
+                    buf.clear();
+                    drop(buf);
+                    Ok(Some(ObjectRef::from_current_buf(id, kind, self)?))
+                }
+                None => Ok(None),
+            },
         }
     }
 
+    /// Return the empty tree, which is always available even if it is not contained in the object database.
+    ///
+    /// Diffing against it lists all entries of the other tree as additions.
+    fn empty_tree(&self) -> Result<TreeRef<'_, Self>, object::find::existing::Error> {
+        let id = ObjectId::empty_tree(self.repo()?.hash_kind);
+        self.state().try_borrow_mut_buf()?.clear();
+        Ok(ObjectRef::from_current_buf(id, git_object::Kind::Tree, self)?.into_tree())
+    }
+
+    /// Return true if the object with `id` is contained in the object database.
+    fn contains_object(&self, id: impl Into<ObjectId>) -> Result<bool, easy::borrow::repo::Error> {
+        let id = id.into();
+        Ok(self.repo()?.odb.contains(id) || well_known_empty_object_kind(&id).is_some())
+    }
+
+    /// Obtain the kind and size of the object with `id` without decoding it, or return an error if it could not be found.
+    ///
+    /// Use this instead of [`find_object(…)`][ObjectAccessExt::find_object()] if the object data isn't needed as
+    /// it avoids decompressing and resolving the object, and does not borrow the shared buffer.
+    fn find_object_header(
+        &self,
+        id: impl Into<ObjectId>,
+    ) -> Result<(git_object::Kind, u64), object::find::existing::Error> {
+        let id = id.into();
+        retry_if_stale(self, |odb| -> Result<_, object::find::existing::Error> {
+            Ok(odb.try_find_header(id).map_err(git_pack::find::existing::Error::Find)?)
+        })?
+        .or_else(|| well_known_empty_object_kind(&id).map(|kind| (kind, 0)))
+        .ok_or(git_pack::find::existing::Error::NotFound { oid: id })
+        .map_err(Into::into)
+    }
+
+    /// Try to obtain the kind and size of the object with `id` without decoding it, or return `None` it it wasn't found.
+    fn try_find_object_header(
+        &self,
+        id: impl Into<ObjectId>,
+    ) -> Result<Option<(git_object::Kind, u64)>, object::find::Error> {
+        let id = id.into();
+        Ok(retry_if_stale(self, |odb| -> Result<_, object::find::Error> {
+            Ok(odb.try_find_header(id)?)
+        })?
+        .or_else(|| well_known_empty_object_kind(&id).map(|kind| (kind, 0))))
+    }
+
     /// Write the given object into the object database and return its object id.
     fn write_object(&self, object: impl git_object::WriteTo) -> Result<Oid<'_, Self>, object::write::Error> {
         use git_odb::Write;
@@ -184,6 +324,50 @@ pub trait ObjectAccessExt: easy::Access + Sized {
         )?;
         Ok(commit_id)
     }
+
+    /// Like [`commit(…)`][ObjectAccessExt::commit()], but with author and committer signatures taken from the `policy`
+    /// to assure the resulting commit id only depends on the given inputs.
+    fn commit_deterministic<Name, E>(
+        &self,
+        reference: Name,
+        policy: &crate::commit::Deterministic,
+        message: impl AsRef<str>,
+        tree: impl Into<ObjectId>,
+        parents: impl IntoIterator<Item = impl Into<ObjectId>>,
+    ) -> Result<Oid<'_, Self>, commit::Error>
+    where
+        Name: TryInto<FullName, Error = E>,
+        commit::Error: From<E>,
+    {
+        let author = policy.author_signature();
+        let committer = policy.committer_signature();
+        self.commit(reference, &author.to_ref(), &committer.to_ref(), message, tree, parents)
+    }
+
+    /// Create an annotated tag object with `name` (and without `refs/tags/` prefix) and `message`, pointing to `target` of
+    /// `target_kind`, with the tagger signature taken from the `policy` to assure the tag id only depends on the given inputs.
+    ///
+    /// The tag reference pointing to the new tag object is created with `constraint` and returned.
+    fn tag_deterministic(
+        &self,
+        name: impl AsRef<str>,
+        target: impl Into<ObjectId>,
+        target_kind: git_object::Kind,
+        policy: &crate::commit::Deterministic,
+        message: impl AsRef<str>,
+        constraint: PreviousValue,
+    ) -> Result<easy::Reference<'_, Self>, easy::tag::create::Error> {
+        let tag = git_object::Tag {
+            target: target.into(),
+            target_kind,
+            name: name.as_ref().into(),
+            tagger: Some(policy.committer_signature()),
+            message: message.as_ref().into(),
+            pgp_signature: None,
+        };
+        let tag_id = self.write_object(&tag)?;
+        Ok(self.tag(name, tag_id, constraint)?)
+    }
 }
 
 impl<A> ObjectAccessExt for A where A: easy::Access + Sized {}
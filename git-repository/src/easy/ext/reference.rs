@@ -165,11 +165,29 @@ pub trait ReferenceAccessExt: easy::Access + Sized {
             }
         };
         let repo = self.repo()?;
-        repo.refs
-            .transaction()
-            .prepare(edits, lock_mode)?
-            .commit(committer)
-            .map_err(Into::into)
+        let edits = repo.refs.transaction().prepare(edits, lock_mode)?.commit(committer)?;
+        Ok(edits)
+    }
+
+    /// Prepare a transaction to edit one or more references as described by their `edits`, with `lock_mode` deciding on how
+    /// to handle competing transactions. `log_committer` is the name appearing in reference logs.
+    ///
+    /// All references are locked and their expected previous values are validated before the transaction is returned, ready to
+    /// be [committed][reference::transaction::PreparedTransaction::commit()] or rolled back later, for instance once an external
+    /// system agreed to the change.
+    fn prepare_reference_transaction(
+        &self,
+        edits: impl IntoIterator<Item = RefEdit>,
+        lock_mode: lock::acquire::Fail,
+        log_committer: Option<&actor::Signature>,
+    ) -> Result<reference::transaction::PreparedTransaction, reference::edit::Error> {
+        let committer = match log_committer {
+            Some(c) => c.to_owned(),
+            None => self.committer()?,
+        };
+        let repo = self.repo()?;
+        let transaction = repo.refs.clone().into_transaction().prepare(edits, lock_mode)?;
+        Ok(reference::transaction::PreparedTransaction::new(transaction, committer))
     }
 
     /// Return the repository head, an abstraction to help dealing with the `HEAD` reference.
@@ -242,6 +260,93 @@ pub trait ReferenceAccessExt: easy::Access + Sized {
             Err(err) => Err(err.into()),
         }
     }
+
+    /// Compare the references within `scope_prefix`, like `refs/remotes/origin/`, with the `expected` names and values, and
+    /// return how they differ.
+    ///
+    /// All `expected` references must be within `scope_prefix`, which is a directory like `refs/heads` whose trailing `/` is optional,
+    /// so it includes `refs/heads/main` but not `refs/heads-old/main`, or includes all references if empty.
+    /// Existing references are read in a single pass over loose references and the packed-refs buffer,
+    /// and symbolic references never match as they are compared without being peeled.
+    ///
+    /// Use [`apply_ref_diff()`][ReferenceAccessExt::apply_ref_diff()] to make the references match the expectation.
+    fn compare_refs(
+        &self,
+        expected: impl IntoIterator<Item = (FullName, ObjectId)>,
+        scope_prefix: impl AsRef<str>,
+    ) -> Result<reference::compare::Outcome, reference::compare::Error> {
+        reference::compare::compare(self, expected, scope_prefix.as_ref())
+    }
+
+    /// Create missing references, delete extra ones and update mismatched ones as found by
+    /// [`compare_refs()`][ReferenceAccessExt::compare_refs()] in a single transaction, writing `log_message` into reference logs.
+    ///
+    /// The transaction fails without changing anything if one of the references changed since it was compared.
+    /// Returns the committed edits, see [`edit_references()`][ReferenceAccessExt::edit_references()] for details.
+    fn apply_ref_diff(
+        &self,
+        diff: reference::compare::Outcome,
+        log_message: impl Into<BString>,
+    ) -> Result<Vec<RefEdit>, reference::edit::Error> {
+        self.edit_references(diff.into_edits(log_message), DEFAULT_LOCK_MODE, None)
+    }
+
+    /// Return an iterator over all notes in `notes_ref`, or in [`refs/notes/commits`][easy::note::DEFAULT_REFERENCE] if `None`.
+    ///
+    /// The iterator is empty if the notes reference doesn't exist.
+    fn iter_notes(
+        &self,
+        notes_ref: Option<PartialNameRef<'_>>,
+    ) -> Result<easy::note::Iter<'_, Self>, easy::note::Error> {
+        easy::note::Iter::new(self, notes_ref)
+    }
+
+    /// Return the names of all local branches whose commit contains the commit `id` in its history, like `git branch --contains`.
+    ///
+    /// All branches are traversed at once, and with a commit-graph only the history above `id` is visited.
+    fn branches_containing(&self, id: impl Into<ObjectId>) -> Result<easy::contains::Outcome, easy::contains::Error> {
+        easy::contains::references_containing(self, id.into(), easy::contains::Kind::LocalBranches)
+    }
+
+    /// Return the names of all tags pointing to a commit which contains the commit `id` in its history, like `git tag --contains`.
+    ///
+    /// Tags that don't point to a commit, directly or through tag objects, are skipped.
+    fn tags_containing(&self, id: impl Into<ObjectId>) -> Result<easy::contains::Outcome, easy::contains::Error> {
+        easy::contains::references_containing(self, id.into(), easy::contains::Kind::Tags)
+    }
+
+    /// Replace `original` with `replacement` by creating a reference in `refs/replace/` like `git replace` does, and return it.
+    ///
+    /// Both objects must exist and be of the same kind unless `options` allow kind changes. Objects can't replace themselves,
+    /// and replacements forming cycles or chains longer than [`options.max_depth`][easy::replace::Options::max_depth] with the
+    /// existing ones are refused, as is replacing an object which is already replaced.
+    fn create_replacement(
+        &self,
+        original: impl Into<ObjectId>,
+        replacement: impl Into<ObjectId>,
+        options: easy::replace::Options,
+    ) -> Result<Reference<'_, Self>, easy::replace::Error> {
+        easy::replace::create(self, original.into(), replacement.into(), options)
+    }
+
+    /// Return all replacements in `refs/replace/`, like `git replace --list` does.
+    fn list_replacements(&self) -> Result<easy::replace::Map, easy::replace::Error> {
+        easy::replace::list(self)
+    }
+
+    /// Delete the replacement of `original` and return the object it was replaced with, or `None` if it wasn't replaced.
+    fn delete_replacement(&self, original: impl Into<ObjectId>) -> Result<Option<ObjectId>, easy::replace::Error> {
+        easy::replace::delete(self, original.into())
+    }
+
+    /// Return a platform to list all tags in an order like `git tag --sort=<key>` does.
+    fn list_tags(&self) -> Result<easy::tag::List<'_, Self>, easy::reference::iter::Error> {
+        Ok(easy::tag::List {
+            references: self.references()?,
+            sort: Default::default(),
+            version_sort_suffixes: None,
+        })
+    }
 }
 
 impl<A> ReferenceAccessExt for A where A: easy::Access + Sized {}
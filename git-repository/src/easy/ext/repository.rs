@@ -11,9 +11,17 @@ pub trait RepositoryAccessExt: easy::Access + Sized {
     /// * …the GIT_(AUTHOR|COMMITTER)_(NAME|EMAIL|DATE) environment variables…
     ///
     /// …and in that order.
+    ///
+    /// If `GIT_COMMITTER_DATE` is unset, the time is taken from a valid `SOURCE_DATE_EPOCH` with a `+0000` offset.
     fn committer(&self) -> easy::borrow::repo::Result<git_actor::Signature> {
         // TODO: actually do the work, probably that should be cached and be refreshable
-        Ok(git_actor::Signature::empty())
+        let mut committer = git_actor::Signature::empty();
+        if std::env::var_os("GIT_COMMITTER_DATE").is_none() {
+            if let Ok(Some(time)) = crate::commit::source_date_epoch::from_env() {
+                committer.time.time = time;
+            }
+        }
+        Ok(committer)
     }
 
     /// The kind of hash the repository is configured to use.
@@ -21,11 +29,88 @@ pub trait RepositoryAccessExt: easy::Access + Sized {
         self.repo().map(|r| r.hash_kind)
     }
 
+    /// The amount of hexadecimal characters to abbreviate object ids to, as configured by `core.abbrev` or derived from the
+    /// amount of objects if it is unset or `auto`.
+    ///
+    /// Abbreviated ids are extended as needed to be unambiguous, see [`Oid::shorten()`][easy::Oid::shorten()].
+    fn abbreviation(&self) -> easy::borrow::repo::Result<usize> {
+        self.repo().map(|r| r.abbrev)
+    }
+
+    /// Prepare a bundle containing `refs` and all objects reachable from them, ready to be [written][git_bundle::BundleWriter::write_to()].
+    ///
+    /// If `since` is set, the bundle becomes incremental and only contains objects not reachable from this commit,
+    /// which is recorded as prerequisite the receiving repository has to have.
+    fn create_bundle(
+        &self,
+        refs: &[&str],
+        since: Option<git_hash::ObjectId>,
+    ) -> Result<git_bundle::BundleWriter<easy::bundle::create::Entries>, easy::bundle::create::Error> {
+        easy::bundle::create::writer(self, refs, since)
+    }
+
+    /// The version of the protocol to request when connecting to remotes, as configured by `protocol.version`,
+    /// or [V2][git_protocol::transport::Protocol::V2] if it is unset.
+    ///
+    /// To override it for a single connection, like forcing V1 to debug a server, pass another version when connecting.
+    ///
+    /// Invalid values are only an error here, when connecting, so repositories can still be opened and used for everything else.
+    #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
+    fn protocol_version(&self) -> Result<git_protocol::transport::Protocol, easy::protocol::version::Error> {
+        match &self.repo()?.protocol_version {
+            Some(value) => git_protocol::transport::Protocol::from_config_value(value.as_ref()).ok_or_else(|| {
+                easy::protocol::version::Error::Invalid {
+                    value: value.to_owned(),
+                }
+            }),
+            None => Ok(git_protocol::transport::Protocol::V2),
+        }
+    }
+
     /// Refresh persistent object database structures to reflect the state on disk.
     fn refresh_object_database(&self) -> Result<(), easy::odb::refresh::Error> {
-        self.repo_mut()?.deref_mut().odb.refresh()?;
+        let mut repo = self.repo_mut()?;
+        repo.deref_mut().odb.refresh()?;
+        repo.update_abbreviation();
+        *self.state().try_borrow_mut_odb()? = None;
         Ok(())
     }
+
+    /// [Refresh the object database][RepositoryAccessExt::refresh_object_database()] if [it is stale][git_odb::linked::Store::is_stale()],
+    /// as its packs, multi-pack index or commit-graph changed, like after [maintenance][RepositoryAccessExt::auto_maintenance()]
+    /// or `git gc`, and return true if it was refreshed.
+    ///
+    /// This happens automatically when objects can't be found, and handles which can't borrow the repository mutably at the time
+    /// continue with a refreshed object database of their own.
+    fn refresh_object_database_if_stale(&self) -> Result<bool, easy::odb::refresh::Error> {
+        if !self.repo()?.odb.is_stale() {
+            return Ok(false);
+        }
+        self.refresh_object_database()?;
+        Ok(true)
+    }
+
+    /// Estimate if the object database needs maintenance with cheap heuristics similar to the ones of `git gc --auto`,
+    /// comparing the amount of loose objects and packs to the limits configured with `gc.auto` and `gc.autoPackLimit`.
+    ///
+    /// The amount of loose objects is estimated by counting the ones in a single fan-out directory.
+    fn maintenance_needed(&self) -> Result<easy::maintenance::Estimate, easy::maintenance::Error> {
+        let repo = self.repo()?;
+        easy::maintenance::estimate(repo.objects_dir(), repo.hash_kind, repo.maintenance_limits)
+    }
+
+    /// If [maintenance is needed][RepositoryAccessExt::maintenance_needed()], write all loose objects into a new pack and remove
+    /// them, and if there are too many packs, combine them all into one, running where `policy` says.
+    ///
+    /// Call it after write-heavy operations like fetches or writing many objects. A guard file in the repository makes sure
+    /// only one maintenance run happens at a time, and with a [quiet policy][easy::maintenance::Policy::quiet] failures are
+    /// returned as part of the outcome so they don't fail the operation that triggered maintenance.
+    fn auto_maintenance(
+        &self,
+        policy: easy::maintenance::Policy,
+    ) -> Result<easy::maintenance::Outcome, easy::maintenance::Error> {
+        easy::maintenance::auto(self, policy)
+    }
 }
 
 impl<A> RepositoryAccessExt for A where A: easy::Access + Sized {}
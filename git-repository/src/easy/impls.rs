@@ -145,6 +145,9 @@ impl easy::Access for EasyArcExclusive {
     fn repo_mut(&self) -> Result<Self::RepoRefMut, easy::borrow::repo::Error> {
         Ok(self.repo.write_arc())
     }
+    fn try_repo_mut(&self) -> Option<Self::RepoRefMut> {
+        self.repo.try_write_arc()
+    }
     fn state(&self) -> &easy::State {
         &self.state
     }
@@ -0,0 +1,518 @@
+//! Decide if the object database needs maintenance after write-heavy operations, and run it, similar to `git gc --auto`.
+use std::{
+    collections::HashSet,
+    convert::{Infallible, TryFrom},
+    io::{Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use git_hash::ObjectId;
+use git_odb::pack;
+use git_pack::data::output;
+
+use crate::{easy, easy::ext::RepositoryAccessExt};
+
+/// The name of the directory holding loose objects starting with `17`, which is sampled to estimate the amount of loose objects.
+const SAMPLE_DIRECTORY: &str = "17";
+
+/// The thresholds beyond which maintenance is needed, as configured by `gc.auto` and `gc.autoPackLimit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Limits {
+    /// The amount of loose objects to tolerate, or `None` if `gc.auto` is `0` or negative, which disables maintenance entirely.
+    pub loose_objects: Option<usize>,
+    /// The amount of packs without `.keep` file to tolerate, or `None` if `gc.autoPackLimit` is `0` or negative.
+    pub packs: Option<usize>,
+}
+
+impl Default for Limits {
+    /// The defaults of `git`, `6700` loose objects and `50` packs.
+    fn default() -> Self {
+        Limits {
+            loose_objects: Some(6700),
+            packs: Some(50),
+        }
+    }
+}
+
+impl Limits {
+    pub(crate) fn from_config(config: &git_config::file::GitConfig<'_>) -> Self {
+        use git_config::values::Integer;
+        let limit = |key: &str, default: Option<usize>| {
+            config.value::<Integer>("gc", None, key).map_or(default, |v| {
+                if v.value <= 0 {
+                    return None;
+                }
+                // Values too large to be represented are as good as no limit at all.
+                Some(
+                    v.value
+                        .checked_mul(1 << v.suffix.map_or(0, |s| s.bitwise_offset()))
+                        .and_then(|value| usize::try_from(value).ok())
+                        .unwrap_or(usize::MAX),
+                )
+            })
+        };
+        let defaults = Limits::default();
+        Limits {
+            loose_objects: limit("auto", defaults.loose_objects),
+            packs: limit("autoPackLimit", defaults.packs),
+        }
+    }
+}
+
+/// The result of [`RepositoryAccessExt::maintenance_needed()`][easy::ext::RepositoryAccessExt::maintenance_needed()],
+/// obtained from cheap heuristics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Estimate {
+    /// The amount of loose objects in the `objects/17` directory, which is expected to hold one 256th of all loose objects.
+    pub sampled_loose_objects: usize,
+    /// The amount of packs in the `objects/pack` directory which don't have a `.keep` file.
+    pub packs: usize,
+    /// The limits the estimate is compared to.
+    pub limits: Limits,
+}
+
+impl Estimate {
+    /// The estimated amount of loose objects in the repository.
+    pub fn loose_objects(&self) -> usize {
+        self.sampled_loose_objects * 256
+    }
+
+    /// Return true if there are more loose objects than configured with `gc.auto`.
+    pub fn too_many_loose_objects(&self) -> bool {
+        self.limits.loose_objects.map_or(false, |limit| {
+            self.sampled_loose_objects > limit / 256 + usize::from(limit % 256 != 0)
+        })
+    }
+
+    /// Return true if there are more packs than configured with `gc.autoPackLimit`, which causes all packs to be combined.
+    ///
+    /// Note that like in `git`, this is never the case if `gc.auto` disables maintenance.
+    pub fn too_many_packs(&self) -> bool {
+        self.limits.loose_objects.is_some() && self.limits.packs.map_or(false, |limit| self.packs > limit)
+    }
+
+    /// Return true if maintenance should run.
+    pub fn is_needed(&self) -> bool {
+        self.too_many_loose_objects() || self.too_many_packs()
+    }
+}
+
+/// Determines where maintenance runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// Run maintenance on the current thread and return once it is done.
+    ///
+    /// The object database of the calling repository is refreshed afterwards if the handle allows mutable access,
+    /// like [`EasyArcExclusive`][crate::EasyArcExclusive], otherwise the repository has to be reopened to see the changes.
+    Inline,
+    /// Run maintenance on a new thread and return right away.
+    ///
+    /// Note that the object database of the calling repository isn't refreshed once it is done,
+    /// see [`refresh_object_database()`][easy::ext::RepositoryAccessExt::refresh_object_database()] or reopen the repository.
+    Background,
+}
+
+/// Configures how [`RepositoryAccessExt::auto_maintenance()`][easy::ext::RepositoryAccessExt::auto_maintenance()] runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Policy {
+    /// Where to run maintenance.
+    pub mode: Mode,
+    /// If true, errors are returned as [`Outcome::Failed`] instead of failing the call, so callers can ignore them
+    /// without risking to fail the operation that triggered maintenance.
+    pub quiet: bool,
+}
+
+impl Default for Policy {
+    /// Run in the background and don't fail, just like `git gc --auto` does after other commands.
+    fn default() -> Self {
+        Policy {
+            mode: Mode::Background,
+            quiet: true,
+        }
+    }
+}
+
+/// Information about a completed maintenance run.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Statistics {
+    /// The amount of objects written into the new pack.
+    pub packed_objects: usize,
+    /// The path to the index of the new pack, or `None` if there was nothing to pack.
+    pub index_path: Option<PathBuf>,
+    /// The amount of loose objects that were removed as they are now packed.
+    pub removed_loose_objects: usize,
+    /// The amount of packs that were removed after their objects were combined into the new pack.
+    pub removed_packs: usize,
+}
+
+/// The result of [`RepositoryAccessExt::auto_maintenance()`][easy::ext::RepositoryAccessExt::auto_maintenance()].
+#[derive(Debug)]
+pub enum Outcome {
+    /// None of the limits were exceeded, so nothing was done.
+    NotNeeded(Estimate),
+    /// Maintenance is needed, but another process or thread holds the guard file and is likely performing it right now.
+    AlreadyRunning,
+    /// Maintenance was performed on the current thread.
+    Completed(Statistics),
+    /// Maintenance runs on a new thread whose result can be obtained by joining it.
+    Started(std::thread::JoinHandle<Result<Statistics, Error>>),
+    /// Maintenance failed, which is only returned if the [policy is quiet][Policy::quiet].
+    Failed(Error),
+}
+
+mod error {
+    use std::path::PathBuf;
+
+    use git_odb::compound;
+    use git_pack::data::output;
+
+    /// The error returned by [`RepositoryAccessExt::maintenance_needed()`][crate::easy::ext::RepositoryAccessExt::maintenance_needed()]
+    /// and [`RepositoryAccessExt::auto_maintenance()`][crate::easy::ext::RepositoryAccessExt::auto_maintenance()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read or delete '{}'", .path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+        #[error(transparent)]
+        Guard(#[from] git_lock::acquire::Error),
+        #[error(transparent)]
+        OpenObjectDatabase(#[from] git_odb::linked::init::Error),
+        #[error(transparent)]
+        LooseIter(#[from] git_odb::loose::iter::Error),
+        #[error(transparent)]
+        Count(
+            #[from]
+            output::count::objects::Error<
+                git_pack::find::existing::Error<compound::find::Error>,
+                std::convert::Infallible,
+            >,
+        ),
+        #[error(transparent)]
+        WritePack(#[from] output::bytes::Error<output::entry::iter_from_counts::Error<compound::find::Error>>),
+        #[error(transparent)]
+        WriteIndex(#[from] git_pack::bundle::write::Error),
+        #[error(transparent)]
+        RefreshObjectDatabase(#[from] crate::easy::odb::refresh::Error),
+        #[error(transparent)]
+        BorrowRepo(#[from] crate::easy::borrow::repo::Error),
+        #[error("Maintenance is needed, but the repository was opened read-only")]
+        ReadOnly,
+        #[error("The written pack could not be opened to verify it")]
+        OpenWrittenPack(#[from] git_pack::bundle::init::Error),
+        #[error("The pack written to '{}' was removed as it failed verification", .path.display())]
+        PackVerificationFailed {
+            path: PathBuf,
+            source: git_pack::index::traverse::Error<git_pack::index::verify::Error>,
+        },
+    }
+}
+pub use error::Error;
+
+pub(crate) fn auto<A>(access: &A, policy: Policy) -> Result<Outcome, Error>
+where
+    A: easy::Access + Sized,
+{
+    let res = (|| {
+        let estimate = access.maintenance_needed()?;
+        if !estimate.is_needed() {
+            return Ok(Outcome::NotNeeded(estimate));
+        }
+        let (objects_dir, common_dir, verify, thread_limit, deterministic) = {
+            let repo = access.repo()?;
+            if repo.is_read_only() {
+                return Err(Error::ReadOnly);
+            }
+            (
+                repo.objects_dir().to_owned(),
+                repo.common_dir().to_owned(),
+                repo.object_write_verification(),
+                repo.object_access_parallelism().thread_limit(),
+                repo.deterministic_packs(),
+            )
+        };
+        let guard = match guard(&common_dir)? {
+            Some(guard) => guard,
+            None => return Ok(Outcome::AlreadyRunning),
+        };
+        let combine_packs = estimate.too_many_packs();
+        Ok(match policy.mode {
+            Mode::Inline => {
+                let statistics = run(&objects_dir, combine_packs, verify, thread_limit, deterministic);
+                drop(guard);
+                let statistics = statistics?;
+                match access.refresh_object_database() {
+                    Ok(()) | Err(easy::odb::refresh::Error::BorrowRepoMut(_)) => {}
+                    Err(err) => return Err(err.into()),
+                }
+                Outcome::Completed(statistics)
+            }
+            Mode::Background => Outcome::Started(std::thread::spawn(move || {
+                let _guard = guard;
+                run(&objects_dir, combine_packs, verify, thread_limit, deterministic)
+            })),
+        })
+    })();
+    match res {
+        Err(err) if policy.quiet => Ok(Outcome::Failed(err)),
+        res => res,
+    }
+}
+
+pub(crate) fn estimate(objects_dir: &Path, hash_kind: git_hash::Kind, limits: Limits) -> Result<Estimate, Error> {
+    let sample_dir = objects_dir.join(SAMPLE_DIRECTORY);
+    let sampled_loose_objects = match std::fs::read_dir(&sample_dir) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                name.len() == hash_kind.len_in_hex() - 2 && name.bytes().all(|b| b.is_ascii_hexdigit())
+            })
+            .count(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => 0,
+        Err(source) => {
+            return Err(Error::Io {
+                path: sample_dir,
+                source,
+            })
+        }
+    };
+    Ok(Estimate {
+        sampled_loose_objects,
+        packs: packs_without_keep_file(objects_dir)?.len(),
+        limits,
+    })
+}
+
+/// Return the paths to all pack data files in `objects_dir` which have an index but no `.keep` file.
+fn packs_without_keep_file(objects_dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let pack_dir = objects_dir.join("pack");
+    let entries = match std::fs::read_dir(&pack_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(source) => return Err(Error::Io { path: pack_dir, source }),
+    };
+    let mut out: Vec<_> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().map_or(false, |ext| ext == "pack")
+                && path.with_extension("idx").is_file()
+                && !path.with_extension("keep").exists()
+        })
+        .collect();
+    out.sort();
+    Ok(out)
+}
+
+/// Acquire the guard file in `common_dir`, or return `None` if it is held already.
+fn guard(common_dir: &Path) -> Result<Option<git_lock::Marker>, Error> {
+    match git_lock::Marker::acquire_to_hold_resource(
+        common_dir.join("maintenance"),
+        git_lock::acquire::Fail::Immediately,
+        None,
+    ) {
+        Ok(marker) => Ok(Some(marker)),
+        Err(git_lock::acquire::Error::PermanentlyLocked { .. }) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Write all loose objects of the repository at `objects_dir` into a new pack and delete them, and if `combine_packs` is true,
+/// add the objects of all packs without `.keep` file as well and delete these packs afterwards.
+///
+/// If `verify` demands it, the new pack is verified before anything is deleted. Up to `thread_limit` threads are used, and
+/// the pack is written `deterministic`ally if set.
+fn run(
+    objects_dir: &Path,
+    combine_packs: bool,
+    verify: git_odb::Verify,
+    thread_limit: Option<usize>,
+    deterministic: bool,
+) -> Result<Statistics, Error> {
+    let db = Arc::new(git_odb::linked::Store::at(objects_dir)?);
+    let local = &db.dbs[0];
+    let loose_ids = local.loose.iter().collect::<Result<Vec<_>, _>>()?;
+    let packs = if combine_packs {
+        packs_without_keep_file(objects_dir)?
+    } else {
+        Vec::new()
+    };
+    let mut ids: HashSet<ObjectId> = loose_ids.iter().cloned().collect();
+    for bundle in local
+        .bundles
+        .iter()
+        .filter(|b| packs.iter().any(|p| p == b.pack.path()))
+    {
+        ids.extend(bundle.index.iter().map(|entry| entry.oid));
+    }
+    let mut out = Statistics::default();
+    if ids.is_empty() {
+        return Ok(out);
+    }
+
+    let (counts, _outcome) = output::count::objects_unthreaded(
+        Arc::clone(&db),
+        (&mut pack::cache::Never, &mut pack::cache::object::Never),
+        ids.into_iter().map(Ok::<_, Infallible>),
+        git_features::progress::Discard,
+        &AtomicBool::default(),
+        output::count::objects::ObjectExpansion::AsIs,
+    )?;
+    out.packed_objects = counts.len();
+    let entries = output::InOrderIter::from(output::entry::iter_from_counts(
+        counts,
+        Arc::clone(&db),
+        || pack::cache::Never,
+        git_features::progress::Discard,
+        output::entry::iter_from_counts::Options {
+            thread_limit,
+            deterministic,
+            ..Default::default()
+        },
+    ));
+    // The pack may be as large as the repository, so it's streamed to a file next to the packs it will be written to.
+    let pack_dir = objects_dir.join("pack");
+    let io_err = |source| Error::Io {
+        path: pack_dir.clone(),
+        source,
+    };
+    let mut data = git_tempfile::new(
+        &pack_dir,
+        git_tempfile::ContainingDirectory::CreateAllRaceProof(Default::default()),
+        git_tempfile::AutoRemove::Tempfile,
+    )
+    .map_err(io_err)?;
+    {
+        let mut writer = std::io::BufWriter::new(&mut data);
+        for written in output::bytes::FromEntriesIter::new(
+            entries,
+            &mut writer,
+            out.packed_objects as u32,
+            pack::data::Version::V2,
+            git_hash::Kind::Sha1,
+        ) {
+            written?;
+        }
+        writer.flush().map_err(io_err)?;
+    }
+    data.seek(SeekFrom::Start(0)).map_err(io_err)?;
+    let written = git_pack::Bundle::write_to_directory(
+        std::io::BufReader::new(data),
+        Some(pack_dir.clone()),
+        git_features::progress::Discard,
+        &AtomicBool::default(),
+        None,
+        pack::bundle::write::Options {
+            thread_limit,
+            ..Default::default()
+        },
+    )?;
+    drop(db);
+    if verify.packs() {
+        verify_written_pack(&written, thread_limit)?;
+    }
+    // Let stores of this process know about the new pack before the objects it contains are removed.
+    git_odb::generation::bump(objects_dir);
+
+    for id in loose_ids {
+        let hex = id.to_string();
+        let path = objects_dir.join(&hex[..2]).join(&hex[2..]);
+        remove_file(&path)?;
+        std::fs::remove_dir(path.parent().expect("fan-out directory")).ok();
+        out.removed_loose_objects += 1;
+    }
+    let new_pack = written.data_path.clone();
+    for pack in packs.into_iter().filter(|pack| Some(pack) != new_pack.as_ref()) {
+        remove_file(&pack.with_extension("idx"))?;
+        remove_file(&pack)?;
+        out.removed_packs += 1;
+    }
+    if out.removed_packs != 0 {
+        remove_file(&objects_dir.join("pack").join("multi-pack-index"))?;
+    }
+    git_odb::generation::bump(objects_dir);
+    out.index_path = written.index_path;
+    Ok(out)
+}
+
+/// Re-open the pack `written` and decompress and hash all of its objects to compare them with the ids in its index,
+/// and remove it if that fails.
+fn verify_written_pack(written: &git_pack::bundle::write::Outcome, thread_limit: Option<usize>) -> Result<(), Error> {
+    let bundle = match written.to_bundle() {
+        Some(bundle) => bundle?,
+        None => return Ok(()),
+    };
+    if let Err(source) = bundle.verify_integrity(
+        git_pack::index::verify::Mode::Sha1Crc32,
+        git_pack::index::traverse::Algorithm::Lookup,
+        || pack::cache::Never,
+        thread_limit,
+        None::<git_features::progress::Discard>,
+        Default::default(),
+    ) {
+        let path = bundle.pack.path().to_owned();
+        drop(bundle);
+        remove_file(&path.with_extension("idx"))?;
+        remove_file(&path)?;
+        return Err(Error::PackVerificationFailed { path, source });
+    }
+    Ok(())
+}
+
+fn remove_file(path: &Path) -> Result<(), Error> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(source) => Err(Error::Io {
+            path: path.to_owned(),
+            source,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use super::*;
+
+    #[test]
+    fn corrupted_packs_fail_verification_and_are_removed() -> Result<(), Box<dyn std::error::Error>> {
+        let base = git_testtools::scripted_fixture_repo_read_only("make_repo_for_maintenance.sh")?;
+        let source = std::fs::read_dir(base.join("packs").join(".git").join("objects").join("pack"))?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .find(|path| path.extension().map_or(false, |ext| ext == "pack"))
+            .expect("at least one pack");
+        let dir = tempfile::tempdir()?;
+        let written = git_pack::Bundle::write_to_directory(
+            std::io::BufReader::new(std::fs::File::open(source)?),
+            Some(dir.path().to_owned()),
+            git_features::progress::Discard,
+            &AtomicBool::default(),
+            None,
+            Default::default(),
+        )?;
+        verify_written_pack(&written, None)?;
+
+        let (data_path, index_path) = (
+            written.data_path.clone().expect("pack written"),
+            written.index_path.clone().expect("index written"),
+        );
+        let mut data = std::fs::read(&data_path)?;
+        let middle = data.len() / 2;
+        data[middle] ^= 0xff;
+        std::fs::write(&data_path, data)?;
+
+        match verify_written_pack(&written, None) {
+            Err(Error::PackVerificationFailed { path, .. }) => assert_eq!(path, data_path),
+            res => panic!("unexpected result: {:?}", res),
+        }
+        assert!(!data_path.exists(), "the corrupt pack was removed");
+        assert!(!index_path.exists(), "along with its index");
+        Ok(())
+    }
+}
@@ -25,13 +25,22 @@ mod impls;
 pub(crate) mod ext;
 
 pub mod borrow;
+pub mod bundle;
 pub mod commit;
+pub mod contains;
 pub mod head;
+pub mod maintenance;
+pub mod note;
 pub mod object;
 pub mod odb;
 pub mod oid;
+#[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
+pub mod protocol;
 pub mod reference;
+pub mod replace;
+pub mod rev_parse;
 pub mod state;
+pub mod tag;
 
 /// The head reference, as created from looking at `.git/HEAD`, able to represent all of its possible states.
 ///
@@ -77,6 +86,15 @@ pub struct TreeRef<'repo, A> {
     access: &'repo A,
 }
 
+/// The kind and size of an object, obtained without decompressing or decoding the object itself.
+#[derive(Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Clone, Copy)]
+pub struct ObjectHeader {
+    /// The kind of the object
+    pub kind: git_object::Kind,
+    /// The size of the object's data in bytes, as it would be returned when finding the object
+    pub size: u64,
+}
+
 /// A detached, self-contained object, without access to its source repository.
 ///
 /// Use it if an `ObjectRef` should be sent over thread boundaries or stored in collections.
@@ -116,6 +134,9 @@ pub struct State {
     pack_cache: RefCell<PackCache>,
     object_cache: RefCell<Option<object::cache::MemoryCappedHashmap>>,
     buf: RefCell<Vec<u8>>,
+    /// The object database to find objects in instead of the one of the repository, which turned out to be stale
+    /// but couldn't be refreshed as the repository can't be borrowed mutably.
+    odb: RefCell<Option<git_odb::linked::Store>>,
 }
 
 /// A utility trait to represent access to a repository.
@@ -146,6 +167,15 @@ pub trait Access {
     /// relevant for long-running applications that make changes to the repository.
     fn repo_mut(&self) -> borrow::repo::Result<Self::RepoRefMut>;
 
+    /// Like [`repo_mut()`][Access::repo_mut()], but return `None` instead of blocking if the repository is borrowed already,
+    /// which may be by the calling thread.
+    ///
+    /// It's not supported by default, which makes handles refresh a stale object database on their own when objects
+    /// can't be found.
+    fn try_repo_mut(&self) -> Option<Self::RepoRefMut> {
+        None
+    }
+
     /// Return a shared borrow of the repository state, with support for interior mutability.
     fn state(&self) -> &State;
 }
@@ -0,0 +1,230 @@
+//! Read notes, annotations of objects which `git notes` stores as blobs in a tree of their own.
+//!
+//! The tree is reachable through a commit pointed to by a notes reference, [`refs/notes/commits`][DEFAULT_REFERENCE] by default.
+//! Like with `git notes --ref`, names of notes references are expanded to be in `refs/notes/` if they aren't already.
+//! Each note blob is stored under the hexadecimal id of the object it annotates, possibly split into a _fan-out_ of
+//! directories named after the leading characters of the id, like `ab/cdef…`.
+use git_hash::{oid, ObjectId};
+use git_object::{
+    bstr::{BString, ByteSlice},
+    tree::EntryMode,
+    TreeRefIter,
+};
+use git_ref::PartialNameRef;
+
+use crate::{
+    easy,
+    easy::{ext::ReferenceAccessExt, object::typed},
+    ext::ObjectIdExt,
+};
+
+/// The reference to read notes from if no other one is specified, like `git notes` does.
+pub const DEFAULT_REFERENCE: &str = "refs/notes/commits";
+
+/// A note attached to an object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Note {
+    /// The object the note is attached to.
+    pub annotated_object: ObjectId,
+    /// The blob containing the text of the note.
+    pub note_blob: ObjectId,
+}
+
+mod error {
+    use crate::easy;
+
+    /// The error returned when reading notes.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        FindReference(#[from] easy::reference::find::Error),
+        #[error(transparent)]
+        PeelReference(#[from] easy::reference::peel::Error),
+        #[error(transparent)]
+        FindExistingObject(#[from] easy::object::find::existing::Error),
+        #[error(transparent)]
+        PeelToTree(#[from] easy::object::peel::to_kind::Error),
+        #[error(transparent)]
+        FindTypedObject(#[from] easy::object::typed::Error),
+        #[error(transparent)]
+        Decode(#[from] git_object::decode::Error),
+    }
+}
+pub use error::Error;
+
+/// An iterator over all notes of a notes reference, created by [`ReferenceAccessExt::iter_notes()`].
+///
+/// Notes are returned in no particular order.
+pub struct Iter<'repo, A> {
+    access: &'repo A,
+    /// Trees still to visit along with the leading characters of object ids which their entries complete.
+    trees: Vec<(BString, ObjectId)>,
+    /// The entries of the tree visited last, with their full path as seen from the root of the notes tree.
+    entries: std::vec::IntoIter<(BString, EntryMode, ObjectId)>,
+}
+
+impl<'repo, A> Iter<'repo, A>
+where
+    A: easy::Access + Sized,
+{
+    pub(crate) fn new(access: &'repo A, notes_ref: Option<PartialNameRef<'_>>) -> Result<Self, Error> {
+        Ok(Iter {
+            access,
+            trees: tree_id(access, notes_ref)?
+                .map(|id| (BString::default(), id))
+                .into_iter()
+                .collect(),
+            entries: Vec::new().into_iter(),
+        })
+    }
+
+    fn entries_of(&self, prefix: BString, tree_id: ObjectId) -> Result<Vec<(BString, EntryMode, ObjectId)>, Error> {
+        let tree = tree_id.attach(self.access).try_object_as::<typed::Tree>()?;
+        let mut out = Vec::new();
+        for entry in TreeRefIter::from_bytes(&tree.data) {
+            let entry = entry?;
+            if !entry.filename.iter().all(u8::is_ascii_hexdigit) {
+                continue;
+            }
+            let mut path = prefix.clone();
+            path.extend_from_slice(entry.filename);
+            out.push((path, entry.mode, entry.oid.to_owned()));
+        }
+        Ok(out)
+    }
+}
+
+impl<'repo, A> Iterator for Iter<'repo, A>
+where
+    A: easy::Access + Sized,
+{
+    type Item = Result<Note, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((path, mode, id)) = self.entries.next() {
+                let hex_len = id.kind().len_in_hex();
+                match mode {
+                    EntryMode::Tree if path.len() < hex_len => self.trees.push((path, id)),
+                    EntryMode::Blob | EntryMode::BlobExecutable if path.len() == hex_len => {
+                        if let Ok(annotated_object) = ObjectId::from_hex(&path) {
+                            return Some(Ok(Note {
+                                annotated_object,
+                                note_blob: id,
+                            }));
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            let (prefix, tree_id) = self.trees.pop()?;
+            match self.entries_of(prefix, tree_id) {
+                Ok(entries) => self.entries = entries.into_iter(),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// Expand `name` to a full notes reference name like `git notes --ref` does, or return [`DEFAULT_REFERENCE`] if `None`.
+///
+/// Names not starting with `refs/notes/` are placed into `refs/notes/`, so `review` and `notes/review` both become `refs/notes/review`.
+fn expand(name: Option<PartialNameRef<'_>>) -> BString {
+    let name = match name {
+        Some(name) => name.as_bstr(),
+        None => return DEFAULT_REFERENCE.into(),
+    };
+    let mut out = BString::default();
+    if !name.starts_with(b"refs/notes/") {
+        out.extend_from_slice(if name.starts_with(b"notes/") {
+            b"refs/"
+        } else {
+            b"refs/notes/"
+        });
+    }
+    out.extend_from_slice(name);
+    out
+}
+
+/// Return the id of the notes tree of `notes_ref`, or `None` if the reference doesn't exist.
+fn tree_id<A>(access: &A, notes_ref: Option<PartialNameRef<'_>>) -> Result<Option<ObjectId>, Error>
+where
+    A: easy::Access + Sized,
+{
+    let notes_ref = expand(notes_ref);
+    let reference = match access.try_find_reference(notes_ref.as_bstr())? {
+        Some(r) => r,
+        None => return Ok(None),
+    };
+    let tree = reference.into_fully_peeled_id()?.object()?.peel_to_tree()?;
+    Ok(Some(tree.id))
+}
+
+/// Find the blob with the note for `annotated_object` in the notes tree of `notes_ref`, trying the full hexadecimal id as path
+/// as well as all fan-out directories matching its leading characters.
+pub(crate) fn find<A>(
+    access: &A,
+    notes_ref: Option<PartialNameRef<'_>>,
+    annotated_object: &oid,
+) -> Result<Option<ObjectId>, Error>
+where
+    A: easy::Access + Sized,
+{
+    let mut tree_id = match tree_id(access, notes_ref)? {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+    let hex = annotated_object.to_sha1_hex();
+    let mut remaining = &hex[..];
+    loop {
+        let tree = tree_id.attach(access).try_object_as::<typed::Tree>()?;
+        let mut fan_out = None;
+        for entry in TreeRefIter::from_bytes(&tree.data) {
+            let entry = entry?;
+            match entry.mode {
+                EntryMode::Blob | EntryMode::BlobExecutable if entry.filename == remaining => {
+                    return Ok(Some(entry.oid.to_owned()))
+                }
+                EntryMode::Tree
+                    if !entry.filename.is_empty()
+                        && entry.filename.len() < remaining.len()
+                        && remaining.starts_with(entry.filename) =>
+                {
+                    fan_out = Some((entry.oid.to_owned(), entry.filename.len()));
+                }
+                _ => {}
+            }
+        }
+        match fan_out {
+            Some((id, consumed)) => {
+                tree_id = id;
+                remaining = &remaining[consumed..];
+            }
+            None => return Ok(None),
+        }
+    }
+}
+
+/// Return the text of the note attached to `annotated_object` in `notes_ref`, or `None` if there is none.
+pub(crate) fn text<A>(
+    access: &A,
+    notes_ref: Option<PartialNameRef<'_>>,
+    annotated_object: &oid,
+) -> Result<Option<BString>, Error>
+where
+    A: easy::Access + Sized,
+{
+    match find(access, notes_ref, annotated_object)? {
+        Some(blob_id) => Ok(Some(
+            blob_id
+                .attach(access)
+                .try_object_as::<typed::Blob>()?
+                .data
+                .as_bstr()
+                .into(),
+        )),
+        None => Ok(None),
+    }
+}
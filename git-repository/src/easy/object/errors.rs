@@ -51,3 +51,30 @@ pub mod write {
         BorrowRepo(#[from] easy::borrow::repo::Error),
     }
 }
+
+///
+pub mod try_into {
+    use git_hash::ObjectId;
+
+    use crate::easy::object::Kind;
+
+    /// The error returned by conversions like [`ObjectRef::try_into_commit()`][crate::easy::ObjectRef::try_into_commit()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Object {id} was expected to be a {expected} but was a {actual}")]
+        WrongKind { id: ObjectId, expected: Kind, actual: Kind },
+        #[error(transparent)]
+        Decode(#[from] git_object::decode::Error),
+    }
+
+    impl Error {
+        pub(crate) fn check(id: ObjectId, actual: Kind, expected: Kind) -> Result<(), Self> {
+            if actual == expected {
+                Ok(())
+            } else {
+                Err(Error::WrongKind { id, expected, actual })
+            }
+        }
+    }
+}
@@ -8,6 +8,12 @@ impl<'repo, A> std::fmt::Debug for ObjectRef<'repo, A> {
     }
 }
 
+impl<'repo, A> std::fmt::Debug for TreeRef<'repo, A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.id, f)
+    }
+}
+
 impl<'repo, A> From<ObjectRef<'repo, A>> for Object {
     fn from(r: ObjectRef<'repo, A>) -> Self {
         r.into_owned()
@@ -40,3 +46,14 @@ impl<'repo, A> TryFrom<ObjectRef<'repo, A>> for TreeRef<'repo, A> {
         }
     }
 }
+
+impl<'repo, A> From<TreeRef<'repo, A>> for ObjectRef<'repo, A> {
+    fn from(tree: TreeRef<'repo, A>) -> Self {
+        ObjectRef {
+            id: tree.id,
+            kind: object::Kind::Tree,
+            data: tree.data,
+            access: tree.access,
+        }
+    }
+}
@@ -13,10 +13,13 @@ mod errors;
 pub(crate) mod cache {
     pub use git_pack::cache::object::MemoryCappedHashmap;
 }
-pub use errors::{find, write};
+pub use errors::{find, try_into, write};
 mod impls;
 pub mod peel;
-mod tree;
+///
+pub mod tree;
+pub mod typed;
+pub use typed::Typed;
 
 impl Object {
     /// Infuse this owned object with an [`easy::Access`].
@@ -59,9 +62,29 @@ where
         }
     }
 
-    /// Transform this object into a tree, or return it as part of the `Err` if it is no tree.
-    pub fn try_into_tree(self) -> Result<TreeRef<'repo, A>, Self> {
-        self.try_into()
+    /// Transform this object into a tree, or return an error if it is no tree.
+    pub fn try_into_tree(self) -> Result<TreeRef<'repo, A>, try_into::Error> {
+        typed::from_object::<_, typed::Tree>(self)
+    }
+
+    /// Decode this object into an owned commit, or return an error if it is no commit.
+    pub fn try_into_commit(self) -> Result<git_object::Commit, try_into::Error> {
+        try_into::Error::check(self.id, self.kind, Kind::Commit)?;
+        Ok(git_object::CommitRef::from_bytes(&self.data)?.into())
+    }
+
+    /// Decode this object into an owned tag, or return an error if it is no tag.
+    pub fn try_into_tag(self) -> Result<git_object::Tag, try_into::Error> {
+        try_into::Error::check(self.id, self.kind, Kind::Tag)?;
+        Ok(git_object::TagRef::from_bytes(&self.data)?.into())
+    }
+
+    /// Copy the data of this object into an owned blob, or return an error if it is no blob.
+    pub fn try_into_blob(self) -> Result<git_object::Blob, try_into::Error> {
+        try_into::Error::check(self.id, self.kind, Kind::Blob)?;
+        Ok(git_object::Blob {
+            data: self.data.to_owned(),
+        })
     }
 }
 
@@ -127,4 +150,58 @@ where
     pub fn try_to_tag_iter(&self) -> Option<git_object::TagRefIter<'_>> {
         git_odb::data::Object::new(self.kind, &self.data).try_into_tag_iter()
     }
+
+    /// Compare the lines of this blob with the ones of the `other` blob using `algorithm`, and pass the hunks with
+    /// up to `context_lines` of unchanged lines around each change to `sink`.
+    ///
+    /// Return an error if either of both objects is not a blob.
+    #[cfg(feature = "git-diff")]
+    pub fn diff_blob<B, S>(
+        &self,
+        other: &ObjectRef<'_, B>,
+        algorithm: git_diff::blob::Algorithm,
+        context_lines: u32,
+        sink: S,
+    ) -> Result<git_diff::blob::Outcome<S::Out>, try_into::Error>
+    where
+        B: easy::Access + Sized,
+        S: git_diff::blob::Sink,
+    {
+        try_into::Error::check(self.id, self.kind, Kind::Blob)?;
+        try_into::Error::check(other.id, other.kind, Kind::Blob)?;
+        Ok(git_diff::blob::diff(
+            &self.data,
+            &other.data,
+            algorithm,
+            context_lines,
+            sink,
+        ))
+    }
+}
+
+/// Typed access
+impl Object {
+    /// Decode this object into an owned commit, or return an error if it is no commit.
+    pub fn try_into_commit(self) -> Result<git_object::Commit, try_into::Error> {
+        try_into::Error::check(self.id, self.kind, Kind::Commit)?;
+        Ok(git_object::CommitRef::from_bytes(&self.data)?.into())
+    }
+
+    /// Decode this object into an owned tree, or return an error if it is no tree.
+    pub fn try_into_tree(self) -> Result<git_object::Tree, try_into::Error> {
+        try_into::Error::check(self.id, self.kind, Kind::Tree)?;
+        Ok(git_object::TreeRef::from_bytes(&self.data)?.into())
+    }
+
+    /// Decode this object into an owned tag, or return an error if it is no tag.
+    pub fn try_into_tag(self) -> Result<git_object::Tag, try_into::Error> {
+        try_into::Error::check(self.id, self.kind, Kind::Tag)?;
+        Ok(git_object::TagRef::from_bytes(&self.data)?.into())
+    }
+
+    /// Turn this object into an owned blob without copying its data, or return an error if it is no blob.
+    pub fn try_into_blob(self) -> Result<git_object::Blob, try_into::Error> {
+        try_into::Error::check(self.id, self.kind, Kind::Blob)?;
+        Ok(git_object::Blob { data: self.data })
+    }
 }
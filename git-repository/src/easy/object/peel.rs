@@ -4,9 +4,10 @@ use crate::{
     easy::{
         ext::ObjectAccessExt,
         object,
-        object::{peel, Kind},
-        ObjectRef,
+        object::{peel, typed, Kind},
+        ObjectRef, TreeRef,
     },
+    ext::ObjectIdExt,
 };
 
 ///
@@ -21,6 +22,8 @@ pub mod to_kind {
         pub enum Error {
             #[error(transparent)]
             FindExistingObject(#[from] object::find::existing::Error),
+            #[error(transparent)]
+            FindTypedObject(#[from] object::typed::Error),
             #[error("Last encountered object kind was {} while trying to peel to {}", .actual, .expected)]
             NotFound {
                 actual: object::Kind,
@@ -47,14 +50,10 @@ where
                     return Ok(self);
                 }
                 Kind::Commit => {
-                    let tree_id = self
-                        .try_to_commit_iter()
-                        .expect("commit")
-                        .tree_id()
-                        .expect("valid commit");
+                    let tree_id = self.commit_iter().tree_id().expect("valid commit");
                     let access = self.access;
                     drop(self);
-                    self = access.find_object(tree_id)?;
+                    self = tree_id.attach(access).try_object_as::<typed::Tree>()?.into();
                 }
                 Kind::Tag => {
                     let target_id = self.tag_iter().target_id().expect("valid tag");
@@ -72,6 +71,27 @@ where
         }
     }
 
+    /// Follow tags to their target and commits to their tree until an object of the kind `T` stands for is encountered, and
+    /// return it as the type of [`typed::Commit`], [`typed::Tree`], [`typed::Tag`] or [`typed::Blob`].
+    pub fn peel_to<T>(self) -> Result<T::Output, peel::to_kind::Error>
+    where
+        T: typed::Typed<'repo, A>,
+    {
+        Ok(typed::from_object::<_, T>(self.peel_to_kind(T::KIND)?).map_err(typed::Error::from)?)
+    }
+
+    /// Follow tags to their target and commits to their tree until a tree is encountered, and return it.
+    pub fn peel_to_tree(self) -> Result<TreeRef<'repo, A>, peel::to_kind::Error> {
+        self.peel_to::<typed::Tree>()
+    }
+
+    /// Follow tags to their target until a commit is encountered and return it, or fail if the chain ends in a tree or blob.
+    ///
+    /// Use [`commit_iter()`][ObjectRef::commit_iter()] or [`commit()`][ObjectRef::commit()] on the result to access the commit.
+    pub fn peel_to_commit(self) -> Result<Self, peel::to_kind::Error> {
+        self.peel_to::<typed::Commit>()
+    }
+
     // TODO: tests
     /// Follow all tag object targets until a commit, tree or blob is reached.
     ///
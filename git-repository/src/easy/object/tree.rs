@@ -1,18 +1,35 @@
+use std::{
+    cmp::Ordering,
+    convert::TryFrom,
+    ops::DerefMut,
+    path::{Component, Path},
+};
+
 use git_hash::ObjectId;
-use git_object::{bstr::BStr, TreeRefIter};
+use git_object::{
+    bstr::{BStr, BString, ByteSlice},
+    tree::{EntryMode, EntryRef},
+    TreeRefIter,
+};
+use git_odb::FindExt;
+use os_str_bytes::OsStrBytes;
 
 use crate::{
     easy,
-    easy::{ext::ObjectAccessExt, object::find, TreeRef},
+    easy::{
+        ext::ObjectAccessExt,
+        object::{find, try_into, Kind},
+        ObjectRef, Oid,
+    },
 };
 
-impl<'repo, A> TreeRef<'repo, A>
+impl<'repo, A> easy::TreeRef<'repo, A>
 where
     A: easy::Access + Sized,
 {
     /// Obtain a tree instance by handing in all components that it is made up of.
     pub fn from_id_and_data(id: impl Into<ObjectId>, data: std::cell::Ref<'repo, [u8]>, access: &'repo A) -> Self {
-        TreeRef {
+        easy::TreeRef {
             id: id.into(),
             data,
             access,
@@ -59,3 +76,232 @@ where
         Ok(None)
     }
 }
+
+/// An entry of a tree with access to the repository, as obtained by [`ObjectRef::entries()`] and
+/// [`ObjectRef::lookup_entry_by_path()`].
+pub struct Entry<'repo, A> {
+    /// The kind of object the entry points to.
+    pub mode: EntryMode,
+    /// The name of the entry in its tree.
+    pub filename: BString,
+    /// The id of the object the entry points to, to continue with [`Oid::object()`].
+    pub oid: Oid<'repo, A>,
+}
+
+impl<'repo, A> Entry<'repo, A>
+where
+    A: easy::Access + Sized,
+{
+    fn from_entry_ref(entry: &EntryRef<'_>, access: &'repo A) -> Self {
+        Entry {
+            mode: entry.mode,
+            filename: entry.filename.to_owned(),
+            oid: Oid::from_id(entry.oid, access),
+        }
+    }
+}
+
+///
+pub mod lookup {
+    use git_object::bstr::BString;
+
+    use crate::easy;
+
+    /// The error returned by [`ObjectRef::lookup_entry_by_path()`][easy::ObjectRef::lookup_entry_by_path()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        NotATree(#[from] easy::object::try_into::Error),
+        #[error(transparent)]
+        Decode(#[from] git_object::decode::Error),
+        #[error(transparent)]
+        FindExisting(#[from] easy::object::find::existing::Error),
+        #[error("The path component '{component}' is not a normal file or directory name")]
+        InvalidComponent { component: BString },
+        #[error("Cannot descend into the submodule at '{path}' as its objects are not part of this repository")]
+        Submodule { path: BString },
+    }
+}
+
+impl<'repo, A> ObjectRef<'repo, A>
+where
+    A: easy::Access + Sized,
+{
+    /// Return an iterator over the entries of this tree, or an error if this object is no tree.
+    pub fn entries(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<Entry<'repo, A>, git_object::decode::Error>> + '_, try_into::Error> {
+        try_into::Error::check(self.id, self.kind, Kind::Tree)?;
+        let access = self.access;
+        Ok(TreeRefIter::from_bytes(&self.data)
+            .map(move |entry| entry.map(|entry| Entry::from_entry_ref(&entry, access))))
+    }
+
+    /// Find the entry at `path` relative to this tree, descending into subtrees for each of its components, or return
+    /// an error if this object is no tree.
+    ///
+    /// Returns `None` if there is no such entry, or if a component other than the last one isn't a tree.
+    /// Path components are compared as bytes, and submodules are returned, but can't be descended into.
+    ///
+    /// # Performance Notes
+    ///
+    /// The raw entries of each tree are binary searched using the ordering `git` uses for tree entries, in which trees sort
+    /// as if their name was suffixed with `/`. Only the entries needed for comparison and the one found are decoded.
+    /// Doing so requires the position of each entry, which is why each tree is scanned once for entry boundaries.
+    /// Subtrees are read into a separate buffer, keeping the shared buffer of [`easy::Access`] untouched.
+    pub fn lookup_entry_by_path(&self, path: impl AsRef<Path>) -> Result<Option<Entry<'repo, A>>, lookup::Error> {
+        try_into::Error::check(self.id, self.kind, Kind::Tree)?;
+        let mut components = Vec::new();
+        for component in path.as_ref().components() {
+            match component {
+                Component::Normal(name) => components.push(name.to_raw_bytes()),
+                Component::CurDir => {}
+                _ => {
+                    return Err(lookup::Error::InvalidComponent {
+                        component: component.as_os_str().to_raw_bytes().into_owned().into(),
+                    })
+                }
+            }
+        }
+
+        let (mut buf, mut offsets) = (Vec::new(), Vec::new());
+        let mut data: &[u8] = &self.data;
+        for (index, component) in components.iter().enumerate() {
+            let entry = match find_entry(data, component, &mut offsets)? {
+                Some(entry) => entry,
+                None => return Ok(None),
+            };
+            if index + 1 == components.len() {
+                return Ok(Some(Entry::from_entry_ref(&entry, self.access)));
+            }
+            match entry.mode {
+                EntryMode::Tree => {}
+                EntryMode::Commit => {
+                    return Err(lookup::Error::Submodule {
+                        path: components[..=index].join(&b'/').into(),
+                    })
+                }
+                _ => return Ok(None),
+            }
+            let id = entry.oid.to_owned();
+            let kind = {
+                let mut pack_cache = self
+                    .access
+                    .state()
+                    .try_borrow_mut_pack_cache()
+                    .map_err(find::existing::Error::from)?;
+                self.access
+                    .repo()
+                    .map_err(find::existing::Error::from)?
+                    .odb
+                    .find(id, &mut buf, pack_cache.deref_mut())
+                    .map_err(find::existing::Error::from)?
+                    .kind
+            };
+            try_into::Error::check(id, kind, Kind::Tree)?;
+            data = &buf;
+        }
+        Ok(None)
+    }
+}
+
+/// Compare tree entry names like `git` does, with trees sorting as if their name was suffixed with `/`.
+fn compare(a: &[u8], a_is_tree: bool, b: &[u8], b_is_tree: bool) -> Ordering {
+    let common = a.len().min(b.len());
+    a[..common].cmp(&b[..common]).then_with(|| {
+        let next = |name: &[u8], is_tree: bool| name.get(common).copied().or(if is_tree { Some(b'/') } else { None });
+        next(a, a_is_tree).cmp(&next(b, b_is_tree))
+    })
+}
+
+/// Binary search the raw entries of the tree `data` for the blob, tree or submodule called `name`, decoding only the entry
+/// that was found. `offsets` is used to store the position of each entry.
+fn find_entry<'a>(
+    data: &'a [u8],
+    name: &[u8],
+    offsets: &mut Vec<usize>,
+) -> Result<Option<EntryRef<'a>>, git_object::decode::Error> {
+    let decode = |offset: usize| TreeRefIter::from_bytes(&data[offset..]).next().transpose();
+
+    // Each entry is `<mode> <name>\0<hash>`, and neither mode nor name may contain a null byte.
+    offsets.clear();
+    let mut offset = 0;
+    while offset < data.len() {
+        match data[offset..].find_byte(0) {
+            Some(nul) if offset + nul + 1 + git_hash::SIZE_OF_SHA1_DIGEST <= data.len() => {
+                offsets.push(offset);
+                offset += nul + 1 + git_hash::SIZE_OF_SHA1_DIGEST;
+            }
+            _ => return decode(offset),
+        }
+    }
+
+    let mode_and_name = |offset: usize| {
+        let header = &data[offset..][..data[offset..].find_byte(0).expect("checked during scan")];
+        match header.find_byte(b' ') {
+            Some(space) => (
+                EntryMode::try_from(&header[..space]).ok() == Some(EntryMode::Tree),
+                &header[space + 1..],
+            ),
+            None => (false, header),
+        }
+    };
+    match [false, true].iter().find_map(|&is_tree| {
+        offsets
+            .binary_search_by(|offset| {
+                let (entry_is_tree, entry_name) = mode_and_name(*offset);
+                compare(entry_name, entry_is_tree, name, is_tree)
+            })
+            .ok()
+    }) {
+        Some(pos) => decode(offsets[pos]),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod find_entry {
+    use super::*;
+
+    fn tree(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for (mode, name) in entries {
+            out.extend_from_slice(format!("{} {}\0", mode, name).as_bytes());
+            out.extend_from_slice(&[name.len() as u8; git_hash::SIZE_OF_SHA1_DIGEST]);
+        }
+        out
+    }
+
+    #[test]
+    fn raw_entries_are_found_in_git_order() {
+        let data = tree(&[
+            ("100644", "a"),
+            ("100644", "a.b"),
+            ("40000", "a"),
+            ("160000", "b"),
+            ("40000", "c"),
+        ]);
+        let mut offsets = Vec::new();
+        for (name, mode) in &[
+            ("a", EntryMode::Blob),
+            ("a.b", EntryMode::Blob),
+            ("b", EntryMode::Commit),
+            ("c", EntryMode::Tree),
+        ] {
+            let entry = find_entry(&data, name.as_bytes(), &mut offsets)
+                .expect("valid tree")
+                .expect("present");
+            assert_eq!((entry.filename, entry.mode), (name.as_bytes().as_bstr(), *mode));
+        }
+        assert_eq!(offsets.len(), 5, "all entry boundaries are known");
+        assert!(find_entry(&data, b"d", &mut offsets).expect("valid tree").is_none());
+    }
+
+    #[test]
+    fn truncated_trees_are_errors() {
+        let mut data = tree(&[("100644", "a"), ("100644", "b")]);
+        data.pop();
+        assert!(find_entry(&data, b"a", &mut Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+//! Look up objects of a kind known at compile time, like [`Oid::try_object_as::<Commit>()`][crate::easy::Oid::try_object_as()],
+//! to obtain them without checking their kind and converting them by hand.
+//!
+//! Tags are not followed to their target, which is what [peeling][crate::easy::ObjectRef::peel_to()] is for.
+use crate::{
+    easy,
+    easy::{
+        object::{try_into, Kind},
+        ObjectRef, TreeRef,
+    },
+};
+
+/// Marks commits, which are returned as [`ObjectRef`] whose [commit accessors][ObjectRef::commit()] can't fail due to their kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Commit;
+/// Marks trees, which are returned as [`TreeRef`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tree;
+/// Marks tags, which are returned as [`ObjectRef`] whose [tag accessors][ObjectRef::tag_iter()] can't fail due to their kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tag;
+/// Marks blobs, which are returned as [`ObjectRef`] with the blob's content as [data][ObjectRef::data].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Blob;
+
+mod private {
+    use crate::easy::{self, ObjectRef};
+
+    /// Seals [`Typed`][super::Typed] and keeps the unchecked conversion to its output private to this crate.
+    pub trait Sealed<'repo, A: 'repo> {
+        /// Convert `object`, which must be of [`KIND`][super::Typed::KIND], into [`Output`][super::Typed::Output].
+        fn from_object(object: ObjectRef<'repo, A>) -> <Self as super::Typed<'repo, A>>::Output
+        where
+            Self: super::Typed<'repo, A>;
+    }
+
+    impl<'repo, A: 'repo> Sealed<'repo, A> for super::Commit {
+        fn from_object(object: ObjectRef<'repo, A>) -> <Self as super::Typed<'repo, A>>::Output {
+            object
+        }
+    }
+
+    impl<'repo, A> Sealed<'repo, A> for super::Tree
+    where
+        A: easy::Access + Sized + 'repo,
+    {
+        fn from_object(object: ObjectRef<'repo, A>) -> <Self as super::Typed<'repo, A>>::Output {
+            object.into_tree()
+        }
+    }
+
+    impl<'repo, A: 'repo> Sealed<'repo, A> for super::Tag {
+        fn from_object(object: ObjectRef<'repo, A>) -> <Self as super::Typed<'repo, A>>::Output {
+            object
+        }
+    }
+
+    impl<'repo, A: 'repo> Sealed<'repo, A> for super::Blob {
+        fn from_object(object: ObjectRef<'repo, A>) -> <Self as super::Typed<'repo, A>>::Output {
+            object
+        }
+    }
+}
+
+/// Maps the marker types [`Commit`], [`Tree`], [`Tag`] and [`Blob`] to their [`Kind`] and to the type objects of that kind
+/// are returned as.
+///
+/// This trait is sealed and can't be implemented outside of this crate.
+pub trait Typed<'repo, A: 'repo>: private::Sealed<'repo, A> {
+    /// The kind of objects this marker stands for.
+    const KIND: Kind;
+    /// The type objects of this kind are returned as.
+    type Output;
+}
+
+impl<'repo, A: 'repo> Typed<'repo, A> for Commit {
+    const KIND: Kind = Kind::Commit;
+    type Output = ObjectRef<'repo, A>;
+}
+
+impl<'repo, A> Typed<'repo, A> for Tree
+where
+    A: easy::Access + Sized + 'repo,
+{
+    const KIND: Kind = Kind::Tree;
+    type Output = TreeRef<'repo, A>;
+}
+
+impl<'repo, A: 'repo> Typed<'repo, A> for Tag {
+    const KIND: Kind = Kind::Tag;
+    type Output = ObjectRef<'repo, A>;
+}
+
+impl<'repo, A: 'repo> Typed<'repo, A> for Blob {
+    const KIND: Kind = Kind::Blob;
+    type Output = ObjectRef<'repo, A>;
+}
+
+mod error {
+    use crate::easy::object::{find, try_into};
+
+    /// The error returned by [`Oid::try_object_as()`][crate::easy::Oid::try_object_as()] and
+    /// [`Oid::object_as_or_none()`][crate::easy::Oid::object_as_or_none()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Find(#[from] find::Error),
+        #[error(transparent)]
+        FindExisting(#[from] find::existing::Error),
+        #[error(transparent)]
+        TryInto(#[from] try_into::Error),
+    }
+}
+pub use error::Error;
+
+/// Convert `object` into the output of `T` if it is of the kind `T` stands for, or fail with
+/// [`WrongKind`][try_into::Error::WrongKind].
+pub(crate) fn from_object<'repo, A, T>(object: ObjectRef<'repo, A>) -> Result<T::Output, try_into::Error>
+where
+    T: Typed<'repo, A>,
+{
+    try_into::Error::check(object.id, object.kind, T::KIND)?;
+    Ok(T::from_object(object))
+}
@@ -11,5 +11,7 @@ pub mod refresh {
         Refresh(#[from] git_odb::linked::init::Error),
         #[error(transparent)]
         BorrowRepoMut(#[from] easy::borrow::repo::Error),
+        #[error(transparent)]
+        BorrowState(#[from] easy::borrow::state::Error),
     }
 }
@@ -4,8 +4,9 @@ use std::ops::Deref;
 use git_hash::{oid, ObjectId};
 
 use crate::{
+    bstr::BString,
     easy,
-    easy::{ext::ObjectAccessExt, object::find, ObjectRef, Oid},
+    easy::{ext::ObjectAccessExt, object, object::find, ObjectHeader, ObjectRef, Oid},
 };
 
 /// An [object id][ObjectId] infused with `Easy`.
@@ -30,6 +31,60 @@ where
     pub fn try_object(&self) -> Result<Option<ObjectRef<'repo, A>>, find::Error> {
         self.access.try_find_object(self.inner)
     }
+
+    /// Find the object associated with this id and return it as the type `T` stands for, one of
+    /// [`Commit`][object::typed::Commit], [`Tree`][object::typed::Tree], [`Tag`][object::typed::Tag] or
+    /// [`Blob`][object::typed::Blob], and consider it an error if it doesn't exist or is of another kind.
+    ///
+    /// Note that tags are not followed to their target, use [`peel_to()`][ObjectRef::peel_to()] for that.
+    pub fn try_object_as<T>(&self) -> Result<T::Output, object::typed::Error>
+    where
+        T: object::Typed<'repo, A>,
+    {
+        Ok(object::typed::from_object::<_, T>(self.object()?)?)
+    }
+
+    /// Like [`try_object_as()`][Oid::try_object_as()], but return `None` if the object isn't available locally.
+    pub fn object_as_or_none<T>(&self) -> Result<Option<T::Output>, object::typed::Error>
+    where
+        T: object::Typed<'repo, A>,
+    {
+        Ok(self.try_object()?.map(object::typed::from_object::<_, T>).transpose()?)
+    }
+
+    /// Return true if the object with this id is contained in the object database.
+    ///
+    /// This is cheaper than [`try_object()`][Oid::try_object()] as the object isn't read.
+    pub fn exists(&self) -> Result<bool, easy::borrow::repo::Error> {
+        self.access.contains_object(self.inner)
+    }
+
+    /// Obtain the kind and size of the object with this id without decoding it, and consider it an error if it doesn't exist.
+    ///
+    /// Unlike [`object()`][Oid::object()], this doesn't borrow the shared buffer and only reads the loose object header or
+    /// the pack entry, without resolving the delta chain of packed objects.
+    pub fn object_header(&self) -> Result<ObjectHeader, find::existing::Error> {
+        self.access
+            .find_object_header(self.inner)
+            .map(|(kind, size)| ObjectHeader { kind, size })
+    }
+
+    /// Try to obtain the kind and size of the object with this id without decoding it, and return `None` if it's not
+    /// available locally.
+    pub fn try_object_header(&self) -> Result<Option<ObjectHeader>, find::Error> {
+        Ok(self
+            .access
+            .try_find_object_header(self.inner)?
+            .map(|(kind, size)| ObjectHeader { kind, size }))
+    }
+
+    /// Return the text of the note attached to this object in `notes_ref`, or in
+    /// [`refs/notes/commits`][easy::note::DEFAULT_REFERENCE] if `None`.
+    ///
+    /// Returns `None` if there is no such note or if the notes reference doesn't exist.
+    pub fn note(&self, notes_ref: Option<git_ref::PartialNameRef<'_>>) -> Result<Option<BString>, easy::note::Error> {
+        easy::note::text(self.access, notes_ref, &self.inner)
+    }
 }
 
 impl<'repo, A> Deref for Oid<'repo, A> {
@@ -65,12 +120,35 @@ where
     repo: A::RepoRef,
     access: &'repo A,
     tips: Box<dyn Iterator<Item = ObjectId>>,
+    cursor: Option<ancestors::Cursor>,
+    max_commits: Option<usize>,
+    max_duration: Option<std::time::Duration>,
+    min_commit_time: Option<u32>,
+    missing: ancestors::Missing,
+}
+
+/// A platform to name a commit relative to the closest tag in its history, similar to `git describe`.
+pub struct Describe<'repo, A>
+where
+    A: easy::Access + Sized,
+{
+    access: &'repo A,
+    id: ObjectId,
+    tags: bool,
+    max_candidates: usize,
+    first_parent: bool,
+    always: bool,
+    abbreviation: Option<usize>,
 }
 
 ///
 pub mod ancestors {
-    use std::ops::{Deref, DerefMut};
+    use std::{
+        ops::{Deref, DerefMut},
+        time::{Duration, Instant},
+    };
 
+    use git_hash::{oid, ObjectId};
     use git_odb::Find;
 
     use crate::{
@@ -85,62 +163,232 @@ pub mod ancestors {
         /// Obtain a platform for traversing ancestors of this commit.
         pub fn ancestors(&self) -> Result<Ancestors<'repo, A>, Error> {
             let repo = self.access.repo()?;
+            let missing = if repo.is_shallow() || repo.is_partial() {
+                Missing::Skip
+            } else {
+                Missing::Error
+            };
             Ok(Ancestors {
                 repo,
                 access: self.access,
                 tips: Box::new(Some(self.inner).into_iter()),
+                cursor: None,
+                max_commits: None,
+                max_duration: None,
+                min_commit_time: None,
+                missing,
             })
         }
     }
 
+    /// Determines what a traversal does if a commit can't be found, as is expected in shallow or partial clones.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Missing {
+        /// Fail with [`NotFound`][git_traverse::commit::ancestors::Error::NotFound].
+        Error,
+        /// Don't return missing commits and don't traverse their parents, so that commits at the boundary of a shallow
+        /// clone are treated like root commits.
+        Skip,
+    }
+
+    /// The position of a traversal [stopped][Ancestors::page()] due to a limit, to [continue it][Ancestors::resume()] later
+    /// exactly where it stopped.
+    #[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Cursor {
+        /// The commits to return next, in order.
+        pub queued: Vec<ObjectId>,
+        /// All commits seen so far, which are not returned again.
+        pub seen: Vec<ObjectId>,
+    }
+
+    /// A part of the commits of a traversal, as returned by [`Ancestors::page()`].
+    pub struct Page<'repo, A>
+    where
+        A: easy::Access + Sized,
+    {
+        /// The commits in traversal order.
+        pub commits: Vec<Oid<'repo, A>>,
+        /// The position at which the traversal stopped, or `None` if all commits were returned.
+        pub cursor: Option<Cursor>,
+    }
+
+    /// Limits
+    impl<'repo, A> Ancestors<'repo, A>
+    where
+        A: easy::Access + Sized,
+    {
+        /// Let [`page()`][Ancestors::page()] return no more than `max_commits`.
+        pub fn max_commits(mut self, max_commits: usize) -> Self {
+            self.max_commits = Some(max_commits);
+            self
+        }
+
+        /// Let [`page()`][Ancestors::page()] stop once it took `max_duration` or longer, after returning at least one commit.
+        pub fn max_duration(mut self, max_duration: Duration) -> Self {
+            self.max_duration = Some(max_duration);
+            self
+        }
+
+        /// Let [`page()`][Ancestors::page()] skip commits whose committer time in seconds since the unix epoch is older
+        /// than `min_commit_time`, along with their parents unless these are reachable through other commits.
+        pub fn min_commit_time(mut self, min_commit_time: u32) -> Self {
+            self.min_commit_time = Some(min_commit_time);
+            self
+        }
+
+        /// Configure what to do if a commit can't be found.
+        ///
+        /// Defaults to [`Missing::Skip`] if the repository [is shallow][crate::Repository::is_shallow()] or
+        /// [partial][crate::Repository::is_partial()], and to [`Missing::Error`] otherwise.
+        pub fn missing(mut self, missing: Missing) -> Self {
+            self.missing = missing;
+            self
+        }
+
+        /// Let [`page()`][Ancestors::page()] continue a traversal at `cursor` instead of starting at the parent [Oid].
+        pub fn resume(mut self, cursor: Cursor) -> Self {
+            self.cursor = Some(cursor);
+            self
+        }
+    }
+
     impl<'repo, A> Ancestors<'repo, A>
     where
         A: easy::Access + Sized,
     {
         /// Return an iterator to traverse all commits in the history of the commit the parent [Oid] is pointing to.
+        ///
+        /// Note that limits only apply to [`page()`][Ancestors::page()].
         pub fn all(&mut self) -> Iter<'_, 'repo, A> {
             let tips = std::mem::replace(&mut self.tips, Box::new(None.into_iter()));
+            let is_present = is_present(self.missing, &self.repo);
             Iter {
                 access: self.access,
-                inner: Box::new(git_traverse::commit::Ancestors::new(
+                inner: Box::new(git_traverse::commit::Ancestors::filtered(
                     tips,
                     git_traverse::commit::ancestors::State::default(),
-                    move |oid, buf| {
-                        let state = self.access.state();
-                        let mut object_cache = state.try_borrow_mut_object_cache().ok()?;
-                        if let Some(c) = object_cache.deref_mut() {
-                            if let Some(kind) = c.get(&oid.to_owned(), buf) {
-                                return git_pack::data::Object::new(kind, buf).try_into_commit_iter();
-                            }
-                        }
-                        match self
-                            .repo
-                            .deref()
-                            .odb
-                            .try_find(
-                                oid,
-                                buf,
-                                state
-                                    .try_borrow_mut_pack_cache()
-                                    .expect("BUG: pack cache is already borrowed")
-                                    .deref_mut(),
-                            )
-                            .ok()
-                            .flatten()
-                            .and_then(|obj| obj.try_into_commit_iter())
-                        {
-                            Some(_) => {
-                                if let Some(c) = object_cache.deref_mut() {
-                                    c.put(oid.to_owned(), git_object::Kind::Commit, buf);
-                                }
-                                Some(git_object::CommitRefIter::from_bytes(buf))
-                            }
-                            None => None,
-                        }
-                    },
+                    find_commit(self.access, &self.repo),
+                    move |id| is_present(id),
                 )),
             }
         }
+
+        /// Traverse the history breadth-first until a limit is reached and return the commits seen so far, along with a
+        /// cursor to continue the traversal.
+        ///
+        /// Calling it again continues where the previous call stopped, and [`resume()`][Ancestors::resume()] allows to do the
+        /// same with a new platform, possibly in another process.
+        pub fn page(&mut self) -> Result<Page<'repo, A>, page::Error> {
+            let access = self.access;
+            let (mut state, tips) = match self.cursor.take() {
+                Some(cursor) => (
+                    git_traverse::commit::ancestors::State::from_queued_and_seen(cursor.queued, cursor.seen),
+                    None,
+                ),
+                None => (
+                    Default::default(),
+                    Some(std::mem::replace(&mut self.tips, Box::new(None.into_iter()))),
+                ),
+            };
+            let min_commit_time = self.min_commit_time;
+            let mut find_for_predicate = find_commit(access, &self.repo);
+            let mut buf = Vec::new();
+            let is_present = is_present(self.missing, &self.repo);
+            let predicate = move |id: &oid| {
+                is_present(id)
+                    && match min_commit_time {
+                        Some(min_commit_time) => {
+                            !matches!(find_for_predicate(id, &mut buf).and_then(commit_time), Some(time) if time < min_commit_time)
+                        }
+                        None => true,
+                    }
+            };
+            let find = find_commit(access, &self.repo);
+            let mut commits = Vec::new();
+            {
+                let mut iter = match tips {
+                    Some(tips) => git_traverse::commit::Ancestors::filtered(tips, &mut state, find, predicate),
+                    None => git_traverse::commit::Ancestors::resume_filtered(&mut state, find, predicate),
+                };
+                let start = Instant::now();
+                loop {
+                    if matches!(self.max_commits, Some(max) if commits.len() >= max)
+                        || (!commits.is_empty() && matches!(self.max_duration, Some(max) if start.elapsed() >= max))
+                    {
+                        break;
+                    }
+                    match iter.next() {
+                        Some(id) => commits.push(id?.attach(access)),
+                        None => break,
+                    }
+                }
+            }
+            let cursor = state.queued().next().is_some().then(|| Cursor {
+                queued: state.queued().map(ToOwned::to_owned).collect(),
+                seen: state.seen().map(ToOwned::to_owned).collect(),
+            });
+            self.cursor = cursor.clone();
+            Ok(Page { commits, cursor })
+        }
+    }
+
+    pub(super) fn is_present<'a>(
+        missing: Missing,
+        repo: &'a impl Deref<Target = crate::Repository>,
+    ) -> impl Fn(&oid) -> bool + 'a {
+        move |id| match missing {
+            Missing::Error => true,
+            Missing::Skip => repo.odb.contains(id),
+        }
+    }
+
+    fn commit_time(commit: git_object::CommitRefIter<'_>) -> Option<u32> {
+        commit.into_iter().find_map(|token| match token {
+            Ok(git_object::commit::ref_iter::Token::Committer { signature }) => Some(signature.time.time),
+            _ => None,
+        })
+    }
+
+    pub(super) fn find_commit<'a, A>(
+        access: &'a A,
+        repo: &'a A::RepoRef,
+    ) -> impl for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<git_object::CommitRefIter<'b>> + 'a
+    where
+        A: easy::Access + Sized,
+    {
+        move |oid, buf| {
+            let state = access.state();
+            let mut object_cache = state.try_borrow_mut_object_cache().ok()?;
+            if let Some(c) = object_cache.deref_mut() {
+                if let Some(kind) = c.get(&oid.to_owned(), buf) {
+                    return git_pack::data::Object::new(kind, buf).try_into_commit_iter();
+                }
+            }
+            match repo
+                .deref()
+                .odb
+                .try_find(
+                    oid,
+                    buf,
+                    state
+                        .try_borrow_mut_pack_cache()
+                        .expect("BUG: pack cache is already borrowed")
+                        .deref_mut(),
+                )
+                .ok()
+                .flatten()
+                .and_then(|obj| obj.try_into_commit_iter())
+            {
+                Some(_) => {
+                    if let Some(c) = object_cache.deref_mut() {
+                        c.put(oid.to_owned(), git_object::Kind::Commit, buf);
+                    }
+                    Some(git_object::CommitRefIter::from_bytes(buf))
+                }
+                None => None,
+            }
+        }
     }
 
     /// The iterator returned by [`Ancestors::all()`].
@@ -163,6 +411,17 @@ pub mod ancestors {
         }
     }
 
+    ///
+    pub mod page {
+        /// The error returned by [`Ancestors::page()`][super::Ancestors::page()].
+        #[derive(Debug, thiserror::Error)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            #[error(transparent)]
+            Traverse(#[from] git_traverse::commit::ancestors::Error),
+        }
+    }
+
     mod error {
         use crate::easy;
 
@@ -182,6 +441,421 @@ pub mod ancestors {
     use crate::ext::ObjectIdExt;
 }
 
+///
+pub mod shorten {
+    use std::fmt;
+
+    use git_hash::{oid, ObjectId};
+
+    use crate::{easy, easy::Oid};
+
+    /// The least amount of hexadecimal characters object ids can be abbreviated to.
+    pub const MIN_HEX_LEN: usize = 4;
+    /// The amount of hexadecimal characters object ids are abbreviated to by default, unless more are needed in large repositories.
+    pub const DEFAULT_HEX_LEN: usize = 7;
+
+    impl<'repo, A> Oid<'repo, A>
+    where
+        A: easy::Access + Sized,
+    {
+        /// Abbreviate this id to the [configured amount][easy::ext::RepositoryAccessExt::abbreviation()] of hexadecimal
+        /// characters, or more if needed to be unambiguous among all objects in the object database.
+        pub fn shorten(&self) -> Result<ShortId, Error> {
+            let hex_len = self.access.repo()?.abbrev;
+            self.shorten_to(hex_len)
+        }
+
+        /// Like [`shorten()`][Oid::shorten()], but abbreviate to at least `hex_len` hexadecimal characters instead of the
+        /// configured amount, while never using less than [`MIN_HEX_LEN`].
+        pub fn shorten_to(&self, hex_len: usize) -> Result<ShortId, Error> {
+            let repo = self.access.repo()?;
+            Ok(ShortId {
+                id: self.inner,
+                hex_len: shortest_unique_hex_len(&repo.odb, &self.inner, hex_len.max(MIN_HEX_LEN))?,
+            })
+        }
+    }
+
+    /// An object id along with the amount of hexadecimal characters to display it with, as returned by [`Oid::shorten()`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct ShortId {
+        /// The full object id.
+        pub id: ObjectId,
+        /// The amount of hexadecimal characters needed to abbreviate `id` without it being ambiguous in the object database.
+        pub hex_len: usize,
+    }
+
+    impl fmt::Display for ShortId {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&self.id.to_sha1_hex_string()[..self.hex_len])
+        }
+    }
+
+    /// Return the amount of hexadecimal characters, but at least `min_hex_len`, needed for `id` to be distinguishable
+    /// from all other objects in `odb`.
+    ///
+    /// Only the neighbors of `id` in each pack index and the loose objects sharing its fan-out directory are looked at.
+    pub(crate) fn shortest_unique_hex_len(
+        odb: &git_odb::linked::Store,
+        id: &oid,
+        min_hex_len: usize,
+    ) -> std::io::Result<usize> {
+        let mut hex_len = min_hex_len;
+        let mut consider = |other: &oid| {
+            if other != id {
+                hex_len = hex_len.max(common_hex_prefix_len(id, other) + 1);
+            }
+        };
+        let hex = id.to_sha1_hex();
+        for db in &odb.dbs {
+            for bundle in &db.bundles {
+                let index = &bundle.index;
+                let lo = lower_bound(index, id);
+                for neighbor in lo.saturating_sub(1)..index.num_objects().min(lo + 2) {
+                    consider(index.oid_at_index(neighbor));
+                }
+            }
+
+            let fan_out_dir = db
+                .loose
+                .path
+                .join(std::str::from_utf8(&hex[..2]).expect("hex is ascii"));
+            let entries = match std::fs::read_dir(fan_out_dir) {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err),
+            };
+            for entry in entries {
+                let mut other_hex = hex[..2].to_vec();
+                other_hex.extend_from_slice(entry?.file_name().to_string_lossy().as_bytes());
+                if let Ok(other) = ObjectId::from_hex(&other_hex) {
+                    consider(&other);
+                }
+            }
+        }
+        Ok(hex_len.min(id.kind().len_in_hex()))
+    }
+
+    /// Return the position of the first object in `index` that isn't smaller than `id`, which is `id` itself if it's contained,
+    /// or the amount of objects in `index` if all objects are smaller.
+    pub(crate) fn lower_bound(index: &git_pack::index::File, id: &oid) -> u32 {
+        let (mut lo, mut hi) = (0, index.num_objects());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if index.oid_at_index(mid) < id {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+
+    fn common_hex_prefix_len(a: &oid, b: &oid) -> usize {
+        let (a, b) = (a.as_bytes(), b.as_bytes());
+        a.iter()
+            .zip(b)
+            .position(|(a, b)| a != b)
+            .map(|pos| pos * 2 + usize::from(a[pos] >> 4 == b[pos] >> 4))
+            .unwrap_or_else(|| a.len() * 2)
+    }
+
+    mod error {
+        use crate::easy;
+
+        /// The error returned by [`Oid::shorten()`][super::Oid::shorten()].
+        #[derive(Debug, thiserror::Error)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            #[error("Could not list loose objects")]
+            Io(#[from] std::io::Error),
+            #[error(transparent)]
+            BorrowRepo(#[from] easy::borrow::repo::Error),
+        }
+    }
+    pub use error::Error;
+}
+
+///
+pub mod describe {
+    use std::{collections::HashMap, fmt};
+
+    use git_hash::ObjectId;
+    use git_object::bstr::BString;
+    use git_traverse::commit::CommitOrder;
+
+    use crate::{
+        easy,
+        easy::{
+            ext::{ObjectAccessExt, ReferenceAccessExt},
+            oid::{
+                ancestors::{find_commit, is_present, Missing},
+                shorten::ShortId,
+                Describe,
+            },
+            Oid,
+        },
+    };
+
+    impl<'repo, A> Oid<'repo, A>
+    where
+        A: easy::Access + Sized,
+    {
+        /// Obtain a platform to name this commit relative to the closest annotated tag in its history, like `git describe` does.
+        pub fn describe(&self) -> Describe<'repo, A> {
+            Describe {
+                access: self.access,
+                id: self.inner,
+                tags: false,
+                max_candidates: 10,
+                first_parent: false,
+                always: false,
+                abbreviation: None,
+            }
+        }
+    }
+
+    /// The outcome of [`Describe::resolve()`], which displays like `v1.2.0-14-gabc1234`.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub struct Resolution {
+        /// The name of the tag closest to the commit without the `refs/tags/` prefix, or `None` if no tag was found and
+        /// [`always()`][Describe::always()] was set.
+        pub name: Option<BString>,
+        /// The amount of commits reachable from the described commit but not from the tagged commit, or 0 if the commit is tagged.
+        pub depth: u32,
+        /// The id of the described commit.
+        pub id: ObjectId,
+        /// The amount of hexadecimal characters needed to abbreviate `id` without it being ambiguous in the object database.
+        pub hex_len: usize,
+    }
+
+    impl fmt::Display for Resolution {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let short_id = ShortId {
+                id: self.id,
+                hex_len: self.hex_len,
+            };
+            match &self.name {
+                Some(name) if self.depth == 0 => write!(f, "{}", name),
+                Some(name) => write!(f, "{}-{}-g{}", name, self.depth, short_id),
+                None => write!(f, "{}", short_id),
+            }
+        }
+    }
+
+    /// Configuration
+    impl<'repo, A> Describe<'repo, A>
+    where
+        A: easy::Access + Sized,
+    {
+        /// If `toggle` is true, consider lightweight tags as well, not only annotated ones. Defaults to `false`.
+        pub fn tags(mut self, toggle: bool) -> Self {
+            self.tags = toggle;
+            self
+        }
+
+        /// Stop looking for tagged commits in the history once `max_candidates` were found, and pick the closest one among them.
+        ///
+        /// With 0, only tags pointing to the commit itself are considered. Defaults to 10.
+        pub fn max_candidates(mut self, max_candidates: usize) -> Self {
+            self.max_candidates = max_candidates;
+            self
+        }
+
+        /// If `toggle` is true, only follow the first parent of merge commits. Defaults to `false`.
+        pub fn first_parent(mut self, toggle: bool) -> Self {
+            self.first_parent = toggle;
+            self
+        }
+
+        /// If `toggle` is true, produce a [`Resolution`] with only the abbreviated id if no tag was found instead of
+        /// failing with [`Error::NoTagFound`]. Defaults to `false`.
+        pub fn always(mut self, toggle: bool) -> Self {
+            self.always = toggle;
+            self
+        }
+
+        /// Abbreviate the id to at least `hex_len` hexadecimal characters instead of the
+        /// [configured amount][easy::ext::RepositoryAccessExt::abbreviation()].
+        pub fn abbreviation(mut self, hex_len: usize) -> Self {
+            self.abbreviation = Some(hex_len);
+            self
+        }
+    }
+
+    struct Tag {
+        name: BString,
+        annotated: bool,
+        time: u32,
+    }
+
+    impl<'repo, A> Describe<'repo, A>
+    where
+        A: easy::Access + Sized,
+    {
+        /// Find the tag closest to our commit and the amount of commits between them.
+        ///
+        /// If multiple tags point to the same commit, annotated tags win over lightweight ones, and the one with the
+        /// most recent tagger date wins among annotated ones. If multiple tagged commits are found, the one with the
+        /// fewest commits in between wins, or the one seen first in the date-ordered traversal if there is a tie.
+        pub fn resolve(&self) -> Result<Resolution, Error> {
+            let tags = self.tags_by_commit()?;
+            let id = Oid::from_id(self.id, self.access);
+            let hex_len = match self.abbreviation {
+                Some(hex_len) => id.shorten_to(hex_len)?,
+                None => id.shorten()?,
+            }
+            .hex_len;
+            let repo = self.access.repo()?;
+            let missing = if repo.is_shallow() || repo.is_partial() {
+                Missing::Skip
+            } else {
+                Missing::Error
+            };
+            let traverse = |tip: ObjectId, order: CommitOrder| {
+                git_traverse::commit::Ancestors::filtered(
+                    Some(tip),
+                    git_traverse::commit::ancestors::State::default(),
+                    find_commit(self.access, &repo),
+                    is_present(missing, &repo),
+                )
+                .first_parent(self.first_parent)
+                .order(order)
+            };
+
+            let (name, depth) = match tags.get(&self.id) {
+                Some(tag) => (Some(tag.name.clone()), 0),
+                None => {
+                    let mut candidates = Vec::new();
+                    if self.max_candidates != 0 {
+                        for id in traverse(self.id, CommitOrder::DateOrder) {
+                            let id = id?;
+                            if let Some(tag) = tags.get(&id) {
+                                candidates.push((id, tag));
+                                if candidates.len() == self.max_candidates {
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                    if candidates.is_empty() {
+                        if self.always {
+                            (None, 0)
+                        } else {
+                            return Err(Error::NoTagFound { id: self.id });
+                        }
+                    } else {
+                        let count = |tip: ObjectId| -> Result<u32, Error> {
+                            let mut count = 0;
+                            for id in traverse(tip, CommitOrder::BreadthFirst) {
+                                id?;
+                                count += 1;
+                            }
+                            Ok(count)
+                        };
+                        let num_reachable = count(self.id)?;
+                        let mut best: Option<(u32, &Tag)> = None;
+                        for (id, tag) in candidates {
+                            let depth = num_reachable - count(id)?;
+                            if best.map_or(true, |(best_depth, _)| depth < best_depth) {
+                                best = Some((depth, tag));
+                            }
+                        }
+                        let (depth, tag) = best.expect("at least one candidate");
+                        (Some(tag.name.clone()), depth)
+                    }
+                }
+            };
+            Ok(Resolution {
+                name,
+                depth,
+                id: self.id,
+                hex_len,
+            })
+        }
+
+        fn tags_by_commit(&self) -> Result<HashMap<ObjectId, Tag>, Error> {
+            let refs = self
+                .access
+                .references()?
+                .tags()?
+                .map(|r| r.map(|r| r.detach()))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(Error::ReferenceIter)?;
+            let mut out = HashMap::<ObjectId, Tag>::new();
+            for r in refs {
+                let id = match r.target {
+                    git_ref::Target::Peeled(id) => id,
+                    git_ref::Target::Symbolic(_) => continue,
+                };
+                let object = self.access.find_object(id)?;
+                let (annotated, time) = match object.kind {
+                    git_object::Kind::Tag => (true, tagger_time(object.tag_iter()).unwrap_or_default()),
+                    git_object::Kind::Commit if self.tags => (false, 0),
+                    _ => continue,
+                };
+                let object = object.peel_tags_to_end()?;
+                if object.kind != git_object::Kind::Commit {
+                    continue;
+                }
+                let name = r.name.as_bstr();
+                let tag = Tag {
+                    name: name.strip_prefix(b"refs/tags/").unwrap_or(name).into(),
+                    annotated,
+                    time,
+                };
+                let keep_existing = match out.get(&object.id) {
+                    Some(existing) => match (existing.annotated, tag.annotated) {
+                        (true, true) => existing.time >= tag.time,
+                        (existing_annotated, annotated) => existing_annotated || !annotated,
+                    },
+                    None => false,
+                };
+                if !keep_existing {
+                    out.insert(object.id, tag);
+                }
+            }
+            Ok(out)
+        }
+    }
+
+    fn tagger_time(tag: git_object::TagRefIter<'_>) -> Option<u32> {
+        tag.into_iter().find_map(|token| match token {
+            Ok(git_object::tag::ref_iter::Token::Tagger(signature)) => signature.map(|s| s.time.time),
+            _ => None,
+        })
+    }
+
+    mod error {
+        use git_hash::ObjectId;
+
+        use crate::easy;
+
+        /// The error returned by [`Describe::resolve()`][super::Describe::resolve()].
+        #[derive(Debug, thiserror::Error)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            #[error("No tag could be found in the history of commit {id} to describe it with")]
+            NoTagFound { id: ObjectId },
+            #[error(transparent)]
+            References(#[from] easy::reference::iter::Error),
+            #[error(transparent)]
+            ReferencesInit(#[from] easy::reference::iter::init::Error),
+            #[error("A tag reference could not be read")]
+            ReferenceIter(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+            #[error(transparent)]
+            FindExisting(#[from] easy::object::find::existing::Error),
+            #[error(transparent)]
+            Traverse(#[from] git_traverse::commit::ancestors::Error),
+            #[error(transparent)]
+            Shorten(#[from] easy::oid::shorten::Error),
+            #[error(transparent)]
+            BorrowRepo(#[from] easy::borrow::repo::Error),
+        }
+    }
+    pub use error::Error;
+}
+
 mod impls {
     use git_hash::{oid, ObjectId};
 
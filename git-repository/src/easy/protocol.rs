@@ -0,0 +1,15 @@
+//!
+///
+pub mod version {
+    use crate::easy;
+
+    /// The error returned by [`RepositoryAccessExt::protocol_version()`][easy::ext::RepositoryAccessExt::protocol_version()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("protocol.version must be 0, 1 or 2, got {:?}", .value)]
+        Invalid { value: crate::bstr::BString },
+        #[error(transparent)]
+        BorrowRepo(#[from] easy::borrow::repo::Error),
+    }
+}
@@ -0,0 +1,178 @@
+//! Compare references with an expected set of names and values, and turn the difference into edits that make them match.
+use git_hash::ObjectId;
+use git_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+use crate::{bstr::BString, easy, easy::ext::ReferenceAccessExt};
+
+/// A reference whose value differs from the expected one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Mismatch {
+    /// The name of the reference.
+    pub name: FullName,
+    /// The value the reference is expected to have.
+    pub expected: ObjectId,
+    /// The value the reference actually has, which might also be symbolic.
+    pub actual: Target,
+}
+
+/// The result of [`compare_refs()`][ReferenceAccessExt::compare_refs()], with all lists sorted by reference name.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Outcome {
+    /// References that are expected, but don't exist, along with the value they are expected to have.
+    pub missing: Vec<(FullName, ObjectId)>,
+    /// References that exist within the scope, but aren't expected, along with their current value.
+    pub extra: Vec<(FullName, Target)>,
+    /// References that exist and are expected, but have a different value.
+    pub mismatched: Vec<Mismatch>,
+}
+
+impl Outcome {
+    /// Return true if all references matched their expectations.
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.mismatched.is_empty()
+    }
+
+    /// Return the edits to create missing references, delete extra ones and set mismatched references to their expected value,
+    /// writing `log_message` into their reference logs.
+    ///
+    /// Each edit expects the reference to still have the value it had when it was compared, so the transaction fails if
+    /// references were changed concurrently.
+    pub fn into_edits(self, log_message: impl Into<BString>) -> Vec<RefEdit> {
+        let log = LogChange {
+            mode: RefLog::AndReference,
+            force_create_reflog: false,
+            message: log_message.into(),
+        };
+        let update = |name: FullName, expected: PreviousValue, id: ObjectId| RefEdit {
+            change: Change::Update {
+                log: log.clone(),
+                expected,
+                new: Target::Peeled(id),
+            },
+            name,
+            deref: false,
+        };
+        let mut edits: Vec<_> = self
+            .missing
+            .into_iter()
+            .map(|(name, id)| update(name, PreviousValue::MustNotExist, id))
+            .chain(
+                self.mismatched
+                    .into_iter()
+                    .map(|m| update(m.name, PreviousValue::MustExistAndMatch(m.actual), m.expected)),
+            )
+            .chain(self.extra.into_iter().map(|(name, actual)| RefEdit {
+                change: Change::Delete {
+                    expected: PreviousValue::MustExistAndMatch(actual),
+                    log: RefLog::AndReference,
+                },
+                name,
+                deref: false,
+            }))
+            .collect();
+        edits.sort_by(|a, b| a.name.cmp(&b.name));
+        edits
+    }
+}
+
+pub(crate) fn compare<A>(
+    access: &A,
+    expected: impl IntoIterator<Item = (FullName, ObjectId)>,
+    scope_prefix: &str,
+) -> Result<Outcome, Error>
+where
+    A: easy::Access + Sized,
+{
+    let scope_prefix = match scope_prefix {
+        "" => String::new(),
+        prefix => format!("{}/", prefix.trim_end_matches('/')),
+    };
+    let mut expected: Vec<_> = expected.into_iter().collect();
+    if let Some((name, _)) = expected
+        .iter()
+        .find(|(name, _)| !name.as_bstr().starts_with(scope_prefix.as_bytes()))
+    {
+        return Err(Error::OutOfScope {
+            name: name.clone(),
+            scope_prefix,
+        });
+    }
+    expected.sort();
+    expected.dedup();
+    if let Some(pair) = expected.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(Error::AmbiguousExpectation {
+            name: pair[0].0.clone(),
+            first: pair[0].1,
+            second: pair[1].1,
+        });
+    }
+
+    let platform = access.references()?;
+    let references = if scope_prefix.is_empty() {
+        platform.all()?
+    } else {
+        platform.prefixed(&scope_prefix)?
+    };
+    let mut seen = vec![false; expected.len()];
+    let mut out = Outcome::default();
+    for reference in references {
+        let reference = reference.map_err(Error::ReferenceIter)?.detach();
+        if !reference.name.as_bstr().starts_with(scope_prefix.as_bytes()) {
+            // Prefixed iteration also yields names which merely start with the last component, like `refs/heads-old`.
+            continue;
+        }
+        match expected.binary_search_by(|(name, _)| name.as_bstr().cmp(reference.name.as_bstr())) {
+            Ok(index) => {
+                seen[index] = true;
+                let expected_id = expected[index].1;
+                if reference.target != Target::Peeled(expected_id) {
+                    out.mismatched.push(Mismatch {
+                        name: reference.name,
+                        expected: expected_id,
+                        actual: reference.target,
+                    });
+                }
+            }
+            Err(_) => out.extra.push((reference.name, reference.target)),
+        }
+    }
+    out.missing = expected
+        .into_iter()
+        .zip(seen)
+        .filter_map(|(expected, seen)| if seen { None } else { Some(expected) })
+        .collect();
+    out.extra.sort();
+    out.mismatched.sort();
+    Ok(out)
+}
+
+mod error {
+    use git_hash::ObjectId;
+    use git_ref::FullName;
+
+    use crate::easy;
+
+    /// The error returned by [`ReferenceAccessExt::compare_refs()`][easy::ext::ReferenceAccessExt::compare_refs()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The expected reference '{}' is not within the scope '{scope_prefix}'", name.as_bstr())]
+        OutOfScope { name: FullName, scope_prefix: String },
+        #[error("The reference '{}' is expected to point to both {first} and {second}", name.as_bstr())]
+        AmbiguousExpectation {
+            name: FullName,
+            first: ObjectId,
+            second: ObjectId,
+        },
+        #[error(transparent)]
+        References(#[from] easy::reference::iter::Error),
+        #[error(transparent)]
+        ReferencesInit(#[from] easy::reference::iter::init::Error),
+        #[error("A reference could not be read")]
+        ReferenceIter(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    }
+}
+pub use error::Error;
@@ -59,6 +59,24 @@ where
             access: self.access,
         })
     }
+
+    /// Return an iterator over all local branches, i.e. references in `refs/heads/`.
+    ///
+    /// Like all iterators of this platform, it streams references from loose files and the borrowed packed-refs buffer
+    /// without collecting them, which keeps memory usage low even in repositories with hundreds of thousands of references.
+    pub fn local_branches(&self) -> Result<Iter<'_, A>, init::Error> {
+        self.prefixed("refs/heads/")
+    }
+
+    /// Return an iterator over all remote branches, i.e. references in `refs/remotes/`.
+    pub fn remote_branches(&self) -> Result<Iter<'_, A>, init::Error> {
+        self.prefixed("refs/remotes/")
+    }
+
+    /// Return an iterator over all tags, i.e. references in `refs/tags/`.
+    pub fn tags(&self) -> Result<Iter<'_, A>, init::Error> {
+        self.prefixed("refs/tags/")
+    }
 }
 
 impl<'r, A> Iter<'r, A> {
@@ -9,7 +9,9 @@ use crate::{
     easy::{Oid, Reference},
 };
 
+pub mod compare;
 pub mod iter;
+pub mod transaction;
 
 mod errors;
 use std::{borrow::Borrow, cell::RefMut, marker::PhantomData};
@@ -72,7 +74,9 @@ where
     /// Follow all symbolic targets this reference might point to and peel the underlying object
     /// to the end of the chain, and return it.
     ///
-    /// This is useful to learn where this reference is ulitmately pointing to.
+    /// This is useful to learn where this reference is ulitmately pointing to. Symbolic references are followed like `git` does,
+    /// failing on cycles, on references pointing to references that don't exist or if more than 5 references would have to be read.
+    /// Annotated tags are peeled by looking up their objects unless packed references already know the peeled id.
     pub fn peel_to_id_in_place(&mut self) -> Result<Oid<'repo, A>, peel::Error> {
         let repo = self.access.repo()?;
         let state = self.access.state();
@@ -0,0 +1,100 @@
+//! Prepare reference transactions to commit or roll them back later, to coordinate reference updates with external systems.
+use std::{
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use git_ref::transaction::RefEdit;
+
+///
+pub mod commit {
+    /// The error returned by [`PreparedTransaction::commit()`][super::PreparedTransaction::commit()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The transaction was rolled back as it wasn't committed in time")]
+        Expired,
+        #[error(transparent)]
+        FileTransactionCommit(#[from] git_ref::file::transaction::commit::Error),
+    }
+}
+
+struct Shared {
+    /// The prepared transaction, or `None` once it was committed or rolled back.
+    transaction: Mutex<Option<git_ref::file::Transaction<'static>>>,
+    /// Notified once the transaction was taken.
+    taken: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Option<git_ref::file::Transaction<'static>>> {
+        self.transaction.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A reference transaction whose references are all locked and validated, ready to be committed or rolled back, as obtained by
+/// [`ReferenceAccessExt::prepare_reference_transaction()`][crate::easy::ext::ReferenceAccessExt::prepare_reference_transaction()].
+///
+/// It can be sent to other threads and kept for as long as it takes to decide on its fate, while competing transactions fail
+/// to lock the same references. As committing and rolling back consume it, neither can happen twice.
+/// The transaction is rolled back when dropped, or once the timeout set with [`rollback_after()`][PreparedTransaction::rollback_after()]
+/// expired.
+pub struct PreparedTransaction {
+    shared: Arc<Shared>,
+    committer: git_actor::Signature,
+}
+
+impl PreparedTransaction {
+    pub(crate) fn new(transaction: git_ref::file::Transaction<'static>, committer: git_actor::Signature) -> Self {
+        PreparedTransaction {
+            shared: Arc::new(Shared {
+                transaction: Mutex::new(Some(transaction)),
+                taken: Condvar::new(),
+            }),
+            committer,
+        }
+    }
+
+    /// Roll the transaction back if it wasn't committed or rolled back within `timeout`, releasing all locks even if it's
+    /// never dropped. Committing it afterwards fails with [`commit::Error::Expired`].
+    ///
+    /// A thread waits for the timeout to expire or for the transaction to be finished, whichever happens first.
+    pub fn rollback_after(self, timeout: Duration) -> Self {
+        let shared = Arc::clone(&self.shared);
+        std::thread::spawn(move || {
+            let (mut transaction, _) = shared
+                .taken
+                .wait_timeout_while(shared.lock(), timeout, |transaction| transaction.is_some())
+                .unwrap_or_else(PoisonError::into_inner);
+            transaction.take();
+        });
+        self
+    }
+
+    /// Return true if the transaction was rolled back as its [timeout][PreparedTransaction::rollback_after()] expired.
+    pub fn is_expired(&self) -> bool {
+        self.shared.lock().is_none()
+    }
+
+    /// Make all prepared edits permanent and return them, see
+    /// [`edit_references()`][crate::easy::ext::ReferenceAccessExt::edit_references()] for details.
+    pub fn commit(self) -> Result<Vec<RefEdit>, commit::Error> {
+        let transaction = self.take().ok_or(commit::Error::Expired)?;
+        Ok(transaction.commit(&self.committer)?)
+    }
+
+    /// Release all locks without changing any reference, which is the same as dropping this instance.
+    pub fn rollback(self) {}
+
+    fn take(&self) -> Option<git_ref::file::Transaction<'static>> {
+        let transaction = self.shared.lock().take();
+        self.shared.taken.notify_all();
+        transaction
+    }
+}
+
+impl Drop for PreparedTransaction {
+    fn drop(&mut self) {
+        self.take();
+    }
+}
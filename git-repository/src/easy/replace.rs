@@ -0,0 +1,270 @@
+//! Create, list and delete replacements, references in `refs/replace/` named after the object they substitute and pointing to
+//! the object to use instead, like `git replace` does.
+//!
+//! Replacements can be chained, with the replacement of an object being replaced itself. As `git` follows chains only up to
+//! [a certain depth][MAX_DEPTH], creating replacements which form longer chains or cycles is refused.
+use std::{collections::BTreeMap, convert::TryInto};
+
+use git_hash::{oid, ObjectId};
+use git_ref::{
+    transaction::{Change, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use crate::{
+    easy,
+    easy::ext::{ObjectAccessExt, ReferenceAccessExt},
+};
+
+/// The prefix of all replacement references.
+pub const REFERENCE_PREFIX: &str = "refs/replace/";
+
+/// The longest chain of replacements `git` follows before giving up, which is the default for [`Options::max_depth`].
+///
+/// `git` allows 5 lookups, the last of which must find the object not to be replaced anymore.
+pub const MAX_DEPTH: usize = 4;
+
+/// An object and the object to use in its place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Replacement {
+    /// The object to substitute.
+    pub original: ObjectId,
+    /// The object to use instead of `original`.
+    pub replacement: ObjectId,
+}
+
+/// Configure how replacements are validated by [`ReferenceAccessExt::create_replacement()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// If true, the replacement may be of another kind than the original, like a commit replacing a tag.
+    ///
+    /// This is `false` by default as it's rarely intended, just like with `git replace` without `--force`.
+    pub allow_kind_change: bool,
+    /// The amount of replacements to follow at most to get from an object to the object finally used in its place.
+    pub max_depth: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            allow_kind_change: false,
+            max_depth: MAX_DEPTH,
+        }
+    }
+}
+
+mod error {
+    use git_hash::ObjectId;
+
+    use crate::easy;
+
+    /// The error returned when creating, listing or deleting replacements.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Object {id} cannot be replaced by itself")]
+        SelfReplacement { id: ObjectId },
+        #[error("Object {original} of kind {original_kind} cannot be replaced by object {replacement} of kind {replacement_kind}")]
+        KindMismatch {
+            original: ObjectId,
+            original_kind: git_object::Kind,
+            replacement: ObjectId,
+            replacement_kind: git_object::Kind,
+        },
+        #[error("Object {original} is already replaced by {replacement}")]
+        AlreadyReplaced { original: ObjectId, replacement: ObjectId },
+        #[error("Replacing {original} with {replacement} would form a cycle")]
+        Cycle { original: ObjectId, replacement: ObjectId },
+        #[error("Replacing {original} would create a chain of more than {max_depth} replacements")]
+        ChainTooDeep { original: ObjectId, max_depth: usize },
+        #[error(transparent)]
+        FindExistingObject(#[from] easy::object::find::existing::Error),
+        #[error(transparent)]
+        FindReference(#[from] easy::reference::find::Error),
+        #[error(transparent)]
+        References(#[from] easy::reference::iter::Error),
+        #[error(transparent)]
+        ReferencesInit(#[from] easy::reference::iter::init::Error),
+        #[error("A reference could not be read")]
+        ReferenceIter(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+        #[error(transparent)]
+        Edit(#[from] easy::reference::edit::Error),
+    }
+}
+pub use error::Error;
+
+/// All replacements of a repository, as obtained by [`ReferenceAccessExt::list_replacements()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Map {
+    replacements: BTreeMap<ObjectId, ObjectId>,
+}
+
+impl Map {
+    /// Return all replacements ordered by the id of the object they replace.
+    pub fn iter(&self) -> impl Iterator<Item = Replacement> + '_ {
+        self.replacements.iter().map(|(original, replacement)| Replacement {
+            original: *original,
+            replacement: *replacement,
+        })
+    }
+
+    /// Return the amount of replacements.
+    pub fn len(&self) -> usize {
+        self.replacements.len()
+    }
+
+    /// Return true if there are no replacements.
+    pub fn is_empty(&self) -> bool {
+        self.replacements.is_empty()
+    }
+
+    /// Return the object directly replacing `original`, or `None` if it isn't replaced.
+    pub fn get(&self, original: impl AsRef<oid>) -> Option<ObjectId> {
+        self.replacements.get(original.as_ref()).copied()
+    }
+
+    /// Return the object to use in place of `id` after following the chain of replacements, which is `id` itself if it
+    /// isn't replaced, or fail if the chain is longer than `max_depth` or forms a cycle.
+    pub fn resolve(&self, id: impl Into<ObjectId>, max_depth: usize) -> Result<ObjectId, Error> {
+        let original = id.into();
+        let mut id = original;
+        for _ in 0..=max_depth {
+            match self.replacements.get(&id) {
+                Some(replacement) => id = *replacement,
+                None => return Ok(id),
+            }
+        }
+        Err(Error::ChainTooDeep { original, max_depth })
+    }
+
+    /// Return the amount of replacements in the longest chain leading to `id`, but no more than `limit`.
+    fn depth_to(&self, id: ObjectId, limit: usize) -> usize {
+        let mut depth = 0;
+        let mut level = vec![id];
+        while depth < limit {
+            let mut next = Vec::new();
+            for id in level {
+                next.extend(
+                    self.replacements
+                        .iter()
+                        .filter(|(_, replacement)| **replacement == id)
+                        .map(|(original, _)| *original),
+                );
+            }
+            if next.is_empty() {
+                break;
+            }
+            depth += 1;
+            level = next;
+        }
+        depth
+    }
+}
+
+/// Return all replacements stored in `refs/replace/`, ignoring references whose name isn't an object id or which are symbolic.
+pub(crate) fn list<A>(access: &A) -> Result<Map, Error>
+where
+    A: easy::Access + Sized,
+{
+    let mut replacements = BTreeMap::new();
+    let references = access.references()?;
+    for reference in references.prefixed(REFERENCE_PREFIX)? {
+        let reference = reference.map_err(Error::ReferenceIter)?.detach();
+        let original = match reference
+            .name
+            .as_bstr()
+            .strip_prefix(REFERENCE_PREFIX.as_bytes())
+            .and_then(|hex| ObjectId::from_hex(hex).ok())
+        {
+            Some(id) => id,
+            None => continue,
+        };
+        if let Target::Peeled(replacement) = reference.target {
+            replacements.insert(original, replacement);
+        }
+    }
+    Ok(Map { replacements })
+}
+
+/// Validate and write a reference to replace `original` with `replacement`.
+pub(crate) fn create<A>(
+    access: &A,
+    original: ObjectId,
+    replacement: ObjectId,
+    options: Options,
+) -> Result<easy::Reference<'_, A>, Error>
+where
+    A: easy::Access + Sized,
+{
+    if original == replacement {
+        return Err(Error::SelfReplacement { id: original });
+    }
+    let original_kind = access.find_object(original)?.kind;
+    let replacement_kind = access.find_object(replacement)?.kind;
+    if original_kind != replacement_kind && !options.allow_kind_change {
+        return Err(Error::KindMismatch {
+            original,
+            original_kind,
+            replacement,
+            replacement_kind,
+        });
+    }
+
+    let map = list(access)?;
+    if let Some(existing) = map.get(original) {
+        return Err(Error::AlreadyReplaced {
+            original,
+            replacement: existing,
+        });
+    }
+    let mut depth = map.depth_to(original, options.max_depth + 1) + 1;
+    let mut id = replacement;
+    while let Some(next) = map.get(id) {
+        if next == original {
+            return Err(Error::Cycle { original, replacement });
+        }
+        depth += 1;
+        if depth > options.max_depth {
+            break;
+        }
+        id = next;
+    }
+    if depth > options.max_depth {
+        return Err(Error::ChainTooDeep {
+            original,
+            max_depth: options.max_depth,
+        });
+    }
+
+    Ok(access.reference(
+        format!("{}{}", REFERENCE_PREFIX, original),
+        replacement,
+        PreviousValue::MustNotExist,
+        format!("replace: {} with {}", original, replacement),
+    )?)
+}
+
+/// Delete the reference replacing `original` and return the id of the object it was replaced with, or `None` if it wasn't replaced.
+pub(crate) fn delete<A>(access: &A, original: ObjectId) -> Result<Option<ObjectId>, Error>
+where
+    A: easy::Access + Sized,
+{
+    let name = format!("{}{}", REFERENCE_PREFIX, original);
+    let replacement = match access.try_find_reference(name.as_str())? {
+        Some(reference) => reference.detach().target,
+        None => return Ok(None),
+    };
+    access.edit_reference(
+        RefEdit {
+            change: Change::Delete {
+                expected: PreviousValue::MustExistAndMatch(replacement.clone()),
+                log: RefLog::AndReference,
+            },
+            name: name.try_into().map_err(easy::reference::edit::Error::from)?,
+            deref: false,
+        },
+        git_lock::acquire::Fail::Immediately,
+        None,
+    )?;
+    Ok(replacement.as_id().map(ToOwned::to_owned))
+}
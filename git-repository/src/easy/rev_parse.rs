@@ -0,0 +1,358 @@
+//! Resolve revision specifications like `HEAD~2^2`, `v1.0^{commit}` or `main:README.md` to object ids.
+//!
+//! A specification consists of a name, followed by any amount of operators, optionally followed by `:<path>`.
+//!
+//! * **names** are resolved in the order `git` uses:
+//!     - a full hexadecimal object id, which isn't checked for existence,
+//!     - a reference, trying `refs/<name>`, `refs/tags/<name>`, `refs/heads/<name>`, `refs/remotes/<name>` and
+//!       `refs/remotes/<name>/HEAD`, with `@` being a shortcut for `HEAD`,
+//!     - an abbreviated hexadecimal object id of at least [`MIN_HEX_LEN`][easy::oid::shorten::MIN_HEX_LEN] characters,
+//!       which must not be ambiguous in the object database.
+//! * **operators** can be chained arbitrarily:
+//!     - `^<n>` selects the `n`th parent of a commit, with `^` being `^1` and `^0` being the commit itself,
+//!     - `~<n>` follows the first parent `n` times, with `~` being `~1`,
+//!     - `^{tree}`, `^{commit}`, `^{tag}` and `^{blob}` peel the object until one of the given kind is encountered,
+//!     - `^{}` peels tags until a non-tag object is encountered, and `^{object}` only asserts that the object exists.
+//! * **`:<path>`** looks up the object at `path` within the tree of the resolved object, with an empty path denoting the
+//!   tree itself.
+//!
+//! Annotated tags and commits are peeled to commits as needed by `^<n>` and `~<n>`.
+use std::convert::TryFrom;
+
+use git_hash::ObjectId;
+use git_object::bstr::{BStr, ByteSlice};
+use git_ref::PartialNameRef;
+
+use crate::{
+    easy,
+    easy::{
+        ext::{ObjectAccessExt, ReferenceAccessExt},
+        object::{typed, Kind},
+        oid::shorten::MIN_HEX_LEN,
+        Oid,
+    },
+    ext::ObjectIdExt,
+};
+
+/// The maximum amount of symbolic references to follow when resolving a name, just like `git`.
+const MAX_SYMBOLIC_REF_DEPTH: usize = 5;
+
+mod error {
+    use git_hash::ObjectId;
+
+    use crate::{bstr::BString, easy, easy::object};
+
+    /// The error returned by [`ObjectAccessExt::rev_parse()`][easy::ext::ObjectAccessExt::rev_parse()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The revision specification is empty")]
+        Empty,
+        #[error("Could not parse '{component}' in revision specification '{spec}'")]
+        Malformed { spec: BString, component: BString },
+        #[error("The path ':{path}' refers to the index, which isn't supported")]
+        IndexPath { path: BString },
+        #[error("'{name}' is neither a reference nor an object id")]
+        UnknownName { name: BString },
+        #[error("The reference '{name}' points to '{target}', which doesn't exist")]
+        UnbornReference { name: BString, target: BString },
+        #[error("More than {max} symbolic references had to be followed to resolve '{name}'")]
+        SymbolicRefDepth { name: BString, max: usize },
+        #[error(
+            "The short id '{prefix}' is ambiguous, candidates are {}",
+            .candidates.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+        )]
+        AmbiguousPrefix { prefix: BString, candidates: Vec<ObjectId> },
+        #[error("Commit {id} has {parents} parent(s), so '{operator}' can't select parent {index}")]
+        ParentOutOfRange {
+            operator: BString,
+            id: ObjectId,
+            index: usize,
+            parents: usize,
+        },
+        #[error("'{operator}' could not be applied to object {id}")]
+        Peel {
+            operator: BString,
+            id: ObjectId,
+            source: object::peel::to_kind::Error,
+        },
+        #[error("The path '{path}' does not exist in tree {tree}")]
+        NoSuchPath { path: BString, tree: ObjectId },
+        #[error(transparent)]
+        FindObject(#[from] object::find::existing::Error),
+        #[error(transparent)]
+        FindReference(#[from] easy::reference::find::Error),
+        #[error(transparent)]
+        DecodeCommit(#[from] git_object::decode::Error),
+        #[error("Could not list loose objects")]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        BorrowRepo(#[from] easy::borrow::repo::Error),
+    }
+}
+pub use error::Error;
+
+enum Peel {
+    ToKind(Kind),
+    Tags,
+    Exists,
+}
+
+enum Operator {
+    Parent(usize),
+    Ancestor(usize),
+    Peel(Peel),
+}
+
+pub(crate) fn resolve<'a, A>(access: &'a A, spec: &BStr) -> Result<Oid<'a, A>, Error>
+where
+    A: easy::Access + Sized,
+{
+    let (rev, path) = match spec.find_byte(b':') {
+        Some(pos) => (spec[..pos].as_bstr(), Some(spec[pos + 1..].as_bstr())),
+        None => (spec, None),
+    };
+    if rev.is_empty() {
+        return Err(match path {
+            Some(path) => Error::IndexPath { path: path.to_owned() },
+            None => Error::Empty,
+        });
+    }
+    let (name, operators) = rev.split_at(rev.find_byteset(b"^~").unwrap_or_else(|| rev.len()));
+    if name.is_empty() {
+        return Err(Error::Malformed {
+            spec: spec.to_owned(),
+            component: rev.to_owned(),
+        });
+    }
+
+    let mut id = resolve_name(access, name.as_bstr())?;
+    for (operator, text) in parse_operators(spec, operators.as_bstr())? {
+        id = apply(access, id, operator, text)?;
+    }
+    if let Some(path) = path {
+        id = lookup_path(access, id, path)?;
+    }
+    Ok(id.attach(access))
+}
+
+fn parse_operators<'a>(spec: &BStr, mut input: &'a BStr) -> Result<Vec<(Operator, &'a BStr)>, Error> {
+    let malformed = |component: &BStr| Error::Malformed {
+        spec: spec.to_owned(),
+        component: component.to_owned(),
+    };
+    let mut operators = Vec::new();
+    while let Some(first) = input.first().copied() {
+        let start = input;
+        let rest = input[1..].as_bstr();
+        let (operator, rest) = match first {
+            b'^' if rest.first() == Some(&b'{') => {
+                let end = rest.find_byte(b'}').ok_or_else(|| malformed(start))?;
+                let peel = match rest[1..end].as_bytes() {
+                    b"" => Peel::Tags,
+                    b"object" => Peel::Exists,
+                    kind => Peel::ToKind(Kind::from_bytes(kind).map_err(|_| malformed(start[..end + 2].as_bstr()))?),
+                };
+                (Operator::Peel(peel), rest[end + 1..].as_bstr())
+            }
+            b'^' | b'~' => {
+                let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+                let n = if digits == 0 {
+                    1
+                } else {
+                    std::str::from_utf8(&rest[..digits])
+                        .expect("digits are ascii")
+                        .parse()
+                        .map_err(|_| malformed(start[..digits + 1].as_bstr()))?
+                };
+                let operator = if first == b'^' {
+                    Operator::Parent(n)
+                } else {
+                    Operator::Ancestor(n)
+                };
+                (operator, rest[digits..].as_bstr())
+            }
+            _ => return Err(malformed(start)),
+        };
+        operators.push((operator, start[..start.len() - rest.len()].as_bstr()));
+        input = rest;
+    }
+    Ok(operators)
+}
+
+fn resolve_name<A>(access: &A, name: &BStr) -> Result<ObjectId, Error>
+where
+    A: easy::Access + Sized,
+{
+    let hex_len = access.repo()?.hash_kind.len_in_hex();
+    if name.len() == hex_len {
+        if let Ok(id) = ObjectId::from_hex(name) {
+            return Ok(id);
+        }
+    }
+
+    let ref_name = if name == "@" { "HEAD".into() } else { name };
+    if let Ok(partial_name) = PartialNameRef::try_from(ref_name) {
+        if let Some(mut reference) = access.try_find_reference(partial_name)? {
+            for _ in 0..MAX_SYMBOLIC_REF_DEPTH {
+                let target = match reference.inner.target {
+                    git_ref::Target::Peeled(id) => return Ok(id),
+                    git_ref::Target::Symbolic(target) => target,
+                };
+                reference = match access.try_find_reference(target.to_partial())? {
+                    Some(reference) => reference,
+                    None => {
+                        return Err(Error::UnbornReference {
+                            name: reference.inner.name.into_inner(),
+                            target: target.into_inner(),
+                        })
+                    }
+                };
+            }
+            return Err(Error::SymbolicRefDepth {
+                name: name.to_owned(),
+                max: MAX_SYMBOLIC_REF_DEPTH,
+            });
+        }
+    }
+
+    if name.len() >= MIN_HEX_LEN && name.len() < hex_len && name.iter().all(u8::is_ascii_hexdigit) {
+        let prefix = name.to_ascii_lowercase();
+        let mut candidates = prefix_candidates(&access.repo()?.odb, &prefix)?;
+        match candidates.len() {
+            0 => {}
+            1 => return Ok(candidates.pop().expect("one candidate")),
+            _ => {
+                return Err(Error::AmbiguousPrefix {
+                    prefix: name.to_owned(),
+                    candidates,
+                })
+            }
+        }
+    }
+    Err(Error::UnknownName { name: name.to_owned() })
+}
+
+fn apply<A>(access: &A, id: ObjectId, operator: Operator, text: &BStr) -> Result<ObjectId, Error>
+where
+    A: easy::Access + Sized,
+{
+    let peel_error = |id: ObjectId, source| Error::Peel {
+        operator: text.to_owned(),
+        id,
+        source,
+    };
+    let peel = |id: ObjectId, kind: Kind| -> Result<ObjectId, Error> {
+        access
+            .find_object(id)?
+            .peel_to_kind(kind)
+            .map(|object| object.id)
+            .map_err(|source| peel_error(id, source))
+    };
+    let parents = |id: ObjectId| -> Result<(ObjectId, Vec<ObjectId>), Error> {
+        let commit = access
+            .find_object(id)?
+            .peel_to::<typed::Commit>()
+            .map_err(|source| peel_error(id, source))?;
+        let parents = commit.commit()?.parents().collect();
+        Ok((commit.id, parents))
+    };
+    let parent_out_of_range = |id: ObjectId, index: usize, parents: usize| Error::ParentOutOfRange {
+        operator: text.to_owned(),
+        id,
+        index,
+        parents,
+    };
+
+    Ok(match operator {
+        Operator::Parent(0) | Operator::Ancestor(0) => peel(id, Kind::Commit)?,
+        Operator::Parent(index) => {
+            let (id, parents) = parents(id)?;
+            *parents
+                .get(index - 1)
+                .ok_or_else(|| parent_out_of_range(id, index, parents.len()))?
+        }
+        Operator::Ancestor(generations) => {
+            let mut id = id;
+            for _ in 0..generations {
+                let (commit_id, parents) = parents(id)?;
+                id = *parents
+                    .first()
+                    .ok_or_else(|| parent_out_of_range(commit_id, 1, parents.len()))?;
+            }
+            id
+        }
+        Operator::Peel(Peel::ToKind(kind)) => peel(id, kind)?,
+        Operator::Peel(Peel::Tags) => access.find_object(id)?.peel_tags_to_end()?.id,
+        Operator::Peel(Peel::Exists) => access.find_object(id)?.id,
+    })
+}
+
+fn lookup_path<A>(access: &A, id: ObjectId, path: &BStr) -> Result<ObjectId, Error>
+where
+    A: easy::Access + Sized,
+{
+    let tree = access.find_object(id)?.peel_to_tree().map_err(|source| Error::Peel {
+        operator: {
+            let mut operator = path.to_owned();
+            operator.insert(0, b':');
+            operator
+        },
+        id,
+        source,
+    })?;
+    if path.is_empty() {
+        return Ok(tree.id);
+    }
+    let tree_id = tree.id;
+    tree.lookup_path(path.split_str("/").filter(|component| !component.is_empty()))?
+        .map(|entry| entry.oid)
+        .ok_or_else(|| Error::NoSuchPath {
+            path: path.to_owned(),
+            tree: tree_id,
+        })
+}
+
+/// Return the ids of all objects in `odb` whose hexadecimal representation starts with the lower-case hexadecimal `prefix`,
+/// sorted and without duplicates.
+fn prefix_candidates(odb: &git_odb::linked::Store, prefix: &[u8]) -> std::io::Result<Vec<ObjectId>> {
+    let has_prefix = |id: &git_hash::oid| id.to_sha1_hex()[..prefix.len()] == *prefix;
+    let mut lowest = prefix.to_vec();
+    lowest.resize(git_hash::Kind::Sha1.len_in_hex(), b'0');
+    let lowest = ObjectId::from_hex(&lowest).expect("valid hex");
+
+    let mut candidates = Vec::new();
+    for db in &odb.dbs {
+        for bundle in &db.bundles {
+            let index = &bundle.index;
+            candidates.extend(
+                (easy::oid::shorten::lower_bound(index, &lowest)..index.num_objects())
+                    .map(|idx| index.oid_at_index(idx))
+                    .take_while(|id| has_prefix(id))
+                    .map(ToOwned::to_owned),
+            );
+        }
+
+        let fan_out_dir = db
+            .loose
+            .path
+            .join(std::str::from_utf8(&prefix[..2]).expect("hex is ascii"));
+        let entries = match std::fs::read_dir(fan_out_dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        for entry in entries {
+            let mut hex = prefix[..2].to_vec();
+            hex.extend_from_slice(entry?.file_name().to_string_lossy().as_bytes());
+            if let Ok(id) = ObjectId::from_hex(&hex) {
+                if has_prefix(&id) {
+                    candidates.push(id);
+                }
+            }
+        }
+    }
+    candidates.sort();
+    candidates.dedup();
+    Ok(candidates)
+}
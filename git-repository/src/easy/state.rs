@@ -21,6 +21,7 @@ impl Default for easy::State {
             pack_cache: RefCell::new(Box::new(git_pack::cache::lru::StaticLinkedList::<64>::default())),
             object_cache: RefCell::new(None),
             buf: RefCell::new(vec![]),
+            odb: RefCell::new(None),
         }
     }
 }
@@ -53,6 +54,16 @@ impl easy::State {
         self.buf.try_borrow_mut().map_err(Into::into)
     }
 
+    #[inline]
+    pub(crate) fn try_borrow_odb(&self) -> borrow::state::Result<Ref<'_, Option<git_odb::linked::Store>>> {
+        self.odb.try_borrow().map_err(Into::into)
+    }
+
+    #[inline]
+    pub(crate) fn try_borrow_mut_odb(&self) -> borrow::state::Result<RefMut<'_, Option<git_odb::linked::Store>>> {
+        self.odb.try_borrow_mut().map_err(Into::into)
+    }
+
     #[inline]
     pub(crate) fn try_borrow_buf(&self) -> borrow::state::Result<Ref<'_, Vec<u8>>> {
         self.buf.try_borrow().map_err(Into::into)
@@ -0,0 +1,352 @@
+//! List tags sorted like `git tag --sort=<key>` does, by name, by version or by the date of their creation.
+use std::{borrow::Cow, cmp::Ordering};
+
+use git_hash::ObjectId;
+use git_object::bstr::BString;
+use git_ref::FullName;
+
+use crate::{easy, easy::ext::ObjectAccessExt};
+
+/// The order in which tags are returned by [`List::all()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sort {
+    /// Byte-wise lexicographic order of their names, like `git tag --sort=refname`, which is the default.
+    Refname,
+    /// Treat sequences of digits in their names as numbers, like `git tag --sort=version:refname`, so `v1.10.0`
+    /// comes after `v1.2.0`.
+    ///
+    /// Names with a configured [pre-release suffix][List::version_sort_suffixes()] come before the release, so `v1.2.0-rc1`
+    /// comes before `v1.2.0` if `-rc` is configured.
+    VersionRefname,
+    /// The date at which they were created, oldest first, like `git tag --sort=creatordate`.
+    ///
+    /// This is the tagger date of annotated tags and the committer date of commits pointed to by lightweight tags,
+    /// which requires their objects to be looked up.
+    ///
+    /// Like `git`, tags without such a date, like annotated tags without a tagger or lightweight tags pointing to trees or blobs,
+    /// are sorted as if they were created at the beginning of the epoch.
+    CreatorDate,
+}
+
+impl Default for Sort {
+    fn default() -> Self {
+        Sort::Refname
+    }
+}
+
+/// A tag as returned by [`List::all()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Tag {
+    /// The full name of the tag reference, like `refs/tags/v1.0`.
+    pub name: FullName,
+    /// The id the tag reference points to, which is a tag object for annotated tags.
+    pub id: ObjectId,
+}
+
+mod error {
+    use crate::easy;
+
+    /// The error returned by [`List::all()`][super::List::all()] and the [iterator][super::Iter] it returns.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        ReferencesInit(#[from] easy::reference::iter::init::Error),
+        #[error("A tag reference could not be read")]
+        ReferenceIter(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+        #[error(transparent)]
+        FindExistingObject(#[from] easy::object::find::existing::Error),
+    }
+}
+pub use error::Error;
+
+/// A platform to list tags in a configurable order, created by
+/// [`ReferenceAccessExt::list_tags()`][easy::ext::ReferenceAccessExt::list_tags()].
+pub struct List<'repo, A>
+where
+    A: easy::Access + Sized,
+{
+    pub(crate) references: easy::reference::iter::Platform<'repo, A>,
+    pub(crate) sort: Sort,
+    pub(crate) version_sort_suffixes: Option<Vec<BString>>,
+}
+
+/// An iterator over tags in the order configured on a [`List`], created by [`List::all()`].
+pub struct Iter<'r, A> {
+    inner: Inner<'r, A>,
+}
+
+enum Inner<'r, A> {
+    /// Tags are streamed as the references are already sorted by name.
+    Refname(Box<easy::reference::iter::Iter<'r, A>>),
+    /// All tags had to be read before they could be sorted.
+    Sorted(std::vec::IntoIter<Tag>),
+}
+
+impl<'r, A> Iterator for Iter<'r, A>
+where
+    A: easy::Access + Sized,
+{
+    type Item = Result<Tag, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            Inner::Refname(references) => references.find_map(|reference| match reference {
+                Ok(reference) => to_tag(reference.detach()).map(Ok),
+                Err(err) => Some(Err(Error::ReferenceIter(err))),
+            }),
+            Inner::Sorted(tags) => tags.next().map(Ok),
+        }
+    }
+}
+
+fn collect<A>(references: easy::reference::iter::Iter<'_, A>) -> Result<Vec<Tag>, Error>
+where
+    A: easy::Access + Sized,
+{
+    let mut tags = Vec::new();
+    for reference in references {
+        tags.extend(to_tag(reference.map_err(Error::ReferenceIter)?.detach()));
+    }
+    Ok(tags)
+}
+
+/// Symbolic references in `refs/tags/` aren't tags.
+fn to_tag(reference: git_ref::Reference) -> Option<Tag> {
+    match reference.target {
+        git_ref::Target::Peeled(id) => Some(Tag {
+            name: reference.name,
+            id,
+        }),
+        git_ref::Target::Symbolic(_) => None,
+    }
+}
+
+/// Configuration
+impl<'repo, A> List<'repo, A>
+where
+    A: easy::Access + Sized,
+{
+    /// Return tags in the given `sort` order instead of [`Sort::Refname`].
+    pub fn sort(mut self, sort: Sort) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Use `suffixes` to identify pre-releases when sorting by [version][Sort::VersionRefname] instead of the ones configured
+    /// with `versionsort.suffix`.
+    ///
+    /// Names with a suffix come before names without one, and suffixes listed earlier come before the ones listed later.
+    pub fn version_sort_suffixes(mut self, suffixes: impl IntoIterator<Item = impl Into<BString>>) -> Self {
+        self.version_sort_suffixes = Some(suffixes.into_iter().map(Into::into).collect());
+        self
+    }
+}
+
+impl<'repo, A> List<'repo, A>
+where
+    A: easy::Access + Sized,
+{
+    /// Return an iterator over all tags in the configured order.
+    ///
+    /// Tags are streamed when sorting by [`Sort::Refname`] as references are already ordered by name, whereas
+    /// all tags are read before they are returned in any other order.
+    /// Objects are only looked up when sorting by [date][Sort::CreatorDate].
+    /// Symbolic references in `refs/tags/` are skipped.
+    pub fn all(&self) -> Result<Iter<'_, A>, Error> {
+        let references = self.references.tags()?;
+        let tags = match self.sort {
+            Sort::Refname => {
+                return Ok(Iter {
+                    inner: Inner::Refname(Box::new(references)),
+                })
+            }
+            Sort::VersionRefname => {
+                let suffixes = match &self.version_sort_suffixes {
+                    Some(suffixes) => Cow::Borrowed(suffixes),
+                    None => Cow::Owned(self.references.repo.version_sort_suffixes.clone()),
+                };
+                let mut tags = collect(references)?;
+                tags.sort_by(|a, b| {
+                    version_cmp(a.name.as_bstr(), b.name.as_bstr(), &suffixes).then_with(|| a.name.cmp(&b.name))
+                });
+                tags
+            }
+            Sort::CreatorDate => {
+                let mut dated = Vec::new();
+                for tag in collect(references)? {
+                    // `git` uses 0 as well if there is no date to sort by.
+                    let time = self.creator_time(tag.id)?.unwrap_or(0);
+                    dated.push((time, tag));
+                }
+                dated.sort_by(|(a_time, a), (b_time, b)| a_time.cmp(b_time).then_with(|| a.name.cmp(&b.name)));
+                dated.into_iter().map(|(_, tag)| tag).collect()
+            }
+        };
+        Ok(Iter {
+            inner: Inner::Sorted(tags.into_iter()),
+        })
+    }
+
+    /// The tagger time of annotated tags or the committer time of commits, or `None` if `id` has no such time.
+    fn creator_time(&self, id: ObjectId) -> Result<Option<u32>, Error> {
+        let object = self.references.access.find_object(id)?;
+        Ok(match object.kind {
+            git_object::Kind::Tag => object.tag_iter().find_map(|token| match token {
+                Ok(git_object::tag::ref_iter::Token::Tagger(signature)) => signature.map(|s| s.time.time),
+                _ => None,
+            }),
+            git_object::Kind::Commit => object.commit_iter().find_map(|token| match token {
+                Ok(git_object::commit::ref_iter::Token::Committer { signature }) => Some(signature.time.time),
+                _ => None,
+            }),
+            _ => None,
+        })
+    }
+}
+
+pub(crate) fn version_sort_suffixes_from_config(config: &git_config::file::GitConfig<'_>) -> Vec<BString> {
+    config
+        .multi_value::<Cow<'_, [u8]>>("versionsort", None, "suffix")
+        .or_else(|_| config.multi_value::<Cow<'_, [u8]>>("versionsort", None, "prereleaseSuffix"))
+        .map(|suffixes| suffixes.into_iter().map(|s| s.into_owned().into()).collect())
+        .unwrap_or_default()
+}
+
+/// Compare `a` and `b` like `git` does when sorting by `version:refname`, using `suffixes` to identify pre-releases.
+///
+/// Sequences of digits are compared numerically, leading zeroes denote fractional parts, and all other bytes
+/// are compared as is. If one of the names contains a suffix where they first differ, it is ordered before the other,
+/// and if both do, the one whose suffix comes first in `suffixes` is ordered first. Names with the same suffix are compared
+/// by what follows it.
+pub fn version_cmp(a: &[u8], b: &[u8], suffixes: &[BString]) -> Ordering {
+    const S_N: usize = 0;
+    const S_I: usize = 3;
+    const S_F: usize = 6;
+    const S_Z: usize = 9;
+    const CMP: i8 = 2;
+    const LEN: i8 = 3;
+    #[rustfmt::skip]
+    const NEXT_STATE: [usize; 12] = [
+        /* S_N */ S_N, S_I, S_Z,
+        /* S_I */ S_N, S_I, S_I,
+        /* S_F */ S_N, S_F, S_F,
+        /* S_Z */ S_N, S_F, S_Z,
+    ];
+    #[rustfmt::skip]
+    const RESULT_TYPE: [i8; 36] = [
+        /*         x/x  x/d  x/0  d/x  d/d  d/0  0/x  0/d  0/0 */
+        /* S_N */ CMP, CMP, CMP, CMP, LEN, CMP, CMP, CMP, CMP,
+        /* S_I */ CMP, -1,  -1,  1,   LEN, LEN, 1,   LEN, LEN,
+        /* S_F */ CMP, CMP, CMP, CMP, CMP, CMP, CMP, CMP, CMP,
+        /* S_Z */ CMP, 1,   1,   -1,  CMP, CMP, -1,  CMP, CMP,
+    ];
+    // Bytes past the end behave like the terminating NUL of C strings.
+    let at = |s: &[u8], idx: usize| s.get(idx).copied().unwrap_or(0);
+    let class = |c: u8| usize::from(c == b'0') + usize::from(c.is_ascii_digit());
+
+    let mut pos = 0;
+    let (mut c1, mut c2) = (at(a, pos), at(b, pos));
+    let mut state = S_N + class(c1);
+    while c1 == c2 {
+        if c1 == 0 {
+            return Ordering::Equal;
+        }
+        pos += 1;
+        c1 = at(a, pos);
+        c2 = at(b, pos);
+        state = NEXT_STATE[state] + class(c1);
+    }
+    let diff = i32::from(c1) - i32::from(c2);
+
+    if let Some(ordering) = compare_prerelease_suffixes(a, b, pos, suffixes) {
+        return ordering;
+    }
+
+    match RESULT_TYPE[state * 3 + class(c2)] {
+        CMP => diff.cmp(&0),
+        LEN => {
+            // The longer sequence of digits is the larger number.
+            let mut idx = pos + 1;
+            while at(a, idx).is_ascii_digit() {
+                if !at(b, idx).is_ascii_digit() {
+                    return Ordering::Greater;
+                }
+                idx += 1;
+            }
+            if at(b, idx).is_ascii_digit() {
+                Ordering::Less
+            } else {
+                diff.cmp(&0)
+            }
+        }
+        result => result.cmp(&0),
+    }
+}
+
+/// The earliest and then longest match of a suffix in a name, along with the suffix's position in the list of suffixes.
+struct SuffixMatch {
+    suffix_index: Option<usize>,
+    start: usize,
+    len: Option<usize>,
+}
+
+impl SuffixMatch {
+    fn improve(&mut self, name: &[u8], suffix: &[u8], start: usize, suffix_index: usize) {
+        // A better match either starts earlier, or starts at the same position but is longer.
+        let end = if self.len < Some(suffix.len()) {
+            Some(self.start)
+        } else {
+            self.start.checked_sub(1)
+        };
+        let end = match end {
+            Some(end) => end,
+            None => return,
+        };
+        for idx in start..=end {
+            if name.get(idx..).map_or(false, |rest| rest.starts_with(suffix)) {
+                self.suffix_index = Some(suffix_index);
+                self.start = idx;
+                self.len = Some(suffix.len());
+                break;
+            }
+        }
+    }
+}
+
+fn compare_prerelease_suffixes(a: &[u8], b: &[u8], offset: usize, suffixes: &[BString]) -> Option<Ordering> {
+    let new_match = || SuffixMatch {
+        suffix_index: None,
+        start: offset,
+        len: None,
+    };
+    let (mut a_match, mut b_match) = (new_match(), new_match());
+    for (suffix_index, suffix) in suffixes.iter().enumerate() {
+        let start = offset.saturating_sub(suffix.len());
+        a_match.improve(a, suffix, start, suffix_index);
+        b_match.improve(b, suffix, start, suffix_index);
+    }
+    match (a_match.suffix_index, b_match.suffix_index) {
+        // Like `git`, names with the same suffix are compared by what follows it, like `2` and `10` in `-rc2` and `-rc10`.
+        (None, None) => None,
+        (Some(a), Some(b)) if a == b => None,
+        (Some(a), Some(b)) => Some(a.cmp(&b)),
+        (Some(_), None) => Some(Ordering::Less),
+        (None, Some(_)) => Some(Ordering::Greater),
+    }
+}
+
+///
+pub mod create {
+    use crate::easy;
+
+    /// The error returned by [`ObjectAccessExt::tag_deterministic(…)`][easy::ext::ObjectAccessExt::tag_deterministic()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        WriteObject(#[from] easy::object::write::Error),
+        #[error(transparent)]
+        ReferenceEdit(#[from] easy::reference::edit::Error),
+    }
+}
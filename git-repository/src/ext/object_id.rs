@@ -1,7 +1,8 @@
 use git_hash::ObjectId;
+use git_ref::PartialNameRef;
 use git_traverse::commit::{ancestors, Ancestors};
 
-use crate::easy;
+use crate::{bstr::BString, easy};
 
 pub trait Sealed {}
 
@@ -14,6 +15,14 @@ pub trait ObjectIdExt: Sealed {
 
     /// Infuse this object id with an [`easy::Access`].
     fn attach<A: easy::Access + Sized>(self, access: &A) -> easy::Oid<'_, A>;
+
+    /// Return the text of the note attached to this object in `notes_ref`, or in
+    /// [`refs/notes/commits`][easy::note::DEFAULT_REFERENCE] if `None`, using `access` to read it.
+    fn note<A: easy::Access + Sized>(
+        self,
+        notes_ref: Option<PartialNameRef<'_>>,
+        access: &A,
+    ) -> Result<Option<BString>, easy::note::Error>;
 }
 
 impl Sealed for ObjectId {}
@@ -29,4 +38,12 @@ impl ObjectIdExt for ObjectId {
     fn attach<A: easy::Access + Sized>(self, access: &A) -> easy::Oid<'_, A> {
         easy::Oid::from_id(self, access)
     }
+
+    fn note<A: easy::Access + Sized>(
+        self,
+        notes_ref: Option<PartialNameRef<'_>>,
+        access: &A,
+    ) -> Result<Option<BString>, easy::note::Error> {
+        self.attach(access).note(notes_ref)
+    }
 }
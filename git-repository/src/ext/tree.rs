@@ -27,6 +27,27 @@ pub trait TreeIterExt: Sealed {
         R: git_diff::tree::Visit,
         StateMut: BorrowMut<git_diff::tree::State>;
 
+    /// Write a unified patch of all changes needed to turn `self` into `other` into `out`, like `git diff-tree -p --full-index` does.
+    ///
+    /// The changes are computed like in [`changes_needed()`][TreeIterExt::changes_needed()], with `state` and `find_tree` being
+    /// used for the traversal, and `find_blob` being a function to lookup blobs to compare their lines as configured by `options`.
+    ///
+    /// Use [`Repository::patch_options()`][crate::Repository::patch_options()] to skip blobs which are missing in partial clones.
+    #[cfg(feature = "git-diff")]
+    fn diff_to_patch<FindTree, FindBlob, StateMut>(
+        &self,
+        other: TreeRefIter<'_>,
+        state: StateMut,
+        find_tree: FindTree,
+        find_blob: FindBlob,
+        options: git_diff::tree::patch::Options,
+        out: impl std::io::Write,
+    ) -> Result<git_diff::tree::patch::Outcome, git_diff::tree::patch::Error>
+    where
+        FindTree: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<TreeRefIter<'b>>,
+        FindBlob: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<git_object::BlobRef<'b>>,
+        StateMut: BorrowMut<git_diff::tree::State>;
+
     /// Traverse this tree with `state` being provided to potentially reuse allocations, and `find` being a function to lookup trees
     /// and turn them into iterators.
     ///
@@ -63,6 +84,26 @@ impl<'d> TreeIterExt for TreeRefIter<'d> {
         git_diff::tree::Changes::from(Some(self.clone())).needed_to_obtain(other, state, find, delegate)
     }
 
+    #[cfg(feature = "git-diff")]
+    fn diff_to_patch<FindTree, FindBlob, StateMut>(
+        &self,
+        other: TreeRefIter<'_>,
+        state: StateMut,
+        find_tree: FindTree,
+        find_blob: FindBlob,
+        options: git_diff::tree::patch::Options,
+        out: impl std::io::Write,
+    ) -> Result<git_diff::tree::patch::Outcome, git_diff::tree::patch::Error>
+    where
+        FindTree: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<TreeRefIter<'b>>,
+        FindBlob: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<git_object::BlobRef<'b>>,
+        StateMut: BorrowMut<git_diff::tree::State>,
+    {
+        let mut recorder = git_diff::tree::Recorder::default();
+        self.changes_needed(other, state, find_tree, &mut recorder)?;
+        git_diff::tree::patch::write(recorder.records, find_blob, options, out)
+    }
+
     fn traverse<StateMut, Find, V>(
         &self,
         state: StateMut,
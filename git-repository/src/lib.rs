@@ -104,6 +104,10 @@
 //!   * [`transport`][protocol::transport]
 //!     * [`packetline`][protocol::transport::packetline]
 //!
+//! ## With the optional "capi" cargo feature
+//!
+//! The `capi` module provides functions to read from repositories with a stable C interface, as declared in `include/gix.h`.
+//!
 #![deny(missing_docs, unsafe_code, rust_2018_idioms)]
 
 use std::{path::PathBuf, rc::Rc, sync::Arc};
@@ -112,6 +116,7 @@ use std::{path::PathBuf, rc::Rc, sync::Arc};
 // This also means that their major version changes affect our major version, but that's alright as we directly expose their
 // APIs/instances anyway.
 pub use git_actor as actor;
+pub use git_bundle as bundle;
 #[cfg(all(feature = "unstable", feature = "git-diff"))]
 pub use git_diff as diff;
 #[cfg(feature = "unstable")]
@@ -147,7 +152,7 @@ pub mod prelude {
 pub mod path;
 
 mod repository;
-pub use repository::{discover, init, open};
+pub use repository::{commands, discover, init, open, verify, worktree, worktree::Worktree};
 
 /// A repository path which either points to a work tree or the `.git` repository itself.
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -156,6 +161,13 @@ pub enum Path {
     WorkTree(PathBuf),
     /// The git repository itself
     Repository(PathBuf),
+    /// A work tree whose `.git` is a file pointing to the git repository elsewhere, as used by submodules and linked worktrees.
+    LinkedWorkTree {
+        /// The directory containing the `.git` file.
+        work_dir: PathBuf,
+        /// The git directory the `.git` file points to.
+        git_dir: PathBuf,
+    },
 }
 
 /// A instance with access to everything a git repository entails, best imagined as container for _most_ for system resources required
@@ -177,6 +189,28 @@ pub struct Repository {
     /// The path to the worktree at which to find checked out files
     pub work_tree: Option<PathBuf>,
     pub(crate) hash_kind: git_hash::Kind,
+    /// The amount of hexadecimal characters to abbreviate object ids to as configured by `core.abbrev`, or `None` if it's
+    /// derived from the amount of objects.
+    pub(crate) abbrev_config: Option<usize>,
+    /// The amount of hexadecimal characters to abbreviate object ids to before extending them to be unambiguous.
+    pub(crate) abbrev: usize,
+    /// The limits beyond which maintenance is needed, as configured by `gc.auto` and `gc.autoPackLimit`.
+    pub(crate) maintenance_limits: easy::maintenance::Limits,
+    /// The suffixes of pre-release versions when sorting tags by version, as configured by `versionsort.suffix`.
+    pub(crate) version_sort_suffixes: Vec<git_object::bstr::BString>,
+    /// The amount of threads to use for operations that can run in parallel.
+    pub(crate) threads: Threads,
+    /// If true, packs are written such that the same objects always yield the same pack, byte for byte.
+    pub(crate) deterministic_packs: bool,
+    /// How much the configuration of the repository is trusted.
+    pub(crate) trust: open::Trust,
+    /// The programs to run as configured, as far as the repository is trusted.
+    pub(crate) commands: commands::Configured,
+    /// The path to the index file.
+    pub(crate) index_path: PathBuf,
+    /// The unvalidated value of `protocol.version`, if set, to determine the protocol version to request when connecting to remotes.
+    #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
+    pub(crate) protocol_version: Option<bstr::BString>,
     // TODO: git-config should be here - it's read a lot but not written much in must applications, so shouldn't be in `State`.
     //       Probably it's best reload it on signal (in servers) or refresh it when it's known to have been changed similar to how
     //       packs are refreshed. This would be `git_config::fs::Config` when ready.
@@ -248,10 +282,15 @@ pub struct EasyArcExclusive {
 
 pub mod easy;
 
+#[cfg(feature = "capi")]
+pub mod capi;
+
 ///
 pub mod commit;
 ///
 pub mod reference;
+#[cfg(feature = "serde1")]
+pub mod summary;
 
 /// The kind of `Repository`
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -269,6 +308,36 @@ impl Kind {
     }
 }
 
+/// The amount of threads to use for operations that can run in parallel, like verifying or writing packs, as configured with
+/// [`Repository::set_object_access_parallelism()`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum Threads {
+    /// Do all work in the calling thread without spawning any threads, making operations deterministic.
+    None,
+    /// Use as many threads as there are logical cores.
+    Auto,
+    /// Use up to the given amount of threads, with `0` meaning as many as there are logical cores.
+    Fixed(usize),
+}
+
+impl Default for Threads {
+    fn default() -> Self {
+        Threads::Auto
+    }
+}
+
+impl Threads {
+    /// Return the thread limit to pass to operations of the `git-pack` and `git-odb` crates.
+    pub fn thread_limit(&self) -> Option<usize> {
+        match self {
+            Threads::None => Some(1),
+            Threads::Auto => None,
+            Threads::Fixed(num_threads) => Some(*num_threads),
+        }
+    }
+}
+
 /// See [Repository::discover()].
 pub fn discover(directory: impl AsRef<std::path::Path>) -> Result<Repository, repository::discover::Error> {
     Repository::discover(directory)
@@ -288,3 +357,11 @@ pub fn init_bare(directory: impl AsRef<std::path::Path>) -> Result<Repository, r
 pub fn open(directory: impl Into<std::path::PathBuf>) -> Result<Repository, repository::open::Error> {
     Repository::open(directory)
 }
+
+/// See [Repository::open_opts()].
+pub fn open_opts(
+    directory: impl Into<std::path::PathBuf>,
+    options: open::Options,
+) -> Result<Repository, repository::open::Error> {
+    Repository::open_opts(directory, options)
+}
@@ -23,6 +23,7 @@ pub mod existing {
 
 /// Find the location of the git repository directly in `directory` or in any of its parent directories.
 ///
+/// `.git` files as used by submodules and linked worktrees are followed to the git directory they point to.
 /// Fail if no valid-looking git repository could be found.
 pub fn existing(directory: impl AsRef<Path>) -> Result<crate::Path, existing::Error> {
     // Canonicalize the path so that `Path::parent` _actually_ gives
@@ -44,7 +45,16 @@ pub fn existing(directory: impl AsRef<Path>) -> Result<crate::Path, existing::Er
             break Ok(crate::Path::from_dot_git_dir(cursor, kind));
         }
         let git_dir = cursor.join(".git");
-        if let Ok(kind) = path::is::git(&git_dir) {
+        if git_dir.is_file() {
+            if let Ok(git_dir) = path::from_gitdir_file(&git_dir) {
+                if path::is::git(&git_dir).is_ok() {
+                    break Ok(crate::Path::LinkedWorkTree {
+                        work_dir: cursor.to_owned(),
+                        git_dir,
+                    });
+                }
+            }
+        } else if let Ok(kind) = path::is::git(&git_dir) {
             break Ok(crate::Path::from_dot_git_dir(git_dir, kind));
         }
         match cursor.parent() {
@@ -13,6 +13,8 @@ pub enum Error {
     MissingObjectsDirectory { missing: PathBuf },
     #[error("Expected a refs directory at '{}'", .missing.display())]
     MissingRefsDirectory { missing: PathBuf },
+    #[error("Could not read the common directory of a linked worktree from '{}'", .path.display())]
+    CommonDir { path: PathBuf, source: std::io::Error },
 }
 
 /// Returns true if the given `git_dir` seems to be a bare repository.
@@ -26,13 +28,25 @@ pub fn bare(git_dir: impl AsRef<Path>) -> bool {
 /// purely based on the presence of files. Note that the git-config ultimately decides what's bare.
 ///
 /// * [x] a valid head
-/// * [ ] git common directory
+/// * [x] git common directory, as used by linked worktrees
 ///   * [ ] respect GIT_COMMON_DIR
 /// * [x] an objects directory
 ///   * [x] respect GIT_OBJECT_DIRECTORY
 /// * [x] a refs directory
 pub fn git(git_dir: impl AsRef<Path>) -> Result<crate::Kind, Error> {
-    let dot_git = git_dir.as_ref();
+    git_with_objects_dir(
+        git_dir.as_ref(),
+        std::env::var_os("GIT_OBJECT_DIRECTORY").map(PathBuf::from),
+    )
+}
+
+/// Like [`git()`], but expect objects in `objects_dir` if set instead of the `objects` directory of the repository.
+pub(crate) fn git_with_objects_dir(dot_git: &Path, objects_dir: Option<PathBuf>) -> Result<crate::Kind, Error> {
+    let common_dir = crate::path::common_dir(dot_git).map_err(|source| Error::CommonDir {
+        path: dot_git.join("commondir"),
+        source,
+    })?;
+    let common_dir = common_dir.as_deref().unwrap_or(dot_git);
 
     {
         let refs = git_ref::file::Store::at(&dot_git, Default::default());
@@ -45,21 +59,19 @@ pub fn git(git_dir: impl AsRef<Path>) -> Result<crate::Kind, Error> {
     }
 
     {
-        let objects_path = std::env::var("GIT_OBJECT_DIRECTORY")
-            .map(PathBuf::from)
-            .unwrap_or_else(|_| dot_git.join("objects"));
+        let objects_path = objects_dir.unwrap_or_else(|| common_dir.join("objects"));
         if !objects_path.is_dir() {
             return Err(Error::MissingObjectsDirectory { missing: objects_path });
         }
     }
     {
-        let refs_path = dot_git.join("refs");
+        let refs_path = common_dir.join("refs");
         if !refs_path.is_dir() {
             return Err(Error::MissingRefsDirectory { missing: refs_path });
         }
     }
 
-    Ok(if bare(git_dir) {
+    Ok(if bare(dot_git) {
         crate::Kind::Bare
     } else {
         crate::Kind::WorkTree
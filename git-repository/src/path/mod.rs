@@ -1,16 +1,87 @@
 use std::path::PathBuf;
 
-use crate::{Kind, Path};
+use crate::{bstr::ByteSlice, Kind, Path};
 
 ///
 pub mod create;
 pub mod discover;
 pub mod is;
 
+///
+pub mod from_gitdir_file {
+    use std::path::PathBuf;
+
+    /// The amount of `.git` files that are followed if one points to another one, before giving up.
+    pub const MAX_DEPTH: usize = 5;
+
+    /// The error returned by [`from_gitdir_file()`][super::from_gitdir_file()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read the git directory file at '{}'", .path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+        #[error("The file at '{}' does not start with 'gitdir: '", .path.display())]
+        Malformed { path: PathBuf },
+        #[error("Gave up following git directory files at '{}' after {} indirections", .path.display(), MAX_DEPTH)]
+        TooDeep { path: PathBuf },
+    }
+}
+
+/// Read the `.git` file at `path`, as used by submodules and linked worktrees, and return the git directory it points to,
+/// with relative paths being relative to the directory containing the file.
+///
+/// If the git directory is a `.git` file itself, it is followed as well up to [`MAX_DEPTH`][from_gitdir_file::MAX_DEPTH] times.
+pub fn from_gitdir_file(path: impl AsRef<std::path::Path>) -> Result<PathBuf, from_gitdir_file::Error> {
+    use from_gitdir_file::Error;
+    let mut path = path.as_ref().to_owned();
+    for _ in 0..from_gitdir_file::MAX_DEPTH {
+        let content = std::fs::read(&path).map_err(|source| Error::Io {
+            path: path.clone(),
+            source,
+        })?;
+        let git_dir = content
+            .strip_prefix(b"gitdir: ")
+            .map(|dir| dir.trim_end_with(|c| c == '\n' || c == '\r'))
+            .filter(|dir| !dir.is_empty())
+            .ok_or_else(|| Error::Malformed { path: path.clone() })?;
+        let git_dir = git_dir.to_path().map_err(|_| Error::Malformed { path: path.clone() })?;
+        let git_dir = path
+            .parent()
+            .map_or_else(|| git_dir.to_owned(), |dir| dir.join(git_dir));
+        if !git_dir.is_file() {
+            return Ok(realpath(git_dir));
+        }
+        path = git_dir;
+    }
+    Err(Error::TooDeep { path })
+}
+
+/// Return the common directory shared by all worktrees if `git_dir` belongs to a linked worktree, as indicated by
+/// the `commondir` file in it.
+pub(crate) fn common_dir(git_dir: &std::path::Path) -> std::io::Result<Option<PathBuf>> {
+    match std::fs::read(git_dir.join("commondir")) {
+        Ok(content) => {
+            let dir = content.trim_end_with(|c| c == '\n' || c == '\r');
+            let dir = dir
+                .to_path()
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+            Ok(Some(realpath(git_dir.join(dir))))
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Resolve relative components of `path` like `git` does, but keep it as is if it doesn't exist.
+pub(crate) fn realpath(path: PathBuf) -> PathBuf {
+    path.canonicalize().unwrap_or(path)
+}
+
 impl AsRef<std::path::Path> for Path {
     fn as_ref(&self) -> &std::path::Path {
         match self {
             Path::WorkTree(path) | Path::Repository(path) => path,
+            Path::LinkedWorkTree { work_dir, .. } => work_dir,
         }
     }
 }
@@ -28,7 +99,7 @@ impl Path {
     /// Returns the [kind][Kind] of this repository path.
     pub fn kind(&self) -> Kind {
         match self {
-            Path::WorkTree(_) => Kind::WorkTree,
+            Path::WorkTree(_) | Path::LinkedWorkTree { .. } => Kind::WorkTree,
             Path::Repository(_) => Kind::Bare,
         }
     }
@@ -37,6 +108,7 @@ impl Path {
     pub fn into_repository_and_work_tree_directories(self) -> (PathBuf, Option<PathBuf>) {
         match self {
             crate::Path::WorkTree(working_tree) => (working_tree.join(".git"), Some(working_tree)),
+            crate::Path::LinkedWorkTree { work_dir, git_dir } => (git_dir, Some(work_dir)),
             crate::Path::Repository(repository) => (repository, None),
         }
     }
@@ -1,2 +1,4 @@
 ///
 pub mod log;
+///
+pub mod update_ref;
@@ -0,0 +1,99 @@
+use std::ops::Deref;
+
+use git_hash::ObjectId;
+use git_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use crate::{
+    easy,
+    easy::ext::ReferenceAccessExt,
+    reference::update_ref::{Outcome, Transaction, Verification},
+};
+
+/// The error returned by [`apply()`][crate::reference::update_ref::apply()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Edit(#[from] easy::reference::edit::Error),
+}
+
+pub(crate) fn transaction<A>(
+    access: &A,
+    transaction: Transaction,
+    lock_mode: git_lock::acquire::Fail,
+    log_committer: Option<&git_actor::Signature>,
+) -> Result<Vec<RefEdit>, Error>
+where
+    A: easy::Access + Sized,
+{
+    let Transaction {
+        mut edits,
+        verifications,
+        prepared,
+        outcome,
+    } = transaction;
+    if outcome == Outcome::Abort && !prepared {
+        return Ok(Vec::new());
+    }
+    // Verifications are checked by the reference transaction while it holds the lock of each reference, so their
+    // references can't change in between.
+    edits.extend(verifications.into_iter().map(into_edit));
+    match outcome {
+        Outcome::Commit => Ok(access
+            .edit_references(edits, lock_mode, log_committer)?
+            .into_iter()
+            .filter(|edit| !is_verification(edit))
+            .collect()),
+        Outcome::Abort => {
+            // Preparing locks and checks all references, dropping the prepared transaction rolls it back.
+            access
+                .repo()
+                .map_err(easy::reference::edit::Error::from)?
+                .deref()
+                .refs
+                .transaction()
+                .prepare(edits, lock_mode)
+                .map_err(easy::reference::edit::Error::from)?;
+            Ok(Vec::new())
+        }
+    }
+}
+
+/// Turn `verification` into an edit which only checks the expected value and keeps the reference as is, without writing
+/// it or its reference log.
+fn into_edit(verification: Verification) -> RefEdit {
+    let (expected, new) = match verification.expected {
+        Some(id) => (PreviousValue::MustExistAndMatch(Target::Peeled(id)), id),
+        None => (PreviousValue::MustNotExist, ObjectId::null_sha1()),
+    };
+    RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::Only,
+                force_create_reflog: false,
+                message: Default::default(),
+            },
+            expected,
+            new: Target::Peeled(new),
+        },
+        name: verification.name,
+        deref: verification.deref,
+    }
+}
+
+fn is_verification(edit: &RefEdit) -> bool {
+    match &edit.change {
+        Change::Update {
+            log,
+            expected,
+            new: Target::Peeled(new),
+        } => {
+            log.mode == RefLog::Only
+                && (new.is_null() || *expected == PreviousValue::MustExistAndMatch(Target::Peeled(*new)))
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,97 @@
+//! Support for the command language of `git update-ref --stdin`, to describe reference transactions.
+//!
+//! Input is first [parsed][parse()] into [`Transaction`]s which can then be [applied][apply()] to a repository.
+//! Values must be full hexadecimal object ids, other ways to name a revision are not supported.
+use git_hash::ObjectId;
+use git_ref::{transaction::RefEdit, FullName};
+
+use crate::bstr::BString;
+
+///
+pub mod apply;
+///
+pub mod parse;
+
+/// Options to control how commands are interpreted, like the respective flags of `git update-ref --stdin`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// If true, commands and their arguments are terminated by NUL instead of being separated by space and terminated
+    /// by newline, like `-z`.
+    pub nul_terminated: bool,
+    /// If false, symbolic references are changed themselves instead of the reference they point to, like `--no-deref`.
+    pub deref: bool,
+    /// The message to put into the reference log of all updated references, like `-m`.
+    pub log_message: BString,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            nul_terminated: false,
+            deref: true,
+            log_message: Default::default(),
+        }
+    }
+}
+
+/// The way a [`Transaction`] is ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Outcome {
+    /// Make all changes permanent, either due to a `commit` command or because input ended without the transaction being
+    /// started explicitly.
+    Commit,
+    /// Discard all changes, either due to an `abort` command or because input ended after a `start` command.
+    Abort,
+}
+
+/// A check for the current value of a reference that must pass for a [`Transaction`] to be committed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Verification {
+    /// The name of the reference to check.
+    pub name: FullName,
+    /// The id the reference must point to, or `None` if the reference must not exist.
+    pub expected: Option<ObjectId>,
+    /// If true, the value of the reference a symbolic reference points to is checked.
+    pub deref: bool,
+}
+
+/// A set of changes to references to be made all at once, or not at all.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Transaction {
+    /// All reference changes in the order of their commands.
+    pub edits: Vec<RefEdit>,
+    /// All checks to perform before applying `edits`, in the order of their commands.
+    pub verifications: Vec<Verification>,
+    /// If true, a `prepare` command was encountered which locks and checks all references even if the transaction is aborted.
+    pub prepared: bool,
+    /// How the transaction ends.
+    pub outcome: Outcome,
+}
+
+/// Parse `input` as interpreted according to `options` into one or more transactions.
+///
+/// Transactions are separated by `commit` or `abort` commands, and without an explicit `start` all commands end up in a single
+/// transaction which is committed at the end of the input.
+pub fn parse(input: &[u8], options: &Options) -> Result<Vec<Transaction>, parse::Error> {
+    parse::Parser::new(input, options).transactions()
+}
+
+/// Apply all `transactions` to the repository accessible through `access`, with `lock_mode` deciding on how to handle competing
+/// transactions and `log_committer` being the name appearing in reference logs, or the configured committer if `None`.
+///
+/// Returns the performed edits of each transaction, which are empty if it was aborted. The first failing transaction stops
+/// the operation, leaving previous transactions committed.
+pub fn apply<A>(
+    access: &A,
+    transactions: impl IntoIterator<Item = Transaction>,
+    lock_mode: git_lock::acquire::Fail,
+    log_committer: Option<&git_actor::Signature>,
+) -> Result<Vec<Vec<RefEdit>>, apply::Error>
+where
+    A: crate::easy::Access + Sized,
+{
+    transactions
+        .into_iter()
+        .map(|transaction| apply::transaction(access, transaction, lock_mode, log_committer))
+        .collect()
+}
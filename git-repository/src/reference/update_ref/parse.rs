@@ -0,0 +1,438 @@
+use std::convert::TryFrom;
+
+use git_hash::ObjectId;
+use git_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+use crate::{
+    bstr::{BString, ByteSlice},
+    reference::update_ref::{Options, Outcome, Transaction, Verification},
+};
+
+/// The error returned by [`parse()`][crate::reference::update_ref::parse()].
+///
+/// The `line` is the 1-based number of the line containing the command, or of the command itself if commands are NUL terminated.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Line {line}: unknown command {command:?}")]
+    UnknownCommand { line: usize, command: BString },
+    #[error("Line {line}: {command}: unknown option {option:?}")]
+    UnknownOption {
+        line: usize,
+        command: &'static str,
+        option: BString,
+    },
+    #[error("Line {line}: {command}: {message}")]
+    Syntax {
+        line: usize,
+        command: &'static str,
+        message: &'static str,
+    },
+    #[error("Line {line}: {command}: invalid reference name {name:?}")]
+    RefName {
+        line: usize,
+        command: &'static str,
+        name: BString,
+        source: git_validate::refname::Error,
+    },
+    #[error("Line {line}: {command}: invalid {field} {value:?}")]
+    ObjectId {
+        line: usize,
+        command: &'static str,
+        field: &'static str,
+        value: BString,
+    },
+    #[error("Line {line}: {command}: {message}")]
+    State {
+        line: usize,
+        command: &'static str,
+        message: &'static str,
+    },
+}
+
+/// The state of the transaction in progress, ordered so that commands can only advance it unless it is closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum State {
+    Open,
+    Started,
+    Prepared,
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Update,
+    Create,
+    Delete,
+    Verify,
+    Option,
+    Start,
+    Prepare,
+    Commit,
+    Abort,
+}
+
+impl Command {
+    fn from_bytes(name: &[u8]) -> Option<Self> {
+        Some(match name {
+            b"update" => Command::Update,
+            b"create" => Command::Create,
+            b"delete" => Command::Delete,
+            b"verify" => Command::Verify,
+            b"option" => Command::Option,
+            b"start" => Command::Start,
+            b"prepare" => Command::Prepare,
+            b"commit" => Command::Commit,
+            b"abort" => Command::Abort,
+            _ => return None,
+        })
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Command::Update => "update",
+            Command::Create => "create",
+            Command::Delete => "delete",
+            Command::Verify => "verify",
+            Command::Option => "option",
+            Command::Start => "start",
+            Command::Prepare => "prepare",
+            Command::Commit => "commit",
+            Command::Abort => "abort",
+        }
+    }
+
+    /// The state the transaction is in after this command.
+    fn state(&self) -> State {
+        match self {
+            Command::Update | Command::Create | Command::Delete | Command::Verify | Command::Option => State::Open,
+            Command::Start => State::Started,
+            Command::Prepare => State::Prepared,
+            Command::Commit | Command::Abort => State::Closed,
+        }
+    }
+
+    /// The amount of values following the reference name, with all of them being mandatory if commands are NUL terminated.
+    fn num_values(&self) -> std::ops::RangeInclusive<usize> {
+        match self {
+            Command::Update => 1..=2,
+            Command::Create => 1..=1,
+            Command::Delete | Command::Verify => 0..=1,
+            Command::Option | Command::Start | Command::Prepare | Command::Commit | Command::Abort => 0..=0,
+        }
+    }
+}
+
+/// A reference name along with its values, with `None` for each value that is missing.
+type NameAndValues<'a> = (&'a [u8], Vec<Option<&'a [u8]>>);
+
+pub(crate) struct Parser<'a> {
+    input: &'a [u8],
+    options: &'a Options,
+    line: usize,
+    state: State,
+    no_deref_once: bool,
+    current: Transaction,
+    out: Vec<Transaction>,
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(input: &'a [u8], options: &'a Options) -> Self {
+        Parser {
+            input,
+            options,
+            line: 0,
+            state: State::Open,
+            no_deref_once: false,
+            current: empty_transaction(),
+            out: Vec::new(),
+        }
+    }
+
+    pub(crate) fn transactions(mut self) -> Result<Vec<Transaction>, Error> {
+        while let Some(record) = self.next_record() {
+            self.line += 1;
+            let (name, rest) = match record.find_byte(b' ') {
+                Some(pos) => (&record[..pos], Some(&record[pos + 1..])),
+                None => (record, None),
+            };
+            let command = match Command::from_bytes(name) {
+                Some(command) => command,
+                None if name.is_empty() => {
+                    return Err(Error::Syntax {
+                        line: self.line,
+                        command: "",
+                        message: "empty command or whitespace before command",
+                    })
+                }
+                None => {
+                    return Err(Error::UnknownCommand {
+                        line: self.line,
+                        command: name.into(),
+                    })
+                }
+            };
+            self.advance_state(command)?;
+            self.execute(command, rest)?;
+        }
+
+        match self.state {
+            State::Open => {
+                // Commit by default if no transaction was requested.
+                if !self.current.edits.is_empty() || !self.current.verifications.is_empty() {
+                    self.finish(Outcome::Commit);
+                }
+            }
+            State::Started | State::Prepared => self.finish(Outcome::Abort),
+            State::Closed => {}
+        }
+        Ok(self.out)
+    }
+
+    fn next_record(&mut self) -> Option<&'a [u8]> {
+        if self.input.is_empty() {
+            return None;
+        }
+        let terminator = if self.options.nul_terminated { 0 } else { b'\n' };
+        let (record, rest) = match self.input.find_byte(terminator) {
+            Some(pos) => (&self.input[..pos], &self.input[pos + 1..]),
+            None => (self.input, &[][..]),
+        };
+        self.input = rest;
+        Some(record)
+    }
+
+    fn advance_state(&mut self, command: Command) -> Result<(), Error> {
+        let next = command.state();
+        let error = |message| Error::State {
+            line: self.line,
+            command: command.as_str(),
+            message,
+        };
+        match self.state {
+            State::Open | State::Started => {
+                if self.state == State::Started && next == State::Started {
+                    return Err(error("cannot restart ongoing transaction"));
+                }
+                // Do not downgrade a transaction to a non-transaction.
+                if next >= self.state {
+                    self.state = next;
+                }
+            }
+            State::Prepared => {
+                if next != State::Closed {
+                    return Err(error("prepared transactions can only be closed"));
+                }
+                self.state = next;
+            }
+            State::Closed => {
+                if next != State::Started {
+                    return Err(error("transaction is closed"));
+                }
+                self.state = next;
+            }
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, command: Command, rest: Option<&'a [u8]>) -> Result<(), Error> {
+        let syntax = |line, message| Error::Syntax {
+            line,
+            command: command.as_str(),
+            message,
+        };
+        match command {
+            Command::Start | Command::Prepare | Command::Commit | Command::Abort => {
+                if rest.is_some() {
+                    return Err(syntax(self.line, "extra input"));
+                }
+                match command {
+                    Command::Prepare => self.current.prepared = true,
+                    Command::Commit => self.finish(Outcome::Commit),
+                    Command::Abort => self.finish(Outcome::Abort),
+                    _ => {}
+                }
+                Ok(())
+            }
+            Command::Option => match rest {
+                Some(b"no-deref") => {
+                    self.no_deref_once = true;
+                    Ok(())
+                }
+                Some(option) => Err(Error::UnknownOption {
+                    line: self.line,
+                    command: command.as_str(),
+                    option: option.into(),
+                }),
+                None => Err(syntax(self.line, "missing option")),
+            },
+            Command::Update | Command::Create | Command::Delete | Command::Verify => {
+                let rest = rest.ok_or_else(|| syntax(self.line, "missing <ref>"))?;
+                let (name, values) = self.name_and_values(command, rest)?;
+                let name = FullName::try_from(name.as_bstr()).map_err(|err| Error::RefName {
+                    line: self.line,
+                    command: command.as_str(),
+                    name: name.into(),
+                    source: err,
+                })?;
+                let deref = self.options.deref && !std::mem::take(&mut self.no_deref_once);
+                self.push_ref_command(command, name, deref, values)
+            }
+        }
+    }
+
+    fn name_and_values(&mut self, command: Command, rest: &'a [u8]) -> Result<NameAndValues<'a>, Error> {
+        let num_values = command.num_values();
+        let syntax = |line, message| Error::Syntax {
+            line,
+            command: command.as_str(),
+            message,
+        };
+        if self.options.nul_terminated {
+            let mut values = Vec::with_capacity(*num_values.end());
+            for _ in 0..*num_values.end() {
+                let value = self
+                    .next_record()
+                    .ok_or_else(|| syntax(self.line, "unexpected end of input"))?;
+                values.push(if value.is_empty() { None } else { Some(value) });
+            }
+            Ok((rest, values))
+        } else {
+            let mut tokens = rest.split(|b| *b == b' ');
+            let name = tokens.next().expect("split yields at least one item");
+            let values: Vec<_> = tokens.map(Some).collect();
+            if values.len() > *num_values.end() {
+                return Err(syntax(self.line, "extra input"));
+            }
+            if values.iter().any(|v| matches!(v, Some(v) if v.is_empty())) {
+                return Err(syntax(self.line, "unexpected whitespace"));
+            }
+            Ok((name, values))
+        }
+    }
+
+    fn push_ref_command(
+        &mut self,
+        command: Command,
+        name: FullName,
+        deref: bool,
+        values: Vec<Option<&'a [u8]>>,
+    ) -> Result<(), Error> {
+        let mut values = values.into_iter();
+        let mut next_id = |field: &'static str| -> Result<Option<ObjectId>, Error> {
+            values
+                .next()
+                .flatten()
+                .map(|value| {
+                    ObjectId::from_hex(value).map_err(|_| Error::ObjectId {
+                        line: self.line,
+                        command: command.as_str(),
+                        field,
+                        value: value.into(),
+                    })
+                })
+                .transpose()
+        };
+        let syntax = |message| Error::Syntax {
+            line: self.line,
+            command: command.as_str(),
+            message,
+        };
+        let log = LogChange {
+            mode: RefLog::AndReference,
+            force_create_reflog: false,
+            message: self.options.log_message.clone(),
+        };
+
+        let change = match command {
+            Command::Update => {
+                let new = match next_id("<newvalue>")? {
+                    Some(id) => id,
+                    None if self.options.nul_terminated => ObjectId::null_sha1(),
+                    None => return Err(syntax("missing <newvalue>")),
+                };
+                let old = next_id("<oldvalue>")?;
+                match (new.is_null(), old) {
+                    (true, Some(old)) if old.is_null() => {
+                        self.current.verifications.push(Verification {
+                            name,
+                            expected: None,
+                            deref,
+                        });
+                        return Ok(());
+                    }
+                    (true, old) => Change::Delete {
+                        expected: old.map_or(PreviousValue::Any, |old| {
+                            PreviousValue::MustExistAndMatch(Target::Peeled(old))
+                        }),
+                        log: RefLog::AndReference,
+                    },
+                    (false, old) => Change::Update {
+                        log,
+                        expected: match old {
+                            None => PreviousValue::Any,
+                            Some(old) if old.is_null() => PreviousValue::MustNotExist,
+                            Some(old) => PreviousValue::MustExistAndMatch(Target::Peeled(old)),
+                        },
+                        new: Target::Peeled(new),
+                    },
+                }
+            }
+            Command::Create => {
+                let new = next_id("<newvalue>")?.ok_or_else(|| syntax("missing <newvalue>"))?;
+                if new.is_null() {
+                    return Err(syntax("zero <newvalue>"));
+                }
+                Change::Update {
+                    log,
+                    expected: PreviousValue::MustNotExist,
+                    new: Target::Peeled(new),
+                }
+            }
+            Command::Delete => {
+                let old = next_id("<oldvalue>")?;
+                if matches!(old, Some(old) if old.is_null()) {
+                    return Err(syntax("zero <oldvalue>"));
+                }
+                Change::Delete {
+                    expected: old.map_or(PreviousValue::Any, |old| {
+                        PreviousValue::MustExistAndMatch(Target::Peeled(old))
+                    }),
+                    log: RefLog::AndReference,
+                }
+            }
+            Command::Verify => {
+                let old = next_id("<oldvalue>")?;
+                self.current.verifications.push(Verification {
+                    name,
+                    expected: old.filter(|old| !old.is_null()),
+                    deref,
+                });
+                return Ok(());
+            }
+            Command::Option | Command::Start | Command::Prepare | Command::Commit | Command::Abort => {
+                unreachable!("BUG: only called for commands changing references")
+            }
+        };
+        self.current.edits.push(RefEdit { change, name, deref });
+        Ok(())
+    }
+
+    fn finish(&mut self, outcome: Outcome) {
+        let mut transaction = std::mem::replace(&mut self.current, empty_transaction());
+        transaction.outcome = outcome;
+        self.out.push(transaction);
+    }
+}
+
+fn empty_transaction() -> Transaction {
+    Transaction {
+        edits: Vec::new(),
+        verifications: Vec::new(),
+        prepared: false,
+        outcome: Outcome::Commit,
+    }
+}
@@ -12,6 +12,139 @@ mod access {
     }
 }
 
+mod incomplete {
+    use crate::Repository;
+
+    impl Repository {
+        /// Return true if this repository is a shallow clone, whose history ends at the commits listed in `<git-dir>/shallow`
+        /// as their parents are missing.
+        pub fn is_shallow(&self) -> bool {
+            std::fs::metadata(self.git_dir().join("shallow")).map_or(false, |m| m.is_file() && m.len() != 0)
+        }
+
+        /// Return true if this repository is a partial clone, which was cloned with a filter like `--filter=blob:none`,
+        /// and thus may lack objects that are referenced by promisor packs.
+        pub fn is_partial(&self) -> bool {
+            std::fs::read_dir(self.objects_dir().join("pack")).map_or(false, |entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .any(|entry| entry.path().extension().map_or(false, |ext| ext == "promisor"))
+            })
+        }
+
+        /// Return the options to [write patches][crate::prelude::TreeIterExt::diff_to_patch()] with, which skip
+        /// missing blobs if this [is a partial clone][Repository::is_partial()], and fail on them otherwise.
+        #[cfg(feature = "git-diff")]
+        pub fn patch_options(&self) -> git_diff::tree::patch::Options {
+            git_diff::tree::patch::Options {
+                missing_blobs: if self.is_partial() {
+                    git_diff::tree::patch::Missing::Skip
+                } else {
+                    git_diff::tree::patch::Missing::Error
+                },
+                ..Default::default()
+            }
+        }
+    }
+}
+
+pub(crate) mod abbrev {
+    use std::borrow::Cow;
+
+    use crate::{easy::oid::shorten, Repository};
+
+    impl Repository {
+        /// Recompute the amount of hexadecimal characters to abbreviate object ids to after the object database changed,
+        /// unless it is configured explicitly.
+        pub(crate) fn update_abbreviation(&mut self) {
+            self.abbrev = self
+                .abbrev_config
+                .unwrap_or_else(|| from_object_count(&self.odb, self.hash_kind));
+        }
+    }
+
+    /// Parse `core.abbrev` from `config`, returning `None` if it's unset or `auto`, or the invalid value as error.
+    pub(crate) fn from_config(
+        config: &git_config::file::GitConfig<'_>,
+        hash_kind: git_hash::Kind,
+    ) -> Result<Option<usize>, crate::bstr::BString> {
+        let value = match config.value::<Cow<'_, [u8]>>("core", None, "abbrev") {
+            Ok(value) => value,
+            Err(_) => return Ok(None),
+        };
+        let value = value.as_ref();
+        let hex_len = hash_kind.len_in_hex();
+        if value.eq_ignore_ascii_case(b"auto") {
+            Ok(None)
+        } else if [&b"false"[..], b"no", b"off"]
+            .iter()
+            .any(|no| value.eq_ignore_ascii_case(no))
+        {
+            Ok(Some(hex_len))
+        } else {
+            match std::str::from_utf8(value)
+                .ok()
+                .and_then(|v| v.trim().parse::<usize>().ok())
+            {
+                Some(len) if (shorten::MIN_HEX_LEN..=hex_len).contains(&len) => Ok(Some(len)),
+                _ => Err(value.into()),
+            }
+        }
+    }
+
+    /// Like `git`, expect a collision among `2^n` objects at about `2^(n/2)` of them, and use enough hexadecimal characters
+    /// to cover these bits, but never less than [`shorten::DEFAULT_HEX_LEN`]. Only packed objects are counted.
+    pub(crate) fn from_object_count(odb: &git_odb::linked::Store, hash_kind: git_hash::Kind) -> usize {
+        let num_objects: u64 = odb
+            .dbs
+            .iter()
+            .flat_map(|db| db.bundles.iter())
+            .map(|bundle| bundle.index.num_objects() as u64)
+            .sum();
+        let num_bits = 64 - num_objects.leading_zeros() as usize;
+        (num_bits / 2 + num_bits % 2)
+            .max(shorten::DEFAULT_HEX_LEN)
+            .min(hash_kind.len_in_hex())
+    }
+}
+
+///
+pub mod verify {
+    use std::sync::{atomic::AtomicBool, Arc};
+
+    use git_features::progress::Progress;
+    pub use git_odb::{
+        compound::verify::{Error, Outcome},
+        verify::{Mode, Statistics},
+    };
+
+    use crate::Repository;
+
+    impl Repository {
+        /// Verify the integrity of all packed and loose objects, including those of alternate object databases,
+        /// using up to `thread_limit` threads and reporting to `progress`.
+        ///
+        /// If `thread_limit` is `None`, the [configured parallelism][Repository::object_access_parallelism()] is used, and
+        /// no threads are spawned if it's [`Threads::None`][crate::Threads::None] no matter the `thread_limit`.
+        ///
+        /// See [`git_odb::compound::Store::verify_integrity()`] for details on what is checked in each `mode`.
+        pub fn verify_integrity(
+            &self,
+            mode: Mode,
+            thread_limit: Option<usize>,
+            progress: impl Progress,
+            should_interrupt: Arc<AtomicBool>,
+        ) -> Result<Outcome, Error> {
+            let thread_limit = match self.threads {
+                crate::Threads::None => self.threads.thread_limit(),
+                _ => thread_limit.or_else(|| self.threads.thread_limit()),
+            };
+            self.odb
+                .verify_integrity(mode, thread_limit, progress, should_interrupt)
+        }
+    }
+}
+
 mod from_path {
     use std::convert::TryFrom;
 
@@ -22,18 +155,21 @@ mod from_path {
 
         fn try_from(value: Path) -> Result<Self, Self::Error> {
             let (git_dir, worktree_dir) = value.into_repository_and_work_tree_directories();
-            crate::Repository::open_from_paths(git_dir, worktree_dir)
+            crate::Repository::open_from_paths(git_dir, worktree_dir, Default::default())
         }
     }
 }
 
 ///
 pub mod open {
-    use std::{borrow::Cow, path::PathBuf};
+    use std::{
+        borrow::Cow,
+        path::{Path, PathBuf},
+    };
 
     use git_config::values::{Boolean, Integer};
 
-    use crate::Repository;
+    use crate::{bstr::ByteSlice, Repository};
 
     /// The error returned by [`Repository::open()`].
     #[derive(Debug, thiserror::Error)]
@@ -47,29 +183,321 @@ pub mod open {
         ObjectStoreInitialization(#[from] git_odb::linked::init::Error),
         #[error("Cannot handle objects formatted as {:?}", .name)]
         UnsupportedObjectFormat { name: crate::bstr::BString },
+        #[error(transparent)]
+        RefsStructure(#[from] git_ref::file::structure::Error),
+        #[error("core.abbrev must be 'auto', 'false' or a number between 4 and the length of a hexadecimal object id, got {:?}", .value)]
+        InvalidAbbreviation { value: crate::bstr::BString },
+        #[error("core.worktree must be a valid path, got {:?}", .value)]
+        InvalidWorktree { value: crate::bstr::BString },
+        #[error(transparent)]
+        GitDirFile(#[from] crate::path::from_gitdir_file::Error),
+        #[error("Could not read '{}'", .path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+    }
+
+    /// How much the configuration of a repository is trusted, which decides if configuration values that make `git` run
+    /// programs take effect.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Trust {
+        /// All configuration values are used, which is the case for repositories owned by the current user.
+        Full,
+        /// Configuration values which run programs, namely `core.hooksPath`, `core.fsmonitor` and the commands of
+        /// `filter.<driver>`, are ignored, which is the case for repositories owned by other users.
+        Reduced,
+    }
+
+    impl Trust {
+        /// Trust [fully][Trust::Full] if all `paths` are owned by the current user, or [partially][Trust::Reduced] otherwise.
+        ///
+        /// Ownership isn't checked on windows, where repositories are always trusted fully.
+        pub fn from_ownership<'a>(paths: impl IntoIterator<Item = &'a Path>) -> Self {
+            if paths.into_iter().all(is_owned_by_current_user) {
+                Trust::Full
+            } else {
+                Trust::Reduced
+            }
+        }
+    }
+
+    #[cfg(not(windows))]
+    fn is_owned_by_current_user(path: &Path) -> bool {
+        use std::os::unix::fs::MetadataExt;
+        // SAFETY: this function always succeeds and has no side-effects.
+        #[allow(unsafe_code)]
+        let uid = unsafe { libc::geteuid() };
+        std::fs::metadata(path).map_or(false, |meta| meta.uid() == uid)
+    }
+
+    #[cfg(windows)]
+    fn is_owned_by_current_user(_path: &Path) -> bool {
+        true
+    }
+
+    /// The environment variables which take precedence over the paths and the configuration of a repository when opening it,
+    /// like they do in `git`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct EnvironmentOverrides {
+        /// If true, the repository in `GIT_DIR` is opened instead of the given one, without searching its parent directories.
+        pub git_dir: bool,
+        /// If true, `GIT_WORK_TREE` is the work tree of the repository, even if `core.worktree` is configured.
+        pub work_tree: bool,
+        /// If true, objects are stored in `GIT_OBJECT_DIRECTORY` instead of the `objects` directory of the repository.
+        pub object_dir: bool,
+        /// If true, `GIT_INDEX_FILE` is the [index file][Repository::index_path()] instead of the `index` in the repository.
+        pub index_file: bool,
+    }
+
+    impl EnvironmentOverrides {
+        /// Honor all environment variables, like `git` does.
+        pub fn all() -> Self {
+            EnvironmentOverrides {
+                git_dir: true,
+                work_tree: true,
+                object_dir: true,
+                index_file: true,
+            }
+        }
+
+        /// Ignore all environment variables.
+        pub fn none() -> Self {
+            EnvironmentOverrides {
+                git_dir: false,
+                work_tree: false,
+                object_dir: false,
+                index_file: false,
+            }
+        }
+
+        fn path(enabled: bool, name: &str) -> Option<PathBuf> {
+            if enabled {
+                std::env::var_os(name)
+                    .filter(|value| !value.is_empty())
+                    .map(PathBuf::from)
+            } else {
+                None
+            }
+        }
+    }
+
+    impl Default for EnvironmentOverrides {
+        fn default() -> Self {
+            EnvironmentOverrides::all()
+        }
+    }
+
+    /// Options to control how a repository is opened with [`Repository::open_opts()`] and [`Repository::discover_opts()`].
+    ///
+    /// The default matches `git`, as needed by command-line tools. Applications which embed repositories they don't control,
+    /// like editors, should use [`Options::isolated()`] instead.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Options {
+        pub(crate) environment: EnvironmentOverrides,
+        pub(crate) repo_config: bool,
+        pub(crate) trust: Option<Trust>,
+    }
+
+    impl Default for Options {
+        fn default() -> Self {
+            Options {
+                environment: EnvironmentOverrides::all(),
+                repo_config: true,
+                trust: None,
+            }
+        }
+    }
+
+    impl Options {
+        /// Open repositories without being affected by the environment or their configuration, which is trusted only
+        /// [partially][Trust::Reduced].
+        pub fn isolated() -> Self {
+            Options {
+                environment: EnvironmentOverrides::none(),
+                repo_config: false,
+                trust: Some(Trust::Reduced),
+            }
+        }
+
+        /// Honor only the environment variables enabled in `overrides`, instead of [all of them][EnvironmentOverrides::all()].
+        pub fn environment(mut self, overrides: EnvironmentOverrides) -> Self {
+            self.environment = overrides;
+            self
+        }
+
+        /// If `toggle` is false, ignore the configuration file of the repository except for `core.repositoryFormatVersion`
+        /// and `extensions.objectFormat`, which are needed to read it correctly. It's `true` by default.
+        pub fn repo_config(mut self, toggle: bool) -> Self {
+            self.repo_config = toggle;
+            self
+        }
+
+        /// Use `trust` instead of [deriving it from the ownership][Trust::from_ownership()] of the repository and its work tree.
+        pub fn trust(mut self, trust: Trust) -> Self {
+            self.trust = Some(trust);
+            self
+        }
     }
 
     impl Repository {
         /// Open a git repository at the given `path`, possibly expanding it to `path/.git` if `path` is a work tree dir.
+        ///
+        /// If `path/.git` is a file as used by submodules and linked worktrees, the git directory it points to is opened
+        /// with `path` as work tree, unless `core.worktree` is configured in the repository.
+        ///
+        /// Environment variables like `GIT_DIR` are honored, see [`open_opts()`][Repository::open_opts()] to control that.
         pub fn open(path: impl Into<std::path::PathBuf>) -> Result<Self, Error> {
-            let path = path.into();
-            let (path, kind) = match crate::path::is::git(&path) {
-                Ok(kind) => (path, kind),
+            Self::open_opts(path, Options::default())
+        }
+
+        /// Like [`open()`][Repository::open()], but ignore all [environment overrides][EnvironmentOverrides], to be sure that
+        /// the repository at `path` is the one that's opened.
+        pub fn open_with_environment_overrides_disabled(path: impl Into<std::path::PathBuf>) -> Result<Self, Error> {
+            Self::open_opts(path, Options::default().environment(EnvironmentOverrides::none()))
+        }
+
+        /// Like [`open()`][Repository::open()], but controlled by `options`.
+        pub fn open_opts(path: impl Into<std::path::PathBuf>, options: Options) -> Result<Self, Error> {
+            let path =
+                EnvironmentOverrides::path(options.environment.git_dir, "GIT_DIR").unwrap_or_else(|| path.into());
+            let objects_dir = EnvironmentOverrides::path(options.environment.object_dir, "GIT_OBJECT_DIRECTORY");
+            let is_git = |path: &Path| crate::path::is::git_with_objects_dir(path, objects_dir.clone());
+            let (git_dir, worktree_dir) = match is_git(&path) {
+                Ok(kind) => {
+                    let worktree_dir = match kind {
+                        crate::Kind::WorkTree => path.parent().map(ToOwned::to_owned),
+                        crate::Kind::Bare => None,
+                    };
+                    (path, worktree_dir)
+                }
                 Err(_) => {
-                    let git_dir = path.join(".git");
-                    crate::path::is::git(&git_dir).map(|kind| (git_dir, kind))?
+                    let dot_git = path.join(".git");
+                    if dot_git.is_file() {
+                        let git_dir = crate::path::from_gitdir_file(&dot_git)?;
+                        is_git(&git_dir)?;
+                        (git_dir, Some(path))
+                    } else {
+                        let kind = is_git(&dot_git)?;
+                        crate::Path::from_dot_git_dir(dot_git, kind).into_repository_and_work_tree_directories()
+                    }
                 }
             };
-            let (git_dir, worktree_dir) =
-                crate::Path::from_dot_git_dir(path, kind).into_repository_and_work_tree_directories();
-            Repository::open_from_paths(git_dir, worktree_dir)
+            Repository::open_from_paths(git_dir, worktree_dir, options)
+        }
+
+        /// Like [`open()`][Repository::open()], but creates missing directories of the reference store, like `refs/heads`,
+        /// to repair repositories that were restored only partially.
+        ///
+        /// Anomalies that can't be repaired safely are returned along with the created directories for the caller to handle.
+        pub fn open_with_repair(
+            path: impl Into<std::path::PathBuf>,
+        ) -> Result<(Self, git_ref::file::structure::Outcome), Error> {
+            let repo = Self::open(path)?;
+            let outcome = repo.refs.verify_or_create_structure()?;
+            Ok((repo, outcome))
+        }
+
+        /// Like [`open()`][Repository::open()], but all operations which would write to the object database or the reference
+        /// store, like writing objects, preparing reference transactions or running maintenance, fail with a dedicated
+        /// `ReadOnly` error instead.
+        ///
+        /// Opening a repository never writes to it, which makes this suitable for repositories that must not or can't be changed.
+        pub fn open_read_only(path: impl Into<std::path::PathBuf>) -> Result<Self, Error> {
+            let mut repo = Self::open(path)?;
+            repo.refs.read_only = true;
+            repo.odb.set_read_only(true);
+            Ok(repo)
+        }
+
+        /// Return true if this instance was [opened read-only][Repository::open_read_only()].
+        pub fn is_read_only(&self) -> bool {
+            self.refs.read_only
+        }
+
+        /// Return how much the configuration of the repository [is trusted][Trust], as decided when it was opened.
+        pub fn trust(&self) -> Trust {
+            self.trust
+        }
+
+        /// Verify objects written to the object database according to `verify` by reading them back, to detect storage
+        /// which doesn't retain what was written to it. Objects failing verification are removed and their write fails.
+        ///
+        /// This affects loose objects as well as packs written during [maintenance][crate::easy::ext::RepositoryAccessExt::auto_maintenance()],
+        /// and costs nothing if set to [`Verify::Never`][git_odb::Verify::Never], the default.
+        pub fn set_object_write_verification(&mut self, verify: git_odb::Verify) -> &mut Self {
+            self.odb.set_verify(verify);
+            self
+        }
+
+        /// Return how objects written to the object database [are verified][Repository::set_object_write_verification()].
+        pub fn object_write_verification(&self) -> git_odb::Verify {
+            self.odb.verify()
+        }
+
+        /// Use `threads` for all operations that can run in parallel, like [verifying objects][Repository::verify_integrity()]
+        /// and writing packs during [maintenance][crate::easy::ext::RepositoryAccessExt::auto_maintenance()] or for
+        /// [bundles][crate::easy::ext::RepositoryAccessExt::create_bundle()].
+        ///
+        /// With [`Threads::None`], no threads are spawned at all, which is [`Threads::Auto`] by default.
+        pub fn set_object_access_parallelism(&mut self, threads: crate::Threads) -> &mut Self {
+            self.threads = threads;
+            self
+        }
+
+        /// Return the amount of threads [to use][Repository::set_object_access_parallelism()] for operations that can run in parallel.
+        pub fn object_access_parallelism(&self) -> crate::Threads {
+            self.threads
+        }
+
+        /// If `deterministic` is true, packs written during [maintenance][crate::easy::ext::RepositoryAccessExt::auto_maintenance()]
+        /// or for [bundles][crate::easy::ext::RepositoryAccessExt::create_bundle()] are the same, byte for byte, whenever they
+        /// contain the same objects, no matter how these are stored or how many [threads][Repository::set_object_access_parallelism()]
+        /// are used.
+        ///
+        /// This is `false` by default as all objects are recompressed instead of being copied from existing packs, which is slower.
+        pub fn set_deterministic_packs(&mut self, deterministic: bool) -> &mut Self {
+            self.deterministic_packs = deterministic;
+            self
+        }
+
+        /// Return true if packs [are written deterministically][Repository::set_deterministic_packs()].
+        pub fn deterministic_packs(&self) -> bool {
+            self.deterministic_packs
         }
 
         pub(in crate::repository) fn open_from_paths(
             git_dir: PathBuf,
             mut worktree_dir: Option<PathBuf>,
+            options: Options,
         ) -> Result<Self, Error> {
-            let config = git_config::file::GitConfig::open(git_dir.join("config"))?;
+            let common_dir = crate::path::common_dir(&git_dir).map_err(|source| Error::Io {
+                path: git_dir.join("commondir"),
+                source,
+            })?;
+            let parsed_config =
+                git_config::parser::parse_from_path(common_dir.as_ref().unwrap_or(&git_dir).join("config"))?;
+            let filter_driver_names = crate::commands::filter_driver_names(&parsed_config);
+            let repo_config = git_config::file::GitConfig::from(parsed_config);
+            let no_config = git_config::file::GitConfig::new();
+            let config = if options.repo_config { &repo_config } else { &no_config };
+            match &common_dir {
+                Some(_) => {
+                    if let Some(dot_git) = std::fs::read(git_dir.join("gitdir")).ok().and_then(|path| {
+                        path.trim_end_with(|c| c == '\n' || c == '\r')
+                            .to_path()
+                            .ok()
+                            .map(ToOwned::to_owned)
+                    }) {
+                        worktree_dir = dot_git.parent().map(ToOwned::to_owned);
+                    }
+                }
+                None => {
+                    if let Ok(path) = config.value::<Cow<'_, [u8]>>("core", None, "worktree") {
+                        let path = path.to_path().map_err(|_| Error::InvalidWorktree {
+                            value: path.to_vec().into(),
+                        })?;
+                        worktree_dir = Some(crate::path::realpath(git_dir.join(path)));
+                    }
+                }
+            }
             if worktree_dir.is_none() {
                 let is_bare = config
                     .value::<Boolean<'_>>("core", None, "bare")
@@ -78,12 +506,15 @@ pub mod open {
                     worktree_dir = Some(git_dir.parent().expect("parent is always available").to_owned());
                 }
             }
-            let hash_kind = if config
+            if let Some(work_tree) = EnvironmentOverrides::path(options.environment.work_tree, "GIT_WORK_TREE") {
+                worktree_dir = Some(work_tree);
+            }
+            let hash_kind = if repo_config
                 .value::<Integer>("core", None, "repositoryFormatVersion")
                 .map_or(0, |v| v.value)
                 == 1
             {
-                if let Ok(format) = config.value::<Cow<'_, [u8]>>("extensions", None, "objectFormat") {
+                if let Ok(format) = repo_config.value::<Cow<'_, [u8]>>("extensions", None, "objectFormat") {
                     match format.as_ref() {
                         b"sha1" => git_hash::Kind::Sha1,
                         _ => {
@@ -99,19 +530,177 @@ pub mod open {
                 git_hash::Kind::Sha1
             };
 
-            Ok(crate::Repository {
-                odb: git_odb::linked::Store::at(git_dir.join("objects"))?,
-                refs: git_ref::file::Store::at(
-                    git_dir,
-                    if worktree_dir.is_none() {
-                        git_ref::file::WriteReflog::Disable
-                    } else {
-                        git_ref::file::WriteReflog::Normal
-                    },
-                ),
+            let abbrev_config = crate::repository::abbrev::from_config(config, hash_kind)
+                .map_err(|value| Error::InvalidAbbreviation { value })?;
+
+            #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
+            let protocol_version = config
+                .value::<Cow<'_, [u8]>>("protocol", None, "version")
+                .ok()
+                .map(|value| value.trim().into());
+
+            let trust = options.trust.unwrap_or_else(|| {
+                Trust::from_ownership(std::iter::once(git_dir.as_path()).chain(worktree_dir.as_deref()))
+            });
+            let commands = crate::commands::Configured::from_config(
+                match trust {
+                    Trust::Full => config,
+                    Trust::Reduced => &no_config,
+                },
+                filter_driver_names,
+                common_dir.as_ref().unwrap_or(&git_dir),
+                worktree_dir.as_deref(),
+            );
+            let index_path = EnvironmentOverrides::path(options.environment.index_file, "GIT_INDEX_FILE")
+                .unwrap_or_else(|| git_dir.join("index"));
+            let objects_dir = EnvironmentOverrides::path(options.environment.object_dir, "GIT_OBJECT_DIRECTORY")
+                .unwrap_or_else(|| common_dir.as_ref().unwrap_or(&git_dir).join("objects"));
+
+            let write_reflog = if worktree_dir.is_none() {
+                git_ref::file::WriteReflog::Disable
+            } else {
+                git_ref::file::WriteReflog::Normal
+            };
+            let mut repo = crate::Repository {
+                odb: git_odb::linked::Store::at(objects_dir)?,
+                refs: match common_dir {
+                    Some(common_dir) => git_ref::file::Store::for_linked_worktree(git_dir, common_dir, write_reflog),
+                    None => git_ref::file::Store::at(git_dir, write_reflog),
+                },
                 work_tree: worktree_dir,
                 hash_kind,
-            })
+                abbrev_config,
+                abbrev: 0,
+                maintenance_limits: crate::easy::maintenance::Limits::from_config(config),
+                version_sort_suffixes: crate::easy::tag::version_sort_suffixes_from_config(config),
+                threads: Default::default(),
+                deterministic_packs: false,
+                trust,
+                commands,
+                index_path,
+                #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
+                protocol_version,
+            };
+            repo.update_abbreviation();
+            Ok(repo)
+        }
+    }
+}
+
+///
+pub mod commands {
+    use std::{
+        borrow::Cow,
+        path::{Path, PathBuf},
+    };
+
+    use git_config::values::Boolean;
+
+    use crate::{
+        bstr::{BString, ByteSlice},
+        Repository,
+    };
+
+    /// The programs of a filter driver configured in `filter.<name>`, which convert files between the work tree and the object
+    /// database as selected by the `filter` attribute.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub struct FilterDriver {
+        /// The name of the driver as used in `.gitattributes`.
+        pub name: BString,
+        /// The command to run when files are added to the object database, as configured by `filter.<name>.clean`.
+        pub clean: Option<BString>,
+        /// The command to run when files are checked out into the work tree, as configured by `filter.<name>.smudge`.
+        pub smudge: Option<BString>,
+        /// The long-running program to convert files in both directions, as configured by `filter.<name>.process`.
+        pub process: Option<BString>,
+        /// If true, failing to convert files is an error instead of keeping them as they are, as configured by `filter.<name>.required`.
+        pub required: bool,
+    }
+
+    /// Configuration values which make `git` run programs, as far as they are [trusted][crate::open::Trust].
+    #[derive(Debug, Clone)]
+    pub(crate) struct Configured {
+        hooks_dir: PathBuf,
+        fsmonitor: Option<BString>,
+        filter_drivers: Vec<FilterDriver>,
+    }
+
+    impl Configured {
+        pub(crate) fn from_config(
+            config: &git_config::file::GitConfig<'_>,
+            filter_driver_names: Vec<String>,
+            common_dir: &Path,
+            worktree_dir: Option<&Path>,
+        ) -> Self {
+            let string = |section: &str, subsection: Option<&str>, key: &str| {
+                config
+                    .value::<Cow<'_, [u8]>>(section, subsection, key)
+                    .ok()
+                    .filter(|value| !value.is_empty())
+                    .map(|value| BString::from(value.into_owned()))
+            };
+            let hooks_dir =
+                match string("core", None, "hooksPath").and_then(|path| path.to_path().ok().map(ToOwned::to_owned)) {
+                    // Like `git`, relative paths are relative to where hooks run, which is the work tree if there is one.
+                    Some(path) => worktree_dir.unwrap_or(common_dir).join(path),
+                    None => common_dir.join("hooks"),
+                };
+            let filter_drivers = filter_driver_names
+                .into_iter()
+                .map(|name| FilterDriver {
+                    clean: string("filter", Some(&name), "clean"),
+                    smudge: string("filter", Some(&name), "smudge"),
+                    process: string("filter", Some(&name), "process"),
+                    required: config
+                        .value::<Boolean<'_>>("filter", Some(&name), "required")
+                        .map_or(false, |b| matches!(b, Boolean::True(_))),
+                    name: name.into(),
+                })
+                .filter(|driver| driver.clean.is_some() || driver.smudge.is_some() || driver.process.is_some())
+                .collect();
+            Configured {
+                hooks_dir,
+                fsmonitor: string("core", None, "fsmonitor"),
+                filter_drivers,
+            }
+        }
+    }
+
+    /// The names of all `filter.<name>` sections in the order of their first appearance, as these can't be listed once the
+    /// configuration is parsed.
+    pub(crate) fn filter_driver_names(config: &git_config::parser::Parser<'_>) -> Vec<String> {
+        let mut names = Vec::<String>::new();
+        for section in config.sections() {
+            let header = &section.section_header;
+            if !header.name.eq_ignore_ascii_case("filter") {
+                continue;
+            }
+            if let Some(name) = header.subsection_name.as_deref() {
+                if !names.iter().any(|existing| existing == name) {
+                    names.push(name.to_owned());
+                }
+            }
+        }
+        names
+    }
+
+    impl Repository {
+        /// The directory containing the hook scripts, as configured by `core.hooksPath` if the repository
+        /// [is trusted][crate::open::Trust::Full], or the `hooks` directory of the repository.
+        pub fn hooks_dir(&self) -> &Path {
+            &self.commands.hooks_dir
+        }
+
+        /// The command to query for changed files in the work tree, as configured by `core.fsmonitor` if the repository
+        /// [is trusted][crate::open::Trust::Full].
+        pub fn fsmonitor_command(&self) -> Option<&crate::bstr::BStr> {
+            self.commands.fsmonitor.as_ref().map(|command| command.as_bstr())
+        }
+
+        /// The filter driver called `name`, if it's configured and the repository [is trusted][crate::open::Trust::Full].
+        pub fn filter_driver(&self, name: impl AsRef<[u8]>) -> Option<&FilterDriver> {
+            let name = name.as_ref();
+            self.commands.filter_drivers.iter().find(|driver| driver.name == name)
         }
     }
 }
@@ -130,6 +719,12 @@ pub mod init {
         Init(#[from] crate::path::create::Error),
         #[error(transparent)]
         Open(#[from] crate::open::Error),
+        #[error(transparent)]
+        RefsStructure(#[from] git_ref::file::structure::Error),
+        #[error("The newly created reference store is invalid: {:?}", .anomalies)]
+        InvalidRefsStructure {
+            anomalies: Vec<git_ref::file::structure::Anomaly>,
+        },
     }
 
     impl Repository {
@@ -139,7 +734,14 @@ pub mod init {
         /// won't mind if the `directory` otherwise is non-empty.
         pub fn init(directory: impl AsRef<Path>, kind: crate::Kind) -> Result<Self, Error> {
             let path = crate::path::create::into(directory.as_ref(), kind)?;
-            Ok(path.try_into()?)
+            let repo: Repository = path.try_into()?;
+            let outcome = repo.refs.verify_or_create_structure()?;
+            if !outcome.is_valid() {
+                return Err(Error::InvalidRefsStructure {
+                    anomalies: outcome.anomalies,
+                });
+            }
+            Ok(repo)
         }
     }
 }
@@ -169,12 +771,275 @@ mod location {
         pub fn objects_dir(&self) -> &std::path::Path {
             &self.odb.dbs[0].loose.path
         }
+
+        /// Return the path to the index file, which may be [overridden][crate::open::EnvironmentOverrides::index_file]
+        /// by `GIT_INDEX_FILE`.
+        pub fn index_path(&self) -> &std::path::Path {
+            &self.index_path
+        }
+    }
+}
+
+mod cleanup {
+    use std::{path::PathBuf, time::Duration};
+
+    use crate::Repository;
+
+    impl Repository {
+        /// Remove lock files and temporary files anywhere within the [common directory][Repository::common_dir()] which weren't
+        /// modified within `older_than`, returning their paths.
+        ///
+        /// These are typically left behind by processes which were killed and couldn't clean up after themselves,
+        /// and lock files in particular prevent further modifications. Locks and tempfiles held by this process are
+        /// never removed, see [`git_tempfile::cleanup_stale()`] for details.
+        ///
+        /// Fails with an error of kind [`PermissionDenied`][std::io::ErrorKind::PermissionDenied] if the repository was
+        /// [opened read-only][Repository::open_read_only()].
+        pub fn cleanup_stale_tempfiles(&self, older_than: Duration) -> std::io::Result<Vec<PathBuf>> {
+            if self.is_read_only() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    "The repository was opened read-only",
+                ));
+            }
+            git_tempfile::cleanup_stale(self.common_dir(), older_than)
+        }
+    }
+}
+
+///
+pub mod worktree {
+    use std::{
+        path::{Path, PathBuf},
+        time::Duration,
+    };
+
+    use git_hash::ObjectId;
+    use git_odb::Find;
+    use git_ref::{file::ReferenceExt, Target};
+
+    use crate::Repository;
+
+    /// The error returned by [`Repository::worktrees()`], [`Repository::main_worktree()`] and [`Repository::for_worktree()`].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read worktree information at '{}'", .path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+        #[error(transparent)]
+        PackedRefsOpen(#[from] git_ref::packed::buffer::open::Error),
+        #[error(transparent)]
+        FindHead(#[from] git_ref::file::find::existing::Error),
+        #[error(transparent)]
+        FindReference(#[from] git_ref::file::find::Error),
+        #[error(transparent)]
+        PeelHead(#[from] git_ref::peel::to_id::Error),
+        #[error("There is no linked worktree named '{}'", .name)]
+        NotFound { name: String },
+        #[error(transparent)]
+        ObjectStoreInitialization(#[from] git_odb::linked::init::Error),
+    }
+
+    /// A working tree of a repository, either the main one or one that was linked with `git worktree add`.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub struct Worktree {
+        name: String,
+        path: PathBuf,
+        git_dir: PathBuf,
+        head: Option<ObjectId>,
+        is_locked: bool,
+    }
+
+    impl Worktree {
+        /// The name of a linked worktree, as used in `<common-dir>/worktrees/<name>`, or an empty string for the main worktree.
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+
+        /// The directory with the checked out files of this worktree.
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+
+        /// The private git directory of this worktree, containing its `HEAD`.
+        ///
+        /// It's the repository directory itself for the main worktree.
+        pub fn git_dir(&self) -> &Path {
+            &self.git_dir
+        }
+
+        /// The id of the commit checked out in this worktree, or `None` if its `HEAD` points to a branch that doesn't
+        /// exist yet.
+        pub fn head(&self) -> Option<ObjectId> {
+            self.head
+        }
+
+        /// Return true if this is the main worktree, as opposed to a linked one.
+        pub fn is_main(&self) -> bool {
+            self.name.is_empty()
+        }
+
+        /// Return true if this worktree was locked with `git worktree lock` to prevent it from being pruned.
+        pub fn is_locked(&self) -> bool {
+            self.is_locked
+        }
+
+        /// Return true if this linked worktree can be pruned like `git worktree prune --expire` would, as its
+        /// [`path`][Worktree::path()] doesn't exist anymore and its metadata wasn't touched within `expiry`.
+        ///
+        /// The main worktree and locked worktrees are never prunable.
+        pub fn is_prunable(&self, expiry: Duration) -> bool {
+            if self.is_main() || self.is_locked || self.path.exists() {
+                return false;
+            }
+            std::fs::metadata(self.git_dir.join("index"))
+                .and_then(|md| md.modified())
+                .map_or(true, |modified| modified.elapsed().map_or(false, |age| age >= expiry))
+        }
+    }
+
+    impl Repository {
+        /// Return the directory shared by all worktrees of this repository, which is [`git_dir()`][Repository::git_dir()]
+        /// unless this instance was [opened for a linked worktree][Repository::for_worktree()].
+        pub fn common_dir(&self) -> &Path {
+            self.refs.common_dir_resolved()
+        }
+
+        /// Return all worktrees that were linked to this repository with `git worktree add`, sorted by name, but without
+        /// the [main worktree][Repository::main_worktree()].
+        pub fn worktrees(&self) -> Result<Vec<Worktree>, Error> {
+            let dir = self.common_dir().join("worktrees");
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+                Err(source) => return Err(Error::Io { path: dir, source }),
+            };
+            let mut worktrees = Vec::new();
+            for entry in entries {
+                let entry = entry.map_err(|source| Error::Io {
+                    path: dir.clone(),
+                    source,
+                })?;
+                let git_dir = entry.path();
+                if !git_dir.join("gitdir").is_file() {
+                    continue;
+                }
+                let name = entry.file_name().to_string_lossy().into_owned();
+                worktrees.push(self.linked_worktree(name, git_dir)?);
+            }
+            worktrees.sort_by(|a, b| a.name.cmp(&b.name));
+            Ok(worktrees)
+        }
+
+        /// Return the main worktree of this repository, or `None` if it is bare.
+        ///
+        /// Note that for instances [opened for a linked worktree][Repository::for_worktree()], the main worktree is
+        /// assumed to be the parent directory of the common directory if that is named `.git`.
+        pub fn main_worktree(&self) -> Result<Option<Worktree>, Error> {
+            let path = match self.refs.common_dir() {
+                None => self.work_tree.clone(),
+                Some(common_dir) => common_dir
+                    .file_name()
+                    .filter(|name| *name == ".git")
+                    .and_then(|_| common_dir.parent())
+                    .map(ToOwned::to_owned),
+            };
+            let path = match path {
+                Some(path) => path,
+                None => return Ok(None),
+            };
+            let git_dir = self.common_dir().to_owned();
+            let refs = git_ref::file::Store::at(&git_dir, git_ref::file::WriteReflog::Normal);
+            Ok(Some(Worktree {
+                name: String::new(),
+                path,
+                head: self.head_of(&refs)?,
+                git_dir,
+                is_locked: false,
+            }))
+        }
+
+        /// Return a new instance for the linked worktree with the given `name`, whose `HEAD` and work tree are the ones of
+        /// the worktree, while all other references and objects are shared.
+        pub fn for_worktree(&self, name: &str) -> Result<Self, Error> {
+            let worktree = self
+                .worktrees()?
+                .into_iter()
+                .find(|worktree| worktree.name == name)
+                .ok_or_else(|| Error::NotFound { name: name.into() })?;
+            let index_path = worktree.git_dir.join("index");
+            let mut refs = git_ref::file::Store::for_linked_worktree(
+                worktree.git_dir,
+                self.common_dir(),
+                git_ref::file::WriteReflog::Normal,
+            );
+            refs.read_only = self.is_read_only();
+            refs.journal = self.refs.journal;
+            let mut odb = git_odb::linked::Store::at(self.objects_dir())?;
+            odb.set_read_only(self.is_read_only())
+                .set_verify(self.object_write_verification());
+            Ok(Repository {
+                refs,
+                odb,
+                work_tree: Some(worktree.path),
+                hash_kind: self.hash_kind,
+                abbrev_config: self.abbrev_config,
+                abbrev: self.abbrev,
+                maintenance_limits: self.maintenance_limits,
+                version_sort_suffixes: self.version_sort_suffixes.clone(),
+                threads: self.threads,
+                deterministic_packs: self.deterministic_packs,
+                trust: self.trust,
+                commands: self.commands.clone(),
+                index_path,
+                #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
+                protocol_version: self.protocol_version.clone(),
+            })
+        }
+
+        fn linked_worktree(&self, name: String, git_dir: PathBuf) -> Result<Worktree, Error> {
+            let gitdir_file = git_dir.join("gitdir");
+            let dot_git = std::fs::read_to_string(&gitdir_file).map_err(|source| Error::Io {
+                path: gitdir_file,
+                source,
+            })?;
+            let dot_git = git_dir.join(dot_git.trim_end_matches(&['\n', '\r'][..]));
+            let path = dot_git.parent().map_or_else(|| dot_git.clone(), ToOwned::to_owned);
+            let refs = git_ref::file::Store::for_linked_worktree(
+                &git_dir,
+                self.common_dir(),
+                git_ref::file::WriteReflog::Normal,
+            );
+            Ok(Worktree {
+                head: self.head_of(&refs)?,
+                is_locked: git_dir.join("locked").is_file(),
+                name,
+                path,
+                git_dir,
+            })
+        }
+
+        fn head_of(&self, refs: &git_ref::file::Store) -> Result<Option<ObjectId>, Error> {
+            let packed = refs.packed_buffer()?;
+            let mut head = refs.find("HEAD", packed.as_ref())?;
+            if let Target::Symbolic(name) = &head.target {
+                if refs.try_find(name.to_partial(), packed.as_ref())?.is_none() {
+                    return Ok(None);
+                }
+            }
+            let id = head.peel_to_id_in_place(refs, packed.as_ref(), |oid, buf| {
+                self.odb
+                    .try_find(oid, buf, &mut git_pack::cache::Never)
+                    .map(|obj| obj.map(|obj| (obj.kind, obj.data)))
+            })?;
+            Ok(Some(id))
+        }
     }
 }
 
 ///
 pub mod discover {
-    use std::{convert::TryInto, path::Path};
+    use std::path::Path;
 
     use crate::{path::discover, Repository};
 
@@ -190,9 +1055,22 @@ pub mod discover {
 
     impl Repository {
         /// Try to open a git repository in `directory` and search upwards through its parents until one is found.
+        ///
+        /// Environment variables like `GIT_DIR` are honored, see [`discover_opts()`][Repository::discover_opts()] to control that.
         pub fn discover(directory: impl AsRef<Path>) -> Result<Self, Error> {
-            let path = discover::existing(directory)?;
-            Ok(path.try_into()?)
+            Self::discover_opts(directory, Default::default())
+        }
+
+        /// Like [`discover()`][Repository::discover()], but controlled by `options`.
+        ///
+        /// If `GIT_DIR` is set and [honored][crate::open::EnvironmentOverrides::git_dir], the repository it points to is opened
+        /// without searching.
+        pub fn discover_opts(directory: impl AsRef<Path>, options: crate::open::Options) -> Result<Self, Error> {
+            if options.environment.git_dir && std::env::var_os("GIT_DIR").map_or(false, |dir| !dir.is_empty()) {
+                return Ok(Repository::open_opts(directory.as_ref(), options)?);
+            }
+            let (git_dir, worktree_dir) = discover::existing(directory)?.into_repository_and_work_tree_directories();
+            Ok(Repository::open_from_paths(git_dir, worktree_dir, options)?)
         }
     }
 }
@@ -0,0 +1,288 @@
+//! Serializable summaries of repository data, for use by tools producing JSON or other serde formats.
+//!
+//! The types read from a repository borrow their data and represent ids and byte strings in ways that are
+//! impractical for human-readable formats, hence the summaries own their data, store ids as hexadecimal strings,
+//! and turn byte strings into [`Text`] according to the [`Encoding`] they are created with.
+//! Conversions with [`From`] use the default [`Encoding::Lossy`].
+use git_hash::oid;
+use git_object::bstr::{BString, ByteSlice};
+
+/// The way byte strings are converted into [`Text`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// Decode bytes as UTF-8, replacing invalid sequences with the unicode replacement character `�`.
+    Lossy,
+    /// Keep valid UTF-8 as string, but encode all other bytes as base64 to retain them exactly.
+    Lossless,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Encoding::Lossy
+    }
+}
+
+/// A byte string as it appears in serialized summaries, either as string or as base64-encoded bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum Text {
+    /// The bytes were valid UTF-8, or were decoded lossily.
+    Utf8(String),
+    /// The bytes were not valid UTF-8 and [`Encoding::Lossless`] was requested.
+    Base64 {
+        /// The base64 encoded bytes.
+        base64: String,
+    },
+}
+
+impl Text {
+    /// Convert `bytes` into text as configured by `encoding`.
+    pub fn new(bytes: impl AsRef<[u8]>, encoding: Encoding) -> Self {
+        let bytes = bytes.as_ref();
+        match (bytes.to_str(), encoding) {
+            (Ok(text), _) => Text::Utf8(text.into()),
+            (Err(_), Encoding::Lossy) => Text::Utf8(bytes.to_str_lossy().into_owned()),
+            (Err(_), Encoding::Lossless) => Text::Base64 {
+                base64: base64::encode(bytes),
+            },
+        }
+    }
+
+    /// Return the bytes this text was created from, which are only the original ones if they were valid UTF-8
+    /// or [`Encoding::Lossless`] was used.
+    pub fn to_bytes(&self) -> Result<BString, base64::DecodeError> {
+        Ok(match self {
+            Text::Utf8(text) => text.as_str().into(),
+            Text::Base64 { base64 } => base64::decode(base64)?.into(),
+        })
+    }
+}
+
+/// The summary of a [`git_actor::Signature`] or [`git_actor::SignatureRef`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Signature {
+    /// The actor's name.
+    pub name: Text,
+    /// The actor's email.
+    pub email: Text,
+    /// The time of signing in [RFC 3339](https://datatracker.ietf.org/doc/html/rfc3339) format in the signer's
+    /// time zone, like `2000-01-02T12:34:56+02:30`.
+    pub time: String,
+}
+
+impl Signature {
+    /// Create a new summary from `signature`, converting byte strings according to `encoding`.
+    pub fn new(signature: &git_actor::SignatureRef<'_>, encoding: Encoding) -> Self {
+        Signature {
+            name: Text::new(signature.name, encoding),
+            email: Text::new(signature.email, encoding),
+            time: rfc3339(&signature.time),
+        }
+    }
+}
+
+impl From<&git_actor::SignatureRef<'_>> for Signature {
+    fn from(signature: &git_actor::SignatureRef<'_>) -> Self {
+        Signature::new(signature, Encoding::default())
+    }
+}
+
+impl From<&git_actor::Signature> for Signature {
+    fn from(signature: &git_actor::Signature) -> Self {
+        Signature::new(&signature.to_ref(), Encoding::default())
+    }
+}
+
+/// The summary of a commit along with its id.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Commit {
+    /// The hexadecimal id of the commit.
+    pub id: String,
+    /// The hexadecimal id of the commit's tree.
+    pub tree: String,
+    /// The hexadecimal ids of all parent commits, in order.
+    pub parents: Vec<String>,
+    /// The author of the commit.
+    pub author: Signature,
+    /// The committer of the commit.
+    pub committer: Signature,
+    /// The encoding of the message, if it was explicitly specified.
+    pub encoding: Option<Text>,
+    /// The complete commit message.
+    pub message: Text,
+}
+
+impl Commit {
+    /// Create a new summary of `commit` with the given `id`, converting byte strings according to `encoding`.
+    pub fn new(id: impl AsRef<oid>, commit: &git_object::CommitRef<'_>, encoding: Encoding) -> Self {
+        Commit {
+            id: id.as_ref().to_string(),
+            tree: commit.tree.to_string(),
+            parents: commit.parents.iter().map(|id| id.to_string()).collect(),
+            author: Signature::new(&commit.author, encoding),
+            committer: Signature::new(&commit.committer, encoding),
+            encoding: commit.encoding.map(|encoding_name| Text::new(encoding_name, encoding)),
+            message: Text::new(commit.message, encoding),
+        }
+    }
+}
+
+impl<Id: AsRef<oid>> From<(Id, &git_object::CommitRef<'_>)> for Commit {
+    fn from((id, commit): (Id, &git_object::CommitRef<'_>)) -> Self {
+        Commit::new(id, commit, Encoding::default())
+    }
+}
+
+/// The summary of a reference target.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Target {
+    /// The hexadecimal id of the object the reference points to.
+    Object(String),
+    /// The full name of the reference this symbolic reference points to.
+    Symbolic(Text),
+}
+
+/// The summary of a reference.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Reference {
+    /// The full name of the reference, like `refs/heads/main`.
+    pub name: Text,
+    /// The target of the reference.
+    pub target: Target,
+    /// The hexadecimal id of the object the reference ultimately points to, if it was peeled.
+    pub peeled: Option<String>,
+}
+
+impl Reference {
+    /// Create a new summary of `reference`, converting byte strings according to `encoding`.
+    pub fn new(reference: &git_ref::Reference, encoding: Encoding) -> Self {
+        Reference {
+            name: Text::new(reference.name.as_bstr(), encoding),
+            target: match &reference.target {
+                git_ref::Target::Peeled(id) => Target::Object(id.to_string()),
+                git_ref::Target::Symbolic(name) => Target::Symbolic(Text::new(name.as_bstr(), encoding)),
+            },
+            peeled: reference.peeled.map(|id| id.to_string()),
+        }
+    }
+}
+
+impl From<&git_ref::Reference> for Reference {
+    fn from(reference: &git_ref::Reference) -> Self {
+        Reference::new(reference, Encoding::default())
+    }
+}
+
+impl<A> From<&crate::easy::Reference<'_, A>> for Reference {
+    fn from(reference: &crate::easy::Reference<'_, A>) -> Self {
+        Reference::new(&reference.inner, Encoding::default())
+    }
+}
+
+/// The summary of a change between two trees, as recorded by [`git_diff::tree::Recorder`].
+///
+/// Entry modes are represented like `git` does, for example `100644` for blobs.
+#[cfg(feature = "git-diff")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+#[allow(missing_docs)]
+pub enum Change {
+    Addition {
+        path: Text,
+        mode: String,
+        id: String,
+    },
+    Deletion {
+        path: Text,
+        mode: String,
+        id: String,
+    },
+    Modification {
+        path: Text,
+        previous_mode: String,
+        previous_id: String,
+        mode: String,
+        id: String,
+    },
+}
+
+#[cfg(feature = "git-diff")]
+impl Change {
+    /// Create a new summary of `change`, converting its path according to `encoding`.
+    pub fn new(change: &git_diff::tree::recorder::Change, encoding: Encoding) -> Self {
+        use git_diff::tree::recorder::Change::*;
+        let mode = |mode: &git_object::tree::EntryMode| mode.as_bytes().to_str_lossy().into_owned();
+        match change {
+            Addition { entry_mode, oid, path } => Change::Addition {
+                path: Text::new(path, encoding),
+                mode: mode(entry_mode),
+                id: oid.to_string(),
+            },
+            Deletion { entry_mode, oid, path } => Change::Deletion {
+                path: Text::new(path, encoding),
+                mode: mode(entry_mode),
+                id: oid.to_string(),
+            },
+            Modification {
+                previous_entry_mode,
+                previous_oid,
+                entry_mode,
+                oid,
+                path,
+            } => Change::Modification {
+                path: Text::new(path, encoding),
+                previous_mode: mode(previous_entry_mode),
+                previous_id: previous_oid.to_string(),
+                mode: mode(entry_mode),
+                id: oid.to_string(),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "git-diff")]
+impl From<&git_diff::tree::recorder::Change> for Change {
+    fn from(change: &git_diff::tree::recorder::Change) -> Self {
+        Change::new(change, Encoding::default())
+    }
+}
+
+/// Format `time` according to RFC 3339 in its own time zone, using `-00:00` for a negative zero offset.
+fn rfc3339(time: &git_actor::Time) -> String {
+    let local = time.time as i64 + time.offset as i64;
+    let (days, seconds_of_day) = (local.div_euclid(86400), local.rem_euclid(86400));
+    let (year, month, day) = civil_from_days(days);
+    let offset_minutes = time.offset.abs() / 60;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60,
+        match time.sign {
+            git_actor::Sign::Plus => '+',
+            git_actor::Sign::Minus => '-',
+        },
+        offset_minutes / 60,
+        offset_minutes % 60
+    )
+}
+
+/// Convert the amount of `days` since 1970-01-01 into a `(year, month, day)` triple of the proleptic gregorian calendar.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days> for details.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
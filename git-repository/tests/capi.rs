@@ -0,0 +1,132 @@
+//! Build this crate as `cdylib` with the `capi` feature, and link and run a C program against it using `include/gix.h`.
+//!
+//! This is a test binary of its own as it needs the `capi` feature, and invokes `cargo` and `cc` to build the library and
+//! the program in a target directory of their own.
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn run(cmd: &mut Command) -> Result<String> {
+    let output = cmd.output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "{:?} failed: {}{}",
+            cmd,
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    Ok(run(Command::new("git").args(args).current_dir(dir))?
+        .trim_end()
+        .to_owned())
+}
+
+/// Build the library and the C test program, and return the path to the latter.
+fn build_test_program() -> Result<PathBuf> {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let target_dir = std::env::current_exe()?
+        .ancestors()
+        .nth(3)
+        .expect("test binary in <target>/<profile>/deps")
+        .join("capi");
+    run(Command::new(option_env!("CARGO").unwrap_or("cargo"))
+        .arg("rustc")
+        .arg("--manifest-path")
+        .arg(manifest_dir.join("Cargo.toml"))
+        .args([
+            "--lib",
+            "--no-default-features",
+            "--features",
+            "capi",
+            "--crate-type",
+            "cdylib",
+        ])
+        .arg("--target-dir")
+        .arg(&target_dir))?;
+
+    let lib_dir = target_dir.join("debug");
+    let program = target_dir.join("test");
+    run(Command::new(std::env::var_os("CC").unwrap_or_else(|| "cc".into()))
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests").join("capi").join("test.c"))
+        .arg(lib_dir.join(format!(
+            "{}git_repository{}",
+            std::env::consts::DLL_PREFIX,
+            std::env::consts::DLL_SUFFIX
+        )))
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-o")
+        .arg(&program))?;
+    Ok(program)
+}
+
+#[test]
+#[cfg(unix)]
+fn each_entry_point_can_be_called_from_c() -> Result {
+    let program = build_test_program()?;
+    let repo = git_testtools::scripted_fixture_repo_writable("make_basic_repo.sh")?;
+    let dir = repo.path();
+    git(dir, &["symbolic-ref", "refs/heads/alias", "refs/heads/main"])?;
+    git(dir, &["tag", "first", "HEAD~1"])?;
+    let no_repo = tempfile::tempdir()?;
+
+    let head = git(dir, &["rev-parse", "HEAD"])?;
+    let parent = git(dir, &["rev-parse", "HEAD~1"])?;
+    let expected = format!(
+        "open: 0
+last error: none
+discover: 0
+open without repository: 1
+last error: some
+discover without repository: 1
+handle: none
+resolve HEAD: 0
+HEAD: {head}
+resolve unknown: 1
+find HEAD: 0
+object: 1 {size} {first_line}
+find missing: 1
+handle: none
+reference: refs/heads/alias -> refs/heads/main
+reference: refs/heads/main {head}
+reference: refs/tags/first {parent}
+references: 0
+reference: refs/tags/first {parent}
+references prefixed: 0
+commit: {head}
+commit: {parent}
+walk: 0
+commit: {head}
+walk stopped: 0
+walk missing: 1
+NULL repository: 2
+NULL callback: 2
+last error: 'cb' must not be NULL
+",
+        head = head,
+        parent = parent,
+        size = git(dir, &["cat-file", "-s", "HEAD"])?,
+        first_line = git(dir, &["cat-file", "commit", "HEAD"])?
+            .lines()
+            .next()
+            .expect("tree line")
+    );
+
+    let actual = run(Command::new(&program)
+        .arg(dir)
+        .arg(dir.join("some").join("very").join("deeply"))
+        .arg(no_repo.path()))?;
+    assert_eq!(actual, expected);
+    Ok(())
+}
@@ -0,0 +1,97 @@
+/*
+ * Exercise each entry point of gix.h and print what was observed, one line per observation, for the test to compare.
+ *
+ * Usage: test <repository> <directory-in-repository> <directory-without-repository>
+ */
+#include <stdio.h>
+#include <string.h>
+
+#include "gix.h"
+
+static void print_id(const uint8_t *id) {
+    for (int i = 0; i < GIX_ID_LEN; i++) {
+        printf("%02x", id[i]);
+    }
+}
+
+static int print_reference(const uint8_t *name, size_t name_len, const uint8_t *id, const uint8_t *target,
+                           size_t target_len, void *user_data) {
+    (void)user_data;
+    printf("reference: %.*s ", (int)name_len, (const char *)name);
+    if (id != NULL) {
+        print_id(id);
+    } else {
+        printf("-> %.*s", (int)target_len, (const char *)target);
+    }
+    printf("\n");
+    return 0;
+}
+
+static int print_commit(const uint8_t *id, void *user_data) {
+    int *remaining = (int *)user_data;
+    printf("commit: ");
+    print_id(id);
+    printf("\n");
+    if (remaining != NULL) {
+        *remaining -= 1;
+        return *remaining == 0;
+    }
+    return 0;
+}
+
+int main(int argc, char **argv) {
+    if (argc != 4) {
+        fprintf(stderr, "usage: %s <repository> <directory-in-repository> <directory-without-repository>\n", argv[0]);
+        return 2;
+    }
+
+    gix_repository *repo = NULL;
+    printf("open: %d\n", gix_repository_open(argv[1], &repo));
+    printf("last error: %s\n", gix_last_error_message() == NULL ? "none" : "some");
+
+    gix_repository *discovered = NULL;
+    printf("discover: %d\n", gix_repository_discover(argv[2], &discovered));
+    gix_repository_free(discovered);
+
+    gix_repository *missing = NULL;
+    printf("open without repository: %d\n", gix_repository_open(argv[3], &missing));
+    printf("last error: %s\n", gix_last_error_message() == NULL ? "none" : "some");
+    printf("discover without repository: %d\n", gix_repository_discover(argv[3], &missing));
+    printf("handle: %s\n", missing == NULL ? "none" : "some");
+
+    uint8_t head[GIX_ID_LEN];
+    printf("resolve HEAD: %d\n", gix_revision_resolve(repo, "HEAD", head));
+    printf("HEAD: ");
+    print_id(head);
+    printf("\n");
+    uint8_t id[GIX_ID_LEN];
+    printf("resolve unknown: %d\n", gix_revision_resolve(repo, "does-not-exist", id));
+
+    gix_object *object = NULL;
+    printf("find HEAD: %d\n", gix_object_find(repo, head, &object));
+    const uint8_t *data = gix_object_data(object);
+    size_t size = gix_object_size(object);
+    const uint8_t *newline = memchr(data, '\n', size);
+    printf("object: %d %zu %.*s\n", gix_object_kind(object), size, (int)(newline - data), (const char *)data);
+    gix_object_free(object);
+
+    memset(id, 0x11, GIX_ID_LEN);
+    object = NULL;
+    printf("find missing: %d\n", gix_object_find(repo, id, &object));
+    printf("handle: %s\n", object == NULL ? "none" : "some");
+
+    printf("references: %d\n", gix_references_foreach(repo, NULL, print_reference, NULL));
+    printf("references prefixed: %d\n", gix_references_foreach(repo, "refs/tags/", print_reference, NULL));
+
+    printf("walk: %d\n", gix_history_walk(repo, head, print_commit, NULL));
+    int remaining = 1;
+    printf("walk stopped: %d\n", gix_history_walk(repo, head, print_commit, &remaining));
+    printf("walk missing: %d\n", gix_history_walk(repo, id, print_commit, NULL));
+
+    printf("NULL repository: %d\n", gix_revision_resolve(NULL, "HEAD", id));
+    printf("NULL callback: %d\n", gix_references_foreach(repo, NULL, NULL, NULL));
+    printf("last error: %s\n", gix_last_error_message());
+
+    gix_repository_free(repo);
+    return 0;
+}
@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use git_lock::acquire::Fail;
+
+#[test]
+fn stale_locks_and_tempfiles_are_removed_but_not_those_of_this_process() -> crate::Result {
+    let (repo, _keep) = crate::repo_rw("make_basic_repo.sh")?;
+    let git_dir = repo.git_dir();
+    for path in &["index.lock", "refs/heads/main.lock", "objects/pack/tmp_pack_123"] {
+        std::fs::write(git_dir.join(path), b"")?;
+    }
+    let _held = git_lock::Marker::acquire_to_hold_resource(git_dir.join("HEAD"), Fail::Immediately, None)?;
+
+    assert!(
+        repo.cleanup_stale_tempfiles(Duration::from_secs(60 * 60))?.is_empty(),
+        "files modified recently are kept"
+    );
+    let mut removed: Vec<_> = repo
+        .cleanup_stale_tempfiles(Duration::from_secs(0))?
+        .into_iter()
+        .map(|p| p.strip_prefix(git_dir).expect("within git dir").to_owned())
+        .collect();
+    removed.sort();
+    assert_eq!(
+        removed,
+        vec![
+            std::path::Path::new("index.lock"),
+            "objects/pack/tmp_pack_123".as_ref(),
+            "refs/heads/main.lock".as_ref()
+        ]
+    );
+    assert!(
+        git_dir.join("HEAD.lock").is_file(),
+        "locks held by this process are kept"
+    );
+    Ok(())
+}
@@ -0,0 +1,90 @@
+//! Assure commits and tags created with a deterministic policy don't depend on the time zone, and that the policy can be
+//! read from `SOURCE_DATE_EPOCH`.
+//!
+//! This is a test binary of its own as it changes the environment of the process, which would affect other tests.
+use git_repository as git;
+use git_repository::prelude::{ObjectAccessExt, RepositoryAccessExt};
+
+type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn policy() -> git::commit::Deterministic {
+    let identity = git::commit::Identity {
+        name: "Release Bot".into(),
+        email: "bot@example.com".into(),
+    };
+    git::commit::Deterministic {
+        author: identity.clone(),
+        committer: identity,
+        time: 946771200,
+    }
+}
+
+/// Return the ids of a commit and of an annotated tag pointing to it.
+fn commit_and_tag_in_new_repo(
+    policy: &git::commit::Deterministic,
+) -> Result<(git::hash::ObjectId, git::hash::ObjectId)> {
+    let tmp = tempfile::tempdir()?;
+    let repo = git::init(&tmp)?.into_easy();
+    let empty_tree_id = repo.write_object(git::objs::Tree::empty())?;
+    let commit_id = repo
+        .commit_deterministic("HEAD", policy, "release", empty_tree_id, git::commit::NO_PARENT_IDS)?
+        .detach();
+    let tag = repo.tag_deterministic(
+        "v1.0",
+        commit_id,
+        git::objs::Kind::Commit,
+        policy,
+        "the release",
+        git::refs::transaction::PreviousValue::MustNotExist,
+    )?;
+    let tag_id = tag.target().as_id().expect("peeled").to_owned();
+    let tag_object = repo.find_object(tag_id)?;
+    assert_eq!(tag_object.kind, git::objs::Kind::Tag);
+    assert_eq!(
+        tag_object.tag_iter().target_id(),
+        Some(commit_id),
+        "the tag points to the commit"
+    );
+    Ok((commit_id, tag_id))
+}
+
+#[test]
+fn the_same_commits_and_tags_are_created_in_any_time_zone_and_from_source_date_epoch() -> Result {
+    let policy = policy();
+    std::env::set_var("TZ", "America/Los_Angeles");
+    let first_ids = commit_and_tag_in_new_repo(&policy)?;
+    std::env::set_var("TZ", "Asia/Tokyo");
+    let second_ids = commit_and_tag_in_new_repo(&policy)?;
+    std::env::remove_var("TZ");
+    assert_eq!(first_ids, second_ids, "the time zone doesn't affect commit or tag ids");
+    assert_eq!(
+        policy.committer_signature().time.offset,
+        0,
+        "signatures are always in UTC"
+    );
+
+    std::env::set_var("SOURCE_DATE_EPOCH", policy.time.to_string());
+    let from_env = git::commit::Deterministic::from_env(policy.author.clone(), policy.committer.clone())?;
+    let committer = git::init(tempfile::tempdir()?)?.into_easy().committer()?;
+    std::env::set_var("SOURCE_DATE_EPOCH", "yesterday");
+    let invalid = git::commit::Deterministic::from_env(policy.author.clone(), policy.committer.clone());
+    std::env::remove_var("SOURCE_DATE_EPOCH");
+
+    assert_eq!(
+        from_env.as_ref(),
+        Some(&policy),
+        "the time is picked up from SOURCE_DATE_EPOCH"
+    );
+    assert_eq!(
+        committer.time.time, policy.time,
+        "the default committer uses SOURCE_DATE_EPOCH as well"
+    );
+    assert_eq!(commit_and_tag_in_new_repo(&from_env.expect("set"))?, first_ids);
+    assert!(invalid.is_err(), "SOURCE_DATE_EPOCH must be numeric");
+    assert_eq!(
+        git::commit::Deterministic::from_env(policy.author.clone(), policy.committer)?,
+        None,
+        "without SOURCE_DATE_EPOCH there is no policy"
+    );
+    Ok(())
+}
@@ -1,2 +1,3 @@
 mod object;
 mod reference;
+mod repository;
@@ -57,6 +57,243 @@ mod find {
     }
 }
 
+mod well_known {
+    use git_repository::prelude::{ObjectAccessExt, RepositoryAccessExt};
+
+    #[test]
+    fn empty_tree_and_blob_are_found_even_if_not_present() -> crate::Result {
+        let tmp = tempfile::tempdir()?;
+        let repo = git_repository::init_bare(&tmp)?.into_easy();
+        let hash_kind = repo.hash_kind()?;
+        for (id, kind) in [
+            (
+                git_repository::hash::ObjectId::empty_tree(hash_kind),
+                git_object::Kind::Tree,
+            ),
+            (
+                git_repository::hash::ObjectId::empty_blob(hash_kind),
+                git_object::Kind::Blob,
+            ),
+        ] {
+            assert!(repo.contains_object(id)?);
+            assert_eq!(repo.find_object_header(id)?, (kind, 0));
+            assert_eq!(repo.try_find_object_header(id)?, Some((kind, 0)));
+
+            let obj = repo.find_object(id)?;
+            assert_eq!((obj.kind, obj.data.len()), (kind, 0));
+            drop(obj);
+            let obj = repo.try_find_object(id)?.expect("always present");
+            assert_eq!((obj.kind, obj.data.len()), (kind, 0));
+        }
+
+        let tree = repo.empty_tree()?;
+        assert_eq!(tree.id, git_repository::hash::ObjectId::empty_tree(hash_kind));
+        assert!(tree.data.is_empty());
+        Ok(())
+    }
+
+    #[cfg(feature = "git-diff")]
+    #[test]
+    fn diffing_the_first_commit_against_the_empty_tree_lists_all_entries_as_added() -> crate::Result {
+        use git_repository::{
+            objs::TreeRefIter,
+            prelude::{ReferenceAccessExt, TreeIterExt},
+        };
+
+        let repo_path = git_testtools::scripted_fixture_repo_read_only("make_repo_with_nested_trees.sh")?;
+        let repo = git_repository::Repository::open(&repo_path)?.into_easy();
+
+        let tree_id = repo
+            .head()?
+            .into_fully_peeled_id()
+            .expect("born")?
+            .object()?
+            .commit_iter()
+            .tree_id()
+            .expect("tree to be set");
+        let tree = repo.find_object(tree_id)?.detach();
+        let empty_tree = repo.empty_tree()?.data.to_vec();
+
+        let mut recorder = git_diff::tree::Recorder::default();
+        TreeRefIter::from_bytes(&empty_tree).changes_needed(
+            TreeRefIter::from_bytes(&tree.data),
+            git_diff::tree::State::default(),
+            |oid, buf| {
+                let obj = repo.find_object(oid).ok()?;
+                buf.clear();
+                buf.extend_from_slice(&obj.data);
+                Some(TreeRefIter::from_bytes(buf))
+            },
+            &mut recorder,
+        )?;
+
+        let mut actual: Vec<_> = recorder
+            .records
+            .into_iter()
+            .map(|change| match change {
+                git_diff::tree::recorder::Change::Addition { entry_mode, oid, path } => {
+                    format!(
+                        ":000000 {:06o} {} {} A\t{}",
+                        entry_mode as u16,
+                        git_repository::hash::ObjectId::null(oid.kind()),
+                        oid,
+                        path
+                    )
+                }
+                change => panic!("only additions are expected, got {:?}", change),
+            })
+            .collect();
+        actual.sort();
+
+        let output = std::process::Command::new("git")
+            .args(["diff-tree", "--root", "-r", "-t", "--no-commit-id", "HEAD"])
+            .current_dir(&repo_path)
+            .output()?;
+        assert!(output.status.success());
+        let mut expected: Vec<_> = String::from_utf8(output.stdout)?
+            .lines()
+            .map(ToOwned::to_owned)
+            .collect();
+        expected.sort();
+
+        assert_eq!(
+            actual, expected,
+            "every entry, trees included, is listed as added like git does"
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "git-diff")]
+    #[test]
+    fn patch_of_the_first_commit_against_the_empty_tree_matches_git() -> crate::Result {
+        use git_repository::{
+            bstr::ByteSlice,
+            objs::TreeRefIter,
+            prelude::{ReferenceAccessExt, TreeIterExt},
+        };
+
+        let repo_path = git_testtools::scripted_fixture_repo_read_only("make_repo_with_nested_trees.sh")?;
+        let repo = git_repository::Repository::open(&repo_path)?.into_easy();
+        let tree_id = repo
+            .head()?
+            .into_fully_peeled_id()
+            .expect("born")?
+            .object()?
+            .commit_iter()
+            .tree_id()
+            .expect("tree to be set");
+        let tree = repo.find_object(tree_id)?.detach();
+        let empty_tree = repo.empty_tree()?.data.to_vec();
+
+        let find = |oid: &git_repository::hash::oid, buf: &mut Vec<u8>| -> Option<()> {
+            let obj = repo.find_object(oid).ok()?;
+            buf.clear();
+            buf.extend_from_slice(&obj.data);
+            Some(())
+        };
+        let mut patch = Vec::new();
+        TreeRefIter::from_bytes(&empty_tree).diff_to_patch(
+            TreeRefIter::from_bytes(&tree.data),
+            git_diff::tree::State::default(),
+            |oid, buf| find(oid, buf).map(move |_| TreeRefIter::from_bytes(buf)),
+            |oid, buf| find(oid, buf).map(move |_| git_repository::objs::BlobRef { data: buf }),
+            Default::default(),
+            &mut patch,
+        )?;
+
+        let output = std::process::Command::new("git")
+            .args([
+                "diff-tree",
+                "-p",
+                "--root",
+                "--full-index",
+                "--no-commit-id",
+                "--no-color",
+                "--no-ext-diff",
+                "--src-prefix=a/",
+                "--dst-prefix=b/",
+                "HEAD",
+            ])
+            .current_dir(&repo_path)
+            .output()?;
+        assert!(output.status.success());
+        assert_eq!(patch.as_bstr(), output.stdout.as_bstr());
+        Ok(())
+    }
+
+    #[cfg(feature = "git-diff")]
+    #[test]
+    fn patches_in_partial_clones_skip_missing_blobs_by_default() -> crate::Result {
+        use git_repository::{
+            bstr::ByteSlice,
+            objs::TreeRefIter,
+            prelude::{ReferenceAccessExt, TreeIterExt},
+        };
+
+        let dir = git_testtools::scripted_fixture_repo_read_only("make_shallow_and_partial_clones.sh")?;
+        let repo = git_repository::Repository::open(dir.join("partial"))?.into_easy();
+        let tree_data = |id| -> crate::Result<Vec<u8>> {
+            let tree_id = repo.find_object(id)?.commit_iter().tree_id().expect("tree to be set");
+            Ok(repo.find_object(tree_id)?.data.to_vec())
+        };
+        let commits = repo
+            .head()?
+            .into_fully_peeled_id()
+            .expect("born")?
+            .ancestors()?
+            .all()
+            .map(|id| id.map(|id| id.detach()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (previous, current) = (tree_data(commits[1])?, tree_data(commits[0])?);
+
+        let find = |oid: &git_repository::hash::oid, buf: &mut Vec<u8>| -> Option<()> {
+            let obj = repo.find_object(oid).ok()?;
+            buf.clear();
+            buf.extend_from_slice(&obj.data);
+            Some(())
+        };
+        let diff = |options, out: &mut Vec<u8>| {
+            TreeRefIter::from_bytes(&previous).diff_to_patch(
+                TreeRefIter::from_bytes(&current),
+                git_diff::tree::State::default(),
+                |oid, buf| find(oid, buf).map(move |_| TreeRefIter::from_bytes(buf)),
+                |oid, buf| find(oid, buf).map(move |_| git_repository::objs::BlobRef { data: buf }),
+                options,
+                out,
+            )
+        };
+
+        assert!(
+            diff(Default::default(), &mut Vec::new()).is_err(),
+            "missing blobs are an error unless configured otherwise"
+        );
+
+        let mut patch = Vec::new();
+        let outcome = diff(repo.repo.patch_options(), &mut patch)?;
+        let missing = &outcome.missing_blobs;
+        assert_eq!(
+            missing.len(),
+            3,
+            "both versions of 'file' and the new 'file-3' weren't fetched"
+        );
+        assert_eq!(
+            patch.as_bstr(),
+            format!(
+                "diff --git a/file b/file\nindex {}..{} 100644\n\
+                 diff --git a/file-3 b/file-3\nnew file mode 100644\nindex {}..{}\n",
+                missing[0],
+                missing[1],
+                git_repository::hash::ObjectId::null_sha1(),
+                missing[2],
+            )
+            .as_bytes()
+            .as_bstr(),
+            "only headers are written for files with missing blobs"
+        );
+        Ok(())
+    }
+}
+
 mod commit {
     use git_repository as git;
     use git_repository::prelude::{ObjectAccessExt, ReferenceAccessExt};
@@ -192,3 +429,263 @@ mod commit {
         Ok(())
     }
 }
+
+mod rev_parse {
+    use std::{path::Path, process::Command};
+
+    use git_repository as git;
+    use git_repository::{bstr::ByteSlice, easy::rev_parse::Error, hash::ObjectId, prelude::ObjectAccessExt};
+
+    fn repo() -> crate::Result<(git::Easy, std::path::PathBuf)> {
+        let dir = git_testtools::scripted_fixture_repo_read_only("make_repo_for_rev_parse.sh")?;
+        Ok((git::Repository::open(&dir)?.into_easy(), dir))
+    }
+
+    fn git(dir: &Path, args: &[&str]) -> crate::Result<Option<Vec<u8>>> {
+        let out = Command::new("git").args(args).current_dir(dir).output()?;
+        Ok(if out.status.success() { Some(out.stdout) } else { None })
+    }
+
+    fn git_rev_parse(dir: &Path, spec: &str) -> crate::Result<Option<ObjectId>> {
+        Ok(match git(dir, &["rev-parse", "--verify", "--quiet", spec])? {
+            Some(out) => Some(ObjectId::from_hex(out.trim_end())?),
+            None => None,
+        })
+    }
+
+    fn all_object_ids(dir: &Path) -> crate::Result<Vec<ObjectId>> {
+        let out = git(dir, &["cat-file", "--batch-all-objects", "--batch-check=%(objectname)"])?.expect("success");
+        Ok(out.lines().map(ObjectId::from_hex).collect::<Result<_, _>>()?)
+    }
+
+    #[test]
+    fn specs_resolve_like_git() -> crate::Result {
+        let (repo, dir) = repo()?;
+        for spec in [
+            "HEAD",
+            "@",
+            "main",
+            "heads/main",
+            "refs/heads/main",
+            "side",
+            "heads/side",
+            "other",
+            "origin",
+            "origin/main",
+            "remotes/origin/HEAD",
+            "v1",
+            "tags/v1",
+            "v1-nested",
+            "lightweight",
+            "HEAD^",
+            "HEAD~",
+            "HEAD^0",
+            "HEAD~0",
+            "@~1",
+            "HEAD^^",
+            "HEAD^^2",
+            "HEAD~1^3",
+            "HEAD~2",
+            "HEAD~1^2~1",
+            "main^1^1",
+            "v1^",
+            "v1~0",
+            "v1^{}",
+            "v1^{tag}",
+            "v1^{commit}",
+            "v1^{tree}",
+            "v1-nested^{tag}",
+            "v1-nested^{}",
+            "v1-nested^{commit}~1",
+            "tree-tag^{tree}",
+            "blob-tag^{blob}",
+            "blob-tag^{}",
+            "HEAD^{object}",
+            "HEAD^{commit}^{tree}",
+            "HEAD:",
+            "HEAD:a",
+            "HEAD:dir",
+            "HEAD:dir/",
+            "HEAD:dir/sub/c",
+            "HEAD^{tree}:dir/b",
+            "v1:dir/sub",
+            "HEAD~1^2:side",
+            "tree-tag:dir/sub/c",
+        ] {
+            let expected = git_rev_parse(&dir, spec)?.unwrap_or_else(|| panic!("git can resolve '{}'", spec));
+            assert_eq!(
+                repo.rev_parse(spec.into())?,
+                expected,
+                "'{}' resolves to the same object as with git",
+                spec
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn full_and_abbreviated_object_ids() -> crate::Result {
+        let (repo, dir) = repo()?;
+        for id in all_object_ids(&dir)?.into_iter().step_by(50) {
+            let hex = id.to_string();
+            let short = git(&dir, &["rev-parse", "--short=4", &hex])?.expect("success");
+            let short = short.trim_end().to_str()?;
+            for spec in [hex.as_str(), short, &short.to_ascii_uppercase()] {
+                assert_eq!(
+                    repo.rev_parse(spec.into())?,
+                    id,
+                    "'{}' resolves to the packed or loose object",
+                    spec
+                );
+            }
+        }
+
+        let missing = "ffffffffffffffffffffffffffffffffffffffff";
+        assert_eq!(
+            repo.rev_parse(missing.into())?,
+            ObjectId::from_hex(missing.as_bytes())?,
+            "full ids aren't checked for existence, just like in git"
+        );
+        assert!(matches!(
+            repo.rev_parse("ffffffffffffffffffffffffffffffffffffffff^{commit}".into()),
+            Err(Error::FindObject(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn ambiguous_prefixes_list_all_candidates() -> crate::Result {
+        let (repo, dir) = repo()?;
+        let ids = all_object_ids(&dir)?;
+        let prefix = ids
+            .windows(2)
+            .map(|pair| (pair[0].to_string(), pair[1].to_string()))
+            .find(|(lhs, rhs)| lhs[..4] == rhs[..4])
+            .map(|(lhs, _)| lhs[..4].to_owned())
+            .expect("the fixture contains enough objects for two to share a prefix of 4");
+        let expected: Vec<_> = ids
+            .iter()
+            .filter(|id| id.to_string().starts_with(&prefix))
+            .cloned()
+            .collect();
+
+        assert!(
+            git_rev_parse(&dir, &prefix)?.is_none(),
+            "git considers it ambiguous too"
+        );
+        match repo.rev_parse(prefix.as_str().into()) {
+            Err(Error::AmbiguousPrefix {
+                prefix: actual,
+                candidates,
+            }) => {
+                assert_eq!(actual, prefix);
+                assert_eq!(candidates, expected, "all candidates are listed in order");
+            }
+            res => panic!("expected ambiguity error, got {:?}", res.map(|id| id.detach())),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn errors_name_the_failing_component() -> crate::Result {
+        let (repo, dir) = repo()?;
+        let merge = git_rev_parse(&dir, "main~1")?.expect("merge exists");
+        let root = git_rev_parse(&dir, "main~3")?.expect("root exists");
+        let head_tree = git_rev_parse(&dir, "HEAD^{tree}")?.expect("tree exists");
+        type IsExpected = Box<dyn Fn(&Error) -> bool>;
+        let cases: Vec<(&str, IsExpected)> = vec![
+            (
+                "does-not-exist",
+                Box::new(|err| matches!(err, Error::UnknownName { name } if name == "does-not-exist")),
+            ),
+            (
+                "does-not-exist~1",
+                Box::new(|err| matches!(err, Error::UnknownName { name } if name == "does-not-exist")),
+            ),
+            (
+                "unborn-target",
+                Box::new(|err| {
+                    matches!(err, Error::UnbornReference { name, target }
+                        if name == "refs/heads/unborn-target" && target == "refs/heads/does-not-exist")
+                }),
+            ),
+            (
+                "HEAD^4",
+                Box::new(move |err| {
+                    matches!(err, Error::ParentOutOfRange { operator, id, index: 4, parents: 1 }
+                        if operator == "^4" && *id != merge)
+                }),
+            ),
+            (
+                "HEAD~1^4",
+                Box::new(move |err| {
+                    matches!(err, Error::ParentOutOfRange { operator, id, index: 4, parents: 3 }
+                        if operator == "^4" && *id == merge)
+                }),
+            ),
+            (
+                "main~100",
+                Box::new(move |err| {
+                    matches!(err, Error::ParentOutOfRange { operator, id, index: 1, parents: 0 }
+                        if operator == "~100" && *id == root)
+                }),
+            ),
+            (
+                "lightweight^{tag}",
+                Box::new(|err| matches!(err, Error::Peel { operator, .. } if operator == "^{tag}")),
+            ),
+            (
+                "tree-tag^",
+                Box::new(|err| matches!(err, Error::Peel { operator, .. } if operator == "^")),
+            ),
+            (
+                "blob-tag:a",
+                Box::new(|err| matches!(err, Error::Peel { operator, .. } if operator == ":a")),
+            ),
+            (
+                "HEAD:does-not-exist",
+                Box::new(
+                    move |err| matches!(err, Error::NoSuchPath { path, tree } if path == "does-not-exist" && *tree == head_tree),
+                ),
+            ),
+            (
+                "HEAD:a/b",
+                Box::new(|err| matches!(err, Error::NoSuchPath { path, .. } if path == "a/b")),
+            ),
+            (
+                "HEAD^{nope}",
+                Box::new(|err| matches!(err, Error::Malformed { component, .. } if component == "^{nope}")),
+            ),
+            (
+                "HEAD^{tree",
+                Box::new(|err| matches!(err, Error::Malformed { component, .. } if component == "^{tree")),
+            ),
+            (
+                "HEAD^x",
+                Box::new(|err| matches!(err, Error::Malformed { component, .. } if component == "x")),
+            ),
+            (
+                "~1",
+                Box::new(|err| matches!(err, Error::Malformed { component, .. } if component == "~1")),
+            ),
+        ];
+        for (spec, is_expected) in cases {
+            assert!(
+                git_rev_parse(&dir, spec)?.is_none(),
+                "git fails to resolve '{}' as well",
+                spec
+            );
+            match repo.rev_parse(spec.into()) {
+                Err(err) => assert!(is_expected(&err), "'{}' failed with unexpected error: {}", spec, err),
+                Ok(id) => panic!("'{}' unexpectedly resolved to {}", spec, id.detach()),
+            }
+        }
+
+        assert!(matches!(repo.rev_parse("".into()), Err(Error::Empty)));
+        assert!(
+            matches!(repo.rev_parse(":a".into()), Err(Error::IndexPath { path }) if path == "a"),
+            "paths in the index aren't supported"
+        );
+        Ok(())
+    }
+}
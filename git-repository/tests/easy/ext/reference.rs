@@ -149,6 +149,39 @@ mod iter_references {
         Ok(())
     }
 
+    #[test]
+    fn branches_and_tags() -> crate::Result {
+        let repo = repo()?;
+        let platform = repo.references()?;
+        let names = |iter: git::easy::reference::iter::Iter<'_, git::Easy>| {
+            iter.filter_map(Result::ok)
+                .map(|r| r.name().as_bstr().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(platform.local_branches()?),
+            vec![
+                "refs/heads/d1",
+                "refs/heads/dt1",
+                "refs/heads/main",
+                "refs/heads/multi-link-target1"
+            ]
+        );
+        assert_eq!(
+            names(platform.remote_branches()?),
+            vec![
+                "refs/remotes/origin/HEAD",
+                "refs/remotes/origin/main",
+                "refs/remotes/origin/multi-link-target3"
+            ]
+        );
+        assert_eq!(
+            names(platform.tags()?),
+            vec!["refs/tags/dt1", "refs/tags/multi-link-target2", "refs/tags/t1"]
+        );
+        Ok(())
+    }
+
     #[test]
     fn prefixed_and_peeled() -> crate::Result {
         let repo = repo()?;
@@ -183,6 +216,45 @@ mod iter_references {
         );
         Ok(())
     }
+
+    #[test]
+    fn symbolic_refs_are_returned_as_is() -> crate::Result {
+        let repo = repo()?;
+        let platform = repo.references()?;
+        let origin_head = platform
+            .prefixed("refs/remotes/origin/")?
+            .filter_map(Result::ok)
+            .find(|r| r.name().as_bstr() == "refs/remotes/origin/HEAD")
+            .expect("present");
+        assert_eq!(
+            origin_head.target().as_name().map(ToOwned::to_owned),
+            Some("refs/remotes/origin/main".into()),
+            "it is not resolved to its referent"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn loose_refs_shadow_packed_refs_of_the_same_name() -> crate::Result {
+        let (repo, keep) = crate::easy_repo_rw("make_references_repo.sh")?;
+        let loose_id = hex_to_id("4c3f4cce493d7beb45012e478021b5f65295e5a3");
+        std::fs::write(
+            keep.path().join(".git").join("refs").join("tags").join("t1"),
+            format!("{}\n", loose_id),
+        )?;
+
+        assert_eq!(
+            repo.references()?
+                .prefixed("refs/tags/")?
+                .filter_map(Result::ok)
+                .filter(|r| r.name().as_bstr() == "refs/tags/t1")
+                .map(|r| r.target().as_id().map(ToOwned::to_owned))
+                .collect::<Vec<_>>(),
+            vec![Some(loose_id)],
+            "only the loose ref is returned, the packed one is hidden"
+        );
+        Ok(())
+    }
 }
 
 mod head {
@@ -226,3 +298,964 @@ mod head {
         Ok(())
     }
 }
+
+mod iter_notes {
+    use std::convert::TryInto;
+
+    use git_repository::{easy::note::Note, prelude::*};
+
+    fn git(dir: &std::path::Path, args: &[&str]) -> crate::Result<String> {
+        let output = std::process::Command::new("git").args(args).current_dir(dir).output()?;
+        assert!(output.status.success(), "git {:?} failed", args);
+        Ok(String::from_utf8(output.stdout)?)
+    }
+
+    #[test]
+    fn notes_on_regular_and_fan_out_paths_are_returned_like_git_lists_them() -> crate::Result {
+        let dir = git_testtools::scripted_fixture_repo_read_only("make_repo_with_notes.sh")?;
+        let repo = git_repository::open(&dir)?.into_easy();
+        for (notes_ref, expected_count) in [(None, 2), (Some("review"), 1)] {
+            let mut args = vec!["notes"];
+            if let Some(name) = notes_ref {
+                args.extend(["--ref", name]);
+            }
+            args.push("list");
+            let mut expected: Vec<_> = git(&dir, &args)?
+                .lines()
+                .map(|line| {
+                    let (blob, object) = line.split_once(' ').expect("two ids");
+                    Note {
+                        annotated_object: git_repository::hash::ObjectId::from_hex(object.as_bytes()).expect("valid"),
+                        note_blob: git_repository::hash::ObjectId::from_hex(blob.as_bytes()).expect("valid"),
+                    }
+                })
+                .collect();
+            expected.sort();
+
+            let mut actual = repo
+                .iter_notes(notes_ref.map(|name| name.try_into().expect("valid")))?
+                .collect::<Result<Vec<_>, _>>()?;
+            actual.sort();
+            assert_eq!(actual.len(), expected_count);
+            assert_eq!(actual, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn missing_notes_references_have_no_notes() -> crate::Result {
+        let repo = crate::basic_repo()?;
+        assert_eq!(repo.iter_notes(None)?.count(), 0);
+        Ok(())
+    }
+}
+
+mod list_tags {
+    use git_repository::{easy::tag::Sort, prelude::*};
+
+    fn git_tags(dir: &std::path::Path, sort: &str) -> crate::Result<Vec<String>> {
+        let output = std::process::Command::new("git")
+            .arg("tag")
+            .arg(format!("--sort={}", sort))
+            .current_dir(dir)
+            .output()?;
+        assert!(output.status.success(), "git tag --sort={} failed", sort);
+        Ok(String::from_utf8(output.stdout)?
+            .lines()
+            .filter(|name| *name != "latest")
+            .map(ToOwned::to_owned)
+            .collect())
+    }
+
+    fn names(
+        tags: impl Iterator<Item = Result<git_repository::easy::tag::Tag, git_repository::easy::tag::Error>>,
+    ) -> crate::Result<Vec<String>> {
+        tags.map(|tag| {
+            Ok(tag?
+                .name
+                .as_bstr()
+                .to_string()
+                .trim_start_matches("refs/tags/")
+                .to_owned())
+        })
+        .collect()
+    }
+
+    #[test]
+    fn all_sort_orders_match_the_ones_of_git_tag() -> crate::Result {
+        let dir = git_testtools::scripted_fixture_repo_read_only("make_repo_with_tags_for_sorting.sh")?;
+        let repo = git_repository::open(&dir)?.into_easy();
+        for (sort, key, expected) in [
+            (
+                Sort::Refname,
+                "refname",
+                ["v1.10.0", "v1.2.0", "v1.2.0-pre1", "v1.2.0-rc1", "v1.9.0", "v2.0.0-rc1"],
+            ),
+            (
+                Sort::VersionRefname,
+                "version:refname",
+                ["v1.2.0-pre1", "v1.2.0-rc1", "v1.2.0", "v1.9.0", "v1.10.0", "v2.0.0-rc1"],
+            ),
+            (
+                Sort::CreatorDate,
+                "creatordate",
+                ["v1.2.0-pre1", "v1.2.0-rc1", "v1.10.0", "v2.0.0-rc1", "v1.2.0", "v1.9.0"],
+            ),
+        ] {
+            let actual = names(repo.list_tags()?.sort(sort).all()?)?;
+            assert_eq!(actual, expected, "{:?}", sort);
+            assert_eq!(
+                actual,
+                git_tags(&dir, key)?,
+                "symbolic tags are skipped, otherwise the order is the same as the one of git"
+            );
+        }
+        let default = names(repo.list_tags()?.all()?)?;
+        assert_eq!(
+            default,
+            names(repo.list_tags()?.sort(Sort::Refname).all()?)?,
+            "refname is the default"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn version_sort_suffixes_can_be_overridden() -> crate::Result {
+        let repo = crate::repo("make_repo_with_tags_for_sorting.sh")?.into_easy();
+        assert_eq!(
+            names(
+                repo.list_tags()?
+                    .sort(Sort::VersionRefname)
+                    .version_sort_suffixes(vec!["-rc", "-pre"])
+                    .all()?
+            )?,
+            ["v1.2.0-rc1", "v1.2.0-pre1", "v1.2.0", "v1.9.0", "v1.10.0", "v2.0.0-rc1"],
+            "the order of suffixes defines the order of pre-releases"
+        );
+        assert_eq!(
+            names(
+                repo.list_tags()?
+                    .sort(Sort::VersionRefname)
+                    .version_sort_suffixes(Vec::<&str>::new())
+                    .all()?
+            )?,
+            ["v1.2.0", "v1.2.0-pre1", "v1.2.0-rc1", "v1.9.0", "v1.10.0", "v2.0.0-rc1"],
+            "without suffixes, pre-releases sort after their release"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn version_cmp_compares_numbers_numerically() {
+        use std::cmp::Ordering::*;
+
+        use git_repository::easy::tag::version_cmp;
+        for (a, b, expected) in [
+            ("v1.2", "v1.10", Less),
+            ("v1.10", "v1.10", Equal),
+            ("v1.010", "v1.09", Less),
+            ("v1.09", "v1.0", Less),
+            ("v1.0", "v1.00", Greater),
+            ("a", "b", Less),
+            ("v1", "v1.0", Less),
+        ] {
+            assert_eq!(version_cmp(a.as_bytes(), b.as_bytes(), &[]), expected, "{} vs {}", a, b);
+        }
+    }
+
+    #[test]
+    fn names_with_the_same_suffix_are_compared_by_what_follows_it_like_git() -> crate::Result {
+        use std::cmp::Ordering::*;
+
+        use git_repository::easy::tag::version_cmp;
+        let suffixes = ["-rc".into()];
+        for (a, b, expected) in [
+            ("v1.0-rc2", "v1.0-rc10", Less),
+            ("v1.0-rc10", "v1.0", Less),
+            ("v1.0-rc2", "v1.0-rc2", Equal),
+        ] {
+            assert_eq!(
+                version_cmp(a.as_bytes(), b.as_bytes(), &suffixes),
+                expected,
+                "{} vs {}",
+                a,
+                b
+            );
+        }
+
+        let tmp = git_testtools::scripted_fixture_repo_writable("make_repo_with_tags_for_sorting.sh")?;
+        for tag in &["v1.2.0-rc2", "v1.2.0-rc10"] {
+            let status = std::process::Command::new("git")
+                .args(&["tag", tag])
+                .current_dir(tmp.path())
+                .status()?;
+            assert!(status.success());
+        }
+        let repo = git_repository::open(tmp.path())?.into_easy();
+        let actual = names(repo.list_tags()?.sort(Sort::VersionRefname).all()?)?;
+        assert_eq!(
+            actual,
+            [
+                "v1.2.0-pre1",
+                "v1.2.0-rc1",
+                "v1.2.0-rc2",
+                "v1.2.0-rc10",
+                "v1.2.0",
+                "v1.9.0",
+                "v1.10.0",
+                "v2.0.0-rc1"
+            ]
+        );
+        assert_eq!(actual, git_tags(tmp.path(), "version:refname")?);
+        Ok(())
+    }
+
+    #[test]
+    fn tags_without_a_creator_date_sort_as_if_created_at_the_epoch_like_git() -> crate::Result {
+        let tmp = git_testtools::scripted_fixture_repo_writable("make_repo_with_tags_for_sorting.sh")?;
+        for args in &[
+            &["tag", "z-tree", "HEAD^{tree}"][..],
+            &["tag", "a-blob", "HEAD:"][..],
+            &[
+                "-c",
+                "user.name=epoch",
+                "-c",
+                "user.email=epoch@example.com",
+                "tag",
+                "-m",
+                "epoch",
+                "m-epoch",
+            ][..],
+        ] {
+            let status = std::process::Command::new("git")
+                .args(*args)
+                .env("GIT_COMMITTER_DATE", "1970-01-01 00:00:00 +0000")
+                .current_dir(tmp.path())
+                .status()?;
+            assert!(status.success(), "git {:?} failed", args);
+        }
+        let repo = git_repository::open(tmp.path())?.into_easy();
+        let actual = names(repo.list_tags()?.sort(Sort::CreatorDate).all()?)?;
+        assert_eq!(
+            &actual[..3],
+            ["a-blob", "m-epoch", "z-tree"],
+            "tags without a date are ordered by name among the ones created at the epoch"
+        );
+        assert_eq!(actual, git_tags(tmp.path(), "creatordate")?);
+        Ok(())
+    }
+}
+
+mod containing {
+    use git_repository::prelude::*;
+
+    fn git(dir: &std::path::Path, args: &[&str]) -> crate::Result<Vec<String>> {
+        let output = std::process::Command::new("git").args(args).current_dir(dir).output()?;
+        assert!(output.status.success(), "git {:?} failed", args);
+        Ok(String::from_utf8(output.stdout)?
+            .lines()
+            .map(ToOwned::to_owned)
+            .collect())
+    }
+
+    fn names(outcome: &git_repository::easy::contains::Outcome) -> Vec<String> {
+        outcome.names.iter().map(|name| name.as_bstr().to_string()).collect()
+    }
+
+    #[test]
+    fn branches_and_tags_are_the_ones_listed_by_git() -> crate::Result {
+        let dir = git_testtools::scripted_fixture_repo_read_only("make_repo_for_contains.sh")?;
+        let repo = git_repository::open(&dir)?.into_easy();
+        let target = repo.find_reference("target")?.id().detach();
+
+        let branches = repo.branches_containing(target)?;
+        assert_eq!(
+            names(&branches),
+            ["refs/heads/feature-a", "refs/heads/main", "refs/heads/merged"]
+        );
+        assert_eq!(
+            names(&branches),
+            git(&dir, &["branch", "--contains", "target", "--format=%(refname)"])?
+        );
+        assert!(branches.statistics.used_commit_graph);
+        assert_eq!(
+            branches.statistics.commits_visited, 6,
+            "only the tips of all branches and the commits of their history above the target are visited"
+        );
+        assert_eq!(
+            branches.statistics.commits_cut_off, 2,
+            "the tips of 'old' and 'feature-b' are below the target's generation, and the other parent of the merge \
+             isn't needed once one parent is known to contain the target"
+        );
+        let commits_not_in_history_of_target =
+            git(&dir, &["rev-list", "--count", "--branches", "^target"])?[0].parse()?;
+        assert!(branches.statistics.commits_visited <= commits_not_in_history_of_target);
+
+        let tags = repo.tags_containing(target)?;
+        assert_eq!(names(&tags), ["refs/tags/nested", "refs/tags/target", "refs/tags/v1"]);
+        assert_eq!(
+            names(&tags),
+            git(&dir, &["tag", "--contains", "target", "--format=%(refname)"])?,
+            "tags pointing to trees are skipped and tags of tags are peeled"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn without_commit_graph_all_history_is_traversed() -> crate::Result {
+        let (repo, dir) = crate::easy_repo_rw("make_repo_for_contains.sh")?;
+        std::fs::remove_file(
+            dir.path()
+                .join(".git")
+                .join("objects")
+                .join("info")
+                .join("commit-graph"),
+        )?;
+        let target = repo.find_reference("target")?.id().detach();
+
+        let branches = repo.branches_containing(target)?;
+        assert_eq!(
+            names(&branches),
+            ["refs/heads/feature-a", "refs/heads/main", "refs/heads/merged"]
+        );
+        assert!(!branches.statistics.used_commit_graph);
+        assert_eq!(branches.statistics.commits_cut_off, 0);
+        assert!(
+            branches.statistics.commits_visited > 6,
+            "history below the target has to be traversed without generation numbers"
+        );
+        Ok(())
+    }
+}
+
+mod compare_refs {
+    use std::convert::TryInto;
+
+    use git_ref::{
+        transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+        FullName, Target,
+    };
+    use git_repository as git;
+    use git_repository::{easy::reference::compare, prelude::ReferenceAccessExt};
+
+    const SCOPE: &str = "refs/remotes/mirror/";
+
+    fn name(name: &str) -> FullName {
+        name.try_into().expect("valid name")
+    }
+
+    fn branch(number: usize) -> FullName {
+        name(&format!("{}branch-{:04}", SCOPE, number))
+    }
+
+    fn ids(repo: &git::Easy) -> crate::Result<(git::hash::ObjectId, git::hash::ObjectId)> {
+        Ok((
+            repo.find_reference("other")?.id().detach(),
+            repo.find_reference("main")?.id().detach(),
+        ))
+    }
+
+    /// All 2000 packed branches point to `c1` in the fixture, except for `branch-0002` which is shadowed by a loose ref
+    /// pointing to `c2`. Expect `branch-0003` to point to `c2`, `branch-0004` to be gone, `branch-2001` to exist,
+    /// and `HEAD` to point to `c1` directly.
+    fn expected(c1: git::hash::ObjectId, c2: git::hash::ObjectId) -> Vec<(FullName, git::hash::ObjectId)> {
+        (1..=2001)
+            .filter(|n| *n != 4)
+            .map(|n| (branch(n), if n == 3 { c2 } else { c1 }))
+            .chain(Some((name("refs/remotes/mirror/HEAD"), c1)))
+            .collect()
+    }
+
+    #[test]
+    fn differences_are_categorized_and_applied_in_one_transaction_until_references_converge() -> crate::Result {
+        let (repo, _keep) = crate::easy_repo_rw("make_repo_for_mirroring.sh")?;
+        let (c1, c2) = ids(&repo)?;
+
+        let diff = repo.compare_refs(expected(c1, c2), SCOPE)?;
+        assert_eq!(diff.missing, vec![(branch(2001), c1)]);
+        assert_eq!(
+            diff.extra,
+            vec![
+                (branch(4), Target::Peeled(c1)),
+                (name("refs/remotes/mirror/loose-only"), Target::Peeled(c2))
+            ]
+        );
+        assert_eq!(
+            diff.mismatched,
+            vec![
+                compare::Mismatch {
+                    name: name("refs/remotes/mirror/HEAD"),
+                    expected: c1,
+                    actual: Target::Symbolic(branch(1)),
+                },
+                compare::Mismatch {
+                    name: branch(2),
+                    expected: c1,
+                    actual: Target::Peeled(c2),
+                },
+                compare::Mismatch {
+                    name: branch(3),
+                    expected: c2,
+                    actual: Target::Peeled(c1),
+                }
+            ]
+        );
+
+        let log = LogChange {
+            mode: RefLog::AndReference,
+            force_create_reflog: false,
+            message: "mirror".into(),
+        };
+        let update = |name: FullName, expected: PreviousValue, new: git::hash::ObjectId| RefEdit {
+            change: Change::Update {
+                log: log.clone(),
+                expected,
+                new: Target::Peeled(new),
+            },
+            name,
+            deref: false,
+        };
+        let delete = |name: FullName, previous: git::hash::ObjectId| RefEdit {
+            change: Change::Delete {
+                expected: PreviousValue::MustExistAndMatch(Target::Peeled(previous)),
+                log: RefLog::AndReference,
+            },
+            name,
+            deref: false,
+        };
+        assert_eq!(
+            diff.clone().into_edits("mirror"),
+            vec![
+                update(
+                    name("refs/remotes/mirror/HEAD"),
+                    PreviousValue::MustExistAndMatch(Target::Symbolic(branch(1))),
+                    c1
+                ),
+                update(branch(2), PreviousValue::MustExistAndMatch(Target::Peeled(c2)), c1),
+                update(branch(3), PreviousValue::MustExistAndMatch(Target::Peeled(c1)), c2),
+                delete(branch(4), c1),
+                update(branch(2001), PreviousValue::MustNotExist, c1),
+                delete(name("refs/remotes/mirror/loose-only"), c2),
+            ],
+            "edits are sorted by name and expect the values that were compared"
+        );
+
+        let edits = repo.apply_ref_diff(diff, "mirror")?;
+        assert_eq!(edits.len(), 6, "symbolic refs aren't followed, so edits aren't split");
+        assert!(
+            repo.compare_refs(expected(c1, c2), SCOPE)?.is_empty(),
+            "after applying the difference, references are as expected"
+        );
+        assert_eq!(
+            repo.references()?.prefixed(SCOPE)?.count(),
+            2001,
+            "only the expected references are left"
+        );
+        assert_eq!(
+            repo.find_reference("main")?.id(),
+            c2,
+            "references outside of the scope are untouched"
+        );
+        assert_eq!(repo.find_reference("other")?.id(), c1);
+        Ok(())
+    }
+
+    #[test]
+    fn nothing_is_applied_if_a_reference_changed_since_it_was_compared() -> crate::Result {
+        let (repo, _keep) = crate::easy_repo_rw("make_repo_for_mirroring.sh")?;
+        let (c1, c2) = ids(&repo)?;
+
+        let diff = repo.compare_refs(expected(c1, c2), SCOPE)?;
+        repo.reference(
+            "refs/remotes/mirror/branch-0003",
+            c2,
+            PreviousValue::Any,
+            "concurrent change",
+        )?;
+        assert!(repo.apply_ref_diff(diff, "mirror").is_err());
+        assert!(
+            repo.try_find_reference(branch(2001).to_partial())?.is_none(),
+            "the missing reference wasn't created as the transaction failed as a whole"
+        );
+        assert!(repo.try_find_reference(branch(4).to_partial())?.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn matching_references_have_no_difference() -> crate::Result {
+        let repo = crate::repo("make_repo_for_mirroring.sh")?.into_easy();
+        let (c1, c2) = ids(&repo)?;
+        let diff = repo.compare_refs(
+            vec![(name("refs/heads/other"), c1), (name("refs/heads/main"), c2)],
+            "refs/heads/",
+        )?;
+        assert!(diff.is_empty());
+
+        let diff = repo.compare_refs(
+            vec![
+                (name("refs/heads/main"), c2),
+                (name("refs/heads/main"), c2),
+                (name("refs/heads/other"), c1),
+            ],
+            "refs/heads",
+        )?;
+        assert!(
+            diff.is_empty(),
+            "duplicate expectations are fine if they agree, and the trailing slash of the scope is optional"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn the_scope_is_a_directory() -> crate::Result {
+        let repo = crate::repo("make_repo_for_mirroring.sh")?.into_easy();
+        let (c1, c2) = ids(&repo)?;
+        assert!(
+            repo.try_find_reference("refs/remotes/mirror-old/packed")?.is_some()
+                && repo.try_find_reference("refs/remotes/mirror-old/loose")?.is_some()
+        );
+        let without_slash = repo.compare_refs(expected(c1, c2), "refs/remotes/mirror")?;
+        assert_eq!(
+            without_slash,
+            repo.compare_refs(expected(c1, c2), SCOPE)?,
+            "references in directories merely starting with the scope aren't extra"
+        );
+        assert!(matches!(
+            repo.compare_refs(Some((name("refs/heads/main"), c2)), "refs/heads/m"),
+            Err(compare::Error::OutOfScope { name, .. }) if name.as_bstr() == "refs/heads/main"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn expectations_must_be_within_the_scope_and_unambiguous() -> crate::Result {
+        let repo = crate::repo("make_repo_for_mirroring.sh")?.into_easy();
+        let (c1, c2) = ids(&repo)?;
+        assert!(matches!(
+            repo.compare_refs(Some((name("refs/heads/main"), c2)), SCOPE),
+            Err(compare::Error::OutOfScope { name, .. }) if name.as_bstr() == "refs/heads/main"
+        ));
+        assert!(matches!(
+            repo.compare_refs(vec![(name("refs/heads/main"), c2), (name("refs/heads/main"), c1)], ""),
+            Err(compare::Error::AmbiguousExpectation { name, .. }) if name.as_bstr() == "refs/heads/main"
+        ));
+        Ok(())
+    }
+}
+
+mod prepare_reference_transaction {
+    use std::{
+        convert::TryInto,
+        time::{Duration, Instant},
+    };
+
+    use git_ref::{
+        file::transaction::prepare,
+        transaction::{Change, LogChange, PreviousValue, RefEdit},
+        Target,
+    };
+    use git_repository as git;
+    use git_repository::{
+        easy::reference::{edit, transaction::commit},
+        lock::acquire::Fail,
+        prelude::ReferenceAccessExt,
+    };
+
+    fn update(name: &str, expected: PreviousValue, new: git::hash::ObjectId) -> crate::Result<RefEdit> {
+        Ok(RefEdit {
+            change: Change::Update {
+                log: LogChange::default(),
+                expected,
+                new: Target::Peeled(new),
+            },
+            name: name.try_into()?,
+            deref: false,
+        })
+    }
+
+    fn ids(dir: &std::path::Path) -> crate::Result<(git::hash::ObjectId, git::hash::ObjectId)> {
+        let out = std::process::Command::new("git")
+            .arg("rev-parse")
+            .arg("main")
+            .arg("main~1")
+            .current_dir(dir)
+            .output()?;
+        assert!(out.status.success());
+        let out = String::from_utf8(out.stdout)?;
+        let mut lines = out.lines();
+        Ok((
+            git::hash::ObjectId::from_hex(lines.next().expect("main").as_bytes())?,
+            git::hash::ObjectId::from_hex(lines.next().expect("parent").as_bytes())?,
+        ))
+    }
+
+    fn id_of(repo: &git::Easy, name: &str) -> crate::Result<Option<git::hash::ObjectId>> {
+        Ok(repo.try_find_reference(name)?.map(|r| r.id().detach()))
+    }
+
+    fn competing_update_of_main(repo: &git::Easy, new: git::hash::ObjectId) -> Result<Vec<RefEdit>, edit::Error> {
+        repo.edit_reference(
+            update("refs/heads/main", PreviousValue::Any, new).expect("valid name"),
+            Fail::Immediately,
+            None,
+        )
+    }
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    #[test]
+    fn locks_are_held_until_the_transaction_is_committed_even_on_another_thread() -> crate::Result {
+        let (repo, dir) = crate::basic_rw_repo()?;
+        let (main, parent) = ids(dir.path())?;
+
+        let prepared = repo.prepare_reference_transaction(
+            vec![
+                update(
+                    "refs/heads/main",
+                    PreviousValue::MustExistAndMatch(Target::Peeled(main)),
+                    parent,
+                )?,
+                update("refs/heads/new", PreviousValue::MustNotExist, main)?,
+            ],
+            Fail::Immediately,
+            None,
+        )?;
+        assert_send(&prepared);
+        assert!(!prepared.is_expired());
+
+        assert!(
+            matches!(
+                competing_update_of_main(&repo, main),
+                Err(edit::Error::FileTransactionPrepare(prepare::Error::LockAcquire { .. }))
+            ),
+            "competing transactions can't acquire the locks held by the prepared one"
+        );
+        assert_eq!(
+            id_of(&repo, "main")?,
+            Some(main),
+            "nothing is visible before the commit"
+        );
+        assert_eq!(id_of(&repo, "new")?, None);
+
+        let edits = std::thread::spawn(move || prepared.commit())
+            .join()
+            .expect("no panic")?;
+        assert_eq!(edits.len(), 2);
+        assert_eq!(
+            id_of(&repo, "main")?,
+            Some(parent),
+            "the edits are visible after the commit"
+        );
+        assert_eq!(id_of(&repo, "new")?, Some(main));
+
+        competing_update_of_main(&repo, main)?;
+        Ok(())
+    }
+
+    #[test]
+    fn transactions_are_rolled_back_explicitly_when_dropped_or_once_they_expire() -> crate::Result {
+        let (repo, dir) = crate::basic_rw_repo()?;
+        let (main, parent) = ids(dir.path())?;
+        let prepare = || {
+            repo.prepare_reference_transaction(
+                Some(update("refs/heads/main", PreviousValue::Any, parent).expect("valid name")),
+                Fail::Immediately,
+                None,
+            )
+        };
+
+        prepare()?.rollback();
+        assert_eq!(id_of(&repo, "main")?, Some(main));
+        drop(prepare()?);
+        assert_eq!(id_of(&repo, "main")?, Some(main));
+
+        let prepared = prepare()?.rollback_after(Duration::from_millis(10));
+        let start = Instant::now();
+        while !prepared.is_expired() {
+            assert!(start.elapsed() < Duration::from_secs(10), "expiry takes too long");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        competing_update_of_main(&repo, main)?;
+        assert!(
+            matches!(prepared.commit(), Err(commit::Error::Expired)),
+            "expired transactions can't be committed"
+        );
+        assert_eq!(id_of(&repo, "main")?, Some(main));
+
+        prepare()?.rollback_after(Duration::from_secs(60)).commit()?;
+        assert_eq!(
+            id_of(&repo, "main")?,
+            Some(parent),
+            "transactions can be committed before they expire"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn validation_failures_are_reported_on_prepare_and_release_all_locks() -> crate::Result {
+        let (repo, dir) = crate::basic_rw_repo()?;
+        let (main, parent) = ids(dir.path())?;
+        assert!(repo
+            .prepare_reference_transaction(
+                vec![
+                    update("refs/heads/new", PreviousValue::MustNotExist, main)?,
+                    update(
+                        "refs/heads/main",
+                        PreviousValue::MustExistAndMatch(Target::Peeled(parent)),
+                        main
+                    )?,
+                ],
+                Fail::Immediately,
+                None,
+            )
+            .is_err());
+        assert_eq!(id_of(&repo, "new")?, None);
+        competing_update_of_main(&repo, parent)?;
+        Ok(())
+    }
+
+    #[test]
+    fn all_edits_are_recorded_in_the_journal_configured_on_the_store() -> crate::Result {
+        let (mut repo, dir) = crate::repo_rw("make_basic_repo.sh")?;
+        let (main, parent) = ids(dir.path())?;
+        repo.refs.journal = Some(Default::default());
+        let refs = repo.refs.clone();
+        let repo = repo.into_easy();
+
+        competing_update_of_main(&repo, parent)?;
+        repo.prepare_reference_transaction(
+            Some(update("refs/heads/new", PreviousValue::MustNotExist, main)?),
+            Fail::Immediately,
+            None,
+        )?
+        .commit()?;
+        drop(repo.prepare_reference_transaction(
+            Some(update("refs/heads/aborted", PreviousValue::MustNotExist, main)?),
+            Fail::Immediately,
+            None,
+        )?);
+
+        assert_eq!(
+            refs.journal_records(..)?
+                .into_iter()
+                .map(|r| (r.name.as_bstr().to_string(), r.new))
+                .collect::<Vec<_>>(),
+            vec![
+                ("refs/heads/main".into(), Some(parent)),
+                ("refs/heads/new".into(), Some(main))
+            ]
+        );
+        Ok(())
+    }
+}
+
+mod replacements {
+    use git_repository as git;
+    use git_repository::{
+        easy::replace::{self, Error, Options, Replacement},
+        hash::ObjectId,
+        prelude::*,
+    };
+
+    fn repo_rw() -> crate::Result<(git::Easy, tempfile::TempDir)> {
+        crate::easy_repo_rw("make_repo_for_replacing.sh")
+    }
+
+    fn id(repo: &git::Easy, name: &str) -> crate::Result<ObjectId> {
+        Ok(repo.find_reference(name)?.id().detach())
+    }
+
+    fn git(dir: &std::path::Path, args: &[&str]) -> crate::Result<Vec<String>> {
+        let output = std::process::Command::new("git").args(args).current_dir(dir).output()?;
+        assert!(output.status.success(), "git {:?} failed", args);
+        Ok(String::from_utf8(output.stdout)?
+            .lines()
+            .map(ToOwned::to_owned)
+            .collect())
+    }
+
+    fn ancestors_with_replacements(repo: &git::Easy, tip: ObjectId) -> crate::Result<Vec<String>> {
+        let map = repo.list_replacements()?;
+        let ids = git_traverse::commit::Ancestors::new(
+            Some(tip),
+            git_traverse::commit::ancestors::State::default(),
+            |id, buf| {
+                let id = map.resolve(id.to_owned(), replace::MAX_DEPTH).ok()?;
+                let object = repo.find_object(id).ok()?;
+                buf.clear();
+                buf.extend_from_slice(&object.data);
+                Some(git::objs::CommitRefIter::from_bytes(buf))
+            },
+        )
+        .collect::<Result<Vec<_>, _>>()?;
+        Ok(ids.into_iter().map(|id| id.to_string()).collect())
+    }
+
+    #[test]
+    fn created_replacements_are_followed_by_traversals_like_git_does() -> crate::Result {
+        let (repo, dir) = repo_rw()?;
+        let (original, replacement) = (id(&repo, "c2")?, id(&repo, "c2-alt")?);
+        let tip = id(&repo, "main")?;
+        assert_eq!(ancestors_with_replacements(&repo, tip)?.len(), 4);
+
+        let reference = repo.create_replacement(original, replacement, Options::default())?;
+        assert_eq!(reference.name().as_bstr(), format!("refs/replace/{}", original));
+        assert_eq!(reference.id(), replacement);
+        assert_eq!(
+            repo.list_replacements()?.iter().collect::<Vec<_>>(),
+            vec![Replacement { original, replacement }]
+        );
+        assert_eq!(
+            git(dir.path(), &["replace", "--list", "--format=medium"])?,
+            vec![format!("{} -> {}", original, replacement)],
+            "git sees the replacement as well"
+        );
+
+        let ancestors = ancestors_with_replacements(&repo, tip)?;
+        assert_eq!(
+            ancestors,
+            git(dir.path(), &["rev-list", "main"])?,
+            "the parent-less replacement cuts off the history below it"
+        );
+        assert_eq!(ancestors.len(), 3);
+
+        assert!(
+            matches!(
+                repo.create_replacement(original, id(&repo, "d1")?, Options::default()),
+                Err(Error::AlreadyReplaced { replacement: existing, .. }) if existing == replacement
+            ),
+            "existing replacements are not overwritten"
+        );
+
+        assert_eq!(repo.delete_replacement(original)?, Some(replacement));
+        assert_eq!(repo.delete_replacement(original)?, None, "it's gone");
+        assert!(repo.list_replacements()?.is_empty());
+        assert_eq!(ancestors_with_replacements(&repo, tip)?.len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn objects_cannot_replace_themselves_or_objects_of_other_kinds_unless_configured() -> crate::Result {
+        let (repo, _dir) = repo_rw()?;
+        let (commit, blob) = (id(&repo, "c1")?, id(&repo, "blob")?);
+        assert!(matches!(
+            repo.create_replacement(commit, commit, Options::default()),
+            Err(Error::SelfReplacement { id }) if id == commit
+        ));
+        assert!(matches!(
+            repo.create_replacement(commit, blob, Options::default()),
+            Err(Error::KindMismatch {
+                original_kind: git::objs::Kind::Commit,
+                replacement_kind: git::objs::Kind::Blob,
+                ..
+            })
+        ));
+        assert!(
+            matches!(
+                repo.create_replacement(commit, ObjectId::null_sha1(), Options::default()),
+                Err(Error::FindExistingObject(_))
+            ),
+            "both objects must exist"
+        );
+        assert!(repo.list_replacements()?.is_empty(), "nothing was written");
+
+        repo.create_replacement(
+            commit,
+            blob,
+            Options {
+                allow_kind_change: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(repo.list_replacements()?.get(commit), Some(blob));
+        Ok(())
+    }
+
+    #[test]
+    fn chains_beyond_the_maximum_depth_and_cycles_are_refused() -> crate::Result {
+        let (repo, _dir) = repo_rw()?;
+        let options = Options {
+            max_depth: 2,
+            ..Default::default()
+        };
+        let [d1, d2, d3, d4] = [id(&repo, "d1")?, id(&repo, "d2")?, id(&repo, "d3")?, id(&repo, "d4")?];
+        repo.create_replacement(d2, d3, options)?;
+        repo.create_replacement(d1, d2, options)?;
+        assert_eq!(repo.list_replacements()?.resolve(d1, options.max_depth)?, d3);
+
+        assert!(
+            matches!(
+                repo.create_replacement(d3, d4, options),
+                Err(Error::ChainTooDeep { original, max_depth: 2 }) if original == d3
+            ),
+            "d1 -> d2 -> d3 -> d4 is too long when appending"
+        );
+        assert!(
+            matches!(
+                repo.create_replacement(d4, d1, options),
+                Err(Error::ChainTooDeep { original, max_depth: 2 }) if original == d4
+            ),
+            "d4 -> d1 -> d2 -> d3 is too long when prepending"
+        );
+        assert!(matches!(
+            repo.create_replacement(d3, d1, Options::default()),
+            Err(Error::Cycle { original, replacement }) if original == d3 && replacement == d1
+        ));
+
+        repo.create_replacement(d3, d4, Options::default())?;
+        let map = repo.list_replacements()?;
+        assert_eq!(map.len(), 3);
+        assert!(matches!(
+            map.resolve(d1, options.max_depth),
+            Err(Error::ChainTooDeep { .. })
+        ));
+        assert_eq!(map.resolve(d1, replace::MAX_DEPTH)?, d4);
+        Ok(())
+    }
+
+    #[test]
+    fn chains_are_limited_by_default_to_the_depth_git_follows() -> crate::Result {
+        let (repo, dir) = repo_rw()?;
+        let ids = ["d1", "d2", "d3", "d4", "d5", "c2-alt"]
+            .iter()
+            .map(|name| id(&repo, name))
+            .collect::<Result<Vec<_>, _>>()?;
+        for pair in ids[..5].windows(2) {
+            repo.create_replacement(pair[0], pair[1], Options::default())?;
+        }
+        assert_eq!(repo.list_replacements()?.resolve(ids[0], replace::MAX_DEPTH)?, ids[4]);
+        assert_eq!(
+            git(dir.path(), &["cat-file", "commit", "d1"])?
+                .last()
+                .map(String::as_str),
+            Some("d5"),
+            "git follows the longest chain we allow to create"
+        );
+
+        assert!(matches!(
+            repo.create_replacement(ids[4], ids[5], Options::default()),
+            Err(Error::ChainTooDeep {
+                max_depth: replace::MAX_DEPTH,
+                ..
+            })
+        ));
+        git(
+            dir.path(),
+            &["update-ref", &format!("refs/replace/{}", ids[4]), &ids[5].to_string()],
+        )?;
+        assert!(matches!(
+            repo.list_replacements()?.resolve(ids[0], replace::MAX_DEPTH),
+            Err(Error::ChainTooDeep { .. })
+        ));
+        let output = std::process::Command::new("git")
+            .args(&["cat-file", "commit", "d1"])
+            .current_dir(dir.path())
+            .output()?;
+        assert!(
+            !output.status.success(),
+            "git refuses to follow the chain we refused to create"
+        );
+        assert!(String::from_utf8_lossy(&output.stderr).contains("replace depth too high"));
+        Ok(())
+    }
+}
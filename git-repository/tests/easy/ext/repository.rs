@@ -0,0 +1,698 @@
+mod create_bundle {
+    use std::path::Path;
+
+    use git_repository::{bundle::Bundle, easy, prelude::*};
+
+    fn git(dir: &Path, args: &[&str]) -> crate::Result<String> {
+        let output = std::process::Command::new("git").args(args).current_dir(dir).output()?;
+        if !output.status.success() {
+            return Err(format!("git {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr)).into());
+        }
+        Ok(String::from_utf8(output.stdout)?.trim_end().to_owned())
+    }
+
+    fn rev_parse(dir: &Path, spec: &str) -> crate::Result<git_hash::ObjectId> {
+        Ok(git_hash::ObjectId::from_hex(
+            git(dir, &["rev-parse", spec])?.as_bytes(),
+        )?)
+    }
+
+    #[test]
+    fn full_bundles_can_be_cloned_by_git() -> crate::Result {
+        let repo = crate::basic_repo()?;
+        let repo_dir = repo.repo.work_tree.clone().expect("non-bare");
+        let main = rev_parse(&repo_dir, "main")?;
+
+        let writer = repo.create_bundle(&["main", "HEAD"], None)?;
+        assert_eq!(
+            writer.refs,
+            vec![(main, "refs/heads/main".into()), (main, "HEAD".into())]
+        );
+        assert!(writer.prerequisites.is_empty());
+        assert_eq!(writer.num_entries, 2 * 3, "two commits, trees and blobs");
+
+        let tmp = tempfile::tempdir()?;
+        let bundle_path = tmp.path().join("full.bundle");
+        writer.write_to(std::io::BufWriter::new(std::fs::File::create(&bundle_path)?))?;
+
+        git(tmp.path(), &["clone", "-q", "full.bundle", "clone"])?;
+        let clone = tmp.path().join("clone");
+        assert_eq!(rev_parse(&clone, "HEAD")?, main);
+        assert_eq!(git(&clone, &["fsck", "--no-dangling"])?, "");
+        assert_eq!(std::fs::read(clone.join("this"))?, b"hello\n");
+        Ok(())
+    }
+
+    #[test]
+    fn incremental_bundles_only_contain_new_objects() -> crate::Result {
+        let repo = crate::basic_repo()?;
+        let repo_dir = repo.repo.work_tree.clone().expect("non-bare");
+        let (main, base) = (rev_parse(&repo_dir, "main")?, rev_parse(&repo_dir, "main~1")?);
+
+        let writer = repo.create_bundle(&["refs/heads/main"], Some(base))?;
+        assert_eq!(writer.prerequisites.len(), 1);
+        assert_eq!(writer.prerequisites[0].id, base);
+        assert_eq!(writer.prerequisites[0].comment, "c1");
+        assert_eq!(writer.num_entries, 3, "the new commit, its tree and the changed blob");
+
+        let mut buf = Vec::new();
+        writer.write_to(&mut buf)?;
+        let bundle = Bundle::from_reader(buf.as_slice())?;
+        assert_eq!(bundle.refs, vec![(main, "refs/heads/main".into())]);
+
+        let tmp = tempfile::tempdir()?;
+        let bundle_path = tmp.path().join("incremental.bundle");
+        std::fs::write(&bundle_path, buf)?;
+        git(
+            &repo_dir,
+            &["bundle", "verify", "-q", bundle_path.to_str().expect("valid UTF-8")],
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn annotated_tags_and_symbolic_refs() -> crate::Result {
+        let repo: git_repository::Easy = crate::repo("make_references_repo.sh")?.into();
+        let repo_dir = repo.repo.git_dir().to_owned();
+        let tag = rev_parse(&repo_dir, "refs/tags/dt1")?;
+        let main = rev_parse(&repo_dir, "main")?;
+        assert_ne!(tag, main, "the tag is annotated");
+
+        let writer = repo.create_bundle(&["dt1", "refs/multi-link"], None)?;
+        assert_eq!(
+            writer.refs,
+            vec![(tag, "refs/tags/dt1".into()), (main, "refs/multi-link".into())],
+            "tag objects are not peeled, symbolic refs are resolved"
+        );
+        assert_eq!(writer.num_entries, 3, "the tag, the commit and its empty tree");
+
+        assert!(matches!(
+            repo.create_bundle(&[], None),
+            Err(easy::bundle::create::Error::Empty)
+        ));
+        assert!(matches!(
+            repo.create_bundle(&["refs/loop-a"], None),
+            Err(easy::bundle::create::Error::UnbornReference { .. })
+        ));
+        Ok(())
+    }
+
+    mod deterministic {
+        use git_repository::{prelude::*, Repository, Threads};
+
+        fn bundle(mut repo: Repository, threads: Threads, deterministic: bool) -> crate::Result<(u32, Vec<u8>)> {
+            repo.set_object_access_parallelism(threads)
+                .set_deterministic_packs(deterministic);
+            let writer = repo.into_easy().create_bundle(&["main"], None)?;
+            let num_entries = writer.num_entries;
+            let mut buf = Vec::new();
+            writer.write_to(&mut buf)?;
+            Ok((num_entries, buf))
+        }
+
+        #[test]
+        fn the_same_objects_yield_the_same_bundle_no_matter_their_storage_or_the_threads() -> crate::Result {
+            assert!(
+                !crate::repo("make_basic_repo.sh")?.deterministic_packs(),
+                "it's off by default"
+            );
+            let (packed, _keep) = crate::repo_rw("make_basic_repo.sh")?;
+            let packed_dir = packed.work_tree.clone().expect("non-bare");
+            drop(packed);
+            super::git(&packed_dir, &["repack", "-adq"])?;
+
+            let loose = bundle(crate::repo("make_basic_repo.sh")?, Threads::None, true)?;
+            let packed = bundle(Repository::open(&packed_dir)?, Threads::Fixed(4), true)?;
+            assert_eq!(loose.0, packed.0);
+            assert!(loose.1 == packed.1, "bundles are byte-identical");
+
+            let loose_default = bundle(crate::repo("make_basic_repo.sh")?, Threads::None, false)?;
+            let packed_default = bundle(Repository::open(&packed_dir)?, Threads::Fixed(4), false)?;
+            assert_eq!(
+                loose_default.0, packed_default.0,
+                "the same objects are bundled either way"
+            );
+            assert_eq!(loose_default.0, loose.0);
+            Ok(())
+        }
+    }
+}
+
+mod abbreviation {
+    use std::process::Command;
+
+    use git_repository as git;
+    use git_repository::prelude::{ReferenceAccessExt, RepositoryAccessExt};
+
+    fn open_with_core_abbrev(
+        value: &str,
+    ) -> crate::Result<(std::result::Result<git::Easy, git::open::Error>, tempfile::TempDir)> {
+        let dir = git_testtools::scripted_fixture_repo_writable("make_basic_repo.sh")?;
+        assert!(Command::new("git")
+            .args(["config", "core.abbrev", value])
+            .current_dir(dir.path())
+            .status()?
+            .success());
+        let res = git::Repository::open(dir.path()).map(git::Repository::into_easy);
+        Ok((res, dir))
+    }
+
+    #[test]
+    fn defaults_to_7_in_small_repositories() -> crate::Result {
+        assert_eq!(crate::basic_repo()?.abbreviation()?, 7);
+        assert_eq!(open_with_core_abbrev("auto")?.0?.abbreviation()?, 7);
+        Ok(())
+    }
+
+    #[test]
+    fn core_abbrev_is_used_as_configured() -> crate::Result {
+        let (repo, _keep) = open_with_core_abbrev("12")?;
+        let repo = repo?;
+        assert_eq!(repo.abbreviation()?, 12);
+        let head = repo.head()?.into_fully_peeled_id().expect("born")?;
+        assert_eq!(
+            head.shorten()?.to_string(),
+            std::str::from_utf8(&head.to_sha1_hex()[..12])?
+        );
+        assert_eq!(
+            head.shorten_to(5)?.hex_len,
+            5,
+            "the configuration can be overridden per call"
+        );
+
+        for no in &["false", "no", "off"] {
+            assert_eq!(
+                open_with_core_abbrev(no)?.0?.abbreviation()?,
+                40,
+                "{} disables abbreviation",
+                no
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn out_of_range_values_are_rejected_on_open() -> crate::Result {
+        for invalid in &["3", "41", "seven"] {
+            assert!(matches!(
+                open_with_core_abbrev(invalid)?.0,
+                Err(git::open::Error::InvalidAbbreviation { value }) if value == *invalid
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(feature = "unstable", feature = "blocking-network-client"))]
+mod protocol_version {
+    use std::process::Command;
+
+    use git_repository as git;
+    use git_repository::{
+        prelude::{ReferenceAccessExt, RepositoryAccessExt},
+        protocol::transport::{
+            self,
+            client::{Transport, TransportWithoutIO},
+        },
+    };
+
+    fn open_with_protocol_version(
+        value: Option<&str>,
+    ) -> crate::Result<(std::result::Result<git::Easy, git::open::Error>, tempfile::TempDir)> {
+        let dir = git_testtools::scripted_fixture_repo_writable("make_basic_repo.sh")?;
+        if let Some(value) = value {
+            assert!(Command::new("git")
+                .arg("config")
+                .arg("protocol.version")
+                .arg(value)
+                .current_dir(dir.path())
+                .status()?
+                .success());
+        }
+        let res = git::Repository::open(dir.path()).map(git::Repository::into_easy);
+        Ok((res, dir))
+    }
+
+    #[test]
+    fn defaults_to_v2_and_is_requested_from_the_server() -> crate::Result {
+        let (repo, dir) = open_with_protocol_version(None)?;
+        let version = repo?.protocol_version()?;
+        assert_eq!(version, transport::Protocol::V2);
+
+        let mut c = transport::connect(
+            dir.path().join(".git").to_str().expect("valid UTF-8").as_bytes(),
+            version,
+        )?;
+        let res = c.handshake(transport::Service::UploadPack, &[])?;
+        assert_eq!(
+            res.version_request,
+            transport::client::VersionRequest::EnvironmentVariable
+        );
+        assert_eq!(res.actual_protocol, transport::Protocol::V2);
+        drop(res);
+        c.request(
+            transport::client::WriteMode::Binary,
+            transport::client::MessageKind::Flush,
+        )?
+        .into_read()?;
+        Ok(())
+    }
+
+    #[test]
+    fn protocol_version_is_used_as_configured() -> crate::Result {
+        for (value, expected) in &[
+            ("0", transport::Protocol::V1),
+            ("1", transport::Protocol::V1),
+            ("2", transport::Protocol::V2),
+        ] {
+            assert_eq!(
+                open_with_protocol_version(Some(value))?.0?.protocol_version()?,
+                *expected
+            );
+        }
+
+        let (repo, dir) = open_with_protocol_version(Some("1"))?;
+        let mut c = transport::connect(
+            dir.path().join(".git").to_str().expect("valid UTF-8").as_bytes(),
+            repo?.protocol_version()?,
+        )?;
+        let res = c.handshake(transport::Service::UploadPack, &[])?;
+        assert_eq!(res.version_request, transport::client::VersionRequest::None);
+        assert_eq!(
+            res.actual_protocol,
+            transport::Protocol::V1,
+            "servers can be debugged by forcing V1"
+        );
+        drop(res);
+        c.request(
+            transport::client::WriteMode::Binary,
+            transport::client::MessageKind::Flush,
+        )?
+        .into_read()?;
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_values_are_rejected_when_connecting_but_do_not_prevent_opening() -> crate::Result {
+        for invalid in &["3", "two"] {
+            let (repo, _dir) = open_with_protocol_version(Some(invalid))?;
+            let repo = repo?;
+            assert!(
+                repo.head()?.into_fully_peeled_id().is_some(),
+                "the repository can be used for anything but connecting"
+            );
+            assert!(matches!(
+                repo.protocol_version(),
+                Err(git::easy::protocol::version::Error::Invalid { value }) if value == *invalid
+            ));
+        }
+        Ok(())
+    }
+}
+
+mod maintenance {
+    use std::path::{Path, PathBuf};
+
+    use git_repository as git;
+    use git_repository::{
+        easy::{
+            maintenance::{Limits, Mode, Outcome, Policy},
+            Access,
+        },
+        prelude::{ObjectAccessExt, RepositoryAccessExt},
+    };
+
+    const FIXTURE: &str = "make_repo_for_maintenance.sh";
+
+    fn open(base: &Path, name: &str) -> crate::Result<git::Easy> {
+        Ok(git::Repository::open(base.join(name))?.into_easy())
+    }
+
+    fn objects_dir(base: &Path, name: &str) -> PathBuf {
+        base.join(name).join(".git").join("objects")
+    }
+
+    fn loose_objects(base: &Path, name: &str) -> crate::Result<usize> {
+        let mut count = 0;
+        for entry in std::fs::read_dir(objects_dir(base, name))? {
+            let entry = entry?;
+            if entry.file_name().len() == 2 && entry.file_type()?.is_dir() {
+                count += std::fs::read_dir(entry.path())?.count();
+            }
+        }
+        Ok(count)
+    }
+
+    fn packs(base: &Path, name: &str) -> crate::Result<Vec<PathBuf>> {
+        let mut out = Vec::new();
+        for entry in std::fs::read_dir(objects_dir(base, name).join("pack"))? {
+            let path = entry?.path();
+            if path.extension().map_or(false, |ext| ext == "pack") {
+                out.push(path);
+            }
+        }
+        out.sort();
+        Ok(out)
+    }
+
+    fn git(dir: &Path, args: &[&str]) -> crate::Result<String> {
+        let output = std::process::Command::new("git").args(args).current_dir(dir).output()?;
+        if !output.status.success() {
+            return Err(format!("git {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr)).into());
+        }
+        Ok(String::from_utf8(output.stdout)?)
+    }
+
+    fn all_objects(dir: &Path) -> crate::Result<String> {
+        git(dir, &["cat-file", "--batch-all-objects", "--batch-check"])
+    }
+
+    fn inline() -> Policy {
+        Policy {
+            mode: Mode::Inline,
+            quiet: false,
+        }
+    }
+
+    #[test]
+    fn heuristics_compare_sampled_loose_objects_and_packs_to_the_configured_limits() -> crate::Result {
+        let base = git_testtools::scripted_fixture_repo_read_only(FIXTURE)?;
+
+        let estimate = open(&base, "loose")?.maintenance_needed()?;
+        assert_eq!(
+            estimate.sampled_loose_objects, 3,
+            "603 loose objects, with 3 of them in objects/17"
+        );
+        assert_eq!(estimate.loose_objects(), 3 * 256);
+        assert_eq!(estimate.packs, 0);
+        assert_eq!(
+            estimate.limits,
+            Limits {
+                loose_objects: Some(256),
+                packs: Some(50)
+            }
+        );
+        assert!(estimate.too_many_loose_objects(), "more than one object in objects/17");
+        assert!(!estimate.too_many_packs());
+        assert!(estimate.is_needed());
+
+        let estimate = open(&base, "below-limits")?.maintenance_needed()?;
+        assert_eq!(estimate.sampled_loose_objects, 3);
+        assert_eq!(estimate.limits, Limits::default());
+        assert!(
+            !estimate.is_needed(),
+            "up to 27 objects in objects/17 are tolerated by default"
+        );
+
+        let estimate = open(&base, "disabled")?.maintenance_needed()?;
+        assert_eq!(estimate.limits.loose_objects, None);
+        assert!(!estimate.is_needed(), "gc.auto = 0 disables maintenance");
+
+        let estimate = open(&base, "packs")?.maintenance_needed()?;
+        assert_eq!(estimate.packs, 3, "the pack with .keep file isn't counted");
+        assert_eq!(estimate.limits.packs, Some(2));
+        assert!(!estimate.too_many_loose_objects());
+        assert!(estimate.too_many_packs());
+        assert!(estimate.is_needed());
+        Ok(())
+    }
+
+    #[test]
+    fn limits_too_large_to_be_represented_are_clamped() -> crate::Result {
+        let base = git_testtools::scripted_fixture_repo_writable(FIXTURE)?;
+        let dir = base.path().join("loose");
+        git(&dir, &["config", "gc.auto", "9223372036854775807g"])?;
+        git(&dir, &["config", "gc.autoPackLimit", "4611686018427387904k"])?;
+        let estimate = open(base.path(), "loose")?.maintenance_needed()?;
+        assert_eq!(
+            estimate.limits,
+            Limits {
+                loose_objects: Some(usize::MAX),
+                packs: Some(usize::MAX)
+            }
+        );
+        assert!(!estimate.is_needed());
+        Ok(())
+    }
+
+    #[test]
+    fn maintenance_runs_only_if_limits_are_crossed() -> crate::Result {
+        let base = git_testtools::scripted_fixture_repo_writable(FIXTURE)?;
+        for name in &["below-limits", "disabled"] {
+            assert!(
+                matches!(
+                    open(base.path(), name)?.auto_maintenance(inline())?,
+                    Outcome::NotNeeded(_)
+                ),
+                "{}",
+                name
+            );
+            assert_eq!(loose_objects(base.path(), name)?, 603, "{}: nothing changed", name);
+            assert!(packs(base.path(), name)?.is_empty(), "{}", name);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn loose_objects_are_packed_and_removed() -> crate::Result {
+        let base = git_testtools::scripted_fixture_repo_writable(FIXTURE)?;
+        let dir = base.path().join("loose");
+        let objects_before = all_objects(&dir)?;
+        let repo = git::Repository::open(&dir)?.into_easy_arc_exclusive();
+        let blob = repo.rev_parse("HEAD:file".into())?.detach();
+
+        let statistics = match repo.auto_maintenance(inline())? {
+            Outcome::Completed(statistics) => statistics,
+            outcome => panic!("unexpected outcome: {:?}", outcome),
+        };
+        assert_eq!(statistics.packed_objects, 603);
+        assert_eq!(statistics.removed_loose_objects, 603);
+        assert_eq!(statistics.removed_packs, 0);
+        assert_eq!(packs(base.path(), "loose")?.len(), 1);
+        assert_eq!(
+            statistics.index_path.expect("a pack was written"),
+            packs(base.path(), "loose")?[0].with_extension("idx")
+        );
+        assert_eq!(loose_objects(base.path(), "loose")?, 0);
+        assert!(
+            !dir.join(".git").join("maintenance.lock").exists(),
+            "the guard file is removed"
+        );
+        assert_eq!(
+            std::fs::read_dir(objects_dir(base.path(), "loose").join("pack"))?.count(),
+            2,
+            "only the new pack and its index remain, the file the pack was streamed to is removed"
+        );
+
+        assert_eq!(all_objects(&dir)?, objects_before, "all objects are still present");
+        assert_eq!(git(&dir, &["fsck", "--no-dangling"])?, "");
+        assert_eq!(
+            &repo.find_object(blob)?.data[..],
+            b"content\n",
+            "the object database is refreshed to find the new pack"
+        );
+        assert!(!repo.maintenance_needed()?.is_needed());
+        assert!(matches!(repo.auto_maintenance(inline())?, Outcome::NotNeeded(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn written_objects_and_packs_are_verified_if_configured() -> crate::Result {
+        let base = git_testtools::scripted_fixture_repo_writable(FIXTURE)?;
+        let dir = base.path().join("loose");
+        let mut repo = git::Repository::open(&dir)?;
+        assert_eq!(repo.object_write_verification(), git_odb::Verify::Never, "the default");
+        repo.set_object_write_verification(git_odb::Verify::Always);
+        let repo = repo.into_easy_arc_exclusive();
+
+        let blob = repo.write_object(&git::objs::Blob {
+            data: b"verified\n".to_vec(),
+        })?;
+        assert_eq!(&repo.find_object(blob)?.data[..], b"verified\n");
+
+        let statistics = match repo.auto_maintenance(inline())? {
+            Outcome::Completed(statistics) => statistics,
+            outcome => panic!("unexpected outcome: {:?}", outcome),
+        };
+        assert_eq!(statistics.packed_objects, 604, "the verified pack is kept");
+        assert_eq!(loose_objects(base.path(), "loose")?, 0);
+        assert_eq!(git(&dir, &["fsck", "--no-dangling"])?, "");
+        Ok(())
+    }
+
+    #[test]
+    fn too_many_packs_are_combined_into_one_except_for_kept_ones() -> crate::Result {
+        let base = git_testtools::scripted_fixture_repo_writable(FIXTURE)?;
+        let dir = base.path().join("packs");
+        let objects_before = all_objects(&dir)?;
+        let kept = packs(base.path(), "packs")?
+            .into_iter()
+            .find(|pack| pack.with_extension("keep").is_file())
+            .expect("one kept pack");
+
+        let statistics = match open(base.path(), "packs")?.auto_maintenance(inline())? {
+            Outcome::Completed(statistics) => statistics,
+            outcome => panic!("unexpected outcome: {:?}", outcome),
+        };
+        assert_eq!(
+            statistics.packed_objects,
+            3 * 3 + 1,
+            "commit, tree and blob of the three packs without .keep file, and the loose blob"
+        );
+        assert_eq!(statistics.removed_loose_objects, 1);
+        assert_eq!(statistics.removed_packs, 3);
+
+        let packs_after = packs(base.path(), "packs")?;
+        assert_eq!(packs_after.len(), 2, "the kept pack and the new one");
+        assert!(packs_after.contains(&kept));
+        assert_eq!(all_objects(&dir)?, objects_before);
+        assert_eq!(git(&dir, &["fsck", "--no-dangling"])?, "");
+        assert!(!open(base.path(), "packs")?.maintenance_needed()?.is_needed());
+        Ok(())
+    }
+
+    #[test]
+    fn a_guard_file_prevents_concurrent_runs_and_maintenance_can_run_in_the_background() -> crate::Result {
+        let base = git_testtools::scripted_fixture_repo_writable(FIXTURE)?;
+        let guard = base.path().join("loose").join(".git").join("maintenance.lock");
+        std::fs::write(&guard, b"")?;
+        let repo = open(base.path(), "loose")?;
+        assert!(matches!(repo.auto_maintenance(inline())?, Outcome::AlreadyRunning));
+        assert_eq!(loose_objects(base.path(), "loose")?, 603, "nothing changed");
+
+        std::fs::remove_file(&guard)?;
+        let handle = match repo.auto_maintenance(Policy::default())? {
+            Outcome::Started(handle) => handle,
+            outcome => panic!("unexpected outcome: {:?}", outcome),
+        };
+        let statistics = handle.join().expect("no panic")?;
+        assert_eq!(statistics.removed_loose_objects, 603);
+        assert_eq!(loose_objects(base.path(), "loose")?, 0);
+        assert!(
+            !guard.exists(),
+            "the guard file is removed once the background thread is done"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn failures_are_returned_in_the_outcome_if_quiet_and_leave_all_objects_in_place() -> crate::Result {
+        let base = git_testtools::scripted_fixture_repo_writable(FIXTURE)?;
+        let sample_dir = objects_dir(base.path(), "loose").join("17");
+        let corrupt = std::fs::read_dir(&sample_dir)?.next().expect("sampled objects")?.path();
+        std::fs::write(&corrupt, b"not a zlib stream")?;
+
+        let repo = open(base.path(), "loose")?;
+        assert!(matches!(
+            repo.auto_maintenance(Policy {
+                mode: Mode::Inline,
+                quiet: true
+            })?,
+            Outcome::Failed(_)
+        ));
+        assert!(
+            repo.auto_maintenance(inline()).is_err(),
+            "errors fail the call unless quiet"
+        );
+        assert_eq!(loose_objects(base.path(), "loose")?, 603);
+        assert!(packs(base.path(), "loose")?.is_empty());
+        assert!(!base.path().join("loose").join(".git").join("maintenance.lock").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn other_handles_find_all_objects_after_maintenance_by_refreshing_their_stale_object_database() -> crate::Result {
+        let base = git_testtools::scripted_fixture_repo_writable(FIXTURE)?;
+        let dir = base.path().join("packs");
+        let ids = all_objects(&dir)?
+            .lines()
+            .map(|line| git_hash::ObjectId::from_hex(&line.as_bytes()[..40]))
+            .collect::<Result<Vec<_>, _>>()?;
+        let repo = git::Repository::open(&dir)?.into_easy_arc_exclusive();
+        let other = git::Repository::open(&dir)?.into_easy_arc_exclusive();
+        for id in &ids {
+            repo.find_object(*id)?;
+        }
+        assert!(!other.refresh_object_database_if_stale()?);
+
+        assert!(matches!(
+            open(base.path(), "packs")?.auto_maintenance(inline())?,
+            Outcome::Completed(_)
+        ));
+        assert!(
+            other.refresh_object_database_if_stale()?,
+            "packs were removed by another handle"
+        );
+        for id in &ids {
+            assert!(
+                repo.find_object(*id).is_ok(),
+                "previously loose objects and those in removed packs are found after refreshing"
+            );
+        }
+        assert!(!repo.refresh_object_database_if_stale()?);
+        Ok(())
+    }
+
+    #[test]
+    fn all_handles_refresh_their_stale_object_database_when_looking_up_objects_after_repacking() -> crate::Result {
+        let base = git_testtools::scripted_fixture_repo_writable(FIXTURE)?;
+        let dir = base.path().join("packs");
+        git(&dir, &["multi-pack-index", "write"])?;
+        let easy = open(base.path(), "packs")?;
+        let arc = git::Repository::open(&dir)?.into_easy_arc();
+        let exclusive = git::Repository::open(&dir)?.into_easy_arc_exclusive();
+        assert!(
+            objects_dir(base.path(), "packs")
+                .join("pack/multi-pack-index")
+                .is_file(),
+            "the handles loaded a multi-pack index"
+        );
+
+        std::fs::write(dir.join("file"), "c5")?;
+        git(&dir, &["add", "file"])?;
+        git(
+            &dir,
+            &[
+                "-c",
+                "user.name=name",
+                "-c",
+                "user.email=name@example.com",
+                "commit",
+                "-q",
+                "-m",
+                "c5",
+            ],
+        )?;
+        git(&dir, &["repack", "-adq"])?;
+        git(&dir, &["prune-packed"])?;
+        assert_eq!(
+            loose_objects(base.path(), "packs")?,
+            1,
+            "only the unreachable blob is left, and the new commit is in the new pack the handles don't know"
+        );
+        let ids = all_objects(&dir)?
+            .lines()
+            .map(|line| git_hash::ObjectId::from_hex(&line.as_bytes()[..40]))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        fn assert_all_found(handle: &impl ObjectAccessExt, ids: &[git_hash::ObjectId]) -> crate::Result {
+            for id in ids {
+                assert!(handle.try_find_object_header(*id)?.is_some());
+                assert!(handle.find_object(*id).is_ok());
+            }
+            Ok(())
+        }
+        assert_all_found(&easy, &ids)?;
+        assert_all_found(&arc, &ids)?;
+        {
+            let _shared_borrow = exclusive.repo()?;
+            assert_all_found(&exclusive, &ids)?;
+        }
+        assert!(
+            exclusive.refresh_object_database_if_stale()?,
+            "the repository couldn't be borrowed mutably, so the handle used an object database of its own"
+        );
+        assert_all_found(&exclusive, &ids)?;
+        Ok(())
+    }
+}
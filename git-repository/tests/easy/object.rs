@@ -17,3 +17,309 @@ fn oid_size_in_memory() {
         "the size of this structure should not changed unexpectedly"
     )
 }
+
+mod typed_access {
+    use git_repository::{
+        easy::object::{try_into, Kind},
+        prelude::{ObjectAccessExt, ReferenceAccessExt},
+    };
+
+    #[test]
+    fn wrong_kinds_are_errors() -> crate::Result {
+        let repo = crate::basic_repo()?;
+        let id = repo.head()?.into_fully_peeled_id().expect("born")?.detach();
+        let tree_id = repo.find_object(id)?.commit_iter().tree_id().expect("valid commit");
+        assert_eq!(repo.find_object(id)?.try_into_commit()?.tree, tree_id);
+        for (res, expected) in [
+            (repo.find_object(id)?.try_into_tree().map(|_| ()), Kind::Tree),
+            (repo.find_object(id)?.try_into_tag().map(|_| ()), Kind::Tag),
+            (repo.find_object(id)?.try_into_blob().map(|_| ()), Kind::Blob),
+        ] {
+            assert!(matches!(
+                res,
+                Err(try_into::Error::WrongKind { expected: e, actual: Kind::Commit, id: err_id }) if e == expected && err_id == id
+            ));
+        }
+
+        let commit = repo.find_object(id)?.detach();
+        assert_eq!(commit.clone().try_into_commit()?.tree, tree_id);
+        let err = commit.clone().try_into_blob().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Object {} was expected to be a blob but was a commit", commit.id)
+        );
+        assert!(commit.clone().try_into_tree().is_err());
+        assert!(commit.try_into_tag().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn peel_to_tree_and_commit() -> crate::Result {
+        let repo = crate::basic_repo()?;
+        let commit = repo.head()?.into_fully_peeled_id().expect("born")?.object()?;
+        let tree_id = commit.commit_iter().tree_id().expect("valid commit");
+        let commit = commit.peel_to_commit()?;
+        assert_eq!(commit.kind, Kind::Commit, "commits are already peeled");
+
+        let tree = commit.peel_to_tree()?;
+        assert_eq!(tree.id, tree_id);
+        let blob_id = git_repository::objs::TreeRefIter::from_bytes(&tree.data)
+            .filter_map(Result::ok)
+            .find(|entry| entry.filename == "this")
+            .expect("present")
+            .oid
+            .to_owned();
+        drop(tree);
+
+        let blob = repo.find_object(blob_id)?;
+        assert_eq!(blob.detach().try_into_blob()?.data, b"hello\n");
+        let blob = repo.find_object(blob_id)?;
+        assert!(matches!(
+            blob.peel_to_tree(),
+            Err(git_repository::easy::object::peel::to_kind::Error::NotFound {
+                actual: Kind::Blob,
+                expected: Kind::Tree
+            })
+        ));
+        Ok(())
+    }
+}
+
+mod header {
+    use git_repository::{
+        easy::object::Kind,
+        prelude::{ObjectAccessExt, ReferenceAccessExt},
+    };
+
+    #[test]
+    fn kind_and_size_without_decoding() -> crate::Result {
+        let repo = crate::basic_repo()?;
+        let commit_id = repo.head()?.into_fully_peeled_id().expect("born")?.detach();
+        assert!(repo.contains_object(commit_id)?);
+        let commit = repo.find_object(commit_id)?;
+        let expected = (Kind::Commit, commit.data.len() as u64);
+        drop(commit);
+        assert_eq!(repo.find_object_header(commit_id)?, expected);
+        assert_eq!(repo.try_find_object_header(commit_id)?, Some(expected));
+
+        let missing = git_repository::hash::ObjectId::from_hex(b"ffffffffffffffffffffffffffffffffffffffff")?;
+        assert!(!repo.contains_object(missing)?);
+        assert_eq!(repo.try_find_object_header(missing)?, None);
+        assert!(repo.find_object_header(missing).is_err());
+        Ok(())
+    }
+}
+
+#[cfg(feature = "git-diff")]
+mod diff_blob {
+    use git_diff::blob::{Algorithm, Header, Outcome, Sink};
+    use git_repository::{
+        bstr::{BStr, BString},
+        easy::object::{try_into, Kind},
+        prelude::{ObjectAccessExt, ReferenceAccessExt},
+    };
+
+    #[derive(Default)]
+    struct Lines(Vec<(char, BString)>);
+
+    impl Sink for Lines {
+        type Out = Vec<(char, BString)>;
+
+        fn hunk(&mut self, header: Header) {
+            self.0.push(('@', header.to_string().into()));
+        }
+        fn context(&mut self, lines: &BStr) {
+            self.0.push((' ', lines.into()));
+        }
+        fn removed(&mut self, lines: &BStr) {
+            self.0.push(('-', lines.into()));
+        }
+        fn added(&mut self, lines: &BStr) {
+            self.0.push(('+', lines.into()));
+        }
+        fn finish(self) -> Self::Out {
+            self.0
+        }
+    }
+
+    fn blob_of_this_at(repo: &git_repository::Easy, rev: &str) -> crate::Result<git_repository::hash::ObjectId> {
+        let tree = repo
+            .find_reference(rev)?
+            .into_fully_peeled_id()?
+            .object()?
+            .peel_to_tree()?;
+        Ok(git_repository::objs::TreeRefIter::from_bytes(&tree.data)
+            .filter_map(Result::ok)
+            .find(|entry| entry.filename == "this")
+            .expect("present")
+            .oid
+            .to_owned())
+    }
+
+    #[test]
+    fn lines_of_two_blobs() -> crate::Result {
+        let repo = crate::basic_repo()?;
+        let new_id = blob_of_this_at(&repo, "refs/heads/main")?;
+        let other_repo = repo.clone();
+        let old = other_repo.find_object(git_repository::hash::ObjectId::empty_blob(
+            git_repository::hash::Kind::Sha1,
+        ))?;
+        let new = repo.find_object(new_id)?;
+
+        let out = old.diff_blob(&new, Algorithm::Myers, 3, Lines::default())?;
+        assert_eq!(
+            out,
+            Outcome::Text(vec![('@', "@@ -0,0 +1 @@".into()), ('+', "hello\n".into())])
+        );
+        assert_eq!(
+            new.diff_blob(&new, Algorithm::Histogram, 3, Lines::default())?,
+            Outcome::Text(vec![]),
+            "identical blobs have no hunks"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn non_blobs_are_errors() -> crate::Result {
+        let repo = crate::basic_repo()?;
+        let other_repo = repo.clone();
+        let commit = repo.head()?.into_fully_peeled_id().expect("born")?.object()?;
+        let blob = other_repo.find_object(blob_of_this_at(&other_repo, "refs/heads/main")?)?;
+        let err = blob
+            .diff_blob(&commit, Algorithm::Myers, 3, Lines::default())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            try_into::Error::WrongKind {
+                actual: Kind::Commit,
+                expected: Kind::Blob,
+                ..
+            }
+        ));
+        Ok(())
+    }
+}
+
+mod lookup_entry_by_path {
+    use git_repository::{
+        easy::object::{tree::lookup, try_into, Kind},
+        objs::tree::EntryMode,
+        prelude::ReferenceAccessExt,
+        Easy,
+    };
+
+    fn repo() -> crate::Result<Easy> {
+        crate::repo("make_repo_with_large_tree.sh").map(|r| r.into_easy())
+    }
+
+    #[test]
+    fn entries_of_large_trees_are_found_in_git_order() -> crate::Result {
+        let repo = repo()?;
+        let commit = repo.head()?.into_fully_peeled_id().expect("born")?.object()?;
+        let tree = commit.peel_to_kind(Kind::Tree)?;
+        assert_eq!(tree.entries()?.count(), 1205);
+
+        for i in 1000..2200 {
+            let name = format!("file-{}", i);
+            let entry = tree.lookup_entry_by_path(&name)?.expect("present");
+            assert_eq!(entry.filename, name.as_str());
+            assert_eq!(entry.mode, EntryMode::Blob);
+        }
+        for (name, mode) in [
+            ("a", EntryMode::Tree),
+            ("a.b", EntryMode::Blob),
+            ("a0", EntryMode::Blob),
+        ] {
+            let entry = tree.lookup_entry_by_path(name)?.expect("present");
+            assert_eq!((entry.filename.as_slice(), entry.mode), (name.as_bytes(), mode));
+        }
+        assert!(tree.lookup_entry_by_path("file-999")?.is_none());
+        assert!(tree.lookup_entry_by_path("file-2200")?.is_none());
+
+        let entry = tree.lookup_entry_by_path("./a/b/c.txt")?.expect("present");
+        assert_eq!(entry.mode, EntryMode::Blob);
+        drop(tree);
+        assert_eq!(&*entry.oid.object()?.data, b"content\n");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_names_are_compared_as_bytes() -> crate::Result {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+        let repo = repo()?;
+        let tree = repo
+            .head()?
+            .into_fully_peeled_id()
+            .expect("born")?
+            .object()?
+            .peel_to_kind(Kind::Tree)?;
+        let entry = tree
+            .lookup_entry_by_path(OsStr::from_bytes(b"caf\xe9"))?
+            .expect("present");
+        assert_eq!(entry.filename, &b"caf\xe9"[..]);
+        Ok(())
+    }
+
+    #[test]
+    fn submodules_are_returned_but_not_descended_into() -> crate::Result {
+        let repo = repo()?;
+        let tree = repo
+            .head()?
+            .into_fully_peeled_id()
+            .expect("born")?
+            .object()?
+            .peel_to_kind(Kind::Tree)?;
+        let entry = tree.lookup_entry_by_path("submodule")?.expect("present");
+        assert_eq!(entry.mode, EntryMode::Commit);
+        assert_eq!(
+            entry.oid.detach(),
+            git_repository::hash::ObjectId::from_hex(b"0123456789012345678901234567890123456789")?
+        );
+
+        assert!(matches!(
+            tree.lookup_entry_by_path("submodule/file"),
+            Err(lookup::Error::Submodule { path }) if path == "submodule"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn missing_intermediate_directories_and_non_trees_yield_none() -> crate::Result {
+        let repo = repo()?;
+        let tree = repo
+            .head()?
+            .into_fully_peeled_id()
+            .expect("born")?
+            .object()?
+            .peel_to_kind(Kind::Tree)?;
+        assert!(tree.lookup_entry_by_path("missing/c.txt")?.is_none());
+        assert!(tree.lookup_entry_by_path("a/missing/c.txt")?.is_none());
+        assert!(
+            tree.lookup_entry_by_path("a.b/c.txt")?.is_none(),
+            "blobs can't be descended into"
+        );
+        assert!(tree.lookup_entry_by_path("a/b/c.txt/d")?.is_none());
+        assert!(matches!(
+            tree.lookup_entry_by_path("../a"),
+            Err(lookup::Error::InvalidComponent { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn non_trees_are_errors() -> crate::Result {
+        let repo = repo()?;
+        let commit = repo.head()?.into_fully_peeled_id().expect("born")?.object()?;
+        match commit.lookup_entry_by_path("a") {
+            Err(lookup::Error::NotATree(try_into::Error::WrongKind {
+                actual: Kind::Commit,
+                expected: Kind::Tree,
+                ..
+            })) => {}
+            _ => unreachable!("commits aren't trees"),
+        }
+        assert!(commit.entries().is_err());
+        Ok(())
+    }
+}
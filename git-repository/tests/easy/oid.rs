@@ -16,4 +16,581 @@ mod ancestors {
         );
         Ok(())
     }
+
+    mod missing {
+        use git_repository as git;
+        use git_repository::{
+            easy::oid::ancestors::Missing,
+            prelude::{ObjectAccessExt, ReferenceAccessExt},
+        };
+
+        fn repo(name: &str) -> crate::Result<git::Easy> {
+            let dir = git_testtools::scripted_fixture_repo_read_only("make_shallow_and_partial_clones.sh")?;
+            Ok(git::Repository::open(dir.join(name))?.into_easy())
+        }
+
+        fn messages(repo: &git::Easy, missing: Option<Missing>) -> crate::Result<Vec<String>> {
+            let head = repo.head()?.into_fully_peeled_id().expect("born")?;
+            let mut ancestors = head.ancestors()?;
+            if let Some(missing) = missing {
+                ancestors = ancestors.missing(missing);
+            }
+            let ids = ancestors
+                .all()
+                .map(|id| id.map(|id| id.detach()))
+                .collect::<Result<Vec<_>, _>>()?;
+            ids.into_iter()
+                .map(|id| {
+                    let object = repo.find_object(id)?;
+                    let message = object.commit()?.message.to_string();
+                    Ok(message.trim_end().into())
+                })
+                .collect()
+        }
+
+        #[test]
+        fn shallow_boundaries_are_treated_as_roots_by_default() -> crate::Result {
+            let repo = repo("shallow")?;
+            assert!(repo.repo.is_shallow());
+            assert!(!repo.repo.is_partial());
+            assert_eq!(messages(&repo, None)?, vec!["c3", "c2"]);
+
+            let page = repo
+                .head()?
+                .into_fully_peeled_id()
+                .expect("born")?
+                .ancestors()?
+                .page()?;
+            assert_eq!(page.commits.len(), 2, "paging stops at the boundary as well");
+            assert!(page.cursor.is_none());
+
+            assert!(
+                messages(&repo, Some(Missing::Error)).is_err(),
+                "the parent of the boundary commit can't be found"
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn partial_clones_traverse_all_commits() -> crate::Result {
+            let repo = repo("partial")?;
+            assert!(repo.repo.is_partial());
+            assert!(!repo.repo.is_shallow());
+            assert_eq!(messages(&repo, None)?, vec!["c3", "c2", "c1"]);
+            Ok(())
+        }
+
+        #[test]
+        fn complete_repositories_are_neither_shallow_nor_partial() -> crate::Result {
+            let repo = repo("base")?;
+            assert!(!repo.repo.is_shallow());
+            assert!(!repo.repo.is_partial());
+            assert_eq!(messages(&repo, Some(Missing::Skip))?, vec!["c3", "c2", "c1"]);
+            Ok(())
+        }
+    }
+
+    mod page {
+        use std::time::Duration;
+
+        use git_repository as git;
+        use git_repository::{
+            easy::oid::ancestors::Cursor,
+            prelude::{ObjectIdExt, ReferenceAccessExt},
+        };
+
+        fn repo() -> crate::Result<git::Easy> {
+            Ok(crate::repo("make_repo_with_10k_commits.sh")?.into_easy())
+        }
+
+        fn unbounded_walk(repo: &git::Easy) -> crate::Result<Vec<git::hash::ObjectId>> {
+            let head = repo.head()?.into_fully_peeled_id().expect("born")?;
+            Ok(head
+                .ancestors()?
+                .all()
+                .map(|id| id.map(|id| id.detach()))
+                .collect::<Result<_, _>>()?)
+        }
+
+        #[test]
+        fn pages_resumed_with_a_cursor_concatenate_to_an_unbounded_walk() -> crate::Result {
+            let repo = repo()?;
+            let all = unbounded_walk(&repo)?;
+            assert_eq!(all.len(), 10_000);
+
+            let head = repo.head()?.into_fully_peeled_id().expect("born")?;
+            let mut cursor: Option<Cursor> = None;
+            let mut paged = Vec::new();
+            let mut pages = 0;
+            loop {
+                let mut platform = head.ancestors()?.max_commits(100);
+                if let Some(cursor) = cursor.take() {
+                    platform = platform.resume(cursor);
+                }
+                let page = platform.page()?;
+                assert!(page.commits.len() <= 100);
+                paged.extend(page.commits.into_iter().map(|id| id.detach()));
+                pages += 1;
+                match page.cursor {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+            assert_eq!(pages, 100);
+            assert_eq!(paged, all, "no commit is skipped or returned twice");
+            Ok(())
+        }
+
+        #[test]
+        fn pages_of_the_same_platform_continue_where_the_previous_one_stopped() -> crate::Result {
+            let repo = repo()?;
+            let all = unbounded_walk(&repo)?;
+            let head = repo.head()?.into_fully_peeled_id().expect("born")?;
+            let mut platform = head.ancestors()?.max_commits(3000);
+            let mut paged = Vec::new();
+            for expected_len in [3000, 3000, 3000, 1000, 0] {
+                let page = platform.page()?;
+                assert_eq!(page.commits.len(), expected_len);
+                assert_eq!(
+                    page.cursor.is_none(),
+                    expected_len < 3000,
+                    "cursor is unset once all commits are seen"
+                );
+                paged.extend(page.commits.into_iter().map(|id| id.detach()));
+            }
+            assert_eq!(paged, all);
+            Ok(())
+        }
+
+        #[test]
+        fn max_duration_returns_at_least_one_commit() -> crate::Result {
+            let repo = repo()?;
+            let all = unbounded_walk(&repo)?;
+            let head = repo.head()?.into_fully_peeled_id().expect("born")?;
+            let mut platform = head.ancestors()?.max_duration(Duration::default());
+            for expected in &all[..10] {
+                let page = platform.page()?;
+                assert_eq!(page.commits.len(), 1, "time is up after the first commit");
+                assert_eq!(page.commits[0], *expected);
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn min_commit_time_skips_older_commits() -> crate::Result {
+            let repo = repo()?;
+            let all = unbounded_walk(&repo)?;
+            let head = repo.head()?.into_fully_peeled_id().expect("born")?;
+            let c9000_time = 1_000_000_000 + 9000 * 60;
+            let page = head.ancestors()?.min_commit_time(c9000_time).page()?;
+            assert!(page.cursor.is_none(), "there is no limit, so it's the only page");
+            assert_eq!(page.commits.len(), 1001, "c9000 to c10000");
+
+            let mut expected = Vec::new();
+            for id in all {
+                if id.attach(&repo).object()?.commit()?.committer.time.time >= c9000_time {
+                    expected.push(id);
+                }
+            }
+            assert_eq!(
+                page.commits.into_iter().map(|id| id.detach()).collect::<Vec<_>>(),
+                expected,
+                "the order of commits is the same as in an unbounded walk"
+            );
+            Ok(())
+        }
+
+        #[test]
+        #[cfg(feature = "serde1")]
+        fn cursors_can_be_serialized() -> crate::Result {
+            let repo = repo()?;
+            let head = repo.head()?.into_fully_peeled_id().expect("born")?;
+            let mut platform = head.ancestors()?.max_commits(100);
+            let cursor = platform.page()?.cursor.expect("more commits");
+            let expected = platform.page()?.commits;
+
+            let cursor: Cursor = serde_json::from_slice(&serde_json::to_vec(&cursor)?)?;
+            let page = head.ancestors()?.max_commits(100).resume(cursor).page()?;
+            assert_eq!(page.commits, expected);
+            Ok(())
+        }
+    }
+}
+
+mod note {
+    use std::convert::TryInto;
+
+    use git_repository::prelude::{ObjectIdExt, ReferenceAccessExt};
+
+    #[test]
+    fn notes_are_found_on_regular_and_fan_out_paths() -> crate::Result {
+        let repo = crate::repo("make_repo_with_notes.sh")?.into_easy();
+        let head = repo.head()?.into_fully_peeled_id().expect("born")?;
+        let mut ancestors: Vec<_> = head.ancestors()?.all().collect::<Result<_, _>>()?;
+        let c1 = ancestors.pop().expect("three commits");
+        let c2 = ancestors.pop().expect("three commits");
+        let c3 = ancestors.pop().expect("three commits");
+
+        assert_eq!(c1.note(None)?.expect("regular path"), "note on c1\n");
+        assert_eq!(
+            c2.detach().note(None, &repo)?.expect("fan-out path"),
+            "note on c2\n",
+            "it works for bare object ids as well"
+        );
+        assert_eq!(c3.note(None)?, None, "no note in the default notes reference");
+        assert_eq!(
+            c3.note(Some("refs/notes/review".try_into()?))?.expect("present"),
+            "reviewed c3\n"
+        );
+        assert_eq!(
+            c3.note(Some("missing".try_into()?))?,
+            None,
+            "missing notes references have no notes"
+        );
+        Ok(())
+    }
+}
+
+mod header {
+    use git_repository::{
+        easy::{object::Kind, ObjectHeader},
+        hash::ObjectId,
+        prelude::{ObjectIdExt, ReferenceAccessExt},
+    };
+
+    #[test]
+    fn existence_kind_and_size_without_decoding() -> crate::Result {
+        let repo = crate::basic_repo()?;
+        let head = repo.head()?.into_fully_peeled_id().expect("born")?;
+        assert!(head.exists()?);
+
+        let commit = head.object()?;
+        let expected = ObjectHeader {
+            kind: Kind::Commit,
+            size: commit.data.len() as u64,
+        };
+        assert_eq!(
+            head.object_header()?,
+            expected,
+            "the shared buffer isn't needed, so headers can be obtained while an object is alive"
+        );
+        assert_eq!(head.try_object_header()?, Some(expected));
+        drop(commit);
+
+        let empty_tree = ObjectId::empty_tree(git_repository::hash::Kind::Sha1).attach(&repo);
+        assert!(empty_tree.exists()?, "the empty tree is always present");
+        assert_eq!(
+            empty_tree.object_header()?,
+            ObjectHeader {
+                kind: Kind::Tree,
+                size: 0
+            }
+        );
+
+        let missing = ObjectId::from_hex(b"ffffffffffffffffffffffffffffffffffffffff")?.attach(&repo);
+        assert!(!missing.exists()?);
+        assert_eq!(missing.try_object_header()?, None);
+        assert!(missing.object_header().is_err());
+        Ok(())
+    }
+}
+
+mod describe {
+    use std::{path::Path, process::Command};
+
+    use git_repository as git;
+    use git_repository::{easy::oid::describe, hash::ObjectId, prelude::ObjectIdExt};
+
+    fn git(dir: &Path, args: &[&str]) -> crate::Result<String> {
+        let out = Command::new("git").args(args).current_dir(dir).output()?;
+        assert!(out.status.success(), "git {:?} failed", args);
+        Ok(String::from_utf8(out.stdout)?.trim_end().into())
+    }
+
+    fn repo() -> crate::Result<(git::Easy, std::path::PathBuf)> {
+        let dir = git_testtools::scripted_fixture_repo_read_only("make_repo_for_describe.sh")?;
+        Ok((git::Repository::open(&dir)?.into_easy(), dir))
+    }
+
+    fn assert_describe_like_git(
+        rev: &str,
+        flags: &[&str],
+        configure: impl Fn(git::easy::oid::Describe<'_, git::Easy>) -> git::easy::oid::Describe<'_, git::Easy>,
+    ) -> crate::Result<describe::Resolution> {
+        let (repo, dir) = repo()?;
+        let id = ObjectId::from_hex(git(&dir, &["rev-parse", rev])?.as_bytes())?;
+        let resolution = configure(id.attach(&repo).describe()).resolve()?;
+        let mut args = vec!["describe"];
+        args.extend_from_slice(flags);
+        args.push(rev);
+        assert_eq!(
+            resolution.to_string(),
+            git(&dir, &args)?,
+            "git describe {:?} {} is matched",
+            flags,
+            rev
+        );
+        assert_eq!(resolution.id, id);
+        Ok(resolution)
+    }
+
+    #[test]
+    fn tagged_commits_are_described_by_the_most_recent_annotated_tag() -> crate::Result {
+        let res = assert_describe_like_git("main~4", &[], |d| d)?;
+        assert_eq!(res.name.as_ref().map(|n| n.to_string()), Some("b-newer".into()));
+        assert_eq!(res.depth, 0);
+        assert_eq!(res.to_string(), "b-newer", "there is no suffix for exact matches");
+        Ok(())
+    }
+
+    #[test]
+    fn the_closest_tag_wins_across_merges() -> crate::Result {
+        let res = assert_describe_like_git("main", &[], |d| d)?;
+        assert_eq!(res.name.as_ref().map(|n| n.to_string()), Some("side-tag".into()));
+        assert_eq!(res.depth, 4);
+        assert_eq!(res.hex_len, 7);
+        Ok(())
+    }
+
+    #[test]
+    fn first_parent_ignores_tags_on_merged_branches() -> crate::Result {
+        let res = assert_describe_like_git("main", &["--first-parent"], |d| d.first_parent(true))?;
+        assert_eq!(res.name.as_ref().map(|n| n.to_string()), Some("b-newer".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn lightweight_tags_are_only_considered_if_enabled() -> crate::Result {
+        assert_describe_like_git("main~2", &[], |d| d)?;
+        let res = assert_describe_like_git("main~2", &["--tags"], |d| d.tags(true))?;
+        assert_eq!(res.name.as_ref().map(|n| n.to_string()), Some("lightweight".into()));
+        assert_eq!(res.depth, 1);
+
+        assert_describe_like_git("main~3", &["--tags"], |d| d.tags(true))?;
+        assert_describe_like_git("main", &["--tags"], |d| d.tags(true))?;
+        Ok(())
+    }
+
+    #[test]
+    fn max_candidates_limits_the_search() -> crate::Result {
+        assert_describe_like_git("main", &["--candidates=1"], |d| d.max_candidates(1))?;
+        assert_describe_like_git("main~4", &["--candidates=0"], |d| d.max_candidates(0))?;
+        Ok(())
+    }
+
+    #[test]
+    fn commits_without_tags_fail_unless_always_is_set() -> crate::Result {
+        let res = assert_describe_like_git("untagged", &["--always"], |d| d.always(true))?;
+        assert_eq!(res.name, None);
+        assert_eq!(res.to_string().len(), res.hex_len);
+
+        let (repo, dir) = repo()?;
+        let id = ObjectId::from_hex(git(&dir, &["rev-parse", "untagged"])?.as_bytes())?;
+        assert!(matches!(
+            id.attach(&repo).describe().resolve(),
+            Err(describe::Error::NoTagFound { id: err_id }) if err_id == id
+        ));
+        assert!(
+            matches!(
+                id.attach(&repo).describe().tags(true).max_candidates(0).resolve(),
+                Err(describe::Error::NoTagFound { .. })
+            ),
+            "without tags in the history there is nothing to describe with"
+        );
+        Ok(())
+    }
+}
+
+mod shorten {
+    use std::{path::Path, process::Command};
+
+    use git_repository as git;
+    use git_repository::{
+        hash::ObjectId,
+        prelude::{ObjectIdExt, RepositoryAccessExt},
+    };
+
+    /// Return the ids of all blobs along with their abbreviation as produced by `git ls-tree` with `abbrev_args`.
+    fn blobs_abbreviated_by_git(dir: &Path, abbrev_arg: &str) -> crate::Result<Vec<(ObjectId, String)>> {
+        let ls_tree = |args: &[&str]| -> crate::Result<Vec<String>> {
+            let out = Command::new("git").args(args).current_dir(dir).output()?;
+            assert!(out.status.success(), "git {:?} failed", args);
+            Ok(String::from_utf8(out.stdout)?
+                .lines()
+                .map(|line| line.split_whitespace().nth(2).expect("id").to_owned())
+                .collect())
+        };
+        let ids = ls_tree(&["ls-tree", "all-blobs"])?;
+        let abbreviated = ls_tree(&["ls-tree", abbrev_arg, "all-blobs"])?;
+        ids.into_iter()
+            .zip(abbreviated)
+            .map(|(id, short)| Ok((ObjectId::from_hex(id.as_bytes())?, short)))
+            .collect()
+    }
+
+    fn repo_with_many_objects() -> crate::Result<(git::Easy, std::path::PathBuf)> {
+        let dir = git_testtools::scripted_fixture_repo_read_only("make_repo_with_many_objects.sh")?;
+        Ok((git::Repository::open(&dir)?.into_easy(), dir))
+    }
+
+    #[test]
+    fn auto_abbreviation_grows_with_the_amount_of_objects() -> crate::Result {
+        let (repo, dir) = repo_with_many_objects()?;
+        assert_eq!(
+            repo.abbreviation()?,
+            8,
+            "16500 packed objects need 8 hexadecimal characters"
+        );
+        for (id, expected) in blobs_abbreviated_by_git(&dir, "--abbrev")?.into_iter().take(100) {
+            assert_eq!(id.attach(&repo).shorten()?.to_string(), expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn ids_are_extended_until_they_are_unambiguous() -> crate::Result {
+        let (repo, dir) = repo_with_many_objects()?;
+        let (mut num_extended_packed, mut num_extended_loose) = (0, 0);
+        for (id, expected) in blobs_abbreviated_by_git(&dir, "--abbrev=4")? {
+            let short = id.attach(&repo).shorten_to(4)?;
+            assert_eq!(short.to_string(), expected, "{} is abbreviated like git does it", id);
+            if short.hex_len > 4 {
+                let hex = id.to_sha1_hex_string();
+                if dir.join(".git/objects").join(&hex[..2]).join(&hex[2..]).is_file() {
+                    num_extended_loose += 1;
+                } else {
+                    num_extended_packed += 1;
+                }
+            }
+        }
+        assert_ne!(num_extended_packed, 0, "collisions among packed objects are found");
+        assert_ne!(num_extended_loose, 0, "collisions of loose objects are found");
+        Ok(())
+    }
+}
+
+mod try_object_as {
+    use git_repository as git;
+    use git_repository::{
+        easy::object::{try_into, typed, Kind},
+        prelude::{ObjectIdExt, ReferenceAccessExt},
+    };
+
+    fn head_commit_tree_and_blob(
+        repo: &git::Easy,
+    ) -> crate::Result<(git::hash::ObjectId, git::hash::ObjectId, git::hash::ObjectId)> {
+        let commit = repo.head()?.into_fully_peeled_id().expect("born")?;
+        let tree = commit.try_object_as::<typed::Commit>()?.peel_to_tree()?;
+        let blob_id = git::objs::TreeRefIter::from_bytes(&tree.data)
+            .filter_map(Result::ok)
+            .find(|entry| entry.filename == "this")
+            .expect("present")
+            .oid
+            .to_owned();
+        Ok((commit.detach(), tree.id, blob_id))
+    }
+
+    #[test]
+    fn each_kind_is_returned_as_its_type() -> crate::Result {
+        let repo = crate::basic_repo()?;
+        let (commit_id, tree_id, blob_id) = head_commit_tree_and_blob(&repo)?;
+
+        let commit = commit_id.attach(&repo).try_object_as::<typed::Commit>()?;
+        assert_eq!(commit.commit()?.tree(), tree_id);
+        drop(commit);
+
+        let tree: git::easy::TreeRef<'_, _> = tree_id.attach(&repo).try_object_as::<typed::Tree>()?;
+        assert_eq!(tree.id, tree_id);
+        drop(tree);
+
+        let blob = blob_id.attach(&repo).try_object_as::<typed::Blob>()?;
+        assert_eq!(blob.kind, Kind::Blob);
+        assert_eq!(&*blob.data, b"hello\n");
+        drop(blob);
+
+        let blob = blob_id
+            .attach(&repo)
+            .object_as_or_none::<typed::Blob>()?
+            .expect("present");
+        assert_eq!(&*blob.data, b"hello\n");
+        Ok(())
+    }
+
+    #[test]
+    fn objects_of_other_kinds_are_errors_carrying_both_kinds() -> crate::Result {
+        let repo = crate::basic_repo()?;
+        let (commit_id, tree_id, blob_id) = head_commit_tree_and_blob(&repo)?;
+
+        for (id, actual) in &[(commit_id, Kind::Commit), (tree_id, Kind::Tree), (blob_id, Kind::Blob)] {
+            let err = id.attach(&repo).try_object_as::<typed::Tag>().unwrap_err();
+            assert!(
+                matches!(err, typed::Error::TryInto(try_into::Error::WrongKind { id: err_id, expected: Kind::Tag, actual: err_actual }) if err_id == *id && err_actual == *actual),
+                "{:?}",
+                err
+            );
+        }
+        let err = blob_id.attach(&repo).try_object_as::<typed::Tree>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Object {} was expected to be a tree but was a blob", blob_id)
+        );
+        assert!(matches!(
+            commit_id.attach(&repo).object_as_or_none::<typed::Blob>(),
+            Err(typed::Error::TryInto(try_into::Error::WrongKind {
+                expected: Kind::Blob,
+                actual: Kind::Commit,
+                ..
+            }))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn missing_objects_are_errors_or_none() -> crate::Result {
+        let repo = crate::basic_repo()?;
+        let missing = git::hash::ObjectId::from_hex(b"ffffffffffffffffffffffffffffffffffffffff")?.attach(&repo);
+        assert!(matches!(
+            missing.try_object_as::<typed::Commit>(),
+            Err(typed::Error::FindExisting(_))
+        ));
+        assert!(missing.object_as_or_none::<typed::Commit>()?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn tags_are_not_peeled() -> crate::Result {
+        let repo: git::Easy = crate::repo("make_repo_for_peeling.sh")?.into();
+        let tag_id = repo
+            .find_reference("refs/tags/annotated")?
+            .detach()
+            .target
+            .as_id()
+            .expect("peeled")
+            .to_owned();
+        let commit_id = repo.head()?.into_fully_peeled_id().expect("born")?.detach();
+        assert_ne!(tag_id, commit_id, "the tag is an object of its own");
+
+        let tag = tag_id.attach(&repo).try_object_as::<typed::Tag>()?;
+        assert_eq!(tag.tag_iter().target_id().expect("valid tag"), commit_id);
+        drop(tag);
+
+        assert!(
+            matches!(
+                tag_id.attach(&repo).try_object_as::<typed::Commit>(),
+                Err(typed::Error::TryInto(try_into::Error::WrongKind {
+                    expected: Kind::Commit,
+                    actual: Kind::Tag,
+                    ..
+                }))
+            ),
+            "tags are not followed to the commit they point to"
+        );
+
+        let commit = tag_id
+            .attach(&repo)
+            .try_object_as::<typed::Tag>()?
+            .peel_to::<typed::Commit>()?;
+        assert_eq!(commit.id, commit_id, "that's what peeling is for");
+        Ok(())
+    }
 }
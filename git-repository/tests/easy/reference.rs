@@ -41,3 +41,50 @@ mod find {
         assert_eq!(symbolic_ref.into_fully_peeled_id().unwrap(), the_commit, "idempotency");
     }
 }
+
+mod peel {
+    use git_repository::{easy::reference::peel, prelude::ReferenceAccessExt};
+
+    fn repo() -> crate::Result<git_repository::Easy> {
+        crate::repo("make_repo_for_peeling.sh").map(Into::into)
+    }
+
+    #[test]
+    fn symbolic_chains_and_nested_tags_resolve_to_the_commit() -> crate::Result {
+        let repo = repo()?;
+        let commit = repo.find_reference("main")?.id().detach();
+
+        let mut r = repo.find_reference("chain-1")?;
+        assert_eq!(
+            r.peel_to_id_in_place()?,
+            commit,
+            "the peeled id from packed-refs is used"
+        );
+        assert_eq!(r.name().as_bstr(), "refs/tags/nested");
+
+        let mut r = repo.find_reference("loose")?;
+        assert_ne!(r.target().as_id(), Some(commit.as_ref()), "points to a tag object");
+        assert_eq!(
+            r.peel_to_id_in_place()?,
+            commit,
+            "tag objects are peeled by looking them up"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cycles_and_dangling_symbolic_references_are_distinct_errors() -> crate::Result {
+        let repo = repo()?;
+        assert!(matches!(
+            repo.find_reference("cycle-a")?.peel_to_id_in_place(),
+            Err(peel::Error::ToId(git_ref::peel::to_id::Error::Cycle(_)))
+        ));
+        assert!(matches!(
+            repo.find_reference("dangling")?.peel_to_id_in_place(),
+            Err(peel::Error::ToId(git_ref::peel::to_id::Error::Follow(
+                git_ref::file::find::existing::Error::NotFound(_)
+            )))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+git config tag.gpgsign false
+git config advice.nestedTag false
+
+git checkout -q -b main
+for i in $(seq 1 20); do
+  git commit -q --allow-empty -m "c$i"
+done
+git tag -m "old release" v0 main~15
+git branch old main~10
+git tag target
+
+git checkout -q -b feature-b main~5
+git commit -q --allow-empty -m b1
+git commit -q --allow-empty -m b2
+
+git checkout -q -b feature-a main
+git commit -q --allow-empty -m a1
+git commit -q --allow-empty -m a2
+git commit -q --allow-empty -m a3
+
+git checkout -q -b merged main~8
+git commit -q --allow-empty -m m1
+git commit -q --allow-empty -m m2
+git merge -q --no-ff -m "merge feature-a" feature-a
+
+git checkout -q main
+git commit -q --allow-empty -m c21
+git tag -m "release" v1
+git tag -m "tag of tag" nested v1
+git commit -q --allow-empty -m c22
+git tag tree-tag "HEAD^{tree}"
+git tag lightweight feature-b
+
+git commit-graph write --no-progress --reachable
@@ -0,0 +1,26 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+git checkout -q -b main
+git commit -q --allow-empty -m c1
+git tag -m "older" a-older
+GIT_COMMITTER_DATE="2001-01-01 00:00:00 +0000" git tag -m "newer" b-newer
+git commit -q --allow-empty -m c2
+git tag lightweight
+git commit -q --allow-empty -m c3
+
+git checkout -q -b side main~2
+git commit -q --allow-empty -m s1
+git commit -q --allow-empty -m s2
+git tag -m "on side branch" side-tag
+
+git checkout -q main
+git merge -q --no-ff -m merge side
+git commit -q --allow-empty -m c4
+
+git checkout -q --orphan untagged
+git commit -q --allow-empty -m untagged
+git checkout -q main
@@ -0,0 +1,41 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q loose
+(cd loose
+  git config commit.gpgsign false
+  git checkout -q -b main
+  echo content > file
+  git add file && git commit -q -m c1
+
+  mkdir blobs
+  for i in $(seq 1 600); do
+    echo "loose $i" > "blobs/$i"
+  done
+  ls -d blobs/* | git hash-object -w --stdin-paths > /dev/null
+  rm -r blobs
+)
+
+# The same objects with the default limit of 6700 loose objects, which isn't crossed.
+cp -R loose below-limits
+# Only 256 loose objects are tolerated, which is crossed by the 600 loose blobs.
+git -C loose config gc.auto 256
+# Maintenance is disabled entirely.
+cp -R loose disabled
+git -C disabled config gc.auto 0
+
+git init -q packs
+(cd packs
+  git config commit.gpgsign false
+  git checkout -q -b main
+  for name in c1 c2 c3 c4; do
+    echo $name > file
+    git add file && git commit -q -m $name
+    git repack -q -d
+  done
+  # One of the four packs is kept and doesn't count, leaving three.
+  kept=$(ls .git/objects/pack/*.pack | head -n 1)
+  touch "${kept%.pack}.keep"
+  echo loose > loose && git hash-object -w loose > /dev/null && rm loose
+  git config gc.autoPackLimit 2
+)
@@ -0,0 +1,21 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+git checkout -q -b main
+git commit -q --allow-empty -m c1
+git commit -q --allow-empty -m c2
+git branch other main~1
+git update-ref refs/remotes/mirror-old/packed main~1
+git pack-refs --all --prune
+
+c1=$(git rev-parse main~1)
+seq 1 2000 | awk -v id="$c1" '{ printf "%s refs/remotes/mirror/branch-%04d\n", id, $1 }' >> .git/packed-refs
+
+git update-ref refs/remotes/mirror/branch-0002 main
+git update-ref refs/remotes/mirror/loose-only main
+git symbolic-ref refs/remotes/mirror/HEAD refs/remotes/mirror/branch-0001
+# Outside of the mirror scope even though its name starts with it.
+git update-ref refs/remotes/mirror-old/loose main
@@ -0,0 +1,23 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+git checkout -q -b main
+git commit -q --allow-empty -m c1
+git tag -m "tag object" annotated
+git tag -m "tag of a tag object" nested annotated
+
+git pack-refs --all --prune
+
+git tag -m "loose tag of a packed tag" loose nested
+
+echo "ref: refs/chain-2" > .git/refs/chain-1
+echo "ref: refs/chain-3" > .git/refs/chain-2
+echo "ref: refs/tags/nested" > .git/refs/chain-3
+
+echo "ref: refs/cycle-b" > .git/refs/cycle-a
+echo "ref: refs/cycle-a" > .git/refs/cycle-b
+
+echo "ref: refs/heads/does-not-exist" > .git/refs/dangling
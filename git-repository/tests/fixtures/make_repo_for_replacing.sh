@@ -0,0 +1,17 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+git checkout -q -b main
+for c in c1 c2 c3 c4; do
+  git commit -q --allow-empty -m $c
+  git tag $c
+done
+
+for c in c2-alt d1 d2 d3 d4 d5; do
+  git tag $c "$(git commit-tree -m $c c1^{tree})"
+done
+
+git tag blob "$(echo content | git hash-object -w --stdin)"
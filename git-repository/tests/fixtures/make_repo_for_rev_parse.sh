@@ -0,0 +1,42 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+git checkout -q -b main
+mkdir -p dir/sub
+echo a > a
+echo b > dir/b
+echo c > dir/sub/c
+git add . && git commit -q -m c1
+echo a2 > a
+git commit -q -am c2
+git tag -m "annotated" v1
+git tag lightweight
+git -c advice.nestedTag=false tag -m "tag of a tag" v1-nested v1
+git tag -m "tag of a tree" tree-tag HEAD^{tree}
+git tag -m "tag of a blob" blob-tag HEAD:a
+
+git checkout -q -b side main~1
+echo side > side
+git add side && git commit -q -m s1
+git checkout -q -b other main~1
+echo other > other
+git add other && git commit -q -m o1
+
+git checkout -q main
+git merge -q --no-ff -m octopus side other
+echo a3 > a
+git commit -q -am c3
+
+git tag -m "tag named like a branch" side side~1
+git update-ref refs/remotes/origin/main main~1
+git symbolic-ref refs/remotes/origin/HEAD refs/remotes/origin/main
+git symbolic-ref refs/heads/unborn-target refs/heads/does-not-exist
+
+for i in $(seq 1000); do echo "blob $i" > "unreachable-$i"; done
+git hash-object -w unreachable-* >/dev/null
+rm unreachable-*
+
+git repack -adq
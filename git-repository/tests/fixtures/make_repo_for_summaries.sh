@@ -0,0 +1,21 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+git checkout -q -b main
+
+echo a > a
+mkdir dir && echo b > dir/b
+git add . && git commit -q -m "first"
+
+echo changed > a
+git rm -q dir/b
+echo binary > "$(printf 'caf\351')"
+git add .
+printf 'second in a caf\351\n\nSigned-off-by: author <author@example.com>\n' |
+  GIT_AUTHOR_DATE="2000-01-01 00:00:00 -0700" GIT_COMMITTER_DATE="2000-01-02 12:34:56 +0230" git -c i18n.commitEncoding=ISO-8859-1 commit -q -F -
+
+git tag first HEAD~1
+git update-ref refs/remotes/origin/main HEAD
+git symbolic-ref refs/remotes/origin/HEAD refs/remotes/origin/main
@@ -0,0 +1,24 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+# A linear history in which every 7th commit also merges a commit 13 commits older, with one commit per minute.
+for i in $(seq 1 10000); do
+  echo "commit refs/heads/main"
+  echo "mark :$i"
+  echo "committer committer <committer@example.com> $((1000000000 + i * 60)) +0000"
+  echo "data <<EOM"
+  echo "c$i"
+  echo "EOM"
+  if [ "$i" -gt 1 ]; then
+    echo "from :$((i - 1))"
+  fi
+  if [ "$i" -gt 20 ] && [ $((i % 7)) -eq 0 ]; then
+    echo "merge :$((i - 13))"
+  fi
+  echo
+done | git fast-import --quiet
+
+git checkout -q main
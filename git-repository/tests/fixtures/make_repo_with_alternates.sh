@@ -0,0 +1,20 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q a
+(cd a
+  git config commit.gpgsign false
+  git checkout -q -b main
+  echo a > a
+  git add a
+  git commit -q -m "a"
+)
+
+git clone -q --shared a b
+echo "../../../a/.git/objects" > b/.git/objects/info/alternates
+(cd b
+  git config commit.gpgsign false
+  echo b > b
+  git add b
+  git commit -q -m "b"
+)
@@ -0,0 +1,23 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+blob=$(echo content | git hash-object -w --stdin)
+c_txt=$(printf '100644 blob %s\tc.txt\n' "$blob" | git mktree)
+a_b=$(printf '040000 tree %s\tb\n' "$c_txt" | git mktree)
+
+root=$({
+  for i in $(seq 1000 2199); do
+    printf '100644 blob %s\tfile-%s\n' "$blob" "$i"
+  done
+  printf '040000 tree %s\ta\n' "$a_b"
+  printf '100644 blob %s\ta.b\n' "$blob"
+  printf '100644 blob %s\ta0\n' "$blob"
+  printf '100644 blob %s\tcaf\351\n' "$blob"
+  printf '160000 commit %s\tsubmodule\n' "0123456789012345678901234567890123456789"
+} | git mktree --missing)
+
+git update-ref refs/heads/main "$(git commit-tree -m "large tree" "$root")"
+git symbolic-ref HEAD refs/heads/main
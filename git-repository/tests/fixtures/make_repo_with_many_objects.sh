@@ -0,0 +1,23 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+# Enough packed objects for the abbreviation of object ids to grow beyond 7 hexadecimal characters.
+for i in $(seq 1 16500); do
+  printf 'blob\ndata <<EOM\npacked %s\nEOM\n\n' "$i"
+done | git fast-import --quiet
+
+mkdir loose
+for i in $(seq 1 300); do
+  echo "loose $i" > "loose/$i"
+done
+ls -d loose/* | git hash-object -w --stdin-paths > /dev/null
+rm -r loose
+
+# A tree listing all blobs, to let `git ls-tree --abbrev` show how git abbreviates their ids.
+git cat-file --batch-all-objects --batch-check='%(objectname)' \
+  | awk '{ printf "100644 blob %s\tblob-%d\n", $1, NR }' \
+  | git mktree \
+  | xargs git tag all-blobs
@@ -0,0 +1,13 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+git checkout -q -b main
+git commit -q --allow-empty -m c1
+git branch other
+git pack-refs --all --prune
+
+commit=$(git rev-parse HEAD)
+seq 1 100000 | awk -v id="$commit" '{ printf "%s refs/tags/t-%06d\n", id, $1 }' >> .git/packed-refs
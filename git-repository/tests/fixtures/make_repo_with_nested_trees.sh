@@ -0,0 +1,16 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+git checkout -q -b main
+mkdir -p a/b dir/sub
+touch empty a/b/empty
+echo content > a/file
+echo content > dir/sub/same-content
+echo other > dir/z
+ln -s a/file link
+echo "#!/bin/sh" > exe && chmod +x exe
+git add .
+git commit -q -m c1
@@ -0,0 +1,26 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+git checkout -q -b main
+git commit -q --allow-empty -m c1
+git commit -q --allow-empty -m c2
+git commit -q --allow-empty -m c3
+
+git notes add -m "note on c1" HEAD~2
+git notes add -m "note on c2" HEAD~1
+git notes --ref review add -m "reviewed c3" HEAD
+
+# move the note of c2 into a fan-out directory, like git does once there are many notes
+export GIT_INDEX_FILE=.git/notes-index
+c2=$(git rev-parse HEAD~1)
+git read-tree refs/notes/commits
+blob=$(git rev-parse "refs/notes/commits:$c2")
+git update-index --force-remove "$c2"
+git update-index --add --cacheinfo "100644,$blob,${c2:0:2}/${c2:2}"
+tree=$(git write-tree)
+commit=$(git commit-tree -p refs/notes/commits -m "fan out" "$tree")
+git update-ref refs/notes/commits "$commit"
+rm "$GIT_INDEX_FILE"
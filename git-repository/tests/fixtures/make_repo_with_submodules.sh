@@ -0,0 +1,36 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q module
+(cd module
+  git config commit.gpgsign false
+  git checkout -q -b main
+  mkdir dir
+  echo a > dir/a
+  git add dir/a
+  git commit -q -m "a"
+)
+
+git init -q super
+(cd super
+  git config commit.gpgsign false
+  git checkout -q -b main
+  echo s > s
+  git add s
+  git commit -q -m "s"
+
+  git -c protocol.file.allow=always submodule --quiet add ../module m1
+  git -c protocol.file.allow=always submodule --quiet add ../module nested/m2
+  git commit -q -m "add submodules"
+
+  git worktree add -q ../wt -b wt
+)
+
+mkdir chained
+echo "gitdir: ../super/m1/.git" > chained/.git
+
+mkdir loop
+echo "gitdir: .git" > loop/.git
+
+mkdir malformed
+echo "not a gitdir file" > malformed/.git
@@ -0,0 +1,20 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+git config tag.gpgsign false
+git config --add versionsort.suffix -pre
+git config --add versionsort.suffix -rc
+
+GIT_COMMITTER_DATE="2001-01-05 00:00:00 +0000" git commit -q --allow-empty -m c1
+git tag v1.2.0
+GIT_COMMITTER_DATE="2001-01-02 00:00:00 +0000" git tag -m rc v1.2.0-rc1
+GIT_COMMITTER_DATE="2001-01-01 00:00:00 +0000" git tag -m pre v1.2.0-pre1
+git pack-refs --all
+
+GIT_COMMITTER_DATE="2001-01-06 00:00:00 +0000" git commit -q --allow-empty -m c2
+git tag v1.9.0
+GIT_COMMITTER_DATE="2001-01-03 00:00:00 +0000" git tag -m release v1.10.0
+GIT_COMMITTER_DATE="2001-01-04 00:00:00 +0000" git tag -m rc v2.0.0-rc1
+git symbolic-ref refs/tags/latest refs/tags/v1.10.0
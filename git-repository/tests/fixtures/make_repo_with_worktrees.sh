@@ -0,0 +1,21 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q main
+(cd main
+  git config commit.gpgsign false
+  git checkout -q -b main
+  git commit -q --allow-empty -m c1
+
+  git worktree add -q ../wt-a -b a
+  git worktree add -q --detach ../wt-b
+  git worktree lock --reason "on a removable drive" ../wt-b
+  git worktree add -q ../gone -b gone
+)
+
+(cd wt-a
+  git commit -q --allow-empty -m c2
+)
+
+rm -rf gone
+git clone -q --bare main bare.git
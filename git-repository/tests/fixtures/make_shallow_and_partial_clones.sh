@@ -0,0 +1,18 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q base
+(cd base
+  git config commit.gpgsign false
+  git config uploadpack.allowFilter true
+  git checkout -q -b main
+  for n in 1 2 3; do
+    seq 1 $n > file
+    echo "$n" > "file-$n"
+    git add .
+    git commit -q -m "c$n"
+  done
+)
+
+git clone -q --depth 2 "file://$PWD/base" shallow
+git clone -q --filter=blob:none --no-checkout "file://$PWD/base" partial
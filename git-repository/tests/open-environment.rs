@@ -0,0 +1,62 @@
+//! Assure that the environment variables which change which repository is opened, and how, can be ignored.
+//!
+//! This is a test binary of its own as it changes the environment of the process, which would affect other tests.
+use git_repository::{open, Repository};
+
+type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[test]
+fn environment_overrides_are_honored_by_default_and_can_be_ignored() -> Result {
+    let tmp = tempfile::tempdir()?;
+    let wanted = tmp.path().join("wanted");
+    let other = tmp.path().join("other");
+    let wanted_git_dir = git_repository::init(&wanted)?.git_dir().to_owned();
+    let other_git_dir = git_repository::init(&other)?.git_dir().to_owned();
+    let other_objects = tmp.path().join("other-objects");
+    std::fs::create_dir_all(other_objects.join("pack"))?;
+    let other_work_tree = tmp.path().join("other-work-tree");
+    let other_index = tmp.path().join("other-index");
+
+    std::env::set_var("GIT_DIR", &other_git_dir);
+    std::env::set_var("GIT_WORK_TREE", &other_work_tree);
+    std::env::set_var("GIT_OBJECT_DIRECTORY", &other_objects);
+    std::env::set_var("GIT_INDEX_FILE", &other_index);
+
+    let repo = Repository::open(&wanted)?;
+    assert_eq!(repo.git_dir(), other_git_dir, "like git, GIT_DIR wins");
+    assert_eq!(repo.workdir(), Some(other_work_tree.as_path()));
+    assert_eq!(repo.objects_dir(), other_objects);
+    assert_eq!(repo.index_path(), other_index);
+    assert_eq!(
+        Repository::discover(wanted.join("does-not-matter"))?.git_dir(),
+        other_git_dir
+    );
+
+    for repo in &[
+        Repository::open_with_environment_overrides_disabled(&wanted)?,
+        git_repository::open_opts(&wanted, open::Options::isolated())?,
+        Repository::discover_opts(
+            &wanted,
+            open::Options::default().environment(open::EnvironmentOverrides::none()),
+        )?,
+    ] {
+        assert_eq!(repo.git_dir(), wanted_git_dir);
+        assert_eq!(repo.workdir(), Some(wanted.as_path()));
+        assert_eq!(repo.objects_dir(), wanted_git_dir.join("objects"));
+        assert_eq!(repo.index_path(), wanted_git_dir.join("index"));
+    }
+
+    let repo = Repository::open_opts(
+        &wanted,
+        open::Options::default().environment(open::EnvironmentOverrides {
+            git_dir: false,
+            index_file: false,
+            ..open::EnvironmentOverrides::all()
+        }),
+    )?;
+    assert_eq!(repo.git_dir(), wanted_git_dir, "overrides can be toggled individually");
+    assert_eq!(repo.index_path(), wanted_git_dir.join("index"));
+    assert_eq!(repo.workdir(), Some(other_work_tree.as_path()));
+    assert_eq!(repo.objects_dir(), other_objects);
+    Ok(())
+}
@@ -0,0 +1,282 @@
+#[test]
+fn with_repair_creates_missing_reference_directories() -> crate::Result {
+    let tmp = tempfile::tempdir()?;
+    let repo = git_repository::init(tmp.path())?;
+    let heads = repo.git_dir().join("refs").join("heads");
+    std::fs::remove_dir(&heads)?;
+
+    let (repo, outcome) = git_repository::Repository::open_with_repair(tmp.path())?;
+    assert!(outcome.is_valid());
+    assert_eq!(outcome.created, vec![heads.clone()]);
+    assert!(heads.is_dir());
+
+    let (_repo, outcome) = git_repository::Repository::open_with_repair(repo.git_dir())?;
+    assert!(outcome.created.is_empty(), "nothing left to repair");
+    Ok(())
+}
+
+#[test]
+fn with_repair_reports_anomalies_without_fixing_them() -> crate::Result {
+    let tmp = tempfile::tempdir()?;
+    let repo = git_repository::init_bare(tmp.path())?;
+    let tags = repo.git_dir().join("refs").join("tags");
+    std::fs::remove_dir(&tags)?;
+    std::fs::write(&tags, b"")?;
+
+    let (_repo, outcome) = git_repository::Repository::open_with_repair(tmp.path())?;
+    assert_eq!(
+        outcome.anomalies,
+        vec![git_repository::refs::file::structure::Anomaly::NotADirectory { path: tags.clone() }]
+    );
+    assert!(tags.is_file());
+    Ok(())
+}
+
+#[test]
+fn objects_of_alternates_are_found() -> crate::Result {
+    use git_repository::prelude::{ObjectAccessExt, ReferenceAccessExt};
+
+    let dir = git_testtools::scripted_fixture_repo_read_only("make_repo_with_alternates.sh")?;
+    let repo = git_repository::Repository::open(dir.join("b"))?;
+
+    let alternate = git_repository::Repository::open(dir.join("a"))?.into_easy();
+    let id_in_alternate = alternate.head()?.into_fully_peeled_id().expect("born")?.detach();
+    let hex = id_in_alternate.to_string();
+    assert!(
+        !dir.join("b/.git/objects").join(&hex[..2]).join(&hex[2..]).exists(),
+        "the commit is only present in the alternate"
+    );
+
+    let repo = repo.into_easy();
+    assert_eq!(
+        repo.find_object(id_in_alternate)?.kind,
+        git_repository::objs::Kind::Commit
+    );
+    let head = repo.head()?.into_fully_peeled_id().expect("born")?.object()?;
+    assert_eq!(
+        git_repository::objs::CommitRef::from_bytes(&head.data)?
+            .parents()
+            .collect::<Vec<_>>(),
+        vec![id_in_alternate],
+        "the commit in the repository builds on the one in its alternate"
+    );
+    Ok(())
+}
+
+mod gitdir_file {
+    use std::path::{Path, PathBuf};
+
+    use git_repository::{prelude::ReferenceAccessExt, Repository};
+
+    fn root() -> crate::Result<PathBuf> {
+        Ok(git_testtools::scripted_fixture_repo_read_only("make_repo_with_submodules.sh")?.canonicalize()?)
+    }
+
+    fn git(dir: &Path, args: &[&str]) -> crate::Result<String> {
+        let out = std::process::Command::new("git").args(args).current_dir(dir).output()?;
+        assert!(out.status.success(), "{:?}", out);
+        Ok(String::from_utf8(out.stdout)?.trim_end().to_owned())
+    }
+
+    /// Assert that `repo` has the git and work tree directories `git` finds in `checkout`, and that `git status` sees
+    /// a clean work tree when using them.
+    fn assert_paths_match_git(repo: Repository, checkout: &Path) -> crate::Result {
+        assert_eq!(
+            repo.git_dir(),
+            Path::new(&git(checkout, &["rev-parse", "--absolute-git-dir"])?)
+        );
+        let work_dir = repo.workdir().expect("non-bare");
+        assert_eq!(work_dir, Path::new(&git(checkout, &["rev-parse", "--show-toplevel"])?));
+
+        let status = std::process::Command::new("git")
+            .arg("--git-dir")
+            .arg(repo.git_dir())
+            .arg("--work-tree")
+            .arg(work_dir)
+            .args(["status", "--porcelain"])
+            .current_dir(work_dir)
+            .output()?;
+        assert!(status.status.success(), "{:?}", status);
+        assert_eq!(status.stdout.as_slice(), b"", "the work tree is clean and complete");
+
+        let head = repo.into_easy().head()?.into_fully_peeled_id().expect("born")?.detach();
+        assert_eq!(head.to_string(), git(checkout, &["rev-parse", "HEAD"])?);
+        Ok(())
+    }
+
+    #[test]
+    fn submodule_checkouts_open_the_git_dir_in_the_superproject() -> crate::Result {
+        let root = root()?;
+        for checkout in &["super/m1", "super/nested/m2"] {
+            let checkout = root.join(checkout);
+            let repo = Repository::open(&checkout)?;
+            assert_eq!(repo.workdir(), Some(checkout.as_path()));
+            assert_paths_match_git(repo, &checkout)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn submodule_git_dirs_use_core_worktree() -> crate::Result {
+        let root = root()?;
+        let repo = Repository::open(root.join("super/.git/modules/nested/m2"))?;
+        assert_eq!(repo.workdir(), Some(root.join("super/nested/m2").as_path()));
+        assert_paths_match_git(repo, &root.join("super/nested/m2"))
+    }
+
+    #[test]
+    fn linked_worktree_checkouts_open_their_private_git_dir() -> crate::Result {
+        let root = root()?;
+        let checkout = root.join("wt");
+        let repo = Repository::open(&checkout)?;
+        assert_eq!(repo.common_dir(), root.join("super/.git"));
+        assert_paths_match_git(repo, &checkout)?;
+
+        let repo = Repository::open(root.join("super/.git/worktrees/wt"))?;
+        assert_eq!(
+            repo.workdir(),
+            Some(checkout.as_path()),
+            "the work tree is known from the 'gitdir' file"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn discovery_finds_the_closest_checkout() -> crate::Result {
+        let root = root()?;
+        let repo = Repository::discover(root.join("super/nested/m2/dir"))?;
+        assert_eq!(repo.git_dir(), root.join("super/.git/modules/nested/m2"));
+        assert_eq!(repo.workdir(), Some(root.join("super/nested/m2").as_path()));
+
+        let path = git_repository::path::discover::existing(root.join("wt"))?;
+        assert_eq!(
+            path,
+            git_repository::Path::LinkedWorkTree {
+                work_dir: root.join("wt"),
+                git_dir: root.join("super/.git/worktrees/wt")
+            }
+        );
+
+        let repo = Repository::discover(root.join("super/nested"))?;
+        assert_eq!(repo.git_dir(), root.join("super/.git"), "nested isn't a checkout");
+        Ok(())
+    }
+
+    #[test]
+    fn gitdir_files_can_point_to_other_gitdir_files() -> crate::Result {
+        let root = root()?;
+        let repo = Repository::open(root.join("chained"))?;
+        assert_eq!(repo.git_dir(), root.join("super/.git/modules/m1"));
+        assert_eq!(
+            repo.workdir(),
+            Some(root.join("super/m1").as_path()),
+            "core.worktree has the final say"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_gitdir_files_are_errors() -> crate::Result {
+        use git_repository::{open::Error, path::from_gitdir_file};
+
+        let root = root()?;
+        assert!(matches!(
+            Repository::open(root.join("loop")),
+            Err(Error::GitDirFile(from_gitdir_file::Error::TooDeep { .. }))
+        ));
+        assert!(matches!(
+            Repository::open(root.join("malformed")),
+            Err(Error::GitDirFile(from_gitdir_file::Error::Malformed { .. }))
+        ));
+        assert!(matches!(
+            git_repository::path::from_gitdir_file(root.join("missing/.git")),
+            Err(from_gitdir_file::Error::Io { .. })
+        ));
+        Ok(())
+    }
+}
+
+mod options {
+    use git_repository::{open, Repository};
+
+    /// A repository whose configuration would make `git` run programs and treat it as bare.
+    fn hostile_repo() -> crate::Result<(tempfile::TempDir, Repository)> {
+        let tmp = tempfile::tempdir()?;
+        let repo = git_repository::init(tmp.path())?;
+        let mut config = std::fs::read_to_string(repo.git_dir().join("config"))?;
+        config.push_str(
+            "[core]\n\tbare = true\n\thooksPath = /tmp/evil-hooks\n\tfsmonitor = /tmp/evil-fsmonitor\n\
+             [filter \"lfs\"]\n\tclean = evil-clean %f\n\tsmudge = evil-smudge %f\n\trequired = true\n",
+        );
+        std::fs::write(repo.git_dir().join("config"), config)?;
+        Ok((tmp, repo))
+    }
+
+    #[test]
+    fn repositories_owned_by_the_current_user_are_trusted_fully() -> crate::Result {
+        let (_tmp, repo) = hostile_repo()?;
+        let repo = Repository::open(repo.git_dir())?;
+        assert_eq!(repo.trust(), open::Trust::Full);
+        assert_eq!(repo.workdir(), None, "core.bare is used");
+        assert_eq!(repo.hooks_dir(), std::path::Path::new("/tmp/evil-hooks"));
+        assert_eq!(repo.fsmonitor_command().expect("set"), "/tmp/evil-fsmonitor");
+        let driver = repo.filter_driver("lfs").expect("configured");
+        assert_eq!(driver.clean.as_ref().expect("set"), "evil-clean %f");
+        assert_eq!(driver.smudge.as_ref().expect("set"), "evil-smudge %f");
+        assert_eq!(driver.process, None);
+        assert!(driver.required);
+        assert!(repo.filter_driver("other").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn reduced_trust_ignores_configured_programs_but_not_other_values() -> crate::Result {
+        let (_tmp, repo) = hostile_repo()?;
+        let git_dir = repo.git_dir().to_owned();
+        let repo = Repository::open_opts(&git_dir, open::Options::default().trust(open::Trust::Reduced))?;
+        assert_eq!(repo.trust(), open::Trust::Reduced);
+        assert_eq!(repo.workdir(), None, "core.bare is still used");
+        assert_eq!(repo.hooks_dir(), git_dir.join("hooks"));
+        assert!(repo.fsmonitor_command().is_none());
+        assert!(repo.filter_driver("lfs").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn isolated_options_ignore_the_repository_configuration() -> crate::Result {
+        let (tmp, repo) = hostile_repo()?;
+        let git_dir = repo.git_dir().to_owned();
+        let repo = git_repository::open_opts(tmp.path(), open::Options::isolated())?;
+        assert_eq!(repo.trust(), open::Trust::Reduced);
+        assert_eq!(repo.git_dir(), git_dir);
+        assert_eq!(repo.workdir(), Some(tmp.path()), "core.bare isn't read");
+        assert_eq!(repo.hooks_dir(), git_dir.join("hooks"));
+        assert!(repo.fsmonitor_command().is_none());
+        assert!(repo.filter_driver("lfs").is_none());
+
+        let repo = Repository::open_opts(
+            tmp.path(),
+            open::Options::default().repo_config(false).trust(open::Trust::Full),
+        )?;
+        assert!(
+            repo.fsmonitor_command().is_none(),
+            "full trust doesn't matter if there is no configuration"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_object_formats_are_detected_even_without_repository_configuration() -> crate::Result {
+        let tmp = tempfile::tempdir()?;
+        let repo = git_repository::init(tmp.path())?;
+        let config_path = repo.git_dir().join("config");
+        let config = std::fs::read_to_string(&config_path)?
+            .replace("repositoryformatversion = 0", "repositoryformatversion = 1");
+        std::fs::write(&config_path, config + "[extensions]\n\tobjectFormat = sha256\n")?;
+        assert!(matches!(
+            Repository::open_opts(tmp.path(), open::Options::isolated()),
+            Err(open::Error::UnsupportedObjectFormat { .. })
+        ));
+        Ok(())
+    }
+}
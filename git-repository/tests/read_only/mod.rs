@@ -0,0 +1,122 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use git_odb::loose;
+use git_repository::{
+    easy::{object, Access},
+    prelude::{ObjectAccessExt, ReferenceAccessExt, RepositoryAccessExt},
+    refs::{file::transaction::prepare, transaction::PreviousValue},
+    Repository,
+};
+
+/// The size and modification time of every file and directory below `root`.
+fn snapshot(root: &Path) -> crate::Result<BTreeMap<PathBuf, (u64, SystemTime)>> {
+    let mut out = BTreeMap::new();
+    let mut dirs = vec![root.to_owned()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let md = std::fs::symlink_metadata(entry.path())?;
+            if md.is_dir() {
+                dirs.push(entry.path());
+            }
+            out.insert(entry.path(), (md.len(), md.modified()?));
+        }
+    }
+    Ok(out)
+}
+
+/// Make all files and directories below `root` read-only for as long as it lives, which doesn't prevent writes
+/// if tests run as superuser.
+struct Restricted<'a>(&'a Path);
+
+impl<'a> Restricted<'a> {
+    fn new(root: &'a Path) -> crate::Result<Self> {
+        set_mode(root, false)?;
+        Ok(Restricted(root))
+    }
+}
+
+impl Drop for Restricted<'_> {
+    fn drop(&mut self) {
+        set_mode(self.0, true).ok();
+    }
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, writable: bool) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let md = std::fs::symlink_metadata(path)?;
+    if md.file_type().is_symlink() {
+        return Ok(());
+    }
+    if md.is_dir() {
+        if writable {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+        }
+        for entry in std::fs::read_dir(path)? {
+            set_mode(&entry?.path(), writable)?;
+        }
+    }
+    let mode = match (md.is_dir(), writable) {
+        (true, true) => 0o755,
+        (true, false) => 0o555,
+        (false, true) => 0o644,
+        (false, false) => 0o444,
+    };
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn set_mode(path: &Path, writable: bool) -> std::io::Result<()> {
+    let mut perms = std::fs::metadata(path)?.permissions();
+    perms.set_readonly(!writable);
+    std::fs::set_permissions(path, perms)
+}
+
+#[test]
+fn reading_writes_nothing_and_all_writes_fail_with_a_dedicated_error() -> crate::Result {
+    let (_repo, tmp) = crate::repo_rw("make_basic_repo.sh")?;
+    let root = tmp.path().to_owned();
+    let _restricted = Restricted::new(&root)?;
+    let before = snapshot(&root)?;
+
+    let repo = Repository::open_read_only(&root)?;
+    assert!(repo.is_read_only());
+    assert!(
+        repo.for_worktree("does-not-exist").is_err(),
+        "there are no linked worktrees, which would be read-only as well"
+    );
+    let repo = repo.into_easy();
+
+    let head = repo.head()?.into_fully_peeled_id().expect("born")?;
+    let tree_id = head.object()?.peel_to_commit()?.commit()?.tree();
+    let tree = repo.find_object(tree_id)?;
+    assert_eq!(tree.kind, git_repository::objs::Kind::Tree);
+    assert_eq!(repo.references()?.all()?.count(), 1);
+    assert!(!repo.maintenance_needed()?.is_needed());
+
+    assert!(matches!(
+        repo.write_object(git_repository::objs::Tree::empty()),
+        Err(object::write::Error::OdbWrite(loose::write::Error::ReadOnly { .. }))
+    ));
+    assert!(matches!(
+        repo.reference("refs/heads/new", head.detach(), PreviousValue::MustNotExist, "create"),
+        Err(git_repository::easy::reference::edit::Error::FileTransactionPrepare(
+            prepare::Error::ReadOnly
+        ))
+    ));
+    assert_eq!(
+        repo.repo()?
+            .cleanup_stale_tempfiles(Duration::from_secs(0))
+            .expect_err("read-only")
+            .kind(),
+        std::io::ErrorKind::PermissionDenied
+    );
+
+    assert_eq!(snapshot(&root)?, before, "no file was created, changed or removed");
+    Ok(())
+}
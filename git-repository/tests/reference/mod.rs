@@ -18,3 +18,370 @@ mod log {
         );
     }
 }
+
+mod update_ref {
+    use std::{path::Path, process::Command};
+
+    use git_repository as git;
+    use git_repository::{
+        prelude::ReferenceAccessExt,
+        reference::update_ref::{self, parse::Error, Options, Outcome},
+    };
+
+    fn git_output(dir: &Path, args: &[&str], stdin: Option<&[u8]>) -> crate::Result<(bool, String)> {
+        use std::io::Write;
+        let mut child = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .spawn()?;
+        child
+            .stdin
+            .take()
+            .expect("piped")
+            .write_all(stdin.unwrap_or_default())?;
+        let out = child.wait_with_output()?;
+        Ok((out.status.success(), String::from_utf8(out.stdout)?))
+    }
+
+    fn ref_state(dir: &Path) -> crate::Result<String> {
+        let (_, refs) = git_output(dir, &["for-each-ref", "--format=%(refname) %(objectname)"], None)?;
+        let (is_symbolic, head) = git_output(dir, &["symbolic-ref", "-q", "HEAD"], None)?;
+        let head = if is_symbolic {
+            head
+        } else {
+            git_output(dir, &["rev-parse", "HEAD"], None)?.1
+        };
+        Ok(format!("{}HEAD {}", refs, head))
+    }
+
+    fn committer() -> git::actor::Signature {
+        git::actor::Signature {
+            name: "committer".into(),
+            email: "committer@example.com".into(),
+            time: git::actor::Time {
+                time: 0,
+                offset: 0,
+                sign: git::actor::Sign::Plus,
+            },
+        }
+    }
+
+    /// Return the ids of the first and the second commit.
+    fn ids(dir: &Path) -> crate::Result<(String, String)> {
+        let (_, out) = git_output(dir, &["rev-parse", "main~1", "main"], None)?;
+        let mut lines = out.lines().map(ToOwned::to_owned);
+        Ok((lines.next().expect("c1"), lines.next().expect("c2")))
+    }
+
+    /// Run `input` produced by `make_input(c1, c2)` through us and through git, and assert both succeed or fail and produce the
+    /// same references. Return true if the input could be applied.
+    fn assert_same_as_git(make_input: impl Fn(&str, &str) -> String, options: Options) -> crate::Result<bool> {
+        let ours = git_testtools::scripted_fixture_repo_writable("make_basic_repo.sh")?;
+        let theirs = git_testtools::scripted_fixture_repo_writable("make_basic_repo.sh")?;
+        let (c1, c2) = ids(ours.path())?;
+        let input = make_input(&c1, &c2);
+        let input = if options.nul_terminated {
+            input.replace('|', "\0")
+        } else {
+            input
+        };
+
+        let repo = git::Repository::open(ours.path())?.into_easy();
+        let our_success = update_ref::parse(input.as_bytes(), &options)
+            .map_err(|err| Box::new(err) as Box<dyn std::error::Error>)
+            .and_then(|transactions| {
+                update_ref::apply(
+                    &repo,
+                    transactions,
+                    git::lock::acquire::Fail::Immediately,
+                    Some(&committer()),
+                )
+                .map_err(Into::into)
+            })
+            .is_ok();
+
+        let mut args = vec!["update-ref", "--stdin"];
+        if options.nul_terminated {
+            args.push("-z");
+        }
+        if !options.deref {
+            args.push("--no-deref");
+        }
+        let (their_success, _) = git_output(theirs.path(), &args, Some(input.as_bytes()))?;
+
+        assert_eq!(
+            our_success, their_success,
+            "both agree on success for input {:?}",
+            input
+        );
+        assert_eq!(ref_state(ours.path())?, ref_state(theirs.path())?, "input: {:?}", input);
+        Ok(our_success)
+    }
+
+    #[test]
+    fn verifications_are_checked_while_holding_the_lock_of_their_reference() -> crate::Result {
+        let dir = git_testtools::scripted_fixture_repo_writable("make_basic_repo.sh")?;
+        let (_, c2) = ids(dir.path())?;
+        let repo = git::Repository::open(dir.path())?.into_easy();
+        let input = format!(
+            "verify refs/heads/main {c2}\nverify refs/heads/missing\ncreate refs/heads/new {c2}\n",
+            c2 = c2
+        );
+        let transactions = update_ref::parse(input.as_bytes(), &Options::default())?;
+
+        let lock = dir.path().join(".git").join("refs").join("heads").join("main.lock");
+        std::fs::write(&lock, "")?;
+        assert!(
+            update_ref::apply(
+                &repo,
+                transactions.clone(),
+                git::lock::acquire::Fail::Immediately,
+                Some(&committer())
+            )
+            .is_err(),
+            "the verified reference is locked by someone else"
+        );
+        assert!(repo.try_find_reference("refs/heads/new")?.is_none());
+        std::fs::remove_file(&lock)?;
+
+        let edits = update_ref::apply(
+            &repo,
+            transactions,
+            git::lock::acquire::Fail::Immediately,
+            Some(&committer()),
+        )?;
+        assert_eq!(edits.len(), 1);
+        assert_eq!(
+            edits[0]
+                .iter()
+                .map(|edit| edit.name.as_bstr().to_string())
+                .collect::<Vec<_>>(),
+            ["refs/heads/new"],
+            "verifications aren't returned as edits"
+        );
+        assert!(repo.try_find_reference("refs/heads/missing")?.is_none());
+        assert!(
+            !dir.path()
+                .join(".git")
+                .join("logs")
+                .join("refs")
+                .join("heads")
+                .join("missing")
+                .exists(),
+            "verifying doesn't write reference logs"
+        );
+        Ok(())
+    }
+
+    fn z() -> Options {
+        Options {
+            nul_terminated: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn documented_commands_without_transaction() -> crate::Result {
+        assert!(assert_same_as_git(
+            |c1, c2| {
+                format!(
+                "create refs/heads/new {c2}\nupdate refs/heads/any {c1}\nupdate refs/heads/other {c1} {zero}\nverify refs/heads/main {c2}\nverify refs/heads/missing\n",
+                c1 = c1,
+                c2 = c2,
+                zero = "0".repeat(40)
+            )
+            },
+            Options::default()
+        )?);
+        assert!(assert_same_as_git(
+            |c1, c2| {
+                format!(
+                "update refs/heads/main|{c1}|{c2}|create refs/heads/new|{c2}|update refs/heads/any|{c1}||verify refs/heads/missing||",
+                c1 = c1,
+                c2 = c2
+            )
+            },
+            z()
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn deletions() -> crate::Result {
+        assert!(assert_same_as_git(
+            |c1, _c2| {
+                format!(
+                "create refs/heads/a {c1}\ncreate refs/heads/b {c1}\ncommit\nstart\ndelete refs/heads/a {c1}\nupdate refs/heads/b {zero}\ndelete refs/heads/main\nupdate refs/heads/missing {zero} {zero}\ncommit\n",
+                c1 = c1,
+                zero = "0".repeat(40)
+            )
+            },
+            Options::default()
+        )?);
+        assert!(assert_same_as_git(
+            |_c1, c2| format!("delete refs/heads/main|{c2}|update refs/heads/other|||", c2 = c2),
+            z()
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn no_deref() -> crate::Result {
+        assert!(assert_same_as_git(
+            |c1, _c2| format!("update HEAD {c1}\n", c1 = c1),
+            Options::default()
+        )?);
+        assert!(assert_same_as_git(
+            |c1, _c2| format!("option no-deref\nupdate HEAD {c1}\n", c1 = c1),
+            Options::default()
+        )?);
+        assert!(assert_same_as_git(
+            |c1, _c2| format!("update HEAD {c1}\n", c1 = c1),
+            Options {
+                deref: false,
+                ..Default::default()
+            }
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn multiple_transactions() -> crate::Result {
+        assert!(assert_same_as_git(
+            |c1, c2| {
+                format!(
+                "start\ncreate refs/heads/a {c1}\nprepare\ncommit\nstart\ncreate refs/heads/b {c1}\nprepare\nabort\nstart\ncreate refs/heads/c {c2}\nabort\nstart\ncreate refs/heads/d {c2}\n",
+                c1 = c1,
+                c2 = c2
+            )
+            },
+            Options::default()
+        )?);
+        assert!(assert_same_as_git(
+            |c1, _c2| format!(
+                "create refs/heads/a|{c1}|start|commit|start|create refs/heads/b|{c1}|prepare|",
+                c1 = c1
+            ),
+            z()
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn failing_transactions_change_nothing() -> crate::Result {
+        assert!(!assert_same_as_git(
+            |c1, c2| format!(
+                "create refs/heads/a {c1}\nverify refs/heads/main {c1}\ncreate refs/heads/b {c2}\n",
+                c1 = c1,
+                c2 = c2
+            ),
+            Options::default()
+        )?);
+        assert!(!assert_same_as_git(
+            |c1, c2| format!(
+                "create refs/heads/a {c1}\nupdate refs/heads/main {c2} {c1}\n",
+                c1 = c1,
+                c2 = c2
+            ),
+            Options::default()
+        )?);
+        assert!(!assert_same_as_git(
+            |c1, _c2| format!("create refs/heads/main|{c1}|", c1 = c1),
+            z()
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_input() -> crate::Result {
+        let repo = git_testtools::scripted_fixture_repo_writable("make_basic_repo.sh")?;
+        let c1 = "1".repeat(40);
+        for (input, options, expected_line) in [
+            (format!("frobnicate refs/heads/a {}\n", c1), Options::default(), 1),
+            (
+                format!("update refs/heads/a {c} {c} {c}\n", c = c1),
+                Options::default(),
+                1,
+            ),
+            (
+                format!("create refs/heads/a {}\nupdate refs/heads/a  {}\n", c1, c1),
+                Options::default(),
+                2,
+            ),
+            ("update refs/heads/a not-a-hash\n".into(), Options::default(), 1),
+            (format!("update refs/heads/a..b {}\n", c1), Options::default(), 1),
+            (
+                format!("create refs/heads/a {}\n", "0".repeat(40)),
+                Options::default(),
+                1,
+            ),
+            ("update refs/heads/a\n".into(), Options::default(), 1),
+            ("option no-deref\noption frobnicate\n".into(), Options::default(), 2),
+            ("start\nstart\n".into(), Options::default(), 2),
+            ("start\nprepare\nverify refs/heads/main\n".into(), Options::default(), 3),
+            ("commit\nverify refs/heads/main\n".into(), Options::default(), 2),
+            ("start extra\n".into(), Options::default(), 1),
+            ("\nstart\n".into(), Options::default(), 1),
+            (" start\n".into(), Options::default(), 1),
+            ("verify refs/heads/main\0\0start\0create refs/heads/a\0".into(), z(), 3),
+        ] {
+            let err = update_ref::parse(input.as_bytes(), &options).expect_err("input is malformed");
+            let line = match err {
+                Error::UnknownCommand { line, .. }
+                | Error::UnknownOption { line, .. }
+                | Error::Syntax { line, .. }
+                | Error::RefName { line, .. }
+                | Error::ObjectId { line, .. }
+                | Error::State { line, .. } => line,
+            };
+            assert_eq!(line, expected_line, "{:?}: {}", input, err);
+
+            let args: &[&str] = if options.nul_terminated {
+                &["update-ref", "--stdin", "-z"]
+            } else {
+                &["update-ref", "--stdin"]
+            };
+            let (git_success, _) = git_output(repo.path(), args, Some(input.as_bytes()))?;
+            assert!(!git_success, "git rejects {:?} as well", input);
+        }
+
+        let err = update_ref::parse(b"option frobnicate\n", &Options::default()).expect_err("unknown option");
+        assert_eq!(err.to_string(), "Line 1: option: unknown option \"frobnicate\"");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_output() -> crate::Result {
+        let transactions = update_ref::parse(
+            b"start\nverify refs/heads/main\nprepare\nabort\nstart\noption no-deref\ndelete HEAD\ncommit\n",
+            &Options {
+                log_message: "message".into(),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0].outcome, Outcome::Abort);
+        assert!(transactions[0].prepared);
+        assert!(transactions[0].edits.is_empty());
+        assert_eq!(transactions[0].verifications.len(), 1);
+        assert_eq!(transactions[0].verifications[0].expected, None);
+
+        assert_eq!(transactions[1].outcome, Outcome::Commit);
+        assert!(!transactions[1].prepared);
+        assert_eq!(transactions[1].edits.len(), 1);
+        assert!(
+            !transactions[1].edits[0].deref,
+            "the option only applies to the next command"
+        );
+        assert_eq!(transactions[1].edits[0].name.as_bstr(), "HEAD");
+
+        assert!(
+            update_ref::parse(b"", &Options::default())?.is_empty(),
+            "no commands, no transaction"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+//! Assure that iterating and finding references doesn't hold all of them in memory at once, which matters in repositories
+//! with hundreds of thousands of references.
+//!
+//! This is a test binary of its own as it installs a global allocator to count allocated bytes, and contains only one test
+//! to keep other threads from interfering with the measurements.
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use git_repository::prelude::ReferenceAccessExt;
+
+type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+struct CountingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+fn track_allocation(size: usize) {
+    let current = CURRENT.fetch_add(size, Ordering::SeqCst) + size;
+    PEAK.fetch_max(current, Ordering::SeqCst);
+}
+
+fn track_deallocation(size: usize) {
+    CURRENT.fetch_sub(size, Ordering::SeqCst);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            track_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        track_deallocation(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            track_deallocation(layout.size());
+            track_allocation(new_size);
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Run `f` and return its result along with the peak amount of bytes it allocated in addition to what was allocated before.
+fn peak_allocation_of<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let base = CURRENT.load(Ordering::SeqCst);
+    PEAK.store(base, Ordering::SeqCst);
+    let out = f();
+    (out, PEAK.load(Ordering::SeqCst).saturating_sub(base))
+}
+
+const TAG_COUNT: usize = 100_000;
+const MAX_PEAK_BYTES: usize = 64 * 1024;
+
+#[test]
+fn iteration_and_lookup_in_100k_packed_refs_uses_bounded_memory() -> Result {
+    let repo = git_repository::Repository::open(git_testtools::scripted_fixture_repo_read_only(
+        "make_repo_with_many_packed_refs.sh",
+    )?)?
+    .into_easy();
+    let head_id = repo.head()?.into_fully_peeled_id().expect("born")?.detach();
+
+    let (naive, naive_peak) = peak_allocation_of(|| {
+        repo.references()?
+            .all()?
+            .map(|r| r.map(|r| r.detach()))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|err| err as Box<dyn std::error::Error>)
+    });
+    let naive = naive?;
+    assert_eq!(naive.len(), TAG_COUNT + 2, "two branches and all tags");
+
+    let (matching, streaming_peak) = peak_allocation_of(|| -> Result<usize> {
+        let platform = repo.references()?;
+        let mut matching = 0;
+        for (actual, expected) in platform.all()?.zip(naive.iter()) {
+            let actual = actual.expect("valid reference");
+            if actual.name() == expected.name.to_ref() && actual.target().as_id() == expected.target.as_id() {
+                matching += 1;
+            }
+        }
+        Ok(matching)
+    });
+    assert_eq!(matching?, naive.len(), "streaming yields the same as collecting");
+    assert!(
+        streaming_peak < MAX_PEAK_BYTES,
+        "iteration allocated up to {} bytes, collecting took {} bytes",
+        streaming_peak,
+        naive_peak
+    );
+
+    let (tags, tags_peak) = peak_allocation_of(|| -> Result<usize> {
+        let platform = repo.references()?;
+        let mut count = 0;
+        for tag in platform.tags()? {
+            assert_eq!(tag.expect("valid tag").target().as_id(), Some(head_id.as_ref()));
+            count += 1;
+        }
+        Ok(count)
+    });
+    assert_eq!(tags?, TAG_COUNT);
+    assert!(tags_peak < MAX_PEAK_BYTES, "listing tags allocated {} bytes", tags_peak);
+
+    let (branches, branches_peak) = peak_allocation_of(|| -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        for branch in repo.references()?.local_branches()? {
+            names.push(branch.expect("valid branch").name().as_bstr().to_string());
+        }
+        Ok(names)
+    });
+    assert_eq!(branches?, vec!["refs/heads/main", "refs/heads/other"]);
+    assert!(
+        branches_peak < MAX_PEAK_BYTES,
+        "listing branches allocated {} bytes",
+        branches_peak
+    );
+
+    let (found, lookup_peak) = peak_allocation_of(|| -> Result<_> {
+        Ok(repo.find_reference("t-050000")?.target().as_id().map(ToOwned::to_owned))
+    });
+    assert_eq!(
+        found?,
+        Some(head_id),
+        "tags are found by binary search in the packed buffer"
+    );
+    assert!(
+        lookup_peak < MAX_PEAK_BYTES,
+        "finding a tag allocated {} bytes",
+        lookup_peak
+    );
+    assert!(
+        naive_peak > 10 * MAX_PEAK_BYTES,
+        "collecting all references is expected to be expensive, took {} bytes",
+        naive_peak
+    );
+    Ok(())
+}
@@ -24,7 +24,14 @@ fn basic_rw_repo() -> crate::Result<(Easy, tempfile::TempDir)> {
     easy_repo_rw("make_basic_repo.sh")
 }
 
+mod cleanup;
 mod discover;
 mod easy;
 mod init;
+mod open;
+mod read_only;
 mod reference;
+#[cfg(feature = "serde1")]
+mod summary;
+mod verify;
+mod worktree;
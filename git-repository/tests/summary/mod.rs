@@ -0,0 +1,222 @@
+use git_repository::{
+    hash::ObjectId,
+    prelude::{ObjectAccessExt, ReferenceAccessExt},
+    summary::{self, Encoding, Text},
+    Easy,
+};
+use serde_json::json;
+
+fn repo() -> crate::Result<Easy> {
+    crate::repo("make_repo_for_summaries.sh").map(|r| r.into_easy())
+}
+
+fn head_id(repo: &Easy) -> crate::Result<ObjectId> {
+    Ok(repo.head()?.into_fully_peeled_id().expect("born")?.detach())
+}
+
+fn commit(repo: &Easy, id: ObjectId, encoding: Encoding) -> crate::Result<summary::Commit> {
+    let object = repo.find_object(id)?;
+    let commit = summary::Commit::new(id, &object.commit()?, encoding);
+    Ok(commit)
+}
+
+fn commit_json(message: serde_json::Value) -> serde_json::Value {
+    json!({
+        "id": "54d6338916ee52c01b22971a9881169e4297e5e0",
+        "tree": "7707090cf98e409e0810df1e4e1ee9021200cdaf",
+        "parents": ["7351988041a5ac7791bb49df0d61c9390c3f026e"],
+        "author": {
+            "name": "author",
+            "email": "author@example.com",
+            "time": "2000-01-01T00:00:00-07:00"
+        },
+        "committer": {
+            "name": "committer",
+            "email": "committer@example.com",
+            "time": "2000-01-02T12:34:56+02:30"
+        },
+        "encoding": "ISO-8859-1",
+        "message": message
+    })
+}
+
+#[test]
+fn commit_with_latin1_message_in_lossy_and_lossless_mode() -> crate::Result {
+    let repo = repo()?;
+    let id = head_id(&repo)?;
+    let object = repo.find_object(id)?;
+    let commit = object.commit()?;
+
+    let lossy = summary::Commit::new(id, &commit, Encoding::Lossy);
+    assert_eq!(
+        serde_json::to_value(&lossy)?,
+        commit_json(json!(
+            "second in a caf\u{FFFD}\n\nSigned-off-by: author <author@example.com>\n"
+        ))
+    );
+    assert_eq!(
+        summary::Commit::from((id, &commit)),
+        lossy,
+        "conversions are lossy by default"
+    );
+
+    let lossless = summary::Commit::new(id, &commit, Encoding::Lossless);
+    assert_eq!(
+        serde_json::to_value(&lossless)?,
+        commit_json(
+            json!({"base64": "c2Vjb25kIGluIGEgY2Fm6QoKU2lnbmVkLW9mZi1ieTogYXV0aG9yIDxhdXRob3JAZXhhbXBsZS5jb20+Cg=="})
+        )
+    );
+    assert_eq!(
+        lossless.message.to_bytes()?,
+        commit.message,
+        "the original bytes can be restored"
+    );
+    assert_eq!(
+        serde_json::from_str::<summary::Commit>(&serde_json::to_string(&lossless)?)?,
+        lossless,
+        "summaries can be deserialized as well"
+    );
+    Ok(())
+}
+
+#[test]
+fn parents_and_signatures_in_utc() -> crate::Result {
+    let repo = repo()?;
+    let first = commit(
+        &repo,
+        ObjectId::from_hex(b"7351988041a5ac7791bb49df0d61c9390c3f026e")?,
+        Encoding::Lossless,
+    )?;
+    assert_eq!(first.parents, Vec::<String>::new());
+    assert_eq!(first.author.time, "2000-01-01T00:00:00+00:00");
+    assert_eq!(first.committer.time, "2000-01-02T00:00:00+00:00");
+    assert_eq!(first.encoding, None);
+    assert_eq!(
+        first.message,
+        Text::Utf8("first\n".into()),
+        "valid UTF-8 is never encoded"
+    );
+    Ok(())
+}
+
+#[test]
+fn references_with_their_targets() -> crate::Result {
+    let repo = repo()?;
+    let refs = repo
+        .references()?
+        .all()?
+        .map(|r| r.map(|r| summary::Reference::from(&r)))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err as Box<dyn std::error::Error>)?;
+    assert_eq!(
+        serde_json::to_value(&refs)?,
+        json!([
+            {
+                "name": "refs/heads/main",
+                "target": { "object": "54d6338916ee52c01b22971a9881169e4297e5e0" },
+                "peeled": null
+            },
+            {
+                "name": "refs/remotes/origin/HEAD",
+                "target": { "symbolic": "refs/remotes/origin/main" },
+                "peeled": null
+            },
+            {
+                "name": "refs/remotes/origin/main",
+                "target": { "object": "54d6338916ee52c01b22971a9881169e4297e5e0" },
+                "peeled": null
+            },
+            {
+                "name": "refs/tags/first",
+                "target": { "object": "7351988041a5ac7791bb49df0d61c9390c3f026e" },
+                "peeled": null
+            }
+        ])
+    );
+
+    let mut main = repo.find_reference("main")?;
+    main.peel_to_id_in_place()?;
+    assert_eq!(
+        summary::Reference::new(&main.inner, Encoding::Lossless)
+            .peeled
+            .as_deref(),
+        Some("54d6338916ee52c01b22971a9881169e4297e5e0"),
+    );
+    Ok(())
+}
+
+#[cfg(feature = "git-diff")]
+#[test]
+fn tree_changes_with_non_utf8_paths() -> crate::Result {
+    use git_repository::{objs::TreeRefIter, prelude::TreeIterExt};
+
+    let repo = repo()?;
+    let tree_of = |hex: &[u8]| -> crate::Result<Vec<u8>> {
+        let object = repo.find_object(ObjectId::from_hex(hex)?)?;
+        Ok(object.data.to_vec())
+    };
+    let (previous, current) = (
+        tree_of(b"59ea6591f74e34fa1ccb267ca4dc6ff08e4dc6ff")?,
+        tree_of(b"7707090cf98e409e0810df1e4e1ee9021200cdaf")?,
+    );
+    let mut recorder = git_diff::tree::Recorder::default();
+    TreeRefIter::from_bytes(&previous).changes_needed(
+        TreeRefIter::from_bytes(&current),
+        git_diff::tree::State::default(),
+        |oid, buf| {
+            let object = repo.find_object(oid).ok()?;
+            buf.clear();
+            buf.extend_from_slice(&object.data);
+            Some(TreeRefIter::from_bytes(buf))
+        },
+        &mut recorder,
+    )?;
+
+    let changes = |encoding| {
+        recorder
+            .records
+            .iter()
+            .map(|change| summary::Change::new(change, encoding))
+            .collect::<Vec<_>>()
+    };
+    let expected = |path_of_addition: serde_json::Value| {
+        json!([
+            {
+                "kind": "modification",
+                "path": "a",
+                "previous_mode": "100644",
+                "previous_id": "78981922613b2afb6025042ff6bd878ac1994e85",
+                "mode": "100644",
+                "id": "5ea2ed416fbd4a4cbe227b75fe255dd7fa6bd4d6"
+            },
+            {
+                "kind": "addition",
+                "path": path_of_addition,
+                "mode": "100644",
+                "id": "a9128c283485202893f5af379dd9beccb6e79486"
+            },
+            {
+                "kind": "deletion",
+                "path": "dir",
+                "mode": "40000",
+                "id": "6be660545b31f61a82a87d2b1915f0b88bb9f16f"
+            },
+            {
+                "kind": "deletion",
+                "path": "dir/b",
+                "mode": "100644",
+                "id": "61780798228d17af2d34fce4cfbdf35556832472"
+            }
+        ])
+    };
+    assert_eq!(
+        serde_json::to_value(changes(Encoding::Lossy))?,
+        expected(json!("caf\u{FFFD}"))
+    );
+    assert_eq!(
+        serde_json::to_value(changes(Encoding::Lossless))?,
+        expected(json!({"base64": "Y2Fm6Q=="}))
+    );
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use std::{
+    collections::HashSet,
+    sync::{atomic::AtomicBool, Arc, Mutex},
+    thread::ThreadId,
+};
+
+use git_features::progress::{self, MessageLevel, Progress, Unit};
+use git_repository::{verify::Mode, Repository, Threads};
+
+#[test]
+fn objects_of_alternates_are_verified_too() -> crate::Result {
+    let dir = git_testtools::scripted_fixture_repo_read_only("make_repo_with_alternates.sh")?;
+    let repo = Repository::open(dir.join("b"))?;
+    let outcome = repo.verify_integrity(Mode::Full, None, progress::Discard, Arc::new(AtomicBool::new(false)))?;
+    let stats = &outcome.statistics;
+    assert_eq!(
+        (stats.num_commits, stats.num_trees, stats.num_blobs),
+        (2, 2, 2),
+        "one commit, tree and blob in each repository"
+    );
+    assert!(outcome.packs.is_empty(), "all objects are loose");
+    Ok(())
+}
+
+mod parallelism {
+    use super::*;
+
+    /// A progress implementation recording the threads it's used from, along with all of its children.
+    #[derive(Clone, Default)]
+    struct RecordThreads(Arc<Mutex<HashSet<ThreadId>>>);
+
+    impl RecordThreads {
+        fn record(&self) {
+            self.0.lock().unwrap().insert(std::thread::current().id());
+        }
+
+        fn threads(&self) -> HashSet<ThreadId> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    impl Progress for RecordThreads {
+        type SubProgress = Self;
+
+        fn add_child(&mut self, _name: impl Into<String>) -> Self::SubProgress {
+            self.record();
+            self.clone()
+        }
+
+        fn init(&mut self, _max: Option<usize>, _unit: Option<Unit>) {
+            self.record();
+        }
+
+        fn set(&mut self, _step: usize) {
+            self.record();
+        }
+
+        fn unit(&self) -> Option<Unit> {
+            self.record();
+            None
+        }
+
+        fn max(&self) -> Option<usize> {
+            self.record();
+            None
+        }
+
+        fn set_max(&mut self, _max: Option<usize>) -> Option<usize> {
+            self.record();
+            None
+        }
+
+        fn step(&self) -> usize {
+            self.record();
+            0
+        }
+
+        fn inc_by(&mut self, _step: usize) {
+            self.record();
+        }
+
+        fn inc(&mut self) {
+            self.record();
+        }
+
+        fn set_name(&mut self, _name: impl Into<String>) {
+            self.record();
+        }
+
+        fn name(&self) -> Option<String> {
+            self.record();
+            None
+        }
+
+        fn message(&mut self, _level: MessageLevel, _message: impl Into<String>) {
+            self.record();
+        }
+    }
+
+    fn threads_used_to_verify(threads: Threads, thread_limit: Option<usize>) -> crate::Result<HashSet<ThreadId>> {
+        let mut repo = crate::repo("make_repo_with_many_objects.sh")?;
+        repo.set_object_access_parallelism(threads);
+        let progress = RecordThreads::default();
+        let outcome = repo.verify_integrity(
+            Mode::Full,
+            thread_limit,
+            progress.clone(),
+            Arc::new(AtomicBool::new(false)),
+        )?;
+        assert_eq!(outcome.packs.len(), 1, "there is one pack to traverse");
+        assert_eq!(
+            outcome.statistics.num_blobs, 16_800,
+            "all blobs are verified either way"
+        );
+        Ok(progress.threads())
+    }
+
+    #[test]
+    fn none_spawns_no_threads_even_if_a_thread_limit_is_given() -> crate::Result {
+        for thread_limit in &[None, Some(4)] {
+            assert_eq!(
+                threads_used_to_verify(Threads::None, *thread_limit)?,
+                Some(std::thread::current().id()).into_iter().collect(),
+                "all work is done in the calling thread"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "max-performance")]
+    fn a_fixed_amount_of_threads_is_used_even_on_a_single_core() -> crate::Result {
+        assert!(
+            threads_used_to_verify(Threads::Fixed(2), None)?.len() > 1,
+            "pack verification is done in threads"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn the_default_is_auto_and_linked_worktrees_inherit_the_setting() -> crate::Result {
+        let dir = git_testtools::scripted_fixture_repo_read_only("make_repo_with_worktrees.sh")?;
+        let mut repo = Repository::open(dir.join("main"))?;
+        assert_eq!(repo.object_access_parallelism(), Threads::Auto);
+        assert_eq!(Threads::Auto.thread_limit(), None);
+        assert_eq!(Threads::None.thread_limit(), Some(1));
+
+        repo.set_object_access_parallelism(Threads::None);
+        assert_eq!(repo.for_worktree("wt-a")?.object_access_parallelism(), Threads::None);
+        let easy = repo.into_easy();
+        assert_eq!(
+            easy.repo.object_access_parallelism(),
+            Threads::None,
+            "handles share the repository"
+        );
+        Ok(())
+    }
+}
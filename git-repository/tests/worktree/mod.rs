@@ -0,0 +1,116 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use git_repository::{hash::ObjectId, prelude::ReferenceAccessExt, Repository};
+
+fn rev_parse(dir: &Path, spec: &str) -> crate::Result<ObjectId> {
+    let out = std::process::Command::new("git")
+        .args(["rev-parse", spec])
+        .current_dir(dir)
+        .output()?;
+    assert!(out.status.success(), "{:?}", out);
+    Ok(ObjectId::from_hex(String::from_utf8(out.stdout)?.trim().as_bytes())?)
+}
+
+/// Linked worktrees refer to each other with absolute paths, so the fixture is accessed through one as well.
+fn root() -> crate::Result<PathBuf> {
+    Ok(git_testtools::scripted_fixture_repo_read_only("make_repo_with_worktrees.sh")?.canonicalize()?)
+}
+
+#[test]
+fn linked_worktrees_are_listed_with_their_state() -> crate::Result {
+    let root = root()?;
+    let repo = Repository::open(root.join("main"))?;
+    let worktrees = repo.worktrees()?;
+
+    assert_eq!(
+        worktrees.iter().map(|wt| wt.name()).collect::<Vec<_>>(),
+        vec!["gone", "wt-a", "wt-b"]
+    );
+    let (gone, a, b) = (&worktrees[0], &worktrees[1], &worktrees[2]);
+    assert_eq!(a.path(), root.join("wt-a"));
+    assert_eq!(a.git_dir(), root.join("main/.git/worktrees/wt-a"));
+    assert_eq!(a.head(), Some(rev_parse(&root.join("wt-a"), "HEAD")?));
+    assert_eq!(
+        b.head(),
+        Some(rev_parse(&root.join("main"), "main")?),
+        "detached heads work, too"
+    );
+    assert_eq!(gone.head(), b.head());
+    assert!(worktrees.iter().all(|wt| !wt.is_main()));
+
+    assert_eq!(
+        worktrees.iter().map(|wt| wt.is_locked()).collect::<Vec<_>>(),
+        vec![false, false, true]
+    );
+    assert_eq!(
+        worktrees
+            .iter()
+            .map(|wt| wt.is_prunable(Duration::from_secs(0)))
+            .collect::<Vec<_>>(),
+        vec![true, false, false],
+        "only worktrees whose directory was removed can be pruned"
+    );
+    assert!(
+        !gone.is_prunable(Duration::from_secs(60 * 60 * 24 * 365 * 100)),
+        "worktrees are kept until they expire, which is measured from their last use"
+    );
+    Ok(())
+}
+
+#[test]
+fn main_worktree_is_the_work_tree_of_the_repository_unless_it_is_bare() -> crate::Result {
+    let root = root()?;
+    let repo = Repository::open(root.join("main"))?;
+    let main = repo.main_worktree()?.expect("not bare");
+    assert!(main.is_main());
+    assert_eq!(main.name(), "");
+    assert_eq!(main.path(), root.join("main"));
+    assert_eq!(main.git_dir(), root.join("main/.git"));
+    assert_eq!(main.head(), Some(rev_parse(&root.join("main"), "HEAD")?));
+    assert!(!main.is_locked());
+    assert!(!main.is_prunable(Duration::from_secs(0)));
+
+    let bare = Repository::open(root.join("bare.git"))?;
+    assert!(bare.main_worktree()?.is_none());
+    assert!(bare.worktrees()?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn repositories_for_linked_worktrees_use_their_head_and_share_everything_else() -> crate::Result {
+    let root = root()?;
+    let main = Repository::open(root.join("main"))?;
+    let repo = main.for_worktree("wt-a")?;
+
+    assert_eq!(repo.workdir(), Some(root.join("wt-a").as_path()));
+    assert_eq!(repo.git_dir(), root.join("main/.git/worktrees/wt-a"));
+    assert_eq!(repo.common_dir(), main.git_dir());
+    assert_eq!(main.common_dir(), main.git_dir());
+    assert_eq!(repo.objects_dir(), main.objects_dir());
+
+    assert_eq!(
+        repo.worktrees()?,
+        main.worktrees()?,
+        "all worktrees are visible from each of them"
+    );
+    assert_eq!(repo.main_worktree()?, main.main_worktree()?);
+
+    let repo = repo.into_easy();
+    let head = repo.head()?.into_fully_peeled_id().expect("born")?;
+    assert_eq!(head, rev_parse(&root.join("wt-a"), "HEAD")?);
+    assert_eq!(head.object()?.commit()?.message, "c2\n");
+    assert_eq!(
+        repo.find_reference("main")?.into_fully_peeled_id()?,
+        rev_parse(&root.join("main"), "main")?,
+        "branches are shared"
+    );
+
+    assert!(matches!(
+        main.for_worktree("gone-for-good"),
+        Err(git_repository::worktree::Error::NotFound { .. })
+    ));
+    Ok(())
+}
@@ -36,6 +36,12 @@ impl ForksafeTempfile {
             TempfileOrTemppath::Temppath(_) => None,
         }
     }
+    pub fn path(&self) -> &Path {
+        match &self.inner {
+            TempfileOrTemppath::Tempfile(file) => file.path(),
+            TempfileOrTemppath::Temppath(path) => path,
+        }
+    }
     pub fn close(self) -> Self {
         if let TempfileOrTemppath::Tempfile(file) = self.inner {
             ForksafeTempfile {
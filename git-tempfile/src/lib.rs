@@ -23,6 +23,8 @@
 //! * The application is performing a write operation on the tempfile when a signal arrives, preventing this tempfile to be removed,
 //!   but not others. Any other operation dealing with the tempfile suffers from the same issue.
 //!
+//! Use [`cleanup_stale()`] to remove such leftovers once they are old enough to not belong to an ongoing operation anymore.
+//!
 //! [signal-hook]: https://docs.rs/signal-hook
 #![deny(missing_docs, unsafe_code, rust_2018_idioms)]
 
@@ -47,6 +49,9 @@ use forksafe::ForksafeTempfile;
 pub mod handle;
 use crate::handle::{Closed, Writable};
 
+pub mod stale;
+pub use stale::cleanup_stale;
+
 static SIGNAL_HANDLER_MODE: AtomicUsize = AtomicUsize::new(SignalHandlerMode::default() as usize);
 static NEXT_MAP_INDEX: AtomicUsize = AtomicUsize::new(0);
 static REGISTER: Lazy<DashMap<usize, Option<ForksafeTempfile>>> = Lazy::new(|| {
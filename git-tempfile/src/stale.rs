@@ -0,0 +1,76 @@
+//! Remove temporary files and lock files left behind by processes which were terminated before they could clean up after themselves.
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::REGISTER;
+
+/// Return true if the file `name` looks like a temporary file created by this crate or by `git`, or like a lock file.
+///
+/// These are `<resource>.lock`, `.tmp*` as created by [`new()`][crate::new()] and `tmp_*` like `git`s `tmp_pack_*`,
+/// `tmp_idx_*` and `tmp_obj_*`.
+pub fn is_tempfile_name(name: &std::ffi::OsStr) -> bool {
+    let name = name.to_string_lossy();
+    name.ends_with(".lock") || name.starts_with(".tmp") || name.starts_with("tmp_")
+}
+
+/// Recursively remove all files in `directory` whose name [is that of a temporary file][is_tempfile_name()] and which
+/// weren't modified within `older_than`, returning the paths of all removed files.
+///
+/// Tempfiles registered by this process are never removed, but those of all other processes are, which is why `older_than`
+/// should be long enough for concurrent operations to finish, like an hour or more. Symbolic links are not followed.
+pub fn cleanup_stale(directory: impl AsRef<Path>, older_than: Duration) -> io::Result<Vec<PathBuf>> {
+    let current_pid = std::process::id();
+    let registered: Vec<PathBuf> = REGISTER
+        .iter()
+        .filter_map(|tempfile| {
+            tempfile
+                .as_ref()
+                .filter(|tf| tf.owning_process_id == current_pid)
+                .map(|tf| canonicalized(tf.path()))
+        })
+        .collect();
+    let now = SystemTime::now();
+    let mut out = Vec::new();
+    cleanup_stale_inner(
+        directory.as_ref(),
+        &|path, modified| {
+            now.duration_since(modified).map_or(false, |age| age >= older_than)
+                && !registered.contains(&canonicalized(path))
+        },
+        &mut out,
+    )?;
+    Ok(out)
+}
+
+fn canonicalized(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_owned())
+}
+
+fn cleanup_stale_inner(
+    directory: &Path,
+    is_stale: &dyn Fn(&Path, SystemTime) -> bool,
+    out: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+        if file_type.is_dir() {
+            cleanup_stale_inner(&path, is_stale, out)?;
+        } else if file_type.is_file()
+            && is_tempfile_name(&entry.file_name())
+            && is_stale(&path, entry.metadata()?.modified()?)
+        {
+            match std::fs::remove_file(&path) {
+                Ok(()) => out.push(path),
+                // Another process might have cleaned up in the mean time.
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+    Ok(())
+}
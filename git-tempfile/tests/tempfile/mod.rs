@@ -1,5 +1,8 @@
 mod fs;
 mod handle;
+mod stale;
+#[cfg(unix)]
+mod termination;
 
 mod force_setup {
     #[test]
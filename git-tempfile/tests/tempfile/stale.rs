@@ -0,0 +1,88 @@
+use std::{path::Path, time::Duration};
+
+use git_tempfile::{AutoRemove, ContainingDirectory};
+
+fn files_in(dir: &Path) -> Vec<String> {
+    let mut out: Vec<_> = walk(dir)
+        .into_iter()
+        .map(|p| {
+            p.strip_prefix(dir)
+                .expect("within dir")
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+        .collect();
+    out.sort();
+    out
+}
+
+fn walk(dir: &Path) -> Vec<std::path::PathBuf> {
+    let mut out = Vec::new();
+    for entry in std::fs::read_dir(dir).expect("readable") {
+        let path = entry.expect("valid").path();
+        if path.is_dir() {
+            out.extend(walk(&path));
+        } else {
+            out.push(path);
+        }
+    }
+    out
+}
+
+#[test]
+fn leftovers_of_other_processes_are_removed_once_they_are_old_enough() -> crate::Result {
+    let dir = tempfile::tempdir()?;
+    for path in &[
+        "HEAD.lock",
+        "HEAD",
+        "refs/heads/main.lock",
+        "refs/heads/main",
+        "objects/pack/tmp_pack_abc",
+        "objects/pack/tmp_idx_abc",
+        "objects/ab/.tmpXyZ",
+    ] {
+        let path = dir.path().join(path);
+        std::fs::create_dir_all(path.parent().expect("parent"))?;
+        std::fs::write(path, b"")?;
+    }
+    let _registered = git_tempfile::writable_at(
+        dir.path().join("config.lock"),
+        ContainingDirectory::Exists,
+        AutoRemove::Tempfile,
+    )?;
+    let _registered_with_random_name = git_tempfile::new(
+        dir.path().join("objects"),
+        ContainingDirectory::Exists,
+        AutoRemove::Tempfile,
+    )?;
+
+    assert!(
+        git_tempfile::cleanup_stale(dir.path(), Duration::from_secs(60 * 60))?.is_empty(),
+        "nothing is old enough"
+    );
+
+    let mut removed: Vec<_> = git_tempfile::cleanup_stale(dir.path(), Duration::from_secs(0))?
+        .into_iter()
+        .map(|p| p.file_name().expect("file").to_string_lossy().into_owned())
+        .collect();
+    removed.sort();
+    assert_eq!(
+        removed,
+        vec![".tmpXyZ", "HEAD.lock", "main.lock", "tmp_idx_abc", "tmp_pack_abc"]
+    );
+    let remaining = files_in(dir.path());
+    assert_eq!(
+        remaining
+            .iter()
+            .filter(|p| !p.starts_with("objects/.tmp"))
+            .collect::<Vec<_>>(),
+        vec!["HEAD", "config.lock", "refs/heads/main"],
+        "tempfiles registered by this process are kept, as well as all other files"
+    );
+    assert_eq!(
+        remaining.len(),
+        4,
+        "the registered tempfile with random name is kept as well"
+    );
+    Ok(())
+}
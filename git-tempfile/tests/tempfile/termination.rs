@@ -0,0 +1,71 @@
+use std::{
+    path::Path,
+    process::Command,
+    time::{Duration, Instant},
+};
+
+use git_tempfile::{AutoRemove, ContainingDirectory};
+
+const CHILD_DIR: &str = "GIT_TEMPFILE_TEST_CHILD_DIR";
+
+/// Create registered tempfiles in `dir` and signal readiness, then wait to be terminated.
+fn be_child(dir: &Path) -> crate::Result {
+    let _lock = git_tempfile::writable_at(dir.join("HEAD.lock"), ContainingDirectory::Exists, AutoRemove::Tempfile)?;
+    let _marker = git_tempfile::mark_at(
+        dir.join("refs/heads/main.lock"),
+        ContainingDirectory::CreateAllRaceProof(Default::default()),
+        AutoRemove::TempfileAndEmptyParentDirectoriesUntil {
+            boundary_directory: dir.to_owned(),
+        },
+    )?;
+    let _pack = git_tempfile::new(dir, ContainingDirectory::Exists, AutoRemove::Tempfile)?;
+    git_tempfile::new(dir, ContainingDirectory::Exists, AutoRemove::Tempfile)?.persist(dir.join("persisted"))?;
+    std::fs::write(dir.join("ready"), b"")?;
+    loop {
+        std::thread::sleep(Duration::from_secs(1));
+    }
+}
+
+#[test]
+fn registered_tempfiles_are_removed_when_the_process_is_terminated() -> crate::Result {
+    if let Some(dir) = std::env::var_os(CHILD_DIR) {
+        return be_child(Path::new(&dir));
+    }
+    let dir = tempfile::tempdir()?;
+    let mut child = Command::new(std::env::current_exe()?)
+        .arg("tempfile::termination::registered_tempfiles_are_removed_when_the_process_is_terminated")
+        .arg("--exact")
+        .arg("--quiet")
+        .env(CHILD_DIR, dir.path())
+        .spawn()?;
+
+    let start = Instant::now();
+    while !dir.path().join("ready").is_file() {
+        if start.elapsed() > Duration::from_secs(30) {
+            child.kill()?;
+            panic!("the child process didn't get ready in time");
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let count = std::fs::read_dir(dir.path())?.count();
+    assert_eq!(count, 5, "all files were created");
+
+    let status = Command::new("kill").arg("-TERM").arg(child.id().to_string()).status()?;
+    assert!(status.success());
+    let status = child.wait()?;
+    assert!(
+        !status.success(),
+        "the default handler terminates the child after cleanup"
+    );
+
+    let mut remaining: Vec<_> = std::fs::read_dir(dir.path())?
+        .map(|e| e.map(|e| e.file_name().to_string_lossy().into_owned()))
+        .collect::<Result<_, _>>()?;
+    remaining.sort();
+    assert_eq!(
+        remaining,
+        vec!["persisted", "ready"],
+        "registered tempfiles and their empty directories are gone, but persisted ones and other files are kept"
+    );
+    Ok(())
+}
@@ -33,6 +33,9 @@ pub trait ExtendedBufRead: AsyncBufRead {
     fn reset(&mut self, version: Protocol);
     /// Return the kind of message at which the reader stopped.
     fn stopped_at(&self) -> Option<MessageKind>;
+    /// Stop decoding packet lines and pass on all following data as is, which is how a pack is sent if no side-band
+    /// was negotiated.
+    fn enable_raw_mode(&mut self);
 }
 
 #[async_trait(?Send)]
@@ -52,6 +55,10 @@ impl<'a, T: ExtendedBufRead + ?Sized + 'a + Unpin> ExtendedBufRead for Box<T> {
     fn stopped_at(&self) -> Option<MessageKind> {
         self.deref().stopped_at()
     }
+
+    fn enable_raw_mode(&mut self) {
+        self.deref_mut().enable_raw_mode()
+    }
 }
 
 #[async_trait(?Send)]
@@ -84,4 +91,7 @@ impl<'a, T: AsyncRead + Unpin> ExtendedBufRead for git_packetline::read::WithSid
             git_packetline::PacketLineRef::Data(_) => unreachable!("data cannot be a delimiter"),
         })
     }
+    fn enable_raw_mode(&mut self) {
+        self.enable_raw_mode()
+    }
 }
@@ -5,7 +5,7 @@ use bstr::BString;
 use futures_lite::io::AsyncWriteExt;
 
 use crate::{
-    client::{Capabilities, Error, ExtendedBufRead, MessageKind, TransportWithoutIO, WriteMode},
+    client::{Capabilities, Error, ExtendedBufRead, MessageKind, TransportWithoutIO, VersionRequest, WriteMode},
     Protocol, Service,
 };
 
@@ -13,6 +13,8 @@ use crate::{
 pub struct SetServiceResponse<'a> {
     /// The protocol the service can provide. May be different from the requested one
     pub actual_protocol: Protocol,
+    /// How the desired protocol version was requested from the server, if at all.
+    pub version_request: VersionRequest,
     /// The capabilities parsed from the server response.
     pub capabilities: Capabilities,
     /// In protocol version one, this is set to a list of refs and their peeled counterparts.
@@ -26,6 +26,9 @@ pub trait ExtendedBufRead: io::BufRead {
     fn reset(&mut self, version: Protocol);
     /// Return the kind of message at which the reader stopped.
     fn stopped_at(&self) -> Option<MessageKind>;
+    /// Stop decoding packet lines and pass on all following data as is, which is how a pack is sent if no side-band
+    /// was negotiated.
+    fn enable_raw_mode(&mut self);
 }
 
 impl<'a, T: ExtendedBufRead + ?Sized + 'a> ExtendedBufRead for Box<T> {
@@ -44,6 +47,10 @@ impl<'a, T: ExtendedBufRead + ?Sized + 'a> ExtendedBufRead for Box<T> {
     fn stopped_at(&self) -> Option<MessageKind> {
         self.deref().stopped_at()
     }
+
+    fn enable_raw_mode(&mut self) {
+        self.deref_mut().enable_raw_mode()
+    }
 }
 
 impl<'a, T: io::Read> ExtendedBufRead for git_packetline::read::WithSidebands<'a, T, HandleProgress> {
@@ -75,4 +82,7 @@ impl<'a, T: io::Read> ExtendedBufRead for git_packetline::read::WithSidebands<'a
             git_packetline::PacketLineRef::Data(_) => unreachable!("data cannot be a delimiter"),
         })
     }
+    fn enable_raw_mode(&mut self) {
+        self.enable_raw_mode()
+    }
 }
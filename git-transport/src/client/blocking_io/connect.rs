@@ -17,7 +17,7 @@ pub fn connect(url: &[u8], desired_version: crate::Protocol) -> Result<Box<dyn T
         git_url::Scheme::Radicle => return Err(Error::UnsupportedScheme(url.scheme)),
         git_url::Scheme::File => {
             if url.user.is_some() || url.host.is_some() || url.port.is_some() {
-                return Err(Error::UnsupportedUrlTokens(urlb.into(), url.scheme));
+                return Err(Error::UnsupportedUrlTokens(url.to_string().into(), url.scheme));
             }
             Box::new(
                 crate::client::blocking_io::file::connect(url.path, desired_version)
@@ -35,8 +35,8 @@ pub fn connect(url: &[u8], desired_version: crate::Protocol) -> Result<Box<dyn T
             .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
         ),
         git_url::Scheme::Git => {
-            if url.user.is_some() {
-                return Err(Error::UnsupportedUrlTokens(urlb.into(), url.scheme));
+            if url.user.is_some() || url.password.is_some() {
+                return Err(Error::UnsupportedUrlTokens(url.to_string().into(), url.scheme));
             }
             Box::new(
                 crate::client::git::connect(
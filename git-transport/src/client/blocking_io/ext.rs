@@ -0,0 +1,249 @@
+use std::process::{self, Command, Stdio};
+
+use bstr::BString;
+use quick_error::quick_error;
+
+use crate::{
+    client::{self, git, MessageKind, RequestWriter, SetServiceResponse, TransportWithoutIO, WriteMode},
+    Protocol, Service,
+};
+
+quick_error! {
+    /// The error used in [`connect()`].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        MissingCommand {
+            display("The command to run was empty")
+        }
+        TrailingPercent {
+            display("The command ends with a single '%', which must be escaped as '%%'")
+        }
+        UnknownPlaceholder(placeholder: char) {
+            display("The placeholder '%{}' is unknown", placeholder)
+        }
+        PlaceholderNotAtStartOfArgument(placeholder: char) {
+            display("The placeholder '%{}' must be at the beginning of an argument", placeholder)
+        }
+    }
+}
+
+/// The program and arguments to run, along with the information to send in a `git` daemon request, if any.
+struct Invocation {
+    args: Vec<String>,
+    /// The repository path to send in a `git` daemon request if `%G` was used.
+    repository_path: Option<String>,
+    /// The virtual host to send along with the `git` daemon request if `%V` was used.
+    virtual_host: Option<String>,
+}
+
+/// Parse `template` like `git` does for `ext::` urls, and substitute the placeholders for `service`.
+fn expand(template: &str, service: Service) -> Result<Invocation, Error> {
+    let mut args = Vec::new();
+    let (mut repository_path, mut virtual_host) = (None, None);
+    let mut chars = template.chars().peekable();
+    while chars.peek().is_some() {
+        let mut arg = String::new();
+        let mut target = None;
+        while let Some(c) = chars.next() {
+            match c {
+                ' ' => break,
+                '%' => match chars.next().ok_or(Error::TrailingPercent)? {
+                    '%' => arg.push('%'),
+                    ' ' => arg.push(' '),
+                    's' => arg.push_str(service.as_str().trim_start_matches("git-")),
+                    'S' => arg.push_str(service.as_str()),
+                    placeholder @ ('G' | 'V') => {
+                        if !arg.is_empty() || target.is_some() {
+                            return Err(Error::PlaceholderNotAtStartOfArgument(placeholder));
+                        }
+                        target = Some(placeholder);
+                    }
+                    other => return Err(Error::UnknownPlaceholder(other)),
+                },
+                c => arg.push(c),
+            }
+        }
+        match target {
+            Some('G') => repository_path = Some(arg),
+            Some(_) => virtual_host = Some(arg),
+            None if arg.is_empty() => {}
+            None => args.push(arg),
+        }
+    }
+    if args.is_empty() {
+        return Err(Error::MissingCommand);
+    }
+    Ok(Invocation {
+        args,
+        repository_path,
+        virtual_host,
+    })
+}
+
+/// A transport spawning a user-provided command on handshake and using its stdin and stdout to talk to a `git` server,
+/// similar to `ext::` urls in `git`.
+///
+/// It can only be instantiated using [`connect()`].
+pub struct Transport {
+    template: String,
+    desired_version: Protocol,
+    connection: Option<git::Connection<process::ChildStdout, process::ChildStdin>>,
+    child: Option<process::Child>,
+    limits: client::Limits,
+}
+
+impl Drop for Transport {
+    fn drop(&mut self) {
+        // Close stdin first so the command can exit.
+        self.connection.take();
+        if let Some(mut child) = self.child.take() {
+            child.wait().ok();
+        }
+    }
+}
+
+impl TransportWithoutIO for Transport {
+    fn request(
+        &mut self,
+        write_mode: WriteMode,
+        on_into_read: MessageKind,
+    ) -> Result<RequestWriter<'_>, client::Error> {
+        self.connection
+            .as_mut()
+            .expect("handshake() to have been called first")
+            .request(write_mode, on_into_read)
+    }
+
+    fn set_limits(&mut self, limits: client::Limits) -> Result<(), client::Error> {
+        self.limits = limits;
+        match self.connection.as_mut() {
+            Some(connection) => connection.set_limits(limits),
+            None => Ok(()),
+        }
+    }
+
+    fn limits(&self) -> client::Limits {
+        self.limits
+    }
+
+    fn to_url(&self) -> String {
+        format!("ext::{}", self.template)
+    }
+
+    fn connection_persists_across_multiple_requests(&self) -> bool {
+        true
+    }
+}
+
+impl client::Transport for Transport {
+    fn handshake<'a>(
+        &mut self,
+        service: Service,
+        extra_parameters: &'a [(&'a str, Option<&'a str>)],
+    ) -> Result<SetServiceResponse<'_>, client::Error> {
+        assert!(
+            self.connection.is_none(),
+            "cannot handshake twice with the same connection"
+        );
+        let Invocation {
+            args,
+            repository_path,
+            virtual_host,
+        } = expand(&self.template, service).expect("template was validated in connect()");
+        let mut cmd = Command::new(&args[0]);
+        cmd.args(&args[1..])
+            .env("GIT_EXT_SERVICE", service.as_str())
+            .env("GIT_EXT_SERVICE_NOPREFIX", service.as_str().trim_start_matches("git-"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .env_remove("GIT_PROTOCOL");
+        if repository_path.is_none() && self.desired_version != Protocol::V1 {
+            cmd.env("GIT_PROTOCOL", format!("version={}", self.desired_version as usize));
+        }
+
+        let mut child = cmd.spawn()?;
+        let mode = if repository_path.is_some() {
+            git::ConnectMode::Daemon
+        } else {
+            git::ConnectMode::Process
+        };
+        self.connection = Some(
+            git::Connection::new(
+                child.stdout.take().expect("stdout configured"),
+                child.stdin.take().expect("stdin configured"),
+                self.desired_version,
+                BString::from(repository_path.unwrap_or_default()),
+                virtual_host.map(|host| (host, None)),
+                mode,
+            )
+            .custom_url(Some(self.to_url())),
+        );
+        self.child = Some(child);
+        let connection = self
+            .connection
+            .as_mut()
+            .expect("connection to be there right after setting it");
+        connection.set_limits(self.limits)?;
+        connection.handshake(service, extra_parameters)
+    }
+}
+
+/// Prepare a transport which runs `command` on handshake, with the `ext::` prefix of `git` urls being optional.
+///
+/// `command` is split into arguments at spaces, and supports the following placeholders:
+///
+/// * `%S` - the name of the service, like `git-upload-pack`.
+/// * `%s` - the name of the service without `git-` prefix, like `upload-pack`.
+/// * `%G<path>` - an argument not passed to the command, but causing a `git` daemon request for the repository at `path` to be sent.
+/// * `%V<host>` - an argument not passed to the command, setting the virtual host of the daemon request.
+/// * `% ` and `%%` - a literal space or percent sign.
+///
+/// As this allows to run arbitrary commands, it must never be used with urls from untrusted sources. This is why `git` disallows
+/// `ext::` urls by default and why they aren't supported by [`client::connect()`].
+///
+/// The `desired_version` is passed with the `GIT_PROTOCOL` environment variable, or as part of the daemon request if `%G` is used.
+pub fn connect(command: &str, desired_version: Protocol) -> Result<Transport, Error> {
+    let template = command.strip_prefix("ext::").unwrap_or(command);
+    expand(template, Service::UploadPack)?;
+    Ok(Transport {
+        template: template.to_owned(),
+        desired_version,
+        connection: None,
+        child: None,
+        limits: Default::default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(template: &str) -> Result<Vec<String>, Error> {
+        expand(template, Service::UploadPack).map(|i| i.args)
+    }
+
+    #[test]
+    fn placeholders_and_escapes_are_substituted() {
+        assert_eq!(
+            args("ssh  host %S% 'repo' %s%%").unwrap(),
+            ["ssh", "host", "git-upload-pack 'repo'", "upload-pack%"]
+        );
+        let invocation = expand("nc host 9418 %G/repo.git %Vexample.com", Service::ReceivePack).unwrap();
+        assert_eq!(invocation.args, ["nc", "host", "9418"]);
+        assert_eq!(invocation.repository_path.as_deref(), Some("/repo.git"));
+        assert_eq!(invocation.virtual_host.as_deref(), Some("example.com"));
+    }
+
+    #[test]
+    fn invalid_templates() {
+        assert!(matches!(args(""), Err(Error::MissingCommand)));
+        assert!(matches!(args("%G/repo"), Err(Error::MissingCommand)));
+        assert!(matches!(args("cmd %"), Err(Error::TrailingPercent)));
+        assert!(matches!(args("cmd %x"), Err(Error::UnknownPlaceholder('x'))));
+        assert!(matches!(
+            args("cmd a%G/repo"),
+            Err(Error::PlaceholderNotAtStartOfArgument('G'))
+        ));
+    }
+}
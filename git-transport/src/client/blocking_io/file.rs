@@ -3,7 +3,7 @@ use std::process::{self, Command, Stdio};
 use bstr::{BString, ByteSlice};
 
 use crate::{
-    client::{self, git, MessageKind, RequestWriter, SetServiceResponse, WriteMode},
+    client::{self, git, MessageKind, RequestWriter, SetServiceResponse, TransportWithoutIO, WriteMode},
     Protocol, Service,
 };
 
@@ -38,6 +38,7 @@ pub struct SpawnProcessOnDemand {
     ssh_env: Vec<(&'static str, String)>,
     connection: Option<git::Connection<process::ChildStdout, process::ChildStdin>>,
     child: Option<process::Child>,
+    limits: client::Limits,
 }
 
 impl Drop for SpawnProcessOnDemand {
@@ -66,6 +67,7 @@ impl SpawnProcessOnDemand {
             child: None,
             connection: None,
             desired_version: version,
+            limits: Default::default(),
         }
     }
     fn new_local(path: BString, version: Protocol) -> SpawnProcessOnDemand {
@@ -73,6 +75,7 @@ impl SpawnProcessOnDemand {
             url: git_url::Url {
                 scheme: git_url::Scheme::File,
                 user: None,
+                password: None,
                 host: None,
                 port: None,
                 path: path.clone(),
@@ -84,6 +87,7 @@ impl SpawnProcessOnDemand {
             child: None,
             connection: None,
             desired_version: version,
+            limits: Default::default(),
         }
     }
 }
@@ -100,6 +104,18 @@ impl client::TransportWithoutIO for SpawnProcessOnDemand {
             .request(write_mode, on_into_read)
     }
 
+    fn set_limits(&mut self, limits: client::Limits) -> Result<(), client::Error> {
+        self.limits = limits;
+        match self.connection.as_mut() {
+            Some(connection) => connection.set_limits(limits),
+            None => Ok(()),
+        }
+    }
+
+    fn limits(&self) -> client::Limits {
+        self.limits
+    }
+
     fn to_url(&self) -> String {
         self.url.to_string()
     }
@@ -126,6 +142,11 @@ impl client::Transport for SpawnProcessOnDemand {
         for env_to_remove in ENV_VARS_TO_REMOVE {
             cmd.env_remove(env_to_remove);
         }
+        // Only the desired version should be requested, not the one of a process that spawned us.
+        cmd.env_remove("GIT_PROTOCOL");
+        if self.ssh_program.is_none() && self.desired_version != Protocol::V1 {
+            cmd.env("GIT_PROTOCOL", format!("version={}", self.desired_version as usize));
+        }
         cmd.envs(std::mem::take(&mut self.ssh_env));
         cmd.args(&mut self.ssh_args);
         cmd.stdin(Stdio::piped()).stdout(Stdio::piped());
@@ -146,13 +167,14 @@ impl client::Transport for SpawnProcessOnDemand {
             .connection
             .as_mut()
             .expect("connection to be there right after setting it");
+        c.set_limits(self.limits)?;
         c.handshake(service, extra_parameters)
     }
 }
 
 /// Connect to a locally readable repository at `path` using the given `desired_version`.
 ///
-/// This will spawn a `git` process locally.
+/// This will spawn a `git` process locally, with the `desired_version` requested using the `GIT_PROTOCOL` environment variable.
 pub fn connect(
     path: impl Into<BString>,
     desired_version: Protocol,
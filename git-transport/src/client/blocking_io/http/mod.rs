@@ -4,8 +4,11 @@ use std::{
     io::{self, BufRead, Read},
 };
 
+use bstr::ByteSlice;
+use git_credentials::{CredentialStore, Credentials};
 use git_packetline::PacketLineRef;
 pub use traits::{Error, GetResponse, Http, PostResponse};
+use zeroize::Zeroize;
 
 use crate::{
     client::{self, capabilities, Capabilities, ExtendedBufRead, HandleProgress, MessageKind, RequestWriter},
@@ -33,13 +36,20 @@ pub struct Transport<H: Http> {
     service: Option<Service>,
     line_provider: Option<git_packetline::StreamingPeekableIter<H::ResponseBody>>,
     identity: Option<client::Identity>,
+    credentials: Option<Credentials>,
+    limits: client::Limits,
 }
 
 impl Transport<Impl> {
     /// Create a new instance to communicate to `url` using the given `desired_version` of the `git` protocol.
+    ///
+    /// If `url` contains a password, user and password are removed from it and used as
+    /// [identity][client::TransportWithoutIO::set_identity()] instead, so they are neither passed to the http backend
+    /// as part of the url nor returned by [`to_url()`][client::TransportWithoutIO::to_url()].
     pub fn new(url: &str, desired_version: crate::Protocol) -> Self {
+        let (url, identity) = split_credentials(url);
         Transport {
-            url: url.to_owned(),
+            url,
             user_agent_header: concat!("User-Agent: git/oxide-", env!("CARGO_PKG_VERSION")),
             desired_version,
             actual_version: desired_version,
@@ -47,12 +57,67 @@ impl Transport<Impl> {
             service: None,
             http: Impl::default(),
             line_provider: None,
-            identity: None,
+            identity,
+            credentials: None,
+            limits: Default::default(),
         }
     }
 }
 
 impl<H: Http> Transport<H> {
+    /// Obtain credentials for the protocol and host of our url from `store` and use them as identity for all subsequent requests,
+    /// similar to [`set_identity()`][client::TransportWithoutIO::set_identity()].
+    ///
+    /// Once a request was made, call [`approve_credentials()`][Transport::approve_credentials()] if it succeeded or
+    /// [`erase_credentials()`][Transport::erase_credentials()] if the server still denied access to inform the `store` accordingly.
+    pub fn set_identity_from(&mut self, store: &mut dyn CredentialStore) -> Result<(), client::Error> {
+        let (protocol, host) = self.credentials_context()?;
+        let credentials = store.get(&protocol, &host)?;
+        self.identity = Some(client::Identity::Account {
+            username: credentials.username.to_str_lossy().into_owned(),
+            password: credentials.password.to_str_lossy().into_owned(),
+        });
+        self.credentials = Some(credentials);
+        Ok(())
+    }
+
+    /// Tell `store` that the credentials obtained with [`set_identity_from()`][Transport::set_identity_from()] were accepted
+    /// by the server. Nothing happens if no credentials were obtained from a store.
+    pub fn approve_credentials(&mut self, store: &mut dyn CredentialStore) -> Result<(), client::Error> {
+        if let Some(credentials) = &self.credentials {
+            let (protocol, host) = self.credentials_context()?;
+            store.approve(&protocol, &host, credentials)?;
+        }
+        Ok(())
+    }
+
+    /// Tell `store` that the credentials obtained with [`set_identity_from()`][Transport::set_identity_from()] were rejected
+    /// by the server, and stop using them for subsequent requests. Nothing happens if no credentials were obtained from a store.
+    pub fn erase_credentials(&mut self, store: &mut dyn CredentialStore) -> Result<(), client::Error> {
+        if let Some(credentials) = self.credentials.take() {
+            self.identity = None;
+            let (protocol, host) = self.credentials_context()?;
+            store.erase(&protocol, &host, &credentials)?;
+        }
+        Ok(())
+    }
+
+    /// Return the protocol and host of our url as understood by credential helpers, with the host including a non-default port.
+    fn credentials_context(&self) -> Result<(String, String), client::Error> {
+        // The url isn't part of the message as it may contain a password.
+        let url = git_url::parse(self.url.as_bytes()).map_err(|err| {
+            client::Error::Http(Error::Detail(format!(
+                "Could not parse url to obtain credentials: {}",
+                err
+            )))
+        })?;
+        let mut host = url.host.unwrap_or_default();
+        if let Some(port) = url.port {
+            host.push_str(&format!(":{}", port));
+        }
+        Ok((url.scheme.to_string(), host))
+    }
+
     fn check_content_type(service: Service, kind: &str, headers: <H as Http>::Headers) -> Result<(), client::Error> {
         let wanted_content_type = format!("Content-Type: application/x-{}-{}", service.as_str(), kind);
         if !headers
@@ -80,10 +145,12 @@ impl<H: Http> Transport<H> {
                             "Will not send credentials in clear text over http",
                         ));
                     }
+                    let mut user_and_password = format!("{}:{}", username, password);
                     headers.push(Cow::Owned(format!(
                         "Authorization: Basic {}",
-                        base64::encode(format!("{}:{}", username, password))
-                    )))
+                        base64::encode(&user_and_password)
+                    )));
+                    user_and_password.zeroize();
                 }
             }
         }
@@ -91,6 +158,33 @@ impl<H: Http> Transport<H> {
     }
 }
 
+/// Return `url` without user and password along with the identity they make up, if `url` contains a password.
+fn split_credentials(url: &str) -> (String, Option<client::Identity>) {
+    match git_url::parse(url.as_bytes()) {
+        Ok(mut parsed) if parsed.password.is_some() => {
+            let identity = client::Identity::Account {
+                username: parsed
+                    .user()
+                    .map(|user| user.to_str_lossy().into_owned())
+                    .unwrap_or_default(),
+                password: parsed
+                    .password()
+                    .map(|password| password.to_str_lossy().into_owned())
+                    .unwrap_or_default(),
+            };
+            parsed.user = None;
+            if let Some(mut password) = parsed.password.take() {
+                password.zeroize();
+            }
+            (
+                parsed.to_bstring_with_credentials().to_str_lossy().into_owned(),
+                Some(identity),
+            )
+        }
+        _ => (url.to_owned(), None),
+    }
+}
+
 fn append_url(base: &str, suffix: &str) -> String {
     if base.ends_with('/') {
         format!("{}{}", base, suffix)
@@ -102,9 +196,22 @@ fn append_url(base: &str, suffix: &str) -> String {
 impl<H: Http> client::TransportWithoutIO for Transport<H> {
     fn set_identity(&mut self, identity: client::Identity) -> Result<(), client::Error> {
         self.identity = Some(identity);
+        self.credentials = None;
+        Ok(())
+    }
+
+    fn set_limits(&mut self, limits: client::Limits) -> Result<(), client::Error> {
+        self.limits = limits;
+        if let Some(line_provider) = self.line_provider.as_mut() {
+            line_provider.set_limits(limits.to_packetline());
+        }
         Ok(())
     }
 
+    fn limits(&self) -> client::Limits {
+        self.limits
+    }
+
     fn request(
         &mut self,
         write_mode: client::WriteMode,
@@ -200,6 +307,7 @@ impl<H: Http> client::Transport for Transport<H> {
         let line_reader = self
             .line_provider
             .get_or_insert_with(|| git_packetline::StreamingPeekableIter::new(body, &[PacketLineRef::Flush]));
+        line_reader.set_limits(self.limits.to_packetline());
 
         let mut announced_service = String::new();
         line_reader.as_read().read_to_string(&mut announced_service)?;
@@ -221,6 +329,11 @@ impl<H: Http> client::Transport for Transport<H> {
         self.service = Some(service);
         Ok(client::SetServiceResponse {
             actual_protocol,
+            version_request: if self.desired_version == Protocol::V1 {
+                client::VersionRequest::None
+            } else {
+                client::VersionRequest::HttpHeader
+            },
             capabilities,
             refs,
         })
@@ -280,6 +393,10 @@ impl<H: Http, B: ExtendedBufRead + Unpin> ExtendedBufRead for HeadersThenBody<H,
     fn stopped_at(&self) -> Option<MessageKind> {
         self.body.stopped_at()
     }
+
+    fn enable_raw_mode(&mut self) {
+        self.body.enable_raw_mode()
+    }
 }
 
 /// Connect to the given `url` via HTTP/S using the `desired_version` of the `git` protocol.
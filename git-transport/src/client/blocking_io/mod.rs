@@ -1,6 +1,9 @@
 ///
 pub mod connect;
 
+///
+pub mod ext;
+
 ///
 pub mod file;
 ///
@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::path::Path;
 
 use bstr::BString;
 use quick_error::quick_error;
@@ -27,7 +27,10 @@ quick_error! {
 /// # Environment Variables
 ///
 /// Use `GIT_SSH_COMMAND` to override the `ssh` program to execute. This can be a script dealing with using the correct
-/// ssh key, for example.
+/// ssh key, for example, as long as it's named `ssh` and understands the arguments of `ssh`.
+///
+/// Unless the `desired_version` is [V1][Protocol::V1], it's requested by setting the `GIT_PROTOCOL` environment variable
+/// which `ssh` is instructed to send to the remote side.
 pub fn connect(
     host: &str,
     path: BString,
@@ -39,24 +42,20 @@ pub fn connect(
     let mut ssh_cmd_line = ssh_cmd_line.split(' ');
     let ssh_cmd = ssh_cmd_line.next().expect("there is always a single item");
 
-    type EnvVar = (&'static str, String);
-    let args_and_env: Option<(Vec<Cow<'_, str>>, Vec<EnvVar>)> = match ssh_cmd {
-        "ssh" | "ssh.exe" => {
-            if desired_version != Protocol::V1 {
-                let mut args = vec![Cow::from("-o"), "SendEnv=GIT_PROTOCOL".into()];
-                if let Some(port) = port {
-                    args.push(format!("-p={}", port).into());
-                }
-                Some((
-                    args,
-                    vec![("GIT_PROTOCOL", format!("version={}", desired_version as usize))],
-                ))
-            } else {
-                None
-            }
-        }
-        _ => return Err(Error::UnsupportedSshCommand(ssh_cmd.into())),
-    };
+    if Path::new(ssh_cmd).file_stem().and_then(|stem| stem.to_str()) != Some("ssh") {
+        return Err(Error::UnsupportedSshCommand(ssh_cmd.into()));
+    }
+    let mut args = Vec::new();
+    let mut envs = Vec::new();
+    if let Some(port) = port {
+        args.push("-p".to_string());
+        args.push(port.to_string());
+    }
+    if desired_version != Protocol::V1 {
+        args.push("-o".into());
+        args.push("SendEnv=GIT_PROTOCOL".into());
+        envs.push(("GIT_PROTOCOL", format!("version={}", desired_version as usize)));
+    }
 
     let host = match user.as_ref() {
         Some(user) => format!("{}@{}", user, host),
@@ -67,28 +66,19 @@ pub fn connect(
     let url = git_url::Url {
         scheme: git_url::Scheme::Ssh,
         user: user.map(Into::into),
+        password: None,
         host: Some(host.clone()),
         port,
         path: path.clone(),
     };
-    Ok(match args_and_env {
-        Some((args, envs)) => blocking_io::file::SpawnProcessOnDemand::new_ssh(
-            url,
-            ssh_cmd.into(),
-            ssh_cmd_line.map(Cow::from).chain(args).chain(Some(host.into())),
-            envs,
-            path,
-            desired_version,
-        ),
-        None => blocking_io::file::SpawnProcessOnDemand::new_ssh(
-            url,
-            ssh_cmd.into(),
-            ssh_cmd_line.chain(Some(host.as_str())),
-            None::<(&str, String)>,
-            path,
-            desired_version,
-        ),
-    })
+    Ok(blocking_io::file::SpawnProcessOnDemand::new_ssh(
+        url,
+        ssh_cmd.into(),
+        ssh_cmd_line.map(String::from).chain(args).chain(Some(host)),
+        envs,
+        path,
+        desired_version,
+    ))
 }
 
 #[cfg(test)]
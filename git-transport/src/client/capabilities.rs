@@ -12,12 +12,6 @@ quick_error! {
     #[derive(Debug)]
     #[allow(missing_docs)]
     pub enum Error {
-        MissingDelimitingNullByte {
-            display("Capabilities were missing entirely as there was no 0 byte")
-        }
-        NoCapabilities {
-            display("there was not a single capability behind the delimiter")
-        }
         MissingVersionLine {
             display("a version line was expected, but none was retrieved")
         }
@@ -75,15 +69,14 @@ impl<'a> Capability<'a> {
 }
 
 impl Capabilities {
-    /// Parse capabilities from the given `bytes`.
+    /// Parse capabilities from the given `bytes`, returning them along with the position of the delimiting null byte.
     ///
     /// Useful in case they are encoded within a `ref` behind a null byte.
+    /// Minimal servers may not send any capabilities, leaving nothing behind the null byte or omitting it entirely,
+    /// in which case the capabilities are empty. Without null byte, the returned position is the length of `bytes`.
     pub fn from_bytes(bytes: &[u8]) -> Result<(Capabilities, usize), Error> {
-        let delimiter_pos = bytes.find_byte(0).ok_or(Error::MissingDelimitingNullByte)?;
-        if delimiter_pos + 1 == bytes.len() {
-            return Err(Error::NoCapabilities);
-        }
-        let capabilities = &bytes[delimiter_pos + 1..];
+        let delimiter_pos = bytes.find_byte(0).unwrap_or(bytes.len());
+        let capabilities = bytes.get(delimiter_pos + 1..).unwrap_or_default();
         Ok((
             Capabilities {
                 data: capabilities.as_bstr().to_owned(),
@@ -135,6 +128,7 @@ impl Capabilities {
     pub fn iter(&self) -> impl Iterator<Item = Capability<'_>> {
         self.data
             .split(move |b| *b == self.value_sep)
+            .filter(|c| !c.is_empty())
             .map(|c| Capability(c.as_bstr()))
     }
 }
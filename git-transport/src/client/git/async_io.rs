@@ -26,12 +26,23 @@ where
             on_into_read,
         ))
     }
+    fn set_limits(&mut self, limits: client::Limits) -> Result<(), client::Error> {
+        self.limits = limits;
+        self.line_provider.set_limits(limits.to_packetline());
+        Ok(())
+    }
+
+    fn limits(&self) -> client::Limits {
+        self.limits
+    }
+
     fn to_url(&self) -> String {
         self.custom_url.as_ref().map_or_else(
             || {
                 git_url::Url {
                     scheme: git_url::Scheme::File,
                     user: None,
+                    password: None,
                     host: None,
                     port: None,
                     path: self.path.clone(),
@@ -68,6 +79,7 @@ where
         service: Service,
         extra_parameters: &'a [(&'a str, Option<&'a str>)],
     ) -> Result<SetServiceResponse<'_>, client::Error> {
+        self.line_provider.set_limits(self.limits.to_packetline());
         if self.mode == git::ConnectMode::Daemon {
             let mut line_writer = git_packetline::Writer::new(&mut self.writer).binary_mode();
             line_writer
@@ -82,6 +94,7 @@ where
             line_writer.flush().await?;
         }
 
+        let version_request = self.version_request();
         let capabilities::recv::Outcome {
             capabilities,
             refs,
@@ -89,6 +102,7 @@ where
         } = Capabilities::from_lines_with_version_detection(&mut self.line_provider).await?;
         Ok(SetServiceResponse {
             actual_protocol,
+            version_request,
             capabilities,
             refs,
         })
@@ -121,6 +135,7 @@ where
             custom_url: None,
             supported_versions: [desired_version],
             mode,
+            limits: Default::default(),
         }
     }
 }
@@ -26,12 +26,23 @@ where
         ))
     }
 
+    fn set_limits(&mut self, limits: client::Limits) -> Result<(), client::Error> {
+        self.limits = limits;
+        self.line_provider.set_limits(limits.to_packetline());
+        Ok(())
+    }
+
+    fn limits(&self) -> client::Limits {
+        self.limits
+    }
+
     fn to_url(&self) -> String {
         self.custom_url.as_ref().map_or_else(
             || {
                 git_url::Url {
                     scheme: git_url::Scheme::File,
                     user: None,
+                    password: None,
                     host: None,
                     port: None,
                     path: self.path.clone(),
@@ -69,6 +80,7 @@ where
         service: Service,
         extra_parameters: &'a [(&'a str, Option<&'a str>)],
     ) -> Result<SetServiceResponse<'_>, client::Error> {
+        self.line_provider.set_limits(self.limits.to_packetline());
         if self.mode == git::ConnectMode::Daemon {
             let mut line_writer = git_packetline::Writer::new(&mut self.writer).binary_mode();
             line_writer.write_all(&git::message::connect(
@@ -81,6 +93,7 @@ where
             line_writer.flush()?;
         }
 
+        let version_request = self.version_request();
         let capabilities::recv::Outcome {
             capabilities,
             refs,
@@ -88,6 +101,7 @@ where
         } = Capabilities::from_lines_with_version_detection(&mut self.line_provider)?;
         Ok(SetServiceResponse {
             actual_protocol,
+            version_request,
             capabilities,
             refs,
         })
@@ -120,6 +134,7 @@ where
             custom_url: None,
             supported_versions: [desired_version],
             mode,
+            limits: Default::default(),
         }
     }
     pub(crate) fn new_for_spawned_process(
@@ -141,6 +156,8 @@ where
 
 ///
 pub mod connect {
+    #[cfg(unix)]
+    use std::os::unix::net::UnixStream;
     use std::{
         io,
         net::{TcpStream, ToSocketAddrs},
@@ -208,6 +225,32 @@ pub mod connect {
             git::ConnectMode::Daemon,
         ))
     }
+
+    /// Connect to a git daemon, or a proxy speaking its protocol, listening on the unix domain socket at `socket_path`,
+    /// to obtain the repository at `path`.
+    ///
+    /// Use `desired_version` to specify a preferred protocol to use, knowing that it can be downgraded by a server not supporting it.
+    #[cfg(unix)]
+    pub fn connect_unix_socket(
+        socket_path: impl AsRef<std::path::Path>,
+        path: BString,
+        desired_version: crate::Protocol,
+    ) -> Result<git::Connection<UnixStream, UnixStream>, Error> {
+        let socket_path = socket_path.as_ref();
+        let read = UnixStream::connect(socket_path)?;
+        let write = read.try_clone()?;
+        Ok(git::Connection::new(
+            read,
+            write,
+            desired_version,
+            path,
+            None::<(&str, _)>,
+            git::ConnectMode::Daemon,
+        )
+        .custom_url(Some(format!("unix://{}", socket_path.display()))))
+    }
 }
 
 pub use connect::connect;
+#[cfg(unix)]
+pub use connect::connect_unix_socket;
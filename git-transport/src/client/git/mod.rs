@@ -24,6 +24,7 @@ pub struct Connection<R, W> {
     supported_versions: [Protocol; 1],
     custom_url: Option<String>,
     pub(in crate::client) mode: ConnectMode,
+    pub(in crate::client) limits: crate::client::Limits,
 }
 
 impl<R, W> Connection<R, W> {
@@ -41,6 +42,17 @@ impl<R, W> Connection<R, W> {
         self.custom_url = url;
         self
     }
+
+    /// The way the desired version is communicated to the server, which for spawned processes is expected to be done by
+    /// whoever spawned them.
+    pub(in crate::client) fn version_request(&self) -> crate::client::VersionRequest {
+        use crate::client::VersionRequest;
+        match (self.desired_version, self.mode) {
+            (Protocol::V1, _) => VersionRequest::None,
+            (_, ConnectMode::Daemon) => VersionRequest::ExtraParameter,
+            (_, ConnectMode::Process) => VersionRequest::EnvironmentVariable,
+        }
+    }
 }
 
 mod message {
@@ -176,3 +188,5 @@ mod async_io;
 mod blocking_io;
 #[cfg(feature = "blocking-client")]
 pub use blocking_io::connect;
+#[cfg(all(feature = "blocking-client", unix))]
+pub use blocking_io::connect_unix_socket;
@@ -0,0 +1,33 @@
+pub use git_packetline::read::limits::{Error, Throughput};
+
+/// Limits to protect against remotes which send too much data or send it too slowly, with `None` meaning unlimited.
+///
+/// All limits but [`max_negotiation_rounds`][Limits::max_negotiation_rounds] are enforced by the transport while reading,
+/// causing IO errors which wrap the [`Error`] naming the exceeded limit.
+#[derive(Default, PartialEq, Eq, Debug, Hash, Clone, Copy)]
+pub struct Limits {
+    /// The maximum amount of data bytes in a single packet line.
+    pub max_packet_line_len: Option<usize>,
+    /// The maximum amount of bytes to receive with the handshake, the advertised references and all other responses before the pack.
+    ///
+    /// The count starts anew with each handshake.
+    pub max_advertisement_bytes: Option<usize>,
+    /// The throughput to maintain while receiving the pack, to abort transfers which stall or are slowed down on purpose.
+    ///
+    /// A remote which sends nothing at all can only be detected with a read timeout on the underlying connection.
+    pub min_pack_throughput: Option<Throughput>,
+    /// The maximum amount of requests to send while negotiating which objects to receive, which has to be enforced by the caller
+    /// performing the negotiation.
+    pub max_negotiation_rounds: Option<usize>,
+}
+
+impl Limits {
+    #[cfg(any(feature = "blocking-client", feature = "async-client"))]
+    pub(crate) fn to_packetline(self) -> git_packetline::read::limits::Limits {
+        git_packetline::read::limits::Limits {
+            max_data_len: self.max_packet_line_len,
+            max_bytes_before_payload: self.max_advertisement_bytes,
+            min_payload_throughput: self.min_pack_throughput,
+        }
+    }
+}
@@ -14,7 +14,8 @@ mod blocking_io;
 pub use blocking_io::http;
 #[cfg(feature = "blocking-client")]
 pub use blocking_io::{
-    connect, file, ssh, ExtendedBufRead, HandleProgress, RequestWriter, SetServiceResponse, Transport, TransportV2Ext,
+    connect, ext, file, ssh, ExtendedBufRead, HandleProgress, RequestWriter, SetServiceResponse, Transport,
+    TransportV2Ext,
 };
 #[cfg(feature = "blocking-client")]
 #[doc(inline)]
@@ -26,7 +27,12 @@ pub mod capabilities;
 pub use capabilities::Capabilities;
 
 mod non_io_types;
-pub use non_io_types::{Error, Identity, MessageKind, WriteMode};
+pub use non_io_types::{Error, Identity, MessageKind, VersionRequest, WriteMode};
+
+///
+pub mod limits;
+#[doc(inline)]
+pub use limits::Limits;
 
 ///
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
@@ -29,9 +29,27 @@ pub enum MessageKind {
     Text(&'static [u8]),
 }
 
+/// The way the desired [protocol version][crate::Protocol] was communicated to the server during the handshake,
+/// as returned in the response of the `handshake()` method.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum VersionRequest {
+    /// No version was requested as [V1][crate::Protocol::V1] was desired, which servers assume if nothing is requested.
+    None,
+    /// The version was sent as `version=N` extra parameter of the initial request to a `git` daemon.
+    ExtraParameter,
+    /// The version was sent in the `Git-Protocol` header of the initial HTTP request.
+    HttpHeader,
+    /// The version was set in the `GIT_PROTOCOL` environment variable of the spawned process, which `ssh` is asked to
+    /// pass on to the remote side as well.
+    EnvironmentVariable,
+}
+
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
 /// An identity for use when authenticating the transport layer.
+///
+/// The password is overwritten with zeros when dropped.
 pub enum Identity {
     /// An account based identity
     Account {
@@ -42,6 +60,15 @@ pub enum Identity {
     },
 }
 
+impl Drop for Identity {
+    fn drop(&mut self) {
+        use zeroize::Zeroize;
+        match self {
+            Identity::Account { password, .. } => password.zeroize(),
+        }
+    }
+}
+
 pub(crate) mod connect {
     use quick_error::quick_error;
     quick_error! {
@@ -64,6 +91,7 @@ pub(crate) mod connect {
                 from()
                 source(&**err)
             }
+            /// The `url` is shown without password.
             UnsupportedUrlTokens(url: bstr::BString, scheme: git_url::Scheme) {
                 display("The url '{}' contains information that would not be used by the '{}' protocol", url, scheme)
             }
@@ -115,12 +143,17 @@ mod error {
         ExpectedDataLine,
         #[error("The transport layer does not support authentication")]
         AuthenticationUnsupported,
+        #[error("The transport layer does not support limits")]
+        LimitsUnsupported,
         #[error("The transport layer refuses to use a given identity: {0}")]
         AuthenticationRefused(&'static str),
         #[error("The protocol version indicated by {:?} is unsupported", {0})]
         UnsupportedProtocolVersion(BString),
         #[error(transparent)]
         Http(#[from] HttpError),
+        #[cfg(feature = "http-client-curl")]
+        #[error("Credentials could not be obtained, approved or erased")]
+        Credentials(#[from] git_credentials::Error),
     }
 }
 
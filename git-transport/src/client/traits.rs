@@ -3,7 +3,7 @@ use std::ops::{Deref, DerefMut};
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
 use crate::client::{MessageKind, RequestWriter, WriteMode};
 use crate::{
-    client::{Error, Identity},
+    client::{Error, Identity, Limits},
     Protocol,
 };
 
@@ -19,6 +19,19 @@ pub trait TransportWithoutIO {
     fn set_identity(&mut self, _identity: Identity) -> Result<(), Error> {
         Err(Error::AuthenticationUnsupported)
     }
+
+    /// Enforce `limits` on all data received from now on, with the amount of bytes received before the pack being counted
+    /// anew with each handshake.
+    /// Please note that custom transport layers may not support limits and return [an error][Error::LimitsUnsupported] here.
+    fn set_limits(&mut self, _limits: Limits) -> Result<(), Error> {
+        Err(Error::LimitsUnsupported)
+    }
+
+    /// Return the limits previously set with [`set_limits()`][TransportWithoutIO::set_limits()], which are unlimited by default.
+    fn limits(&self) -> Limits {
+        Limits::default()
+    }
+
     /// Get a writer for sending data and obtaining the response. It can be configured in various ways
     /// to support the task at hand.
     /// `write_mode` determines how calls to the `write(…)` method are interpreted, and `on_into_read` determines
@@ -57,6 +70,14 @@ impl<T: TransportWithoutIO + ?Sized> TransportWithoutIO for Box<T> {
         self.deref_mut().set_identity(identity)
     }
 
+    fn set_limits(&mut self, limits: Limits) -> Result<(), Error> {
+        self.deref_mut().set_limits(limits)
+    }
+
+    fn limits(&self) -> Limits {
+        self.deref().limits()
+    }
+
     #[cfg(any(feature = "blocking-client", feature = "async-client"))]
     fn request(&mut self, write_mode: WriteMode, on_into_read: MessageKind) -> Result<RequestWriter<'_>, Error> {
         self.deref_mut().request(write_mode, on_into_read)
@@ -80,6 +101,14 @@ impl<T: TransportWithoutIO + ?Sized> TransportWithoutIO for &mut T {
         self.deref_mut().set_identity(identity)
     }
 
+    fn set_limits(&mut self, limits: Limits) -> Result<(), Error> {
+        self.deref_mut().set_limits(limits)
+    }
+
+    fn limits(&self) -> Limits {
+        self.deref().limits()
+    }
+
     #[cfg(any(feature = "blocking-client", feature = "async-client"))]
     fn request(&mut self, write_mode: WriteMode, on_into_read: MessageKind) -> Result<RequestWriter<'_>, Error> {
         self.deref_mut().request(write_mode, on_into_read)
@@ -16,6 +16,20 @@ pub enum Protocol {
     V2 = 2,
 }
 
+impl Protocol {
+    /// Parse the `value` of the `protocol.version` configuration key, returning `None` if it's not one of `0`, `1` or `2`.
+    ///
+    /// Version `0` maps to [V1][Protocol::V1] as both are requested by not requesting any version at all, which is what we do
+    /// for V1 as well.
+    pub fn from_config_value(value: &[u8]) -> Option<Self> {
+        Some(match value {
+            b"0" | b"1" => Protocol::V1,
+            b"2" => Protocol::V2,
+            _ => return None,
+        })
+    }
+}
+
 /// The kind of service to invoke on the client or the server side.
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
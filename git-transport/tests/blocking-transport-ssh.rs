@@ -0,0 +1,63 @@
+//! Assure the protocol version is passed to the ssh program as configured by `GIT_SSH_COMMAND`.
+//!
+//! This is a test binary of its own as it changes the environment of the process, which would affect other tests.
+use git_transport::{
+    client::{self, ssh, Transport, TransportWithoutIO, VersionRequest},
+    Protocol, Service,
+};
+
+type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[test]
+fn the_version_is_requested_in_the_environment_which_ssh_is_told_to_send() -> Result {
+    let dir = git_testtools::scripted_fixture_repo_writable("make_repo_for_custom_transports.sh")?;
+    std::env::set_var("GIT_SSH_COMMAND", dir.path().join("bin").join("ssh"));
+    let repo = dir.path().join("repo.git");
+    let invocation = || std::fs::read_to_string(dir.path().join("ssh-invocation"));
+
+    let mut c = ssh::connect(
+        "host",
+        repo.to_str().expect("valid UTF-8").into(),
+        Protocol::V2,
+        None,
+        Some(2222),
+    )?;
+    let res = c.handshake(Service::UploadPack, &[])?;
+    assert_eq!(res.version_request, VersionRequest::EnvironmentVariable);
+    assert_eq!(res.actual_protocol, Protocol::V2);
+    drop(res);
+    c.request(client::WriteMode::Binary, client::MessageKind::Flush)?
+        .into_read()?;
+    drop(c);
+    assert_eq!(
+        invocation()?,
+        format!(
+            "GIT_PROTOCOL=version=2\n-p 2222 -o SendEnv=GIT_PROTOCOL host git-upload-pack --strict --timeout=0 {}\n",
+            repo.display()
+        )
+    );
+
+    let mut c = ssh::connect(
+        "host",
+        repo.to_str().expect("valid UTF-8").into(),
+        Protocol::V1,
+        None,
+        None,
+    )?;
+    let res = c.handshake(Service::UploadPack, &[])?;
+    assert_eq!(res.version_request, VersionRequest::None);
+    assert_eq!(res.actual_protocol, Protocol::V1);
+    drop(res);
+    c.request(client::WriteMode::Binary, client::MessageKind::Flush)?
+        .into_read()?;
+    drop(c);
+    assert_eq!(
+        invocation()?,
+        format!(
+            "GIT_PROTOCOL=\nhost git-upload-pack --strict --timeout=0 {}\n",
+            repo.display()
+        ),
+        "without a version to request, ssh isn't told to send any environment"
+    );
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use std::io::BufRead;
+
+use git_transport::{
+    client::{self, ext, Transport, TransportV2Ext, TransportWithoutIO},
+    Protocol, Service,
+};
+
+fn repo_dir() -> crate::Result<std::path::PathBuf> {
+    git_testtools::scripted_fixture_repo_read_only("make_repo_for_custom_transports.sh")
+}
+
+/// Escape `path` for use as single argument in an `ext::` command.
+fn escape(path: &std::path::Path) -> String {
+    path.display().to_string().replace('%', "%%").replace(' ', "% ")
+}
+
+fn assert_main_branch_is_advertised(refs: Vec<String>) {
+    assert!(
+        refs.iter().any(|line| line.ends_with(" refs/heads/main")),
+        "the branch is advertised: {:?}",
+        refs
+    );
+}
+
+#[test]
+fn handshake_v1_with_upload_pack_and_end_of_interaction() -> crate::Result {
+    let repo = repo_dir()?.join("repo.git");
+    let url = format!("ext::git %s {}", escape(&repo));
+    let mut c = ext::connect(&url, Protocol::V1)?;
+    assert_eq!(c.to_url(), url, "placeholders are kept as the service isn't known yet");
+
+    let mut res = c.handshake(Service::UploadPack, &[])?;
+    assert_eq!(res.actual_protocol, Protocol::V1);
+    assert_eq!(res.version_request, client::VersionRequest::None);
+    assert!(res.capabilities.contains("symref"));
+    let refs = res
+        .refs
+        .as_mut()
+        .expect("v1 protocol provides refs")
+        .lines()
+        .collect::<Result<Vec<_>, _>>()?;
+    drop(res);
+    assert_main_branch_is_advertised(refs);
+
+    c.request(client::WriteMode::Binary, client::MessageKind::Flush)?
+        .into_read()?;
+    Ok(())
+}
+
+#[test]
+fn handshake_v2_and_ls_refs() -> crate::Result {
+    let repo = repo_dir()?.join("repo.git");
+    let mut c = ext::connect(&format!("git %s {}", escape(&repo)), Protocol::V2)?;
+
+    let res = c.handshake(Service::UploadPack, &[])?;
+    assert_eq!(
+        res.actual_protocol,
+        Protocol::V2,
+        "GIT_PROTOCOL was passed to the command"
+    );
+    assert_eq!(res.version_request, client::VersionRequest::EnvironmentVariable);
+    assert!(res.refs.is_none(), "V2 needs a separate request for refs");
+    drop(res);
+
+    let refs = c
+        .invoke("ls-refs", std::iter::empty(), None::<std::iter::Empty<_>>)?
+        .lines()
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_main_branch_is_advertised(refs);
+    Ok(())
+}
+
+#[test]
+fn daemon_requests_are_sent_with_git_placeholder() -> crate::Result {
+    let dir = repo_dir()?;
+    let mut c = ext::connect(
+        &format!(
+            "git daemon --inetd --export-all --base-path={} %G/repo.git",
+            escape(&dir)
+        ),
+        Protocol::V1,
+    )?;
+
+    let mut res = c.handshake(Service::UploadPack, &[])?;
+    assert_eq!(res.actual_protocol, Protocol::V1);
+    let refs = res
+        .refs
+        .as_mut()
+        .expect("v1 protocol provides refs")
+        .lines()
+        .collect::<Result<Vec<_>, _>>()?;
+    drop(res);
+    assert_main_branch_is_advertised(refs);
+
+    c.request(client::WriteMode::Binary, client::MessageKind::Flush)?
+        .into_read()?;
+    Ok(())
+}
+
+#[test]
+fn daemon_requests_carry_the_version_as_extra_parameter() -> crate::Result {
+    let dir = repo_dir()?;
+    let mut c = ext::connect(
+        &format!(
+            "git daemon --inetd --export-all --base-path={} %G/repo.git",
+            escape(&dir)
+        ),
+        Protocol::V2,
+    )?;
+
+    let res = c.handshake(Service::UploadPack, &[])?;
+    assert_eq!(res.version_request, client::VersionRequest::ExtraParameter);
+    assert_eq!(
+        res.actual_protocol,
+        Protocol::V2,
+        "the daemon passed the requested version on to upload-pack"
+    );
+    drop(res);
+
+    c.request(client::WriteMode::Binary, client::MessageKind::Flush)?
+        .into_read()?;
+    Ok(())
+}
+
+#[test]
+fn invalid_templates_are_rejected_on_connect() {
+    assert!(matches!(
+        ext::connect("ext::git %x", Protocol::V1),
+        Err(ext::Error::UnknownPlaceholder('x'))
+    ));
+}
@@ -0,0 +1,40 @@
+use git_transport::{
+    client::{self, file, Transport, TransportWithoutIO, VersionRequest},
+    Protocol, Service,
+};
+
+fn repo_path() -> crate::Result<std::path::PathBuf> {
+    Ok(git_testtools::scripted_fixture_repo_read_only("make_repo_for_custom_transports.sh")?.join("repo.git"))
+}
+
+#[test]
+fn handshake_v2_requests_the_version_in_the_environment() -> crate::Result {
+    let mut c = file::connect(repo_path()?.to_str().expect("valid UTF-8"), Protocol::V2)?;
+    let res = c.handshake(Service::UploadPack, &[])?;
+    assert_eq!(res.version_request, VersionRequest::EnvironmentVariable);
+    assert_eq!(
+        res.actual_protocol,
+        Protocol::V2,
+        "the server selected the version passed with GIT_PROTOCOL"
+    );
+    assert!(res.refs.is_none(), "V2 needs a separate request for refs");
+    drop(res);
+
+    c.request(client::WriteMode::Binary, client::MessageKind::Flush)?
+        .into_read()?;
+    Ok(())
+}
+
+#[test]
+fn handshake_v1_can_be_forced() -> crate::Result {
+    let mut c = file::connect(repo_path()?.to_str().expect("valid UTF-8"), Protocol::V1)?;
+    let res = c.handshake(Service::UploadPack, &[])?;
+    assert_eq!(res.version_request, VersionRequest::None);
+    assert_eq!(res.actual_protocol, Protocol::V1);
+    assert!(res.refs.is_some(), "V1 provides refs as part of the handshake");
+    drop(res);
+
+    c.request(client::WriteMode::Binary, client::MessageKind::Flush)?
+        .into_read()?;
+    Ok(())
+}
@@ -1,2 +1,7 @@
+mod connect;
+mod ext;
+mod file;
 #[cfg(feature = "http-client-curl")]
 mod http;
+#[cfg(unix)]
+mod unix_socket;
@@ -0,0 +1,55 @@
+use std::{
+    io::{BufRead, Read, Write},
+    os::unix::net::UnixListener,
+};
+
+use bstr::ByteSlice;
+use git_transport::{
+    client::{git, Transport, TransportWithoutIO},
+    Protocol, Service,
+};
+
+use crate::fixture_bytes;
+
+#[test]
+fn handshake_v1_with_daemon_listening_on_socket() -> crate::Result {
+    let dir = git_testtools::tempfile::tempdir()?;
+    let socket_path = dir.path().join("daemon.sock");
+    let listener = UnixListener::bind(&socket_path)?;
+    let server = std::thread::spawn(move || -> std::io::Result<Vec<u8>> {
+        let (mut stream, _addr) = listener.accept()?;
+        let mut len = [0u8; 4];
+        stream.read_exact(&mut len)?;
+        let len = usize::from_str_radix(std::str::from_utf8(&len).expect("hex"), 16).expect("valid length");
+        let mut request = vec![0; len - 4];
+        stream.read_exact(&mut request)?;
+        stream.write_all(&fixture_bytes("v1/clone.response"))?;
+        Ok(request)
+    });
+
+    let mut c = git::connect_unix_socket(&socket_path, "/repo.git".into(), Protocol::V1)?;
+    assert_eq!(c.to_url(), format!("unix://{}", socket_path.display()));
+    let mut res = c.handshake(Service::UploadPack, &[])?;
+    assert_eq!(res.actual_protocol, Protocol::V1);
+    let refs = res
+        .refs
+        .as_mut()
+        .expect("v1 protocol provides refs")
+        .lines()
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        refs,
+        vec![
+            "808e50d724f604f69ab93c6da2919c014667bedb HEAD",
+            "808e50d724f604f69ab93c6da2919c014667bedb refs/heads/master"
+        ]
+    );
+
+    let request = server.join().expect("no panic")?;
+    assert_eq!(
+        request.as_bstr(),
+        b"git-upload-pack /repo.git\0".as_bstr(),
+        "a daemon request is sent"
+    );
+    Ok(())
+}
@@ -49,3 +49,18 @@ fn from_bytes() -> crate::Result {
     );
     Ok(())
 }
+
+#[test]
+fn from_bytes_without_capabilities() -> crate::Result {
+    let line = b"7814e8a05a59c0cf5fb186661d1551c75d1299b5 HEAD";
+    for (input, expected_delim_pos) in [
+        (&b"7814e8a05a59c0cf5fb186661d1551c75d1299b5 HEAD\0"[..], 45),
+        (&line[..], 45),
+    ] {
+        let (caps, delim_pos) = Capabilities::from_bytes(input)?;
+        assert_eq!(delim_pos, expected_delim_pos);
+        assert_eq!(caps.iter().count(), 0, "an empty list of capabilities is no capability");
+        assert!(!caps.contains("multi_ack"));
+    }
+    Ok(())
+}
@@ -38,6 +38,11 @@ async fn handshake_v1_and_request() -> crate::Result {
     assert_eq!(c.to_url(), "file:///foo.git");
     let mut res = c.handshake(Service::UploadPack, &[]).await?;
     assert_eq!(res.actual_protocol, Protocol::V1);
+    assert_eq!(
+        res.version_request,
+        client::VersionRequest::None,
+        "V1 is assumed by servers if no version is requested"
+    );
     assert_eq!(
         res.capabilities
             .iter()
@@ -155,7 +160,9 @@ async fn handshake_v1_process_mode() -> crate::Result {
         Some(("example.org", None)),
         git::ConnectMode::Process,
     );
-    c.handshake(Service::UploadPack, &[]).await?;
+    let res = c.handshake(Service::UploadPack, &[]).await?;
+    assert_eq!(res.version_request, client::VersionRequest::None);
+    drop(res);
 
     assert_eq!(
         out.as_slice().as_bstr(),
@@ -165,6 +172,45 @@ async fn handshake_v1_process_mode() -> crate::Result {
     Ok(())
 }
 
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn handshake_v1_without_capabilities() -> crate::Result {
+    for first_line in &[
+        &b"0032808e50d724f604f69ab93c6da2919c014667bedb HEAD\n"[..],
+        &b"0033808e50d724f604f69ab93c6da2919c014667bedb HEAD\0\n"[..],
+        &b"0031808e50d724f604f69ab93c6da2919c014667bedb HEAD"[..],
+    ] {
+        let mut server_response = first_line.to_vec();
+        server_response.extend_from_slice(b"003f808e50d724f604f69ab93c6da2919c014667bedb refs/heads/master\n0000");
+        let mut out = Vec::new();
+        let mut c = git::Connection::new(
+            server_response.as_slice(),
+            &mut out,
+            Protocol::V1,
+            "/foo.git",
+            Some(("example.org", None)),
+            git::ConnectMode::Process,
+        );
+        let mut res = c.handshake(Service::UploadPack, &[]).await?;
+        assert_eq!(res.actual_protocol, Protocol::V1);
+        assert_eq!(res.capabilities.iter().count(), 0, "there are no capabilities");
+
+        let mut lines = res.refs.as_mut().expect("v1 protocol provides refs").lines();
+        let mut refs = Vec::new();
+        #[allow(clippy::while_let_on_iterator)] // needed in async version of test
+        while let Some(line) = lines.next().await {
+            refs.push(line?)
+        }
+        assert_eq!(
+            refs,
+            vec![
+                "808e50d724f604f69ab93c6da2919c014667bedb HEAD",
+                "808e50d724f604f69ab93c6da2919c014667bedb refs/heads/master"
+            ]
+        );
+    }
+    Ok(())
+}
+
 #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
 async fn handshake_v2_downgrade_to_v1() -> crate::Result {
     let mut out = Vec::new();
@@ -179,6 +225,11 @@ async fn handshake_v2_downgrade_to_v1() -> crate::Result {
     );
     let res = c.handshake(Service::UploadPack, &[]).await?;
     assert_eq!(res.actual_protocol, Protocol::V1);
+    assert_eq!(
+        res.version_request,
+        client::VersionRequest::ExtraParameter,
+        "V2 was requested, but the server selected V1"
+    );
     assert!(
         res.refs.is_some(),
         "V1 downgrades 'just happen', so we should have refs as part of the handshake"
@@ -186,6 +237,35 @@ async fn handshake_v2_downgrade_to_v1() -> crate::Result {
     drop(res);
 
     assert_eq!(c.supported_protocol_versions(), [Protocol::V2]);
+    assert_eq!(
+        out.as_bstr(),
+        b"0039git-upload-pack /bar.git\0host=example.org\0\0version=2\0".as_bstr(),
+        "the version is sent as extra parameter"
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn handshake_v2_process_mode() -> crate::Result {
+    let mut out = Vec::new();
+    let input = fixture_bytes("v2/clone.response");
+    let mut c = git::Connection::new(
+        input.as_slice(),
+        &mut out,
+        Protocol::V2,
+        "/bar.git",
+        Some(("example.org", None)),
+        git::ConnectMode::Process,
+    );
+    let res = c.handshake(Service::UploadPack, &[]).await?;
+    assert_eq!(res.actual_protocol, Protocol::V2);
+    assert_eq!(
+        res.version_request,
+        client::VersionRequest::EnvironmentVariable,
+        "spawned processes get the version through their environment"
+    );
+    drop(res);
+    assert!(out.is_empty(), "nothing is sent to processes");
     Ok(())
 }
 
@@ -0,0 +1,214 @@
+use std::io;
+#[cfg(feature = "blocking-client")]
+use std::io::BufRead;
+
+#[cfg(feature = "async-client")]
+use futures_lite::{AsyncBufReadExt, StreamExt};
+use git_transport::{
+    client,
+    client::{git, limits, Transport, TransportWithoutIO},
+    Protocol, Service,
+};
+
+const ID: &str = "808e50d724f604f69ab93c6da2919c014667bedb";
+
+fn packet_line(data: &str) -> String {
+    format!("{:04x}{}", data.len() + 4, data)
+}
+
+fn connection(server_response: &[u8], limits: client::Limits) -> git::Connection<&[u8], Vec<u8>> {
+    let mut c = git::Connection::new(
+        server_response,
+        Vec::new(),
+        Protocol::V1,
+        "/foo.git",
+        None::<(&str, _)>,
+        git::ConnectMode::Process,
+    );
+    c.set_limits(limits).expect("git connections support limits");
+    assert_eq!(c.limits(), limits);
+    c
+}
+
+fn limit_error(err: &io::Error) -> &limits::Error {
+    err.get_ref()
+        .and_then(|err| err.downcast_ref::<limits::Error>())
+        .expect("the error to be caused by a limit")
+}
+
+fn transport_limit_error(err: &client::Error) -> &limits::Error {
+    match err {
+        client::Error::Io { err } => limit_error(err),
+        other => panic!("expected an IO error, got {:?}", other),
+    }
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn enormous_capability_line() -> crate::Result {
+    let server_response = packet_line(&format!("{} HEAD\0agent=git/2.28.0 {}\n", ID, "x".repeat(2000)));
+    let mut c = connection(
+        server_response.as_bytes(),
+        client::Limits {
+            max_packet_line_len: Some(1000),
+            ..Default::default()
+        },
+    );
+    let err = c
+        .handshake(Service::UploadPack, &[])
+        .await
+        .err()
+        .expect("the line is too long");
+    assert!(matches!(
+        transport_limit_error(&err),
+        limits::Error::DataLength { length, limit: 1000 } if *length > 2000
+    ));
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn endless_capability_advertisement() -> crate::Result {
+    let mut server_response = packet_line("version 2\n");
+    for id in 0..10_000 {
+        server_response.push_str(&packet_line(&format!("capability-{}\n", id)));
+    }
+    let mut c = connection(
+        server_response.as_bytes(),
+        client::Limits {
+            max_advertisement_bytes: Some(16 * 1024),
+            ..Default::default()
+        },
+    );
+    let err = c
+        .handshake(Service::UploadPack, &[])
+        .await
+        .err()
+        .expect("too many capabilities");
+    assert!(matches!(
+        transport_limit_error(&err),
+        limits::Error::BytesBeforePayload { limit: 16384 }
+    ));
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn endless_ref_advertisement() -> crate::Result {
+    let mut server_response = packet_line(&format!("{} HEAD\0agent=git/2.28.0\n", ID));
+    for id in 0..10_000 {
+        server_response.push_str(&packet_line(&format!("{} refs/heads/branch-{}\n", ID, id)));
+    }
+    let mut c = connection(
+        server_response.as_bytes(),
+        client::Limits {
+            max_advertisement_bytes: Some(16 * 1024),
+            ..Default::default()
+        },
+    );
+    let mut res = c.handshake(Service::UploadPack, &[]).await?;
+    let mut lines = res.refs.as_mut().expect("v1 protocol provides refs").lines();
+    let mut refs = 0;
+    let err = loop {
+        match lines
+            .next()
+            .await
+            .expect("the limit to be hit before all refs are read")
+        {
+            Ok(_) => refs += 1,
+            Err(err) => break err,
+        }
+    };
+    assert!(refs < 300, "{} refs were read before the limit hit", refs);
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(matches!(
+        limit_error(&err),
+        limits::Error::BytesBeforePayload { limit: 16384 }
+    ));
+    Ok(())
+}
+
+#[cfg(feature = "blocking-client")]
+mod blocking_io {
+    use std::{
+        io::{self, Read, Write},
+        time::Duration,
+    };
+
+    use bstr::ByteSlice;
+    use git_transport::{
+        client,
+        client::{git, limits, Transport, TransportWithoutIO},
+        Protocol, Service,
+    };
+
+    use crate::fixture_bytes;
+
+    /// A server which sends everything up to `fast_bytes` right away, and all of the rest byte by byte with a pause inbetween.
+    struct SlowLoris {
+        data: Vec<u8>,
+        pos: usize,
+        fast_bytes: usize,
+    }
+
+    impl Read for SlowLoris {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let end = if self.pos < self.fast_bytes {
+                self.fast_bytes
+            } else {
+                std::thread::sleep(Duration::from_millis(1));
+                self.pos + 1
+            };
+            let mut remaining = &self.data[self.pos..end.min(self.data.len())];
+            let n = remaining.read(buf)?;
+            self.pos += n;
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn slow_pack_transfer() -> crate::Result {
+        let data = fixture_bytes("v1/clone.response");
+        let nak = b"0008NAK\n";
+        let fast_bytes = data.find(nak).expect("NAK line") + nak.len();
+        let mut c = git::Connection::new(
+            SlowLoris {
+                data,
+                pos: 0,
+                fast_bytes,
+            },
+            Vec::new(),
+            Protocol::V1,
+            "/foo.git",
+            None::<(&str, _)>,
+            git::ConnectMode::Process,
+        );
+        let limit = limits::Throughput {
+            bytes_per_second: 10_000,
+            window: Duration::from_millis(50),
+        };
+        c.set_limits(client::Limits {
+            min_pack_throughput: Some(limit),
+            ..Default::default()
+        })?;
+        let mut res = c.handshake(Service::UploadPack, &[])?;
+        io::copy(res.refs.as_mut().expect("v1 protocol provides refs"), &mut io::sink())?;
+        drop(res);
+
+        let mut writer = c.request(
+            client::WriteMode::OneLfTerminatedLinePerWriteCall,
+            client::MessageKind::Text(b"done"),
+        )?;
+        writer.write_all(b"want 808e50d724f604f69ab93c6da2919c014667bedb")?;
+        let mut reader = writer.into_read()?;
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        assert_eq!(line, "NAK\n", "the negotiation is fast");
+        reader.set_progress_handler(Some(Box::new(|_is_err, _text| {})));
+
+        let err = io::copy(&mut reader, &mut io::sink()).expect_err("the pack trickles in too slowly");
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(matches!(
+            err.get_ref().and_then(|err| err.downcast_ref::<limits::Error>()),
+            Some(limits::Error::Throughput { limit: actual, .. }) if *actual == limit
+        ));
+        Ok(())
+    }
+}
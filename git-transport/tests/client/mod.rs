@@ -3,3 +3,4 @@ mod blocking_io;
 #[cfg(not(feature = "http-client-curl"))]
 mod capabilities;
 mod git;
+mod limits;
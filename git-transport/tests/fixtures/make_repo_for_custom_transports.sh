@@ -0,0 +1,26 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q --bare repo.git
+git init -q
+git checkout -q -b main
+git commit -q --allow-empty -m "first"
+git push -q repo.git main
+git -C repo.git symbolic-ref HEAD refs/heads/main
+
+# An `ssh` program which records how it was invoked and runs the remote command locally instead.
+mkdir bin
+cat <<'SSH' > bin/ssh
+#!/bin/bash
+set -eu -o pipefail
+{
+  echo "GIT_PROTOCOL=${GIT_PROTOCOL:-}"
+  echo "$@"
+} > "${0%/*}/../ssh-invocation"
+while [[ "$1" == -* ]]; do
+  shift 2
+done
+shift
+exec "$@"
+SSH
+chmod +x bin/ssh
@@ -0,0 +1,65 @@
+//! Compare a full traversal of the history with one that stops at a minimum generation, as obtained from a commit-graph.
+//!
+//! The benchmark needs a clone of a large repository with a commit-graph, like the linux kernel, whose path is set in the
+//! `GITOXIDE_BENCH_REPO` environment variable. Use `git commit-graph write --reachable` to create a commit-graph if needed.
+//! It does nothing if the variable isn't set.
+use std::path::{Path, PathBuf};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use git_hash::ObjectId;
+use git_odb::{linked::Store, pack, FindExt};
+use git_traverse::commit;
+
+/// How many commits to go back from `HEAD` to find the commit whose generation is the minimum generation to traverse.
+const DISTANCE: &str = "HEAD~1000";
+
+fn rev_parse(repo: &Path, spec: &str) -> ObjectId {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", spec])
+        .current_dir(repo)
+        .output()
+        .expect("git can be executed");
+    assert!(output.status.success(), "git rev-parse {} failed", spec);
+    ObjectId::from_hex(&output.stdout[..40]).expect("valid hex id")
+}
+
+fn ancestors(c: &mut Criterion) {
+    let repo = match std::env::var_os("GITOXIDE_BENCH_REPO") {
+        Some(repo) => PathBuf::from(repo),
+        None => return,
+    };
+    let objects = repo.join(".git").join("objects");
+    let db = Store::at(&objects).expect("valid object database");
+    let graph = git_commitgraph::Graph::from_info_dir(objects.join("info")).expect("repository has a commit-graph");
+    let tip = rev_parse(&repo, "HEAD");
+    let min_generation = graph
+        .commit_by_id(rev_parse(&repo, DISTANCE))
+        .expect("commit is part of the commit-graph")
+        .generation();
+
+    let mut state = commit::ancestors::State::default();
+    let mut group = c.benchmark_group("ancestors");
+    group.sample_size(10);
+    group.bench_function("full history", |b| {
+        b.iter(|| {
+            commit::Ancestors::new(Some(tip), &mut state, |oid, buf| {
+                db.find_commit_iter(oid, buf, &mut pack::cache::Never).ok()
+            })
+            .count()
+        })
+    });
+    group.bench_function(format!("down to generation of {}", DISTANCE), |b| {
+        b.iter(|| {
+            commit::Ancestors::new(Some(tip), &mut state, |oid, buf| {
+                db.find_commit_iter(oid, buf, &mut pack::cache::Never).ok()
+            })
+            .with_min_generation(min_generation)
+            .with_commit_graph(&graph)
+            .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, ancestors);
+criterion_main!(benches);
@@ -1,9 +1,13 @@
 /// An iterator over the ancestors one or more starting commits
-pub struct Ancestors<Find, Predicate, StateMut> {
+pub struct Ancestors<Find, Predicate, StateMut, Generation = fn(&git_hash::oid) -> Option<u32>> {
     find: Find,
     predicate: Predicate,
     state: StateMut,
     mode: Parents,
+    order: CommitOrder,
+    sorted: bool,
+    generation: Option<Generation>,
+    min_generation: Option<u32>,
 }
 
 /// Specify how to handle commit parents during traversal.
@@ -20,18 +24,45 @@ impl Default for Parents {
     }
 }
 
+/// Specify the order in which commits are returned during traversal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitOrder {
+    /// Commits are returned in the order they are discovered, walking the graph breadth first.
+    ///
+    /// This is the fastest mode as nothing needs to be known upfront, but a commit may be returned before
+    /// all of its children are returned.
+    BreadthFirst,
+    /// No commit is returned before all of its children that are part of the traversal have been returned,
+    /// and lines of history are not intermixed, similar to `git rev-list --topo-order`.
+    ///
+    /// This requires the entire commit graph reachable from the tips to be traversed before the first commit is returned.
+    TopoOrder,
+    /// No commit is returned before all of its children that are part of the traversal have been returned,
+    /// but otherwise commits are returned in order of their committer timestamp, newest first, similar to `git rev-list --date-order`.
+    ///
+    /// This requires the entire commit graph reachable from the tips to be traversed before the first commit is returned.
+    DateOrder,
+}
+
+impl Default for CommitOrder {
+    fn default() -> Self {
+        CommitOrder::BreadthFirst
+    }
+}
+
 ///
 pub mod ancestors {
     use std::{
         borrow::BorrowMut,
-        collections::{BTreeSet, VecDeque},
+        cmp::Reverse,
+        collections::{BTreeMap, BTreeSet, BinaryHeap, VecDeque},
     };
 
     use git_hash::{oid, ObjectId};
     use git_object::CommitRefIter;
     use quick_error::quick_error;
 
-    use crate::commit::{Ancestors, Parents};
+    use crate::commit::{Ancestors, CommitOrder, Parents};
 
     quick_error! {
         /// The error is part of the item returned by the [Ancestors] iterator.
@@ -55,6 +86,18 @@ pub mod ancestors {
         next: VecDeque<ObjectId>,
         buf: Vec<u8>,
         seen: BTreeSet<ObjectId>,
+        graph: BTreeMap<ObjectId, Vertex>,
+        by_date: BinaryHeap<(u32, Reverse<usize>, ObjectId)>,
+        num_queued: usize,
+    }
+
+    /// A commit in the graph to traverse when sorting is required.
+    #[derive(Default, Clone)]
+    struct Vertex {
+        /// The amount of children that have not yet been returned.
+        in_degree: usize,
+        parents: Vec<ObjectId>,
+        commit_time: u32,
     }
 
     impl State {
@@ -62,15 +105,127 @@ pub mod ancestors {
             self.next.clear();
             self.buf.clear();
             self.seen.clear();
+            self.graph.clear();
+            self.by_date.clear();
+            self.num_queued = 0;
+        }
+
+        /// Queue `id` for being returned next, which is only done once all of its children have been returned.
+        fn queue_sorted(&mut self, id: ObjectId, order: CommitOrder) {
+            match order {
+                CommitOrder::TopoOrder => self.next.push_back(id),
+                CommitOrder::DateOrder => {
+                    let commit_time = self.graph.get(&id).map(|v| v.commit_time).unwrap_or_default();
+                    self.by_date.push((commit_time, Reverse(self.num_queued), id));
+                    self.num_queued += 1;
+                }
+                CommitOrder::BreadthFirst => unreachable!("breadth first traversal doesn't use the sorted queue"),
+            }
+        }
+
+        fn pop_sorted(&mut self, order: CommitOrder) -> Option<ObjectId> {
+            match order {
+                CommitOrder::TopoOrder => self.next.pop_back(),
+                CommitOrder::DateOrder => self.by_date.pop().map(|(_, _, id)| id),
+                CommitOrder::BreadthFirst => unreachable!("breadth first traversal doesn't use the sorted queue"),
+            }
+        }
+    }
+
+    /// Resuming traversals
+    impl State {
+        /// Create a state to continue a [breadth-first][CommitOrder::BreadthFirst] traversal with
+        /// [`Ancestors::resume()`], from the commits `queued` for traversal and all commits `seen` when the previous traversal
+        /// was stopped, as obtained by [`queued()`][State::queued()] and [`seen()`][State::seen()].
+        pub fn from_queued_and_seen(
+            queued: impl IntoIterator<Item = ObjectId>,
+            seen: impl IntoIterator<Item = ObjectId>,
+        ) -> Self {
+            State {
+                next: queued.into_iter().collect(),
+                seen: seen.into_iter().collect(),
+                ..Default::default()
+            }
+        }
+
+        /// Return the commits which a breadth-first traversal would return next, in order.
+        pub fn queued(&self) -> impl Iterator<Item = &oid> + '_ {
+            self.next.iter().map(|id| id.as_ref())
+        }
+
+        /// Return all commits seen by a traversal so far, including the ones which are queued and those that were skipped.
+        pub fn seen(&self) -> impl Iterator<Item = &oid> + '_ {
+            self.seen.iter().map(|id| id.as_ref())
         }
     }
 
-    impl<Find, Predicate, StateMut> Ancestors<Find, Predicate, StateMut> {
+    impl<Find, Predicate, StateMut, Generation> Ancestors<Find, Predicate, StateMut, Generation> {
         /// Change our commit parent handling mode to the given one.
         pub fn mode(mut self, mode: Parents) -> Self {
             self.mode = mode;
             self
         }
+
+        /// If `toggle` is true, only traverse the first parent of each commit, otherwise traverse all of them.
+        pub fn first_parent(self, toggle: bool) -> Self {
+            self.mode(if toggle { Parents::First } else { Parents::All })
+        }
+
+        /// Change the order in which commits are returned to the given one.
+        ///
+        /// Note that all orders but [`CommitOrder::BreadthFirst`] will traverse all commits reachable from the tips
+        /// when the first commit is requested.
+        pub fn order(mut self, order: CommitOrder) -> Self {
+            self.order = order;
+            self
+        }
+
+        /// Do not traverse into parents whose generation number is lower than `min_generation`, as none of their ancestors
+        /// can have a higher generation number. Parents whose generation isn't known are always traversed.
+        ///
+        /// This only has an effect if generation numbers are available, see
+        /// [`with_commit_graph()`][Ancestors::with_commit_graph()] and [`with_generations()`][Ancestors::with_generations()].
+        pub fn with_min_generation(mut self, min_generation: u32) -> Self {
+            self.min_generation = Some(min_generation);
+            self
+        }
+
+        /// Use `generation` to lookup the generation number of a commit, returning `None` if it isn't known, to be able to skip
+        /// parents below the [minimum generation][Ancestors::with_min_generation()].
+        pub fn with_generations<NewGeneration>(
+            self,
+            generation: NewGeneration,
+        ) -> Ancestors<Find, Predicate, StateMut, NewGeneration>
+        where
+            NewGeneration: FnMut(&oid) -> Option<u32>,
+        {
+            Ancestors {
+                find: self.find,
+                predicate: self.predicate,
+                state: self.state,
+                mode: self.mode,
+                order: self.order,
+                sorted: self.sorted,
+                generation: Some(generation),
+                min_generation: self.min_generation,
+            }
+        }
+
+        /// Obtain generation numbers from the commit-graph `graph` to be able to skip parents below the
+        /// [minimum generation][Ancestors::with_min_generation()]. Commits which are not part of `graph` are always traversed.
+        ///
+        /// This allows to stop traversing a history early if the commits to find are known to have a certain generation,
+        /// instead of traversing it entirely.
+        pub fn with_commit_graph(
+            self,
+            graph: &git_commitgraph::Graph,
+        ) -> Ancestors<Find, Predicate, StateMut, impl FnMut(&oid) -> Option<u32> + '_> {
+            self.with_generations(generations_of(graph))
+        }
+    }
+
+    fn generations_of(graph: &git_commitgraph::Graph) -> impl FnMut(&oid) -> Option<u32> + '_ {
+        move |id| graph.commit_by_id(id).map(|commit| commit.generation())
     }
 
     impl<Find, StateMut> Ancestors<Find, fn(&oid) -> bool, StateMut>
@@ -93,6 +248,14 @@ pub mod ancestors {
         pub fn new(tips: impl IntoIterator<Item = impl Into<ObjectId>>, state: StateMut, find: Find) -> Self {
             Self::filtered(tips, state, find, |_| true)
         }
+
+        /// Continue a breadth-first traversal with `state` as left by a previous one, or as created with
+        /// [`State::from_queued_and_seen()`], to return the commits the previous traversal would have returned next.
+        ///
+        /// See [`new()`][Ancestors::new()] for details on `find`.
+        pub fn resume(state: StateMut, find: Find) -> Self {
+            Self::resume_filtered(state, find, |_| true)
+        }
     }
 
     impl<Find, Predicate, StateMut> Ancestors<Find, Predicate, StateMut>
@@ -138,19 +301,144 @@ pub mod ancestors {
                 predicate,
                 state,
                 mode: Default::default(),
+                order: Default::default(),
+                sorted: false,
+                generation: None,
+                min_generation: None,
+            }
+        }
+
+        /// Like [`resume()`][Ancestors::resume()], but with `predicate` to filter commits like in
+        /// [`filtered()`][Ancestors::filtered()].
+        pub fn resume_filtered(state: StateMut, find: Find, predicate: Predicate) -> Self {
+            Self {
+                find,
+                predicate,
+                state,
+                mode: Default::default(),
+                order: Default::default(),
+                sorted: false,
+                generation: None,
+                min_generation: None,
             }
         }
     }
 
-    impl<Find, Predicate, StateMut> Iterator for Ancestors<Find, Predicate, StateMut>
+    impl<Find, Predicate, StateMut, Generation> Ancestors<Find, Predicate, StateMut, Generation>
     where
         Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<CommitRefIter<'a>>,
         Predicate: FnMut(&oid) -> bool,
         StateMut: BorrowMut<State>,
+        Generation: FnMut(&oid) -> Option<u32>,
+    {
+        /// Return true if the parent `id` should be traversed as it isn't known to be below our minimum generation.
+        fn is_above_min_generation(generation: &mut Option<Generation>, min_generation: Option<u32>, id: &oid) -> bool {
+            match (generation, min_generation) {
+                (Some(generation), Some(min_generation)) => !matches!(generation(id), Some(g) if g < min_generation),
+                _ => true,
+            }
+        }
+
+        /// Traverse the entire graph reachable from our tips to learn about parents, commit times and the amount of
+        /// children of each commit, and queue all commits without children for being returned.
+        fn sort(&mut self) -> Result<(), Error> {
+            let state = self.state.borrow_mut();
+            let tips: Vec<_> = state.next.drain(..).collect();
+            for tip in &tips {
+                state.graph.insert(*tip, Vertex::default());
+            }
+            let mut queue: VecDeque<_> = tips.iter().cloned().collect();
+            while let Some(oid) = queue.pop_front() {
+                let mut parents = Vec::new();
+                let mut commit_time = 0;
+                let mut commit_iter = (self.find)(&oid, &mut state.buf).ok_or(Error::NotFound { oid })?;
+                for token in &mut commit_iter {
+                    match token? {
+                        git_object::commit::ref_iter::Token::Tree { .. } => continue,
+                        git_object::commit::ref_iter::Token::Parent { id } => {
+                            if matches!(self.mode, Parents::First) && !parents.is_empty() {
+                                continue;
+                            }
+                            let was_inserted = state.seen.insert(id);
+                            if was_inserted
+                                && Self::is_above_min_generation(&mut self.generation, self.min_generation, &id)
+                                && (self.predicate)(&id)
+                            {
+                                state.graph.insert(id, Vertex::default());
+                                queue.push_back(id);
+                            }
+                            parents.push(id);
+                        }
+                        git_object::commit::ref_iter::Token::Author { .. } => continue,
+                        git_object::commit::ref_iter::Token::Committer { signature } => {
+                            commit_time = signature.time.time;
+                            break;
+                        }
+                        _a_token_past_the_committer => break,
+                    }
+                }
+                parents.retain(|id| state.graph.contains_key(id));
+                let vertex = state.graph.get_mut(&oid).expect("vertex inserted when queued");
+                vertex.commit_time = commit_time;
+                vertex.parents = parents;
+            }
+
+            let edges: Vec<_> = state.graph.values().flat_map(|v| v.parents.iter().cloned()).collect();
+            for parent in edges {
+                state
+                    .graph
+                    .get_mut(&parent)
+                    .expect("only edges to known commits")
+                    .in_degree += 1;
+            }
+            let mut tips_without_children: Vec<_> =
+                tips.into_iter().filter(|id| state.graph[id].in_degree == 0).collect();
+            if self.order == CommitOrder::TopoOrder {
+                // the queue is a stack in topological order, and we want the first tip to be returned first.
+                tips_without_children.reverse();
+            }
+            for tip in tips_without_children {
+                state.queue_sorted(tip, self.order);
+            }
+            Ok(())
+        }
+
+        fn next_sorted(&mut self) -> Option<Result<ObjectId, Error>> {
+            if !self.sorted {
+                self.sorted = true;
+                if let Err(err) = self.sort() {
+                    self.state.borrow_mut().clear();
+                    return Some(Err(err));
+                }
+            }
+            let order = self.order;
+            let state = self.state.borrow_mut();
+            let oid = state.pop_sorted(order)?;
+            let parents = std::mem::take(&mut state.graph.get_mut(&oid).expect("queued commits are known").parents);
+            for parent in parents {
+                let vertex = state.graph.get_mut(&parent).expect("only edges to known commits");
+                vertex.in_degree -= 1;
+                if vertex.in_degree == 0 {
+                    state.queue_sorted(parent, order);
+                }
+            }
+            Some(Ok(oid))
+        }
+    }
+
+    impl<Find, Predicate, StateMut, Generation> Iterator for Ancestors<Find, Predicate, StateMut, Generation>
+    where
+        Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<CommitRefIter<'a>>,
+        Predicate: FnMut(&oid) -> bool,
+        StateMut: BorrowMut<State>,
+        Generation: FnMut(&oid) -> Option<u32>,
     {
         type Item = Result<ObjectId, Error>;
 
         fn next(&mut self) -> Option<Self::Item> {
+            if self.order != CommitOrder::BreadthFirst {
+                return self.next_sorted();
+            }
             let state = self.state.borrow_mut();
             let res = state.next.pop_front();
             if let Some(oid) = res {
@@ -163,7 +451,10 @@ pub mod ancestors {
                             match token {
                                 Ok(git_object::commit::ref_iter::Token::Parent { id }) => {
                                     let was_inserted = state.seen.insert(id);
-                                    if was_inserted && (self.predicate)(&id) {
+                                    if was_inserted
+                                        && Self::is_above_min_generation(&mut self.generation, self.min_generation, &id)
+                                        && (self.predicate)(&id)
+                                    {
                                         state.next.push_back(id);
                                     }
                                     if matches!(self.mode, Parents::First) {
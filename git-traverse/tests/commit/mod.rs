@@ -181,4 +181,333 @@ mod ancestor {
             },
         )
     }
+
+    #[test]
+    fn resuming_continues_where_the_previous_traversal_stopped() -> crate::Result {
+        let db = db()?;
+        let tip = hex_to_id("01ec18a3ebf2855708ad3c9d244306bc1fae3e9b");
+        let all: Vec<_> = commit::Ancestors::new(Some(tip), commit::ancestors::State::default(), |oid, buf| {
+            db.find_commit_iter(oid, buf, &mut pack::cache::Never).ok()
+        })
+        .collect::<Result<_, _>>()?;
+
+        for stop_after in 0..=all.len() {
+            let mut state = commit::ancestors::State::default();
+            let mut commits: Vec<_> = commit::Ancestors::new(Some(tip), &mut state, |oid, buf| {
+                db.find_commit_iter(oid, buf, &mut pack::cache::Never).ok()
+            })
+            .take(stop_after)
+            .collect::<Result<_, _>>()?;
+            let state = commit::ancestors::State::from_queued_and_seen(
+                state.queued().map(ToOwned::to_owned).collect::<Vec<_>>(),
+                state.seen().map(ToOwned::to_owned).collect::<Vec<_>>(),
+            );
+            for commit in commit::Ancestors::resume(state, |oid, buf| {
+                db.find_commit_iter(oid, buf, &mut pack::cache::Never).ok()
+            }) {
+                commits.push(commit?);
+            }
+            assert_eq!(commits, all, "stopped after {} commits", stop_after);
+        }
+        Ok(())
+    }
+
+    mod order {
+        use git_hash::ObjectId;
+        use git_odb::{linked::Store, pack, FindExt};
+        use git_traverse::commit;
+
+        use crate::hex_to_id;
+
+        fn ordered_iter(
+            tips: impl IntoIterator<Item = impl Into<ObjectId>>,
+            order: commit::CommitOrder,
+            first_parent: bool,
+        ) -> crate::Result<impl Iterator<Item = Result<ObjectId, commit::ancestors::Error>>> {
+            let dir = git_testtools::scripted_fixture_repo_read_only("make_traversal_repo_for_commits_with_dates.sh")?;
+            let db = Store::at(dir.join(".git").join("objects"))?;
+            Ok(
+                commit::Ancestors::new(tips, commit::ancestors::State::default(), move |oid, buf| {
+                    db.find_commit_iter(oid, buf, &mut pack::cache::Never).ok()
+                })
+                .order(order)
+                .first_parent(first_parent),
+            )
+        }
+
+        fn check_traversal(
+            tips: &[&str],
+            expected: &[&str],
+            order: commit::CommitOrder,
+            first_parent: bool,
+        ) -> crate::Result {
+            let tips: Vec<_> = tips.iter().copied().map(hex_to_id).collect();
+            let oids: Result<Vec<_>, _> = ordered_iter(tips, order, first_parent)?.collect();
+            let expected: Vec<_> = expected.iter().copied().map(hex_to_id).collect();
+            assert_eq!(oids?, expected);
+            Ok(())
+        }
+
+        const MERGE: &str = "5c512b267df508cd51b4cf890b56985d06f50a58";
+        const C3: &str = "4d12a4d848dedea97a8abae8dd89df0cb29e252d";
+        const B1C3: &str = "f7e98a4579885209f2c1b7486c6bd76f6ea0aa35";
+        const B1C2: &str = "de58e7631f944f1a8306786c173aabc8aa2f715c";
+        const B1C1: &str = "b7ccb1e5f53670269e7efb2b85ea07def9df14b6";
+        const C2: &str = "c3486bde8ffb19046536a1b2668f9feb03a492a5";
+        const C1: &str = "134385f6d781b7e97062102c6a483440bfda2a03";
+
+        #[test]
+        fn breadth_first_is_the_default_and_may_return_parents_before_children() -> crate::Result {
+            check_traversal(
+                &[MERGE],
+                &[MERGE, C3, B1C3, C2, B1C2, C1, B1C1],
+                commit::CommitOrder::default(),
+                false,
+            )
+        }
+
+        #[test]
+        fn topo_order_does_not_intermix_lines_of_history() -> crate::Result {
+            check_traversal(
+                &[MERGE],
+                &[MERGE, B1C3, B1C2, B1C1, C3, C2, C1],
+                commit::CommitOrder::TopoOrder,
+                false,
+            )
+        }
+
+        #[test]
+        fn date_order_returns_newest_commits_first() -> crate::Result {
+            check_traversal(
+                &[MERGE],
+                &[MERGE, C3, B1C3, B1C2, B1C1, C2, C1],
+                commit::CommitOrder::DateOrder,
+                false,
+            )
+        }
+
+        #[test]
+        fn first_parent_applies_to_all_orders() -> crate::Result {
+            for order in [
+                commit::CommitOrder::BreadthFirst,
+                commit::CommitOrder::TopoOrder,
+                commit::CommitOrder::DateOrder,
+            ] {
+                check_traversal(&[MERGE], &[MERGE, C3, C2, C1], order, true)?;
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn tips_reachable_from_other_tips_are_returned_after_their_children() -> crate::Result {
+            check_traversal(
+                &[B1C1, MERGE],
+                &[MERGE, B1C3, B1C2, B1C1, C3, C2, C1],
+                commit::CommitOrder::TopoOrder,
+                false,
+            )?;
+            check_traversal(
+                &[C2, B1C2],
+                &[B1C2, B1C1, C2, C1],
+                commit::CommitOrder::DateOrder,
+                false,
+            )
+        }
+    }
+
+    mod compare_with_git_rev_list {
+        use std::path::Path;
+
+        use git_hash::{oid, ObjectId};
+        use git_odb::{linked::Store, pack, FindExt};
+        use git_traverse::commit;
+
+        fn rev_list(dir: &Path, args: &[&str]) -> crate::Result<Vec<ObjectId>> {
+            let output = std::process::Command::new("git")
+                .arg("rev-list")
+                .args(args)
+                .arg("main")
+                .current_dir(dir)
+                .output()?;
+            assert!(output.status.success(), "git rev-list {:?} failed", args);
+            Ok(output
+                .stdout
+                .split(|b| *b == b'\n')
+                .filter(|line| !line.is_empty())
+                .map(ObjectId::from_hex)
+                .collect::<Result<_, _>>()?)
+        }
+
+        fn traverse(
+            dir: &Path,
+            order: commit::CommitOrder,
+            first_parent: bool,
+            predicate: impl FnMut(&oid) -> bool,
+        ) -> crate::Result<Vec<ObjectId>> {
+            let db = Store::at(dir.join(".git").join("objects"))?;
+            let tip = rev_list(dir, &["-1"])?;
+            Ok(commit::Ancestors::filtered(
+                tip,
+                commit::ancestors::State::default(),
+                move |oid, buf| db.find_commit_iter(oid, buf, &mut pack::cache::Never).ok(),
+                predicate,
+            )
+            .order(order)
+            .first_parent(first_parent)
+            .collect::<Result<_, _>>()?)
+        }
+
+        const MODES: [(commit::CommitOrder, &str); 2] = [
+            (commit::CommitOrder::TopoOrder, "--topo-order"),
+            (commit::CommitOrder::DateOrder, "--date-order"),
+        ];
+
+        #[test]
+        fn sorted_orders_match_git_even_with_clock_skew() -> crate::Result {
+            let dir =
+                git_testtools::scripted_fixture_repo_read_only("make_traversal_repo_for_commits_with_clock_skew.sh")?;
+            for (order, arg) in MODES {
+                for first_parent in [false, true] {
+                    let mut args = vec![arg];
+                    if first_parent {
+                        args.push("--first-parent");
+                    }
+                    assert_eq!(
+                        traverse(&dir, order, first_parent, |_| true)?,
+                        rev_list(&dir, &args)?,
+                        "{:?}",
+                        args
+                    );
+                }
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn predicates_prune_commits_and_their_exclusive_ancestors_in_sorted_orders() -> crate::Result {
+            let dir =
+                git_testtools::scripted_fixture_repo_read_only("make_traversal_repo_for_commits_with_clock_skew.sh")?;
+            let pruned = rev_list(&dir, &["--skip=5", "-1", "--topo-order"])?[0];
+            let only_reachable_through_pruned = rev_list(&dir, &["--skip=6", "-1", "--topo-order"])?[0];
+            for (order, arg) in MODES {
+                let expected: Vec<_> = rev_list(&dir, &[arg])?
+                    .into_iter()
+                    .filter(|id| *id != pruned && *id != only_reachable_through_pruned)
+                    .collect();
+                assert_eq!(traverse(&dir, order, false, |id| id != pruned)?, expected, "{}", arg);
+            }
+            Ok(())
+        }
+    }
+
+    mod generation {
+        use std::{cell::Cell, path::Path};
+
+        use git_hash::ObjectId;
+        use git_object::bstr::ByteSlice;
+        use git_odb::{linked::Store, pack, FindExt};
+        use git_traverse::commit;
+
+        fn rev_parse(dir: &Path, spec: &str) -> crate::Result<ObjectId> {
+            let output = std::process::Command::new("git")
+                .args(["rev-parse", spec])
+                .current_dir(dir)
+                .output()?;
+            assert!(output.status.success(), "git rev-parse {} failed", spec);
+            Ok(ObjectId::from_hex(output.stdout.trim_end())?)
+        }
+
+        /// Return all commits reachable from `main` in `order` along with the amount of commits that were looked up.
+        fn traverse(
+            dir: &Path,
+            order: commit::CommitOrder,
+            graph: Option<&git_commitgraph::Graph>,
+            min_generation: Option<u32>,
+        ) -> crate::Result<(Vec<ObjectId>, usize)> {
+            let db = Store::at(dir.join(".git").join("objects"))?;
+            let lookups = Cell::new(0);
+            let mut ancestors = commit::Ancestors::new(
+                Some(rev_parse(dir, "main")?),
+                commit::ancestors::State::default(),
+                |oid, buf| {
+                    lookups.set(lookups.get() + 1);
+                    db.find_commit_iter(oid, buf, &mut pack::cache::Never).ok()
+                },
+            )
+            .order(order);
+            if let Some(min_generation) = min_generation {
+                ancestors = ancestors.with_min_generation(min_generation);
+            }
+            let commits = match graph {
+                Some(graph) => ancestors.with_commit_graph(graph).collect::<Result<_, _>>()?,
+                None => ancestors.collect::<Result<_, _>>()?,
+            };
+            Ok((commits, lookups.get()))
+        }
+
+        const ORDERS: [commit::CommitOrder; 3] = [
+            commit::CommitOrder::BreadthFirst,
+            commit::CommitOrder::TopoOrder,
+            commit::CommitOrder::DateOrder,
+        ];
+
+        #[test]
+        fn parents_below_the_min_generation_are_not_traversed_in_all_orders() -> crate::Result {
+            let dir = git_testtools::scripted_fixture_repo_read_only("make_traversal_repo_with_commit_graph.sh")?;
+            let graph = git_commitgraph::Graph::from_info_dir(dir.join(".git").join("objects").join("info"))?;
+            let boundary = graph
+                .commit_by_id(rev_parse(&dir, "main~12")?)
+                .expect("boundary is in commit-graph");
+            let min_generation = boundary.generation();
+            let is_kept = |id: &ObjectId| {
+                graph
+                    .commit_by_id(id)
+                    .map(|c| c.generation() >= min_generation)
+                    .unwrap_or(true)
+            };
+
+            for order in ORDERS {
+                let (all, all_lookups) = traverse(&dir, order, None, None)?;
+                assert_eq!(all.len(), 25, "{:?}: all commits are reachable", order);
+
+                let (pruned, pruned_lookups) = traverse(&dir, order, Some(&graph), Some(min_generation))?;
+                let expected: Vec<_> = all.iter().filter(|id| is_kept(id)).cloned().collect();
+                assert_eq!(
+                    pruned, expected,
+                    "{:?}: the order of the remaining commits is unchanged",
+                    order
+                );
+                assert_eq!(
+                    pruned.last(),
+                    Some(&boundary.id().to_owned()),
+                    "{:?}: the commit at the min generation is the last one",
+                    order
+                );
+                assert_eq!(all_lookups, all.len());
+                assert_eq!(
+                    pruned_lookups,
+                    pruned.len(),
+                    "{:?}: only the commits that are returned are looked up",
+                    order
+                );
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn min_generation_has_no_effect_without_generation_numbers() -> crate::Result {
+            let dir = git_testtools::scripted_fixture_repo_read_only("make_traversal_repo_with_commit_graph.sh")?;
+            let graph = git_commitgraph::Graph::from_info_dir(dir.join(".git").join("objects").join("info"))?;
+            for order in ORDERS {
+                let (all, _) = traverse(&dir, order, None, None)?;
+                assert_eq!(traverse(&dir, order, None, Some(u32::MAX))?.0, all);
+                assert_eq!(
+                    traverse(&dir, order, Some(&graph), None)?.0,
+                    all,
+                    "a commit-graph alone doesn't prune anything"
+                );
+            }
+            Ok(())
+        }
+    }
 }
@@ -0,0 +1,30 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function commit() {
+  GIT_COMMITTER_DATE="$1 +0000" git commit -q --allow-empty -m "$2"
+}
+
+git init -q
+git config commit.gpgsign false
+
+git checkout -q -b main
+commit "2000-01-02 00:00:00" c1
+commit "2000-01-03 00:00:00" c2
+
+git checkout -q -b branch1
+commit "2000-01-10 00:00:00" b1c1
+# a child that is older than its parent and all other commits
+commit "2000-01-01 00:00:00" b1c2
+commit "2000-01-05 00:00:00" b1c3
+
+git checkout -q main
+commit "2000-01-04 00:00:00" c3
+commit "2000-01-09 00:00:00" c4
+GIT_COMMITTER_DATE="2000-01-06 00:00:00 +0000" git merge -q --no-ff branch1 -m m1b1
+
+git checkout -q -b branch2 main~2
+commit "2000-01-08 00:00:00" b2c1
+git checkout -q main
+GIT_COMMITTER_DATE="2000-01-07 00:00:00 +0000" git merge -q --no-ff branch2 -m m2b2
+commit "2000-01-11 00:00:00" c5
@@ -0,0 +1,18 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+git checkout -q -b main
+GIT_COMMITTER_DATE="2000-01-02 00:00:00 +0000" git commit -q --allow-empty -m c1
+GIT_COMMITTER_DATE="2000-01-03 00:00:00 +0000" git commit -q --allow-empty -m c2
+
+git checkout -q -b branch1
+GIT_COMMITTER_DATE="2000-01-04 00:00:00 +0000" git commit -q --allow-empty -m b1c1
+GIT_COMMITTER_DATE="2000-01-05 00:00:00 +0000" git commit -q --allow-empty -m b1c2
+GIT_COMMITTER_DATE="2000-01-06 00:00:00 +0000" git commit -q --allow-empty -m b1c3
+
+git checkout -q main
+GIT_COMMITTER_DATE="2000-01-07 00:00:00 +0000" git commit -q --allow-empty -m c3
+GIT_COMMITTER_DATE="2000-01-08 00:00:00 +0000" git merge branch1 -m m1b1
@@ -0,0 +1,26 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+git checkout -q -b main
+for i in $(seq 1 10); do
+  git commit -q --allow-empty -m "c$i"
+done
+
+git checkout -q -b branch1 main~5
+git commit -q --allow-empty -m b1c1
+git commit -q --allow-empty -m b1c2
+git checkout -q main
+git merge -q --no-ff branch1 -m m1b1
+
+for i in $(seq 11 20); do
+  git commit -q --allow-empty -m "c$i"
+done
+
+git commit-graph write --no-progress --reachable
+
+# commits which are not part of the commit-graph
+git commit -q --allow-empty -m c21
+git commit -q --allow-empty -m c22
@@ -2,12 +2,9 @@
 #![forbid(unsafe_code)]
 #![deny(rust_2018_idioms)]
 
-use std::{
-    convert::TryFrom,
-    fmt::{self, Write},
-};
+use std::{borrow::Cow, convert::TryFrom, fmt};
 
-use bstr::ByteSlice;
+use bstr::{BStr, BString, ByteSlice};
 
 ///
 pub mod parse;
@@ -49,13 +46,23 @@ impl fmt::Display for Scheme {
 ///
 /// Additionally there is support for [deserialization][Url::from_bytes()] and serialization
 /// (_see the `Display::fmt()` implementation_).
-#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+///
+/// As URLs end up in logs and error messages, neither the `Display` nor the `Debug` implementation show the password, and
+/// with the alternate flag as in `{:#}`, `Display` omits the user as well. The password isn't serialized either.
+/// Use [`to_bstring_with_credentials()`][Url::to_bstring_with_credentials()] to obtain the URL including the password,
+/// for example to pass it on to credential helpers.
+#[derive(PartialEq, Eq, Hash, Ord, PartialOrd, Clone)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
 pub struct Url {
     /// The URL scheme.
     pub scheme: Scheme,
-    /// The user to impersonate on the remote.
+    /// The user to impersonate on the remote, percent-encoded as it appears in the URL. See [`user()`][Url::user()]
+    /// to obtain it decoded.
     pub user: Option<String>,
+    /// The password to authenticate the user with, percent-encoded as it appears in the URL. See
+    /// [`password()`][Url::password()] to obtain it decoded.
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    pub password: Option<String>,
     /// The host to which to connect. Localhost is implied if `None`.
     pub host: Option<String>,
     /// The port to use when connecting to a host. If `None`, standard ports depending on `scheme` will be used.
@@ -69,6 +76,7 @@ impl Default for Url {
         Url {
             scheme: Scheme::Ssh,
             user: None,
+            password: None,
             host: None,
             port: None,
             path: bstr::BString::default(),
@@ -76,29 +84,108 @@ impl Default for Url {
     }
 }
 
+/// The placeholder shown instead of the password when displaying a [`Url`].
+const REDACTED: &str = "<redacted>";
+
+/// How to write the user and password of a [`Url`].
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Credentials {
+    /// Write the user and the password.
+    Show,
+    /// Write the user, and a placeholder instead of the password.
+    RedactPassword,
+    /// Write neither the user nor the password.
+    Omit,
+}
+
 impl fmt::Display for Url {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.scheme.fmt(f)?;
-        f.write_str("://")?;
-        match (&self.user, &self.host) {
-            (Some(user), Some(host)) => f.write_fmt(format_args!("{}@{}", user, host)),
-            (None, Some(host)) => f.write_str(host),
-            (None, None) => Ok(()),
-            _ => return Err(fmt::Error),
-        }?;
-        if let Some(port) = &self.port {
-            f.write_char(':')?;
-            f.write_fmt(format_args!("{}", port))?;
-        }
+        self.write_prefix(
+            f,
+            if f.alternate() {
+                Credentials::Omit
+            } else {
+                Credentials::RedactPassword
+            },
+        )?;
         f.write_str(self.path.to_str_lossy().as_ref())
     }
 }
 
+impl fmt::Debug for Url {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Url")
+            .field("scheme", &self.scheme)
+            .field("user", &self.user)
+            .field("password", &self.password.as_ref().map(|_| REDACTED))
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
 impl Url {
     /// Parse a URL from `bytes`
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, parse::Error> {
         parse(bytes)
     }
+
+    /// Return the user with all percent-encoded characters decoded, or `None` if there is no user.
+    pub fn user(&self) -> Option<Cow<'_, BStr>> {
+        self.user.as_deref().map(percent_decode)
+    }
+
+    /// Return the password with all percent-encoded characters decoded, or `None` if there is no password.
+    pub fn password(&self) -> Option<Cow<'_, BStr>> {
+        self.password.as_deref().map(percent_decode)
+    }
+
+    /// Serialize this URL including its password, which is the only way to obtain it in full.
+    ///
+    /// Only use it where the password is needed, like when handing the URL to credential helpers, and never for display.
+    pub fn to_bstring_with_credentials(&self) -> BString {
+        let mut prefix = String::new();
+        self.write_prefix(&mut prefix, Credentials::Show)
+            .expect("only URLs with a user but without host fail to serialize, which parsing never produces");
+        let mut out = BString::from(prefix);
+        out.extend_from_slice(&self.path);
+        out
+    }
+
+    fn write_prefix(&self, out: &mut impl fmt::Write, credentials: Credentials) -> fmt::Result {
+        write!(out, "{}://", self.scheme)?;
+        match &self.host {
+            Some(host) => {
+                if credentials != Credentials::Omit && (self.user.is_some() || self.password.is_some()) {
+                    out.write_str(self.user.as_deref().unwrap_or_default())?;
+                    if let Some(password) = &self.password {
+                        out.write_char(':')?;
+                        out.write_str(match credentials {
+                            Credentials::Show => password,
+                            _ => REDACTED,
+                        })?;
+                    }
+                    out.write_char('@')?;
+                }
+                out.write_str(host)?;
+            }
+            None if self.user.is_some() || self.password.is_some() => return Err(fmt::Error),
+            None => {}
+        }
+        if let Some(port) = &self.port {
+            out.write_char(':')?;
+            write!(out, "{}", port)?;
+        }
+        Ok(())
+    }
+}
+
+fn percent_decode(input: &str) -> Cow<'_, BStr> {
+    match percent_encoding::percent_decode_str(input).into() {
+        Cow::Borrowed(bytes) => Cow::Borrowed(bytes.as_bstr()),
+        Cow::Owned(bytes) => Cow::Owned(bytes.into()),
+    }
 }
 
 impl TryFrom<&[u8]> for Url {
@@ -27,6 +27,9 @@ quick_error! {
         RelativeUrl(url: String) {
             display("Relative URLs are not permitted: '{}'", url)
         }
+        ControlCharacter {
+            display("URLs must not contain newlines or other control characters, not even percent-encoded in the user or password")
+        }
     }
 }
 
@@ -82,6 +85,7 @@ fn to_owned_url(url: url::Url) -> Result<crate::Url, Error> {
         } else {
             Some(url.username().into())
         },
+        password: url.password().map(Into::into),
         host: url.host_str().map(Into::into),
         port: url.port(),
         path: url.path().into(),
@@ -94,7 +98,13 @@ fn to_owned_url(url: url::Url) -> Result<crate::Url, Error> {
 ///
 /// We cannot and should never have to deal with UTF-16 encoded windows strings, so bytes input is acceptable.
 /// For file-paths, we don't expect UTF8 encoding either.
+///
+/// URLs containing control characters like newlines are rejected to prevent them from injecting lines into the protocols
+/// they are passed on in, like HTTP headers or the input of credential helpers.
 pub fn parse(bytes: &[u8]) -> Result<crate::Url, Error> {
+    if bytes.iter().any(u8::is_ascii_control) {
+        return Err(Error::ControlCharacter);
+    }
     let guessed_protocol = guess_protocol(bytes);
     if possibly_strip_file_protocol(bytes) != bytes || (has_no_explicit_protocol(bytes) && guessed_protocol == "file") {
         return Ok(crate::Url {
@@ -132,5 +142,14 @@ pub fn parse(bytes: &[u8]) -> Result<crate::Url, Error> {
         return Err(Error::RelativeUrl(url.into()));
     }
 
-    to_owned_url(url)
+    let url = to_owned_url(url)?;
+    if url
+        .user()
+        .into_iter()
+        .chain(url.password())
+        .any(|decoded| decoded.iter().any(u8::is_ascii_control))
+    {
+        return Err(Error::ControlCharacter);
+    }
+    Ok(url)
 }
@@ -24,12 +24,14 @@ fn url(
     git_url::Url {
         scheme: protocol,
         user: user.into().map(Into::into),
+        password: None,
         host: host.into().map(Into::into),
         port: port.into(),
         path: path.into(),
     }
 }
 
+mod credentials;
 mod file;
 mod invalid;
 mod ssh;
@@ -90,8 +90,8 @@ mod async_io {
         let url = git_repository::url::parse(urlb)?;
         Ok(match url.scheme {
             git_repository::url::Scheme::Git => {
-                if url.user.is_some() {
-                    return Err(Error::UnsupportedUrlTokens(urlb.into(), url.scheme));
+                if url.user.is_some() || url.password.is_some() {
+                    return Err(Error::UnsupportedUrlTokens(url.to_string().into(), url.scheme));
                 }
                 git_connect(
                     url.host.as_ref().expect("host is present in url"),
@@ -241,6 +241,7 @@ where
                 allow_thin_pack: thin,
                 chunk_size,
                 version: Default::default(),
+                deterministic: false,
             },
         ))
     };
@@ -1,4 +1,6 @@
 use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
     io,
     path::PathBuf,
     sync::{atomic::AtomicBool, Arc},
@@ -14,6 +16,7 @@ use git_repository::{
         transport,
         transport::client::Capabilities,
     },
+    refs::FullName,
 };
 
 use crate::{remote::refs::JsonRef, OutputFormat};
@@ -33,6 +36,7 @@ struct CloneDelegate<W> {
     refs_directory: Option<PathBuf>,
     ref_filter: Option<&'static [&'static str]>,
     wanted_refs: Vec<BString>,
+    symrefs: BTreeMap<FullName, FullName>,
 }
 static FILTER: &[&str] = &["HEAD", "refs/tags", "refs/heads"];
 
@@ -65,8 +69,10 @@ impl<W> protocol::fetch::DelegateBlocking for CloneDelegate<W> {
         version: transport::Protocol,
         server: &Capabilities,
         _features: &mut Vec<(&str, Option<&str>)>,
-        _refs: &[Ref],
+        refs: &[Ref],
     ) -> io::Result<Action> {
+        self.symrefs =
+            protocol::fetch::refs::symrefs(server, refs).map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
         if !self.wanted_refs.is_empty() && !remote_supports_ref_in_want(server) {
             return Err(io::Error::new(
                 io::ErrorKind::Other,
@@ -131,6 +137,7 @@ mod blocking_io {
             receive_pack_blocking(
                 self.directory.take(),
                 self.refs_directory.take(),
+                &self.symrefs,
                 &mut self.ctx,
                 input,
                 progress,
@@ -155,6 +162,7 @@ mod blocking_io {
             refs_directory,
             ref_filter: None,
             wanted_refs,
+            symrefs: Default::default(),
         };
         protocol::fetch(
             transport,
@@ -199,6 +207,7 @@ mod async_io {
             receive_pack_blocking(
                 self.directory.take(),
                 self.refs_directory.take(),
+                &self.symrefs,
                 &mut self.ctx,
                 futures_lite::io::BlockOn::new(input),
                 progress,
@@ -223,6 +232,7 @@ mod async_io {
             refs_directory,
             ref_filter: None,
             wanted_refs,
+            symrefs: Default::default(),
         };
         blocking::unblock(move || {
             futures_lite::future::block_on(protocol::fetch(
@@ -299,20 +309,30 @@ fn print(out: &mut impl io::Write, res: pack::bundle::write::Outcome, refs: &[Re
     Ok(())
 }
 
-fn write_raw_refs(refs: &[Ref], directory: PathBuf) -> std::io::Result<()> {
-    let assure_dir_exists = |path: &BString| {
-        assert!(!path.starts_with_str("/"), "no ref start with a /, they are relative");
-        let path = directory.join(path.to_path().expect("UTF-8 conversion succeeds"));
+/// Write all `refs` into `directory`, with symbolic refs written as advertised in `symrefs` to also create those which weren't
+/// among the `refs`, like the remote `HEAD`.
+///
+/// The names of all `refs` are validated to prevent the server from writing outside of `directory`.
+fn write_raw_refs(refs: &[Ref], symrefs: &BTreeMap<FullName, FullName>, directory: PathBuf) -> std::io::Result<()> {
+    let assure_dir_exists = |name: &FullName| {
+        let path = directory.join(name.to_path());
         std::fs::create_dir_all(path.parent().expect("multi-component path")).map(|_| path)
     };
     for r in refs {
-        let (path, content) = match r {
-            Ref::Symbolic { path, target, .. } => (assure_dir_exists(path)?, format!("ref: {}", target)),
-            Ref::Peeled { path, tag: object, .. } | Ref::Direct { path, object } => {
-                (assure_dir_exists(path)?, object.to_string())
-            }
+        let (name, content) = match r {
+            Ref::Symbolic { .. } => continue,
+            Ref::Peeled { path, tag: object, .. } | Ref::Direct { path, object } => (
+                FullName::try_from(path.as_bstr()).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+                object.to_string(),
+            ),
         };
-        std::fs::write(path, content.as_bytes())?;
+        std::fs::write(assure_dir_exists(&name)?, content.as_bytes())?;
+    }
+    for (name, target) in symrefs {
+        std::fs::write(
+            assure_dir_exists(name)?,
+            format!("ref: {}", target.as_bstr()).as_bytes(),
+        )?;
     }
     Ok(())
 }
@@ -320,6 +340,7 @@ fn write_raw_refs(refs: &[Ref], directory: PathBuf) -> std::io::Result<()> {
 fn receive_pack_blocking<W: io::Write>(
     mut directory: Option<PathBuf>,
     mut refs_directory: Option<PathBuf>,
+    symrefs: &BTreeMap<FullName, FullName>,
     ctx: &mut Context<W>,
     input: impl io::BufRead,
     progress: impl git_repository::Progress,
@@ -335,7 +356,7 @@ fn receive_pack_blocking<W: io::Write>(
             .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
 
     if let Some(directory) = refs_directory.take() {
-        write_raw_refs(refs, directory)?;
+        write_raw_refs(refs, symrefs, directory)?;
     }
 
     match ctx.format {